@@ -0,0 +1,47 @@
+//! Throughput of batch propagation against propagating each satellite in
+//! turn, for a fleet of near Earth satellites
+#[macro_use]
+extern crate criterion;
+extern crate sgp4;
+
+use criterion::{Criterion, Throughput};
+use sgp4::angle::Deg;
+use sgp4::batch::Batch;
+use sgp4::Propagator;
+
+/// Satellites in the fleet
+const FLEET: usize = 1024;
+
+/// ISS-like orbits spread around in mean anomaly, node and drag
+fn fleet() -> Vec<Propagator> {
+    let iss = sgp4::tle::load_from_str(
+        "ISS (ZARYA)",
+        "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+        "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    ).unwrap();
+    (0..FLEET)
+        .map(|index| {
+            let mut tle = iss.clone();
+            tle.mean_anomaly = Deg((7.0 * index as f64) % 360.0);
+            tle.raan = Deg((13.0 * index as f64) % 360.0);
+            tle.bstar *= 1.0 + (index % 10) as f64;
+            Propagator::new(&tle).unwrap()
+        })
+        .collect()
+}
+
+fn propagate(c: &mut Criterion) {
+    let propagators = fleet();
+    let batch = Batch::new(&propagators);
+
+    let mut group = c.benchmark_group("propagate");
+    group.throughput(Throughput::Elements(FLEET as u64));
+    group.bench_function("scalar", |b| b.iter(|| {
+        propagators.iter().map(|propagator| propagator.propagate(criterion::black_box(90.0))).collect::<Vec<_>>()
+    }));
+    group.bench_function("batch", |b| b.iter(|| batch.propagate_all(criterion::black_box(90.0))));
+    group.finish();
+}
+
+criterion_group!(benches, propagate);
+criterion_main!(benches);
@@ -6,14 +6,19 @@ fn main() {
         "ISS (ZARYA)",
         "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
         "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
-    );
+    ).unwrap();
     println!("Satellite {}:", tle.int_designator);
 
     let time = 0.0;
+    let state = sgp4::propagate(&tle, time).unwrap();
+
     println!("TEME position at time t={}:", time);
+    println!("    X: {}", state.position.X);
+    println!("    Y: {}", state.position.Y);
+    println!("    Z: {}", state.position.Z);
 
-    let location = sgp4::propagate(tle, time);
-    println!("    X: {}", location.X);
-    println!("    Y: {}", location.Y);
-    println!("    Z: {}", location.Z);
+    println!("TEME velocity at time t={}:", time);
+    println!("    X: {}", state.velocity.X);
+    println!("    Y: {}", state.velocity.Y);
+    println!("    Z: {}", state.velocity.Z);
 }
@@ -0,0 +1,115 @@
+#ifndef SGP4_H
+#define SGP4_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// ## Status
+//
+// Result of each call: zero for success, otherwise why it failed.
+typedef enum Sgp4Status {
+  // Success
+  SGP4_STATUS_OK = 0,
+  // A pointer argument was null
+  SGP4_STATUS_NULL_POINTER,
+  // A line of the TLE isn't valid UTF-8
+  SGP4_STATUS_INVALID_UTF8,
+  // A line of the TLE couldn't be parsed or has a bad checksum
+  SGP4_STATUS_PARSE_ERROR,
+  // The element set can't be propagated
+  SGP4_STATUS_BAD_ELEMENTS,
+  // The mean eccentricity diverged
+  SGP4_STATUS_DIVERGED,
+  // The semi-latus rectum went negative
+  SGP4_STATUS_NEGATIVE_SEMI_LATUS_RECTUM,
+  // The satellite has decayed
+  SGP4_STATUS_DECAYED,
+  // The time is too far from the epoch
+  SGP4_STATUS_TOO_OLD,
+} Sgp4Status;
+
+// ## Propagator
+//
+// An initialized element set, opaque to C. Create with `sgp4_init` and
+// release with `sgp4_free`.
+typedef struct Sgp4Propagator Sgp4Propagator;
+
+// ## State
+//
+// Position (kilometers) and velocity (kilometers/second) in the TEME
+// frame.
+typedef struct Sgp4State {
+  // X, Y and Z position (kilometers)
+  double position[3];
+  // X, Y and Z velocity (kilometers/second)
+  double velocity[3];
+} Sgp4State;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Parse the two lines of a TLE and initialize the propagator. On success
+// `*propagator` points to a new propagator, which must be released with
+// `sgp4_free`.
+//
+// # Safety
+//
+// `line1` and `line2` must be NUL terminated strings and `propagator` a
+// valid pointer to write to.
+enum Sgp4Status sgp4_init(const char *line1, const char *line2, struct Sgp4Propagator **propagator);
+
+// Propagate to `minutes` since the epoch, writing the position and
+// velocity to `*state`.
+//
+// # Safety
+//
+// `propagator` must come from `sgp4_init` and not yet be freed, and
+// `state` must be a valid pointer to write to.
+enum Sgp4Status sgp4_propagate(const struct Sgp4Propagator *propagator,
+                               double minutes,
+                               struct Sgp4State *state);
+
+// Propagate to `count` times, `start`, `start + step` and so on (minutes
+// since the epoch), writing the states to `states[0]` to
+// `states[count - 1]`. Nothing is allocated. On an error the states
+// before the failing time are written and the rest are left as they were.
+//
+// # Safety
+//
+// `propagator` must come from `sgp4_init` and not yet be freed, and
+// `states` must be valid to write `count` states to.
+enum Sgp4Status sgp4_propagate_range(const struct Sgp4Propagator *propagator,
+                                     double start,
+                                     double step,
+                                     struct Sgp4State *states,
+                                     uintptr_t count);
+
+// Julian date (UTC) of the element set epoch, or NaN if `propagator` is
+// null. Subtract from a Julian date and multiply by 1440 to get the
+// minutes to pass to `sgp4_propagate`.
+//
+// # Safety
+//
+// `propagator` must come from `sgp4_init` and not yet be freed.
+double sgp4_epoch(const struct Sgp4Propagator *propagator);
+
+// Release a propagator. Does nothing if `propagator` is null.
+//
+// # Safety
+//
+// `propagator` must come from `sgp4_init` and not already be freed.
+void sgp4_free(struct Sgp4Propagator *propagator);
+
+// A static, NUL terminated description of a status.
+const char *sgp4_status_message(enum Sgp4Status status);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SGP4_H */
@@ -0,0 +1,236 @@
+/*!  # Python Bindings
+
+Exposes the `sgp4` crate to Python as the `rust_sgp4` module: `Tle`,
+`Propagator` and the coordinate conversions. Build and install into the
+current environment with
+
+```text
+cd python
+maturin develop --release
+```
+
+```python
+import rust_sgp4
+
+tle = rust_sgp4.Tle.parse("""ISS (ZARYA)
+1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433""")
+
+propagator = rust_sgp4.Propagator(tle)
+position, velocity = propagator.propagate(90.0)
+```
+
+Positions are `(x, y, z)` tuples in kilometers and velocities in
+kilometers/second, as in the crate.
+*/
+#![deny(missing_docs)]
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+use sgp4::coordinates::{self, ECEF, TEME};
+use sgp4::tle::TLE;
+
+/// A position or velocity as a Python tuple
+type Vector = (f64, f64, f64);
+
+fn teme((x, y, z): Vector) -> TEME {
+    TEME::new(x, y, z)
+}
+
+fn propagation_error(error: sgp4::PropagationError) -> PyErr {
+    PyRuntimeError::new_err(error.to_string())
+}
+
+/// A two line element set
+#[pyclass(name = "Tle", module = "rust_sgp4", frozen)]
+pub struct Tle {
+    inner: TLE,
+}
+
+#[pymethods]
+impl Tle {
+
+    /// Read a TLE from its two lines of elements, and optionally a name
+    #[new]
+    #[pyo3(signature = (line1, line2, name = ""))]
+    fn new(line1: &str, line2: &str, name: &str) -> PyResult<Tle> {
+        sgp4::tle::load_from_str(name, line1, line2)
+            .map(|inner| Tle { inner })
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+
+    /// Read a TLE from text holding two lines of elements, or a name line
+    /// and two lines of elements
+    #[staticmethod]
+    fn parse(text: &str) -> PyResult<Tle> {
+        sgp4::tle::parse(text)
+            .map(|inner| Tle { inner })
+            .map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+
+    /// Name of the object
+    #[getter]
+    fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Satellite catalog number
+    #[getter]
+    fn sat_number(&self) -> u32 {
+        self.inner.sat_number
+    }
+
+    /// Four digit year of the epoch
+    #[getter]
+    fn epoch_year(&self) -> u16 {
+        self.inner.epoch_year
+    }
+
+    /// Fractional day of the year of the epoch
+    #[getter]
+    fn epoch_day(&self) -> f64 {
+        self.inner.epoch_day
+    }
+
+    /// Julian date (UTC) of the epoch
+    #[getter]
+    fn epoch_julian_date(&self) -> f64 {
+        self.inner.epoch_julian_date()
+    }
+
+    /// Inclination (degrees)
+    #[getter]
+    fn inclination(&self) -> f64 {
+        self.inner.i.0
+    }
+
+    /// Right ascension of the ascending node (degrees)
+    #[getter]
+    fn raan(&self) -> f64 {
+        self.inner.raan.0
+    }
+
+    /// Eccentricity
+    #[getter]
+    fn eccentricity(&self) -> f64 {
+        self.inner.e
+    }
+
+    /// Argument of perigee (degrees)
+    #[getter]
+    fn argp(&self) -> f64 {
+        self.inner.omega.0
+    }
+
+    /// Mean anomaly (degrees)
+    #[getter]
+    fn mean_anomaly(&self) -> f64 {
+        self.inner.mean_anomaly.0
+    }
+
+    /// Mean motion (revolutions/day)
+    #[getter]
+    fn mean_motion(&self) -> f64 {
+        self.inner.mean_motion
+    }
+
+    /// B* drag term (Earth radii⁻¹)
+    #[getter]
+    fn bstar(&self) -> f64 {
+        self.inner.bstar
+    }
+
+    /// The two lines of elements, with checksums
+    fn to_lines(&self) -> PyResult<(String, String)> {
+        self.inner.to_lines().map_err(|error| PyValueError::new_err(error.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Tle({:?}, sat_number={})", self.inner.name, self.inner.sat_number)
+    }
+}
+
+/// An element set initialized for propagation
+#[pyclass(name = "Propagator", module = "rust_sgp4", frozen)]
+pub struct Propagator {
+    inner: sgp4::Propagator,
+}
+
+#[pymethods]
+impl Propagator {
+
+    /// Initialize a TLE for propagation, optionally with the AFSPC
+    /// compatible ops mode
+    #[new]
+    #[pyo3(signature = (tle, afspc = false))]
+    fn new(tle: &Tle, afspc: bool) -> PyResult<Propagator> {
+        let ops_mode = if afspc { sgp4::OpsMode::Afspc } else { sgp4::OpsMode::Improved };
+        sgp4::Propagator::with_ops_mode(&tle.inner, ops_mode)
+            .map(|inner| Propagator { inner })
+            .map_err(propagation_error)
+    }
+
+    /// TEME position and velocity `minutes` since the epoch
+    fn propagate(&self, minutes: f64) -> PyResult<(Vector, Vector)> {
+        let state = self.inner.propagate(minutes).map_err(propagation_error)?;
+        Ok((
+            (state.position.X, state.position.Y, state.position.Z),
+            (state.velocity.X, state.velocity.Y, state.velocity.Z),
+        ))
+    }
+
+    /// TEME positions and velocities at each of `times` (minutes since the
+    /// epoch)
+    fn propagate_many(&self, times: Vec<f64>) -> PyResult<Vec<(Vector, Vector)>> {
+        times.into_iter().map(|time| self.propagate(time)).collect()
+    }
+}
+
+/// Greenwich mean sidereal time (radians) at a Julian date (UT1)
+#[pyfunction]
+fn gmst(julian_date: f64) -> f64 {
+    sgp4::time::gmst(julian_date)
+}
+
+/// Julian date of a calendar date, with the time of day in the day
+#[pyfunction]
+fn julian_date(year: i32, month: u32, day: f64) -> f64 {
+    sgp4::time::julian_date(year, month, day)
+}
+
+/// Rotate a TEME position into the Earth fixed frame at a sidereal time
+#[pyfunction]
+fn ecef_from_teme(position: Vector, gmst: f64) -> Vector {
+    let ecef = coordinates::ecef_from_teme(&teme(position), gmst);
+    (ecef.X, ecef.Y, ecef.Z)
+}
+
+/// WGS84 latitude, longitude (radians) and altitude (kilometers) of an
+/// Earth fixed position
+#[pyfunction]
+fn geodetic_from_ecef(position: Vector) -> Vector {
+    let (x, y, z) = position;
+    let geodetic = coordinates::geodetic_from_ecef(&ECEF::new(x, y, z));
+    (geodetic.lat, geodetic.lon, geodetic.alt)
+}
+
+/// Rotate a TEME position into the J2000 frame at a Julian date (TT)
+#[pyfunction]
+fn j2000_from_teme(position: Vector, julian_date_tt: f64) -> Vector {
+    let j2000 = coordinates::j2000_from_teme(&teme(position), julian_date_tt);
+    (j2000.X, j2000.Y, j2000.Z)
+}
+
+/// SGP4 satellite propagation, implemented in Rust
+#[pymodule]
+fn rust_sgp4(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Tle>()?;
+    module.add_class::<Propagator>()?;
+    module.add_function(wrap_pyfunction!(gmst, module)?)?;
+    module.add_function(wrap_pyfunction!(julian_date, module)?)?;
+    module.add_function(wrap_pyfunction!(ecef_from_teme, module)?)?;
+    module.add_function(wrap_pyfunction!(geodetic_from_ecef, module)?)?;
+    module.add_function(wrap_pyfunction!(j2000_from_teme, module)?)?;
+    Ok(())
+}
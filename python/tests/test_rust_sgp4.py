@@ -0,0 +1,70 @@
+"""Tests for the rust_sgp4 module. Run after `maturin develop` with
+
+    python -m unittest discover python/tests
+
+Where python-sgp4 is installed the results are compared against it too.
+"""
+import math
+import unittest
+
+import rust_sgp4
+
+try:
+    from sgp4.api import Satrec, WGS72
+except ImportError:
+    Satrec = None
+
+LINE1 = "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990"
+LINE2 = "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433"
+
+
+class TleTest(unittest.TestCase):
+
+    def test_parse(self):
+        tle = rust_sgp4.Tle.parse("ISS (ZARYA)\n" + LINE1 + "\n" + LINE2)
+        self.assertEqual(tle.name, "ISS (ZARYA)")
+        self.assertEqual(tle.sat_number, 25544)
+        self.assertEqual(tle.epoch_year, 2016)
+        self.assertAlmostEqual(tle.epoch_julian_date, 2457598.09822142, places=8)
+        self.assertEqual(tle.to_lines(), (LINE1, LINE2))
+
+    def test_errors(self):
+        with self.assertRaises(ValueError):
+            rust_sgp4.Tle(LINE1[:-1] + "1", LINE2)
+
+
+class PropagatorTest(unittest.TestCase):
+
+    def test_propagate(self):
+        propagator = rust_sgp4.Propagator(rust_sgp4.Tle(LINE1, LINE2))
+        (x, y, z), (vx, vy, vz) = propagator.propagate(0.0)
+        self.assertAlmostEqual(math.sqrt(x * x + y * y + z * z), 6776, delta=20)
+        self.assertAlmostEqual(math.sqrt(vx * vx + vy * vy + vz * vz), 7.67, delta=0.05)
+        self.assertEqual(len(propagator.propagate_many([0.0, 1.0, 2.0])), 3)
+
+    @unittest.skipIf(Satrec is None, "python-sgp4 is not installed")
+    def test_matches_python_sgp4(self):
+        reference = Satrec.twoline2rv(LINE1, LINE2, WGS72)
+        propagator = rust_sgp4.Propagator(rust_sgp4.Tle(LINE1, LINE2), afspc=True)
+        for minutes in (0.0, 360.0, 1440.0):
+            error, position, velocity = reference.sgp4_tsince(minutes)
+            self.assertEqual(error, 0)
+            (x, y, z), _ = propagator.propagate(minutes)
+            distance = math.sqrt((x - position[0]) ** 2 + (y - position[1]) ** 2 + (z - position[2]) ** 2)
+            self.assertLess(distance, 0.01)
+
+
+class CoordinatesTest(unittest.TestCase):
+
+    def test_geodetic(self):
+        propagator = rust_sgp4.Propagator(rust_sgp4.Tle(LINE1, LINE2))
+        position, _ = propagator.propagate(0.0)
+        gmst = rust_sgp4.gmst(2457598.09822142)
+        latitude, longitude, altitude = rust_sgp4.geodetic_from_ecef(rust_sgp4.ecef_from_teme(position, gmst))
+        self.assertLessEqual(abs(math.degrees(latitude)), 51.7)
+        self.assertGreater(altitude, 350)
+        self.assertLess(altitude, 450)
+
+
+if __name__ == "__main__":
+    unittest.main()
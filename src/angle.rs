@@ -0,0 +1,257 @@
+/*!  # Angles
+
+Angles that know their unit. TLEs and OMMs give angles in degrees while the
+propagator and every trigonometric function want radians, and an `f64`
+doesn't say which it holds. [`Deg`](struct.Deg.html) and
+[`Rad`](struct.Rad.html) do: each converts to the other, and only `Rad`
+has the trigonometric functions, so an angle in degrees can't be passed to
+`cos()` without being converted first.
+
+The TLE fields and the builder's angles are `Deg`, and the angles of
+[`Elements`](../elements/struct.Elements.html),
+[`MeanElements`](../elements/struct.MeanElements.html) and
+[`KeplerianElements`](../elements/struct.KeplerianElements.html) are `Rad`.
+Functions taking `impl Into<Rad>` accept either.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+
+/// ## Degrees
+///
+/// An angle in degrees.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::angle::{Deg, Rad};
+///
+/// let inclination = Deg(51.6406);
+/// let radians: Rad = inclination.into();
+/// assert_eq!(radians, inclination.to_radians());
+/// assert!((radians.cos() - 0.6206).abs() < 1e-4);
+/// assert_eq!((Deg(350.0) + Deg(20.0)).normalized(), Deg(10.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct Deg(pub f64);
+
+/// ## Radians
+///
+/// An angle in radians, with the trigonometric functions.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
+pub struct Rad(pub f64);
+
+impl Deg {
+
+    /// The same angle in radians
+    pub fn to_radians(self) -> Rad {
+        Rad(self.0.to_radians())
+    }
+
+    /// The same angle in $[0°, 360°)$
+    pub fn normalized(self) -> Deg {
+        Deg(self.0.rem_euclid(360.0))
+    }
+
+    /// Magnitude of the angle
+    pub fn abs(self) -> Deg {
+        Deg(self.0.abs())
+    }
+}
+
+impl Rad {
+
+    /// The same angle in degrees
+    pub fn to_degrees(self) -> Deg {
+        Deg(self.0.to_degrees())
+    }
+
+    /// The same angle in $[0, 2\pi)$
+    pub fn normalized(self) -> Rad {
+        Rad(self.0.rem_euclid(2.0 * PI))
+    }
+
+    /// Magnitude of the angle
+    pub fn abs(self) -> Rad {
+        Rad(self.0.abs())
+    }
+
+    /// Sine
+    pub fn sin(self) -> f64 {
+        self.0.sin()
+    }
+
+    /// Cosine
+    pub fn cos(self) -> f64 {
+        self.0.cos()
+    }
+
+    /// Tangent
+    pub fn tan(self) -> f64 {
+        self.0.tan()
+    }
+
+    /// Sine and cosine together
+    pub fn sin_cos(self) -> (f64, f64) {
+        self.0.sin_cos()
+    }
+
+    /// Arcsine of `x`
+    pub fn asin(x: f64) -> Rad {
+        Rad(x.asin())
+    }
+
+    /// Arccosine of `x`
+    pub fn acos(x: f64) -> Rad {
+        Rad(x.acos())
+    }
+
+    /// Four quadrant arctangent of `y / x`
+    pub fn atan2(y: f64, x: f64) -> Rad {
+        Rad(y.atan2(x))
+    }
+}
+
+impl From<Rad> for Deg {
+    fn from(angle: Rad) -> Deg {
+        angle.to_degrees()
+    }
+}
+
+impl From<Deg> for Rad {
+    fn from(angle: Deg) -> Rad {
+        angle.to_radians()
+    }
+}
+
+impl fmt::Display for Deg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)?;
+        f.write_str("°")
+    }
+}
+
+impl fmt::Display for Rad {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)?;
+        f.write_str(" rad")
+    }
+}
+
+/// Sums, differences and scaling, which don't change the unit
+macro_rules! arithmetic {
+    ($angle:ident) => {
+        impl Add for $angle {
+            type Output = $angle;
+
+            fn add(self, other: $angle) -> $angle {
+                $angle(self.0 + other.0)
+            }
+        }
+
+        impl Sub for $angle {
+            type Output = $angle;
+
+            fn sub(self, other: $angle) -> $angle {
+                $angle(self.0 - other.0)
+            }
+        }
+
+        impl AddAssign for $angle {
+            fn add_assign(&mut self, other: $angle) {
+                self.0 += other.0;
+            }
+        }
+
+        impl SubAssign for $angle {
+            fn sub_assign(&mut self, other: $angle) {
+                self.0 -= other.0;
+            }
+        }
+
+        impl Neg for $angle {
+            type Output = $angle;
+
+            fn neg(self) -> $angle {
+                $angle(-self.0)
+            }
+        }
+
+        impl Mul<f64> for $angle {
+            type Output = $angle;
+
+            fn mul(self, scale: f64) -> $angle {
+                $angle(self.0 * scale)
+            }
+        }
+
+        impl Mul<$angle> for f64 {
+            type Output = $angle;
+
+            fn mul(self, angle: $angle) -> $angle {
+                $angle(self * angle.0)
+            }
+        }
+
+        impl Div<f64> for $angle {
+            type Output = $angle;
+
+            fn div(self, scale: f64) -> $angle {
+                $angle(self.0 / scale)
+            }
+        }
+    };
+}
+
+arithmetic!(Deg);
+arithmetic!(Rad);
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Deg(180.0).to_radians(), Rad(PI));
+        assert_eq!(Rad::from(Deg(90.0)), Rad(PI / 2.0));
+        assert_eq!(Deg::from(Rad(PI)), Deg(180.0));
+        assert!((Deg(60.0).to_radians().cos() - 0.5).abs() < 1e-15);
+        assert_eq!(Rad::atan2(1.0, 1.0).to_degrees(), Deg(45.0));
+
+        assert_eq!(Deg(-10.0).normalized(), Deg(350.0));
+        assert_eq!(Deg(720.0).normalized(), Deg(0.0));
+        assert_eq!(Rad(-PI).normalized(), Rad(PI));
+        assert_eq!(Deg(10.0) - Deg(30.0), Deg(-20.0));
+        assert_eq!(2.0 * Deg(10.0) / 4.0, Deg(5.0));
+
+        let mut raan = Deg(211.4156);
+        raan += Deg(5.0);
+        assert_eq!(raan, Deg(216.4156));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Deg(51.6406).to_string(), "51.6406°");
+        assert_eq!(format!("{:.2}", Rad(PI)), "3.14 rad");
+    }
+}
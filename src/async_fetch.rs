@@ -0,0 +1,440 @@
+/*!  # Async Fetch
+
+Non-blocking versions of the [`fetch`](../fetch/index.html) and
+[`spacetrack`](../spacetrack/index.html) clients for
+[tokio](https://tokio.rs), with the `async` feature, and a
+[`CatalogWatcher`](struct.CatalogWatcher.html) that keeps a group up to date
+in the background.
+
+The requests are the blocking clients' own, run on tokio's blocking
+threads, so a cached [`AsyncFetcher`](struct.AsyncFetcher.html) honours the
+server's caching headers exactly as a [`Fetcher`](../fetch/struct.Fetcher.html)
+does and the crate doesn't need a second HTTP client. Everything here has
+to be called from within a tokio runtime. A request that's dropped before
+it finishes still runs to the end on its thread; only the answer is lost.
+
+A watcher downloads its group straight away and then once every interval.
+Whenever the response differs from the last one, the new catalog replaces
+the old one whole: readers hold an `Arc<Catalog>`, so they see all of the
+old elements or all of the new, and never wait on a download. A failed
+refresh keeps the last good catalog.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::future::Future;
+use std::panic;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+
+use tokio;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::{Interval, MissedTickBehavior};
+
+use catalog::Catalog;
+use fetch::{celestrak_url, FetchError, Fetcher};
+#[cfg(feature = "spacetrack")]
+use spacetrack::SpaceTrack;
+#[cfg(feature = "spacetrack")]
+use tle::TLE;
+
+
+/// ## Request
+///
+/// A request running on tokio's blocking threads, which resolves to its
+/// result. A panic in the request is passed on to whoever awaits it.
+#[derive(Debug)]
+pub struct Request<T> {
+    handle: JoinHandle<Result<T, FetchError>>,
+}
+
+impl<T> Future for Request<T> {
+    type Output = Result<T, FetchError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match ready!(Pin::new(&mut self.get_mut().handle).poll(cx)) {
+            Ok(result) => Poll::Ready(result),
+            Err(error) => panic::resume_unwind(error.into_panic()),
+        }
+    }
+}
+
+/// Start a blocking call on tokio's blocking threads
+fn request<T, F>(call: F) -> Request<T>
+where F: FnOnce() -> Result<T, FetchError> + Send + 'static, T: Send + 'static {
+    Request { handle: tokio::task::spawn_blocking(call) }
+}
+
+
+/// ## Async Fetcher
+///
+/// A [`Fetcher`](../fetch/struct.Fetcher.html) that doesn't block the
+/// runtime.
+///
+/// ### Example
+///
+/// ```no_run
+/// extern crate sgp4;
+/// extern crate tokio;
+///
+/// use sgp4::async_fetch::AsyncFetcher;
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+/// let fetcher = AsyncFetcher::with_cache("/tmp/celestrak");
+///
+/// let _guard = runtime.enter();
+/// let stations = runtime.block_on(fetcher.celestrak("stations")).unwrap();
+/// println!("{} stations", stations.len());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AsyncFetcher {
+    fetcher: Fetcher,
+}
+
+impl AsyncFetcher {
+
+    /// A fetcher without a cache: every call downloads
+    pub fn new() -> AsyncFetcher {
+        AsyncFetcher { fetcher: Fetcher::new() }
+    }
+
+    /// A fetcher keeping responses in `directory`, which is created if need
+    /// be
+    pub fn with_cache<P: AsRef<Path>>(directory: P) -> AsyncFetcher {
+        AsyncFetcher { fetcher: Fetcher::with_cache(directory) }
+    }
+
+    /// Download a named Celestrak group as TLEs
+    pub fn celestrak(&self, group: &str) -> Request<Catalog> {
+        let (fetcher, group) = (self.fetcher.clone(), String::from(group));
+        request(move || fetcher.celestrak(&group))
+    }
+
+    /// The body of a URL, from the cache while it's fresh
+    pub fn get(&self, url: &str) -> Request<String> {
+        let (fetcher, url) = (self.fetcher.clone(), String::from(url));
+        request(move || fetcher.get(&url))
+    }
+}
+
+impl From<Fetcher> for AsyncFetcher {
+    fn from(fetcher: Fetcher) -> AsyncFetcher {
+        AsyncFetcher { fetcher }
+    }
+}
+
+
+/// ## Async Space-Track
+///
+/// A signed in [`SpaceTrack`](../spacetrack/struct.SpaceTrack.html)
+/// session that doesn't block the runtime. Requires the `spacetrack`
+/// feature as well.
+#[cfg(feature = "spacetrack")]
+#[derive(Debug, Clone)]
+pub struct AsyncSpaceTrack {
+    session: SpaceTrack,
+}
+
+#[cfg(feature = "spacetrack")]
+impl AsyncSpaceTrack {
+
+    /// Sign in to Space-Track.org
+    pub fn login(identity: &str, password: &str) -> Request<AsyncSpaceTrack> {
+        AsyncSpaceTrack::login_to(::spacetrack::SPACE_TRACK_URL, identity, password)
+    }
+
+    /// Sign in to a server with the Space-Track API at `base_url`
+    pub fn login_to(base_url: &str, identity: &str, password: &str) -> Request<AsyncSpaceTrack> {
+        let (base_url, identity, password) = (String::from(base_url), String::from(identity), String::from(password));
+        request(move || {
+            SpaceTrack::login_to(&base_url, &identity, &password).map(|session| AsyncSpaceTrack { session })
+        })
+    }
+
+    /// The newest element set for each satellite
+    pub fn latest(&self, sat_numbers: &[u32]) -> Request<Vec<TLE>> {
+        let (session, sat_numbers) = (self.session.clone(), sat_numbers.to_vec());
+        request(move || session.latest(&sat_numbers))
+    }
+
+    /// Every element set for a satellite with an epoch from `start` up to
+    /// `end`, oldest first, as [`SpaceTrack::history`](../spacetrack/struct.SpaceTrack.html#method.history)
+    pub fn history(&self, sat_number: u32, start: &str, end: &str) -> Request<Vec<TLE>> {
+        let (session, start, end) = (self.session.clone(), String::from(start), String::from(end));
+        request(move || session.history(sat_number, &start, &end))
+    }
+
+    /// Any query of the GP classes, as [`SpaceTrack::query`](../spacetrack/struct.SpaceTrack.html#method.query)
+    pub fn query(&self, query: &str) -> Request<Vec<TLE>> {
+        let (session, query) = (self.session.clone(), String::from(query));
+        request(move || session.query(&query))
+    }
+
+    /// Sign out, ending the session
+    pub fn logout(self) -> Request<()> {
+        let session = self.session;
+        request(move || session.logout())
+    }
+}
+
+
+/// The errors of the last refresh, shared with the watcher
+type LastError = Arc<Mutex<Option<FetchError>>>;
+
+/// ## Catalog Watcher
+///
+/// A catalog downloaded again every interval in a background task, until
+/// the watcher is dropped.
+///
+/// ### Example
+///
+/// ```no_run
+/// extern crate sgp4;
+/// extern crate tokio;
+///
+/// use std::time::Duration;
+/// use sgp4::async_fetch::{AsyncFetcher, CatalogWatcher};
+///
+/// let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+/// let _guard = runtime.enter();
+///
+/// // Celestrak updates every two hours
+/// let fetcher = AsyncFetcher::with_cache("/tmp/celestrak");
+/// let mut watcher = CatalogWatcher::celestrak(fetcher, "stations", Duration::from_secs(2 * 3600)).unwrap();
+///
+/// // Wait for the first download, then use whatever is newest
+/// runtime.block_on(watcher.changed()).unwrap();
+/// let iss = watcher.catalog().get(25544).cloned();
+/// ```
+#[derive(Debug)]
+pub struct CatalogWatcher {
+    receiver: watch::Receiver<Arc<Catalog>>,
+    error: LastError,
+    task: JoinHandle<()>,
+}
+
+impl CatalogWatcher {
+
+    /// ## New
+    ///
+    /// Watch the catalog at `url`, downloading it now and then every
+    /// `interval`. The catalog is empty until the first download finishes;
+    /// wait for it with [`changed`](#method.changed).
+    ///
+    /// ### Panics
+    ///
+    /// Outside a tokio runtime with its timer enabled, or if `interval` is
+    /// zero.
+    pub fn new(fetcher: AsyncFetcher, url: &str, interval: Duration) -> CatalogWatcher {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let (sender, receiver) = watch::channel(Arc::new(Catalog::new()));
+        let error = LastError::default();
+        let task = tokio::spawn(Refresh {
+            fetcher,
+            url: String::from(url),
+            interval,
+            download: None,
+            body: None,
+            sender,
+            error: error.clone(),
+        });
+        CatalogWatcher { receiver, error, task }
+    }
+
+    /// Watch a named Celestrak group, as [`new`](#method.new)
+    pub fn celestrak(fetcher: AsyncFetcher, group: &str, interval: Duration) -> Result<CatalogWatcher, FetchError> {
+        Ok(CatalogWatcher::new(fetcher, &celestrak_url(group)?, interval))
+    }
+
+    /// The newest catalog. It doesn't change under the caller: a refresh
+    /// replaces the watcher's catalog, not this one.
+    pub fn catalog(&self) -> Arc<Catalog> {
+        self.receiver.borrow().clone()
+    }
+
+    /// Wait until the catalog is next replaced. An error means the
+    /// background task has stopped, which only happens if it panicked.
+    pub fn changed(&mut self) -> impl Future<Output = Result<(), watch::error::RecvError>> + '_ {
+        self.receiver.changed()
+    }
+
+    /// A receiver of the newest catalog, for another task to wait on
+    /// changes independently. It stops changing when the watcher is
+    /// dropped.
+    pub fn subscribe(&self) -> watch::Receiver<Arc<Catalog>> {
+        self.receiver.clone()
+    }
+
+    /// Why the latest refresh failed, if it did, clearing it
+    pub fn take_error(&self) -> Option<FetchError> {
+        self.error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).take()
+    }
+}
+
+impl Drop for CatalogWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The watcher's background task: wait for the next tick, download, and
+/// swap in the catalog if it changed, forever
+struct Refresh {
+    fetcher: AsyncFetcher,
+    url: String,
+    interval: Interval,
+    download: Option<Request<String>>,
+    /// The response the current catalog was read from
+    body: Option<String>,
+    sender: watch::Sender<Arc<Catalog>>,
+    error: LastError,
+}
+
+impl Refresh {
+
+    fn finish(&mut self, result: Result<String, FetchError>) -> Result<(), FetchError> {
+        let body = result?;
+        if self.body.as_ref() != Some(&body) {
+            let catalog = Catalog::parse(&body)?;
+            self.sender.send_replace(Arc::new(catalog));
+            self.body = Some(body);
+        }
+        Ok(())
+    }
+}
+
+impl Future for Refresh {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let refresh = self.get_mut();
+        loop {
+            match refresh.download {
+                None => {
+                    ready!(refresh.interval.poll_tick(cx));
+                    refresh.download = Some(refresh.fetcher.get(&refresh.url));
+                }
+                Some(ref mut download) => {
+                    let result = ready!(Pin::new(download).poll(cx));
+                    refresh.download = None;
+                    let outcome = refresh.finish(result);
+                    *refresh.error.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = outcome.err();
+                }
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use tokio::runtime::{Builder, Runtime};
+    use tokio::time::timeout;
+
+    /// Longest to wait for a change before failing rather than hanging
+    const WAIT: Duration = Duration::from_secs(5);
+
+    const ISS: &str = "\
+ISS (ZARYA)
+1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+";
+
+    const HST: &str = "\
+HST
+1 20580U 90037B   16210.21609549  .00000636  00000-0  30994-4 0  9994
+2 20580  28.4697 116.0488 0002540 243.1513 174.7453 15.08648106232480
+";
+
+    /// Answer each request with the next canned body
+    fn serve(bodies: Vec<String>) -> (String, thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/gp.php?GROUP=stations", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            for body in bodies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+                let response = format!("HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{}", body.len(), body);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        (url, server)
+    }
+
+    fn runtime() -> Runtime {
+        Builder::new_current_thread().enable_time().build().unwrap()
+    }
+
+    #[test]
+    fn get() {
+        let runtime = runtime();
+        let _guard = runtime.enter();
+        let (url, server) = serve(vec![String::from(ISS)]);
+        let body = runtime.block_on(AsyncFetcher::new().get(&url)).unwrap();
+        assert_eq!(body, ISS);
+        server.join().unwrap();
+
+        match runtime.block_on(AsyncFetcher::new().celestrak("../stations")) {
+            Err(FetchError::InvalidGroup(group)) => assert_eq!(group, "../stations"),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn watcher() {
+        let runtime = runtime();
+        let _guard = runtime.enter();
+
+        // The same response twice doesn't count as a change
+        let both = format!("{}{}", ISS, HST);
+        let (url, server) = serve(vec![String::from(ISS), String::from(ISS), both]);
+        let mut watcher = CatalogWatcher::new(AsyncFetcher::new(), &url, Duration::from_millis(20));
+        let mut subscriber = watcher.subscribe();
+        assert!(watcher.catalog().is_empty());
+
+        runtime.block_on(timeout(WAIT, watcher.changed())).unwrap().unwrap();
+        let first = watcher.catalog();
+        assert_eq!(first.len(), 1);
+        runtime.block_on(timeout(WAIT, watcher.changed())).unwrap().unwrap();
+        assert_eq!(watcher.catalog().len(), 2);
+        assert!(watcher.take_error().is_none());
+
+        // Earlier snapshots are left alone, and subscribers see the latest
+        assert_eq!(first.len(), 1);
+        runtime.block_on(timeout(WAIT, subscriber.changed())).unwrap().unwrap();
+        assert_eq!(subscriber.borrow().len(), 2);
+        server.join().unwrap();
+
+        // With the server gone, refreshing fails and the catalog stays
+        runtime.block_on(tokio::time::sleep(Duration::from_millis(100)));
+        assert!(watcher.take_error().is_some());
+        assert_eq!(watcher.catalog().len(), 2);
+    }
+
+    #[test]
+    fn invalid_group() {
+        let runtime = runtime();
+        let _guard = runtime.enter();
+        assert!(CatalogWatcher::celestrak(AsyncFetcher::new(), "", Duration::from_secs(60)).is_err());
+    }
+}
@@ -0,0 +1,490 @@
+/*!  # Batch Propagation
+
+Propagate many satellites at once. A [`Batch`](struct.Batch.html) stores
+the constants of its propagators in structure-of-arrays form, `LANES`
+satellites to a block, and works on a whole block at a time: the secular
+terms, the long period terms, Kepler's equation and the short period terms
+are each written as straight-line arithmetic over the lanes, with the
+branches of the scalar code turned into selects. The compiler packs much of
+that arithmetic into SIMD instructions on stable Rust (two lanes to an
+instruction on baseline x86-64, all four with AVX), with no `unsafe` and no
+dependencies. The trigonometric functions, powers and square roots are
+still called once per lane, and take much of the time.
+
+The scalar [`Kernel<f64>`](../precision/struct.Kernel.html), the one
+[`Propagator::propagate`](../struct.Propagator.html#method.propagate)
+runs, remains the reference implementation, and the batch results match it
+to rounding error. Deep space satellites, whose lunar-solar terms and
+resonance integration don't vectorize, are propagated by it in their lanes.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::array;
+use std::f64::consts::PI;
+
+use coordinates::{StateVector, TEME};
+use precision::Kernel;
+use {PropagationError, Propagator, KEPLER_ITERATIONS, KEPLER_TOLERANCE, MINUTES_PER_DAY, RE};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// Satellites propagated together, sized for 256 bit vector registers
+pub const LANES: usize = 4;
+
+type Lanes = [f64; LANES];
+
+/// A value for each lane
+fn lanes<F: FnMut(usize) -> f64>(f: F) -> Lanes {
+    array::from_fn(f)
+}
+
+/// The constants of `LANES` propagators, field by field
+#[derive(Debug, Clone)]
+struct Block {
+    n0_dp: Lanes,
+    ao_dp: Lanes,
+    e0: Lanes,
+    i0: Lanes,
+    wo: Lanes,
+    Omega0: Lanes,
+    M0: Lanes,
+    Bstar: Lanes,
+    sin_io: Lanes,
+    O: Lanes,
+    O2: Lanes,
+    one_plus_O: Lanes,
+    Bo2: Lanes,
+    n: Lanes,
+    qs4: Lanes,
+    xi4: Lanes,
+    simple: [bool; LANES],
+    circular: [bool; LANES],
+    C1: Lanes,
+    C3: Lanes,
+    C4: Lanes,
+    C5: Lanes,
+    D2: Lanes,
+    D3: Lanes,
+    D4: Lanes,
+    M_dot: Lanes,
+    omega_dot: Lanes,
+    Omega_dot1: Lanes,
+    Omega_dot: Lanes,
+    ke: Lanes,
+    k2: Lanes,
+    A30: Lanes,
+    XKMPER: Lanes,
+    max_age: [Option<f64>; LANES],
+    strict: [bool; LANES],
+    epoch: Lanes,
+    deep_space: [Option<Kernel<f64>>; LANES],
+}
+
+impl Block {
+
+    /// Gather up to `LANES` propagators. Short blocks repeat the last one
+    /// in the unused lanes.
+    fn new(propagators: &[Propagator]) -> Block {
+        let p = |lane: usize| &propagators[lane.min(propagators.len() - 1)];
+        Block {
+            n0_dp: lanes(|l| p(l).n0_dp),
+            ao_dp: lanes(|l| p(l).ao_dp),
+            e0: lanes(|l| p(l).e0),
+            i0: lanes(|l| p(l).i0),
+            wo: lanes(|l| p(l).wo),
+            Omega0: lanes(|l| p(l).Omega0),
+            M0: lanes(|l| p(l).M0),
+            Bstar: lanes(|l| p(l).Bstar),
+            sin_io: lanes(|l| p(l).sin_io),
+            O: lanes(|l| p(l).O),
+            O2: lanes(|l| p(l).O2),
+            one_plus_O: lanes(|l| p(l).one_plus_O),
+            Bo2: lanes(|l| p(l).Bo2),
+            n: lanes(|l| p(l).n),
+            qs4: lanes(|l| p(l).qs4),
+            xi4: lanes(|l| p(l).xi4),
+            simple: array::from_fn(|l| p(l).simple),
+            circular: array::from_fn(|l| p(l).circular),
+            C1: lanes(|l| p(l).C1),
+            C3: lanes(|l| p(l).C3),
+            C4: lanes(|l| p(l).C4),
+            C5: lanes(|l| p(l).C5),
+            D2: lanes(|l| p(l).D2),
+            D3: lanes(|l| p(l).D3),
+            D4: lanes(|l| p(l).D4),
+            M_dot: lanes(|l| p(l).M_dot),
+            omega_dot: lanes(|l| p(l).omega_dot),
+            Omega_dot1: lanes(|l| p(l).Omega_dot1),
+            Omega_dot: lanes(|l| p(l).Omega_dot),
+            ke: lanes(|l| p(l).gravity.ke),
+            k2: lanes(|l| p(l).gravity.k2()),
+            A30: lanes(|l| p(l).gravity.A30()),
+            XKMPER: lanes(|l| p(l).gravity.radius),
+            max_age: array::from_fn(|l| p(l).max_age),
+            strict: array::from_fn(|l| p(l).strict),
+            epoch: lanes(|l| p(l).epoch),
+            deep_space: array::from_fn(|l| p(l).deep_space.map(|_| Kernel::new(p(l)))),
+        }
+    }
+
+    /// The kernel's algorithm, one step at a time across every lane. See
+    /// `Kernel::propagate` for the equations. Every lane computes every
+    /// step, and a lane that fails a check gets its error at the end.
+    fn propagate(&self, time: &Lanes) -> [Result<StateVector, PropagationError>; LANES] {
+        let c = self;
+        let t = time;
+
+        // Section 5: secular effects of drag and gravitation
+        let M_DF = lanes(|l| c.M0[l] + c.M_dot[l] * t[l]);
+        let omega_DF = lanes(|l| c.wo[l] + c.omega_dot[l] * t[l]);
+        let Omega_DF = lanes(|l| c.Omega0[l] + c.Omega_dot[l] * t[l]);
+        let Omega = lanes(|l| Omega_DF[l] + 3.5 * c.Bo2[l] * c.Omega_dot1[l] * c.C1[l] * t[l].powi(2));
+
+        // The full drag terms, selected out in lanes with a low perigee
+        let cos_M_DF = lanes(|l| M_DF[l].cos());
+        let cos_M0 = lanes(|l| c.M0[l].cos());
+        let cos_wo = lanes(|l| c.wo[l].cos());
+        let d_omega = lanes(|l| c.Bstar[l] * c.C3[l] * cos_wo[l] * t[l]);
+        let d_M = lanes(|l| {
+            let d_M = -(2.0/3.0) * c.qs4[l] * c.Bstar[l] * c.xi4[l] * (RE / (c.e0[l] * c.n[l]))
+                * ((1.0 + c.n[l] * cos_M_DF[l]).powi(3) - (1.0 + c.n[l] * cos_M0[l]).powi(3));
+            if c.circular[l] { 0.0 } else { d_M }
+        });
+        let M_p = lanes(|l| if c.simple[l] { M_DF[l] } else { M_DF[l] + d_omega[l] + d_M[l] });
+        let omega = lanes(|l| if c.simple[l] { omega_DF[l] } else { omega_DF[l] - d_omega[l] - d_M[l] });
+        let tempa = lanes(|l| {
+            let full = 1.0 - c.C1[l] * t[l] - c.D2[l] * t[l].powi(2) - c.D3[l] * t[l].powi(3) - c.D4[l] * t[l].powi(4);
+            if c.simple[l] { 1.0 - c.C1[l] * t[l] } else { full }
+        });
+        let sin_M_p = lanes(|l| M_p[l].sin());
+        let sin_M0 = lanes(|l| c.M0[l].sin());
+        let tempe = lanes(|l| {
+            let full = c.Bstar[l] * c.C4[l] * t[l] + c.Bstar[l] * c.C5[l] * (sin_M_p[l] - sin_M0[l]);
+            if c.simple[l] { c.Bstar[l] * c.C4[l] * t[l] } else { full }
+        });
+        let templ = lanes(|l| {
+            let (C1, D2, D3, D4) = (c.C1[l], c.D2[l], c.D3[l], c.D4[l]);
+            let full = 1.5 * C1 * t[l].powi(2)
+                + (D2 + 2.0 * C1.powi(2)) * t[l].powi(3)
+                + 0.25 * (3.0 * D3 + 12.0 * C1 * D2 + 10.0 * C1.powi(3)) * t[l].powi(4)
+                + 0.2 * (3.0 * D4 + 12.0 * C1 * D3 + 6.0 * D2.powi(2) + 30.0 * C1.powi(2) * D2 + 15.0 * C1.powi(4)) * t[l].powi(5);
+            if c.simple[l] { 1.5 * C1 * t[l].powi(2) } else { full }
+        });
+
+        let a = lanes(|l| c.ao_dp[l] * tempa[l].powi(2));
+        let e_raw = lanes(|l| c.e0[l] - tempe[l]);
+        let e = lanes(|l| e_raw[l].max(1.0e-6));
+        let IL = lanes(|l| M_p[l] + omega[l] + Omega[l] + c.n0_dp[l] * templ[l]);
+        let B = lanes(|l| (1.0 - e[l].powi(2)).sqrt());
+        let n_ = lanes(|l| c.ke[l] / a[l].powf(3.0/2.0));
+
+        // Section 6: long period periodic terms
+        let cos_omega = lanes(|l| omega[l].cos());
+        let sin_omega = lanes(|l| omega[l].sin());
+        let axN = lanes(|l| e[l] * cos_omega[l]);
+        let IL_L = lanes(|l| {
+            (c.A30[l] * c.sin_io[l]) / (8.0 * c.k2[l] * a[l] * B[l].powi(2)) * axN[l] * (3.0 + 5.0 * c.O[l]) / c.one_plus_O[l]
+        });
+        let ayNL = lanes(|l| (c.A30[l] * c.sin_io[l]) / (4.0 * c.k2[l] * a[l] * B[l].powi(2)));
+        let IL_T = lanes(|l| IL[l] + IL_L[l]);
+        let ayN = lanes(|l| e[l] * sin_omega[l] + ayNL[l]);
+        let U = lanes(|l| (IL_T[l] - Omega[l]) % (2.0 * PI));
+
+        // Section 7: Kepler's equation, freezing each lane as it converges
+        let mut Ew = U;
+        let mut converged = [false; LANES];
+        for _ in 0..KEPLER_ITERATIONS {
+            let sin_Ew = lanes(|l| Ew[l].sin());
+            let cos_Ew = lanes(|l| Ew[l].cos());
+            let delta = lanes(|l| {
+                (U[l] - ayN[l] * cos_Ew[l] + axN[l] * sin_Ew[l] - Ew[l]) / (1.0 - ayN[l] * sin_Ew[l] - axN[l] * cos_Ew[l])
+            });
+            Ew = lanes(|l| if converged[l] { Ew[l] } else { Ew[l] + delta[l] });
+            converged = array::from_fn(|l| converged[l] || delta[l].abs() <= KEPLER_TOLERANCE);
+            if converged.iter().all(|&done| done) {
+                break;
+            }
+        }
+        let sin_Ew = lanes(|l| Ew[l].sin());
+        let cos_Ew = lanes(|l| Ew[l].cos());
+
+        // Section 8: short period periodic terms
+        let ecosE = lanes(|l| axN[l] * cos_Ew[l] + ayN[l] * sin_Ew[l]);
+        let esinE = lanes(|l| axN[l] * sin_Ew[l] - ayN[l] * cos_Ew[l]);
+        let eL2 = lanes(|l| axN[l].powi(2) + ayN[l].powi(2));
+        let pL = lanes(|l| a[l] * (1.0 - eL2[l]));
+        let r = lanes(|l| a[l] * (1.0 - ecosE[l]));
+        let sqrt_a = lanes(|l| a[l].sqrt());
+        let sqrt_pL = lanes(|l| pL[l].sqrt());
+        let r_dot = lanes(|l| c.ke[l] * sqrt_a[l] / r[l] * esinE[l]);
+        let rf_dot = lanes(|l| c.ke[l] * sqrt_pL[l] / r[l]);
+        let BL = lanes(|l| (1.0 - eL2[l]).sqrt());
+        let cos_u = lanes(|l| (a[l] / r[l]) * (cos_Ew[l] - axN[l] + ayN[l] * esinE[l] / (1.0 + BL[l])));
+        let sin_u = lanes(|l| (a[l] / r[l]) * (sin_Ew[l] - ayN[l] - axN[l] * esinE[l] / (1.0 + BL[l])));
+        let u = lanes(|l| sin_u[l].atan2(cos_u[l]));
+        let sin_2u = lanes(|l| 2.0 * sin_u[l] * cos_u[l]);
+        let cos_2u = lanes(|l| 2.0 * cos_u[l].powi(2) - 1.0);
+
+        let dr = lanes(|l| c.k2[l] / (2.0 * pL[l]) * (1.0 - c.O2[l]) * cos_2u[l]);
+        let du = lanes(|l| -c.k2[l] / (4.0 * pL[l].powi(2)) * (7.0 * c.O2[l] - 1.0) * sin_2u[l]);
+        let dOmega = lanes(|l| (3.0 * c.k2[l] * c.O[l]) / (2.0 * pL[l].powi(2)) * sin_2u[l]);
+        let di = lanes(|l| (3.0 * c.k2[l] * c.O[l]) / (2.0 * pL[l].powi(2)) * c.sin_io[l] * cos_2u[l]);
+        let dr_dot = lanes(|l| -c.k2[l] * n_[l] / pL[l] * (1.0 - c.O2[l]) * sin_2u[l]);
+        let drf_dot = lanes(|l| c.k2[l] * n_[l] / pL[l] * ((1.0 - c.O2[l]) * cos_2u[l] + 1.5 * (3.0 * c.O2[l] - 1.0)));
+
+        let rk = lanes(|l| r[l] * (1.0 - 1.5 * c.k2[l] * BL[l] / pL[l].powi(2) * (3.0 * c.O2[l] - 1.0)) + dr[l]);
+        let uk = lanes(|l| u[l] + du[l]);
+        let Omegak = lanes(|l| Omega[l] + dOmega[l]);
+        let ik = lanes(|l| c.i0[l] + di[l]);
+        let r_dotk = lanes(|l| r_dot[l] + dr_dot[l]);
+        let rf_dotk = lanes(|l| rf_dot[l] + drf_dot[l]);
+
+        // Section 9: unit orientation vectors
+        let (sin_Omegak, cos_Omegak) = (lanes(|l| Omegak[l].sin()), lanes(|l| Omegak[l].cos()));
+        let (sin_ik, cos_ik) = (lanes(|l| ik[l].sin()), lanes(|l| ik[l].cos()));
+        let (sin_uk, cos_uk) = (lanes(|l| uk[l].sin()), lanes(|l| uk[l].cos()));
+        let Mx = lanes(|l| -sin_Omegak[l] * cos_ik[l]);
+        let My = lanes(|l| cos_Omegak[l] * cos_ik[l]);
+        let Mz = sin_ik;
+        let (Nx, Ny) = (cos_Omegak, sin_Omegak);
+        let Ux = lanes(|l| Mx[l] * sin_uk[l] + Nx[l] * cos_uk[l]);
+        let Uy = lanes(|l| My[l] * sin_uk[l] + Ny[l] * cos_uk[l]);
+        let Uz = lanes(|l| Mz[l] * sin_uk[l]);
+        let Vx = lanes(|l| Mx[l] * cos_uk[l] - Nx[l] * sin_uk[l]);
+        let Vy = lanes(|l| My[l] * cos_uk[l] - Ny[l] * sin_uk[l]);
+        let Vz = lanes(|l| Mz[l] * cos_uk[l]);
+
+        let v_scale = lanes(|l| c.XKMPER[l] / 60.0);
+        let position = [
+            lanes(|l| rk[l] * Ux[l] * c.XKMPER[l]),
+            lanes(|l| rk[l] * Uy[l] * c.XKMPER[l]),
+            lanes(|l| rk[l] * Uz[l] * c.XKMPER[l]),
+        ];
+        let velocity = [
+            lanes(|l| (r_dotk[l] * Ux[l] + rf_dotk[l] * Vx[l]) * v_scale[l]),
+            lanes(|l| (r_dotk[l] * Uy[l] + rf_dotk[l] * Vy[l]) * v_scale[l]),
+            lanes(|l| (r_dotk[l] * Uz[l] + rf_dotk[l] * Vz[l]) * v_scale[l]),
+        ];
+
+        array::from_fn(|l| {
+            let epoch = c.epoch[l] + t[l] / MINUTES_PER_DAY;
+            if let Some(ref kernel) = c.deep_space[l] {
+                let state = kernel.propagate(t[l])?;
+                let (position, velocity) = (state.position, state.velocity);
+                return Ok(StateVector {
+                    epoch,
+                    position: TEME::new(position[0], position[1], position[2]),
+                    velocity: TEME::new(velocity[0], velocity[1], velocity[2]),
+                });
+            }
+
+            // The same checks as the kernel, in the same order
+            if c.strict[l] && c.max_age[l].is_some_and(|max_age| t[l].abs() > max_age) {
+                return Err(PropagationError::TooOld);
+            }
+            if tempa[l] <= 0.0 || a[l] < RE {
+                return Err(PropagationError::Decayed);
+            }
+            if !(-0.001..1.0).contains(&e_raw[l]) {
+                return Err(PropagationError::Diverged);
+            }
+            if pL[l] < 0.0 {
+                return Err(PropagationError::NegativeSemiLatusRectum);
+            }
+            if rk[l] < RE {
+                return Err(PropagationError::Decayed);
+            }
+
+            Ok(StateVector {
+                epoch,
+                position: TEME::new(position[0][l], position[1][l], position[2][l]),
+                velocity: TEME::new(velocity[0][l], velocity[1][l], velocity[2][l]),
+            })
+        })
+    }
+}
+
+/// ## Batch
+///
+/// Many initialized propagators, laid out for propagating together.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::angle::Deg;
+/// use sgp4::batch::Batch;
+///
+/// let iss = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // A fleet spread around the ISS orbit
+/// let propagators: Vec<sgp4::Propagator> = (0..10)
+///     .map(|index| {
+///         let mut tle = iss.clone();
+///         tle.mean_anomaly = Deg(36.0 * index as f64);
+///         sgp4::Propagator::new(&tle).unwrap()
+///     })
+///     .collect();
+///
+/// let batch = Batch::new(&propagators);
+///
+/// // Everything an hour after its own epoch
+/// for state in batch.propagate_all(60.0) {
+///     let state = state.unwrap();
+///     println!("{} {} {}", state.position.X, state.position.Y, state.position.Z);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Batch {
+    blocks: Vec<Block>,
+    len: usize,
+}
+
+impl Batch {
+
+    /// Lay out a set of propagators for batch propagation
+    pub fn new(propagators: &[Propagator]) -> Batch {
+        Batch {
+            blocks: propagators.chunks(LANES).map(Block::new).collect(),
+            len: propagators.len(),
+        }
+    }
+
+    /// Number of satellites
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no satellites
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Propagate each satellite to its own time, `times[i]` minutes since
+    /// the epoch of satellite `i`. Results are in the same order as the
+    /// propagators the batch was made from.
+    ///
+    /// ### Panics
+    ///
+    /// If there isn't one time for each satellite.
+    pub fn propagate(&self, times: &[f64]) -> Vec<Result<StateVector, PropagationError>> {
+        assert_eq!(times.len(), self.len, "need one time for each satellite");
+
+        let mut states = Vec::with_capacity(self.len);
+        for (block, times) in self.blocks.iter().zip(times.chunks(LANES)) {
+            let time = lanes(|l| times[l.min(times.len() - 1)]);
+            states.extend(IntoIterator::into_iter(block.propagate(&time)).take(times.len()));
+        }
+        states
+    }
+
+    /// Propagate every satellite to `time` minutes since its own epoch
+    pub fn propagate_all(&self, time: f64) -> Vec<Result<StateVector, PropagationError>> {
+        let mut states = Vec::with_capacity(self.len);
+        for (index, block) in self.blocks.iter().enumerate() {
+            let used = (self.len - index * LANES).min(LANES);
+            states.extend(IntoIterator::into_iter(block.propagate(&[time; LANES])).take(used));
+        }
+        states
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use angle::Deg;
+    use test_support;
+
+    /// The ISS, a low perigee orbit, an eccentric orbit, a decaying orbit
+    /// and a resonant deep space one, so the blocks mix simple, full drag
+    /// and deep space lanes and a lane fails
+    fn propagators() -> Vec<Propagator> {
+        let iss = test_support::iss();
+        let mut low = iss.clone();
+        low.mean_motion = 16.3;
+        low.bstar = 0.001;
+        let mut eccentric = iss.clone();
+        eccentric.e = 0.2;
+        eccentric.mean_motion = 11.0;
+        let mut decaying = iss.clone();
+        decaying.mean_motion = 16.4;
+        decaying.bstar = 0.05;
+        let molniya = test_support::molniya();
+
+        [iss, low, eccentric, decaying, molniya].iter()
+            .cycle()
+            .take(11)
+            .enumerate()
+            .map(|(index, tle)| {
+                let mut tle = tle.clone();
+                tle.mean_anomaly = (tle.mean_anomaly + Deg(37.0 * index as f64)).normalized();
+                Propagator::new(&tle).unwrap()
+            })
+            .collect()
+    }
+
+    fn assert_matches(batch: &Result<StateVector, PropagationError>, scalar: &Result<StateVector, PropagationError>) {
+        match (batch, scalar) {
+            (Ok(batch), Ok(scalar)) => {
+                assert_eq!(batch.epoch, scalar.epoch);
+                assert!((batch.position - scalar.position).norm() < 1e-8, "{:?} {:?}", batch, scalar);
+                assert!((batch.velocity - scalar.velocity).norm() < 1e-11, "{:?} {:?}", batch, scalar);
+            }
+            (batch, scalar) => assert_eq!(batch, scalar),
+        }
+    }
+
+    #[test]
+    fn matches_scalar() {
+        let propagators = propagators();
+        assert!(propagators.iter().any(|propagator| propagator.simple));
+        assert!(propagators.iter().any(|propagator| !propagator.simple));
+        assert!(propagators.iter().any(|propagator| propagator.is_deep_space()));
+
+        let batch = Batch::new(&propagators);
+        assert_eq!(batch.len(), 11);
+
+        for &time in [0.0, 90.0, -720.0, 1440.0, 10.0 * 1440.0].iter() {
+            let states = batch.propagate_all(time);
+            assert_eq!(states.len(), 11);
+            for (state, propagator) in states.iter().zip(&propagators) {
+                assert_matches(state, &propagator.propagate(time));
+            }
+        }
+
+        // Some lanes fail while their neighbours carry on
+        let states = batch.propagate_all(10.0 * 1440.0);
+        assert!(states.iter().any(|state| state.is_err()));
+        assert!(states.iter().any(|state| state.is_ok()));
+    }
+
+    #[test]
+    fn own_times() {
+        let propagators = propagators();
+        let batch = Batch::new(&propagators);
+        let times: Vec<f64> = (0..propagators.len()).map(|index| 13.0 * index as f64).collect();
+
+        let states = batch.propagate(&times);
+        for ((state, propagator), &time) in states.iter().zip(&propagators).zip(&times) {
+            assert_matches(state, &propagator.propagate(time));
+        }
+
+        assert!(Batch::new(&[]).propagate(&[]).is_empty());
+    }
+}
@@ -0,0 +1,270 @@
+/*!  # Command Line Propagator
+
+Reads TLEs from a file, or stdin, and prints each satellite's TEME position
+and velocity as CSV. Requires the `cli` feature.
+
+```text
+cargo run --features cli -- iss.txt --time 2016-07-29T00:00:00Z --geodetic
+curl -s https://celestrak.org/NORAD/elements/stations.txt \
+    | sgp4 --start now --end 2025-01-01T06:00:00Z --step 10
+```
+
+Without a time each TLE is propagated to its own epoch.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+extern crate chrono;
+extern crate sgp4;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
+
+use sgp4::coordinates;
+use sgp4::time;
+use sgp4::tle::{self, TLE};
+use sgp4::{Propagator, MINUTES_PER_DAY};
+
+const USAGE: &str = "\
+usage: sgp4 [FILE] [--time TIME | --start TIME --end TIME [--step MINUTES]] [--geodetic]
+
+Print the TEME position (km) and velocity (km/s) of each TLE in FILE, or
+stdin, as CSV. TIME is RFC 3339 (2016-07-29T00:00:00Z) or \"now\"; without
+one each TLE is propagated to its epoch.
+
+    --geodetic    also print WGS84 latitude, longitude (degrees) and altitude (km)
+";
+
+/// When to propagate to
+#[derive(Debug, Clone, PartialEq)]
+enum Times {
+    Epoch,
+    At(DateTime<Utc>),
+    Range { start: DateTime<Utc>, end: DateTime<Utc>, step: f64 },
+}
+
+/// What the command line asked for
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    path: Option<String>,
+    times: Times,
+    geodetic: bool,
+}
+
+fn parse_time(text: &str) -> Result<DateTime<Utc>, String> {
+    if text == "now" {
+        return Ok(Utc::now());
+    }
+    DateTime::parse_from_rfc3339(text)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|error| format!("invalid time \"{}\": {}", text, error))
+}
+
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut path = None;
+    let mut time = None;
+    let mut start = None;
+    let mut end = None;
+    let mut step = None;
+    let mut geodetic = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--time" => time = Some(parse_time(&value()?)?),
+            "--start" => start = Some(parse_time(&value()?)?),
+            "--end" => end = Some(parse_time(&value()?)?),
+            "--step" => {
+                let text = value()?;
+                match text.parse::<f64>() {
+                    Ok(minutes) if minutes > 0.0 => step = Some(minutes),
+                    _ => return Err(format!("invalid step \"{}\"", text)),
+                }
+            }
+            "--geodetic" => geodetic = true,
+            "-h" | "--help" => return Err(String::new()),
+            "-" => path = None,
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    let times = match (time, start, end) {
+        (None, None, None) if step.is_none() => Times::Epoch,
+        (Some(time), None, None) if step.is_none() => Times::At(time),
+        (None, Some(start), Some(end)) if start <= end => Times::Range { start, end, step: step.unwrap_or(1.0) },
+        (None, Some(_), Some(_)) => return Err("--end is before --start".into()),
+        _ => return Err("give either --time, or --start and --end".into()),
+    };
+
+    Ok(Options { path, times, geodetic })
+}
+
+/// The times to propagate a TLE to
+fn times_for(tle: &TLE, times: &Times) -> Vec<DateTime<Utc>> {
+    match *times {
+        Times::Epoch => vec![tle.epoch()],
+        Times::At(time) => vec![time],
+        Times::Range { start, end, step } => {
+            let step = Duration::nanoseconds((step * 60.0e9) as i64);
+            let mut times = Vec::new();
+            let mut time = start;
+            while time <= end {
+                times.push(time);
+                time += step;
+            }
+            times
+        }
+    }
+}
+
+/// Julian date (UTC) of a time
+fn julian_date(time: DateTime<Utc>) -> f64 {
+    time::julian_date_from_unix(time.timestamp() as f64 + f64::from(time.timestamp_subsec_nanos()) / 1.0e9)
+}
+
+fn header(geodetic: bool) -> &'static str {
+    if geodetic {
+        "sat_number,name,time,x,y,z,vx,vy,vz,latitude,longitude,altitude"
+    } else {
+        "sat_number,name,time,x,y,z,vx,vy,vz"
+    }
+}
+
+/// Write a CSV row for each time, stopping at the first that can't be
+/// propagated
+fn write_rows<W: Write>(out: &mut W, tle: &TLE, options: &Options) -> Result<(), String> {
+    let propagator = Propagator::new(tle).map_err(|error| format!("{}: {}", tle.sat_number, error))?;
+    let epoch = tle.epoch_julian_date();
+
+    for time in times_for(tle, &options.times) {
+        let julian_date = julian_date(time);
+        let state = propagator.propagate((julian_date - epoch) * MINUTES_PER_DAY)
+            .map_err(|error| format!("{} at {}: {}", tle.sat_number, time.to_rfc3339(), error))?;
+
+        let mut row = format!(
+            "{},\"{}\",{},{:.6},{:.6},{:.6},{:.9},{:.9},{:.9}",
+            tle.sat_number, tle.name.replace('"', "\"\""), time.to_rfc3339_opts(SecondsFormat::Millis, true),
+            state.position.X, state.position.Y, state.position.Z,
+            state.velocity.X, state.velocity.Y, state.velocity.Z,
+        );
+        if options.geodetic {
+            // UTC stands in for UT1, good to a fraction of a kilometer
+            let ecef = coordinates::ecef_from_teme(&state.position, time::gmst(julian_date));
+            let geodetic = coordinates::geodetic_from_ecef(&ecef);
+            row += &format!(",{:.6},{:.6},{:.6}", geodetic.lat.to_degrees(), geodetic.lon.to_degrees(), geodetic.alt);
+        }
+        writeln!(out, "{}", row).map_err(|error| error.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let text = match options.path {
+        Some(ref path) => fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?,
+        None => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text).map_err(|error| error.to_string())?;
+            text
+        }
+    };
+    let tles = tle::parse_all(&text).map_err(|error| error.to_string())?;
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    writeln!(out, "{}", header(options.geodetic)).map_err(|error| error.to_string())?;
+    for tle in &tles {
+        write_rows(&mut out, tle, options)?;
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            if message.is_empty() {
+                print!("{}", USAGE);
+                return;
+            }
+            eprint!("sgp4: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(message) = run(&options) {
+        eprintln!("sgp4: {}", message);
+        process::exit(1);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISS: &str = "ISS (ZARYA)
+1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn arguments() {
+        let options = parse_args(args("iss.txt --geodetic")).unwrap();
+        assert_eq!(options, Options { path: Some("iss.txt".into()), times: Times::Epoch, geodetic: true });
+
+        let options = parse_args(args("--start 2016-07-29T00:00:00Z --end 2016-07-29T01:00:00+01:00")).unwrap();
+        let start = parse_time("2016-07-29T00:00:00Z").unwrap();
+        assert_eq!(options.times, Times::Range { start, end: start, step: 1.0 });
+        assert_eq!(options.path, None);
+
+        assert!(parse_args(args("--time")).is_err());
+        assert!(parse_args(args("--time yesterday")).is_err());
+        assert!(parse_args(args("--start 2016-07-29T00:00:00Z")).is_err());
+        assert!(parse_args(args("--time 2016-07-29T00:00:00Z --step 5")).is_err());
+        assert!(parse_args(args("--start 2016-07-29T00:00:00Z --end 2016-07-28T00:00:00Z")).is_err());
+        assert!(parse_args(args("a.txt b.txt")).is_err());
+        assert_eq!(parse_args(args("--help")), Err(String::new()));
+    }
+
+    #[test]
+    fn rows() {
+        let tle = tle::parse(ISS).unwrap();
+        let start = parse_time("2016-07-29T00:00:00Z").unwrap();
+        let options = Options {
+            path: None,
+            times: Times::Range { start, end: start + Duration::minutes(10), step: 5.0 },
+            geodetic: true,
+        };
+
+        let mut out = Vec::new();
+        write_rows(&mut out, &tle, &options).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = out.lines().collect();
+        assert_eq!(rows.len(), 3);
+
+        let fields: Vec<&str> = rows[2].split(',').collect();
+        assert_eq!(fields.len(), header(true).split(',').count());
+        assert_eq!(&fields[..3], &["25544", "\"ISS (ZARYA)\"", "2016-07-29T00:10:00.000Z"]);
+
+        let expected = sgp4::propagate_to(&tle, start + Duration::minutes(10)).unwrap();
+        assert!((fields[3].parse::<f64>().unwrap() - expected.position.X).abs() < 1e-3);
+        let altitude = fields[11].parse::<f64>().unwrap();
+        assert!(altitude > 350.0 && altitude < 450.0);
+    }
+}
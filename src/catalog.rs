@@ -0,0 +1,270 @@
+/*!  # Catalog
+
+Many TLEs, one per satellite, keyed by NORAD catalog number.
+
+Catalog files often carry several element sets for the same satellite, or
+get merged from more than one source. A [`Catalog`](struct.Catalog.html)
+keeps only the newest element set for each satellite number, and can be
+searched by number, name or international designator.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::iter::FromIterator;
+
+#[cfg(any(feature = "std", test))]
+use std::collections::{btree_map, BTreeMap};
+#[cfg(not(any(feature = "std", test)))]
+use alloc::collections::{btree_map, BTreeMap};
+
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use tle::{self, ReadError, TLE};
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::string::String;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// ## Catalog
+///
+/// The newest TLE for each satellite, keyed by NORAD catalog number.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::catalog::Catalog;
+///
+/// let catalog = Catalog::parse("\
+/// ISS (ZARYA)
+/// 1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+/// 2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+/// ").unwrap();
+///
+/// let iss = catalog.get(25544).unwrap();
+/// assert_eq!(catalog.find_designator("1998-067A").unwrap().sat_number, 25544);
+/// assert_eq!(catalog.find_name("zarya").next().unwrap().sat_number, iss.sat_number);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    tles: BTreeMap<u32, TLE>,
+}
+
+impl Catalog {
+
+    /// An empty catalog
+    pub fn new() -> Catalog {
+        Catalog::default()
+    }
+
+    /// Read a catalog of TLEs in any of the formats
+    /// [`tle::parse_all`](../tle/fn.parse_all.html) accepts
+    pub fn parse(text: &str) -> Result<Catalog, ReadError> {
+        Ok(tle::parse_all(text)?.into_iter().collect())
+    }
+
+    /// Read a catalog file
+    #[cfg(feature = "std")]
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Catalog, ReadError> {
+        Ok(tle::load_from_file(path)?.into_iter().collect())
+    }
+
+    /// ## Insert
+    ///
+    /// Add a TLE, keeping whichever element set for its satellite has the
+    /// later epoch. Returns the one that was dropped, if the satellite was
+    /// already in the catalog.
+    pub fn insert(&mut self, tle: TLE) -> Option<TLE> {
+        match self.tles.entry(tle.sat_number) {
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(tle);
+                None
+            }
+            btree_map::Entry::Occupied(mut entry) => {
+                if tle.epoch_julian_date() >= entry.get().epoch_julian_date() {
+                    Some(entry.insert(tle))
+                } else {
+                    Some(tle)
+                }
+            }
+        }
+    }
+
+    /// Take a satellite out of the catalog
+    pub fn remove(&mut self, sat_number: u32) -> Option<TLE> {
+        self.tles.remove(&sat_number)
+    }
+
+    /// The TLE for a NORAD catalog number
+    pub fn get(&self, sat_number: u32) -> Option<&TLE> {
+        self.tles.get(&sat_number)
+    }
+
+    /// ## Find Name
+    ///
+    /// Every satellite whose name contains `text`, ignoring case, in order
+    /// of catalog number.
+    pub fn find_name<'a>(&'a self, text: &str) -> impl Iterator<Item = &'a TLE> + 'a {
+        let text = text.to_lowercase();
+        self.iter().filter(move |tle| tle.name.to_lowercase().contains(&text))
+    }
+
+    /// ## Find Designator
+    ///
+    /// The satellite with an international designator, either as written in
+    /// a TLE (`98067A`) or in full COSPAR form (`1998-067A`).
+    pub fn find_designator(&self, designator: &str) -> Option<&TLE> {
+        let designator = short_designator(designator.trim());
+        self.iter().find(|tle| tle.int_designator.trim() == designator)
+    }
+
+    /// Number of satellites
+    pub fn len(&self) -> usize {
+        self.tles.len()
+    }
+
+    /// Whether the catalog has no satellites
+    pub fn is_empty(&self) -> bool {
+        self.tles.is_empty()
+    }
+
+    /// Every TLE, in order of catalog number
+    pub fn iter(&self) -> btree_map::Values<'_, u32, TLE> {
+        self.tles.values()
+    }
+
+    /// Every TLE, oldest epoch first. Satellites with the same epoch are in
+    /// order of catalog number.
+    pub fn by_epoch(&self) -> Vec<&TLE> {
+        let mut tles: Vec<&TLE> = self.iter().collect();
+        tles.sort_by(|a, b| a.epoch_julian_date().total_cmp(&b.epoch_julian_date()));
+        tles
+    }
+}
+
+/// `1998-067A` as it's written in a TLE, `98067A`. Anything else is left
+/// alone.
+fn short_designator(designator: &str) -> String {
+    match designator.split_once('-') {
+        Some((year, piece)) if year.len() == 4 => {
+            let mut short = String::from(&year[2..]);
+            short.push_str(piece);
+            short
+        }
+        _ => String::from(designator),
+    }
+}
+
+impl FromIterator<TLE> for Catalog {
+    fn from_iter<I: IntoIterator<Item = TLE>>(tles: I) -> Catalog {
+        let mut catalog = Catalog::new();
+        catalog.extend(tles);
+        catalog
+    }
+}
+
+impl Extend<TLE> for Catalog {
+    fn extend<I: IntoIterator<Item = TLE>>(&mut self, tles: I) {
+        for tle in tles {
+            self.insert(tle);
+        }
+    }
+}
+
+impl IntoIterator for Catalog {
+    type Item = TLE;
+    type IntoIter = btree_map::IntoValues<u32, TLE>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tles.into_values()
+    }
+}
+
+impl<'a> IntoIterator for &'a Catalog {
+    type Item = &'a TLE;
+    type IntoIter = btree_map::Values<'a, u32, TLE>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOG: &str = "\
+ISS (ZARYA)
+1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+1 11801U          80230.29629788  .01431103  00000-0  14311-1 0    13
+2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848    13
+";
+
+    fn catalog() -> Catalog {
+        let mut catalog = Catalog::parse(CATALOG).unwrap();
+
+        // An older element set for the ISS, and a second station
+        let mut old = catalog.get(25544).unwrap().clone();
+        old.epoch_day -= 1.0;
+        old.mean_motion = 15.0;
+        assert_eq!(catalog.insert(old).unwrap().mean_motion, 15.0);
+
+        let mut tiangong = catalog.get(25544).unwrap().clone();
+        tiangong.name = String::from("TIANGONG 1");
+        tiangong.sat_number = 37820;
+        tiangong.int_designator = String::from("11053A  ");
+        tiangong.epoch_day = 200.5;
+        assert!(catalog.insert(tiangong).is_none());
+
+        catalog
+    }
+
+    #[test]
+    fn lookup() {
+        let catalog = catalog();
+        assert_eq!(catalog.len(), 3);
+        assert_eq!(catalog.get(25544).unwrap().mean_motion, 15.54888439);
+        assert!(catalog.get(1).is_none());
+
+        let stations: Vec<u32> = catalog.find_name("I").map(|tle| tle.sat_number).collect();
+        assert_eq!(stations, vec![25544, 37820]);
+        assert_eq!(catalog.find_name("zarya").count(), 1);
+
+        assert_eq!(catalog.find_designator("98067A").unwrap().sat_number, 25544);
+        assert_eq!(catalog.find_designator("2011-053A").unwrap().sat_number, 37820);
+        assert!(catalog.find_designator("1998-067B").is_none());
+    }
+
+    #[test]
+    fn newer_replaces_older() {
+        let mut catalog = catalog();
+        let mut newer = catalog.get(25544).unwrap().clone();
+        newer.epoch_day += 0.5;
+        newer.mean_motion = 15.6;
+
+        assert_eq!(catalog.insert(newer).unwrap().mean_motion, 15.54888439);
+        assert_eq!(catalog.get(25544).unwrap().mean_motion, 15.6);
+        assert_eq!(catalog.remove(25544).unwrap().mean_motion, 15.6);
+        assert_eq!(catalog.len(), 2);
+    }
+
+    #[test]
+    fn epoch_order() {
+        let catalog = catalog();
+        let numbers: Vec<u32> = catalog.by_epoch().iter().map(|tle| tle.sat_number).collect();
+        assert_eq!(numbers, vec![11801, 37820, 25544]);
+
+        let numbers: Vec<u32> = catalog.into_iter().map(|tle| tle.sat_number).collect();
+        assert_eq!(numbers, vec![11801, 25544, 37820]);
+    }
+}
@@ -0,0 +1,237 @@
+/*!  # Classification
+
+Sort element sets by the kind of orbit they describe: low, medium, high
+or geosynchronous Earth orbit, or highly elliptical, and whether an orbit
+is geostationary, sun synchronous or frozen.
+
+Every test works from the mean elements the propagator recovers from the
+TLE, with the thresholds catalogs such as SATCAT commonly use. They're
+meant for filtering a catalog, not for deciding whether a particular
+satellite is being station kept.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+use std::fmt;
+
+use angle::Deg;
+use elements::{frozen_eccentricity, raan_rate, Elements, TROPICAL_YEAR};
+use tle::TLE;
+use MINUTES_PER_DAY;
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+
+/// Longest period of a low Earth orbit (minutes), about 2000 km up
+pub const LEO_MAX_PERIOD: f64 = 128.0;
+
+/// Least eccentricity of a highly elliptical orbit
+pub const HEO_MIN_ECCENTRICITY: f64 = 0.25;
+
+/// Length of the sidereal day (minutes), the period of a geosynchronous
+/// orbit
+pub const SIDEREAL_DAY: f64 = 1436.0682;
+
+/// Range of mean motions (revolutions/day) classed as geosynchronous orbit
+const GEO_MEAN_MOTION: (f64, f64) = (0.9, 1.1);
+
+/// Most a geosynchronous orbit's mean motion may differ from one
+/// revolution a sidereal day (revolutions/day)
+const GEOSYNCHRONOUS_TOLERANCE: f64 = 0.01;
+
+/// Most eccentricity of a geostationary orbit
+const GEOSTATIONARY_MAX_ECCENTRICITY: f64 = 0.01;
+
+/// Most inclination of a geostationary orbit
+const GEOSTATIONARY_MAX_INCLINATION: Deg = Deg(1.0);
+
+/// Most a sun synchronous orbit's nodal precession may differ from the
+/// Sun's motion, as a fraction of it
+const SUN_SYNCHRONOUS_TOLERANCE: f64 = 0.05;
+
+/// Most a frozen orbit's argument of perigee may differ from 90°
+const FROZEN_ARGP_TOLERANCE: Deg = Deg(10.0);
+
+/// Most a frozen orbit's eccentricity may differ from the frozen
+/// eccentricity
+const FROZEN_ECCENTRICITY_TOLERANCE: f64 = 2e-4;
+
+
+/// ## Orbit Class
+///
+/// The broad kind of an orbit, by its period and eccentricity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrbitClass {
+
+    /// Low Earth orbit: a period under 128 minutes
+    Leo,
+
+    /// Medium Earth orbit: between low Earth and geosynchronous orbit
+    Meo,
+
+    /// Geosynchronous orbit: between 0.9 and 1.1 revolutions a day
+    Geo,
+
+    /// Beyond geosynchronous orbit: fewer than 0.9 revolutions a day
+    BeyondGeo,
+
+    /// Highly elliptical orbit: an eccentricity of 0.25 or more, at any
+    /// period
+    Heo,
+}
+
+impl fmt::Display for OrbitClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            OrbitClass::Leo => "LEO",
+            OrbitClass::Meo => "MEO",
+            OrbitClass::Geo => "GEO",
+            OrbitClass::BeyondGeo => "beyond GEO",
+            OrbitClass::Heo => "HEO",
+        };
+        f.write_str(name)
+    }
+}
+
+impl TLE {
+
+    /// ## Orbit Class
+    ///
+    /// The broad kind of orbit the element set describes.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// use sgp4::classification::OrbitClass;
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    ///
+    /// assert_eq!(tle.orbit_class(), OrbitClass::Leo);
+    /// assert!(!tle.is_sun_synchronous());
+    /// ```
+    pub fn orbit_class(&self) -> OrbitClass {
+        if self.e >= HEO_MIN_ECCENTRICITY {
+            OrbitClass::Heo
+        } else if self.period_minutes() < LEO_MAX_PERIOD {
+            OrbitClass::Leo
+        } else if self.mean_motion > GEO_MEAN_MOTION.1 {
+            OrbitClass::Meo
+        } else if self.mean_motion >= GEO_MEAN_MOTION.0 {
+            OrbitClass::Geo
+        } else {
+            OrbitClass::BeyondGeo
+        }
+    }
+
+    /// Whether the propagator picks SDP4 for these elements, as
+    /// [`Elements::is_deep_space`](../elements/struct.Elements.html#method.is_deep_space)
+    pub fn is_deep_space(&self) -> bool {
+        Elements::from_tle(self).is_deep_space()
+    }
+
+    /// Whether the orbit goes round once a sidereal day, to within 0.01
+    /// revolutions a day, at any eccentricity and inclination
+    pub fn is_geosynchronous(&self) -> bool {
+        (self.mean_motion - MINUTES_PER_DAY / SIDEREAL_DAY).abs() <= GEOSYNCHRONOUS_TOLERANCE
+    }
+
+    /// Whether the orbit is geosynchronous, circular to within an
+    /// eccentricity of 0.01 and equatorial to within 1°, so the satellite
+    /// hangs over one spot
+    pub fn is_geostationary(&self) -> bool {
+        self.is_geosynchronous()
+            && self.e < GEOSTATIONARY_MAX_ECCENTRICITY
+            && self.i < GEOSTATIONARY_MAX_INCLINATION
+    }
+
+    /// Whether $J_2$ turns the orbit's plane with the Sun, once a tropical
+    /// year, to within 5%. See
+    /// [`sun_synchronous_inclination`](../elements/fn.sun_synchronous_inclination.html).
+    pub fn is_sun_synchronous(&self) -> bool {
+        let sun = 2.0 * PI / (TROPICAL_YEAR * MINUTES_PER_DAY);
+        let rate = raan_rate(self.semi_major_axis_km(), self.e, self.i);
+        (rate.0 / sun - 1.0).abs() <= SUN_SYNCHRONOUS_TOLERANCE
+    }
+
+    /// Whether the orbit is frozen: its perigee within 10° of the northern
+    /// apex and its eccentricity within 0.0002 of the
+    /// [frozen eccentricity](../elements/fn.frozen_eccentricity.html), so
+    /// its shape and orientation hold steady
+    pub fn is_frozen(&self) -> bool {
+        let frozen = frozen_eccentricity(self.semi_major_axis_km(), self.i);
+        (self.omega - Deg(90.0)).abs() <= FROZEN_ARGP_TOLERANCE
+            && (self.e - frozen).abs() <= FROZEN_ECCENTRICITY_TOLERANCE
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::{self, iss};
+
+    fn orbit(mean_motion: f64, eccentricity: f64, inclination: f64, argp: f64) -> TLE {
+        TLE::builder()
+            .epoch(2024, 100.0)
+            .mean_motion(mean_motion)
+            .eccentricity(eccentricity)
+            .inclination(Deg(inclination))
+            .argp(Deg(argp))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn classes() {
+        let geo = test_support::geostationary();
+        let molniya = test_support::molniya();
+
+        assert_eq!(iss().orbit_class(), OrbitClass::Leo);
+        assert_eq!(geo.orbit_class(), OrbitClass::Geo);
+        assert_eq!(molniya.orbit_class(), OrbitClass::Heo);
+
+        // GPS, and a graveyard orbit well above GEO
+        assert_eq!(orbit(2.0057, 0.01, 55.0, 0.0).orbit_class(), OrbitClass::Meo);
+        assert_eq!(orbit(0.8, 0.0, 0.0, 0.0).orbit_class(), OrbitClass::BeyondGeo);
+        assert_eq!(OrbitClass::Heo.to_string(), "HEO");
+
+        assert!(geo.is_geosynchronous() && geo.is_geostationary());
+        let inclined = orbit(1.0027, 0.0002, 8.0, 0.0);
+        assert!(inclined.is_geosynchronous() && !inclined.is_geostationary());
+        assert!(!molniya.is_geosynchronous());
+    }
+
+    #[test]
+    fn sun_synchronous() {
+        // 700 km up
+        let polar = orbit(14.5, 0.001, 98.19, 0.0);
+        assert!(polar.is_sun_synchronous());
+        assert!(!orbit(14.5, 0.001, 97.0, 0.0).is_sun_synchronous());
+        assert!(!iss().is_sun_synchronous());
+    }
+
+    #[test]
+    fn frozen() {
+        let a = orbit(14.5, 0.0, 98.19, 90.0).semi_major_axis_km();
+        let e = frozen_eccentricity(a, Deg(98.19));
+        assert!(e > 8e-4 && e < 1.3e-3, "{}", e);
+
+        assert!(orbit(14.5, e, 98.19, 90.0).is_frozen());
+        assert!(!orbit(14.5, e, 98.19, 270.0).is_frozen());
+        assert!(!orbit(14.5, e + 0.001, 98.19, 90.0).is_frozen());
+        assert!(!iss().is_frozen());
+    }
+}
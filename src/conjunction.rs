@@ -0,0 +1,303 @@
+/*!  # Conjunctions
+
+When two objects come closest to each other, and how close.
+
+Both orbits are propagated on a coarse grid, and wherever the range rate
+between them turns from closing to opening the time is refined by
+bisection. Windows and times are Julian dates (UTC) rather than minutes
+since an epoch, since the two TLEs generally have different epochs.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use coordinates::{dot, StateVector, TEME};
+use tle::TLE;
+use {PropagationError, Propagator, MINUTES_PER_DAY};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// Spacing of the coarse search (minutes)
+pub const DEFAULT_STEP: f64 = 1.0;
+
+/// How closely to locate the time of closest approach (minutes). Julian
+/// dates resolve about 40 microseconds, so there's no point going finer.
+const TOLERANCE: f64 = 1.0e-5;
+
+/// ## Object
+///
+/// A propagator that can be propagated to a Julian date, from its epoch.
+#[derive(Debug, Clone)]
+pub struct Object {
+    propagator: Propagator,
+}
+
+impl Object {
+
+    /// Initialize a TLE for propagation
+    pub fn new(tle: &TLE) -> Result<Object, PropagationError> {
+        Ok(Object::from_propagator(Propagator::new(tle)?))
+    }
+
+    /// Use a propagator already initialized
+    pub fn from_propagator(propagator: Propagator) -> Object {
+        Object { propagator }
+    }
+
+    /// Julian date of the element set epoch
+    pub fn epoch(&self) -> f64 {
+        self.propagator.epoch()
+    }
+
+    /// TEME state at a Julian date
+    pub fn state(&self, julian_date: f64) -> Result<StateVector, PropagationError> {
+        self.propagator.propagate((julian_date - self.propagator.epoch()) * MINUTES_PER_DAY)
+    }
+}
+
+/// ## Approach
+///
+/// Two objects at one instant, usually their closest. Relative vectors are
+/// the second object minus the first, in TEME.
+#[derive(Debug, PartialEq)]
+pub struct Approach {
+
+    /// Julian date (UTC)
+    pub time: f64,
+
+    /// Distance between the objects (kilometers)
+    pub miss_distance: f64,
+
+    /// Relative speed (kilometers/second)
+    pub relative_speed: f64,
+
+    /// Position of the second object relative to the first (kilometers)
+    pub relative_position: TEME,
+
+    /// Velocity of the second object relative to the first
+    /// (kilometers/second)
+    pub relative_velocity: TEME,
+}
+
+/// Relative position and velocity, second object minus first
+fn relative(a: &Object, b: &Object, julian_date: f64) -> Result<([f64; 3], [f64; 3]), PropagationError> {
+    let first = a.state(julian_date)?;
+    let second = b.state(julian_date)?;
+    Ok((
+        [
+            second.position.X - first.position.X,
+            second.position.Y - first.position.Y,
+            second.position.Z - first.position.Z,
+        ],
+        [
+            second.velocity.X - first.velocity.X,
+            second.velocity.Y - first.velocity.Y,
+            second.velocity.Z - first.velocity.Z,
+        ],
+    ))
+}
+
+/// Positive when the objects are moving apart
+fn opening(a: &Object, b: &Object, julian_date: f64) -> Result<bool, PropagationError> {
+    let (position, velocity) = relative(a, b, julian_date)?;
+    Ok(dot(&position, &velocity) >= 0.0)
+}
+
+fn approach(a: &Object, b: &Object, julian_date: f64) -> Result<Approach, PropagationError> {
+    let (position, velocity) = relative(a, b, julian_date)?;
+    Ok(Approach {
+        time: julian_date,
+        miss_distance: dot(&position, &position).sqrt(),
+        relative_speed: dot(&velocity, &velocity).sqrt(),
+        relative_position: TEME::new(position[0], position[1], position[2]),
+        relative_velocity: TEME::new(velocity[0], velocity[1], velocity[2]),
+    })
+}
+
+/// ## Approaches
+///
+/// Every local minimum of the distance between two objects from `start` to
+/// `end` (Julian dates), searching every `step` minutes. Minima closer
+/// together than `step` can be missed.
+///
+/// ### Panics
+///
+/// If `step` isn't positive.
+pub fn approaches(a: &Object, b: &Object, start: f64, end: f64, step: f64) -> Result<Vec<Approach>, PropagationError> {
+    assert!(step > 0.0, "approach search step must be positive");
+
+    let mut found = Vec::new();
+    if end < start {
+        return Ok(found);
+    }
+
+    let step_days = step / MINUTES_PER_DAY;
+    let count = ((end - start) / step_days + 1.0e-9).floor() as usize + 1;
+    let mut times: Vec<f64> = (0..count).map(|index| start + step_days * index as f64).collect();
+    if times.last().is_some_and(|&last| last < end) {
+        times.push(end);
+    }
+
+    let mut previous: Option<(f64, bool)> = None;
+    for time in times {
+        let now_opening = opening(a, b, time)?;
+        if let Some((before, false)) = previous {
+            if now_opening {
+                found.push(approach(a, b, minimum(a, b, before, time)?)?);
+            }
+        }
+        previous = Some((time, now_opening));
+    }
+
+    Ok(found)
+}
+
+/// Where the objects stop closing and start opening between `closing` and
+/// `opening`
+fn minimum(a: &Object, b: &Object, mut closing: f64, mut opens: f64) -> Result<f64, PropagationError> {
+    let tolerance = TOLERANCE / MINUTES_PER_DAY;
+    while opens - closing > tolerance {
+        let middle = 0.5 * (closing + opens);
+        if middle <= closing || middle >= opens {
+            break;
+        }
+        if opening(a, b, middle)? {
+            opens = middle;
+        } else {
+            closing = middle;
+        }
+    }
+    Ok(0.5 * (closing + opens))
+}
+
+/// ## Closest Approach
+///
+/// The closest two TLEs come to each other between `start` and `end`
+/// (Julian dates, UTC). If the objects are still closing at the end of
+/// the window, or already opening at the start, that end is the closest
+/// point.
+///
+/// ### Panics
+///
+/// If `end` is before `start`.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::angle::Deg;
+///
+/// let iss = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // The same orbit turned 5° about the Earth's axis, and far enough
+/// // behind to reach the line where the planes cross at the same time
+/// let mut other = iss.clone();
+/// other.raan += Deg(5.0);
+/// other.mean_anomaly -= Deg(3.1);
+///
+/// let start = iss.epoch_julian_date();
+/// let closest = sgp4::conjunction::closest_approach(&iss, &other, start, start + 1.0).unwrap();
+/// assert!(closest.miss_distance < 1.0);
+/// println!("{:.1} km at {:.5}", closest.miss_distance, closest.time);
+/// ```
+pub fn closest_approach(a: &TLE, b: &TLE, start: f64, end: f64) -> Result<Approach, PropagationError> {
+    assert!(start <= end, "closest approach window ends before it starts");
+
+    let a = Object::new(a)?;
+    let b = Object::new(b)?;
+
+    let mut closest = approach(&a, &b, start)?;
+    let last = approach(&a, &b, end)?;
+    for candidate in approaches(&a, &b, start, end, DEFAULT_STEP)?.into_iter().chain(Some(last)) {
+        if candidate.miss_distance < closest.miss_distance {
+            closest = candidate;
+        }
+    }
+
+    Ok(closest)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use angle::Deg;
+    use test_support::iss;
+
+    /// The ISS orbit turned about the Earth's axis, and phased to meet it
+    /// where the planes cross, twice an orbit
+    fn neighbour() -> TLE {
+        let mut tle = iss();
+        tle.raan += Deg(5.0);
+        tle.mean_anomaly -= Deg(3.1);
+        tle
+    }
+
+    #[test]
+    fn refined_minimum() {
+        let (a, b) = (iss(), neighbour());
+        let start = a.epoch_julian_date();
+        let closest = closest_approach(&a, &b, start, start + 0.1).unwrap();
+
+        // A brute force scan every 6 milliseconds finds nothing closer
+        let (first, second) = (Object::new(&a).unwrap(), Object::new(&b).unwrap());
+        let step = 1.0e-4 / MINUTES_PER_DAY;
+        let mut time = start;
+        while time <= start + 0.1 {
+            let (position, _) = relative(&first, &second, time).unwrap();
+            assert!(dot(&position, &position).sqrt() >= closest.miss_distance - 1e-6);
+            time += step;
+        }
+
+        assert!(closest.miss_distance < 1.0);
+        assert!(closest.relative_speed > 0.4 && closest.relative_speed < 0.7);
+
+        // Perpendicular at closest approach
+        let position = [closest.relative_position.X, closest.relative_position.Y, closest.relative_position.Z];
+        let velocity = [closest.relative_velocity.X, closest.relative_velocity.Y, closest.relative_velocity.Z];
+        assert!(dot(&position, &velocity).abs() / closest.miss_distance < 1e-3);
+    }
+
+    #[test]
+    fn every_crossing() {
+        let (a, b) = (Object::new(&iss()).unwrap(), Object::new(&neighbour()).unwrap());
+        let start = a.epoch();
+
+        let found = approaches(&a, &b, start, start + 1.0, DEFAULT_STEP).unwrap();
+        assert!(found.len() >= 30 && found.len() <= 32);
+        assert!(found.windows(2).all(|pair| pair[0].time < pair[1].time));
+    }
+
+    #[test]
+    fn window_edges() {
+        let (a, b) = (iss(), neighbour());
+        let start = a.epoch_julian_date();
+        let objects = (Object::new(&a).unwrap(), Object::new(&b).unwrap());
+        let crossing = approaches(&objects.0, &objects.1, start, start + 0.1, DEFAULT_STEP).unwrap().remove(0);
+
+        // Closing all the way through the window, then opening all the way
+        let before = closest_approach(&a, &b, crossing.time - 0.003, crossing.time - 0.001).unwrap();
+        assert_eq!(before.time, crossing.time - 0.001);
+        assert!(before.miss_distance > crossing.miss_distance);
+
+        let after = closest_approach(&a, &b, crossing.time + 0.001, crossing.time + 0.003).unwrap();
+        assert_eq!(after.time, crossing.time + 0.001);
+        assert!(after.miss_distance > crossing.miss_distance);
+
+        let same = closest_approach(&a, &a, start, start).unwrap();
+        assert_eq!(same.miss_distance, 0.0);
+    }
+}
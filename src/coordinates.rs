@@ -9,11 +9,105 @@
 
 #![allow(non_snake_case)]
 
-/// ## TEME
+use std::marker::PhantomData;
+use std::ops::{Add, Mul, Neg, Sub};
+
+use eop::Eop;
+use gravity::GravityModel;
+use time;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+/// ## Frames
 ///
-/// **T**rue **E**quator, **M**ean **E**quinox coordinate.
-#[derive(Debug, PartialEq, PartialOrd)]
-pub struct TEME {
+/// Markers for the coordinate frame a [`Vec3`](../struct.Vec3.html) is
+/// in. They have no values; they only keep vectors in different frames
+/// from being mixed up.
+pub mod frame {
+
+    /// A coordinate frame
+    pub trait Frame {
+
+        /// Short name of the frame, such as `TEME`
+        const NAME: &'static str;
+    }
+
+    /// **T**rue **E**quator, **M**ean **E**quinox, the frame SGP4 works in
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    pub enum TEME {}
+
+    /// **E**arth **C**entered, **E**arth **F**ixed
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    pub enum ECEF {}
+
+    /// Mean equator and equinox of J2000
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    pub enum J2000 {}
+
+    /// Topocentric **S**outh, **E**ast, **Z**enith, centered on an
+    /// observer
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    pub enum SEZ {}
+
+    impl Frame for TEME { const NAME: &'static str = "TEME"; }
+    impl Frame for ECEF { const NAME: &'static str = "ECEF"; }
+    impl Frame for J2000 { const NAME: &'static str = "J2000"; }
+    impl Frame for SEZ { const NAME: &'static str = "SEZ"; }
+}
+
+
+/// ## Vec3
+///
+/// A Cartesian vector in the coordinate frame `F`, one of the
+/// [`frame`](frame/index.html) markers. Vectors add, subtract, negate and
+/// scale, and have `dot`, `cross`, `norm` and `distance` methods, but only
+/// with other vectors in the same frame: moving between frames is always an
+/// explicit conversion, such as [`ecef_from_teme`](fn.ecef_from_teme.html).
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let state = sgp4::propagate(&tle, 0.0).unwrap();
+/// let later = sgp4::propagate(&tle, 1.0).unwrap();
+///
+/// // A minute's travel, bent a little by gravity
+/// let moved = later.position - state.position;
+/// let predicted = state.velocity * 60.0;
+/// assert!(moved.distance(&predicted) < 20.0);
+///
+/// // Angular momentum per unit mass
+/// let h = state.position.cross(&state.velocity);
+/// assert!(h.dot(&state.position).abs() < 1e-3);
+/// ```
+///
+/// Mixing frames doesn't compile:
+///
+/// ```compile_fail
+/// extern crate sgp4;
+///
+/// use sgp4::coordinates::{ecef_from_teme, TEME};
+///
+/// let teme = TEME::new(7000.0, 0.0, 0.0);
+/// let ecef = ecef_from_teme(&teme, 1.0);
+/// let wrong = teme + ecef;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct Vec3<F> {
 
     /// $X$
     pub X: f64,
@@ -23,4 +117,986 @@ pub struct TEME {
 
     /// $Z$
     pub Z: f64,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    frame: PhantomData<F>,
+}
+
+impl<F> Vec3<F> {
+
+    /// A vector from its components
+    pub fn new(X: f64, Y: f64, Z: f64) -> Vec3<F> {
+        Vec3 { X, Y, Z, frame: PhantomData }
+    }
+
+    /// Dot product with another vector in the same frame
+    pub fn dot(&self, other: &Vec3<F>) -> f64 {
+        self.X * other.X + self.Y * other.Y + self.Z * other.Z
+    }
+
+    /// Cross product with another vector in the same frame
+    pub fn cross(&self, other: &Vec3<F>) -> Vec3<F> {
+        Vec3::new(
+            self.Y * other.Z - self.Z * other.Y,
+            self.Z * other.X - self.X * other.Z,
+            self.X * other.Y - self.Y * other.X,
+        )
+    }
+
+    /// Length of the vector
+    pub fn norm(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Distance to another point in the same frame
+    pub fn distance(&self, other: &Vec3<F>) -> f64 {
+        let difference: Vec3<F> = Vec3::new(self.X - other.X, self.Y - other.Y, self.Z - other.Z);
+        difference.norm()
+    }
+}
+
+impl<F> Add for Vec3<F> {
+    type Output = Vec3<F>;
+
+    fn add(self, other: Vec3<F>) -> Vec3<F> {
+        Vec3::new(self.X + other.X, self.Y + other.Y, self.Z + other.Z)
+    }
+}
+
+impl<F> Sub for Vec3<F> {
+    type Output = Vec3<F>;
+
+    fn sub(self, other: Vec3<F>) -> Vec3<F> {
+        Vec3::new(self.X - other.X, self.Y - other.Y, self.Z - other.Z)
+    }
+}
+
+impl<F> Neg for Vec3<F> {
+    type Output = Vec3<F>;
+
+    fn neg(self) -> Vec3<F> {
+        Vec3::new(-self.X, -self.Y, -self.Z)
+    }
+}
+
+impl<F> Mul<f64> for Vec3<F> {
+    type Output = Vec3<F>;
+
+    fn mul(self, scale: f64) -> Vec3<F> {
+        Vec3::new(self.X * scale, self.Y * scale, self.Z * scale)
+    }
+}
+
+impl<F> Mul<Vec3<F>> for f64 {
+    type Output = Vec3<F>;
+
+    fn mul(self, vector: Vec3<F>) -> Vec3<F> {
+        vector * self
+    }
+}
+
+
+/// ## TEME
+///
+/// **T**rue **E**quator, **M**ean **E**quinox coordinate.
+pub type TEME = Vec3<frame::TEME>;
+
+
+/// ## State Vector
+///
+/// Position and velocity of an object at one instant, in the frame `F`.
+/// This is what [`propagate`](../fn.propagate.html) returns, in TEME, and
+/// what everything that works on a propagated orbit takes.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let state = sgp4::propagate(&tle, 60.0).unwrap();
+/// assert_eq!(state.epoch, tle.epoch_julian_date() + 60.0 / sgp4::MINUTES_PER_DAY);
+/// assert_eq!(state.frame(), "TEME");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct StateVector<F = frame::TEME> {
+
+    /// When (Julian date, UTC)
+    pub epoch: f64,
+
+    /// Position (kilometers)
+    pub position: Vec3<F>,
+
+    /// Velocity (kilometers/second)
+    pub velocity: Vec3<F>,
+}
+
+impl<F: frame::Frame> StateVector<F> {
+
+    /// Name of the frame the state is in
+    pub fn frame(&self) -> &'static str {
+        F::NAME
+    }
+}
+
+impl<F> StateVector<F> {
+
+    /// ## In Units
+    ///
+    /// The state, in kilometers and kilometers/second as returned by the
+    /// propagator, converted to other `units`. Earth radii are those of the
+    /// `gravity` model the state was propagated with.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// use sgp4::coordinates::Units;
+    /// use sgp4::gravity::GravityModel;
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    ///
+    /// let state = sgp4::propagate(&tle, 0.0).unwrap();
+    /// let meters = state.in_units(Units::Meters, &GravityModel::default());
+    /// assert_eq!(meters.position.X, state.position.X * 1000.0);
+    /// ```
+    pub fn in_units(&self, units: Units, gravity: &GravityModel) -> StateVector<F> {
+        let (distance, velocity) = match units {
+            Units::EarthRadii => (1.0 / gravity.radius, 60.0 / gravity.radius),
+            Units::Kilometers => (1.0, 1.0),
+            Units::Meters => (1000.0, 1000.0),
+        };
+        StateVector {
+            epoch: self.epoch,
+            position: Vec3::new(self.position.X * distance, self.position.Y * distance, self.position.Z * distance),
+            velocity: Vec3::new(self.velocity.X * velocity, self.velocity.Y * velocity, self.velocity.Z * velocity),
+        }
+    }
+}
+
+
+/// ## Units
+///
+/// Units of distance and speed for a state vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Units {
+
+    /// Earth radii and Earth radii/minute, as SGP4 works internally
+    EarthRadii,
+
+    /// Kilometers and kilometers/second, as the propagator returns
+    Kilometers,
+
+    /// Meters and meters/second
+    Meters,
+}
+
+
+/// ## ECEF
+///
+/// **E**arth **C**entered, **E**arth **F**ixed coordinate. The $Z$ axis
+/// points through the north pole and the $X$ axis through the Greenwich
+/// meridian, and the frame rotates with the Earth.
+pub type ECEF = Vec3<frame::ECEF>;
+
+
+/// ## ECEF From TEME
+///
+/// Rotate a TEME position into the Earth fixed frame, given the Greenwich
+/// mean sidereal time (radians, see [`time::gmst`](../time/fn.gmst.html)).
+/// Polar motion is neglected, so strictly this is the pseudo Earth fixed
+/// frame, which differs from ITRF by at most a few tens of meters; see
+/// [`itrf_from_teme`](fn.itrf_from_teme.html) to correct for it.
+///
+/// $$\begin{bmatrix}X\\\\Y\\\\Z\end{bmatrix}_{ECEF} = \begin{bmatrix}\cos\theta & \sin\theta & 0\\\\-\sin\theta & \cos\theta & 0\\\\0 & 0 & 1\end{bmatrix}\begin{bmatrix}X\\\\Y\\\\Z\end{bmatrix}_{TEME}$$
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let state = sgp4::propagate(&tle, 0.0).unwrap();
+/// let gmst = sgp4::time::gmst(tle.epoch_julian_date());
+/// let ecef = sgp4::coordinates::ecef_from_teme(&state.position, gmst);
+/// ```
+pub fn ecef_from_teme(teme: &TEME, gmst: f64) -> ECEF {
+    let (sin_theta, cos_theta) = gmst.sin_cos();
+
+    ECEF::new(
+        cos_theta * teme.X + sin_theta * teme.Y,
+        -sin_theta * teme.X + cos_theta * teme.Y,
+        teme.Z,
+    )
+}
+
+
+/// ## ITRF From PEF
+///
+/// Apply polar motion to a pseudo Earth fixed position (as returned by
+/// [`ecef_from_teme`](fn.ecef_from_teme.html)), giving the position in the
+/// International Terrestrial Reference Frame. (Vallado, *Fundamentals of
+/// Astrodynamics and Applications*, 4th ed., eq. 3-77)
+///
+/// $$r_{ITRF} = \begin{bmatrix}\cos x_p & \sin x_p\sin y_p & \sin x_p\cos y_p\\\\0 & \cos y_p & -\sin y_p\\\\-\sin x_p & \cos x_p\sin y_p & \cos x_p\cos y_p\end{bmatrix} r_{PEF}$$
+pub fn itrf_from_pef(pef: &ECEF, eop: &Eop) -> ECEF {
+    let (sin_xp, cos_xp) = eop.xp.sin_cos();
+    let (sin_yp, cos_yp) = eop.yp.sin_cos();
+
+    ECEF::new(
+        cos_xp * pef.X + sin_xp * sin_yp * pef.Y + sin_xp * cos_yp * pef.Z,
+        cos_yp * pef.Y - sin_yp * pef.Z,
+        -sin_xp * pef.X + cos_xp * sin_yp * pef.Y + cos_xp * cos_yp * pef.Z,
+    )
+}
+
+/// ## ITRF From TEME
+///
+/// Rotate a TEME position into the Earth fixed frame at a Julian date
+/// (UTC), with the sidereal time from UT1 and polar motion both taken from
+/// Earth orientation parameters (see the [`eop`](../eop/index.html)
+/// module).
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::coordinates::{geodetic_from_ecef, itrf_from_teme};
+/// use sgp4::eop::Eop;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let state = sgp4::propagate(&tle, 0.0).unwrap();
+/// let eop = Eop::from_arcseconds(0.186180, 0.447357, -0.0461858);
+/// let geodetic = geodetic_from_ecef(&itrf_from_teme(&state.position, state.epoch, &eop));
+/// ```
+pub fn itrf_from_teme(teme: &TEME, julian_date_utc: f64, eop: &Eop) -> ECEF {
+    let pef = ecef_from_teme(teme, time::gmst(eop.ut1(julian_date_utc)));
+    itrf_from_pef(&pef, eop)
+}
+
+
+/// WGS84 equatorial radius of the Earth (kilometers)
+pub const WGS84_EQUATORIAL_RADIUS: f64 = 6378.137;
+
+/// WGS84 flattening of the Earth
+pub const WGS84_FLATTENING: f64 = 1.0 / 298.257223563;
+
+/// Rotation rate of the Earth (radians/second), consistent with the IAU-82
+/// sidereal time model
+pub const EARTH_ROTATION_RATE: f64 = 7.292115146706979e-5;
+
+/// Convergence tolerance (radians) for the geodetic latitude iteration
+const LATITUDE_TOLERANCE: f64 = 1e-12;
+
+/// Maximum iterations for the geodetic latitude
+const LATITUDE_ITERATIONS: usize = 10;
+
+
+/// ## Geodetic
+///
+/// Position relative to an ellipsoid: WGS84, unless converted with the
+/// `_with_gravity_model` functions.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Geodetic {
+
+    /// Geodetic latitude (radians, north positive)
+    pub lat: f64,
+
+    /// Longitude (radians, east positive, $-\pi$ to $\pi$)
+    pub lon: f64,
+
+    /// Height above the ellipsoid (kilometers)
+    pub alt: f64,
+}
+
+
+/// ## Geodetic From ECEF
+///
+/// Latitude, longitude and altitude above the WGS84 ellipsoid of an Earth
+/// fixed position. The latitude is found by fixed point iteration, which
+/// converges to well under a millimeter in a few steps for any point above
+/// the Earth's surface.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::coordinates::{geodetic_from_ecef, ECEF};
+///
+/// let ecef = ECEF::new(6524.834, 6862.875, 6448.296);
+/// let geodetic = geodetic_from_ecef(&ecef);
+///
+/// assert!((geodetic.lat.to_degrees() - 34.352496).abs() < 1e-6);
+/// assert!((geodetic.lon.to_degrees() - 46.446416).abs() < 1e-6);
+/// ```
+pub fn geodetic_from_ecef(ecef: &ECEF) -> Geodetic {
+    geodetic_from_ecef_with_gravity_model(ecef, &GravityModel::WGS84)
+}
+
+/// ## Geodetic From ECEF With Gravity Model
+///
+/// As [`geodetic_from_ecef`](fn.geodetic_from_ecef.html), above the
+/// ellipsoid of `gravity`'s radius and flattening, to match a propagator
+/// using the same constants.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::coordinates::{ecef_from_teme, geodetic_from_ecef_with_gravity_model};
+/// use sgp4::gravity::GravityModel;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let propagator = sgp4::Propagator::with_gravity_model(&tle, GravityModel::WGS72).unwrap();
+/// let state = propagator.propagate(0.0).unwrap();
+/// let ecef = ecef_from_teme(&state.position, sgp4::time::gmst(tle.epoch_julian_date()));
+/// let geodetic = geodetic_from_ecef_with_gravity_model(&ecef, propagator.gravity_model());
+/// ```
+pub fn geodetic_from_ecef_with_gravity_model(ecef: &ECEF, gravity: &GravityModel) -> Geodetic {
+    let a = gravity.radius;
+    let e2 = gravity.eccentricity_squared();
+
+    let lon = ecef.Y.atan2(ecef.X);
+    let p = (ecef.X * ecef.X + ecef.Y * ecef.Y).sqrt();
+
+    // Start from the geocentric latitude and iterate
+    let mut lat = ecef.Z.atan2(p * (1.0 - e2));
+    for _ in 0..LATITUDE_ITERATIONS {
+        let sin_lat = lat.sin();
+        let N = a / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+        let next = (ecef.Z + e2 * N * sin_lat).atan2(p);
+        let converged = (next - lat).abs() < LATITUDE_TOLERANCE;
+        lat = next;
+        if converged {
+            break;
+        }
+    }
+
+    // Height without dividing by cos(lat), so it holds at the poles too
+    let (sin_lat, cos_lat) = lat.sin_cos();
+    let alt = p * cos_lat + ecef.Z * sin_lat - a * (1.0 - e2 * sin_lat * sin_lat).sqrt();
+
+    Geodetic { lat, lon, alt }
+}
+
+
+/// ## ECEF From Geodetic
+///
+/// Earth fixed position of a point given relative to the WGS84 ellipsoid.
+pub fn ecef_from_geodetic(geodetic: &Geodetic) -> ECEF {
+    ecef_from_geodetic_with_gravity_model(geodetic, &GravityModel::WGS84)
+}
+
+/// ## ECEF From Geodetic With Gravity Model
+///
+/// Earth fixed position of a point given relative to the ellipsoid of
+/// `gravity`'s radius and flattening.
+pub fn ecef_from_geodetic_with_gravity_model(geodetic: &Geodetic, gravity: &GravityModel) -> ECEF {
+    let e2 = gravity.eccentricity_squared();
+
+    let (sin_lat, cos_lat) = geodetic.lat.sin_cos();
+    let (sin_lon, cos_lon) = geodetic.lon.sin_cos();
+
+    // Radius of curvature in the prime vertical
+    let N = gravity.radius / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+
+    ECEF::new(
+        (N + geodetic.alt) * cos_lat * cos_lon,
+        (N + geodetic.alt) * cos_lat * sin_lon,
+        (N * (1.0 - e2) + geodetic.alt) * sin_lat,
+    )
+}
+
+
+/// ## SEZ
+///
+/// Topocentric **S**outh, **E**ast, **Z**enith coordinate, centered on an
+/// observer on the ground.
+pub type SEZ = Vec3<frame::SEZ>;
+
+
+/// ## SEZ From ECEF
+///
+/// Position of an Earth fixed point as seen from `site`, in the site's
+/// south, east, zenith frame.
+///
+/// $$\begin{bmatrix}S\\\\E\\\\Z\end{bmatrix} = \begin{bmatrix}\sin\phi\cos\lambda & \sin\phi\sin\lambda & -\cos\phi\\\\-\sin\lambda & \cos\lambda & 0\\\\\cos\phi\cos\lambda & \cos\phi\sin\lambda & \sin\phi\end{bmatrix}\left(r_{ECEF} - r_{site}\right)$$
+pub fn sez_from_ecef(ecef: &ECEF, site: &Geodetic) -> SEZ {
+    sez_rotation(&(*ecef - ecef_from_geodetic(site)), site)
+}
+
+/// Rotate an Earth fixed vector into the south, east, zenith axes at `site`
+pub(crate) fn sez_rotation(vector: &ECEF, site: &Geodetic) -> SEZ {
+    let (sin_lat, cos_lat) = site.lat.sin_cos();
+    let (sin_lon, cos_lon) = site.lon.sin_cos();
+
+    SEZ::new(
+        sin_lat * cos_lon * vector.X + sin_lat * sin_lon * vector.Y - cos_lat * vector.Z,
+        -sin_lon * vector.X + cos_lon * vector.Y,
+        cos_lat * cos_lon * vector.X + cos_lat * sin_lon * vector.Y + sin_lat * vector.Z,
+    )
+}
+
+
+/// ## J2000
+///
+/// Coordinate in the J2000 frame: the mean equator and mean equinox of
+/// 2000 January 1, 12:00 TT.
+pub type J2000 = Vec3<frame::J2000>;
+
+
+/// A 3×3 rotation matrix
+type Matrix = [[f64; 3]; 3];
+
+/// Arcseconds to radians
+const ARCSECOND: f64 = std::f64::consts::PI / (180.0 * 3600.0);
+
+/// The IAU 1980 nutation series, truncated to the terms with amplitudes
+/// above 0.0005 arcseconds. Each row is the multiples of the fundamental
+/// arguments $(l, l', F, D, \Omega)$, then the longitude coefficients
+/// $A + BT$ and the obliquity coefficients $C + DT$ (units of 0.0001
+/// arcseconds).
+const NUTATION_1980: [([i8; 5], f64, f64, f64, f64); 45] = [
+    ([ 0,  0,  0,  0,  1], -171996.0, -174.2, 92025.0,  8.9),
+    ([ 0,  0,  2, -2,  2],  -13187.0,   -1.6,  5736.0, -3.1),
+    ([ 0,  0,  2,  0,  2],   -2274.0,   -0.2,   977.0, -0.5),
+    ([ 0,  0,  0,  0,  2],    2062.0,    0.2,  -895.0,  0.5),
+    ([ 0,  1,  0,  0,  0],    1426.0,   -3.4,    54.0, -0.1),
+    ([ 1,  0,  0,  0,  0],     712.0,    0.1,    -7.0,  0.0),
+    ([ 0,  1,  2, -2,  2],    -517.0,    1.2,   224.0, -0.6),
+    ([ 0,  0,  2,  0,  1],    -386.0,   -0.4,   200.0,  0.0),
+    ([ 1,  0,  2,  0,  2],    -301.0,    0.0,   129.0, -0.1),
+    ([ 0, -1,  2, -2,  2],     217.0,   -0.5,   -95.0,  0.3),
+    ([ 1,  0,  0, -2,  0],    -158.0,    0.0,    -1.0,  0.0),
+    ([ 0,  0,  2, -2,  1],     129.0,    0.1,   -70.0,  0.0),
+    ([-1,  0,  2,  0,  2],     123.0,    0.0,   -53.0,  0.0),
+    ([ 1,  0,  0,  0,  1],      63.0,    0.1,   -33.0,  0.0),
+    ([ 0,  0,  0,  2,  0],      63.0,    0.0,    -2.0,  0.0),
+    ([-1,  0,  2,  2,  2],     -59.0,    0.0,    26.0,  0.0),
+    ([-1,  0,  0,  0,  1],     -58.0,   -0.1,    32.0,  0.0),
+    ([ 1,  0,  2,  0,  1],     -51.0,    0.0,    27.0,  0.0),
+    ([ 2,  0,  0, -2,  0],      48.0,    0.0,     1.0,  0.0),
+    ([-2,  0,  2,  0,  1],      46.0,    0.0,   -24.0,  0.0),
+    ([ 0,  0,  2,  2,  2],     -38.0,    0.0,    16.0,  0.0),
+    ([ 2,  0,  2,  0,  2],     -31.0,    0.0,    13.0,  0.0),
+    ([ 2,  0,  0,  0,  0],      29.0,    0.0,    -1.0,  0.0),
+    ([ 1,  0,  2, -2,  2],      29.0,    0.0,   -12.0,  0.0),
+    ([ 0,  0,  2,  0,  0],      26.0,    0.0,    -1.0,  0.0),
+    ([ 0,  0,  2, -2,  0],     -22.0,    0.0,     0.0,  0.0),
+    ([-1,  0,  2,  0,  1],      21.0,    0.0,   -10.0,  0.0),
+    ([ 0,  2,  0,  0,  0],      17.0,   -0.1,     0.0,  0.0),
+    ([ 0,  2,  2, -2,  2],     -16.0,    0.1,     7.0,  0.0),
+    ([-1,  0,  0,  2,  1],      16.0,    0.0,    -8.0,  0.0),
+    ([ 0,  1,  0,  0,  1],     -15.0,    0.0,     9.0,  0.0),
+    ([ 1,  0,  0, -2,  1],     -13.0,    0.0,     7.0,  0.0),
+    ([ 0, -1,  0,  0,  1],     -12.0,    0.0,     6.0,  0.0),
+    ([ 2,  0, -2,  0,  0],      11.0,    0.0,     0.0,  0.0),
+    ([-1,  0,  2,  2,  1],     -10.0,    0.0,     5.0,  0.0),
+    ([ 1,  0,  2,  2,  2],      -8.0,    0.0,     3.0,  0.0),
+    ([ 0, -1,  2,  0,  2],      -7.0,    0.0,     3.0,  0.0),
+    ([ 0,  0,  2,  2,  1],      -7.0,    0.0,     3.0,  0.0),
+    ([ 1,  1,  0, -2,  0],      -7.0,    0.0,     0.0,  0.0),
+    ([ 0,  1,  2,  0,  2],       7.0,    0.0,    -3.0,  0.0),
+    ([-2,  0,  0,  2,  1],      -6.0,    0.0,     3.0,  0.0),
+    ([ 0,  0,  0,  2,  1],      -6.0,    0.0,     3.0,  0.0),
+    ([ 2,  0,  2, -2,  2],       6.0,    0.0,    -3.0,  0.0),
+    ([ 1,  0,  0,  2,  0],       6.0,    0.0,     0.0,  0.0),
+    ([ 1,  0,  2, -2,  1],       6.0,    0.0,    -3.0,  0.0),
+];
+
+/// Rotation of the coordinate frame by `angle` about the $X$ axis
+fn rotation_x(angle: f64) -> Matrix {
+    let (s, c) = angle.sin_cos();
+    [[1.0, 0.0, 0.0], [0.0, c, s], [0.0, -s, c]]
+}
+
+/// Rotation of the coordinate frame by `angle` about the $Y$ axis
+fn rotation_y(angle: f64) -> Matrix {
+    let (s, c) = angle.sin_cos();
+    [[c, 0.0, -s], [0.0, 1.0, 0.0], [s, 0.0, c]]
+}
+
+/// Rotation of the coordinate frame by `angle` about the $Z$ axis
+fn rotation_z(angle: f64) -> Matrix {
+    let (s, c) = angle.sin_cos();
+    [[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]]
+}
+
+/// Matrix product $AB$
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut product = [[0.0; 3]; 3];
+    for (i, row) in product.iter_mut().enumerate() {
+        for (j, element) in row.iter_mut().enumerate() {
+            *element = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    product
+}
+
+/// Matrix product $A\^TB$
+fn multiply_transpose(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut product = [[0.0; 3]; 3];
+    for (i, row) in product.iter_mut().enumerate() {
+        for (j, element) in row.iter_mut().enumerate() {
+            *element = (0..3).map(|k| a[k][i] * b[k][j]).sum();
+        }
+    }
+    product
+}
+
+/// Rotation from the TEME frame to J2000 at a time $T$ in Julian centuries
+/// of TT since J2000.0
+fn teme_to_j2000(T: f64) -> Matrix {
+    // IAU 1976 precession angles (arcseconds)
+    let zeta = (2306.2181 + (0.30188 + 0.017998 * T) * T) * T;
+    let theta = (2004.3109 - (0.42665 + 0.041833 * T) * T) * T;
+    let z = (2306.2181 + (1.09468 + 0.018203 * T) * T) * T;
+
+    // J2000 to mean of date
+    let precession = multiply(&multiply(&rotation_z(-z * ARCSECOND), &rotation_y(theta * ARCSECOND)), &rotation_z(-zeta * ARCSECOND));
+
+    // IAU 1980 fundamental arguments of the Moon and Sun (degrees)
+    let l = 134.96298139 + (((0.064 * T + 31.310) * T + 1717915922.6330) * T) / 3600.0;
+    let l_ = 357.52772333 + (((-0.012 * T - 0.577) * T + 129596581.2240) * T) / 3600.0;
+    let F = 93.27191028 + (((0.011 * T - 13.257) * T + 1739527263.1370) * T) / 3600.0;
+    let D = 297.85036306 + (((0.019 * T - 6.891) * T + 1602961601.3280) * T) / 3600.0;
+    let Omega = 125.04452222 + (((0.008 * T + 7.455) * T - 6962890.5390) * T) / 3600.0;
+    let arguments = [l.to_radians(), l_.to_radians(), F.to_radians(), D.to_radians(), Omega.to_radians()];
+
+    // Nutation in longitude and obliquity
+    let (mut delta_psi, mut delta_epsilon) = (0.0, 0.0);
+    for &(multiples, A, B, C, D) in NUTATION_1980.iter() {
+        let angle: f64 = multiples.iter().zip(arguments.iter()).map(|(&m, &a)| f64::from(m) * a).sum();
+        delta_psi += (A + B * T) * angle.sin();
+        delta_epsilon += (C + D * T) * angle.cos();
+    }
+    let delta_psi = delta_psi * 1e-4 * ARCSECOND;
+    let delta_epsilon = delta_epsilon * 1e-4 * ARCSECOND;
+
+    // IAU 1976 mean obliquity of the ecliptic
+    let epsilon = (84381.448 - (46.8150 + (0.00059 - 0.001813 * T) * T) * T) * ARCSECOND;
+
+    // Mean of date to true of date
+    let nutation = multiply(&multiply(&rotation_x(-(epsilon + delta_epsilon)), &rotation_z(-delta_psi)), &rotation_x(epsilon));
+
+    // TEME differs from true of date by the equation of the equinoxes
+    let equinox = rotation_z(-delta_psi * epsilon.cos());
+
+    // TEME -> true of date -> mean of date -> J2000
+    multiply_transpose(&precession, &multiply_transpose(&nutation, &equinox))
+}
+
+
+/// ## J2000 From TEME
+///
+/// Rotate a TEME vector into the J2000 frame using the IAU-76/FK5 precession
+/// and nutation theory, at a Julian date in the TT time scale. Positions and
+/// velocities are both converted with this rotation (the frame itself turns
+/// far too slowly to matter).
+///
+/// The time scale hardly matters at SGP4's accuracy: using a UTC Julian date
+/// instead of TT moves the result by well under a millimeter. For TT, see
+/// [`timescale::tt_from_utc`](../timescale/fn.tt_from_utc.html).
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let state = sgp4::propagate(&tle, 0.0).unwrap();
+/// let jd = tle.epoch_julian_date();
+/// let position = sgp4::coordinates::j2000_from_teme(&state.position, jd);
+/// let velocity = sgp4::coordinates::j2000_from_teme(&state.velocity, jd);
+/// ```
+pub fn j2000_from_teme(teme: &TEME, julian_date_tt: f64) -> J2000 {
+    let m = teme_to_j2000((julian_date_tt - time::J2000) / 36525.0);
+
+    J2000::new(
+        m[0][0] * teme.X + m[0][1] * teme.Y + m[0][2] * teme.Z,
+        m[1][0] * teme.X + m[1][1] * teme.Y + m[1][2] * teme.Z,
+        m[2][0] * teme.X + m[2][1] * teme.Y + m[2][2] * teme.Z,
+    )
+}
+
+
+/// ## RIC
+///
+/// Coordinate in a **R**adial, **I**n-track, **C**ross-track frame centered
+/// on a reference object. $R$ points away from the Earth's center, $C$ along
+/// the orbit normal, and $I$ completes the right handed set (along the
+/// velocity for a circular orbit).
+#[derive(Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RIC {
+
+    /// Radial
+    pub R: f64,
+
+    /// In-track
+    pub I: f64,
+
+    /// Cross-track
+    pub C: f64,
+}
+
+
+/// ## Relative State
+///
+/// Position and velocity of an object relative to a reference object.
+#[derive(Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RelativeState {
+
+    /// Position (kilometers)
+    pub position: RIC,
+
+    /// Velocity in the rotating frame (kilometers/second)
+    pub velocity: RIC,
+}
+
+
+/// Components of a TEME coordinate
+fn components(teme: &TEME) -> [f64; 3] {
+    [teme.X, teme.Y, teme.Z]
+}
+
+/// Dot product $a \cdot b$
+pub(crate) fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Cross product $a \times b$
+pub(crate) fn cross(a: &[f64; 3], b: &[f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Unit vector in the direction of `a`
+pub(crate) fn unit(a: &[f64; 3]) -> [f64; 3] {
+    let norm = dot(a, a).sqrt();
+    [a[0] / norm, a[1] / norm, a[2] / norm]
+}
+
+
+/// ## Relative RIC
+///
+/// Express `other` relative to `reference` in the reference object's
+/// radial, in-track, cross-track frame. Both states must be TEME states at
+/// the same instant, e.g. from propagating two TLEs to the same time.
+///
+/// The relative velocity is taken in the rotating frame, so an object
+/// sharing the reference's circular orbit has zero relative velocity:
+///
+/// $$\dot{\rho} = \Delta v - \omega \times \Delta r, \quad \omega = \frac{r \times v}{|r|\^2}$$
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // Where the ISS is a minute from now, relative to where it is now
+/// let now = sgp4::propagate(&tle, 0.0).unwrap();
+/// let later = sgp4::propagate(&tle, 1.0).unwrap();
+/// let relative = sgp4::coordinates::relative_ric(&now, &later);
+///
+/// assert!(relative.position.I > 400.0);
+/// ```
+pub fn relative_ric(reference: &StateVector, other: &StateVector) -> RelativeState {
+    let r = components(&reference.position);
+    let v = components(&reference.velocity);
+
+    // Frame axes
+    let h = cross(&r, &v);
+    let R = unit(&r);
+    let C = unit(&h);
+    let I = cross(&C, &R);
+
+    // Angular velocity of the frame
+    let r2 = dot(&r, &r);
+    let omega = [h[0] / r2, h[1] / r2, h[2] / r2];
+
+    let other_r = components(&other.position);
+    let other_v = components(&other.velocity);
+    let dr = [other_r[0] - r[0], other_r[1] - r[1], other_r[2] - r[2]];
+    let rotation = cross(&omega, &dr);
+    let dv = [
+        other_v[0] - v[0] - rotation[0],
+        other_v[1] - v[1] - rotation[1],
+        other_v[2] - v[2] - rotation[2],
+    ];
+
+    RelativeState {
+        position: RIC { R: dot(&dr, &R), I: dot(&dr, &I), C: dot(&dr, &C) },
+        velocity: RIC { R: dot(&dv, &R), I: dot(&dv, &I), C: dot(&dv, &C) },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vector_operations() {
+        let a = TEME::new(1.0, 2.0, 3.0);
+        let b = TEME::new(-2.0, 0.5, 4.0);
+
+        assert_eq!(a + b, TEME::new(-1.0, 2.5, 7.0));
+        assert_eq!(a - b, TEME::new(3.0, 1.5, -1.0));
+        assert_eq!(-a, TEME::new(-1.0, -2.0, -3.0));
+        assert_eq!(a * 2.0, 2.0 * a);
+        assert_eq!(a * 2.0, TEME::new(2.0, 4.0, 6.0));
+        assert_eq!(a.dot(&b), 11.0);
+
+        // Same as the array helpers, and perpendicular to both
+        let c = a.cross(&b);
+        assert_eq!(components(&c), cross(&components(&a), &components(&b)));
+        assert_eq!(c.dot(&a), 0.0);
+        assert_eq!(c.dot(&b), 0.0);
+
+        let x = ECEF::new(3.0, 0.0, 0.0);
+        let y = ECEF::new(0.0, 4.0, 0.0);
+        assert_eq!(x.norm(), 3.0);
+        assert_eq!(x.distance(&y), 5.0);
+        assert_eq!(J2000::new(0.0, 0.0, 2.0).norm(), 2.0);
+    }
+
+    #[test]
+    fn sez_from_ecef_axes() {
+        let site = Geodetic { lat: 0.75, lon: -2.1, alt: 0.1 };
+        let up = Geodetic { alt: 500.0, ..site };
+        let north = Geodetic { lat: site.lat + 0.001, ..site };
+        let east = Geodetic { lon: site.lon + 0.001, ..site };
+
+        let overhead = sez_from_ecef(&ecef_from_geodetic(&up), &site);
+        assert!(overhead.X.abs() < 1e-9 && overhead.Y.abs() < 1e-9);
+        assert!((overhead.Z - 499.9).abs() < 1e-9);
+
+        // Neighbours on the ground are nearly level
+        let north = sez_from_ecef(&ecef_from_geodetic(&north), &site);
+        assert!(north.X < -6.0 && north.Y.abs() < 1e-9 && north.Z.abs() < 0.01);
+        let east = sez_from_ecef(&ecef_from_geodetic(&east), &site);
+        assert!(east.Y > 4.0 && east.X.abs() < 0.01 && east.Z.abs() < 0.01);
+    }
+
+    #[test]
+    fn ecef_from_teme_rotation() {
+        let teme = TEME::new(1.0, 2.0, 3.0);
+
+        assert_eq!(ecef_from_teme(&teme, 0.0), ECEF::new(1.0, 2.0, 3.0));
+
+        let ecef = ecef_from_teme(&teme, std::f64::consts::FRAC_PI_2);
+        assert!((ecef.X - 2.0).abs() < 1e-12);
+        assert!((ecef.Y + 1.0).abs() < 1e-12);
+        assert_eq!(ecef.Z, 3.0);
+    }
+
+    #[test]
+    fn vallado_example_3_15() {
+        // 2004 April 6, 07:51:28.386009 UTC, with UT1 - UTC = -0.4399619 s
+        let seconds = 7.0 * 3600.0 + 51.0 * 60.0 + 28.386009 - 0.4399619;
+        let gmst = time::gmst(time::julian_date(2004, 4, 6.0 + seconds / 86400.0));
+
+        let teme = TEME::new(5094.18016210, 6127.64465950, 6380.34453270);
+        let pef = ecef_from_teme(&teme, gmst);
+
+        assert!((pef.X - -1033.47503130).abs() < 1e-4);
+        assert!((pef.Y - 7901.30558560).abs() < 1e-4);
+        assert!((pef.Z - 6380.34453270).abs() < 1e-4);
+
+        // Then polar motion
+        let eop = Eop::from_arcseconds(-0.140682, 0.333309, -0.4399619);
+        let utc = time::julian_date(2004, 4, 6.0 + (seconds + 0.4399619) / 86400.0);
+        let itrf = itrf_from_teme(&teme, utc, &eop);
+        assert!((itrf.X - -1033.4793830).abs() < 1e-4);
+        assert!((itrf.Y - 7901.2952754).abs() < 1e-4);
+        assert!((itrf.Z - 6380.3565958).abs() < 1e-4);
+        assert_eq!(itrf_from_pef(&pef, &eop), itrf);
+    }
+
+    #[test]
+    fn vallado_example_3_3() {
+        let geodetic = geodetic_from_ecef(&ECEF::new(6524.834, 6862.875, 6448.296));
+
+        assert!((geodetic.lat.to_degrees() - 34.352496).abs() < 1e-6);
+        assert!((geodetic.lon.to_degrees() - 46.446416).abs() < 1e-6);
+        assert!((geodetic.alt - 5085.22).abs() < 1e-2);
+    }
+
+    #[test]
+    fn geodetic_round_trip() {
+        let sites = [
+            (0.0, 0.0, 0.0),
+            (51.4779, -0.0015, 0.046),
+            (-33.8688, 151.2093, 0.058),
+            (90.0, 0.0, 400.0),
+            (-89.9999, 123.0, 35786.0),
+        ];
+
+        for &(lat, lon, alt) in sites.iter() {
+            let site = Geodetic { lat: f64::to_radians(lat), lon: f64::to_radians(lon), alt };
+            let geodetic = geodetic_from_ecef(&ecef_from_geodetic(&site));
+
+            assert!((geodetic.lat - site.lat).abs() < 1e-10);
+            assert!((geodetic.alt - site.alt).abs() < 1e-6);
+            if lat.abs() < 90.0 {
+                assert!((geodetic.lon - site.lon).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn geodetic_gravity_models() {
+        let wgs84 = GravityModel::WGS84;
+        assert_eq!(wgs84.radius, WGS84_EQUATORIAL_RADIUS);
+        assert_eq!(wgs84.flattening, WGS84_FLATTENING);
+
+        // Each model round trips on its own ellipsoid, and the surfaces are
+        // two meters apart at the equator
+        let site = Geodetic { lat: 0.0, lon: f64::to_radians(30.0), alt: 0.0 };
+        let wgs72 = GravityModel::WGS72;
+        let ecef = ecef_from_geodetic_with_gravity_model(&site, &wgs72);
+        assert!((ecef.norm() - 6378.135).abs() < 1e-9);
+        let geodetic = geodetic_from_ecef_with_gravity_model(&ecef, &wgs72);
+        assert!(geodetic.alt.abs() < 1e-9);
+        assert!((geodetic_from_ecef(&ecef).alt + 0.002).abs() < 1e-9);
+
+        // At the pole the difference is in the flattening too
+        let pole = Geodetic { lat: f64::to_radians(90.0), lon: 0.0, alt: 0.0 };
+        let z72 = ecef_from_geodetic_with_gravity_model(&pole, &wgs72).Z;
+        let z84 = ecef_from_geodetic(&pole).Z;
+        assert!((z72 - 6378.135 * (1.0 - 1.0 / 298.26)).abs() < 1e-9);
+        assert!((z84 - 6356.752314245).abs() < 1e-6);
+    }
+
+    #[test]
+    fn vallado_teme_to_j2000() {
+        // Vallado et al., "Revisiting Spacetrack Report #3", 2006
+        // 2004 April 6, 07:51:28.386009 UTC, TT = UTC + 64.184 s
+        let seconds = 7.0 * 3600.0 + 51.0 * 60.0 + 28.386009 + 64.184;
+        let jd_tt = time::julian_date(2004, 4, 6.0 + seconds / 86400.0);
+
+        let teme = TEME::new(5094.18016210, 6127.64465950, 6380.34453270);
+        let j2000 = j2000_from_teme(&teme, jd_tt);
+
+        assert!((j2000.X - 5102.50960000).abs() < 1e-4);
+        assert!((j2000.Y - 6123.01152000).abs() < 1e-4);
+        assert!((j2000.Z - 6378.13630000).abs() < 1e-4);
+
+        let teme = TEME::new(-4.746131487, 0.785818041, 5.531931288);
+        let j2000 = j2000_from_teme(&teme, jd_tt);
+
+        assert!((j2000.X - -4.7432196000).abs() < 1e-6);
+        assert!((j2000.Y - 0.7905366000).abs() < 1e-6);
+        assert!((j2000.Z - 5.5337561900).abs() < 1e-6);
+    }
+
+    #[test]
+    fn relative_ric_axes() {
+        let reference = StateVector {
+            epoch: 0.0,
+            position: TEME::new(7000.0, 0.0, 0.0),
+            velocity: TEME::new(0.0, 7.5, 0.0),
+        };
+        let other = StateVector {
+            epoch: 0.0,
+            position: TEME::new(7001.0, 2.0, 3.0),
+            velocity: TEME::new(0.0, 7.5, 0.0),
+        };
+
+        let relative = relative_ric(&reference, &other);
+        assert_eq!(relative.position, RIC { R: 1.0, I: 2.0, C: 3.0 });
+    }
+
+    #[test]
+    fn relative_ric_same_orbit() {
+        // Circular orbit, with the second object a little further along it
+        let r: f64 = 7000.0;
+        let v = (398600.4418 / r).sqrt();
+        let (s, c) = f64::sin_cos(0.01);
+
+        let reference = StateVector {
+            epoch: 0.0,
+            position: TEME::new(r, 0.0, 0.0),
+            velocity: TEME::new(0.0, v, 0.0),
+        };
+        let other = StateVector {
+            epoch: 0.0,
+            position: TEME::new(r * c, r * s, 0.0),
+            velocity: TEME::new(-v * s, v * c, 0.0),
+        };
+
+        let relative = relative_ric(&reference, &other);
+        assert!((relative.position.R - r * (c - 1.0)).abs() < 1e-9);
+        assert!((relative.position.I - r * s).abs() < 1e-9);
+        assert!(relative.position.C.abs() < 1e-12);
+        assert!(relative.velocity.R.abs() < 1e-12);
+        assert!(relative.velocity.I.abs() < 1e-12);
+        assert!(relative.velocity.C.abs() < 1e-12);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use serde_json;
+
+        let state = StateVector {
+            epoch: 2457598.5,
+            position: TEME::new(7000.0, -12.5, 0.25),
+            velocity: TEME::new(0.0, 7.5, -0.001),
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(json, r#"{"epoch":2457598.5,"position":{"X":7000.0,"Y":-12.5,"Z":0.25},"velocity":{"X":0.0,"Y":7.5,"Z":-0.001}}"#);
+        assert_eq!(serde_json::from_str::<StateVector>(&json).unwrap(), state);
+
+        let geodetic = Geodetic { lat: 0.5, lon: -2.0, alt: 0.1 };
+        let json = serde_json::to_string(&geodetic).unwrap();
+        assert_eq!(serde_json::from_str::<Geodetic>(&json).unwrap(), geodetic);
+    }
 }
@@ -0,0 +1,354 @@
+/*!  # Covariance
+
+Uncertainty in the mean elements carried along to the propagated state.
+
+A [`Covariance`](struct.Covariance.html) pairs mean elements with a 6×6
+covariance over them, in the order and units of
+[`Elements`](../elements/struct.Elements.html): mean motion
+(radians/minute), eccentricity, inclination, right ascension, argument of
+perigee and mean anomaly (radians). B* is held fixed.
+
+The state transition matrix $\Phi(t) = \partial x(t) / \partial q$, from
+the elements $q$ to the TEME state $x$ at time $t$, is found by central
+differences of the propagator itself, so it includes everything SGP4 and
+SDP4 model. The state covariance is then
+
+$$P_x(t) = \Phi(t) \\, P_q \\, \Phi(t)^T$$
+
+in kilometers and kilometers/second. The mapping is linear, which holds
+while the element uncertainty is small; mean motion uncertainty grows
+along-track error without bound, and after long enough the true dispersion
+is better found by sampling.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use angle::Rad;
+use coordinates::{cross, dot, unit, StateVector};
+use elements::Elements;
+use tle::TLE;
+use {PropagationError, Propagator};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+
+/// A 6×6 matrix, row major
+pub type Matrix6 = [[f64; 6]; 6];
+
+/// Relative difference step for the mean motion, and absolute step for
+/// the other elements
+const DIFFERENCE_STEP: f64 = 1.0e-6;
+
+/// Elements as an array, in the covariance order
+fn parameters(elements: &Elements) -> [f64; 6] {
+    [
+        elements.mean_motion,
+        elements.eccentricity,
+        elements.inclination.0,
+        elements.raan.0,
+        elements.argp.0,
+        elements.mean_anomaly.0,
+    ]
+}
+
+/// Elements from an array in the covariance order, with B*
+fn elements(q: &[f64; 6], bstar: f64) -> Elements {
+    Elements {
+        mean_motion: q[0],
+        eccentricity: q[1],
+        inclination: Rad(q[2]),
+        raan: Rad(q[3]),
+        argp: Rad(q[4]),
+        mean_anomaly: Rad(q[5]),
+        bstar,
+    }
+}
+
+/// State as an array: position then velocity
+fn components(state: &StateVector) -> [f64; 6] {
+    [
+        state.position.X, state.position.Y, state.position.Z,
+        state.velocity.X, state.velocity.Y, state.velocity.Z,
+    ]
+}
+
+/// $A B A^T$
+fn sandwich(a: &Matrix6, b: &Matrix6) -> Matrix6 {
+    let mut ab = [[0.0; 6]; 6];
+    for (row, a_row) in ab.iter_mut().zip(a.iter()) {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = a_row.iter().zip(b.iter()).map(|(a, b_row)| a * b_row[j]).sum();
+        }
+    }
+    let mut result = [[0.0; 6]; 6];
+    for (row, ab_row) in result.iter_mut().zip(ab.iter()) {
+        for (value, a_row) in row.iter_mut().zip(a.iter()) {
+            *value = ab_row.iter().zip(a_row.iter()).map(|(x, y)| x * y).sum();
+        }
+    }
+    result
+}
+
+/// ## Covariance
+///
+/// Mean elements with a covariance over them, dated at an epoch.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::covariance::Covariance;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // 100 meters of uncertainty along the orbit, and a little in its period
+/// let mut p = [[0.0; 6]; 6];
+/// p[0][0] = 1.0e-9_f64.powi(2);
+/// p[5][5] = (0.1 / 6780.0_f64).powi(2);
+/// let covariance = Covariance::new(&tle, p).unwrap();
+///
+/// let day = covariance.propagate(1440.0).unwrap();
+/// let sigma = day.position_ric();
+/// assert!(sigma[1][1].sqrt() > 0.1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Covariance {
+    elements: Elements,
+    epoch: f64,
+    covariance: Matrix6,
+    propagator: Propagator,
+}
+
+impl Covariance {
+
+    /// A TLE's elements with a `covariance` over them
+    pub fn new(tle: &TLE, covariance: Matrix6) -> Result<Covariance, PropagationError> {
+        Covariance::from_elements(&Elements::from_tle(tle), tle.epoch_julian_date(), covariance)
+    }
+
+    /// Mean elements dated at `epoch` (Julian date, UTC) with a
+    /// `covariance` over them
+    pub fn from_elements(elements: &Elements, epoch: f64, covariance: Matrix6) -> Result<Covariance, PropagationError> {
+        let propagator = Propagator::from_elements(elements)?.at_epoch(epoch);
+        Ok(Covariance { elements: *elements, epoch, covariance, propagator })
+    }
+
+    /// The mean elements
+    pub fn elements(&self) -> &Elements {
+        &self.elements
+    }
+
+    /// Julian date of the elements' epoch
+    pub fn epoch(&self) -> f64 {
+        self.epoch
+    }
+
+    /// The covariance over the elements
+    pub fn covariance(&self) -> &Matrix6 {
+        &self.covariance
+    }
+
+    /// ## State Transition Matrix
+    ///
+    /// $\partial x / \partial q$, the change in the TEME state `time`
+    /// minutes after the epoch for a change in each element: row $i$,
+    /// column $j$ is state component $i$ differentiated by element $j$.
+    ///
+    /// Eccentricity and inclination can't go negative, so at or near zero
+    /// they're differenced forward rather than centrally.
+    pub fn transition(&self, time: f64) -> Result<Matrix6, PropagationError> {
+        let q = parameters(&self.elements);
+        let state = |q: &[f64; 6]| -> Result<[f64; 6], PropagationError> {
+            let propagator = Propagator::from_elements(&elements(q, self.elements.bstar))?.at_epoch(self.epoch);
+            Ok(components(&propagator.propagate(time)?))
+        };
+
+        let mut phi = [[0.0; 6]; 6];
+        for j in 0..6 {
+            let h = if j == 0 { DIFFERENCE_STEP * q[0] } else { DIFFERENCE_STEP };
+            let mut plus = q;
+            plus[j] += h;
+            let mut minus = q;
+            let bounded = j == 1 || j == 2;
+            let (low, width) = if bounded && q[j] < h {
+                (components(&self.propagator.propagate(time)?), h)
+            } else {
+                minus[j] -= h;
+                (state(&minus)?, 2.0 * h)
+            };
+            let high = state(&plus)?;
+            for (row, (high, low)) in phi.iter_mut().zip(high.iter().zip(low.iter())) {
+                row[j] = (high - low) / width;
+            }
+        }
+        Ok(phi)
+    }
+
+    /// ## Propagate
+    ///
+    /// The TEME state `time` minutes after the epoch and its covariance,
+    /// $\Phi P_q \Phi^T$.
+    pub fn propagate(&self, time: f64) -> Result<StateCovariance, PropagationError> {
+        let state = self.propagator.propagate(time)?;
+        let phi = self.transition(time)?;
+        Ok(StateCovariance { state, covariance: sandwich(&phi, &self.covariance) })
+    }
+}
+
+/// ## State Covariance
+///
+/// A propagated TEME state and its 6×6 covariance, position then velocity,
+/// in kilometers² and (kilometers/second)² with mixed terms in between.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateCovariance {
+
+    /// The state
+    pub state: StateVector,
+
+    /// Its covariance
+    pub covariance: Matrix6,
+}
+
+impl StateCovariance {
+
+    /// Standard deviation of each position component (kilometers)
+    pub fn position_sigma(&self) -> [f64; 3] {
+        [
+            self.covariance[0][0].sqrt(),
+            self.covariance[1][1].sqrt(),
+            self.covariance[2][2].sqrt(),
+        ]
+    }
+
+    /// ## RIC Position Covariance
+    ///
+    /// The 3×3 position covariance (kilometers²) rotated into the state's
+    /// own radial, in-track, cross-track frame, as in
+    /// [`relative_ric`](../coordinates/fn.relative_ric.html). Along-track
+    /// uncertainty usually dominates, and is the middle diagonal term.
+    pub fn position_ric(&self) -> [[f64; 3]; 3] {
+        let r = [self.state.position.X, self.state.position.Y, self.state.position.Z];
+        let v = [self.state.velocity.X, self.state.velocity.Y, self.state.velocity.Z];
+        let R = unit(&r);
+        let C = unit(&cross(&r, &v));
+        let I = cross(&C, &R);
+        let axes = [R, I, C];
+
+        // Rotate: A P A^T, with the rows of A the frame axes
+        let mut result = [[0.0; 3]; 3];
+        for (row, a) in result.iter_mut().zip(axes.iter()) {
+            for (value, b) in row.iter_mut().zip(axes.iter()) {
+                let mut sum = 0.0;
+                for (k, a_k) in a.iter().enumerate() {
+                    let column = [self.covariance[k][0], self.covariance[k][1], self.covariance[k][2]];
+                    sum += a_k * dot(&column, b);
+                }
+                *value = sum;
+            }
+        }
+        result
+    }
+}
+
+/// ## Diagonal Covariance
+///
+/// A covariance over the elements with no correlation between them, from
+/// the standard deviation of each, in the covariance order.
+pub fn diagonal(sigma: &[f64; 6]) -> Matrix6 {
+    let mut covariance = [[0.0; 6]; 6];
+    for (j, (row, sigma)) in covariance.iter_mut().zip(sigma.iter()).enumerate() {
+        row[j] = sigma * sigma;
+    }
+    covariance
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use MINUTES_PER_DAY;
+    use test_support::iss;
+
+    #[test]
+    fn transition() {
+        let tle = iss();
+        let covariance = Covariance::new(&tle, [[0.0; 6]; 6]).unwrap();
+
+        // The nominal state is the TLE's
+        let state = covariance.propagate(90.0).unwrap().state;
+        let expected = ::propagate(&tle, 90.0).unwrap();
+        assert!((state.position.X - expected.position.X).abs() < 1e-9);
+
+        // Against a finite step, the linear prediction holds
+        let phi = covariance.transition(90.0).unwrap();
+        let dq = [1.0e-8, 1.0e-5, 1.0e-5, 2.0e-5, -1.0e-5, 3.0e-5];
+        let mut q = parameters(covariance.elements());
+        for (q, dq) in q.iter_mut().zip(dq.iter()) {
+            *q += dq;
+        }
+        let moved = Propagator::from_elements(&elements(&q, tle.bstar)).unwrap().at_epoch(tle.epoch_julian_date());
+        let actual = components(&moved.propagate(90.0).unwrap());
+        let nominal = components(&state);
+        for i in 0..6 {
+            let predicted: f64 = phi[i].iter().zip(dq.iter()).map(|(p, d)| p * d).sum();
+            assert!((actual[i] - nominal[i] - predicted).abs() < 1e-3 * (1.0 + predicted.abs()), "{} {} {}", i, actual[i] - nominal[i], predicted);
+        }
+    }
+
+    #[test]
+    fn along_track_growth() {
+        // A small mean motion error stretches the position along the orbit
+        let covariance = Covariance::new(&iss(), diagonal(&[1.0e-9, 0.0, 0.0, 0.0, 0.0, 0.0])).unwrap();
+
+        let mut previous = 0.0;
+        for days in 1..=4 {
+            let propagated = covariance.propagate(f64::from(days) * MINUTES_PER_DAY).unwrap();
+            let ric = propagated.position_ric();
+
+            // Growing linearly with time, and mostly in-track
+            let in_track = ric[1][1].sqrt();
+            let expected = 6780.0 * 1.0e-9 * f64::from(days) * MINUTES_PER_DAY;
+            assert!((in_track - expected).abs() < 0.05 * expected, "{} {}", in_track, expected);
+            assert!(in_track > previous);
+            assert!(ric[0][0] < 1e-2 * ric[1][1]);
+            assert!(ric[2][2] < 1e-4 * ric[1][1]);
+            previous = in_track;
+
+            // The rotation keeps the total
+            let trace = ric[0][0] + ric[1][1] + ric[2][2];
+            let sigma = propagated.position_sigma();
+            let total = sigma.iter().map(|s| s * s).sum::<f64>();
+            assert!((trace - total).abs() < 1e-9 * total);
+
+            // Symmetric
+            for i in 0..6 {
+                for j in 0..6 {
+                    let (a, b) = (propagated.covariance[i][j], propagated.covariance[j][i]);
+                    assert!((a - b).abs() <= 1e-12 * (a.abs() + b.abs()) + 1e-30);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn circular_equatorial() {
+        // Forward differences where the elements can't go lower
+        let elements = Elements::from_keplerian(7000.0, 0.0, Rad(0.0), Rad(0.0), Rad(0.0), Rad(0.0), 0.0).unwrap();
+        let covariance = Covariance::from_elements(&elements, 2451545.0, diagonal(&[0.0, 1.0e-4, 1.0e-4, 0.0, 0.0, 0.0])).unwrap();
+        let propagated = covariance.propagate(60.0).unwrap();
+        let sigma = propagated.position_sigma();
+        assert!(sigma.iter().all(|s| s.is_finite()));
+        assert!(sigma[2] > 0.1);
+    }
+}
@@ -0,0 +1,379 @@
+/*!  # Coverage
+
+How well a satellite or constellation covers a region: for each point of
+a latitude and longitude grid, when it can see a satellite, and how long
+it waits between looks.
+
+A point has access while any satellite is above the minimum elevation
+from it, that is, while the point is inside the satellite's
+[footprint](../footprint/index.html). Access is checked every
+`CoverageOptions::step` minutes through the window, so access times are
+good to within a step; a look shorter than a step may be missed. The
+statistics per point are the usual ones for coverage analysis: the
+fraction of the time with access, the gaps between accesses, and the
+revisit time from the start of one access to the start of the next.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use coordinates::{ecef_from_teme, geodetic_from_ecef, Geodetic};
+use footprint::Footprint;
+use time;
+use tle::TLE;
+use {Propagator, MINUTES_PER_DAY};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// ## Coverage Options
+///
+/// How to decide and sample access.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoverageOptions {
+
+    /// Elevation a satellite has to be above to be seen from a point
+    /// (radians, default 0)
+    pub min_elevation: f64,
+
+    /// Time between checks for access (minutes, default 1)
+    pub step: f64,
+}
+
+impl Default for CoverageOptions {
+    fn default() -> CoverageOptions {
+        CoverageOptions {
+            min_elevation: 0.0,
+            step: 1.0,
+        }
+    }
+}
+
+/// ## Grid
+///
+/// Points on the ground every `spacing` radians of latitude and longitude,
+/// from `south` to `north` and from `west` to `east` inclusive (radians).
+/// Rows run west to east, from the southernmost row up.
+///
+/// ### Panics
+///
+/// If `spacing` isn't positive.
+pub fn grid(south: f64, north: f64, west: f64, east: f64, spacing: f64) -> Vec<Geodetic> {
+    assert!(spacing > 0.0, "grid spacing must be positive");
+
+    // A little slack so an edge a whole number of spacings away is kept
+    let count = |from: f64, to: f64| ((to - from) / spacing + 1e-9).floor().max(-1.0) as i64 + 1;
+    let (rows, columns) = (count(south, north), count(west, east));
+    let mut points = Vec::with_capacity((rows * columns).max(0) as usize);
+    for row in 0..rows {
+        for column in 0..columns {
+            points.push(Geodetic {
+                lat: south + row as f64 * spacing,
+                lon: west + column as f64 * spacing,
+                alt: 0.0,
+            });
+        }
+    }
+    points
+}
+
+/// ## Access
+///
+/// A time a point could see a satellite, from the first check it could to
+/// the last (Julian dates, UTC).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Access {
+
+    /// First check with access
+    pub start: f64,
+
+    /// Last check with access
+    pub end: f64,
+}
+
+impl Access {
+
+    /// Length of the access (minutes)
+    pub fn duration(&self) -> f64 {
+        (self.end - self.start) * MINUTES_PER_DAY
+    }
+}
+
+/// ## Point Coverage
+///
+/// Access to one grid point through the window, and its statistics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointCoverage {
+
+    /// The point
+    pub point: Geodetic,
+
+    /// Times the point had access, in order
+    pub accesses: Vec<Access>,
+
+    /// Fraction of the checks with access, 0 to 1
+    pub coverage: f64,
+
+    /// Longest time without access (minutes), counting from the start of
+    /// the window to the first access and from the last to the end. The
+    /// whole window for a point never covered.
+    pub max_gap: f64,
+
+    /// Mean time without access (minutes), over the same gaps
+    pub mean_gap: f64,
+
+    /// Mean time from the start of one access to the start of the next
+    /// (minutes), if there were two or more
+    pub mean_revisit: Option<f64>,
+}
+
+impl PointCoverage {
+
+    /// Work out the statistics for `accesses` to `point` through a window
+    fn new(point: Geodetic, accesses: Vec<Access>, covered: usize, checks: usize, start: f64, end: f64) -> PointCoverage {
+        let mut gaps = Vec::with_capacity(accesses.len() + 1);
+        let mut previous = start;
+        for access in &accesses {
+            gaps.push((access.start - previous) * MINUTES_PER_DAY);
+            previous = access.end;
+        }
+        gaps.push((end - previous) * MINUTES_PER_DAY);
+        gaps.retain(|&gap| gap > 0.0);
+
+        let (max_gap, mean_gap) = if gaps.is_empty() {
+            (0.0, 0.0)
+        } else {
+            (gaps.iter().cloned().fold(0.0, f64::max), gaps.iter().sum::<f64>() / gaps.len() as f64)
+        };
+        let mean_revisit = match (accesses.first(), accesses.last()) {
+            (Some(first), Some(last)) if accesses.len() > 1 => {
+                Some((last.start - first.start) * MINUTES_PER_DAY / (accesses.len() - 1) as f64)
+            }
+            _ => None,
+        };
+
+        PointCoverage {
+            point,
+            accesses,
+            coverage: covered as f64 / checks as f64,
+            max_gap,
+            mean_gap,
+            mean_revisit,
+        }
+    }
+}
+
+/// ## Coverage Report
+///
+/// Access and statistics for every grid point through a window.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Coverage {
+
+    /// Start of the window (Julian date, UTC)
+    pub start: f64,
+
+    /// End of the window (Julian date, UTC)
+    pub end: f64,
+
+    /// Each point's coverage, in the order the points were given
+    pub points: Vec<PointCoverage>,
+}
+
+impl Coverage {
+
+    /// Mean of the points' coverage fractions
+    pub fn mean_coverage(&self) -> f64 {
+        self.points.iter().map(|point| point.coverage).sum::<f64>() / self.points.len() as f64
+    }
+
+    /// Longest gap at any point (minutes)
+    pub fn max_gap(&self) -> f64 {
+        self.points.iter().map(|point| point.max_gap).fold(0.0, f64::max)
+    }
+
+    /// Fraction of the points that had access at least once
+    pub fn fraction_covered(&self) -> f64 {
+        let covered = self.points.iter().filter(|point| !point.accesses.is_empty()).count();
+        covered as f64 / self.points.len() as f64
+    }
+}
+
+/// ## Coverage
+///
+/// Access to each of `points` from any of the satellites `tles`, from
+/// `start` to `end` (Julian dates, UTC). A satellite that can't be
+/// propagated at some time, because it's decayed, gives no access then.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::coverage::{coverage, grid, CoverageOptions};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // The contiguous United States, every 5°, for a day
+/// let points = grid(
+///     f64::to_radians(25.0), f64::to_radians(50.0),
+///     f64::to_radians(-125.0), f64::to_radians(-65.0),
+///     f64::to_radians(5.0),
+/// );
+/// let options = CoverageOptions { min_elevation: f64::to_radians(10.0), ..CoverageOptions::default() };
+/// let start = tle.epoch_julian_date();
+/// let report = coverage(&[tle], &points, start, start + 1.0, &options);
+///
+/// for point in &report.points {
+///     println!("{:.0}° {:.0}°: {} accesses, longest gap {:.0} minutes",
+///              point.point.lat.to_degrees(), point.point.lon.to_degrees(),
+///              point.accesses.len(), point.max_gap);
+/// }
+/// ```
+///
+/// ### Panics
+///
+/// If `options.step` isn't positive.
+pub fn coverage(tles: &[TLE], points: &[Geodetic], start: f64, end: f64, options: &CoverageOptions) -> Coverage {
+    assert!(options.step > 0.0, "coverage step must be positive");
+
+    let satellites: Vec<(Propagator, f64)> = tles.iter()
+        .filter_map(|tle| Propagator::new(tle).ok().map(|propagator| (propagator, tle.epoch_julian_date())))
+        .collect();
+
+    // For each point, the access so far and how many checks had access
+    let mut accesses: Vec<Vec<Access>> = vec![Vec::new(); points.len()];
+    let mut open: Vec<Option<Access>> = vec![None; points.len()];
+    let mut covered = vec![0; points.len()];
+
+    let mut footprints = Vec::with_capacity(satellites.len());
+    let mut checks = 0;
+    loop {
+        let julian_date = start + checks as f64 * options.step / MINUTES_PER_DAY;
+        if julian_date > end {
+            break;
+        }
+        checks += 1;
+
+        let gmst = time::gmst(julian_date);
+        footprints.clear();
+        footprints.extend(satellites.iter().filter_map(|&(ref propagator, epoch)| {
+            let state = propagator.propagate((julian_date - epoch) * MINUTES_PER_DAY).ok()?;
+            let position = geodetic_from_ecef(&ecef_from_teme(&state.position, gmst));
+            Some(Footprint::new(&position, options.min_elevation))
+        }));
+
+        for (index, point) in points.iter().enumerate() {
+            if footprints.iter().any(|footprint| footprint.contains(point)) {
+                covered[index] += 1;
+                match open[index] {
+                    Some(ref mut access) => access.end = julian_date,
+                    None => open[index] = Some(Access { start: julian_date, end: julian_date }),
+                }
+            } else if let Some(access) = open[index].take() {
+                accesses[index].push(access);
+            }
+        }
+    }
+
+    let points = points.iter()
+        .zip(accesses)
+        .zip(open)
+        .zip(covered)
+        .map(|(((point, mut accesses), open), covered)| {
+            accesses.extend(open);
+            PointCoverage::new(*point, accesses, covered, checks, start, end)
+        })
+        .collect();
+    Coverage { start, end, points }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use angle::Deg;
+    use observer::Observer;
+    use passes::{find_passes, PassOptions};
+    use test_support::iss;
+
+    #[test]
+    fn grids() {
+        let points = grid(-0.5, 0.5, 1.0, 1.35, 0.25);
+        assert_eq!(points.len(), 5 * 2);
+        assert_eq!(points[0], Geodetic { lat: -0.5, lon: 1.0, alt: 0.0 });
+        assert_eq!(points[1].lon, 1.25);
+        assert_eq!(points[9].lat, 0.5);
+        assert!(grid(1.0, 0.0, 0.0, 1.0, 0.1).is_empty());
+        assert_eq!(grid(0.0, 0.0, 0.0, 0.0, 0.1).len(), 1);
+    }
+
+    #[test]
+    fn single_satellite() {
+        let tle = iss();
+        let start = tle.epoch_julian_date();
+        let points = [
+            Geodetic { lat: f64::to_radians(45.52), lon: f64::to_radians(-122.68), alt: 0.0 },
+            Geodetic { lat: f64::to_radians(85.0), lon: 0.0, alt: 0.0 },
+        ];
+        let options = CoverageOptions { min_elevation: f64::to_radians(10.0), step: 0.05 };
+        let report = coverage(std::slice::from_ref(&tle), &points, start, start + 1.0, &options);
+        assert_eq!(report.points.len(), 2);
+
+        // The same passes as the pass finder, to within a check
+        let portland = &report.points[0];
+        let pass_options = PassOptions { min_elevation: options.min_elevation, ..PassOptions::default() };
+        let propagator = Propagator::new(&tle).unwrap();
+        let passes = find_passes(&propagator, &Observer::new(points[0]), 0.0, MINUTES_PER_DAY, &pass_options).unwrap();
+        assert_eq!(portland.accesses.len(), passes.len());
+        for (access, pass) in portland.accesses.iter().zip(&passes) {
+            let rise = start + pass.rise / MINUTES_PER_DAY;
+            let set = start + pass.set / MINUTES_PER_DAY;
+            assert!((access.start - rise) * MINUTES_PER_DAY < 0.1, "{} {}", access.start, rise);
+            assert!((set - access.end) * MINUTES_PER_DAY < 0.1, "{} {}", access.end, set);
+        }
+        let total: f64 = portland.accesses.iter().map(Access::duration).sum();
+        assert!((portland.coverage * MINUTES_PER_DAY - total).abs() < options.step * (passes.len() + 1) as f64);
+        assert!(portland.max_gap > 90.0 && portland.mean_gap <= portland.max_gap);
+        assert!(portland.mean_revisit.unwrap() > 80.0);
+
+        // Too far north for the ISS
+        let north = &report.points[1];
+        assert!(north.accesses.is_empty());
+        assert_eq!(north.coverage, 0.0);
+        assert_eq!(north.max_gap, MINUTES_PER_DAY);
+        assert_eq!(north.mean_revisit, None);
+
+        assert_eq!(report.fraction_covered(), 0.5);
+        assert_eq!(report.max_gap(), MINUTES_PER_DAY);
+        assert_eq!(report.mean_coverage(), portland.coverage / 2.0);
+    }
+
+    #[test]
+    fn constellation() {
+        // A second satellite half an orbit behind covers more of the time
+        let mut trailing = iss();
+        trailing.sat_number = 1;
+        trailing.mean_anomaly = (trailing.mean_anomaly + Deg(180.0)).normalized();
+        let start = iss().epoch_julian_date();
+        let points = grid(f64::to_radians(-40.0), f64::to_radians(40.0), 0.0, f64::to_radians(350.0), f64::to_radians(10.0));
+
+        let one = coverage(&[iss()], &points, start, start + 1.0, &CoverageOptions::default());
+        let two = coverage(&[iss(), trailing], &points, start, start + 1.0, &CoverageOptions::default());
+        assert!(two.mean_coverage() > 1.5 * one.mean_coverage());
+        assert_eq!(one.fraction_covered(), 1.0);
+        for (one, two) in one.points.iter().zip(&two.points) {
+            assert!(two.coverage >= one.coverage);
+        }
+    }
+}
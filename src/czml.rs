@@ -0,0 +1,230 @@
+/*!  # CZML
+
+Satellite tracks as CZML, the JSON document CesiumJS animates. Requires
+the `serde_json` feature.
+
+A document is an array of packets: a `document` packet setting the clock to
+the window, then one packet per satellite with its name, the interval it's
+available over, and its position sampled through the window. Positions are
+given in Cesium's `FIXED` (Earth-fixed) frame, in meters, rotated from TEME
+by Greenwich mean sidereal time, so Cesium needs no inertial frame data to
+show them. Between samples Cesium interpolates with Lagrange polynomials.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use serde_json::{self, Value};
+
+use coordinates::ecef_from_teme;
+use time;
+use tle::TLE;
+use {Propagator, MINUTES_PER_DAY};
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::string::String;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// ## CZML Options
+///
+/// How the document samples and shows the tracks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CzmlOptions {
+
+    /// Name of the document (default `sgp4`)
+    pub name: String,
+
+    /// Time between position samples (minutes, default 1)
+    pub step: f64,
+
+    /// Degree of the Lagrange interpolation between samples (default 5)
+    pub interpolation_degree: u32,
+
+    /// Simulated seconds per real second when the clock runs (default 60)
+    pub multiplier: f64,
+}
+
+impl Default for CzmlOptions {
+    fn default() -> CzmlOptions {
+        CzmlOptions {
+            name: String::from("sgp4"),
+            step: 1.0,
+            interpolation_degree: 5,
+            multiplier: 60.0,
+        }
+    }
+}
+
+/// A Julian date as a CZML time, ISO 8601 in UTC
+fn timestamp(julian_date: f64) -> String {
+    let mut timestamp = time::iso_8601(julian_date);
+    timestamp.push('Z');
+    timestamp
+}
+
+/// A CZML interval
+fn interval(start: f64, end: f64) -> String {
+    format!("{}/{}", timestamp(start), timestamp(end))
+}
+
+/// The packet for one satellite, or `None` if it can't be propagated at
+/// the start of the window
+fn packet(tle: &TLE, start: f64, end: f64, options: &CzmlOptions) -> Option<Value> {
+    let propagator = Propagator::new(tle).ok()?;
+    let offset = (start - tle.epoch_julian_date()) * MINUTES_PER_DAY;
+
+    // Seconds since the start, then meters, for each sample until the end
+    // of the window or the satellite's decay
+    let mut cartesian = Vec::new();
+    let mut last = None;
+    let samples = propagator.sample(offset, offset + (end - start) * MINUTES_PER_DAY, options.step);
+    for (index, (_, state)) in samples.enumerate() {
+        let state = match state {
+            Ok(state) => state,
+            Err(_) => break,
+        };
+        let elapsed = index as f64 * options.step;
+        let julian_date = start + elapsed / MINUTES_PER_DAY;
+        let ecef = ecef_from_teme(&state.position, time::gmst(julian_date));
+        cartesian.extend_from_slice(&[elapsed * 60.0, ecef.X * 1000.0, ecef.Y * 1000.0, ecef.Z * 1000.0]);
+        last = Some(julian_date);
+    }
+    let last = last?;
+
+    let name = tle.name.trim();
+    Some(serde_json::json!({
+        "id": format!("{}", tle.sat_number),
+        "name": name,
+        "availability": interval(start, last),
+        "label": {
+            "text": name,
+            "horizontalOrigin": "LEFT",
+            "pixelOffset": { "cartesian2": [8, 0] },
+        },
+        "point": { "pixelSize": 6 },
+        "path": {
+            "leadTime": 0,
+            "trailTime": 5400,
+        },
+        "position": {
+            "epoch": timestamp(start),
+            "referenceFrame": "FIXED",
+            "interpolationAlgorithm": "LAGRANGE",
+            "interpolationDegree": options.interpolation_degree,
+            "cartesian": cartesian,
+        },
+    }))
+}
+
+/// ## CZML
+///
+/// A CZML document of the satellites' tracks from `start` to `end` (Julian
+/// dates, UTC). A satellite that decays part way through is shown until
+/// then; one that can't be propagated at all is left out.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::czml::{czml, CzmlOptions};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // The next three hours
+/// let start = tle.epoch_julian_date();
+/// let document = czml(&[tle], start, start + 0.125, &CzmlOptions::default());
+/// let text = document.to_string();
+/// assert!(text.contains("\"referenceFrame\":\"FIXED\""));
+/// ```
+///
+/// ### Panics
+///
+/// If `options.step` isn't positive.
+pub fn czml(tles: &[TLE], start: f64, end: f64, options: &CzmlOptions) -> Value {
+    let mut packets = Vec::with_capacity(tles.len() + 1);
+    packets.push(serde_json::json!({
+        "id": "document",
+        "name": options.name,
+        "version": "1.0",
+        "clock": {
+            "interval": interval(start, end),
+            "currentTime": timestamp(start),
+            "multiplier": options.multiplier,
+            "range": "LOOP_STOP",
+            "step": "SYSTEM_CLOCK_MULTIPLIER",
+        },
+    }));
+    packets.extend(tles.iter().filter_map(|tle| packet(tle, start, end, options)));
+    Value::Array(packets)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::iss;
+
+    #[test]
+    fn document() {
+        let tle = iss();
+        let start = time::julian_date(2016, 7, 28.75);
+        let document = czml(std::slice::from_ref(&tle), start, start + 0.0625, &CzmlOptions::default());
+        let packets = document.as_array().unwrap();
+        assert_eq!(packets.len(), 2);
+
+        let clock = &packets[0]["clock"];
+        assert_eq!(packets[0]["id"], "document");
+        assert_eq!(clock["interval"], "2016-07-28T18:00:00.000000Z/2016-07-28T19:30:00.000000Z");
+        assert_eq!(clock["currentTime"], "2016-07-28T18:00:00.000000Z");
+
+        let satellite = &packets[1];
+        assert_eq!(satellite["id"], "25544");
+        assert_eq!(satellite["name"], "ISS (ZARYA)");
+        assert_eq!(satellite["availability"], clock["interval"]);
+        assert_eq!(satellite["position"]["referenceFrame"], "FIXED");
+        assert_eq!(satellite["position"]["interpolationDegree"], 5);
+
+        // A sample a minute, in seconds and meters
+        let cartesian = satellite["position"]["cartesian"].as_array().unwrap();
+        assert_eq!(cartesian.len(), 91 * 4);
+        assert_eq!(cartesian[4], 60.0);
+        let time = (start - tle.epoch_julian_date()) * MINUTES_PER_DAY + 1.0;
+        let state = ::propagate(&tle, time).unwrap();
+        let ecef = ecef_from_teme(&state.position, time::gmst(start + 1.0 / MINUTES_PER_DAY));
+        assert!((cartesian[5].as_f64().unwrap() - ecef.X * 1000.0).abs() < 1e-3);
+        let radius = (1..4).map(|i| cartesian[4 + i].as_f64().unwrap().powi(2)).sum::<f64>().sqrt();
+        assert!((radius - 6.78e6).abs() < 5e4, "{}", radius);
+    }
+
+    #[test]
+    fn decayed() {
+        // A satellite that comes down in the window is shown until it does,
+        // and one already down is left out
+        let mut decaying = iss();
+        decaying.bstar = 0.5;
+        decaying.sat_number = 1;
+        let start = decaying.epoch_julian_date();
+        let propagator = Propagator::new(&decaying).unwrap();
+        let decay = propagator.decay_time(0.0, 14400.0, 1.0).unwrap();
+
+        let document = czml(&[iss(), decaying.clone()], start, start + 10.0, &CzmlOptions { step: 10.0, ..CzmlOptions::default() });
+        let packets = document.as_array().unwrap();
+        assert_eq!(packets.len(), 3);
+        let samples = packets[2]["position"]["cartesian"].as_array().unwrap().len() / 4;
+        assert!(samples > 0 && (samples as f64) * 10.0 < decay + 10.0);
+        assert_ne!(packets[2]["availability"], packets[0]["clock"]["interval"]);
+
+        let later = czml(&[decaying], start + 10.0, start + 11.0, &CzmlOptions::default());
+        assert_eq!(later.as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,219 @@
+/*!  # Decay
+
+When a satellite will re-enter, by following the SGP4 drag model forward
+until its perigee drops into the atmosphere.
+
+SGP4's drag terms are a polynomial in time fitted around the epoch through
+B*, so the further out the estimate, the rougher it is: the real decay rate
+follows solar activity, which the model knows nothing about. For a
+satellite with weeks to live the estimate is typically good to a few days;
+years out it is an order of magnitude at best. Re-estimate from fresh
+elements as they're published.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use tle::TLE;
+use {PropagationError, Propagator, MINUTES_PER_DAY};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+
+/// The conventional entry interface (kilometers above the equatorial
+/// radius)
+pub const REENTRY_ALTITUDE: f64 = 120.0;
+
+/// How closely the estimate is refined (minutes)
+const TOLERANCE: f64 = 1.0;
+
+/// ## Decay Options
+///
+/// How to search for the re-entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DecayOptions {
+
+    /// Perigee altitude counted as re-entry (kilometers, default
+    /// `REENTRY_ALTITUDE`)
+    pub threshold: f64,
+
+    /// Search step (minutes, default one day). The perigee falls
+    /// monotonically, so this only needs to be short enough not to step
+    /// past the point where the model breaks down.
+    pub step: f64,
+
+    /// Give up this long after the epoch (days, default 25 years)
+    pub limit: f64,
+}
+
+impl Default for DecayOptions {
+    fn default() -> DecayOptions {
+        DecayOptions {
+            threshold: REENTRY_ALTITUDE,
+            step: MINUTES_PER_DAY,
+            limit: 25.0 * 365.25,
+        }
+    }
+}
+
+/// ## Mean Perigee Altitude
+///
+/// Altitude of the mean perigee above the equatorial radius (kilometers)
+/// at `time` minutes since the epoch, from the
+/// [mean elements](../struct.Propagator.html#method.mean_elements) the
+/// propagator has then: with the secular effects of drag, and in deep
+/// space those of the Sun and Moon. Errors once the drag model says the
+/// orbit has collapsed.
+pub fn mean_perigee_altitude(propagator: &Propagator, time: f64) -> Result<f64, PropagationError> {
+    let mean = propagator.mean_elements(time)?;
+    Ok(mean.semi_major_axis * (1.0 - mean.eccentricity) - propagator.gravity_model().radius)
+}
+
+/// Whether the satellite is down by `time`
+fn has_decayed(propagator: &Propagator, time: f64, threshold: f64) -> bool {
+    match mean_perigee_altitude(propagator, time) {
+        Ok(altitude) => altitude < threshold,
+        Err(PropagationError::Decayed) | Err(PropagationError::Diverged) => true,
+        Err(_) => false,
+    }
+}
+
+/// ## Decay Time
+///
+/// Minutes since the epoch at which the mean perigee falls below the
+/// threshold, to within a minute, or `None` if it's still above it at the
+/// limit. A satellite already below the threshold decays at time zero.
+///
+/// ### Panics
+///
+/// If `options.step` isn't positive.
+pub fn decay_time(propagator: &Propagator, options: &DecayOptions) -> Option<f64> {
+    assert!(options.step > 0.0, "decay search step must be positive");
+
+    let limit = options.limit * MINUTES_PER_DAY;
+    if has_decayed(propagator, 0.0, options.threshold) {
+        return Some(0.0);
+    }
+
+    let mut up = 0.0;
+    let mut down = None;
+    while up < limit {
+        let next = (up + options.step).min(limit);
+        if has_decayed(propagator, next, options.threshold) {
+            down = Some(next);
+            break;
+        }
+        up = next;
+    }
+
+    let mut down = down?;
+    while down - up > TOLERANCE {
+        let middle = 0.5 * (up + down);
+        if has_decayed(propagator, middle, options.threshold) {
+            down = middle;
+        } else {
+            up = middle;
+        }
+    }
+    Some(down)
+}
+
+/// ## Decay Date
+///
+/// Estimated re-entry of a TLE as a Julian date (UTC), or `None` if it's
+/// still in orbit at the limit.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::decay::{self, DecayOptions};
+///
+/// let mut tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // Low, with a lot of drag
+/// tle.mean_motion = 16.2;
+/// tle.bstar = 0.002;
+///
+/// let date = decay::decay_date(&tle, &DecayOptions::default()).unwrap().unwrap();
+/// println!("re-entry in {:.1} days", date - tle.epoch_julian_date());
+/// ```
+pub fn decay_date(tle: &TLE, options: &DecayOptions) -> Result<Option<f64>, PropagationError> {
+    let propagator = Propagator::new(tle)?;
+    Ok(decay_time(&propagator, options).map(|time| tle.epoch_julian_date() + time / MINUTES_PER_DAY))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::{self, iss};
+
+    #[test]
+    fn perigee() {
+        let propagator = Propagator::new(&iss()).unwrap();
+
+        // About 400 km, and falling
+        let now = mean_perigee_altitude(&propagator, 0.0).unwrap();
+        let later = mean_perigee_altitude(&propagator, 30.0 * MINUTES_PER_DAY).unwrap();
+        assert!(now > 380.0 && now < 420.0);
+        assert!(later < now);
+
+        // A circular orbit leaves out the terms divided by the eccentricity
+        let mut tle = iss();
+        tle.e = 0.0;
+        let propagator = Propagator::new(&tle).unwrap();
+        assert!(mean_perigee_altitude(&propagator, 30.0 * MINUTES_PER_DAY).unwrap() < mean_perigee_altitude(&propagator, 0.0).unwrap());
+
+        // In deep space the Sun and Moon move the perigee far more than
+        // drag does: they raise a Molniya orbit's by tens of kilometers a
+        // month
+        let molniya = test_support::molniya();
+        let propagator = Propagator::new(&molniya).unwrap();
+        let rise = mean_perigee_altitude(&propagator, 30.0 * MINUTES_PER_DAY).unwrap() - mean_perigee_altitude(&propagator, 0.0).unwrap();
+        assert!(rise > 20.0 && rise < 50.0, "{} km", rise);
+    }
+
+    #[test]
+    fn decaying() {
+        let mut tle = iss();
+        tle.mean_motion = 16.2;
+        tle.bstar = 0.002;
+        let propagator = Propagator::new(&tle).unwrap();
+        let options = DecayOptions::default();
+
+        let time = decay_time(&propagator, &options).unwrap();
+        assert!(mean_perigee_altitude(&propagator, time - TOLERANCE).unwrap() >= options.threshold);
+        assert!(has_decayed(&propagator, time, options.threshold));
+
+        // The propagator itself gives up soon after
+        let propagator_decay = propagator.decay_time(time, time + 30.0 * MINUTES_PER_DAY, 1.0);
+        assert!(propagator_decay.is_some_and(|decay| decay >= time));
+
+        // Finer steps find the same time
+        let fine = decay_time(&propagator, &DecayOptions { step: 60.0, ..options }).unwrap();
+        assert!((fine - time).abs() <= TOLERANCE);
+
+        let date = decay_date(&tle, &options).unwrap().unwrap();
+        assert!((date - tle.epoch_julian_date() - time / MINUTES_PER_DAY).abs() < 1e-9);
+    }
+
+    #[test]
+    fn limits() {
+        let propagator = Propagator::new(&iss()).unwrap();
+        assert_eq!(decay_time(&propagator, &DecayOptions { limit: 30.0, ..DecayOptions::default() }), None);
+
+        let options = DecayOptions { threshold: 1000.0, ..DecayOptions::default() };
+        assert_eq!(decay_time(&propagator, &options), Some(0.0));
+    }
+}
@@ -0,0 +1,864 @@
+/*!  # Deep Space
+
+The SDP4 terms for orbits with periods of 225 minutes or more, following
+Vallado, Crawford, Hujsak and Kelso, *Revisiting Spacetrack Report #3*
+(AIAA 2006-6753) and its reference code (`dscom`, `dsinit`, `dspace` and
+`dpper`), whose names the constants keep.
+
+At these altitudes the Sun and Moon matter as much as drag: they add
+secular rates to the eccentricity, inclination, node, perigee and mean
+anomaly, and long period periodics with the solar and lunar orbits. Orbits
+near 24 hours (geosynchronous) or 12 hours with a high eccentricity
+(Molniya) are also in resonance with the tesseral harmonics of the Earth's
+gravity field, which the reference integrates numerically in 720 minute
+steps from the epoch.
+
+The reference code keeps the integrator's state in the satellite record
+between calls, and carries on from the last time if it can. Here the
+propagator is never modified, so that one can be shared between threads
+without locking, and
+[`Propagator::propagate`](../struct.Propagator.html#method.propagate)
+integrates from the epoch every time, a step per 12 hours from it. Callers
+sampling far from the epoch keep the state themselves in a
+[`ResonanceState`](../struct.ResonanceState.html) and call
+[`Propagator::propagate_with`](../struct.Propagator.html#method.propagate_with),
+which carries on from it like the reference. The steps are the same either
+way, and so are the results.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+
+use time;
+use precision::Secular;
+use {OpsMode, PropagationError, Propagator, ResonanceState};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+
+/// Solar eccentricity
+const ZES: f64 = 0.01675;
+
+/// Lunar eccentricity
+const ZEL: f64 = 0.05490;
+
+/// Solar mean motion (radians/minute)
+const ZNS: f64 = 1.19459e-5;
+
+/// Lunar mean motion (radians/minute)
+const ZNL: f64 = 1.5835218e-4;
+
+/// Solar perturbation coefficient (radians/minute)
+const C1SS: f64 = 2.9864797e-6;
+
+/// Lunar perturbation coefficient (radians/minute)
+const C1L: f64 = 4.7968065e-7;
+
+/// Sine and cosine of the solar inclination (the obliquity of the ecliptic)
+const ZSINIS: f64 = 0.39785416;
+const ZCOSIS: f64 = 0.91744867;
+
+/// Sine and cosine of the solar argument of perigee
+const ZSINGS: f64 = -0.98088458;
+const ZCOSGS: f64 = 0.1945905;
+
+/// Earth rotation rate (radians/minute)
+const RPTIM: f64 = 4.3752690880113e-3;
+
+/// Geopotential coefficients for the synchronous resonance
+const Q22: f64 = 1.7891679e-6;
+const Q31: f64 = 2.1460748e-6;
+const Q33: f64 = 2.2123015e-7;
+
+/// Geopotential coefficients for the half day resonance
+const ROOT22: f64 = 1.7891679e-6;
+const ROOT32: f64 = 3.7393792e-7;
+const ROOT44: f64 = 7.3636953e-9;
+const ROOT52: f64 = 1.1428639e-7;
+const ROOT54: f64 = 2.1765803e-9;
+
+/// Phases of the synchronous resonance terms (radians)
+const FASX2: f64 = 0.13130908;
+const FASX4: f64 = 2.8843198;
+const FASX6: f64 = 0.37448087;
+
+/// Phases of the half day resonance terms (radians)
+const G22: f64 = 5.7686396;
+const G32: f64 = 0.95240898;
+const G44: f64 = 1.8014998;
+const G52: f64 = 1.0508330;
+const G54: f64 = 4.4108898;
+
+/// Resonance integrator step (minutes)
+const STEP: f64 = 720.0;
+
+/// Half the step squared (minutes²)
+const STEP2: f64 = 259200.0;
+
+/// Inclination within which of 0° or 180° the node terms are left out
+/// (radians, 3°)
+const EQUATORIAL: f64 = 5.2359877e-2;
+
+/// Inclination below which the periodics are applied with Lyddane's
+/// modification (radians)
+const LYDDANE: f64 = 0.2;
+
+
+/// ## Deep Space
+///
+/// The deep space constants of one propagator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DeepSpace {
+    /// Solar periodic coefficients
+    se2: f64,
+    se3: f64,
+    si2: f64,
+    si3: f64,
+    sl2: f64,
+    sl3: f64,
+    sl4: f64,
+    sgh2: f64,
+    sgh3: f64,
+    sgh4: f64,
+    sh2: f64,
+    sh3: f64,
+    /// Lunar periodic coefficients
+    ee2: f64,
+    e3: f64,
+    xi2: f64,
+    xi3: f64,
+    xl2: f64,
+    xl3: f64,
+    xl4: f64,
+    xgh2: f64,
+    xgh3: f64,
+    xgh4: f64,
+    xh2: f64,
+    xh3: f64,
+    /// Solar mean anomaly at the epoch (radians)
+    zmos: f64,
+    /// Lunar mean anomaly at the epoch (radians)
+    zmol: f64,
+    /// Secular rates of e, i, M, ω and Ω from the Sun and Moon (per minute)
+    dedt: f64,
+    didt: f64,
+    dmdt: f64,
+    domdt: f64,
+    dnodt: f64,
+    /// Greenwich sidereal angle at the epoch (radians)
+    gsto: f64,
+    /// Brouwer mean motion at the epoch (radians/minute)
+    no: f64,
+    /// Argument of perigee at the epoch (radians) and its secular rate
+    /// from gravity (radians/minute)
+    argpo: f64,
+    argpdot: f64,
+    /// Resonance with the Earth's gravity field
+    resonance: Resonance,
+}
+
+/// Resonance with the tesseral harmonics of the Earth's gravity field
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Resonance {
+    /// No resonance
+    None,
+    /// A period near 24 hours
+    Synchronous {
+        del1: f64,
+        del2: f64,
+        del3: f64,
+        /// Rate of the resonant angle, less the mean motion
+        xfact: f64,
+        /// Resonant angle at the epoch
+        xlamo: f64,
+    },
+    /// A period near 12 hours and an eccentricity of 0.5 or more
+    HalfDay {
+        d2201: f64,
+        d2211: f64,
+        d3210: f64,
+        d3222: f64,
+        d4410: f64,
+        d4422: f64,
+        d5220: f64,
+        d5232: f64,
+        d5421: f64,
+        d5433: f64,
+        /// Rate of the resonant angle, less the mean motion
+        xfact: f64,
+        /// Resonant angle at the epoch
+        xlamo: f64,
+    },
+}
+
+/// Mean elements the secular terms act on
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Mean {
+    /// Eccentricity
+    pub e: f64,
+    /// Inclination (radians)
+    pub i: f64,
+    /// Argument of perigee (radians)
+    pub omega: f64,
+    /// Right ascension of the ascending node (radians)
+    pub Omega: f64,
+    /// Mean anomaly (radians)
+    pub M: f64,
+}
+
+/// Direction of the Sun or Moon's orbit, and its perturbation coefficient
+struct Body {
+    cos_g: f64,
+    sin_g: f64,
+    cos_i: f64,
+    sin_i: f64,
+    cos_h: f64,
+    sin_h: f64,
+    c: f64,
+}
+
+/// The satellite's orbit at the epoch, as the Sun and Moon see it
+struct Orbit {
+    cos_i: f64,
+    sin_i: f64,
+    cos_w: f64,
+    sin_w: f64,
+    e: f64,
+    emsq: f64,
+    betasq: f64,
+    rtemsq: f64,
+    xnoi: f64,
+}
+
+/// The geometry of one body's perturbation (`dscom`)
+struct Terms {
+    s1: f64,
+    s2: f64,
+    s3: f64,
+    s4: f64,
+    s5: f64,
+    s6: f64,
+    s7: f64,
+    z1: f64,
+    z2: f64,
+    z3: f64,
+    z11: f64,
+    z12: f64,
+    z13: f64,
+    z21: f64,
+    z22: f64,
+    z23: f64,
+    z31: f64,
+    z32: f64,
+    z33: f64,
+}
+
+impl Terms {
+
+    fn new(body: &Body, orbit: &Orbit) -> Terms {
+        let Body { cos_g, sin_g, cos_i, sin_i, cos_h, sin_h, c } = *body;
+        let Orbit { cos_i: cosim, sin_i: sinim, cos_w, sin_w, e, emsq, betasq, rtemsq, xnoi } = *orbit;
+
+        let a1 = cos_g * cos_h + sin_g * cos_i * sin_h;
+        let a3 = -sin_g * cos_h + cos_g * cos_i * sin_h;
+        let a7 = -cos_g * sin_h + sin_g * cos_i * cos_h;
+        let a8 = sin_g * sin_i;
+        let a9 = sin_g * sin_h + cos_g * cos_i * cos_h;
+        let a10 = cos_g * sin_i;
+        let a2 = cosim * a7 + sinim * a8;
+        let a4 = cosim * a9 + sinim * a10;
+        let a5 = -sinim * a7 + cosim * a8;
+        let a6 = -sinim * a9 + cosim * a10;
+
+        let x1 = a1 * cos_w + a2 * sin_w;
+        let x2 = a3 * cos_w + a4 * sin_w;
+        let x3 = -a1 * sin_w + a2 * cos_w;
+        let x4 = -a3 * sin_w + a4 * cos_w;
+        let x5 = a5 * sin_w;
+        let x6 = a6 * sin_w;
+        let x7 = a5 * cos_w;
+        let x8 = a6 * cos_w;
+
+        let z31 = 12.0 * x1 * x1 - 3.0 * x3 * x3;
+        let z32 = 24.0 * x1 * x2 - 6.0 * x3 * x4;
+        let z33 = 12.0 * x2 * x2 - 3.0 * x4 * x4;
+        let z1 = 3.0 * (a1 * a1 + a2 * a2) + z31 * emsq;
+        let z2 = 6.0 * (a1 * a3 + a2 * a4) + z32 * emsq;
+        let z3 = 3.0 * (a3 * a3 + a4 * a4) + z33 * emsq;
+        let z11 = -6.0 * a1 * a5 + emsq * (-24.0 * x1 * x7 - 6.0 * x3 * x5);
+        let z12 = -6.0 * (a1 * a6 + a3 * a5) + emsq * (-24.0 * (x2 * x7 + x1 * x8) - 6.0 * (x3 * x6 + x4 * x5));
+        let z13 = -6.0 * a3 * a6 + emsq * (-24.0 * x2 * x8 - 6.0 * x4 * x6);
+        let z21 = 6.0 * a2 * a5 + emsq * (24.0 * x1 * x5 - 6.0 * x3 * x7);
+        let z22 = 6.0 * (a4 * a5 + a2 * a6) + emsq * (24.0 * (x2 * x5 + x1 * x6) - 6.0 * (x4 * x7 + x3 * x8));
+        let z23 = 6.0 * a4 * a6 + emsq * (24.0 * x2 * x6 - 6.0 * x4 * x8);
+        let z1 = z1 + z1 + betasq * z31;
+        let z2 = z2 + z2 + betasq * z32;
+        let z3 = z3 + z3 + betasq * z33;
+
+        let s3 = c * xnoi;
+        let s2 = -0.5 * s3 / rtemsq;
+        let s4 = s3 * rtemsq;
+        let s1 = -15.0 * e * s4;
+        let s5 = x1 * x3 + x2 * x4;
+        let s6 = x2 * x3 + x1 * x4;
+        let s7 = x2 * x4 - x1 * x3;
+
+        Terms { s1, s2, s3, s4, s5, s6, s7, z1, z2, z3, z11, z12, z13, z21, z22, z23, z31, z32, z33 }
+    }
+}
+
+/// Greenwich sidereal angle at the epoch (radians). The Air Force code
+/// works it out from 1970 January 0 with its own constants.
+fn sidereal_angle(julian_date: f64, ops_mode: OpsMode) -> f64 {
+    match ops_mode {
+        OpsMode::Improved | OpsMode::SpacetrackReport3 => time::gmst(julian_date),
+        OpsMode::Afspc => {
+            let ts70 = julian_date - 2433281.5 - 7305.0;
+            let ds70 = (ts70 + 1.0e-8).floor();
+            let tfrac = ts70 - ds70;
+            let c1 = 1.7202791694070362e-2;
+            let thgr70 = 1.7321343856509374;
+            let fk5r = 5.075514194322695e-15;
+            let c1p2p = c1 + 2.0 * PI;
+            let gsto = (thgr70 + c1 * ds70 + c1p2p * tfrac + ts70 * ts70 * fk5r) % (2.0 * PI);
+            if gsto < 0.0 {
+                gsto + 2.0 * PI
+            } else {
+                gsto
+            }
+        }
+    }
+}
+
+impl DeepSpace {
+
+    /// Compute the deep space constants for a propagator's elements and
+    /// epoch (`dscom` and `dsinit`)
+    pub(crate) fn new(propagator: &Propagator, ops_mode: OpsMode) -> DeepSpace {
+        let p = propagator;
+        let (em, inclm, nm) = (p.e0, p.i0, p.n0_dp);
+        let (sinim, cosim) = (p.sin_io, p.O);
+        let emsq = em * em;
+        let betasq = 1.0 - emsq;
+        let rtemsq = betasq.sqrt();
+        let snodm = p.Omega0.sin();
+        let cnodm = p.Omega0.cos();
+
+        // Where the Moon's orbit is, days from 2000 January 1 12h
+        let day = p.epoch - 2433281.5 + 18261.5;
+        let xnodce = (4.5236020 - 9.2422029e-4 * day) % (2.0 * PI);
+        let stem = xnodce.sin();
+        let ctem = xnodce.cos();
+        let zcosil = 0.91375164 - 0.03568096 * ctem;
+        let zsinil = (1.0 - zcosil * zcosil).sqrt();
+        let zsinhl = 0.089683511 * stem / zsinil;
+        let zcoshl = (1.0 - zsinhl * zsinhl).sqrt();
+        let gam = 5.8351514 + 0.0019443680 * day;
+        let zx = 0.39785416 * stem / zsinil;
+        let zy = zcoshl * ctem + 0.91744867 * zsinhl * stem;
+        let zx = gam + zx.atan2(zy) - xnodce;
+
+        let orbit = Orbit {
+            cos_i: cosim,
+            sin_i: sinim,
+            cos_w: p.wo.cos(),
+            sin_w: p.wo.sin(),
+            e: em,
+            emsq,
+            betasq,
+            rtemsq,
+            xnoi: 1.0 / nm,
+        };
+        let solar = Terms::new(&Body {
+            cos_g: ZCOSGS,
+            sin_g: ZSINGS,
+            cos_i: ZCOSIS,
+            sin_i: ZSINIS,
+            cos_h: cnodm,
+            sin_h: snodm,
+            c: C1SS,
+        }, &orbit);
+        let lunar = Terms::new(&Body {
+            cos_g: zx.cos(),
+            sin_g: zx.sin(),
+            cos_i: zcosil,
+            sin_i: zsinil,
+            cos_h: zcoshl * cnodm + zsinhl * snodm,
+            sin_h: snodm * zcoshl - cnodm * zsinhl,
+            c: C1L,
+        }, &orbit);
+
+        let zmol = (4.7199672 + 0.22997150 * day - gam) % (2.0 * PI);
+        let zmos = (6.2565837 + 0.017201977 * day) % (2.0 * PI);
+
+        // Secular rates from the Sun
+        let (ss, s) = (&solar, &lunar);
+        let equatorial = !(EQUATORIAL..=PI - EQUATORIAL).contains(&inclm);
+        let ses = ss.s1 * ZNS * ss.s5;
+        let sis = ss.s2 * ZNS * (ss.z11 + ss.z13);
+        let sls = -ZNS * ss.s3 * (ss.z1 + ss.z3 - 14.0 - 6.0 * emsq);
+        let sghs = ss.s4 * ZNS * (ss.z31 + ss.z33 - 6.0);
+        let mut shs = if equatorial { 0.0 } else { -ZNS * ss.s2 * (ss.z21 + ss.z23) };
+        if sinim != 0.0 {
+            shs /= sinim;
+        }
+        let sgs = sghs - cosim * shs;
+
+        // And the Moon
+        let dedt = ses + s.s1 * ZNL * s.s5;
+        let didt = sis + s.s2 * ZNL * (s.z11 + s.z13);
+        let dmdt = sls - ZNL * s.s3 * (s.z1 + s.z3 - 14.0 - 6.0 * emsq);
+        let sghl = s.s4 * ZNL * (s.z31 + s.z33 - 6.0);
+        let shll = if equatorial { 0.0 } else { -ZNL * s.s2 * (s.z21 + s.z23) };
+        let mut domdt = sgs + sghl;
+        let mut dnodt = shs;
+        if sinim != 0.0 {
+            domdt -= cosim / sinim * shll;
+            dnodt += shll / sinim;
+        }
+
+        let gsto = sidereal_angle(p.epoch, ops_mode);
+        let resonance = if nm < 0.0052359877 && nm > 0.0034906585 {
+            DeepSpace::synchronous(p, gsto, dmdt, domdt, dnodt)
+        } else if (8.26e-3..=9.24e-3).contains(&nm) && em >= 0.5 {
+            DeepSpace::half_day(p, gsto, dmdt, dnodt)
+        } else {
+            Resonance::None
+        };
+
+        DeepSpace {
+            se2: 2.0 * ss.s1 * ss.s6,
+            se3: 2.0 * ss.s1 * ss.s7,
+            si2: 2.0 * ss.s2 * ss.z12,
+            si3: 2.0 * ss.s2 * (ss.z13 - ss.z11),
+            sl2: -2.0 * ss.s3 * ss.z2,
+            sl3: -2.0 * ss.s3 * (ss.z3 - ss.z1),
+            sl4: -2.0 * ss.s3 * (-21.0 - 9.0 * emsq) * ZES,
+            sgh2: 2.0 * ss.s4 * ss.z32,
+            sgh3: 2.0 * ss.s4 * (ss.z33 - ss.z31),
+            sgh4: -18.0 * ss.s4 * ZES,
+            sh2: -2.0 * ss.s2 * ss.z22,
+            sh3: -2.0 * ss.s2 * (ss.z23 - ss.z21),
+            ee2: 2.0 * s.s1 * s.s6,
+            e3: 2.0 * s.s1 * s.s7,
+            xi2: 2.0 * s.s2 * s.z12,
+            xi3: 2.0 * s.s2 * (s.z13 - s.z11),
+            xl2: -2.0 * s.s3 * s.z2,
+            xl3: -2.0 * s.s3 * (s.z3 - s.z1),
+            xl4: -2.0 * s.s3 * (-21.0 - 9.0 * emsq) * ZEL,
+            xgh2: 2.0 * s.s4 * s.z32,
+            xgh3: 2.0 * s.s4 * (s.z33 - s.z31),
+            xgh4: -18.0 * s.s4 * ZEL,
+            xh2: -2.0 * s.s2 * s.z22,
+            xh3: -2.0 * s.s2 * (s.z23 - s.z21),
+            zmos,
+            zmol,
+            dedt,
+            didt,
+            dmdt,
+            domdt,
+            dnodt,
+            gsto,
+            no: p.n0_dp,
+            argpo: p.wo,
+            argpdot: p.omega_dot,
+            resonance,
+        }
+    }
+
+    /// Resonance terms for a period near 24 hours
+    fn synchronous(p: &Propagator, gsto: f64, dmdt: f64, domdt: f64, dnodt: f64) -> Resonance {
+        let (nm, cosim, sinim) = (p.n0_dp, p.O, p.sin_io);
+        let emsq = p.e0 * p.e0;
+        let aonv = (nm / p.gravity.ke).powf(2.0 / 3.0);
+
+        let g200 = 1.0 + emsq * (-2.5 + 0.8125 * emsq);
+        let g310 = 1.0 + 2.0 * emsq;
+        let g300 = 1.0 + emsq * (-6.0 + 6.60937 * emsq);
+        let f220 = 0.75 * (1.0 + cosim) * (1.0 + cosim);
+        let f311 = 0.9375 * sinim * sinim * (1.0 + 3.0 * cosim) - 0.75 * (1.0 + cosim);
+        let f330 = 1.0 + cosim;
+        let f330 = 1.875 * f330 * f330 * f330;
+        let del1 = 3.0 * nm * nm * aonv * aonv;
+        let del2 = 2.0 * del1 * f220 * g200 * Q22;
+        let del3 = 3.0 * del1 * f330 * g300 * Q33 * aonv;
+        let del1 = del1 * f311 * g310 * Q31 * aonv;
+
+        let theta = gsto % (2.0 * PI);
+        Resonance::Synchronous {
+            del1,
+            del2,
+            del3,
+            xfact: p.M_dot + (p.omega_dot + p.Omega_dot) - RPTIM + dmdt + domdt + dnodt - p.n0_dp,
+            xlamo: (p.M0 + p.Omega0 + p.wo - theta) % (2.0 * PI),
+        }
+    }
+
+    /// Resonance terms for a period near 12 hours
+    fn half_day(p: &Propagator, gsto: f64, dmdt: f64, dnodt: f64) -> Resonance {
+        let (nm, cosim, sinim) = (p.n0_dp, p.O, p.sin_io);
+        let em = p.e0;
+        let emsq = em * em;
+        let eoc = em * emsq;
+        let cosisq = cosim * cosim;
+        let aonv = (nm / p.gravity.ke).powf(2.0 / 3.0);
+
+        let g201 = -0.306 - (em - 0.64) * 0.440;
+        let (g211, g310, g322, g410, g422, g520) = if em <= 0.65 {
+            (
+                3.616 - 13.2470 * em + 16.2900 * emsq,
+                -19.302 + 117.3900 * em - 228.4190 * emsq + 156.5910 * eoc,
+                -18.9068 + 109.7927 * em - 214.6334 * emsq + 146.5816 * eoc,
+                -41.122 + 242.6940 * em - 471.0940 * emsq + 313.9530 * eoc,
+                -146.407 + 841.8800 * em - 1629.014 * emsq + 1083.4350 * eoc,
+                -532.114 + 3017.977 * em - 5740.032 * emsq + 3708.2760 * eoc,
+            )
+        } else {
+            (
+                -72.099 + 331.819 * em - 508.738 * emsq + 266.724 * eoc,
+                -346.844 + 1582.851 * em - 2415.925 * emsq + 1246.113 * eoc,
+                -342.585 + 1554.908 * em - 2366.899 * emsq + 1215.972 * eoc,
+                -1052.797 + 4758.686 * em - 7193.992 * emsq + 3651.957 * eoc,
+                -3581.690 + 16178.110 * em - 24462.770 * emsq + 12422.520 * eoc,
+                if em > 0.715 {
+                    -5149.66 + 29936.92 * em - 54087.36 * emsq + 31324.56 * eoc
+                } else {
+                    1464.74 - 4664.75 * em + 3763.64 * emsq
+                },
+            )
+        };
+        let (g533, g521, g532) = if em < 0.7 {
+            (
+                -919.22770 + 4988.6100 * em - 9064.7700 * emsq + 5542.21 * eoc,
+                -822.71072 + 4568.6173 * em - 8491.4146 * emsq + 5337.524 * eoc,
+                -853.66600 + 4690.2500 * em - 8624.7700 * emsq + 5341.4 * eoc,
+            )
+        } else {
+            (
+                -37995.780 + 161616.52 * em - 229838.20 * emsq + 109377.94 * eoc,
+                -51752.104 + 218913.95 * em - 309468.16 * emsq + 146349.42 * eoc,
+                -40023.880 + 170470.89 * em - 242699.48 * emsq + 115605.82 * eoc,
+            )
+        };
+
+        let sini2 = sinim * sinim;
+        let f220 = 0.75 * (1.0 + 2.0 * cosim + cosisq);
+        let f221 = 1.5 * sini2;
+        let f321 = 1.875 * sinim * (1.0 - 2.0 * cosim - 3.0 * cosisq);
+        let f322 = -1.875 * sinim * (1.0 + 2.0 * cosim - 3.0 * cosisq);
+        let f441 = 35.0 * sini2 * f220;
+        let f442 = 39.3750 * sini2 * sini2;
+        let f522 = 9.84375 * sinim * (sini2 * (1.0 - 2.0 * cosim - 5.0 * cosisq)
+            + 0.33333333 * (-2.0 + 4.0 * cosim + 6.0 * cosisq));
+        let f523 = sinim * (4.92187512 * sini2 * (-2.0 - 4.0 * cosim + 10.0 * cosisq)
+            + 6.56250012 * (1.0 + 2.0 * cosim - 3.0 * cosisq));
+        let f542 = 29.53125 * sinim * (2.0 - 8.0 * cosim + cosisq * (-12.0 + 8.0 * cosim + 10.0 * cosisq));
+        let f543 = 29.53125 * sinim * (-2.0 - 8.0 * cosim + cosisq * (12.0 + 8.0 * cosim - 10.0 * cosisq));
+
+        let xno2 = nm * nm;
+        let ainv2 = aonv * aonv;
+        let temp1 = 3.0 * xno2 * ainv2;
+        let temp = temp1 * ROOT22;
+        let d2201 = temp * f220 * g201;
+        let d2211 = temp * f221 * g211;
+        let temp1 = temp1 * aonv;
+        let temp = temp1 * ROOT32;
+        let d3210 = temp * f321 * g310;
+        let d3222 = temp * f322 * g322;
+        let temp1 = temp1 * aonv;
+        let temp = 2.0 * temp1 * ROOT44;
+        let d4410 = temp * f441 * g410;
+        let d4422 = temp * f442 * g422;
+        let temp1 = temp1 * aonv;
+        let temp = temp1 * ROOT52;
+        let d5220 = temp * f522 * g520;
+        let d5232 = temp * f523 * g532;
+        let temp = 2.0 * temp1 * ROOT54;
+        let d5421 = temp * f542 * g521;
+        let d5433 = temp * f543 * g533;
+
+        let theta = gsto % (2.0 * PI);
+        Resonance::HalfDay {
+            d2201,
+            d2211,
+            d3210,
+            d3222,
+            d4410,
+            d4422,
+            d5220,
+            d5232,
+            d5421,
+            d5433,
+            xfact: p.M_dot + dmdt + 2.0 * (p.Omega_dot + dnodt - RPTIM) - p.n0_dp,
+            xlamo: (p.M0 + p.Omega0 + p.Omega0 - theta - theta) % (2.0 * PI),
+        }
+    }
+
+    /// ## Secular
+    ///
+    /// Add the secular effects of the Sun and Moon to the mean elements
+    /// `time` minutes after the epoch, and integrate any resonance
+    /// (`dspace`) on from `state`. Returns the mean motion
+    /// (radians/minute), which only resonance changes.
+    pub(crate) fn secular(&self, time: f64, mean: &mut Mean, state: &mut ResonanceState) -> f64 {
+        let no = self.no;
+        mean.e += self.dedt * time;
+        mean.i += self.didt * time;
+        mean.omega += self.domdt * time;
+        mean.Omega += self.dnodt * time;
+        mean.M += self.dmdt * time;
+
+        let xlamo = match self.resonance {
+            Resonance::None => return no,
+            Resonance::Synchronous { xlamo, .. } | Resonance::HalfDay { xlamo, .. } => xlamo,
+        };
+
+        // Start again from the epoch for another propagator, the other side
+        // of the epoch or an earlier time, as the reference does
+        if state.no != no || state.xlamo != xlamo || time * state.atime < 0.0 || time.abs() < state.atime.abs() {
+            *state = ResonanceState { atime: 0.0, xni: no, xli: xlamo, no, xlamo };
+        }
+
+        // Euler-Maclaurin integration in whole steps toward `time`, then a
+        // Taylor series for the rest
+        let theta = (self.gsto + time * RPTIM) % (2.0 * PI);
+        let delt = if time > 0.0 { STEP } else { -STEP };
+        let (xndt, xldot, xnddt, ft) = loop {
+            let (xndt, xldot, xnddt) = self.resonance_rates(state.atime, state.xli, state.xni);
+            if (time - state.atime).abs() < STEP {
+                break (xndt, xldot, xnddt, time - state.atime);
+            }
+            state.xli += xldot * delt + xndt * STEP2;
+            state.xni += xndt * delt + xnddt * STEP2;
+            state.atime += delt;
+        };
+
+        let nm = state.xni + xndt * ft + xnddt * ft * ft * 0.5;
+        let xl = state.xli + xldot * ft + xndt * ft * ft * 0.5;
+        mean.M = match self.resonance {
+            Resonance::Synchronous { .. } => xl - mean.Omega - mean.omega + theta,
+            _ => xl - 2.0 * mean.Omega + 2.0 * theta,
+        };
+        no + (nm - no)
+    }
+
+    /// Rates of the mean motion and resonant angle, and the mean motion's
+    /// second derivative, at `atime` minutes from the epoch
+    fn resonance_rates(&self, atime: f64, xli: f64, xni: f64) -> (f64, f64, f64) {
+        match self.resonance {
+            Resonance::None => (0.0, 0.0, 0.0),
+            Resonance::Synchronous { del1, del2, del3, xfact, .. } => {
+                let xndt = del1 * (xli - FASX2).sin()
+                    + del2 * (2.0 * (xli - FASX4)).sin()
+                    + del3 * (3.0 * (xli - FASX6)).sin();
+                let xldot = xni + xfact;
+                let xnddt = del1 * (xli - FASX2).cos()
+                    + 2.0 * del2 * (2.0 * (xli - FASX4)).cos()
+                    + 3.0 * del3 * (3.0 * (xli - FASX6)).cos();
+                (xndt, xldot, xnddt * xldot)
+            }
+            Resonance::HalfDay { d2201, d2211, d3210, d3222, d4410, d4422, d5220, d5232, d5421, d5433, xfact, .. } => {
+                let xomi = self.argpo + self.argpdot * atime;
+                let x2omi = xomi + xomi;
+                let x2li = xli + xli;
+                let xndt = d2201 * (x2omi + xli - G22).sin() + d2211 * (xli - G22).sin()
+                    + d3210 * (xomi + xli - G32).sin() + d3222 * (-xomi + xli - G32).sin()
+                    + d4410 * (x2omi + x2li - G44).sin() + d4422 * (x2li - G44).sin()
+                    + d5220 * (xomi + xli - G52).sin() + d5232 * (-xomi + xli - G52).sin()
+                    + d5421 * (xomi + x2li - G54).sin() + d5433 * (-xomi + x2li - G54).sin();
+                let xldot = xni + xfact;
+                let xnddt = d2201 * (x2omi + xli - G22).cos() + d2211 * (xli - G22).cos()
+                    + d3210 * (xomi + xli - G32).cos() + d3222 * (-xomi + xli - G32).cos()
+                    + d5220 * (xomi + xli - G52).cos() + d5232 * (-xomi + xli - G52).cos()
+                    + 2.0 * (d4410 * (x2omi + x2li - G44).cos() + d4422 * (x2li - G44).cos()
+                        + d5421 * (xomi + x2li - G54).cos() + d5433 * (-xomi + x2li - G54).cos());
+                (xndt, xldot, xnddt * xldot)
+            }
+        }
+    }
+
+    /// ## Periodics
+    ///
+    /// Add the long period effects of the Sun and Moon to the secularly
+    /// updated elements (`dpper`). Below 0.2 radians of inclination they
+    /// are applied with Lyddane's modification, which avoids dividing by
+    /// the sine of the inclination.
+    pub(crate) fn periodics(&self, time: f64, ops_mode: OpsMode, secular: Secular<f64>) -> Result<Secular<f64>, PropagationError> {
+        let twopi = 2.0 * PI;
+        let Omega = secular.Omega % twopi;
+        let omega = secular.omega % twopi;
+        let IL = secular.IL % twopi;
+        let M = (IL - omega - Omega) % twopi;
+
+        // Solar terms
+        let zm = self.zmos + ZNS * time;
+        let zf = zm + 2.0 * ZES * zm.sin();
+        let sinzf = zf.sin();
+        let f2 = 0.5 * sinzf * sinzf - 0.25;
+        let f3 = -0.5 * sinzf * zf.cos();
+        let ses = self.se2 * f2 + self.se3 * f3;
+        let sis = self.si2 * f2 + self.si3 * f3;
+        let sls = self.sl2 * f2 + self.sl3 * f3 + self.sl4 * sinzf;
+        let sghs = self.sgh2 * f2 + self.sgh3 * f3 + self.sgh4 * sinzf;
+        let shs = self.sh2 * f2 + self.sh3 * f3;
+
+        // Lunar terms
+        let zm = self.zmol + ZNL * time;
+        let zf = zm + 2.0 * ZEL * zm.sin();
+        let sinzf = zf.sin();
+        let f2 = 0.5 * sinzf * sinzf - 0.25;
+        let f3 = -0.5 * sinzf * zf.cos();
+        let sel = self.ee2 * f2 + self.e3 * f3;
+        let sil = self.xi2 * f2 + self.xi3 * f3;
+        let sll = self.xl2 * f2 + self.xl3 * f3 + self.xl4 * sinzf;
+        let sghl = self.xgh2 * f2 + self.xgh3 * f3 + self.xgh4 * sinzf;
+        let shll = self.xh2 * f2 + self.xh3 * f3;
+
+        let pe = ses + sel;
+        let pinc = sis + sil;
+        let pl = sls + sll;
+        let pgh = sghs + sghl;
+        let ph = shs + shll;
+
+        let mut i = secular.i + pinc;
+        let e = secular.e + pe;
+        let sinip = i.sin();
+        let cosip = i.cos();
+
+        let (mut Omega, mut omega, M) = if i >= LYDDANE {
+            let ph = ph / sinip;
+            let pgh = pgh - cosip * ph;
+            (Omega + ph, omega + pgh, M + pl)
+        } else {
+            // Lyddane's modification: perturb the node through its
+            // direction, and the perigee through the mean longitude
+            let sinop = Omega.sin();
+            let cosop = Omega.cos();
+            let alfdp = sinip * sinop + (ph * cosop + pinc * cosip * sinop);
+            let betdp = sinip * cosop + (-ph * sinop + pinc * cosip * cosop);
+            let mut nodep = Omega % twopi;
+            if nodep < 0.0 && ops_mode == OpsMode::Afspc {
+                nodep += twopi;
+            }
+            let xls = M + omega + cosip * nodep + (pl + pgh - pinc * nodep * sinip);
+            let xnoh = nodep;
+            nodep = alfdp.atan2(betdp);
+            if nodep < 0.0 && ops_mode == OpsMode::Afspc {
+                nodep += twopi;
+            }
+            if (xnoh - nodep).abs() > PI {
+                if nodep < xnoh {
+                    nodep += twopi;
+                } else {
+                    nodep -= twopi;
+                }
+            }
+            let mp = M + pl;
+            (nodep, xls - mp - cosip * nodep, mp)
+        };
+
+        if i < 0.0 {
+            i = -i;
+            Omega += PI;
+            omega -= PI;
+        }
+        if !(0.0..=1.0).contains(&e) {
+            return Err(PropagationError::Diverged);
+        }
+
+        Ok(Secular {
+            Omega,
+            omega,
+            e,
+            i,
+            IL: M + omega + Omega,
+            B: (1.0 - e.powi(2)).sqrt(),
+            M,
+            ..secular
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tle;
+    use test_support;
+
+    #[test]
+    fn sidereal_angle_modes() {
+        // The two agree to well under a millidegree
+        let jd = ::time::julian_date(2006, 6, 25.0);
+        let difference = sidereal_angle(jd, OpsMode::Afspc) - sidereal_angle(jd, OpsMode::Improved);
+        assert!(difference.abs() < 1e-5, "{}", difference);
+    }
+
+    #[test]
+    fn resonance() {
+        let geo = test_support::geostationary();
+        let molniya = test_support::molniya();
+        let gto = test_support::spacetrack_report_3_sdp4();
+
+        let deep = |tle: &tle::TLE| Propagator::new(tle).unwrap().deep_space.unwrap();
+        match deep(&geo).resonance {
+            Resonance::Synchronous { .. } => {}
+            other => panic!("{:?}", other),
+        }
+        match deep(&molniya).resonance {
+            Resonance::HalfDay { .. } => {}
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(deep(&gto).resonance, Resonance::None);
+
+        // Both resonant cases start where the reference code (tcppver.out)
+        // has them
+        let molniya_state = Propagator::with_ops_mode(&molniya, OpsMode::Afspc).unwrap().propagate(0.0).unwrap();
+        let expected = [2349.89483350, -14785.93811562, 0.02119378, 2.721488096, -3.256811655, 4.498416672];
+        let actual = [
+            molniya_state.position.X, molniya_state.position.Y, molniya_state.position.Z,
+            molniya_state.velocity.X, molniya_state.velocity.Y, molniya_state.velocity.Z,
+        ];
+        for (actual, expected) in actual.iter().zip(expected.iter()) {
+            assert!((actual - expected).abs() < 1e-6, "{:?}", actual);
+        }
+        let geo_state = Propagator::with_ops_mode(&geo, OpsMode::Afspc).unwrap().propagate(0.0).unwrap();
+        let expected = [0.193105177, 3.068688251, 0.000438449];
+        let actual = [geo_state.velocity.X, geo_state.velocity.Y, geo_state.velocity.Z];
+        for (actual, expected) in actual.iter().zip(expected.iter()) {
+            assert!((actual - expected).abs() < 1e-8, "{:?}", actual);
+        }
+    }
+
+    #[test]
+    fn propagate_with() {
+        let geo = Propagator::new(&test_support::geostationary()).unwrap();
+        let molniya = Propagator::new(&test_support::molniya()).unwrap();
+
+        // Out past a step, two and many, part way into steps, back toward
+        // the epoch, across it, and over to another propagator
+        let times = [0.0, 100.0, 720.0, 1000.0, 1440.0, 1440.0, 2000.0, 30.0 * 1440.0 + 7.0, 500.0, -1500.0, -800.0, 3000.0];
+        let mut resonance = ResonanceState::default();
+        for propagator in [&geo, &molniya, &geo].iter() {
+            for &time in times.iter() {
+                assert_eq!(propagator.propagate_with(&mut resonance, time), propagator.propagate(time), "t={}", time);
+            }
+        }
+
+        // The state carries on from the last whole step
+        let mut resonance = ResonanceState::default();
+        molniya.propagate_with(&mut resonance, 1000.0).unwrap();
+        assert_eq!(resonance.atime, 720.0);
+        molniya.propagate_with(&mut resonance, 3000.0).unwrap();
+        assert_eq!(resonance.atime, 2880.0);
+        molniya.propagate_with(&mut resonance, -100.0).unwrap();
+        assert_eq!(resonance.atime, 0.0);
+    }
+}
@@ -0,0 +1,372 @@
+/*!  # Drag
+
+The drag an object has actually felt, from a history of its TLEs.
+
+A TLE's B* is whatever value made its own fit work, and on its own it can
+be far off: negative, or absorbing errors that have nothing to do with
+drag. The decay between successive element sets is more trustworthy. For
+each consecutive pair, [`drag_history`](fn.drag_history.html) finds the
+*effective* B* that, propagated from the earlier set, lowers the SGP4 mean
+semi-major axis to the later set's, and compares it with the B* that was
+published. A trend through the effective values shows how the drag is
+changing with solar activity or attitude, for reentry studies, and the
+pairs where the published B* disagrees are worth treating with suspicion.
+
+[`ballistic_coefficient`](fn.ballistic_coefficient.html) turns B* into the
+more physical $C_D A / m$.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::error;
+use std::fmt;
+
+use elements::Elements;
+use tle::TLE;
+use {PropagationError, Propagator, MINUTES_PER_DAY};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// $\rho_o$, the reference atmospheric density B* is scaled by, times an
+/// Earth radius (kilograms/meter²/Earth radius)
+pub const REFERENCE_DENSITY: f64 = 0.15696615;
+
+/// How closely the effective B* reproduces the later semi-major axis
+/// (kilometers)
+const TOLERANCE: f64 = 1.0e-6;
+
+/// Most secant steps to find the effective B*
+const ITERATIONS: usize = 20;
+
+/// ## Ballistic Coefficient
+///
+/// $C_D A / m$ (meters²/kilogram) for a B* (per Earth radius), by
+/// $B^* = \rho_o C_D A / 2m$.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// // The ISS, about 0.005 m²/kg
+/// let coefficient = sgp4::drag::ballistic_coefficient(0.11901e-4);
+/// assert!((coefficient - 1.5e-4).abs() < 1e-5);
+/// ```
+pub fn ballistic_coefficient(bstar: f64) -> f64 {
+    2.0 * bstar / REFERENCE_DENSITY
+}
+
+/// ## Drag Estimate
+///
+/// The drag between two successive element sets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DragEstimate {
+
+    /// Epoch of the earlier element set (Julian date, UTC)
+    pub start: f64,
+
+    /// Epoch of the later element set (Julian date, UTC)
+    pub end: f64,
+
+    /// Change in the SGP4 mean semi-major axis (kilometers/day, negative
+    /// while decaying)
+    pub decay_rate: f64,
+
+    /// B* that takes the earlier element set's mean semi-major axis to
+    /// the later one's
+    pub bstar: f64,
+
+    /// B* the earlier element set was published with
+    pub published: f64,
+}
+
+impl DragEstimate {
+
+    /// Midpoint of the interval (Julian date, UTC)
+    pub fn epoch(&self) -> f64 {
+        0.5 * (self.start + self.end)
+    }
+
+    /// Whether the published B* is implausible against the effective one:
+    /// of the other sign, or more than `factor` times larger or smaller
+    pub fn is_anomalous(&self, factor: f64) -> bool {
+        let ratio = self.published / self.bstar;
+        !(1.0 / factor..=factor).contains(&ratio)
+    }
+}
+
+/// ## Drag History
+///
+/// Effective B* over a series of element sets, with a straight line
+/// through it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DragHistory {
+
+    /// One estimate per consecutive pair of element sets
+    pub estimates: Vec<DragEstimate>,
+
+    /// Mean effective B*, weighted by the length of each interval
+    pub mean_bstar: f64,
+
+    /// Trend in the effective B* (per day), by least squares
+    pub bstar_rate: f64,
+}
+
+impl DragHistory {
+
+    /// Effective B* on the trend line at a Julian date
+    pub fn bstar_at(&self, julian_date: f64) -> f64 {
+        self.mean_bstar + self.bstar_rate * (julian_date - self.mean_epoch())
+    }
+
+    /// The interval weighted mean epoch, which the trend line pivots on
+    fn mean_epoch(&self) -> f64 {
+        let (sum, weight) = self.estimates.iter()
+            .fold((0.0, 0.0), |(sum, weight), estimate| {
+                let length = estimate.end - estimate.start;
+                (sum + estimate.epoch() * length, weight + length)
+            });
+        sum / weight
+    }
+
+    /// The estimates whose published B* is anomalous, as in
+    /// [`DragEstimate::is_anomalous`](struct.DragEstimate.html#method.is_anomalous)
+    pub fn anomalies<'a>(&'a self, factor: f64) -> impl Iterator<Item = &'a DragEstimate> + 'a {
+        self.estimates.iter().filter(move |estimate| estimate.is_anomalous(factor))
+    }
+}
+
+/// ## Drag Errors
+///
+/// Why a drag history couldn't be worked out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DragError {
+
+    /// Fewer than two element sets
+    TooFewTles,
+
+    /// The element sets are for more than one satellite
+    MixedObjects,
+
+    /// An element set's epoch isn't after the one before it
+    Unordered {
+        /// Index of the element set out of order
+        index: usize,
+    },
+
+    /// An element set couldn't be propagated over its interval
+    Propagation {
+        /// Index of the element set
+        index: usize,
+        /// What went wrong
+        error: PropagationError,
+    },
+}
+
+impl fmt::Display for DragError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DragError::TooFewTles => write!(f, "need at least two element sets"),
+            DragError::MixedObjects => write!(f, "element sets are for more than one satellite"),
+            DragError::Unordered { index } => write!(f, "element set {} isn't after the one before it", index),
+            DragError::Propagation { index, error } => write!(f, "element set {}: {}", index, error),
+        }
+    }
+}
+
+impl error::Error for DragError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            DragError::Propagation { ref error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// The drag between an element set and the next
+fn estimate(earlier: &TLE, later: &TLE) -> Result<DragEstimate, PropagationError> {
+    let start = earlier.epoch_julian_date();
+    let end = later.epoch_julian_date();
+    let time = (end - start) * MINUTES_PER_DAY;
+
+    let initial = Propagator::new(earlier)?.mean_elements(0.0)?.semi_major_axis;
+    let observed = Propagator::new(later)?.mean_elements(0.0)?.semi_major_axis;
+
+    // How far the later semi-major axis is missed with a B*
+    let mut elements = Elements::from_tle(earlier);
+    let mut miss = |bstar: f64| -> Result<f64, PropagationError> {
+        elements.bstar = bstar;
+        let propagator = Propagator::from_elements(&elements)?.at_epoch(start);
+        Ok(propagator.mean_elements(time)?.semi_major_axis - observed)
+    };
+
+    // The semi-major axis falls very nearly linearly with B*, so the
+    // secant method converges in a step or two
+    let (mut b0, mut f0) = (0.0, miss(0.0)?);
+    let mut b1 = if earlier.bstar > 0.0 { earlier.bstar } else { 1.0e-4 };
+    let mut f1 = miss(b1)?;
+    for _ in 0..ITERATIONS {
+        if f1.abs() <= TOLERANCE || f1 == f0 {
+            break;
+        }
+        let b2 = b1 - f1 * (b1 - b0) / (f1 - f0);
+        b0 = b1;
+        f0 = f1;
+        b1 = b2;
+        f1 = miss(b1)?;
+    }
+
+    Ok(DragEstimate {
+        start,
+        end,
+        decay_rate: (observed - initial) / (end - start),
+        bstar: b1,
+        published: earlier.bstar,
+    })
+}
+
+/// ## Drag History
+///
+/// The effective B* between each consecutive pair of a satellite's element
+/// sets, in epoch order, and its trend.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tles = sgp4::tle::parse_all("\
+/// 1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+/// 2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+/// 1 25544U 98067A   16213.52148064  .00000747  00000-0  11166-4 0  9990
+/// 2 25544  51.6420 196.8262 0001893  94.7452 337.9786 15.54932474 11870
+/// ").unwrap();
+///
+/// let history = sgp4::drag::drag_history(&tles).unwrap();
+/// for estimate in &history.estimates {
+///     println!("{:.4e} published {:.4e}", estimate.bstar, estimate.published);
+/// }
+/// ```
+pub fn drag_history(tles: &[TLE]) -> Result<DragHistory, DragError> {
+    if tles.len() < 2 {
+        return Err(DragError::TooFewTles);
+    }
+    if tles.iter().any(|tle| tle.sat_number != tles[0].sat_number) {
+        return Err(DragError::MixedObjects);
+    }
+
+    let mut estimates = Vec::with_capacity(tles.len() - 1);
+    for (index, pair) in tles.windows(2).enumerate() {
+        if pair[1].epoch_julian_date() <= pair[0].epoch_julian_date() {
+            return Err(DragError::Unordered { index: index + 1 });
+        }
+        let estimate = estimate(&pair[0], &pair[1])
+            .map_err(|error| DragError::Propagation { index, error })?;
+        estimates.push(estimate);
+    }
+
+    // Weighted least squares line through the effective B*, each interval
+    // weighted by its length
+    let weight = |estimate: &DragEstimate| estimate.end - estimate.start;
+    let total: f64 = estimates.iter().map(weight).sum();
+    let mean_epoch = estimates.iter().map(|estimate| weight(estimate) * estimate.epoch()).sum::<f64>() / total;
+    let mean_bstar = estimates.iter().map(|estimate| weight(estimate) * estimate.bstar).sum::<f64>() / total;
+    let (covariance, variance) = estimates.iter().fold((0.0, 0.0), |(covariance, variance), estimate| {
+        let dt = estimate.epoch() - mean_epoch;
+        (covariance + weight(estimate) * dt * (estimate.bstar - mean_bstar), variance + weight(estimate) * dt * dt)
+    });
+    let bstar_rate = if variance > 0.0 { covariance / variance } else { 0.0 };
+
+    Ok(DragHistory { estimates, mean_bstar, bstar_rate })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::iss;
+
+    /// The element set `days` on from `tle` if it decayed with `bstar`,
+    /// published with B* `published`
+    fn later(tle: &TLE, days: f64, bstar: f64, published: f64) -> TLE {
+        let mut elements = Elements::from_tle(tle);
+        elements.bstar = bstar;
+        let propagator = Propagator::from_elements(&elements).unwrap().at_epoch(tle.epoch_julian_date());
+        let mean = propagator.mean_elements(days * MINUTES_PER_DAY).unwrap();
+        let elements = Elements::from_keplerian(
+            mean.semi_major_axis,
+            mean.eccentricity,
+            mean.inclination,
+            mean.raan,
+            mean.argp,
+            mean.mean_anomaly,
+            published,
+        ).unwrap();
+        TLE {
+            epoch_day: tle.epoch_day + days,
+            i: elements.inclination.to_degrees(),
+            raan: elements.raan.to_degrees(),
+            e: elements.eccentricity,
+            omega: elements.argp.to_degrees(),
+            mean_anomaly: elements.mean_anomaly.to_degrees(),
+            mean_motion: elements.revolutions_per_day(),
+            bstar: published,
+            ..tle.clone()
+        }
+    }
+
+    #[test]
+    fn recovers_bstar() {
+        // Drag doubling over three weeks
+        let mut tles = vec![iss()];
+        for week in 1..=3 {
+            let bstar = 1.0e-5 * (1.0 + 0.5 * f64::from(week - 1));
+            let next = later(tles.last().unwrap(), 7.0, bstar, bstar);
+            tles.last_mut().unwrap().bstar = bstar;
+            tles.push(next);
+        }
+
+        let history = drag_history(&tles).unwrap();
+        assert_eq!(history.estimates.len(), 3);
+        for (estimate, tle) in history.estimates.iter().zip(tles.iter()) {
+            assert!((estimate.bstar - tle.bstar).abs() < 1e-3 * tle.bstar, "{:?}", estimate);
+            assert!(estimate.decay_rate < 0.0);
+            assert!(!estimate.is_anomalous(2.0));
+        }
+        assert!((history.bstar_rate * 7.0 - 0.5e-5).abs() < 1e-8, "{}", history.bstar_rate);
+        assert!((history.bstar_at(tles[0].epoch_julian_date() + 3.5) - 1.0e-5).abs() < 1e-8);
+        assert_eq!(history.anomalies(2.0).count(), 0);
+    }
+
+    #[test]
+    fn anomalies() {
+        let first = iss();
+        let second = later(&first, 3.0, first.bstar, -1.0e-4);
+        let third = later(&second, 3.0, first.bstar, first.bstar);
+        let history = drag_history(&[first, second, third]).unwrap();
+
+        let anomalies: Vec<_> = history.anomalies(3.0).collect();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].published, -1.0e-4);
+        assert!(anomalies[0].bstar > 0.0);
+    }
+
+    #[test]
+    fn problems() {
+        let tle = iss();
+        assert_eq!(drag_history(std::slice::from_ref(&tle)), Err(DragError::TooFewTles));
+        assert_eq!(drag_history(&[tle.clone(), tle.clone()]), Err(DragError::Unordered { index: 1 }));
+
+        let other = TLE { sat_number: 11801, ..later(&tle, 1.0, tle.bstar, tle.bstar) };
+        assert_eq!(drag_history(&[tle, other]), Err(DragError::MixedObjects));
+    }
+}
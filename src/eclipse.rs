@@ -0,0 +1,333 @@
+/*!  # Eclipses
+
+When a satellite passes through the Earth's shadow: the times it enters
+and leaves the penumbra, where the Earth covers part of the Sun, and the
+umbra, where it covers all of it, for each eclipse over a window of time.
+
+The shadow is the conical one of [`sun::illumination`](../sun/fn.illumination.html).
+How far the Sun's disk is clear of the Earth's is sampled on a coarse grid
+to find the eclipses, and each entry and exit is refined by bisection.
+An eclipse shorter than the grid step can fall between samples and be
+missed, as can an umbra grazed for less than a step; the default one
+minute step catches every eclipse of a low Earth orbit but for a few
+seconds of the year, at the edges of eclipse season.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use sun::{self, shadow_margins};
+use tle::TLE;
+use {PropagationError, Propagator, MINUTES_PER_DAY};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// ## Eclipse
+///
+/// One pass of a satellite through the Earth's shadow. Times are minutes
+/// since the TLE epoch.
+///
+/// An eclipse already under way at the start of the search window enters
+/// the shadow at the start of the window, and one still under way at the
+/// end leaves it at the end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Eclipse {
+
+    /// Time the Earth starts to cover the Sun
+    pub penumbra_entry: f64,
+
+    /// Time the Earth covers all of the Sun, if it does
+    pub umbra_entry: Option<f64>,
+
+    /// Time the Sun starts to show again, if it was covered
+    pub umbra_exit: Option<f64>,
+
+    /// Time the whole Sun shows again
+    pub penumbra_exit: f64,
+}
+
+impl Eclipse {
+
+    /// Time in any shadow (minutes)
+    pub fn duration(&self) -> f64 {
+        self.penumbra_exit - self.penumbra_entry
+    }
+
+    /// Time in total shadow (minutes)
+    pub fn umbra_duration(&self) -> f64 {
+        match (self.umbra_entry, self.umbra_exit) {
+            (Some(entry), Some(exit)) => exit - entry,
+            _ => 0.0,
+        }
+    }
+}
+
+/// ## Eclipse Options
+///
+/// How finely to search for eclipses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EclipseOptions {
+
+    /// Spacing of the coarse samples (minutes, default 1)
+    pub step: f64,
+
+    /// How closely to locate entries and exits (minutes, default 0.0001,
+    /// or about 6 milliseconds)
+    pub tolerance: f64,
+}
+
+impl Default for EclipseOptions {
+    fn default() -> EclipseOptions {
+        EclipseOptions {
+            step: 1.0,
+            tolerance: 1.0e-4,
+        }
+    }
+}
+
+/// The shadow a propagated satellite is in
+struct Shadow<'a> {
+    propagator: &'a Propagator,
+    options: &'a EclipseOptions,
+}
+
+impl<'a> Shadow<'a> {
+
+    /// How far the Sun is clear of the Earth: negative in any shadow, and
+    /// negative in the umbra
+    fn margins(&self, time: f64) -> Result<(f64, f64), PropagationError> {
+        let state = self.propagator.propagate(time)?;
+        let sun = sun::position(self.propagator.epoch() + time / MINUTES_PER_DAY);
+        Ok(shadow_margins(&state.position, &sun))
+    }
+
+    /// Where a margin crosses zero between `clear` and `covered`
+    fn crossing<F>(&self, margin: F, mut clear: f64, mut covered: f64) -> Result<f64, PropagationError>
+    where F: Fn((f64, f64)) -> f64 {
+        while (covered - clear).abs() > self.options.tolerance {
+            let middle = 0.5 * (clear + covered);
+            if margin(self.margins(middle)?) < 0.0 {
+                covered = middle;
+            } else {
+                clear = middle;
+            }
+        }
+        Ok(0.5 * (clear + covered))
+    }
+
+    /// Complete an eclipse from its penumbra entry and exit and the umbra
+    /// margin at the coarse samples in between
+    fn eclipse(&self, entry: f64, exit: f64, samples: &[(f64, f64)], start: f64, end: f64) -> Result<Eclipse, PropagationError> {
+        let umbra = |margins: (f64, f64)| margins.1;
+        let first = samples.iter().position(|&(_, margin)| margin <= 0.0);
+        let last = samples.iter().rposition(|&(_, margin)| margin <= 0.0);
+
+        let (umbra_entry, umbra_exit) = match (first, last) {
+            (Some(first), Some(last)) => {
+                let (time, _) = samples[first];
+                let umbra_entry = if time == start {
+                    time
+                } else {
+                    let before = if first == 0 { entry } else { samples[first - 1].0 };
+                    self.crossing(umbra, before, time)?
+                };
+                let (time, _) = samples[last];
+                let umbra_exit = if time == end {
+                    time
+                } else {
+                    let after = samples.get(last + 1).map_or(exit, |&(time, _)| time);
+                    self.crossing(umbra, after, time)?
+                };
+                (Some(umbra_entry), Some(umbra_exit))
+            }
+            _ => (None, None),
+        };
+
+        Ok(Eclipse {
+            penumbra_entry: entry,
+            umbra_entry,
+            umbra_exit,
+            penumbra_exit: exit,
+        })
+    }
+}
+
+/// ## Find Eclipses
+///
+/// Every eclipse of a satellite between `start` and `end` (minutes since
+/// the TLE epoch). The propagator's epoch dates them, to know where the Sun
+/// is.
+///
+/// ### Errors
+///
+/// Stops at the first time the orbit can't be propagated.
+///
+/// ### Panics
+///
+/// If `options.step` or `options.tolerance` isn't positive.
+pub fn find_eclipses(propagator: &Propagator, start: f64, end: f64, options: &EclipseOptions) -> Result<Vec<Eclipse>, PropagationError> {
+    assert!(options.step > 0.0, "eclipse search step must be positive");
+    assert!(options.tolerance > 0.0, "eclipse search tolerance must be positive");
+
+    let shadow = Shadow { propagator, options };
+    let penumbra = |margins: (f64, f64)| margins.0;
+    let mut eclipses = Vec::new();
+    if end < start {
+        return Ok(eclipses);
+    }
+
+    // Sample on the same grid as `Propagator::sample`, plus the end itself
+    let count = ((end - start) / options.step + 1.0e-9).floor() as usize + 1;
+    let mut times: Vec<f64> = (0..count).map(|index| start + options.step * index as f64).collect();
+    if times.last().is_some_and(|&last| last < end) {
+        times.push(end);
+    }
+
+    // The entry of the eclipse under way, and the umbra margin at each
+    // sample in it
+    let mut current: Option<f64> = None;
+    let mut samples: Vec<(f64, f64)> = Vec::new();
+    let mut previous: Option<f64> = None;
+
+    for time in times {
+        let margins = shadow.margins(time)?;
+        let covered = penumbra(margins) < 0.0;
+
+        current = match (current, previous) {
+            (None, _) if !covered => None,
+            (None, None) => Some(start),
+            (None, Some(before)) => Some(shadow.crossing(penumbra, before, time)?),
+            (Some(entry), Some(before)) if !covered => {
+                let exit = shadow.crossing(penumbra, time, before)?;
+                eclipses.push(shadow.eclipse(entry, exit, &samples, start, end)?);
+                samples.clear();
+                None
+            }
+            (Some(entry), _) => Some(entry),
+        };
+        if current.is_some() {
+            samples.push((time, margins.1));
+        }
+
+        previous = Some(time);
+    }
+
+    if let Some(entry) = current {
+        eclipses.push(shadow.eclipse(entry, end, &samples, start, end)?);
+    }
+
+    Ok(eclipses)
+}
+
+/// ## Eclipses
+///
+/// Every eclipse of a TLE between `start` and `end` (minutes since the TLE
+/// epoch), with the default `EclipseOptions`.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // The next day, about one eclipse an orbit
+/// for eclipse in sgp4::eclipse::eclipses(&tle, 0.0, 1440.0).unwrap() {
+///     println!("shadow {:.2} to {:.2}, {:.1} minutes in the umbra",
+///              eclipse.penumbra_entry, eclipse.penumbra_exit, eclipse.umbra_duration());
+/// }
+/// ```
+pub fn eclipses(tle: &TLE, start: f64, end: f64) -> Result<Vec<Eclipse>, PropagationError> {
+    let propagator = Propagator::new(tle)?;
+    find_eclipses(&propagator, start, end, &EclipseOptions::default())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sun::Illumination;
+    use test_support::iss;
+
+    fn illumination(propagator: &Propagator, epoch: f64, time: f64) -> Illumination {
+        let state = propagator.propagate(time).unwrap();
+        sun::illumination(&state, &sun::position(epoch + time / MINUTES_PER_DAY))
+    }
+
+    #[test]
+    fn iss_day() {
+        let tle = iss();
+        let propagator = Propagator::new(&tle).unwrap();
+        let epoch = tle.epoch_julian_date();
+        let eclipses = eclipses(&tle, 0.0, MINUTES_PER_DAY).unwrap();
+
+        // One an orbit
+        let orbits = MINUTES_PER_DAY / tle.period_minutes();
+        assert!((eclipses.len() as f64 - orbits).abs() <= 1.0, "{} eclipses", eclipses.len());
+
+        let tolerance = EclipseOptions::default().tolerance;
+        for eclipse in &eclipses[1..eclipses.len() - 1] {
+            let (umbra_entry, umbra_exit) = (eclipse.umbra_entry.unwrap(), eclipse.umbra_exit.unwrap());
+            assert!(eclipse.penumbra_entry < umbra_entry && umbra_entry < umbra_exit && umbra_exit < eclipse.penumbra_exit);
+
+            // Under half an orbit, with the penumbra either side a small
+            // part of it. The ISS is at a high beta angle, near 70°, here, so
+            // its eclipses are short and cross the shadow's edge obliquely.
+            assert!(eclipse.duration() > 5.0 && eclipse.duration() < 46.0, "{}", eclipse.duration());
+            let penumbra = umbra_entry - eclipse.penumbra_entry;
+            assert!(penumbra > 0.05 && penumbra < 0.2 * eclipse.duration(), "{}", penumbra);
+            assert!((eclipse.penumbra_exit - umbra_exit - penumbra).abs() < 0.1);
+
+            // Each boundary is where the illumination changes
+            let check = |time: f64, before: Illumination, after: Illumination| {
+                assert_eq!(illumination(&propagator, epoch, time - tolerance), before);
+                assert_eq!(illumination(&propagator, epoch, time + tolerance), after);
+            };
+            check(eclipse.penumbra_entry, Illumination::Sunlit, Illumination::Penumbra);
+            check(umbra_entry, Illumination::Penumbra, Illumination::Umbra);
+            check(umbra_exit, Illumination::Umbra, Illumination::Penumbra);
+            check(eclipse.penumbra_exit, Illumination::Penumbra, Illumination::Sunlit);
+        }
+    }
+
+    #[test]
+    fn clipped() {
+        let tle = iss();
+        let all = eclipses(&tle, 0.0, 200.0).unwrap();
+        let middle = 0.5 * (all[0].umbra_entry.unwrap() + all[0].umbra_exit.unwrap());
+
+        // Starting and ending in the umbra
+        let clipped = eclipses(&tle, middle, middle + tle.period_minutes()).unwrap();
+        assert_eq!(clipped.len(), 2);
+        assert_eq!(clipped[0].penumbra_entry, middle);
+        assert_eq!(clipped[0].umbra_entry, Some(middle));
+        assert!((clipped[0].penumbra_exit - all[0].penumbra_exit).abs() < 1e-3);
+        assert_eq!(clipped[1].penumbra_exit, middle + tle.period_minutes());
+        assert_eq!(clipped[1].umbra_exit, Some(middle + tle.period_minutes()));
+
+        assert!(eclipses(&tle, 10.0, 0.0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn no_umbra() {
+        let eclipse = Eclipse { penumbra_entry: 1.0, umbra_entry: None, umbra_exit: None, penumbra_exit: 1.5 };
+        assert_eq!(eclipse.duration(), 0.5);
+        assert_eq!(eclipse.umbra_duration(), 0.0);
+    }
+}
@@ -0,0 +1,637 @@
+/*!  # Elements
+
+Mean elements in the units the propagator works in, classical (osculating)
+orbital elements, and the secular rates $J_2$ turns an orbit at.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+use angle::Rad;
+use coordinates::{cross, dot, StateVector};
+use gravity::GravityModel;
+use tle::TLE;
+use {kozai_to_brouwer, OpsMode, PropagationError, DEEP_SPACE_PERIOD, MINUTES_PER_DAY};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+/// $\mu = GM_{\oplus}$ in kilometers³/second², from the propagator's
+/// default gravity model
+const MU: f64 = GravityModel::WGS72.mu;
+
+/// Eccentricity and inclination (radians) below which an orbit is treated
+/// as circular or equatorial, where the usual angles are undefined
+const SINGULAR: f64 = 1e-10;
+
+/// Relative change in the mean motion at which recovering it from a
+/// semi-major axis has converged
+const MEAN_MOTION_TOLERANCE: f64 = 1e-15;
+
+/// Most iterations to recover the mean motion from a semi-major axis
+const MEAN_MOTION_ITERATIONS: usize = 20;
+
+/// Length of the tropical year (days), over which a sun synchronous
+/// orbit's plane turns once
+pub(crate) const TROPICAL_YEAR: f64 = 365.2421897;
+
+
+/// ## Elements
+///
+/// The mean elements of a TLE converted once into the units SGP4 works in:
+/// angles in radians and mean motion in radians/minute, rather than the
+/// degrees and revolutions/day of the TLE itself.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::elements::Elements;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let elements = Elements::from_tle(&tle);
+/// assert_eq!(elements.inclination, sgp4::angle::Deg(51.6406).to_radians());
+///
+/// let propagator = sgp4::Propagator::from_elements(&elements).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Elements {
+
+    /// Kozai mean motion (radians/minute)
+    pub mean_motion: f64,
+
+    /// Eccentricity
+    pub eccentricity: f64,
+
+    /// Inclination
+    pub inclination: Rad,
+
+    /// Right ascension of the ascending node
+    pub raan: Rad,
+
+    /// Argument of perigee
+    pub argp: Rad,
+
+    /// Mean anomaly
+    pub mean_anomaly: Rad,
+
+    /// B* drag term (Earth radii⁻¹)
+    pub bstar: f64,
+}
+
+impl Elements {
+
+    /// Convert the elements of a TLE from degrees and revolutions/day
+    pub fn from_tle(tle: &TLE) -> Elements {
+        Elements {
+            mean_motion: tle.mean_motion * 2.0 * PI / MINUTES_PER_DAY,
+            eccentricity: tle.e,
+            inclination: tle.i.to_radians(),
+            raan: tle.raan.to_radians(),
+            argp: tle.omega.to_radians(),
+            mean_anomaly: tle.mean_anomaly.to_radians(),
+            bstar: tle.bstar,
+        }
+    }
+
+    /// ## From Keplerian
+    ///
+    /// Elements for an orbit given by its SGP4 mean semi-major axis
+    /// (kilometers), eccentricity, angles (`Rad` or `Deg`) and B*, as for a
+    /// simulated satellite with no TLE.
+    ///
+    /// The semi-major axis is the Brouwer mean one the propagator works
+    /// with, and that [`Propagator::mean_elements`](../struct.Propagator.html#method.mean_elements)
+    /// returns, not an osculating one: the Kozai mean motion a TLE would
+    /// carry is found by inverting the propagator's own conversion, with
+    /// the default gravity model and ops mode. The elements have no epoch;
+    /// date them with [`Propagator::at_epoch`](../struct.Propagator.html#method.at_epoch),
+    /// which deep space orbits need for the positions of the Sun and Moon.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// use sgp4::angle::{Deg, Rad};
+    /// use sgp4::elements::Elements;
+    ///
+    /// // A sun synchronous orbit 700 km up
+    /// let elements = Elements::from_keplerian(7078.0, 0.001, Deg(98.2), Rad(0.0), Rad(0.0), Rad(0.0), 1.0e-4).unwrap();
+    /// let propagator = sgp4::Propagator::from_elements(&elements)
+    ///     .unwrap()
+    ///     .at_epoch(sgp4::time::julian_date(2024, 3, 20.0));
+    ///
+    /// let mean = propagator.mean_elements(0.0).unwrap();
+    /// assert!((mean.semi_major_axis - 7078.0).abs() < 1e-6);
+    /// ```
+    ///
+    /// ### Errors
+    ///
+    /// `BadElements` for a semi-major axis that isn't positive or an
+    /// eccentricity outside $0 \le e < 1$.
+    pub fn from_keplerian(a: f64, e: f64, i: impl Into<Rad>, raan: impl Into<Rad>, argp: impl Into<Rad>, mean_anomaly: impl Into<Rad>, bstar: f64) -> Result<Elements, PropagationError> {
+        if !(a > 0.0 && (0.0..1.0).contains(&e)) {
+            return Err(PropagationError::BadElements);
+        }
+        let i = i.into();
+
+        let gravity = GravityModel::default();
+        let ao_dp = a / gravity.radius;
+
+        // The semi-major axis the propagator recovers goes very nearly as
+        // the mean motion to the -²/₃, so scaling by the ratio converges in
+        // a few steps
+        let mut n0 = gravity.ke / ao_dp.powf(1.5);
+        for _ in 0..MEAN_MOTION_ITERATIONS {
+            let (_, recovered) = kozai_to_brouwer(n0, e, i.0, &gravity, OpsMode::default());
+            let next = n0 * (recovered / ao_dp).powf(1.5);
+            let converged = (next - n0).abs() <= MEAN_MOTION_TOLERANCE * n0;
+            n0 = next;
+            if converged {
+                break;
+            }
+        }
+
+        Ok(Elements {
+            mean_motion: n0,
+            eccentricity: e,
+            inclination: i,
+            raan: raan.into(),
+            argp: argp.into(),
+            mean_anomaly: mean_anomaly.into(),
+            bstar,
+        })
+    }
+
+    /// Mean motion in revolutions/day, as written in a TLE
+    pub fn revolutions_per_day(&self) -> f64 {
+        self.mean_motion * MINUTES_PER_DAY / (2.0 * PI)
+    }
+
+    /// ## Is Deep Space
+    ///
+    /// Whether the propagator picks SDP4 for these elements: when the
+    /// period from the Brouwer mean motion, with the default gravity model
+    /// and ops mode, is 225 minutes or more. Other gravity models move the
+    /// boundary by a tiny fraction of a second.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// use sgp4::elements::Elements;
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    ///
+    /// assert!(!Elements::from_tle(&tle).is_deep_space());
+    /// ```
+    pub fn is_deep_space(&self) -> bool {
+        let (n0_dp, _) = kozai_to_brouwer(self.mean_motion, self.eccentricity, self.inclination.0, &GravityModel::default(), OpsMode::default());
+        2.0 * PI / n0_dp >= DEEP_SPACE_PERIOD
+    }
+}
+
+impl From<&TLE> for Elements {
+    fn from(tle: &TLE) -> Elements {
+        Elements::from_tle(tle)
+    }
+}
+
+impl From<&Elements> for Elements {
+    fn from(elements: &Elements) -> Elements {
+        *elements
+    }
+}
+
+
+/// ## Mean Elements
+///
+/// SGP4 mean elements at some time after the TLE epoch, from
+/// [`Propagator::mean_elements`](../struct.Propagator.html#method.mean_elements).
+/// Angles are 0 to $2\pi$.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MeanElements {
+
+    /// Julian date (UTC)
+    pub epoch: f64,
+
+    /// Semi-major axis (kilometers)
+    pub semi_major_axis: f64,
+
+    /// Eccentricity
+    pub eccentricity: f64,
+
+    /// Inclination
+    pub inclination: Rad,
+
+    /// Right ascension of the ascending node
+    pub raan: Rad,
+
+    /// Argument of perigee
+    pub argp: Rad,
+
+    /// Mean anomaly
+    pub mean_anomaly: Rad,
+
+    /// Mean motion (radians/minute)
+    pub mean_motion: f64,
+}
+
+impl MeanElements {
+
+    /// Mean motion in revolutions/day, as written in a TLE
+    pub fn revolutions_per_day(&self) -> f64 {
+        self.mean_motion * MINUTES_PER_DAY / (2.0 * PI)
+    }
+
+    /// Nodal precession from $J_2$ (per minute), as
+    /// [`raan_rate`](fn.raan_rate.html)
+    pub fn raan_rate(&self) -> Rad {
+        raan_rate(self.semi_major_axis, self.eccentricity, self.inclination)
+    }
+
+    /// Apsidal rotation from $J_2$ (per minute), as
+    /// [`argp_rate`](fn.argp_rate.html)
+    pub fn argp_rate(&self) -> Rad {
+        argp_rate(self.semi_major_axis, self.eccentricity, self.inclination)
+    }
+}
+
+
+/// $n J_2 (R_\oplus / p)^2$ (radians/minute), the scale of the $J_2$
+/// secular rates
+fn j2_rate(a: f64, e: f64) -> f64 {
+    let gravity = GravityModel::WGS72;
+    let n = gravity.ke * (gravity.radius / a).powf(1.5);
+    let p = a * (1.0 - e * e);
+    n * gravity.J2 * (gravity.radius / p).powi(2)
+}
+
+/// ## RAAN Rate
+///
+/// Secular rate of change of the right ascension of the ascending node
+/// from $J_2$ (per minute), for a mean semi-major axis `a` (kilometers),
+/// eccentricity `e` and inclination `i` (`Rad` or `Deg`):
+///
+/// $$\dot{\Omega} = -\frac{3}{2} n J_2 \left(\frac{R_\oplus}{p}\right)\^2 \cos i$$
+///
+/// Prograde orbits regress (the node moves west) and retrograde orbits
+/// advance. SGP4 adds smaller $J_2^2$ and $J_4$ terms, so this is within
+/// a fraction of a percent of the rate it propagates with.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::angle::Deg;
+///
+/// // The ISS's node regresses about 5° a day
+/// let rate = sgp4::elements::raan_rate(6780.0, 0.0002, Deg(51.64));
+/// assert!(((rate * 1440.0).to_degrees() + Deg(5.0)).abs() < Deg(0.1));
+/// ```
+pub fn raan_rate(a: f64, e: f64, i: impl Into<Rad>) -> Rad {
+    Rad(-1.5 * j2_rate(a, e) * i.into().cos())
+}
+
+/// ## Argument of Perigee Rate
+///
+/// Secular rate of change of the argument of perigee from $J_2$ (per
+/// minute), for a mean semi-major axis `a` (kilometers), eccentricity `e`
+/// and inclination `i` (`Rad` or `Deg`):
+///
+/// $$\dot{\omega} = \frac{3}{4} n J_2 \left(\frac{R_\oplus}{p}\right)\^2 (5 \cos\^2 i - 1)$$
+///
+/// Perigee stands still at the critical inclinations, 63.4° and 116.6°,
+/// where Molniya orbits are flown.
+pub fn argp_rate(a: f64, e: f64, i: impl Into<Rad>) -> Rad {
+    let cos_i = i.into().cos();
+    Rad(0.75 * j2_rate(a, e) * (5.0 * cos_i * cos_i - 1.0))
+}
+
+/// ## Sun Synchronous Inclination
+///
+/// The inclination at which an orbit of mean semi-major axis
+/// `a` (kilometers) and eccentricity `e` precesses once a tropical year,
+/// keeping its plane at a fixed angle to the Sun. `None` above about 6000
+/// km altitude, where $J_2$ is too weak to turn the plane that fast at
+/// any inclination.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// // 700 km up
+/// let i = sgp4::elements::sun_synchronous_inclination(7078.0, 0.0).unwrap();
+/// assert!((i.to_degrees() - sgp4::angle::Deg(98.19)).abs() < sgp4::angle::Deg(0.01));
+/// ```
+pub fn sun_synchronous_inclination(a: f64, e: f64) -> Option<Rad> {
+    let rate = 2.0 * PI / (TROPICAL_YEAR * MINUTES_PER_DAY);
+    let cos_i = -rate / (1.5 * j2_rate(a, e));
+    if (-1.0..=1.0).contains(&cos_i) {
+        Some(Rad::acos(cos_i))
+    } else {
+        None
+    }
+}
+
+/// ## Frozen Eccentricity
+///
+/// The eccentricity at which $J_3$ balances $J_2$ for an orbit of mean
+/// semi-major axis `a` (kilometers) and inclination `i` (`Rad` or `Deg`), so that
+/// with perigee over the northern apex ($\omega = 90°$) neither the
+/// eccentricity nor the argument of perigee drifts:
+///
+/// $$e_f = -\frac{J_3}{2 J_2} \frac{R_\oplus}{a} \sin i$$
+///
+/// About 0.001 for low Earth orbits.
+pub fn frozen_eccentricity(a: f64, i: impl Into<Rad>) -> f64 {
+    let gravity = GravityModel::WGS72;
+    -gravity.J3 / (2.0 * gravity.J2) * gravity.radius / a * i.into().sin()
+}
+
+
+/// ## Keplerian Elements
+///
+/// Osculating (instantaneous two body) elements of an orbit. Angles are 0
+/// to $2\pi$.
+///
+/// For a circular orbit the argument of perigee is set to zero and the
+/// true anomaly is measured from the ascending node (the argument of
+/// latitude). For an equatorial orbit the right ascension of the ascending
+/// node is zero and angles are measured from the $X$ axis instead.
+#[derive(Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeplerianElements {
+
+    /// Semi-major axis (kilometers, negative for hyperbolic orbits)
+    pub a: f64,
+
+    /// Eccentricity
+    pub e: f64,
+
+    /// Inclination
+    pub i: Rad,
+
+    /// Right ascension of the ascending node
+    pub raan: Rad,
+
+    /// Argument of perigee
+    pub argp: Rad,
+
+    /// True anomaly
+    pub nu: Rad,
+}
+
+/// Angle from `a` to `b` (0 to $2\pi$), positive counterclockwise looking
+/// down the unit vector `normal`
+fn angle(a: &[f64; 3], b: &[f64; 3], normal: &[f64; 3]) -> Rad {
+    Rad::atan2(dot(&cross(a, b), normal), dot(a, b)).normalized()
+}
+
+impl KeplerianElements {
+
+    /// ## From State
+    ///
+    /// Osculating elements of a TEME state vector (kilometers and
+    /// kilometers/second), using the WGS72 $\mu$ that SGP4 is built on.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// use sgp4::elements::KeplerianElements;
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    ///
+    /// let state = sgp4::propagate(&tle, 0.0).unwrap();
+    /// let elements = KeplerianElements::from_state(&state);
+    ///
+    /// assert!((elements.i.to_degrees() - sgp4::angle::Deg(51.64)).abs() < sgp4::angle::Deg(0.1));
+    /// ```
+    pub fn from_state(state: &StateVector) -> KeplerianElements {
+        let r = [state.position.X, state.position.Y, state.position.Z];
+        let v = [state.velocity.X, state.velocity.Y, state.velocity.Z];
+        let r_mag = dot(&r, &r).sqrt();
+        let v2 = dot(&v, &v);
+        let rv = dot(&r, &v);
+
+        // Angular momentum and its direction
+        let h = cross(&r, &v);
+        let h_mag = dot(&h, &h).sqrt();
+        let w = [h[0] / h_mag, h[1] / h_mag, h[2] / h_mag];
+
+        // Eccentricity vector, pointing at perigee
+        let e_vec = [
+            ((v2 - MU / r_mag) * r[0] - rv * v[0]) / MU,
+            ((v2 - MU / r_mag) * r[1] - rv * v[1]) / MU,
+            ((v2 - MU / r_mag) * r[2] - rv * v[2]) / MU,
+        ];
+        let e = dot(&e_vec, &e_vec).sqrt();
+
+        // Vis-viva
+        let a = 1.0 / (2.0 / r_mag - v2 / MU);
+
+        let i = Rad::acos(w[2].clamp(-1.0, 1.0));
+
+        // Line of nodes, or the X axis when the orbit is equatorial
+        let (node, raan) = if i.0 < SINGULAR || PI - i.0 < SINGULAR {
+            ([1.0, 0.0, 0.0], Rad(0.0))
+        } else {
+            let node = [-w[1], w[0], 0.0];
+            let raan = angle(&[1.0, 0.0, 0.0], &node, &[0.0, 0.0, 1.0]);
+            (node, raan)
+        };
+
+        let (argp, nu) = if e < SINGULAR {
+            (Rad(0.0), angle(&node, &r, &w))
+        } else {
+            (angle(&node, &e_vec, &w), angle(&e_vec, &r, &w))
+        };
+
+        KeplerianElements { a, e, i, raan, argp, nu }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use angle::Deg;
+    use coordinates::TEME;
+    use test_support;
+
+    #[test]
+    fn from_tle() {
+        let tle = test_support::iss();
+        let elements = Elements::from(&tle);
+
+        assert!((elements.mean_motion - 0.06784480690).abs() < 1e-11);
+        assert!((elements.revolutions_per_day() - tle.mean_motion).abs() < 1e-12);
+        assert_eq!(elements.eccentricity, 0.000178);
+        assert!((elements.raan.to_degrees() - tle.raan).abs() < Deg(1e-12));
+        assert!((elements.argp.to_degrees() - tle.omega).abs() < Deg(1e-12));
+        assert!((elements.mean_anomaly.to_degrees() - tle.mean_anomaly).abs() < Deg(1e-12));
+        assert_eq!(elements.bstar, tle.bstar);
+
+        // The propagator gives the same answer either way
+        let from_tle = ::Propagator::new(&tle).unwrap().propagate(60.0).unwrap();
+        let from_elements = ::Propagator::from_elements(&elements).unwrap()
+            .at_epoch(tle.epoch_julian_date())
+            .propagate(60.0)
+            .unwrap();
+        assert_eq!(from_tle, from_elements);
+    }
+
+    #[test]
+    fn from_keplerian() {
+        let tle = test_support::iss();
+        let expected = Elements::from_tle(&tle);
+
+        // The TLE's own mean semi-major axis gives back its mean motion
+        let mean = ::Propagator::new(&tle).unwrap().mean_elements(0.0).unwrap();
+        let elements = Elements::from_keplerian(
+            mean.semi_major_axis,
+            tle.e,
+            expected.inclination,
+            expected.raan,
+            expected.argp,
+            expected.mean_anomaly,
+            tle.bstar,
+        ).unwrap();
+        assert!((elements.mean_motion - expected.mean_motion).abs() < 1e-14 * expected.mean_motion);
+        assert_eq!(elements.raan, expected.raan);
+        assert_eq!(elements.bstar, expected.bstar);
+
+        // Including far out, and in deep space
+        for &(a, e, i) in [(26560.0, 0.7, 63.4_f64), (42164.0, 0.0, 0.0), (6600.0, 0.0, 180.0)].iter() {
+            let elements = Elements::from_keplerian(a, e, Deg(i), Rad(1.0), Rad(2.0), Rad(3.0), 0.0).unwrap();
+            let propagator = ::Propagator::from_elements(&elements).unwrap();
+            assert!((propagator.mean_elements(0.0).unwrap().semi_major_axis - a).abs() < 1e-8 * a);
+        }
+
+        assert_eq!(Elements::from_keplerian(0.0, 0.0, Rad(0.0), Rad(0.0), Rad(0.0), Rad(0.0), 0.0), Err(PropagationError::BadElements));
+        assert_eq!(Elements::from_keplerian(7000.0, 1.0, Rad(0.0), Rad(0.0), Rad(0.0), Rad(0.0), 0.0), Err(PropagationError::BadElements));
+    }
+
+    #[test]
+    fn mean_elements() {
+        let tle = test_support::iss();
+        let propagator = ::Propagator::new(&tle).unwrap();
+
+        // At the epoch they're the TLE's elements, with the mean motion
+        // un-Kozai'd
+        let epoch = propagator.mean_elements(0.0).unwrap();
+        assert_eq!(epoch.epoch, tle.epoch_julian_date());
+        assert_eq!(epoch.eccentricity, tle.e);
+        assert_eq!(epoch.inclination, tle.i.to_radians());
+        assert!((epoch.raan.to_degrees() - tle.raan).abs() < Deg(1e-10));
+        assert!((epoch.argp.to_degrees() - tle.omega).abs() < Deg(1e-10));
+        assert!((epoch.mean_anomaly.to_degrees() - tle.mean_anomaly).abs() < Deg(1e-10));
+        assert!((epoch.mean_motion - propagator.constants().n0_dp).abs() < 1e-15);
+        assert!((epoch.revolutions_per_day() - tle.mean_motion).abs() < 1e-2);
+        assert!((epoch.semi_major_axis - 6780.0).abs() < 10.0);
+
+        // A week on, drag has lowered the orbit and sped it up
+        let week = propagator.mean_elements(7.0 * MINUTES_PER_DAY).unwrap();
+        assert!(week.semi_major_axis < epoch.semi_major_axis);
+        assert!(week.mean_motion > epoch.mean_motion);
+        assert_eq!(week.inclination, epoch.inclination);
+        assert!(week.raan >= Rad(0.0) && week.raan < Rad(2.0 * PI));
+    }
+
+    #[test]
+    fn secular_rates() {
+        let tle = test_support::iss();
+        let propagator = ::Propagator::new(&tle).unwrap();
+        let epoch = propagator.mean_elements(0.0).unwrap();
+
+        // Within a fraction of a percent of what SGP4 propagates
+        let day = propagator.mean_elements(MINUTES_PER_DAY).unwrap();
+        let node = (day.raan - epoch.raan + Rad(PI)).normalized() - Rad(PI);
+        assert!((node.0 / (epoch.raan_rate().0 * MINUTES_PER_DAY) - 1.0).abs() < 2e-3);
+        let perigee = (day.argp - epoch.argp + Rad(PI)).normalized() - Rad(PI);
+        assert!((perigee.0 / (epoch.argp_rate().0 * MINUTES_PER_DAY) - 1.0).abs() < 5e-3);
+
+        // Still at the critical inclination, and the node still when polar
+        let critical = Rad::acos((1.0_f64 / 5.0).sqrt());
+        assert!(argp_rate(26600.0, 0.7, critical).abs() < Rad(1e-15));
+        assert!(argp_rate(26600.0, 0.7, Rad(PI) - critical).abs() < Rad(1e-15));
+        assert!(raan_rate(7000.0, 0.0, Deg(90.0)).abs() < Rad(1e-15));
+        assert!(raan_rate(7000.0, 0.0, Rad(2.0)) > Rad(0.0));
+
+        // Sun synchronous orbits are retrograde, more so the higher they are
+        let low = sun_synchronous_inclination(6878.0, 0.0).unwrap();
+        let high = sun_synchronous_inclination(7378.0, 0.0).unwrap();
+        assert!(low > Deg(90.0).to_radians() && high > low);
+        let year = raan_rate(7378.0, 0.0, high) * TROPICAL_YEAR * MINUTES_PER_DAY;
+        assert!((year - Rad(2.0 * PI)).abs() < Rad(1e-12));
+        assert_eq!(sun_synchronous_inclination(20000.0, 0.0), None);
+    }
+
+    #[test]
+    fn vallado_example_2_5() {
+        let state = StateVector {
+            epoch: 0.0,
+            position: TEME::new(6524.834, 6862.875, 6448.296),
+            velocity: TEME::new(4.901327, 5.533756, -1.976341),
+        };
+        let elements = KeplerianElements::from_state(&state);
+
+        // Vallado uses a slightly different mu, which shifts the semi-major axis
+        assert!((elements.a - 36127.343).abs() < 0.5);
+        assert!((elements.e - 0.832853).abs() < 1e-5);
+        assert!((elements.i.to_degrees() - Deg(87.870)).abs() < Deg(1e-3));
+        assert!((elements.raan.to_degrees() - Deg(227.898)).abs() < Deg(1e-3));
+        assert!((elements.argp.to_degrees() - Deg(53.38)).abs() < Deg(1e-2));
+        assert!((elements.nu.to_degrees() - Deg(92.335)).abs() < Deg(1e-3));
+    }
+
+    #[test]
+    fn circular_equatorial() {
+        let r = 7000.0;
+        let v = (MU / r).sqrt();
+        let (s, c) = f64::sin_cos(1.0);
+        let state = StateVector {
+            epoch: 0.0,
+            position: TEME::new(r * c, r * s, 0.0),
+            velocity: TEME::new(-v * s, v * c, 0.0),
+        };
+        let elements = KeplerianElements::from_state(&state);
+
+        assert!((elements.a - r).abs() < 1e-6);
+        assert!(elements.e < 1e-12);
+        assert_eq!(elements.i, Rad(0.0));
+        assert_eq!(elements.raan, Rad(0.0));
+        assert_eq!(elements.argp, Rad(0.0));
+        assert!((elements.nu - Rad(1.0)).abs() < Rad(1e-12));
+    }
+}
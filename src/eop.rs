@@ -0,0 +1,293 @@
+/*!  # Earth Orientation Parameters
+
+How the real Earth's rotation differs from the model: the position of the
+pole (polar motion, $x_p$ and $y_p$) and the difference between UT1, the
+time scale of the Earth's rotation, and UTC.
+
+Neglecting them costs a few tens of meters in an Earth fixed position:
+UT1 − UTC is kept below 0.9 seconds by leap seconds, and the pole wanders
+by up to about half an arcsecond. For better than that, give an
+[`Eop`](struct.Eop.html) to [`itrf_from_teme`](../coordinates/fn.itrf_from_teme.html)
+or [`Observer::look_angles_with_eop`](../observer/struct.Observer.html#method.look_angles_with_eop).
+
+The IERS publishes daily values, measured and predicted, in its
+`finals2000A` files (<https://datacenter.iers.org/>), which
+[`EopTable::parse_finals`](struct.EopTable.html#method.parse_finals) reads.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::error;
+use std::fmt;
+
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use time;
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// Arcseconds to radians
+const ARCSECOND: f64 = std::f64::consts::PI / (180.0 * 3600.0);
+
+/// ## Earth Orientation
+///
+/// Earth orientation parameters at one instant.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::eop::Eop;
+///
+/// // From IERS Bulletin A for 2016 July 28
+/// let eop = Eop::from_arcseconds(0.186180, 0.447357, -0.0461858);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Eop {
+
+    /// Polar motion $x_p$ (radians)
+    pub xp: f64,
+
+    /// Polar motion $y_p$ (radians)
+    pub yp: f64,
+
+    /// UT1 − UTC (seconds)
+    pub dut1: f64,
+}
+
+impl Eop {
+
+    /// Parameters with the polar motion in arcseconds, as the IERS
+    /// publishes it
+    pub fn from_arcseconds(xp: f64, yp: f64, dut1: f64) -> Eop {
+        Eop { xp: xp * ARCSECOND, yp: yp * ARCSECOND, dut1 }
+    }
+
+    /// A Julian date in UTC as a Julian date in UT1
+    pub fn ut1(&self, julian_date_utc: f64) -> f64 {
+        julian_date_utc + self.dut1 / 86400.0
+    }
+}
+
+
+/// ## EOP Errors
+///
+/// Why a table of Earth orientation parameters couldn't be read.
+#[derive(Debug)]
+pub enum EopError {
+
+    /// The file couldn't be read
+    #[cfg(feature = "std")]
+    Io(io::Error),
+
+    /// A line doesn't have a date, or has a value that isn't a number
+    Malformed {
+        /// Line number (counting from 1)
+        line: usize,
+    },
+}
+
+impl fmt::Display for EopError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            #[cfg(feature = "std")]
+            EopError::Io(ref error) => write!(f, "{}", error),
+            EopError::Malformed { line } => write!(f, "malformed Earth orientation parameters at line {}", line),
+        }
+    }
+}
+
+impl error::Error for EopError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            #[cfg(feature = "std")]
+            EopError::Io(ref error) => Some(error),
+            EopError::Malformed { .. } => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for EopError {
+    fn from(error: io::Error) -> EopError {
+        EopError::Io(error)
+    }
+}
+
+
+/// ## EOP Table
+///
+/// Earth orientation parameters for a run of days, interpolated in between.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::eop::{Eop, EopTable};
+///
+/// let mut table = EopTable::new();
+/// table.insert(57597.0, Eop::from_arcseconds(0.186180, 0.447357, -0.0461858));
+/// table.insert(57598.0, Eop::from_arcseconds(0.188410, 0.446822, -0.0472470));
+///
+/// let noon = sgp4::time::julian_date_from_modified(57597.5);
+/// let eop = table.get(noon).unwrap();
+/// assert!((eop.dut1 + 0.0467164).abs() < 1e-9);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EopTable {
+
+    /// Parameters at 0h UTC on each day, by modified Julian date
+    days: Vec<(f64, Eop)>,
+}
+
+impl EopTable {
+
+    /// An empty table
+    pub fn new() -> EopTable {
+        EopTable::default()
+    }
+
+    /// ## Parse Finals
+    ///
+    /// Read the IERS `finals2000A` fixed column format (`finals.all`,
+    /// `finals.data`, `finals.daily`), using the Bulletin A values. Days at
+    /// the end of the file with no predicted values are skipped.
+    pub fn parse_finals(text: &str) -> Result<EopTable, EopError> {
+        let mut table = EopTable::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
+            }
+            let malformed = || EopError::Malformed { line: index + 1 };
+            let mjd = field(line, 7..15).ok_or_else(malformed)?;
+            let values = (field(line, 18..27), field(line, 37..46), field(line, 58..68));
+            match values {
+                (Some(xp), Some(yp), Some(dut1)) => table.insert(mjd, Eop::from_arcseconds(xp, yp, dut1)),
+                (None, None, None) => {}
+                _ => return Err(malformed()),
+            }
+        }
+        Ok(table)
+    }
+
+    /// Read a `finals2000A` file
+    #[cfg(feature = "std")]
+    pub fn load_finals<P: AsRef<Path>>(path: P) -> Result<EopTable, EopError> {
+        EopTable::parse_finals(&fs::read_to_string(path)?)
+    }
+
+    /// Set the parameters at a modified Julian date (UTC), replacing any
+    /// already there
+    pub fn insert(&mut self, modified_julian_date: f64, eop: Eop) {
+        match self.days.binary_search_by(|&(mjd, _)| mjd.total_cmp(&modified_julian_date)) {
+            Ok(index) => self.days[index].1 = eop,
+            Err(index) => self.days.insert(index, (modified_julian_date, eop)),
+        }
+    }
+
+    /// Number of days in the table
+    pub fn len(&self) -> usize {
+        self.days.len()
+    }
+
+    /// Whether the table has no days
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+
+    /// ## Get
+    ///
+    /// Parameters at a Julian date (UTC), linearly interpolated between the
+    /// days either side, or `None` outside the table. A leap second between
+    /// the two days is taken out of UT1 − UTC before interpolating.
+    pub fn get(&self, julian_date_utc: f64) -> Option<Eop> {
+        let mjd = time::modified_julian_date(julian_date_utc);
+        let index = self.days.partition_point(|&(day, _)| day <= mjd);
+        if index == 0 {
+            return None;
+        }
+        let (start, before) = self.days[index - 1];
+        if mjd == start {
+            return Some(before);
+        }
+        let &(end, after) = self.days.get(index)?;
+
+        let mut dut1 = after.dut1;
+        if dut1 - before.dut1 > 0.5 {
+            dut1 -= 1.0;
+        } else if dut1 - before.dut1 < -0.5 {
+            dut1 += 1.0;
+        }
+
+        let fraction = (mjd - start) / (end - start);
+        Some(Eop {
+            xp: before.xp + fraction * (after.xp - before.xp),
+            yp: before.yp + fraction * (after.yp - before.yp),
+            dut1: before.dut1 + fraction * (dut1 - before.dut1),
+        })
+    }
+}
+
+/// A number in a fixed column range, or `None` if it's blank or short
+fn field(line: &str, columns: std::ops::Range<usize>) -> Option<f64> {
+    line.get(columns)?.trim().parse().ok()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FINALS: &str = "\
+16 728 57597.00 I  0.186180 0.000024  0.447357 0.000034  I-0.0461858 0.0000091
+16 729 57598.00 P  0.188410 0.000024  0.446822 0.000034  P-0.0472470 0.0000091
+16 730 57599.00
+";
+
+    #[test]
+    fn finals() {
+        let table = EopTable::parse_finals(FINALS).unwrap();
+        assert_eq!(table.len(), 2);
+
+        let day = time::julian_date_from_modified(57597.0);
+        assert_eq!(table.get(day).unwrap(), Eop::from_arcseconds(0.186180, 0.447357, -0.0461858));
+        assert_eq!(table.get(day + 1.0).unwrap().dut1, -0.0472470);
+        assert!((table.get(day + 0.5).unwrap().dut1 + 0.0467164).abs() < 1e-12);
+        assert!(table.get(day + 1.5).is_none());
+        assert!(table.get(day - 0.5).is_none());
+    }
+
+    #[test]
+    fn leap_second() {
+        // UT1 − UTC jumps by a second at the end of 2016
+        let mut table = EopTable::new();
+        table.insert(57753.0, Eop { dut1: -0.4088, ..Eop::default() });
+        table.insert(57754.0, Eop { dut1: 0.5909, ..Eop::default() });
+
+        let dut1 = table.get(time::julian_date_from_modified(57753.5)).unwrap().dut1;
+        assert!((dut1 + 0.40895).abs() < 1e-12);
+    }
+
+    #[test]
+    fn malformed() {
+        match EopTable::parse_finals("16 728 57597.00 I  0.186180 0.000024") {
+            Err(EopError::Malformed { line: 1 }) => {}
+            other => panic!("{:?}", other),
+        }
+        assert!(EopTable::parse_finals("not a date").is_err());
+    }
+}
@@ -0,0 +1,416 @@
+/*!  # Fetch
+
+Download element sets over HTTP, with the `http` feature.
+
+[`celestrak`](fn.celestrak.html) reads one of Celestrak's named groups
+("stations", "starlink", "active" and so on) straight into a
+[`Catalog`](../catalog/struct.Catalog.html). Celestrak only updates its
+data every couple of hours and asks that clients don't download it more
+often than that, so a [`Fetcher`](struct.Fetcher.html) with a cache
+directory keeps each response on disk and honours the server's caching
+headers: nothing is requested while `Cache-Control: max-age` says the copy
+is fresh, and after that the request is conditional on the `ETag` and
+`Last-Modified` of the copy, so an unchanged group costs a
+`304 Not Modified` rather than the whole file.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ureq;
+
+use catalog::Catalog;
+use omm::OmmError;
+use tle::ReadError;
+
+
+/// Celestrak's GP query endpoint
+pub const CELESTRAK_URL: &str = "https://celestrak.org/NORAD/elements/gp.php";
+
+/// How long to wait for a server before giving up
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// ## Fetch Errors
+///
+/// Why elements couldn't be downloaded.
+#[derive(Debug)]
+pub enum FetchError {
+
+    /// The request failed, or the server answered with an error status
+    Http(Box<ureq::Error>),
+
+    /// The response or the cache couldn't be read or written
+    Io(io::Error),
+
+    /// The response wasn't a catalog of TLEs
+    Read(ReadError),
+
+    /// The response wasn't a set of OMM records
+    Omm(OmmError),
+
+    /// Group names are letters, digits, `-` and `_`
+    InvalidGroup(String),
+
+    /// The server refused the credentials
+    Login,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FetchError::Http(ref error) => write!(f, "{}", error),
+            FetchError::Io(ref error) => write!(f, "{}", error),
+            FetchError::Read(ref error) => write!(f, "{}", error),
+            FetchError::Omm(ref error) => write!(f, "{}", error),
+            FetchError::InvalidGroup(ref group) => write!(f, "invalid group name {:?}", group),
+            FetchError::Login => write!(f, "login failed"),
+        }
+    }
+}
+
+impl error::Error for FetchError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            FetchError::Http(ref error) => Some(error.as_ref()),
+            FetchError::Io(ref error) => Some(error),
+            FetchError::Read(ref error) => Some(error),
+            FetchError::Omm(ref error) => Some(error),
+            FetchError::InvalidGroup(_) | FetchError::Login => None,
+        }
+    }
+}
+
+impl From<ureq::Error> for FetchError {
+    fn from(error: ureq::Error) -> FetchError {
+        FetchError::Http(Box::new(error))
+    }
+}
+
+impl From<io::Error> for FetchError {
+    fn from(error: io::Error) -> FetchError {
+        FetchError::Io(error)
+    }
+}
+
+impl From<ReadError> for FetchError {
+    fn from(error: ReadError) -> FetchError {
+        FetchError::Read(error)
+    }
+}
+
+impl From<OmmError> for FetchError {
+    fn from(error: OmmError) -> FetchError {
+        FetchError::Omm(error)
+    }
+}
+
+/// What the server said about caching a response, stored next to it
+#[derive(Debug, Default, PartialEq)]
+struct Validators {
+    /// When the response was received or revalidated (seconds since 1970)
+    fetched: u64,
+    /// Seconds the response is fresh for
+    max_age: u64,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+
+    fn from_response(response: &ureq::Response, now: u64) -> Validators {
+        Validators {
+            fetched: now,
+            max_age: response.header("Cache-Control").and_then(max_age).unwrap_or(0),
+            etag: response.header("ETag").map(String::from),
+            last_modified: response.header("Last-Modified").map(String::from),
+        }
+    }
+
+    fn is_fresh(&self, now: u64) -> bool {
+        now < self.fetched.saturating_add(self.max_age)
+    }
+
+    fn parse(text: &str) -> Validators {
+        let mut validators = Validators::default();
+        for line in text.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "fetched" => validators.fetched = value.parse().unwrap_or(0),
+                "max-age" => validators.max_age = value.parse().unwrap_or(0),
+                "etag" => validators.etag = Some(String::from(value)),
+                "last-modified" => validators.last_modified = Some(String::from(value)),
+                _ => {}
+            }
+        }
+        validators
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!("fetched {}\nmax-age {}\n", self.fetched, self.max_age);
+        if let Some(ref etag) = self.etag {
+            text.push_str(&format!("etag {}\n", etag));
+        }
+        if let Some(ref last_modified) = self.last_modified {
+            text.push_str(&format!("last-modified {}\n", last_modified));
+        }
+        text
+    }
+}
+
+/// The `max-age` directive of a `Cache-Control` header, unless caching is
+/// forbidden outright
+fn max_age(cache_control: &str) -> Option<u64> {
+    let directives: Vec<&str> = cache_control.split(',').map(str::trim).collect();
+    if directives.iter().any(|&directive| directive.eq_ignore_ascii_case("no-store") || directive.eq_ignore_ascii_case("no-cache")) {
+        return Some(0);
+    }
+    directives.iter()
+        .filter_map(|directive| directive.split_once('='))
+        .find(|&(name, _)| name.trim().eq_ignore_ascii_case("max-age"))
+        .and_then(|(_, seconds)| seconds.trim().parse().ok())
+}
+
+/// An HTTP client identifying itself as this crate
+pub(crate) fn agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .user_agent(concat!("sgp4/", env!("CARGO_PKG_VERSION")))
+        .build()
+}
+
+/// The URL of a Celestrak group as TLEs
+pub(crate) fn celestrak_url(group: &str) -> Result<String, FetchError> {
+    if group.is_empty() || !group.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(FetchError::InvalidGroup(String::from(group)));
+    }
+    Ok(format!("{}?GROUP={}&FORMAT=tle", CELESTRAK_URL, group))
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0)
+}
+
+/// ## Fetcher
+///
+/// An HTTP client for element sets, optionally caching responses on disk.
+#[derive(Debug, Clone)]
+pub struct Fetcher {
+    agent: ureq::Agent,
+    cache: Option<PathBuf>,
+}
+
+impl Default for Fetcher {
+    fn default() -> Fetcher {
+        Fetcher::new()
+    }
+}
+
+impl Fetcher {
+
+    /// A fetcher without a cache: every call downloads
+    pub fn new() -> Fetcher {
+        Fetcher {
+            agent: agent(),
+            cache: None,
+        }
+    }
+
+    /// A fetcher keeping responses in `directory`, which is created if need
+    /// be
+    pub fn with_cache<P: AsRef<Path>>(directory: P) -> Fetcher {
+        Fetcher {
+            cache: Some(directory.as_ref().to_path_buf()),
+            ..Fetcher::new()
+        }
+    }
+
+    /// ## Celestrak
+    ///
+    /// Download a named Celestrak group as TLEs.
+    pub fn celestrak(&self, group: &str) -> Result<Catalog, FetchError> {
+        let text = self.get(&celestrak_url(group)?)?;
+        Ok(Catalog::parse(&text)?)
+    }
+
+    /// ## Get
+    ///
+    /// The body of a URL, from the cache while it's fresh.
+    pub fn get(&self, url: &str) -> Result<String, FetchError> {
+        let directory = match self.cache {
+            Some(ref directory) => directory,
+            None => return Ok(self.agent.get(url).call()?.into_string()?),
+        };
+
+        let key: String = url.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        let body_path = directory.join(format!("{}.txt", key));
+        let meta_path = directory.join(format!("{}.meta", key));
+
+        let cached = match (fs::read_to_string(&body_path), fs::read_to_string(&meta_path)) {
+            (Ok(body), Ok(meta)) => Some((body, Validators::parse(&meta))),
+            _ => None,
+        };
+
+        if let Some((ref body, ref validators)) = cached {
+            if validators.is_fresh(now()) {
+                return Ok(body.clone());
+            }
+        }
+
+        let mut request = self.agent.get(url);
+        if let Some((_, ref validators)) = cached {
+            if let Some(ref etag) = validators.etag {
+                request = request.set("If-None-Match", etag);
+            }
+            if let Some(ref last_modified) = validators.last_modified {
+                request = request.set("If-Modified-Since", last_modified);
+            }
+        }
+        let response = request.call()?;
+        let mut validators = Validators::from_response(&response, now());
+
+        fs::create_dir_all(directory)?;
+        let body = match cached {
+            Some((body, old)) if response.status() == 304 => {
+                // A 304 needn't repeat the validators
+                validators.etag = validators.etag.or(old.etag);
+                validators.last_modified = validators.last_modified.or(old.last_modified);
+                body
+            }
+            _ => {
+                let body = response.into_string()?;
+                fs::write(&body_path, &body)?;
+                body
+            }
+        };
+        fs::write(&meta_path, validators.to_text())?;
+
+        Ok(body)
+    }
+}
+
+/// ## Celestrak
+///
+/// Download a named Celestrak group, such as `"stations"` or `"starlink"`,
+/// without caching. Use a [`Fetcher`](struct.Fetcher.html) with a cache
+/// directory for anything that runs more than occasionally.
+///
+/// ### Example
+///
+/// ```no_run
+/// extern crate sgp4;
+///
+/// let stations = sgp4::fetch::celestrak("stations").unwrap();
+/// let iss = stations.get(25544).unwrap();
+/// println!("{} at epoch {}", iss.name, iss.epoch_julian_date());
+/// ```
+pub fn celestrak(group: &str) -> Result<Catalog, FetchError> {
+    Fetcher::new().celestrak(group)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    const ISS: &str = "\
+ISS (ZARYA)
+1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+";
+
+    /// Answer each request with the next canned response, returning the
+    /// request headers received
+    fn serve(responses: Vec<String>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/gp.php?GROUP=stations", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            responses.into_iter().map(|response| {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = String::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    request.push_str(&line);
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+                request
+            }).collect()
+        });
+        (url, server)
+    }
+
+    fn response(status: &str, headers: &str, body: &str) -> String {
+        format!("HTTP/1.1 {}\r\nConnection: close\r\nContent-Length: {}\r\n{}\r\n{}", status, body.len(), headers, body)
+    }
+
+    #[test]
+    fn cache_control() {
+        assert_eq!(max_age("public, max-age=7200"), Some(7200));
+        assert_eq!(max_age("max-age=60, no-cache"), Some(0));
+        assert_eq!(max_age("private"), None);
+
+        let validators = Validators {
+            fetched: 1_000,
+            max_age: 7200,
+            etag: Some(String::from("\"abc\"")),
+            last_modified: None,
+        };
+        assert_eq!(Validators::parse(&validators.to_text()), validators);
+        assert!(validators.is_fresh(8_199));
+        assert!(!validators.is_fresh(8_200));
+    }
+
+    #[test]
+    fn conditional_requests() {
+        let directory = env::temp_dir().join(format!("sgp4-fetch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        let fetcher = Fetcher::with_cache(&directory);
+
+        // Downloaded, then revalidated with the ETag, then fresh for an
+        // hour so not requested at all
+        let (url, server) = serve(vec![
+            response("200 OK", "ETag: \"v1\"\r\n", ISS),
+            response("304 Not Modified", "Cache-Control: max-age=3600\r\n", ""),
+        ]);
+        assert_eq!(fetcher.get(&url).unwrap(), ISS);
+        assert_eq!(fetcher.get(&url).unwrap(), ISS);
+        assert_eq!(fetcher.get(&url).unwrap(), ISS);
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].to_lowercase().contains("if-none-match"));
+        assert!(requests[1].contains("\"v1\""));
+
+        let catalog = Catalog::parse(&fetcher.get(&url).unwrap()).unwrap();
+        assert_eq!(catalog.get(25544).unwrap().name, "ISS (ZARYA)");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn invalid_group() {
+        match celestrak("../stations") {
+            Err(FetchError::InvalidGroup(group)) => assert_eq!(group, "../stations"),
+            other => panic!("{:?}", other),
+        }
+    }
+}
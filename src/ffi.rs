@@ -0,0 +1,348 @@
+/*!  # C Interface
+
+`extern "C"` functions for calling the propagator from C and C++, mirroring
+the init/propagate split of the reference C code. Requires the `ffi`
+feature.
+
+Build a shared library with
+
+```text
+cargo rustc --release --features ffi --crate-type cdylib
+```
+
+and include `include/sgp4.h`, which is generated from this module with
+`cbindgen --config cbindgen.toml --output include/sgp4.h`.
+
+```c
+#include <stdio.h>
+#include "sgp4.h"
+
+int main(void) {
+    Sgp4Propagator *propagator;
+    Sgp4State state;
+
+    Sgp4Status status = sgp4_init(
+        "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+        "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+        &propagator);
+    if (status != SGP4_STATUS_OK) {
+        fprintf(stderr, "%s\n", sgp4_status_message(status));
+        return 1;
+    }
+
+    status = sgp4_propagate(propagator, 90.0, &state);
+    if (status == SGP4_STATUS_OK) {
+        printf("%f %f %f\n", state.position[0], state.position[1], state.position[2]);
+    }
+
+    sgp4_free(propagator);
+    return 0;
+}
+```
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+// Dereferencing the pointers C hands us needs `unsafe`; every function
+// checks them for null first.
+#![allow(unsafe_code)]
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::ptr;
+use std::slice;
+
+use coordinates::StateVector;
+use tle;
+use PropagationError;
+use Propagator;
+
+/// ## Status
+///
+/// Result of each call: zero for success, otherwise why it failed.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sgp4Status {
+
+    /// Success
+    Ok = 0,
+
+    /// A pointer argument was null
+    NullPointer,
+
+    /// A line of the TLE isn't valid UTF-8
+    InvalidUtf8,
+
+    /// A line of the TLE couldn't be parsed or has a bad checksum
+    ParseError,
+
+    /// The element set can't be propagated
+    BadElements,
+
+    /// The mean eccentricity diverged
+    Diverged,
+
+    /// The semi-latus rectum went negative
+    NegativeSemiLatusRectum,
+
+    /// The satellite has decayed
+    Decayed,
+
+    /// The time is too far from the epoch
+    TooOld,
+}
+
+impl From<PropagationError> for Sgp4Status {
+    fn from(error: PropagationError) -> Sgp4Status {
+        match error {
+            PropagationError::BadElements => Sgp4Status::BadElements,
+            PropagationError::Diverged => Sgp4Status::Diverged,
+            PropagationError::NegativeSemiLatusRectum => Sgp4Status::NegativeSemiLatusRectum,
+            PropagationError::Decayed => Sgp4Status::Decayed,
+            PropagationError::TooOld => Sgp4Status::TooOld,
+        }
+    }
+}
+
+/// ## State
+///
+/// Position (kilometers) and velocity (kilometers/second) in the TEME
+/// frame.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Sgp4State {
+
+    /// X, Y and Z position (kilometers)
+    pub position: [f64; 3],
+
+    /// X, Y and Z velocity (kilometers/second)
+    pub velocity: [f64; 3],
+}
+
+impl From<StateVector> for Sgp4State {
+    fn from(state: StateVector) -> Sgp4State {
+        Sgp4State {
+            position: [state.position.X, state.position.Y, state.position.Z],
+            velocity: [state.velocity.X, state.velocity.Y, state.velocity.Z],
+        }
+    }
+}
+
+/// ## Propagator
+///
+/// An initialized element set, opaque to C. Create with `sgp4_init` and
+/// release with `sgp4_free`.
+pub struct Sgp4Propagator {
+    propagator: Propagator,
+    epoch: f64,
+}
+
+/// Read a NUL terminated line of a TLE
+unsafe fn line<'a>(text: *const c_char) -> Result<&'a str, Sgp4Status> {
+    if text.is_null() {
+        return Err(Sgp4Status::NullPointer);
+    }
+    CStr::from_ptr(text).to_str().map_err(|_| Sgp4Status::InvalidUtf8)
+}
+
+/// Parse the two lines of a TLE and initialize the propagator. On success
+/// `*propagator` points to a new propagator, which must be released with
+/// `sgp4_free`.
+///
+/// # Safety
+///
+/// `line1` and `line2` must be NUL terminated strings and `propagator` a
+/// valid pointer to write to.
+#[no_mangle]
+pub unsafe extern "C" fn sgp4_init(line1: *const c_char, line2: *const c_char, propagator: *mut *mut Sgp4Propagator) -> Sgp4Status {
+    if propagator.is_null() {
+        return Sgp4Status::NullPointer;
+    }
+    *propagator = ptr::null_mut();
+
+    let (line1, line2) = match (line(line1), line(line2)) {
+        (Ok(line1), Ok(line2)) => (line1, line2),
+        (Err(status), _) | (_, Err(status)) => return status,
+    };
+
+    let tle = match tle::load_from_str("", line1, line2) {
+        Ok(tle) => tle,
+        Err(_) => return Sgp4Status::ParseError,
+    };
+
+    match Propagator::new(&tle) {
+        Ok(initialized) => {
+            *propagator = Box::into_raw(Box::new(Sgp4Propagator {
+                propagator: initialized,
+                epoch: tle.epoch_julian_date(),
+            }));
+            Sgp4Status::Ok
+        }
+        Err(error) => error.into(),
+    }
+}
+
+/// Propagate to `minutes` since the epoch, writing the position and
+/// velocity to `*state`.
+///
+/// # Safety
+///
+/// `propagator` must come from `sgp4_init` and not yet be freed, and
+/// `state` must be a valid pointer to write to.
+#[no_mangle]
+pub unsafe extern "C" fn sgp4_propagate(propagator: *const Sgp4Propagator, minutes: f64, state: *mut Sgp4State) -> Sgp4Status {
+    if propagator.is_null() || state.is_null() {
+        return Sgp4Status::NullPointer;
+    }
+
+    match (*propagator).propagator.propagate(minutes) {
+        Ok(result) => {
+            *state = result.into();
+            Sgp4Status::Ok
+        }
+        Err(error) => error.into(),
+    }
+}
+
+/// Propagate to `count` times, `start`, `start + step` and so on (minutes
+/// since the epoch), writing the states to `states[0]` to
+/// `states[count - 1]`. Nothing is allocated. On an error the states
+/// before the failing time are written and the rest are left as they were.
+///
+/// # Safety
+///
+/// `propagator` must come from `sgp4_init` and not yet be freed, and
+/// `states` must be valid to write `count` states to.
+#[no_mangle]
+pub unsafe extern "C" fn sgp4_propagate_range(propagator: *const Sgp4Propagator, start: f64, step: f64, states: *mut Sgp4State, count: usize) -> Sgp4Status {
+    if propagator.is_null() || states.is_null() {
+        return Sgp4Status::NullPointer;
+    }
+
+    let states = slice::from_raw_parts_mut(states, count);
+    for (index, state) in states.iter_mut().enumerate() {
+        match (*propagator).propagator.propagate(start + step * index as f64) {
+            Ok(result) => *state = result.into(),
+            Err(error) => return error.into(),
+        }
+    }
+    Sgp4Status::Ok
+}
+
+/// Julian date (UTC) of the element set epoch, or NaN if `propagator` is
+/// null. Subtract from a Julian date and multiply by 1440 to get the
+/// minutes to pass to `sgp4_propagate`.
+///
+/// # Safety
+///
+/// `propagator` must come from `sgp4_init` and not yet be freed.
+#[no_mangle]
+pub unsafe extern "C" fn sgp4_epoch(propagator: *const Sgp4Propagator) -> f64 {
+    if propagator.is_null() {
+        return f64::NAN;
+    }
+    (*propagator).epoch
+}
+
+/// Release a propagator. Does nothing if `propagator` is null.
+///
+/// # Safety
+///
+/// `propagator` must come from `sgp4_init` and not already be freed.
+#[no_mangle]
+pub unsafe extern "C" fn sgp4_free(propagator: *mut Sgp4Propagator) {
+    if !propagator.is_null() {
+        drop(Box::from_raw(propagator));
+    }
+}
+
+/// A static, NUL terminated description of a status.
+#[no_mangle]
+pub extern "C" fn sgp4_status_message(status: Sgp4Status) -> *const c_char {
+    let message: &'static [u8] = match status {
+        Sgp4Status::Ok => b"ok\0",
+        Sgp4Status::NullPointer => b"null pointer\0",
+        Sgp4Status::InvalidUtf8 => b"TLE line is not valid UTF-8\0",
+        Sgp4Status::ParseError => b"TLE could not be parsed\0",
+        Sgp4Status::BadElements => b"element set has an eccentricity outside [0, 1) or a non-positive mean motion\0",
+        Sgp4Status::Diverged => b"mean eccentricity diverged outside [0, 1)\0",
+        Sgp4Status::NegativeSemiLatusRectum => b"semi-latus rectum is negative\0",
+        Sgp4Status::Decayed => b"satellite has decayed\0",
+        Sgp4Status::TooOld => b"time is further from the epoch than the elements are good for\0",
+    };
+    message.as_ptr().cast()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn init_and_propagate() {
+        let line1 = CString::new("1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990").unwrap();
+        let line2 = CString::new("2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433").unwrap();
+        let tle = tle::load_from_str("", line1.to_str().unwrap(), line2.to_str().unwrap()).unwrap();
+        let expected = ::propagate(&tle, 90.0).unwrap();
+
+        unsafe {
+            let mut propagator = ptr::null_mut();
+            assert_eq!(sgp4_init(line1.as_ptr(), line2.as_ptr(), &mut propagator), Sgp4Status::Ok);
+            assert!(!propagator.is_null());
+            assert_eq!(sgp4_epoch(propagator), tle.epoch_julian_date());
+
+            let mut state = Sgp4State::default();
+            assert_eq!(sgp4_propagate(propagator, 90.0, &mut state), Sgp4Status::Ok);
+            assert_eq!(state.position, [expected.position.X, expected.position.Y, expected.position.Z]);
+            assert_eq!(state.velocity, [expected.velocity.X, expected.velocity.Y, expected.velocity.Z]);
+
+            sgp4_free(propagator);
+        }
+    }
+
+    #[test]
+    fn propagate_range() {
+        let line1 = CString::new("1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990").unwrap();
+        let line2 = CString::new("2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433").unwrap();
+        let tle = tle::load_from_str("", line1.to_str().unwrap(), line2.to_str().unwrap()).unwrap();
+
+        unsafe {
+            let mut propagator = ptr::null_mut();
+            assert_eq!(sgp4_init(line1.as_ptr(), line2.as_ptr(), &mut propagator), Sgp4Status::Ok);
+
+            let mut states = [Sgp4State::default(); 4];
+            assert_eq!(sgp4_propagate_range(propagator, 10.0, 30.0, states.as_mut_ptr(), states.len()), Sgp4Status::Ok);
+            for (index, state) in states.iter().enumerate() {
+                assert_eq!(*state, ::propagate(&tle, 10.0 + 30.0 * index as f64).unwrap().into());
+            }
+            assert_eq!(sgp4_propagate_range(propagator, 0.0, 1.0, ptr::null_mut(), 0), Sgp4Status::NullPointer);
+
+            sgp4_free(propagator);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let line1 = CString::new("1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9991").unwrap();
+        let line2 = CString::new("2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433").unwrap();
+
+        unsafe {
+            let mut propagator = ptr::null_mut();
+            assert_eq!(sgp4_init(line1.as_ptr(), line2.as_ptr(), &mut propagator), Sgp4Status::ParseError);
+            assert!(propagator.is_null());
+            assert_eq!(sgp4_init(ptr::null(), line2.as_ptr(), &mut propagator), Sgp4Status::NullPointer);
+            assert_eq!(sgp4_init(line1.as_ptr(), line2.as_ptr(), ptr::null_mut()), Sgp4Status::NullPointer);
+
+            let mut state = Sgp4State::default();
+            assert_eq!(sgp4_propagate(ptr::null(), 0.0, &mut state), Sgp4Status::NullPointer);
+            assert!(sgp4_epoch(ptr::null()).is_nan());
+            sgp4_free(ptr::null_mut());
+
+            let message = CStr::from_ptr(sgp4_status_message(Sgp4Status::Decayed));
+            assert_eq!(message.to_str().unwrap(), "satellite has decayed");
+        }
+    }
+}
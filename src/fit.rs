@@ -0,0 +1,474 @@
+/*!  # Fitting
+
+Orbit determination: the SGP4 mean elements and B* that best reproduce a
+set of observed TEME states, such as a satellite's own GPS fixes, so they
+can be published as a TLE.
+
+[`fit`](fn.fit.html) is a least squares differential corrector. It starts
+from the osculating elements of the first state and adjusts the mean
+elements, by Levenberg-Marquardt steps with the partial derivatives found
+by differencing the propagator, until the residuals stop shrinking. Each
+state contributes its position in kilometers and its velocity in
+kilometers/second times `VELOCITY_WEIGHT`, so a meter per second counts as
+much as a kilometer.
+
+The eccentricity and argument of perigee are solved for as $e \cos \omega$
+and $e \sin \omega$, and the mean anomaly as the argument of latitude
+$M + \omega$, so near circular orbits fit as well as any. The right
+ascension of an equatorial orbit is still undefined, and is held near its
+first guess by the damping.
+
+The states should span at least a few orbits, and a day or more for B* to
+be meaningful: over one orbit drag is indistinguishable from a slightly
+different mean motion.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::error;
+use std::fmt;
+use std::mem;
+
+use angle::Rad;
+use coordinates::StateVector;
+use elements::{Elements, KeplerianElements};
+use kepler;
+use tle::{TleBuildError, TLE};
+use {PropagationError, Propagator, MINUTES_PER_DAY};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// Weight of velocity residuals against position residuals (seconds)
+pub const VELOCITY_WEIGHT: f64 = 1000.0;
+
+/// Solved for: mean motion, e cos ω, e sin ω, inclination, right
+/// ascension, M + ω and B*
+const PARAMETERS: usize = 7;
+
+/// Step for the central differences, relative to each parameter's scale
+const DIFFERENCE_STEP: f64 = 1.0e-6;
+
+/// Starting Levenberg-Marquardt damping
+const DAMPING: f64 = 1.0e-3;
+
+/// Damping past which no step can reduce the residuals
+const MAX_DAMPING: f64 = 1.0e10;
+
+/// ## Fit Options
+///
+/// What to solve for, and when to stop.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FitOptions {
+
+    /// Solve for B* (default `true`). If not, it's held at `bstar`.
+    pub fit_bstar: bool,
+
+    /// B* to start from, or to hold (default 0)
+    pub bstar: f64,
+
+    /// Most iterations to take (default 50)
+    pub max_iterations: usize,
+
+    /// Stop once an iteration reduces the sum of squared residuals by less
+    /// than this fraction (default 10⁻¹⁰)
+    pub tolerance: f64,
+}
+
+impl Default for FitOptions {
+    fn default() -> FitOptions {
+        FitOptions {
+            fit_bstar: true,
+            bstar: 0.0,
+            max_iterations: 50,
+            tolerance: 1.0e-10,
+        }
+    }
+}
+
+/// ## Fit
+///
+/// Mean elements fitted to a set of states.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fit {
+
+    /// Epoch of the elements (Julian date, UTC), that of the first state
+    pub epoch: f64,
+
+    /// The fitted mean elements
+    pub elements: Elements,
+
+    /// Root mean square position residual (kilometers)
+    pub rms: f64,
+
+    /// Iterations taken
+    pub iterations: usize,
+
+    /// Whether the residuals stopped shrinking within `max_iterations`
+    pub converged: bool,
+}
+
+impl Fit {
+
+    /// A propagator for the fitted elements, dated at the fit's epoch
+    pub fn propagator(&self) -> Result<Propagator, PropagationError> {
+        Ok(Propagator::from_elements(&self.elements)?.at_epoch(self.epoch))
+    }
+
+    /// ## To TLE
+    ///
+    /// The fitted elements as a TLE, for satellite number 0 with no name or
+    /// designator. Set those fields, and the mean motion derivatives if
+    /// they're wanted, before publishing it.
+    pub fn to_tle(&self) -> Result<TLE, TleBuildError> {
+        let elements = &self.elements;
+        TLE::builder()
+            .epoch_julian_date(self.epoch)
+            .inclination(elements.inclination)
+            .raan(elements.raan)
+            .eccentricity(elements.eccentricity)
+            .argp(elements.argp)
+            .mean_anomaly(elements.mean_anomaly)
+            .mean_motion(elements.revolutions_per_day())
+            .bstar(elements.bstar)
+            .build()
+    }
+}
+
+/// ## Fit Errors
+///
+/// Why no elements could be fitted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FitError {
+
+    /// Fewer than two states at different times
+    TooFewStates,
+
+    /// The first guess, or the elements part way through the fit, can't be
+    /// propagated to the times of the states
+    Propagation(PropagationError),
+
+    /// The states don't determine the elements: some parameter has no
+    /// effect on them at all
+    Singular,
+}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FitError::TooFewStates => write!(f, "need states at two or more times to fit"),
+            FitError::Propagation(ref error) => write!(f, "elements can't be propagated: {}", error),
+            FitError::Singular => write!(f, "states don't determine the elements"),
+        }
+    }
+}
+
+impl error::Error for FitError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            FitError::Propagation(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<PropagationError> for FitError {
+    fn from(error: PropagationError) -> FitError {
+        FitError::Propagation(error)
+    }
+}
+
+
+/// The solved for parameters: mean motion, e cos ω, e sin ω, inclination,
+/// right ascension, M + ω and B*
+type Parameters = [f64; PARAMETERS];
+
+/// Mean elements from the parameters
+fn elements(x: &Parameters) -> Elements {
+    let argp = x[2].atan2(x[1]);
+    Elements {
+        mean_motion: x[0],
+        eccentricity: (x[1] * x[1] + x[2] * x[2]).sqrt(),
+        inclination: Rad(x[3]),
+        raan: Rad(x[4]).normalized(),
+        argp: Rad(argp).normalized(),
+        mean_anomaly: Rad(x[5] - argp).normalized(),
+        bstar: x[6],
+    }
+}
+
+/// The parameters of mean elements
+fn parameters(elements: &Elements) -> Parameters {
+    let e = elements.eccentricity;
+    [
+        elements.mean_motion,
+        e * elements.argp.cos(),
+        e * elements.argp.sin(),
+        elements.inclination.0,
+        elements.raan.0,
+        (elements.mean_anomaly + elements.argp).0,
+        elements.bstar,
+    ]
+}
+
+/// Scale of each parameter, for the difference steps
+fn scales(x: &Parameters) -> Parameters {
+    [x[0], 1.0, 1.0, 1.0, 1.0, 1.0, x[6].abs().max(1.0e-4)]
+}
+
+/// Weighted residuals, observed minus computed, for every state
+fn residuals(x: &Parameters, states: &[StateVector], epoch: f64, residuals: &mut Vec<f64>) -> Result<f64, PropagationError> {
+    let propagator = Propagator::from_elements(&elements(x))?.at_epoch(epoch);
+    residuals.clear();
+    for state in states {
+        let computed = propagator.propagate((state.epoch - epoch) * MINUTES_PER_DAY)?;
+        residuals.extend_from_slice(&[
+            state.position.X - computed.position.X,
+            state.position.Y - computed.position.Y,
+            state.position.Z - computed.position.Z,
+            (state.velocity.X - computed.velocity.X) * VELOCITY_WEIGHT,
+            (state.velocity.Y - computed.velocity.Y) * VELOCITY_WEIGHT,
+            (state.velocity.Z - computed.velocity.Z) * VELOCITY_WEIGHT,
+        ]);
+    }
+    Ok(residuals.iter().map(|r| r * r).sum())
+}
+
+/// Root mean square of the position residuals (kilometers)
+fn position_rms(residuals: &[f64]) -> f64 {
+    let sum: f64 = residuals.chunks(6).map(|r| r[0] * r[0] + r[1] * r[1] + r[2] * r[2]).sum();
+    (sum / (residuals.len() / 6) as f64).sqrt()
+}
+
+/// First guess at the mean elements: the osculating elements of a state
+fn first_guess(state: &StateVector, bstar: f64) -> Result<Elements, PropagationError> {
+    let osculating = KeplerianElements::from_state(state);
+    let e = osculating.e;
+    if !(0.0..1.0).contains(&e) {
+        return Err(PropagationError::BadElements);
+    }
+    let M = kepler::mean_from_eccentric(kepler::eccentric_from_true(osculating.nu.0, e), e);
+    Elements::from_keplerian(osculating.a, e, osculating.i, osculating.raan, osculating.argp, Rad(M).normalized(), bstar)
+}
+
+/// Solve `a x = b` by Gaussian elimination with partial pivoting, for the
+/// first `n` rows and columns
+fn solve(mut a: [Parameters; PARAMETERS], mut b: Parameters, n: usize) -> Option<Parameters> {
+    for column in 0..n {
+        let pivot = (column..n).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+        if a[pivot][column] == 0.0 || !a[pivot][column].is_finite() {
+            return None;
+        }
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+        let pivot_row = a[column];
+        for row in column + 1..n {
+            let factor = a[row][column] / pivot_row[column];
+            for (value, pivot) in a[row].iter_mut().zip(pivot_row.iter()).take(n).skip(column) {
+                *value -= factor * pivot;
+            }
+            b[row] -= factor * b[column];
+        }
+    }
+
+    let mut x = [0.0; PARAMETERS];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+/// ## Fit
+///
+/// Fit SGP4 mean elements, and B* unless told not to, to TEME states
+/// (kilometers and kilometers/second), dating them at the first state's
+/// epoch.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::fit::{fit, FitOptions};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // Stand in for a day of GPS fixes, every ten minutes
+/// let propagator = sgp4::Propagator::new(&tle).unwrap();
+/// let states: Vec<_> = (0..144).map(|i| propagator.propagate(10.0 * i as f64).unwrap()).collect();
+///
+/// let fit = fit(&states, &FitOptions::default()).unwrap();
+/// assert!(fit.rms < 0.01);
+/// let (line1, line2) = fit.to_tle().unwrap().to_lines().unwrap();
+/// ```
+pub fn fit(states: &[StateVector], options: &FitOptions) -> Result<Fit, FitError> {
+    let epoch = states.first().ok_or(FitError::TooFewStates)?.epoch;
+    if states.iter().all(|state| state.epoch == epoch) {
+        return Err(FitError::TooFewStates);
+    }
+    let n = if options.fit_bstar { PARAMETERS } else { PARAMETERS - 1 };
+
+    let mut x = parameters(&first_guess(&states[0], options.bstar)?);
+    let mut r = Vec::with_capacity(6 * states.len());
+    let mut cost = residuals(&x, states, epoch, &mut r)?;
+    let mut trial_r = Vec::with_capacity(r.len());
+    let mut plus = Vec::with_capacity(r.len());
+    let mut minus = Vec::with_capacity(r.len());
+    let mut jacobian = vec![[0.0; PARAMETERS]; r.len()];
+    let mut damping = DAMPING;
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while iterations < options.max_iterations && !converged {
+        iterations += 1;
+
+        // Partial derivatives of the computed states, by central
+        // differences. The residuals are observed minus computed, so these
+        // are their negatives.
+        let scale = scales(&x);
+        for j in 0..n {
+            let step = DIFFERENCE_STEP * scale[j];
+            let (mut above, mut below) = (x, x);
+            above[j] += step;
+            below[j] -= step;
+            residuals(&above, states, epoch, &mut plus)?;
+            residuals(&below, states, epoch, &mut minus)?;
+            for (row, (p, m)) in jacobian.iter_mut().zip(plus.iter().zip(minus.iter())) {
+                row[j] = (m - p) / (2.0 * step);
+            }
+        }
+
+        // Normal equations
+        let mut normal = [[0.0; PARAMETERS]; PARAMETERS];
+        let mut gradient = [0.0; PARAMETERS];
+        for (row, residual) in jacobian.iter().zip(r.iter()) {
+            for j in 0..n {
+                gradient[j] += row[j] * residual;
+                for k in 0..n {
+                    normal[j][k] += row[j] * row[k];
+                }
+            }
+        }
+
+        // Take the least damped step that reduces the residuals
+        loop {
+            let mut damped = normal;
+            for (j, row) in damped.iter_mut().enumerate().take(n) {
+                row[j] += damping * normal[j][j];
+            }
+            let step = solve(damped, gradient, n).ok_or(FitError::Singular)?;
+            let mut trial = x;
+            for j in 0..n {
+                trial[j] += step[j];
+            }
+
+            match residuals(&trial, states, epoch, &mut trial_r) {
+                Ok(trial_cost) if trial_cost <= cost => {
+                    converged = cost - trial_cost <= options.tolerance * cost;
+                    x = trial;
+                    cost = trial_cost;
+                    mem::swap(&mut r, &mut trial_r);
+                    damping = (damping / 10.0).max(1.0e-12);
+                    break;
+                }
+                _ => {
+                    damping *= 10.0;
+                    if damping > MAX_DAMPING {
+                        converged = true;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Fit {
+        epoch,
+        elements: elements(&x),
+        rms: position_rms(&r),
+        iterations,
+        converged,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::iss;
+
+    /// States every `step` minutes for `count` steps
+    fn states(tle: &TLE, step: f64, count: usize) -> Vec<StateVector> {
+        let propagator = Propagator::new(tle).unwrap();
+        (0..count).map(|i| propagator.propagate(step * i as f64).unwrap()).collect()
+    }
+
+    #[test]
+    fn recovers_elements() {
+        let tle = iss();
+        let fit = fit(&states(&tle, 10.0, 432), &FitOptions::default()).unwrap();
+        assert!(fit.converged);
+        assert!(fit.rms < 1e-3, "{:?}", fit);
+        assert_eq!(fit.epoch, tle.epoch_julian_date());
+
+        let expected = Elements::from_tle(&tle);
+        let elements = fit.elements;
+        assert!((elements.mean_motion - expected.mean_motion).abs() < 1e-10);
+        assert!((elements.eccentricity - expected.eccentricity).abs() < 1e-7);
+        assert!((elements.inclination - expected.inclination).abs() < Rad(1e-8));
+        assert!((elements.raan - expected.raan).abs() < Rad(1e-8));
+        assert!((elements.bstar - expected.bstar).abs() < 0.01 * expected.bstar, "{} {}", elements.bstar, expected.bstar);
+
+        let refit = fit.to_tle().unwrap();
+        assert_eq!(refit.epoch_year, 2016);
+        assert!((refit.mean_motion - tle.mean_motion).abs() < 1e-7);
+    }
+
+    #[test]
+    fn noisy() {
+        // Tens of meters of noise, like a GPS receiver
+        let mut states = states(&iss(), 5.0, 288);
+        for (i, state) in states.iter_mut().enumerate() {
+            let i = i as f64;
+            state.position.X += 0.03 * (1.3 * i).sin();
+            state.position.Y += 0.03 * (2.9 * i).cos();
+            state.position.Z += 0.03 * (0.7 * i).sin();
+        }
+
+        let options = FitOptions { fit_bstar: false, bstar: iss().bstar, ..FitOptions::default() };
+        let fit = fit(&states, &options).unwrap();
+        assert!(fit.rms < 0.05, "{:?}", fit);
+        assert_eq!(fit.elements.bstar, iss().bstar);
+
+        // Closer to the truth than the data is
+        let truth = Propagator::new(&iss()).unwrap();
+        let fitted = fit.propagator().unwrap();
+        let error = (truth.propagate(720.0).unwrap().position - fitted.propagate(720.0).unwrap().position).norm();
+        assert!(error < 0.03, "{}", error);
+    }
+
+    #[test]
+    fn problems() {
+        let states = states(&iss(), 10.0, 2);
+        assert_eq!(fit(&states[..1], &FitOptions::default()), Err(FitError::TooFewStates));
+        assert_eq!(fit(&[], &FitOptions::default()), Err(FitError::TooFewStates));
+        assert_eq!(fit(&[states[0], states[0]], &FitOptions::default()), Err(FitError::TooFewStates));
+
+        let mut hyperbolic = states[0];
+        hyperbolic.velocity = hyperbolic.velocity * 2.0;
+        assert_eq!(fit(&[hyperbolic, states[1]], &FitOptions::default()), Err(FitError::Propagation(PropagationError::BadElements)));
+    }
+}
@@ -0,0 +1,212 @@
+/*!  # Footprint
+
+The patch of ground a satellite can be seen from: every point from which
+it's above some minimum elevation. Coverage maps draw its boundary, and
+contact planning asks whether a station is inside it.
+
+The Earth is taken as a sphere of the mean radius, which puts the
+boundary within a few kilometers of where the ellipsoid would, well
+inside the uncertainty of a TLE. The footprint is then a circle on the
+sphere around the point under the satellite, of an angular radius set by
+the satellite's altitude and the minimum elevation.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+
+use coordinates::Geodetic;
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// Mean radius of the Earth (kilometers, IUGG)
+pub const EARTH_RADIUS: f64 = 6371.0088;
+
+/// ## Central Angle
+///
+/// The angle at the center of the Earth between two points (radians), by
+/// the haversine formula. Multiply by
+/// [`EARTH_RADIUS`](constant.EARTH_RADIUS.html) for the distance along
+/// the ground.
+pub fn central_angle(a: &Geodetic, b: &Geodetic) -> f64 {
+    let haversine = ((b.lat - a.lat) / 2.0).sin().powi(2)
+        + a.lat.cos() * b.lat.cos() * ((b.lon - a.lon) / 2.0).sin().powi(2);
+    2.0 * haversine.sqrt().min(1.0).asin()
+}
+
+/// ## Footprint Angle
+///
+/// The angular radius (radians, at the center of the Earth) of the
+/// footprint of a satellite `altitude` kilometers up, seen from the ground
+/// at `min_elevation` radians or more:
+///
+/// $$\lambda = \arccos\left(\frac{R_\oplus \cos \varepsilon}{R_\oplus + h}\right) - \varepsilon$$
+///
+/// Zero for a satellite on or below the ground.
+pub fn footprint_angle(altitude: f64, min_elevation: f64) -> f64 {
+    let ratio = EARTH_RADIUS * min_elevation.cos() / (EARTH_RADIUS + altitude);
+    (ratio.min(1.0).acos() - min_elevation).max(0.0)
+}
+
+/// ## Footprint
+///
+/// The circle on the ground a satellite is above a minimum elevation from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Footprint {
+
+    /// The point under the satellite, and its altitude
+    pub center: Geodetic,
+
+    /// Minimum elevation (radians)
+    pub min_elevation: f64,
+
+    /// Angular radius at the center of the Earth (radians)
+    pub angle: f64,
+}
+
+impl Footprint {
+
+    /// ## New
+    ///
+    /// The footprint of a satellite at `position`, e.g. a point of a
+    /// [ground track](../ground_track/index.html), seen at `min_elevation`
+    /// radians or more.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// use sgp4::coordinates::Geodetic;
+    /// use sgp4::footprint::Footprint;
+    ///
+    /// // The ISS over Portland, seen 10° up or more
+    /// let iss = Geodetic { lat: f64::to_radians(45.52), lon: f64::to_radians(-122.68), alt: 420.0 };
+    /// let footprint = Footprint::new(&iss, f64::to_radians(10.0));
+    /// assert!((footprint.radius() - 1390.0).abs() < 1.0);
+    ///
+    /// // Seattle is inside it, Denver isn't
+    /// let seattle = Geodetic { lat: f64::to_radians(47.61), lon: f64::to_radians(-122.33), alt: 0.0 };
+    /// let denver = Geodetic { lat: f64::to_radians(39.74), lon: f64::to_radians(-104.99), alt: 1.6 };
+    /// assert!(footprint.contains(&seattle));
+    /// assert!(!footprint.contains(&denver));
+    ///
+    /// // Its edge, every 5°
+    /// let boundary = footprint.boundary(72);
+    /// ```
+    pub fn new(position: &Geodetic, min_elevation: f64) -> Footprint {
+        Footprint {
+            center: *position,
+            min_elevation,
+            angle: footprint_angle(position.alt, min_elevation),
+        }
+    }
+
+    /// Radius along the ground (kilometers)
+    pub fn radius(&self) -> f64 {
+        self.angle * EARTH_RADIUS
+    }
+
+    /// Distance in a straight line from the edge up to the satellite
+    /// (kilometers), the furthest it's seen from
+    pub fn max_range(&self) -> f64 {
+        let r = EARTH_RADIUS + self.center.alt;
+        (EARTH_RADIUS * EARTH_RADIUS + r * r - 2.0 * EARTH_RADIUS * r * self.angle.cos()).sqrt()
+    }
+
+    /// Whether a point on the ground sees the satellite at the minimum
+    /// elevation or above. Its altitude is ignored.
+    pub fn contains(&self, point: &Geodetic) -> bool {
+        central_angle(&self.center, point) <= self.angle
+    }
+
+    /// ## Boundary
+    ///
+    /// `points` points evenly spaced round the edge of the footprint, on
+    /// the ground, starting due north of the center and going clockwise.
+    /// Longitudes are wrapped to $-\pi$ to $\pi$, so a footprint over the
+    /// antimeridian or a pole needs splitting there before it's drawn as a
+    /// polygon on a map.
+    pub fn boundary(&self, points: usize) -> Vec<Geodetic> {
+        let (sin_lat, cos_lat) = self.center.lat.sin_cos();
+        let (sin_angle, cos_angle) = self.angle.sin_cos();
+        (0..points)
+            .map(|index| {
+                let bearing = 2.0 * PI * index as f64 / points as f64;
+                let lat = (sin_lat * cos_angle + cos_lat * sin_angle * bearing.cos()).asin();
+                let lon = self.center.lon
+                    + (bearing.sin() * sin_angle * cos_lat).atan2(cos_angle - sin_lat * lat.sin());
+                Geodetic {
+                    lat,
+                    lon: (lon + PI).rem_euclid(2.0 * PI) - PI,
+                    alt: 0.0,
+                }
+            })
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geodetic(lat: f64, lon: f64, alt: f64) -> Geodetic {
+        Geodetic { lat: lat.to_radians(), lon: lon.to_radians(), alt }
+    }
+
+    #[test]
+    fn angles() {
+        // From the horizon, the edge is where the line of sight grazes the
+        // Earth
+        let geo = footprint_angle(35786.0, 0.0);
+        assert!((geo.to_degrees() - 81.3).abs() < 0.05, "{}", geo.to_degrees());
+        assert!(footprint_angle(420.0, 0.3) < footprint_angle(420.0, 0.1));
+        assert!(footprint_angle(800.0, 0.1) > footprint_angle(420.0, 0.1));
+
+        // Straight up only
+        assert!(footprint_angle(420.0, PI / 2.0).abs() < 1e-12);
+        assert_eq!(footprint_angle(0.0, 0.0), 0.0);
+        assert_eq!(footprint_angle(-1.0, 0.0), 0.0);
+
+        // A quarter of the way round the equator
+        let angle = central_angle(&geodetic(0.0, 170.0, 0.0), &geodetic(0.0, -100.0, 0.0));
+        assert!((angle - PI / 2.0).abs() < 1e-12);
+        assert!((central_angle(&geodetic(90.0, 0.0, 0.0), &geodetic(-90.0, 45.0, 0.0)) - PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn boundary() {
+        let footprint = Footprint::new(&geodetic(60.0, 175.0, 800.0), 0.2);
+        let boundary = footprint.boundary(36);
+        assert_eq!(boundary.len(), 36);
+
+        // Every point is on the edge, which is seen at the minimum elevation
+        for point in &boundary {
+            assert!((central_angle(&footprint.center, point) - footprint.angle).abs() < 1e-12);
+            assert!(point.lon.abs() <= PI);
+        }
+        assert!((boundary[0].lat - footprint.center.lat - footprint.angle).abs() < 1e-12);
+        assert!(boundary[9].lon > footprint.center.lon || boundary[9].lon < 0.0);
+
+        // The slant range to the edge makes a triangle with the Earth's
+        // radius and the satellite's that has the minimum elevation in it
+        let r = EARTH_RADIUS + footprint.center.alt;
+        let range = footprint.max_range();
+        let cos_zenith = (EARTH_RADIUS * EARTH_RADIUS + range * range - r * r) / (2.0 * EARTH_RADIUS * range);
+        assert!((cos_zenith.acos() - (PI / 2.0 + 0.2)).abs() < 1e-9);
+
+        let inside = geodetic(62.0, -178.0, 0.0);
+        assert!(footprint.contains(&inside));
+        assert!(!footprint.contains(&geodetic(30.0, 175.0, 0.0)));
+    }
+}
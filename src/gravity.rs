@@ -0,0 +1,153 @@
+/*!  # Gravity
+
+Earth gravity constants. SGP4 element sets are fitted with the WGS72
+constants, but implementations differ in exactly which values they use;
+picking the same set as another implementation makes results agree with it
+to the last digit.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+#![allow(non_snake_case)]
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+
+/// ## Gravity Model
+///
+/// A set of Earth constants for the propagator. The three standard sets
+/// are the ones offered by Vallado's reference SGP4 implementation.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::gravity::GravityModel;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let propagator = sgp4::Propagator::with_gravity_model(&tle, GravityModel::WGS84).unwrap();
+/// let state = propagator.propagate(0.0).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GravityModel {
+
+    /// $\mu = GM_{\oplus}$ (kilometers³/second²)
+    pub mu: f64,
+
+    /// Equatorial radius of the Earth, $a_E$ (kilometers/Earth radii)
+    pub radius: f64,
+
+    /// Flattening of the Earth, $f$, which with the radius gives the
+    /// ellipsoid geodetic coordinates are measured from
+    pub flattening: f64,
+
+    /// $k_e$  Orbital constant for Earth defined as $\sqrt{GM_{\oplus}}$ where $G$ is Newton’s universal gravitational constant and $M_{\oplus}$ is the mass of the Earth. Units: $(\frac{\mathrm{Earth\ radii}}{\mathrm{minute}})\^{\frac{3}{2}}$
+    pub ke: f64,
+
+    /// $J_2$ zonal harmonic
+    pub J2: f64,
+
+    /// $J_3$ zonal harmonic
+    pub J3: f64,
+
+    /// $J_4$ zonal harmonic
+    pub J4: f64,
+}
+
+impl GravityModel {
+
+    /// The constants printed in Spacetrack Report #3, with $k_e$ given
+    /// directly rather than derived from $\mu$
+    pub const WGS72_OLD: GravityModel = GravityModel {
+        mu: 398600.79964,
+        radius: 6378.135,
+        flattening: 1.0 / 298.26,
+        ke: 0.0743669161,
+        J2: 0.001082616,
+        J3: -0.00000253881,
+        J4: -0.00000165597,
+    };
+
+    /// WGS72, the constants the element sets are generated with
+    pub const WGS72: GravityModel = GravityModel {
+        mu: 398600.8,
+        radius: 6378.135,
+        flattening: 1.0 / 298.26,
+        ke: 0.07436691613317342,
+        J2: 0.001082616,
+        J3: -0.00000253881,
+        J4: -0.00000165597,
+    };
+
+    /// WGS84
+    pub const WGS84: GravityModel = GravityModel {
+        mu: 398600.5,
+        radius: 6378.137,
+        flattening: 1.0 / 298.257223563,
+        ke: 0.07436685316871385,
+        J2: 0.00108262998905,
+        J3: -0.00000253215306,
+        J4: -0.00000161098761,
+    };
+
+    /// $k_2$  Harmonic gravity constant for the SGP4 model. Defined as $\frac{1}{2}J_2a_E\^2$.
+    pub fn k2(&self) -> f64 {
+        0.5 * self.J2
+    }
+
+    /// $k_4$  Harmonic gravity constant for the SGP4 model. Defined as $-\frac{3}{8}J_4a_E\^4$.
+    pub fn k4(&self) -> f64 {
+        -0.375 * self.J4
+    }
+
+    /// $A_{3,0} = -J_3a_E\^3$
+    pub fn A30(&self) -> f64 {
+        -self.J3
+    }
+
+    /// Square of the eccentricity of the ellipsoid, $e^2 = f(2 - f)$
+    pub fn eccentricity_squared(&self) -> f64 {
+        self.flattening * (2.0 - self.flattening)
+    }
+}
+
+impl Default for GravityModel {
+    fn default() -> GravityModel {
+        GravityModel::WGS72
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ke_matches_mu() {
+        for model in [GravityModel::WGS72_OLD, GravityModel::WGS72, GravityModel::WGS84].iter() {
+            let ke = 60.0 / (model.radius.powi(3) / model.mu).sqrt();
+            assert!((model.ke - ke).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn spacetrack_report_3_constants() {
+        // As printed in the report
+        let model = GravityModel::WGS72_OLD;
+        assert!((model.k2() - 5.413080e-4).abs() < 1e-12);
+        assert!((model.k4() - 6.209887e-7).abs() < 1e-12);
+        assert!((model.A30() - 2.53881e-6).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,358 @@
+/*!  # Ground Track
+
+The path a satellite traces over the ground, and exporters that draw it
+in mapping tools: KML for Google Earth and GeoJSON for Leaflet and most
+web maps.
+
+The track is the geodetic point under the satellite, found by rotating
+each TEME position to Earth fixed by Greenwich mean sidereal time. Maps
+can't draw a line across the antimeridian (longitude ±180°) without it
+wrapping the wrong way round the world, so the exporters split the track
+there, ending one line at the crossing and starting the next from the
+other side.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::fmt::Write;
+
+use coordinates::{ecef_from_teme, geodetic_from_ecef, Geodetic};
+use time;
+use tle::TLE;
+use {PropagationError, Propagator, MINUTES_PER_DAY};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::string::String;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// ## Track Point
+///
+/// Where the satellite is over the Earth at one time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackPoint {
+
+    /// When (Julian date, UTC)
+    pub julian_date: f64,
+
+    /// Latitude, longitude and height above the WGS84 ellipsoid
+    pub position: Geodetic,
+}
+
+/// ## Ground Track
+///
+/// The satellite's ground track from `start` to `end` (Julian dates, UTC),
+/// a point every `step` minutes.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // One orbit
+/// let start = tle.epoch_julian_date();
+/// let track = sgp4::ground_track::ground_track(&tle, start, start + 92.0 / 1440.0, 1.0).unwrap();
+///
+/// // Never further from the equator than the inclination
+/// assert!(track.iter().all(|point| point.position.lat.to_degrees().abs() < 52.0));
+/// ```
+///
+/// ### Panics
+///
+/// If `step` isn't positive.
+pub fn ground_track(tle: &TLE, start: f64, end: f64, step: f64) -> Result<Vec<TrackPoint>, PropagationError> {
+    let propagator = Propagator::new(tle)?;
+    let offset = (start - tle.epoch_julian_date()) * MINUTES_PER_DAY;
+    propagator.sample(offset, offset + (end - start) * MINUTES_PER_DAY, step)
+        .enumerate()
+        .map(|(index, (_, state))| {
+            let julian_date = start + index as f64 * step / MINUTES_PER_DAY;
+            let ecef = ecef_from_teme(&state?.position, time::gmst(julian_date));
+            Ok(TrackPoint { julian_date, position: geodetic_from_ecef(&ecef) })
+        })
+        .collect()
+}
+
+/// A longitude, latitude (degrees) and altitude (meters) on a map
+type Coordinate = [f64; 3];
+
+/// ## Antimeridian Split
+///
+/// The track as separate lines of longitude, latitude (degrees) and
+/// altitude (meters), broken where it crosses the antimeridian. The
+/// latitude and altitude at the crossing are interpolated, and each side
+/// gets a point on it.
+pub fn split_at_antimeridian(track: &[TrackPoint]) -> Vec<Vec<Coordinate>> {
+    let mut lines: Vec<Vec<Coordinate>> = Vec::new();
+    let mut line: Vec<Coordinate> = Vec::new();
+    for point in track {
+        let next = [point.position.lon.to_degrees(), point.position.lat.to_degrees(), point.position.alt * 1000.0];
+        if let Some(&previous) = line.last() {
+            // A jump of more than half the world is a crossing, not a step
+            let jump = next[0] - previous[0];
+            if jump.abs() > 180.0 {
+                let edge = if jump < 0.0 { 180.0 } else { -180.0 };
+                let unwrapped = next[0] + 2.0 * edge;
+                let fraction = (edge - previous[0]) / (unwrapped - previous[0]);
+                let latitude = previous[1] + fraction * (next[1] - previous[1]);
+                let altitude = previous[2] + fraction * (next[2] - previous[2]);
+                line.push([edge, latitude, altitude]);
+                lines.push(line);
+                line = vec![[-edge, latitude, altitude]];
+            }
+        }
+        line.push(next);
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// ## Altitude Mode
+///
+/// How KML places the track in height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AltitudeMode {
+
+    /// On the ground, ignoring the altitude
+    ClampToGround,
+
+    /// At the satellite's altitude above sea level
+    Absolute,
+}
+
+impl AltitudeMode {
+
+    /// The KML keyword
+    fn keyword(self) -> &'static str {
+        match self {
+            AltitudeMode::ClampToGround => "clampToGround",
+            AltitudeMode::Absolute => "absolute",
+        }
+    }
+}
+
+/// Replace the characters XML reserves with entities
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// A JSON string literal, quoted and escaped
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => { let _ = write!(quoted, "\\u{:04x}", c as u32); }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// ## KML
+///
+/// A KML document with the track as a placemark named `name`, one
+/// `LineString` per side of each antimeridian crossing. With
+/// `AltitudeMode::Absolute` the line is drawn at the satellite's height.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::ground_track::{ground_track, to_kml, AltitudeMode};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let start = tle.epoch_julian_date();
+/// let track = ground_track(&tle, start, start + 0.1, 1.0).unwrap();
+/// let kml = to_kml("ISS (ZARYA)", &track, AltitudeMode::Absolute);
+/// assert!(kml.contains("<altitudeMode>absolute</altitudeMode>"));
+/// ```
+pub fn to_kml(name: &str, track: &[TrackPoint], altitude_mode: AltitudeMode) -> String {
+    let mut kml = String::new();
+    kml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    kml.push_str("<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n");
+    kml.push_str("<Document>\n");
+    let _ = writeln!(kml, "<Placemark>\n<name>{}</name>\n<MultiGeometry>", escape_xml(name));
+    for line in split_at_antimeridian(track) {
+        let _ = writeln!(kml, "<LineString>\n<tessellate>1</tessellate>\n<altitudeMode>{}</altitudeMode>\n<coordinates>", altitude_mode.keyword());
+        for [lon, lat, alt] in line {
+            let _ = writeln!(kml, "{:.6},{:.6},{:.1}", lon, lat, alt);
+        }
+        kml.push_str("</coordinates>\n</LineString>\n");
+    }
+    kml.push_str("</MultiGeometry>\n</Placemark>\n</Document>\n</kml>\n");
+    kml
+}
+
+/// ## GeoJSON
+///
+/// A GeoJSON `FeatureCollection` with a `LineString` feature for each side
+/// of each antimeridian crossing. Each feature's properties give `name`
+/// and the `start` and `end` of the whole track (ISO 8601, UTC).
+/// Coordinates are longitude, latitude and altitude in meters, as RFC 7946
+/// has them.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::ground_track::{ground_track, to_geojson};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let start = tle.epoch_julian_date();
+/// let track = ground_track(&tle, start, start + 0.1, 1.0).unwrap();
+/// let geojson = to_geojson("ISS (ZARYA)", &track);
+/// assert!(geojson.starts_with("{\"type\":\"FeatureCollection\""));
+/// ```
+pub fn to_geojson(name: &str, track: &[TrackPoint]) -> String {
+    let name = json_string(name);
+    let (start, end) = match (track.first(), track.last()) {
+        (Some(first), Some(last)) => (json_string(&time::iso_8601(first.julian_date)), json_string(&time::iso_8601(last.julian_date))),
+        _ => (String::from("null"), String::from("null")),
+    };
+
+    let mut geojson = String::from("{\"type\":\"FeatureCollection\",\"features\":[");
+    for (index, line) in split_at_antimeridian(track).iter().enumerate() {
+        if index > 0 {
+            geojson.push(',');
+        }
+        let _ = write!(geojson,
+                       "{{\"type\":\"Feature\",\"properties\":{{\"name\":{},\"start\":{},\"end\":{}}},\"geometry\":{{\"type\":\"LineString\",\"coordinates\":[",
+                       name, start, end);
+        for (index, [lon, lat, alt]) in line.iter().enumerate() {
+            if index > 0 {
+                geojson.push(',');
+            }
+            let _ = write!(geojson, "[{:.6},{:.6},{:.1}]", lon, lat, alt);
+        }
+        geojson.push_str("]}}");
+    }
+    geojson.push_str("]}");
+    geojson
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::iss;
+
+    fn point(lon: f64, lat: f64) -> TrackPoint {
+        TrackPoint {
+            julian_date: 0.0,
+            position: Geodetic { lat: lat.to_radians(), lon: lon.to_radians(), alt: 400.0 },
+        }
+    }
+
+    #[test]
+    fn track() {
+        let tle = iss();
+        let start = tle.epoch_julian_date();
+        let track = ground_track(&tle, start, start + 1.0, 1.0).unwrap();
+        assert_eq!(track.len(), 1441);
+        assert_eq!(track[60].julian_date, start + 60.0 / MINUTES_PER_DAY);
+        assert!(track.iter().all(|point| (350.0..450.0).contains(&point.position.alt)));
+        let highest = track.iter().map(|point| point.position.lat.to_degrees()).fold(0.0, f64::max);
+        assert!(highest > 51.0 && highest < 52.0, "{}", highest);
+    }
+
+    #[test]
+    fn antimeridian() {
+        // Eastbound across the line, halfway between the points
+        let lines = split_at_antimeridian(&[point(170.0, 0.0), point(178.0, 10.0), point(-178.0, 20.0), point(-170.0, 30.0)]);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 3);
+        assert_eq!(lines[1].len(), 3);
+        assert_eq!(lines[0][2][0], 180.0);
+        assert_eq!(lines[1][0][0], -180.0);
+        assert!((lines[0][2][1] - 15.0).abs() < 1e-9);
+        assert_eq!(lines[0][2][1], lines[1][0][1]);
+
+        // Westbound
+        let lines = split_at_antimeridian(&[point(-179.0, 0.0), point(177.0, -4.0)]);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0][1][0], -180.0);
+        assert_eq!(lines[1][0][0], 180.0);
+        assert!((lines[1][0][1] + 1.0).abs() < 1e-9);
+
+        // A day of the ISS crosses about once an orbit
+        let tle = iss();
+        let start = tle.epoch_julian_date();
+        let track = ground_track(&tle, start, start + 1.0, 1.0).unwrap();
+        let lines = split_at_antimeridian(&track);
+        assert!((15..=17).contains(&lines.len()), "{}", lines.len());
+        for line in &lines {
+            for pair in line.windows(2) {
+                assert!((pair[1][0] - pair[0][0]).abs() < 180.0);
+            }
+        }
+
+        assert!(split_at_antimeridian(&[]).is_empty());
+    }
+
+    #[test]
+    fn kml() {
+        let kml = to_kml("A & B", &[point(178.0, 0.0), point(-178.0, 2.0)], AltitudeMode::ClampToGround);
+        assert!(kml.contains("<name>A &amp; B</name>"));
+        assert_eq!(kml.matches("<LineString>").count(), 2);
+        assert_eq!(kml.matches("<altitudeMode>clampToGround</altitudeMode>").count(), 2);
+        assert!(kml.contains("178.000000,0.000000,400000.0\n180.000000,1.000000,400000.0\n</coordinates>"));
+        assert!(kml.ends_with("</kml>\n"));
+    }
+
+    #[test]
+    fn geojson() {
+        let geojson = to_geojson("\"ISS\"", &[point(178.0, 0.0), point(-178.0, 2.0)]);
+        assert_eq!(geojson.matches("\"type\":\"Feature\"").count(), 2);
+        assert!(geojson.contains("\"name\":\"\\\"ISS\\\"\""));
+        assert!(geojson.contains("\"coordinates\":[[178.000000,0.000000,400000.0],[180.000000,1.000000,400000.0]]"));
+        assert!(geojson.ends_with("]}}]}"));
+
+        assert_eq!(to_geojson("empty", &[]), "{\"type\":\"FeatureCollection\",\"features\":[]}");
+        assert_eq!(json_string("a\u{1}"), "\"a\\u0001\"");
+
+        // Dated by the whole track
+        let start = time::julian_date(2016, 7, 28.75);
+        let track = ground_track(&iss(), start, start + 0.0625, 1.0).unwrap();
+        let geojson = to_geojson("ISS", &track);
+        assert!(geojson.contains("\"start\":\"2016-07-28T18:00:00.000000\",\"end\":\"2016-07-28T19:30:00.000000\""));
+    }
+}
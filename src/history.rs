@@ -0,0 +1,559 @@
+/*!  # History
+
+Every element set published for one satellite, in order of epoch.
+
+A TLE is most accurate near its epoch and degrades by kilometers a day
+either side of it, so the way to know where a satellite was on some date
+in the past is to propagate the element set whose epoch is closest to
+that date, not the newest one. An [`ElementHistory`](struct.ElementHistory.html)
+finds it. Where only what was known at the time should be used, as when
+reconstructing a decision made then, the latest element set before the
+date is the one to pick instead.
+
+An [`Ephemeris`](struct.Ephemeris.html) covers a window longer than any one
+element set is good for by propagating each one only for the stretch of
+time it's the best for, switching to the next halfway between their
+epochs. Successive element sets don't quite agree, so the states jump by
+up to a few kilometers at each switch; blending the two across a short
+window either side smooths the jumps out.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::iter::FromIterator;
+
+use coordinates::StateVector;
+use tle::TLE;
+use {PropagationError, PropagationOptions, Propagator, MINUTES_PER_DAY};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::{self, Vec};
+#[cfg(any(feature = "std", test))]
+use std::vec;
+use std::slice;
+
+
+/// ## Element History
+///
+/// The element sets of one satellite, oldest epoch first, with at most one
+/// for each epoch.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::history::ElementHistory;
+///
+/// let history: ElementHistory = sgp4::tle::parse_all("\
+/// ISS (ZARYA)
+/// 1 25544U 98067A   16209.55555556  .00000812  00000-0  11901-4 0  9996
+/// 2 25544  51.6409 216.6214 0001780  81.9560 197.9220 15.54885439 11277
+/// ISS (ZARYA)
+/// 1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+/// 2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+/// ").unwrap().into_iter().collect();
+///
+/// // Noon on the 28th of July is nearer the second epoch, but only the
+/// // first was published by then
+/// let noon = sgp4::time::julian_date(2016, 7, 28.5);
+/// assert_eq!(history.best_for(noon).unwrap().epoch_day, 210.59822142);
+/// assert_eq!(history.best_before(noon).unwrap().epoch_day, 209.55555556);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ElementHistory {
+    tles: Vec<TLE>,
+}
+
+impl ElementHistory {
+
+    /// An empty history
+    pub fn new() -> ElementHistory {
+        ElementHistory::default()
+    }
+
+    /// ## Insert
+    ///
+    /// Add an element set in its place by epoch. One with the same epoch
+    /// is replaced, and returned.
+    ///
+    /// ### Panics
+    ///
+    /// If the element set is for a different satellite from the ones
+    /// already in the history.
+    pub fn insert(&mut self, tle: TLE) -> Option<TLE> {
+        if let Some(sat_number) = self.sat_number() {
+            assert_eq!(tle.sat_number, sat_number, "an element history is for one satellite");
+        }
+        let epoch = tle.epoch_julian_date();
+        let index = self.index_after(epoch);
+        if index > 0 && self.tles[index - 1].epoch_julian_date() == epoch {
+            return Some(std::mem::replace(&mut self.tles[index - 1], tle));
+        }
+        self.tles.insert(index, tle);
+        None
+    }
+
+    /// The satellite number, or `None` for an empty history
+    pub fn sat_number(&self) -> Option<u32> {
+        self.tles.first().map(|tle| tle.sat_number)
+    }
+
+    /// How many element sets there are
+    pub fn len(&self) -> usize {
+        self.tles.len()
+    }
+
+    /// Whether there are none
+    pub fn is_empty(&self) -> bool {
+        self.tles.is_empty()
+    }
+
+    /// Every element set, oldest first
+    pub fn iter(&self) -> slice::Iter<'_, TLE> {
+        self.tles.iter()
+    }
+
+    /// The element sets as a slice, oldest first
+    pub fn as_slice(&self) -> &[TLE] {
+        &self.tles
+    }
+
+    /// The oldest element set
+    pub fn first(&self) -> Option<&TLE> {
+        self.tles.first()
+    }
+
+    /// The newest element set
+    pub fn last(&self) -> Option<&TLE> {
+        self.tles.last()
+    }
+
+    /// ## Best For
+    ///
+    /// The element set with the epoch nearest `julian_date` (UTC), before
+    /// or after it, which propagates to then most accurately. Of two
+    /// equally near, the later. `None` for an empty history.
+    pub fn best_for(&self, julian_date: f64) -> Option<&TLE> {
+        let index = self.index_after(julian_date);
+        let before = index.checked_sub(1).map(|index| &self.tles[index]);
+        let after = self.tles.get(index);
+        match (before, after) {
+            (Some(before), Some(after)) => {
+                if julian_date - before.epoch_julian_date() < after.epoch_julian_date() - julian_date {
+                    Some(before)
+                } else {
+                    Some(after)
+                }
+            }
+            (before, after) => before.or(after),
+        }
+    }
+
+    /// ## Best Before
+    ///
+    /// The newest element set with an epoch at or before `julian_date`
+    /// (UTC), the best that was available then, or `None` if there isn't
+    /// one that old.
+    pub fn best_before(&self, julian_date: f64) -> Option<&TLE> {
+        self.index_after(julian_date).checked_sub(1).map(|index| &self.tles[index])
+    }
+
+    /// The index of the first element set with an epoch after
+    /// `julian_date`
+    fn index_after(&self, julian_date: f64) -> usize {
+        self.tles.partition_point(|tle| tle.epoch_julian_date() <= julian_date)
+    }
+}
+
+impl FromIterator<TLE> for ElementHistory {
+    fn from_iter<I: IntoIterator<Item = TLE>>(tles: I) -> ElementHistory {
+        let mut history = ElementHistory::new();
+        history.extend(tles);
+        history
+    }
+}
+
+impl Extend<TLE> for ElementHistory {
+    fn extend<I: IntoIterator<Item = TLE>>(&mut self, tles: I) {
+        for tle in tles {
+            self.insert(tle);
+        }
+    }
+}
+
+impl IntoIterator for ElementHistory {
+    type Item = TLE;
+    type IntoIter = vec::IntoIter<TLE>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tles.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ElementHistory {
+    type Item = &'a TLE;
+    type IntoIter = slice::Iter<'a, TLE>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// ## Ephemeris
+///
+/// States over a long window, each from the element set of an
+/// [`ElementHistory`](struct.ElementHistory.html) with the nearest epoch.
+/// Before the first epoch and after the last, the oldest and newest
+/// element sets are propagated as far as they need to be.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::history::{ElementHistory, Ephemeris};
+///
+/// let history: ElementHistory = sgp4::tle::parse_all("\
+/// ISS (ZARYA)
+/// 1 25544U 98067A   16209.55555556  .00000812  00000-0  11901-4 0  9996
+/// 2 25544  51.6409 216.6214 0001780  81.9560 197.9220 15.54885439 11277
+/// ISS (ZARYA)
+/// 1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+/// 2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+/// ").unwrap().into_iter().collect();
+///
+/// // Ten minutes of blending either side of the switch
+/// let ephemeris = Ephemeris::new(&history).unwrap().with_blending(10.0);
+///
+/// // Two days, every ten minutes
+/// let start = sgp4::time::julian_date(2016, 7, 27.0);
+/// for state in ephemeris.sample(start, start + 2.0, 10.0) {
+///     let state = state.unwrap();
+///     println!("{} {} {} {}", state.epoch, state.position.X, state.position.Y, state.position.Z);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ephemeris {
+    propagators: Vec<Propagator>,
+    blending: f64,
+}
+
+impl Ephemeris {
+
+    /// ## New
+    ///
+    /// An ephemeris from every element set in `history`, with the default
+    /// `PropagationOptions` and no blending.
+    ///
+    /// ### Panics
+    ///
+    /// If the history is empty.
+    pub fn new(history: &ElementHistory) -> Result<Ephemeris, PropagationError> {
+        Ephemeris::with_options(history, &PropagationOptions::default())
+    }
+
+    /// ## With Options
+    ///
+    /// An ephemeris from every element set in `history`, each converted
+    /// with the given `PropagationOptions`. A `max_age` applies to each
+    /// element set over the stretch it's used for.
+    ///
+    /// ### Panics
+    ///
+    /// If the history is empty.
+    pub fn with_options(history: &ElementHistory, options: &PropagationOptions) -> Result<Ephemeris, PropagationError> {
+        assert!(!history.is_empty(), "an ephemeris needs at least one element set");
+        let propagators = history.iter()
+            .map(|tle| Propagator::with_options(tle, options))
+            .collect::<Result<Vec<Propagator>, PropagationError>>()?;
+        Ok(Ephemeris { propagators, blending: 0.0 })
+    }
+
+    /// ## With Blending
+    ///
+    /// Blend the states of successive element sets over `minutes` either
+    /// side of each switch, rather than jumping from one to the other.
+    /// The weight moves from one to the other along a smoothstep curve,
+    /// so the blended position and velocity are continuous across the
+    /// window, and the velocity stays the rate of change of the position.
+    /// A window wider than half the time between two epochs is narrowed
+    /// to fit.
+    ///
+    /// ### Panics
+    ///
+    /// If `minutes` is negative.
+    pub fn with_blending(self, minutes: f64) -> Ephemeris {
+        assert!(minutes >= 0.0, "blending window can't be negative");
+        Ephemeris { blending: minutes, ..self }
+    }
+
+    /// The propagators, one for each element set, oldest first
+    pub fn propagators(&self) -> &[Propagator] {
+        &self.propagators
+    }
+
+    /// ## Switch Times
+    ///
+    /// When (Julian dates, UTC) each element set hands over to the next,
+    /// halfway between their epochs.
+    pub fn switch_times(&self) -> impl Iterator<Item = f64> + '_ {
+        self.propagators.windows(2).map(|pair| (pair[0].epoch() + pair[1].epoch()) / 2.0)
+    }
+
+    /// ## State At
+    ///
+    /// The state at `julian_date` (UTC) from the element set with the
+    /// nearest epoch, or a blend of two near a switch.
+    pub fn state_at(&self, julian_date: f64) -> Result<StateVector, PropagationError> {
+        // The first propagator whose stretch ends after the date
+        let index = self.propagators.windows(2)
+            .take_while(|pair| (pair[0].epoch() + pair[1].epoch()) / 2.0 <= julian_date)
+            .count();
+        let current = &self.propagators[index];
+
+        // The element set on the other side of the switch nearest the
+        // date. Blending windows are narrowed to end at epochs, so that's
+        // the switch on the same side of this element set's epoch.
+        let epoch = current.epoch();
+        let before = index.checked_sub(1).map(|index| &self.propagators[index]);
+        let after = self.propagators.get(index + 1);
+        let neighbour = match (before, after) {
+            (Some(before), Some(after)) => {
+                if julian_date - epoch < 0.0 { before } else { after }
+            }
+            (Some(before), None) => before,
+            (None, Some(after)) => after,
+            (None, None) => return propagate(current, julian_date),
+        };
+        let switch = (epoch + neighbour.epoch()) / 2.0;
+        let half_width = self.blending.min((neighbour.epoch() - epoch).abs() / 2.0 * MINUTES_PER_DAY);
+        let offset = (julian_date - switch) * MINUTES_PER_DAY;
+        if half_width == 0.0 || offset.abs() >= half_width {
+            return propagate(current, julian_date);
+        }
+
+        // Smoothstep from the earlier element set to the later
+        let (earlier, later) = if neighbour.epoch() < epoch { (neighbour, current) } else { (current, neighbour) };
+        let a = propagate(earlier, julian_date)?;
+        let b = propagate(later, julian_date)?;
+        let s = (offset + half_width) / (2.0 * half_width);
+        let weight = s * s * (3.0 - 2.0 * s);
+        let rate = 6.0 * s * (1.0 - s) / (2.0 * half_width * 60.0);
+        Ok(StateVector {
+            epoch: julian_date,
+            position: a.position * (1.0 - weight) + b.position * weight,
+            velocity: a.velocity * (1.0 - weight) + b.velocity * weight + (b.position - a.position) * rate,
+        })
+    }
+
+    /// ## Sample
+    ///
+    /// States from `start` to `end` (Julian dates, UTC), every `step`
+    /// minutes.
+    ///
+    /// ### Panics
+    ///
+    /// If `step` isn't positive.
+    pub fn sample(&self, start: f64, end: f64, step: f64) -> EphemerisSamples<'_> {
+        assert!(step > 0.0, "sample step must be positive");
+        let count = if end < start {
+            0
+        } else {
+            ((end - start) * MINUTES_PER_DAY / step + 1.0e-9).floor() as usize + 1
+        };
+        EphemerisSamples {
+            ephemeris: self,
+            start,
+            step,
+            index: 0,
+            count,
+        }
+    }
+}
+
+/// The state of one propagator at a Julian date, dated exactly then
+fn propagate(propagator: &Propagator, julian_date: f64) -> Result<StateVector, PropagationError> {
+    let mut state = propagator.propagate((julian_date - propagator.epoch()) * MINUTES_PER_DAY)?;
+    state.epoch = julian_date;
+    Ok(state)
+}
+
+/// ## Ephemeris Samples
+///
+/// Iterator over evenly spaced states from an ephemeris, created by
+/// `Ephemeris::sample`.
+#[derive(Debug, Clone)]
+pub struct EphemerisSamples<'a> {
+    ephemeris: &'a Ephemeris,
+    start: f64,
+    step: f64,
+    index: usize,
+    count: usize,
+}
+
+impl<'a> Iterator for EphemerisSamples<'a> {
+    type Item = Result<StateVector, PropagationError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+        let time = self.start + self.step * self.index as f64 / MINUTES_PER_DAY;
+        self.index += 1;
+        Some(self.ephemeris.state_at(time))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for EphemerisSamples<'a> {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use angle::Deg;
+
+    fn iss(epoch_day: f64) -> TLE {
+        TLE::builder()
+            .name("ISS (ZARYA)")
+            .sat_number(25544)
+            .epoch(2016, epoch_day)
+            .inclination(Deg(51.6406))
+            .raan(Deg(211.4156))
+            .eccentricity(0.000178)
+            .argp(Deg(85.8307))
+            .mean_anomaly(Deg(274.3426))
+            .mean_motion(15.54888439)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn selection() {
+        // Out of order, with a duplicate epoch
+        let mut history: ElementHistory = [212.0, 210.0, 215.0].iter().map(|&epoch| iss(epoch)).collect();
+        let mut replacement = iss(212.0);
+        replacement.revolution_number = 7;
+        assert_eq!(history.insert(replacement).unwrap().revolution_number, 0);
+        assert_eq!(history.len(), 3);
+        let epochs: Vec<f64> = history.iter().map(|tle| tle.epoch_day).collect();
+        assert_eq!(epochs, vec![210.0, 212.0, 215.0]);
+
+        let at = |day: f64| iss(day).epoch_julian_date();
+        let best = |day: f64| history.best_for(at(day)).unwrap().epoch_day;
+        assert_eq!(best(100.0), 210.0);
+        assert_eq!(best(210.9), 210.0);
+        assert_eq!(best(211.0), 212.0);
+        assert_eq!(best(213.4), 212.0);
+        assert_eq!(best(213.6), 215.0);
+        assert_eq!(best(300.0), 215.0);
+        assert_eq!(history.best_for(at(212.0)).unwrap().revolution_number, 7);
+
+        assert!(history.best_before(at(209.9)).is_none());
+        assert_eq!(history.best_before(at(210.0)).unwrap().epoch_day, 210.0);
+        assert_eq!(history.best_before(at(214.9)).unwrap().epoch_day, 212.0);
+        assert_eq!(history.best_before(at(300.0)).unwrap().epoch_day, 215.0);
+
+        let empty = ElementHistory::new();
+        assert!(empty.best_for(at(210.0)).is_none());
+        assert_eq!(empty.sat_number(), None);
+    }
+
+    fn pair() -> ElementHistory {
+        ::tle::parse_all("\
+ISS (ZARYA)
+1 25544U 98067A   16209.55555556  .00000812  00000-0  11901-4 0  9996
+2 25544  51.6409 216.6214 0001780  81.9560 197.9220 15.54885439 11277
+ISS (ZARYA)
+1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+").unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn stitching() {
+        let history = pair();
+        let ephemeris = Ephemeris::new(&history).unwrap();
+        let first = Propagator::new(history.first().unwrap()).unwrap();
+        let last = Propagator::new(history.last().unwrap()).unwrap();
+        let switches: Vec<f64> = ephemeris.switch_times().collect();
+        assert_eq!(switches, vec![(first.epoch() + last.epoch()) / 2.0]);
+        let switch = switches[0];
+
+        // Each element set is used on its side of the switch, and beyond
+        // the ends of the history
+        for &(julian_date, propagator) in &[(first.epoch() - 3.0, &first), (switch - 1e-4, &first), (switch, &last), (last.epoch() + 3.0, &last)] {
+            let state = ephemeris.state_at(julian_date).unwrap();
+            let expected = propagator.propagate((julian_date - propagator.epoch()) * MINUTES_PER_DAY).unwrap();
+            assert_eq!(state.epoch, julian_date);
+            assert_eq!(state.position, expected.position);
+            assert_eq!(state.velocity, expected.velocity);
+        }
+
+        let samples: Vec<StateVector> = ephemeris.sample(first.epoch(), last.epoch(), 1.0).map(Result::unwrap).collect();
+        assert_eq!(samples.len(), ((last.epoch() - first.epoch()) * MINUTES_PER_DAY).floor() as usize + 1);
+        assert_eq!(samples[0].position, first.propagate(0.0).unwrap().position);
+        assert_eq!(ephemeris.sample(1.0, 0.0, 1.0).len(), 0);
+
+        // One element set is just its propagator
+        let single = Ephemeris::new(&history.iter().take(1).cloned().collect()).unwrap().with_blending(30.0);
+        assert_eq!(single.switch_times().count(), 0);
+        assert_eq!(single.state_at(first.epoch() + 0.5).unwrap().position, first.propagate(720.0).unwrap().position);
+    }
+
+    #[test]
+    fn blending() {
+        let history = pair();
+        let abrupt = Ephemeris::new(&history).unwrap();
+        let blended = abrupt.clone().with_blending(10.0);
+        let switch = abrupt.switch_times().next().unwrap();
+        let step = 10.0 / 86400.0;
+
+        // The element sets disagree at the switch
+        let a = propagate(&abrupt.propagators()[0], switch).unwrap().position;
+        let b = propagate(&abrupt.propagators()[1], switch).unwrap().position;
+        assert!((a - b).norm() > 0.1, "{}", (a - b).norm());
+
+        // With it, the velocity is the rate of change of the position
+        for &minutes in &[-10.5, -9.9, -5.0, -0.01, 0.0, 3.0, 9.99, 10.1] {
+            let julian_date = switch + minutes / MINUTES_PER_DAY;
+            let a = blended.state_at(julian_date - step / 2.0).unwrap();
+            let b = blended.state_at(julian_date + step / 2.0).unwrap();
+            let state = blended.state_at(julian_date).unwrap();
+            let rate = (b.position - a.position) * (1.0 / 10.0);
+            assert!((rate - state.velocity).norm() < 1e-4, "{}: {}", minutes, (rate - state.velocity).norm());
+        }
+
+        // Outside the window it's the nearest element set alone
+        for &minutes in &[-10.0, 10.0, -600.0] {
+            let julian_date = switch + minutes / MINUTES_PER_DAY;
+            assert_eq!(blended.state_at(julian_date).unwrap(), abrupt.state_at(julian_date).unwrap());
+        }
+
+        // Halfway, the blend is the average
+        let middle = blended.state_at(switch).unwrap().position;
+        assert!((middle - (a + b) * 0.5).norm() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn one_satellite() {
+        let mut history: ElementHistory = Some(iss(210.0)).into_iter().collect();
+        let mut other = iss(211.0);
+        other.sat_number = 20580;
+        history.insert(other);
+    }
+}
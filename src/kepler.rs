@@ -0,0 +1,273 @@
+/*!  # Kepler's Equation
+
+Solvers for Kepler's equation, which gives the position in an orbit at a
+time: the eccentric anomaly $E$ of an elliptic orbit from its mean anomaly
+$M$,
+
+$$M = E - e \sin E$$
+
+and the hyperbolic anomaly $H$ of a hyperbolic one,
+
+$$M = e \sinh H - H$$
+
+along with conversions between these anomalies and the true anomaly. Both
+solvers use Newton's method from starting values (Danby's) that converge
+for every eccentricity, to a tolerance and within a number of iterations
+that can be set. The propagator solves its own form of the equation, in
+the SGP4 variables, the same way.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+
+/// ## Kepler Options
+///
+/// When to stop iterating.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeplerOptions {
+
+    /// Largest Newton step (radians) that counts as converged (default
+    /// 1e-12)
+    pub tolerance: f64,
+
+    /// Most Newton steps to take (default 50)
+    pub max_iterations: usize,
+}
+
+impl Default for KeplerOptions {
+    fn default() -> KeplerOptions {
+        KeplerOptions {
+            tolerance: 1.0e-12,
+            max_iterations: 50,
+        }
+    }
+}
+
+/// ## Solution
+///
+/// An anomaly found by a solver, and how it got there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Solution {
+
+    /// The eccentric or hyperbolic anomaly (radians)
+    pub anomaly: f64,
+
+    /// Newton steps taken
+    pub iterations: usize,
+
+    /// Whether the last step was within the tolerance. If not, `anomaly`
+    /// is where the iteration cap left it.
+    pub converged: bool,
+}
+
+/// Newton's method on `f`, which gives the function and its derivative,
+/// from `x`
+fn newton<F>(mut x: f64, options: &KeplerOptions, f: F) -> Solution
+where F: Fn(f64) -> (f64, f64) {
+    for iteration in 0..options.max_iterations {
+        let (value, slope) = f(x);
+        let delta = value / slope;
+        x -= delta;
+        if delta.abs() <= options.tolerance {
+            return Solution { anomaly: x, iterations: iteration + 1, converged: true };
+        }
+    }
+    Solution { anomaly: x, iterations: options.max_iterations, converged: false }
+}
+
+/// ## Solve Elliptic
+///
+/// The eccentric anomaly (radians) of an elliptic orbit of eccentricity
+/// `e` at mean anomaly `mean_anomaly` (radians). The eccentric anomaly is
+/// in the same revolution as the mean anomaly, so they agree at perigee
+/// and apogee.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::kepler::{solve_elliptic, true_from_eccentric, KeplerOptions};
+///
+/// // Vallado, example 2-1
+/// let solution = solve_elliptic(235.4_f64.to_radians(), 0.4, &KeplerOptions::default());
+/// assert!(solution.converged);
+/// assert!((solution.anomaly - 3.84866174).abs() < 1e-8);
+///
+/// let nu = true_from_eccentric(solution.anomaly, 0.4);
+/// ```
+///
+/// ### Panics
+///
+/// If `e` isn't in $0 \le e < 1$.
+pub fn solve_elliptic(mean_anomaly: f64, e: f64, options: &KeplerOptions) -> Solution {
+    assert!((0.0..1.0).contains(&e), "an elliptic orbit needs 0 <= e < 1");
+
+    // Solve in -π to π, where the starting value is good, and put back the
+    // whole revolutions after
+    let revolutions = ((mean_anomaly + PI) / (2.0 * PI)).floor();
+    let M = mean_anomaly - revolutions * 2.0 * PI;
+
+    // Perigee and apogee are exact, and the iteration is slow to find
+    // them near e = 1, where the slope there vanishes
+    if M == 0.0 || M == -PI {
+        return Solution { anomaly: mean_anomaly, iterations: 0, converged: true };
+    }
+    let start = M + 0.85 * e * M.sin().signum();
+    let mut solution = newton(start, options, |E| (E - e * E.sin() - M, 1.0 - e * E.cos()));
+    solution.anomaly += revolutions * 2.0 * PI;
+    solution
+}
+
+/// ## Solve Hyperbolic
+///
+/// The hyperbolic anomaly (radians) of a hyperbolic orbit of eccentricity
+/// `e` at mean anomaly `mean_anomaly` (radians), negative before perigee.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::kepler::{solve_hyperbolic, KeplerOptions};
+///
+/// let solution = solve_hyperbolic(10.0, 2.5, &KeplerOptions::default());
+/// let H = solution.anomaly;
+/// assert!((2.5 * H.sinh() - H - 10.0).abs() < 1e-12);
+/// ```
+///
+/// ### Panics
+///
+/// If `e` isn't greater than 1.
+pub fn solve_hyperbolic(mean_anomaly: f64, e: f64, options: &KeplerOptions) -> Solution {
+    assert!(e > 1.0, "a hyperbolic orbit needs e > 1");
+
+    // Starts beyond the root, where the function is convex, so Newton's
+    // method closes in from one side
+    let M = mean_anomaly;
+    let start = M.signum() * (2.0 * M.abs() / e + 1.8).ln();
+    newton(start, options, |H| (e * H.sinh() - H - M, e * H.cosh() - 1.0))
+}
+
+/// True anomaly (radians) from the eccentric anomaly of an elliptic orbit,
+/// in the same revolution
+pub fn true_from_eccentric(E: f64, e: f64) -> f64 {
+    let half = E / 2.0;
+    let nu = 2.0 * ((1.0 + e).sqrt() * half.sin()).atan2((1.0 - e).sqrt() * half.cos());
+    nu + ((E - nu + PI) / (2.0 * PI)).floor() * 2.0 * PI
+}
+
+/// Eccentric anomaly (radians) from the true anomaly of an elliptic orbit,
+/// in the same revolution
+pub fn eccentric_from_true(nu: f64, e: f64) -> f64 {
+    let half = nu / 2.0;
+    let E = 2.0 * ((1.0 - e).sqrt() * half.sin()).atan2((1.0 + e).sqrt() * half.cos());
+    E + ((nu - E + PI) / (2.0 * PI)).floor() * 2.0 * PI
+}
+
+/// Mean anomaly (radians) from the eccentric anomaly of an elliptic orbit
+pub fn mean_from_eccentric(E: f64, e: f64) -> f64 {
+    E - e * E.sin()
+}
+
+/// True anomaly (radians) from the hyperbolic anomaly of a hyperbolic
+/// orbit
+pub fn true_from_hyperbolic(H: f64, e: f64) -> f64 {
+    2.0 * (((e + 1.0) / (e - 1.0)).sqrt() * (H / 2.0).tanh()).atan()
+}
+
+/// Hyperbolic anomaly (radians) from the true anomaly of a hyperbolic
+/// orbit, which must be inside the asymptotes
+pub fn hyperbolic_from_true(nu: f64, e: f64) -> f64 {
+    2.0 * (((e - 1.0) / (e + 1.0)).sqrt() * (nu / 2.0).tan()).atanh()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn elliptic() {
+        let options = KeplerOptions::default();
+        for &e in &[0.0, 1e-4, 0.1, 0.5, 0.9, 0.99, 0.999999] {
+            for step in -40..=40 {
+                let M = step as f64 * 0.2;
+                let solution = solve_elliptic(M, e, &options);
+                assert!(solution.converged, "e={} M={}", e, M);
+                assert!(solution.iterations <= 10, "e={} M={}: {} iterations", e, M, solution.iterations);
+                assert!((mean_from_eccentric(solution.anomaly, e) - M).abs() < 1e-11, "e={} M={}", e, M);
+                assert!((solution.anomaly - M).abs() <= e + 1e-12);
+            }
+        }
+
+        // Perigee and apogee, in whichever revolution
+        assert_eq!(solve_elliptic(0.0, 0.7, &options).anomaly, 0.0);
+        assert!((solve_elliptic(5.0 * PI, 0.7, &options).anomaly - 5.0 * PI).abs() < 1e-12);
+    }
+
+    #[test]
+    fn hyperbolic() {
+        let options = KeplerOptions::default();
+        for &e in &[1.000001, 1.01, 1.5, 3.0, 100.0] {
+            for step in -20..=20 {
+                let M = step as f64 * step as f64 * step as f64 * 0.05;
+                let solution = solve_hyperbolic(M, e, &options);
+                assert!(solution.converged, "e={} M={}", e, M);
+                let H = solution.anomaly;
+                assert!((e * H.sinh() - H - M).abs() < 1e-9 * M.abs().max(1.0), "e={} M={}", e, M);
+            }
+        }
+    }
+
+    #[test]
+    fn iteration_cap() {
+        let options = KeplerOptions { tolerance: 0.0, max_iterations: 3 };
+        let solution = solve_elliptic(1.0, 0.9, &options);
+        assert!(!solution.converged);
+        assert_eq!(solution.iterations, 3);
+        assert!((mean_from_eccentric(solution.anomaly, 0.9) - 1.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn anomalies() {
+        for &e in &[0.0, 0.3, 0.95] {
+            for step in -30..=30 {
+                let E = step as f64 * 0.3;
+                let nu = true_from_eccentric(E, e);
+                assert!((eccentric_from_true(nu, e) - E).abs() < 1e-12, "e={} E={}", e, E);
+                assert!((nu - E).abs() < PI);
+            }
+        }
+        assert_eq!(true_from_eccentric(PI, 0.5), PI);
+
+        // A true anomaly of 90° is the semi-latus rectum, where cos E = e
+        let E = eccentric_from_true(PI / 2.0, 0.6);
+        assert!((E.cos() - 0.6).abs() < 1e-15);
+
+        for &e in &[1.1_f64, 2.0] {
+            let asymptote = (-1.0 / e).acos();
+            for step in -9..=9 {
+                let nu = step as f64 / 10.0 * asymptote;
+                let H = hyperbolic_from_true(nu, e);
+                assert!((true_from_hyperbolic(H, e) - nu).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn not_elliptic() {
+        solve_elliptic(1.0, 1.0, &KeplerOptions::default());
+    }
+}
@@ -10,7 +10,60 @@ The SGP4 and SDP4 models were published as FORTRAN IV in 1988. It has also
 been ported to C. This is a port to Rust.
 
 Original paper: [Hoots_Roehrich_1980_SPACETRACK_REPORT_NO_3.pdf](../Hoots_Roehrich_1980_SPACETRACK_REPORT_NO_3.pdf)
+
+## `no_std`
+
+The propagator doesn't need the standard library. Build with
+`default-features = false, features = ["libm"]` to use it on embedded
+targets: math functions then come from `libm`, and TLE parsing only needs
+an allocator (for the name and designator strings).
+
+## Circular and Equatorial Orbits
+
+The near Earth equations solve Kepler's equation and apply the periodic
+terms in Lyddane's variables, $a_{xN} = e \cos \omega$ and
+$a_{yN} = e \sin \omega$ (plus a long period term), so a zero eccentricity
+or inclination leaves nothing undefined. The remaining special cases follow
+the reference implementation: below an eccentricity of 10⁻⁴ the drag terms
+divided by $e_o$ are left out, and $1 + \cos i$ is kept from zero for a
+retrograde equatorial orbit. Deep space orbits inclined less than 0.2
+radians, geostationary satellites among them, get the lunar-solar periodics
+in Lyddane's form too.
+
+## Deep Space
+
+Orbits with periods of 225 minutes or more get the SDP4 terms as well: the
+secular and long period effects of the Sun and Moon, and for periods near
+12 and 24 hours, resonance with the Earth's gravity field. The propagator
+picks the model from the period itself, unless `PropagationOptions::model`
+forces one; `Elements::is_deep_space` tells which it will pick.
+
+## Threads
+
+A `Propagator` is `Send` and `Sync`, and propagating takes `&self`, so one
+can be shared between threads, behind an `Arc` or in a scoped thread, with
+no locking. It's never modified after it's made: where the reference code
+carries the deep space resonance integrator on from the last call, this
+propagator integrates from the epoch every time, and gets the same steps.
+
+## Allocation
+
+Making a `Propagator` and propagating it never touch the heap: there are
+no `String`s, `Vec`s or boxes anywhere between the elements and the state
+vector, near Earth or deep space, so the propagator is safe to call from
+real-time code. [`Propagator::propagate_into`](struct.Propagator.html#method.propagate_into)
+also writes the state into the caller's memory. The only exception is the
+`log` feature's warning when Kepler's equation doesn't converge, which
+allocates if the installed logger does.
+
+## Diagnostics
+
+The library never prints anything itself. With the `log` feature, warnings
+such as Kepler's equation failing to converge are sent to the
+[`log`](https://docs.rs/log) facade, so the application's logger decides
+whether and where they appear. This works with or without `std`.
 */
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![deny(
     missing_docs,
     trivial_casts,
@@ -28,66 +81,234 @@ Original paper: [Hoots_Roehrich_1980_SPACETRACK_REPORT_NO_3.pdf](../Hoots_Roehri
 )]
 
 
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("sgp4 needs either the `std` feature or the `libm` feature for math functions");
+
+// Without std, `std::` paths resolve to `core`. Tests always have std.
+#[cfg(not(any(feature = "std", test)))]
+extern crate core as std;
+#[cfg(not(any(feature = "std", test)))]
+#[macro_use]
+extern crate alloc;
+#[cfg(not(any(feature = "std", test)))]
+extern crate libm;
+
+#[cfg(feature = "chrono")]
+extern crate chrono;
+
+#[cfg(feature = "hifitime")]
+extern crate hifitime;
+
+#[cfg(feature = "serde")]
+extern crate serde;
+
+#[cfg(feature = "wasm-bindgen")]
+extern crate wasm_bindgen;
+
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+
+#[cfg(feature = "http")]
+extern crate ureq;
+#[cfg(feature = "async")]
+extern crate tokio;
+#[cfg(feature = "sqlite")]
+extern crate rusqlite;
+
+#[cfg(any(feature = "serde_json", all(test, feature = "serde")))]
+extern crate serde_json;
+#[cfg(feature = "csv")]
+extern crate csv;
+
+#[cfg(not(any(feature = "std", test)))]
+mod math;
+mod deep_space;
+#[cfg(test)]
+pub(crate) mod test_support;
+
 pub mod tle;
 pub mod coordinates;
+pub mod time;
+pub mod angle;
+pub mod observer;
+pub mod elements;
+pub mod kepler;
+pub mod classification;
+pub mod gravity;
+pub mod omm;
+pub mod oem;
+#[cfg(feature = "serde_json")]
+pub mod czml;
+pub mod ground_track;
+pub mod footprint;
+pub mod coverage;
+pub mod passes;
+pub mod radio;
+pub mod scheduling;
+pub mod sun;
+pub mod eclipse;
+pub mod conjunction;
+pub mod batch;
+pub mod precision;
+pub mod catalog;
+pub mod history;
+pub mod satcat;
+pub mod decay;
+pub mod eop;
+pub mod timescale;
+pub mod verification;
+pub mod verbose;
+pub mod fit;
+pub mod drag;
+pub mod covariance;
+#[cfg(feature = "std")]
+pub mod screening;
+#[cfg(feature = "std")]
+pub mod monte_carlo;
+#[cfg(feature = "http")]
+pub mod fetch;
+#[cfg(feature = "spacetrack")]
+pub mod spacetrack;
+#[cfg(feature = "async")]
+pub mod async_fetch;
+#[cfg(feature = "sqlite")]
+pub mod store;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "wasm-bindgen")]
+pub mod wasm;
 
-use std::io::Write;
-
+use std::f64::consts::PI;
+use std::fmt;
+use std::error;
+use deep_space::DeepSpace;
+use angle::Rad;
+use elements::Elements;
+use gravity::GravityModel;
 
-macro_rules! println_stderr(
-    ($($arg:tt)*) => { {
-        let r = writeln!(&mut ::std::io::stderr(), $($arg)*);
-        r.expect("failed printing to stderr");
-    } }
-);
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+#[cfg(feature = "hifitime")]
+use hifitime::{Epoch, Unit};
 
-/// $k_e = 7.43669161 \times 10\^{-2}$  Orbital constant for Earth defined as $\sqrt{GM_{\oplus}}$ where $G$ is Newton’s universal gravitational constant and $M_{\oplus}$ is the mass of the Earth. Units: $(\frac{\mathrm{Earth\ radii}}{\mathrm{minute}})\^{\frac{3}{2}}$
-pub const ke: f64 = 7.43669161e-2;
 
-/// $k_2 = 5.413080 \times 10\^{-4}$  Harmonic gravity constant for the SGP4 model. Defined as $\frac{1}{2}J_2aE\^2$.
-pub const k2: f64 = 5.413080e-4;
 
 /// $R_\oplus = 1.0$  Radius of the Earth (in Earth Radii).
 pub const RE: f64 = 1.0;
 
-/// $6378.135$ kilometers/Earth radii.
-pub const XKMPER: f64 = 6378.135;
+/// Minutes per day.
+pub const MINUTES_PER_DAY: f64 = 1440.0;
 
-/// S (?)
-pub const S: f64 = 1.01222928;
+/// Convergence tolerance for Kepler's equation (radians).
+pub(crate) const KEPLER_TOLERANCE: f64 = 1.0e-6;
 
-/// qs4 (?)
-pub const QS4: f64 = 1.88027916e-9;
+/// Maximum number of iterations when solving Kepler's equation.
+pub(crate) const KEPLER_ITERATIONS: usize = 10;
 
-/// $J_3 = -2.53881 \times 10\^{-4}$: the third gravitational zonal harmonic of the Earth
-pub const J3: f64 = -2.53881e-4;
+/// Eccentricity at or below which the drag terms divided by eₒ (C₃ and δM)
+/// are left out, as in the reference implementation.
+const CIRCULAR_ECCENTRICITY: f64 = 1.0e-4;
 
-/// $A_{3,0} = -J_3a_E\^3$
-pub const A30: f64 = -J3 * RE * RE * RE;
+/// Smallest 1 + θ divided by in the long period periodics, which would be
+/// zero for a retrograde equatorial orbit, as in the reference
+/// implementation.
+const RETROGRADE_EQUATORIAL: f64 = 1.5e-12;
 
-/// ## Propagate
+/// Period at or above which an orbit gets the deep space (SDP4) terms
+/// (minutes)
+pub(crate) const DEEP_SPACE_PERIOD: f64 = 225.0;
+
+/// ## Ops Mode
 ///
-/// Propagate the orbit to the desired time.
-pub fn propagate(tle: tle::TLE, time: f64) -> coordinates::TEME {
-
-    // Copy from NORAD elements
-    let n0 = tle.mean_motion;
-    let i0 = tle.i;
-    let e0 = tle.e;
-    let wo = tle.omega;
-    let Bstar = tle.bstar;
-
-    // Pre-compute expensive things
-    let cos_i0 = i0.cos();
-    let sin_io = i0.sin();
-    let cos2_i0 = cos_i0.powi(2);
-    let e02 = e0.powi(2);
+/// Which variant of the SGP4 equations to follow where implementations
+/// differ, like the `opsmode` of Vallado's reference code. `Improved` and
+/// `Afspc` recover the same mean elements, and only differ for deep space
+/// orbits: in the Greenwich sidereal time at the epoch, and in whether a
+/// negative node is wrapped into $[0, 2\pi)$ in the Lyddane modification
+/// for low inclinations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OpsMode {
 
+    /// Vallado's `opsmode = 'i'`, with the sidereal time from the IAU 1982
+    /// GMST formula (the default)
+    #[default]
+    Improved,
+
+    /// Match the operational Air Force Space Command code, and Vallado's
+    /// `opsmode = 'a'`, with the sidereal time worked out from 1970 and the
+    /// Lyddane node kept positive. Deep space results differ from
+    /// `Improved` by meters.
+    Afspc,
+
+    /// As `Improved`, but recover the semi-major axis as
+    /// $a_o / (1 - \delta_o)$, as printed in Spacetrack Report #3, rather
+    /// than from the Brouwer mean motion. Results differ from `Improved` by
+    /// meters, and from the reference code's.
+    SpacetrackReport3,
+}
+
+/// ## Model
+///
+/// Whether to propagate with SGP4 alone or with the SDP4 deep space terms
+/// added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Model {
 
-    // ************************************************************************
-    // Section 1.
-    // Convert from NORAD (TLE) mean elements to SGP4 elements.
+    /// Pick from the period: deep space at 225 minutes or more, as
+    /// [`Elements::is_deep_space`](elements/struct.Elements.html#method.is_deep_space)
+    /// decides (the default)
+    #[default]
+    Auto,
+
+    /// SGP4 only, whatever the period
+    NearEarth,
+
+    /// SDP4, whatever the period. The lunar-solar terms apply to any orbit,
+    /// and the resonance terms only to periods near 12 or 24 hours.
+    DeepSpace,
+}
+
+/// ## Kozai to Brouwer
+///
+/// Recover the Brouwer mean motion $n_o''$ (radians/minute) and semi-major
+/// axis $a_o''$ (Earth radii) SGP4 works with from the Kozai mean motion
+/// $n_o$ (radians/minute) published in a TLE, given its eccentricity and
+/// inclination (radians).
+///
+/// Every ops mode shares $n_o''$. `Improved` and `Afspc` take
+/// $a_o'' = (k_e / n_o'')\^{2/3}$, as the reference code does, while
+/// `SpacetrackReport3` takes $a_o'' = a_o / (1 - \delta_o)$. The two agree
+/// to second order in $\delta_o$.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::gravity::GravityModel;
+/// use sgp4::OpsMode;
+///
+/// // ISS: 15.54888439 rev/day, e = 0.000178, i = 51.6406°
+/// let n0 = 15.54888439 * 2.0 * std::f64::consts::PI / sgp4::MINUTES_PER_DAY;
+/// let (n, a) = sgp4::kozai_to_brouwer(n0, 0.000178, 51.6406_f64.to_radians(), &GravityModel::WGS72, OpsMode::Afspc);
+///
+/// // The Brouwer mean motion is slightly slower, with a mean semi-major
+/// // axis about 6781 km
+/// assert!(n < n0);
+/// assert!((a * 6378.135 - 6781.12).abs() < 0.01);
+/// ```
+pub fn kozai_to_brouwer(n0: f64, e0: f64, i0: f64, gravity: &GravityModel, ops_mode: OpsMode) -> (f64, f64) {
+    let ke = gravity.ke;
+    let k2 = gravity.k2();
+    let cos2_i0 = i0.cos().powi(2);
+    let e02 = e0.powi(2);
 
     // We go through two iterations of refining aₒ (semi-major axis) and
     // nₒ (mean motion)
@@ -100,7 +321,7 @@ pub fn propagate(tle: tle::TLE, time: f64) -> coordinates::TEME {
     //      3 k₂   (3 cos² iₒ - 1)
     // δ₁ = - --- ----------------
     //      2 a₁²   (1 - eₒ²)³/₂
-    let d1 = (3.0 * k2  * ( 3.0 * cos2_i0 - 1.0)) / (2.0 * a1 * a1 * ( 1.0 - e02).powf(3.0/2.0));
+    let d1 = (3.0 * k2 * ( 3.0 * cos2_i0 - 1.0)) / (2.0 * a1 * a1 * ( 1.0 - e02).powf(3.0/2.0));
 
     //         ⌈     1           134    ⌉
     // aₒ = a₁ | 1 - -δ₁ - δ₁² - ---δ₁³ |
@@ -110,159 +331,1533 @@ pub fn propagate(tle: tle::TLE, time: f64) -> coordinates::TEME {
     //      3 k₂   (3 cos² iₒ - 1)
     // δₒ = - --- ----------------
     //      2 aₒ²   (1 - eₒ²)³/₂
-    let d0 = (3.0 * k2  * ( 3.0 * cos2_i0 - 1.0)) / (2.0 * a0 * a0 * ( 1.0 - e02).powf(3.0/2.0));
+    let d0 = (3.0 * k2 * ( 3.0 * cos2_i0 - 1.0)) / (2.0 * a0 * a0 * ( 1.0 - e02).powf(3.0/2.0));
 
     //          nₒ
     // nₒ" = --------
     //       (1 + δₒ)
     let n0_dp = n0 / (1.0 + d0);
 
-    //          aₒ
-    // aₒ" = --------
-    //       (1 - δₒ)
-    let ao_dp = a0 / (1.0 - d0);
-
-
-    // ************************************************************************
-    // Section 2.
-    // Determine apogee and perigee so we can deicide which SGP4 variant to
-    // use later.
-
-    // p = [aₒ"(1 - eₒ) - Rₑ] * XKMPER
-    let perigee = (ao_dp * (1.0 - e0) - RE) * XKMPER;
-
-    // p = [aₒ"(1 + eₒ) - Rₑ] * XKMPER
-    let apogee = (ao_dp * (1.0 + e0) - RE) * XKMPER;
-
-
-    // ************************************************************************
-    // Section 3.
-    // Calculate more constants
-
-    // Set parameter "s" depending on perigee of the satellite:
-    let s: f64;
-    let qs4: f64;
-
-    // Above 156 km we use normal SGP
-    if perigee > 156.0 {
-        // Use original value of s
-        s = S;
-        qs4 = QS4;
-    }
-
-    // Between 156 and 98 km use this modification:
-    else if perigee > 98.0 {
-        // s = aₒ"(1 − eₒ) − s + aE
-        s = ao_dp * (1.0 - e0) - S + RE;
-        qs4 = (QS4.powf(1.0/4.0) + S - s).powi(4);
-    }
-    else {
-        s = (20.0 / XKMPER) + RE;
-        qs4 = (QS4.powf(1.0/4.0) + S - s).powi(4);
-    }
-
-    // θ = cos iₒ
-    let O = cos_i0;
-    let O2 = O * O;
-
-    //        1
-    // ξ = -------
-    //     aₒ" - s
-    let xi = 1.0 / (ao_dp - s);
-    let xi2 = xi.powi(2);
-    let xi3 = xi.powi(3);
-    let xi4 = xi.powi(4);
-    let xi5 = xi.powi(5);
-
-    //               ½
-    // βₒ = (1 − eₒ²)
-    let Bo = (1.0 - e02).sqrt();
-    let Bo2 = Bo.powi(2);
-
-    // η = aₒ"eₒξ
-    let n = ao_dp * e0 * xi;
-    let n2 = n.powi(2);
-    let n3 = n.powi(3);
-    let n4 = n.powi(4);
-
-    //                           -⁷/₂⌈   ⌈    3                ⌉   3   k₂ξ    ⌈ 1   3  ⌉                ⌉
-    // C₂ = (qₒ − s)⁴ξ⁴nₒ"(1 - η²)   |aₒ"|1 + -η² + 4eₒη + eₒη³| + - -------- |-- + -θ²|(8 + 24η² + 3η⁴)|
-    //                               ⌊   ⌊    2                ⌋   2 (1 - η²) ⌊ 2   2  ⌋                ⌋
-    let C2 = qs4 * xi4 * n0_dp * (1.0 - n2).powf(-7.0/2.0) * (ao_dp * (1.0 + (1.5 * n2) + (4.0 * e0 * n) + (e0 * n3)) + 1.5 * (k2 * xi)/(1.0 - n2) * (-0.5 + (1.5 * O2)) * (8.0 + (24.0 * n2) + (3.0 * n4)));
-
-    // C₁ = B*C₂
-    let C1 = Bstar * C2;
-
-    //      (qₒ − s)⁴ξ⁵A₃₀ nₒ" aE sin iₒ
-    // C₃ = -----------------------------
-    //                 k₂eₒ
-    let C3 = (qs4 * xi5 * A30 * n0_dp * RE * sin_io) / (k2 * e0);
-
-    //                                  -⁷/₂⌈⌈              1     1  ⌉      2k₂ξ       ⌈          ⌈    3            1    ⌉   3                                ⌉⌉
-    // C₄ = 2nₒ"(qₒ − s)⁴ξ⁴aₒ"βₒ²(1 - η²)   ||2η(1 + eₒη) + -eₒ + -η³| - ----------- × |3(1 - 3θ²)|1 + -η² - 2eₒη - -eₒη³| + -(1 - θ²)(2η² - eₒη - eₒη³)cos2ωₒ||
-    //                                      ⌊⌊              2     2  ⌋   aₒ"(1 - η²)   ⌊          ⌊    2            2    ⌋   4                                ⌋⌋
-    // This one is really long, so let's break it into some pieces:
-    //     |            C4_1               | |         C4_2          |  |    C4_3    | |             C4_4                |  |               C4_5              |
-    let C4_1 = 2.0 * n0_dp * qs4 * xi4 * ao_dp * Bo2 * (1.0 - n2).powf(-7.0/2.0);
-    let C4_2 = 2.0 * n * (1.0 + e0 * n) + (0.5 * e0) + (0.5 * n3);
-    let C4_3 = (2.0 * k2 * xi) / (ao_dp * (1.0 - n2));
-    let C4_4 = 3.0 * (1.0 - 3.0 * O2) * (1.0 + (1.5 * n2) - (2.0 * e0 * n) - (0.5 * e0 * n3));
-    let C4_5 = 0.75 * (1.0 - O2) * ((2.0 * n2) - (e0 * n) - (e0 * n3)) * (2.0 * wo).cos();
-    let C4 = C4_1 * (C4_2 - (C4_3 * (C4_4 + C4_5)));
-
-    //                               -⁷/₂⌈    11                ⌉
-    // C₅ = 2(qₒ − s)⁴ξ⁴aₒ"βₒ²(1 - η²)   |1 + --η(η + eₒ) + eₒη³|
-    //                                   ⌊     4                ⌋
-    let C5 = 2.0 * qs4 * xi4 * ao_dp * Bo2 * (1.0 - n2).powf(-7.0/2.0) * (1.0 + (2.75 * n * (n + e0)) + (e0 * n3));
-
-    // D₂ = 4aₒ"ξC₁²
-    let D2 = 4.0 * ao_dp * xi * C1.powi(2);
-
-    //      4
-    // D₃ = -aₒ"ξ²(17aₒ" + s)C₁³
-    //      3
-    let D3 = (4.0/3.0) * ao_dp * xi2 * (17.0 * ao_dp + s) * C1.powi(3);
-
-    //      2
-    // D₄ = -aₒ"ξ³(221aₒ" + 31s)C₁⁴
-    //      3
-    let D4 = (2.0/3.0) * ao_dp * xi3 * (221.0 * ao_dp + (31.0 * s)) * C1.powi(4);
-
-
-    // TODO: dummy
-    // Return coordinates
-    coordinates::TEME {
-        X: 0.0,
-        Y: 0.0,
-        Z: 0.0,
+    let ao_dp = match ops_mode {
+        //        kₑ  ⅔
+        // aₒ" = ---
+        //        nₒ"
+        OpsMode::Improved | OpsMode::Afspc => (ke / n0_dp).powf(2.0/3.0),
+
+        //          aₒ
+        // aₒ" = --------
+        //       (1 - δₒ)
+        OpsMode::SpacetrackReport3 => a0 / (1.0 - d0),
+    };
+
+    (n0_dp, ao_dp)
+}
+
+impl tle::TLE {
+
+    /// The Brouwer mean motion (radians/minute) and semi-major axis (Earth
+    /// radii) the propagator recovers, with the default `PropagationOptions`
+    fn brouwer(&self) -> (f64, f64) {
+        let n0 = self.mean_motion * 2.0 * PI / MINUTES_PER_DAY;
+        kozai_to_brouwer(n0, self.e, self.i.to_radians().0, &GravityModel::default(), OpsMode::default())
+    }
+
+    /// ## Semi-Major Axis
+    ///
+    /// Mean semi-major axis (kilometers), recovered from the Kozai mean
+    /// motion the same way the propagator does.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    ///
+    /// assert!((tle.semi_major_axis_km() - 6781.1).abs() < 0.1);
+    /// assert!((tle.period_minutes() - 92.62).abs() < 0.01);
+    /// println!("{:.1} x {:.1} km", tle.perigee_km(), tle.apogee_km());
+    /// ```
+    pub fn semi_major_axis_km(&self) -> f64 {
+        self.brouwer().1 * GravityModel::default().radius
+    }
+
+    /// Mean orbital period (minutes)
+    pub fn period_minutes(&self) -> f64 {
+        2.0 * PI / self.brouwer().0
+    }
+
+    /// Mean apogee altitude above the equatorial radius (kilometers)
+    pub fn apogee_km(&self) -> f64 {
+        (self.brouwer().1 * (1.0 + self.e) - RE) * GravityModel::default().radius
+    }
+
+    /// Mean perigee altitude above the equatorial radius (kilometers), the
+    /// altitude the propagator compares against 220 km to decide whether to
+    /// truncate the drag terms
+    pub fn perigee_km(&self) -> f64 {
+        (self.brouwer().1 * (1.0 - self.e) - RE) * GravityModel::default().radius
     }
 }
 
+/// ## Propagation Options
+///
+/// Settings the propagator consults: which Earth constants and variant of
+/// the equations to use, and how far from the epoch to trust an element set.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::{PropagationError, PropagationOptions, Propagator};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // Refuse to go more than two weeks from the epoch
+/// let options = PropagationOptions {
+///     max_age: Some(14.0),
+///     strict: true,
+///     ..PropagationOptions::default()
+/// };
+/// let propagator = Propagator::with_options(&tle, &options).unwrap();
+///
+/// assert!(propagator.propagate(7.0 * 1440.0).is_ok());
+/// assert_eq!(propagator.propagate(30.0 * 1440.0), Err(PropagationError::TooOld));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PropagationOptions {
+
+    /// Earth constants (default WGS72)
+    pub gravity: GravityModel,
+
+    /// Variant of the equations (default `OpsMode::Improved`)
+    pub ops_mode: OpsMode,
+
+    /// How many days before or after the epoch the elements are good for,
+    /// or `None` (the default) for no limit. Accuracy degrades by a few
+    /// kilometers a day for typical low Earth orbits.
+    pub max_age: Option<f64>,
+
+    /// Whether propagating past `max_age` is an error (`TooOld`). If not
+    /// (the default), the state is still returned and
+    /// `Propagator::is_too_old` tells whether to trust it.
+    pub strict: bool,
+
+    /// Near Earth or deep space equations (default `Model::Auto`)
+    pub model: Model,
+}
+
+/// ## Propagation Errors
+///
+/// Reasons the propagator can fail to produce a state vector.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropagationError {
+
+    /// The element set can't be propagated at all: the eccentricity is
+    /// outside $0 \le e < 1$ or the mean motion isn't positive.
+    BadElements,
+
+    /// The mean eccentricity left the range $0 \le e < 1$ after applying
+    /// the secular perturbations.
+    Diverged,
+
+    /// The semi-latus rectum went negative after applying the long period
+    /// perturbations.
+    NegativeSemiLatusRectum,
+
+    /// The satellite has reentered: atmospheric drag has shrunk the mean
+    /// semi-major axis to below the surface of the Earth, or the propagated
+    /// position is below the surface.
+    Decayed,
+
+    /// The time is further from the epoch than the `max_age` allowed by
+    /// strict `PropagationOptions`.
+    TooOld,
+}
+
+impl fmt::Display for PropagationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PropagationError::BadElements => write!(f, "element set has an eccentricity outside [0, 1) or a non-positive mean motion"),
+            PropagationError::Diverged => write!(f, "mean eccentricity diverged outside [0, 1)"),
+            PropagationError::NegativeSemiLatusRectum => write!(f, "semi-latus rectum is negative"),
+            PropagationError::Decayed => write!(f, "satellite has decayed"),
+            PropagationError::TooOld => write!(f, "time is further from the epoch than the elements are good for"),
+        }
+    }
+}
+
+impl error::Error for PropagationError {}
+
+/// ## Resonance State
+///
+/// How far the resonance integration of a deep space propagator has got,
+/// for [`Propagator::propagate_with`](struct.Propagator.html#method.propagate_with)
+/// to carry on from. A new state starts at the epoch, and one last used
+/// with a different propagator starts over.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResonanceState {
+    /// Minutes from the epoch of the last whole step
+    atime: f64,
+    /// Mean motion (radians/minute) and resonant angle (radians) there
+    xni: f64,
+    xli: f64,
+    /// The propagator's mean motion and resonant angle at the epoch
+    no: f64,
+    xlamo: f64,
+}
+
+/// ## Propagator
+///
+/// An element set converted to SGP4 elements, along with every constant
+/// that doesn't depend on time. Initialize once and reuse it to propagate
+/// the same TLE to many times.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let propagator = sgp4::Propagator::new(&tle).unwrap();
+/// let states = propagator.propagate_many(&[0.0, 1.0, 2.0]).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Propagator {
+    /// nₒ" (radians/minute)
+    n0_dp: f64,
+    /// aₒ" (Earth radii)
+    ao_dp: f64,
+    /// eₒ
+    e0: f64,
+    /// iₒ (radians)
+    i0: f64,
+    /// ωₒ (radians)
+    wo: f64,
+    /// Ωₒ (radians)
+    Omega0: f64,
+    /// Mₒ (radians)
+    M0: f64,
+    /// B*
+    Bstar: f64,
+    /// sin iₒ
+    sin_io: f64,
+    /// θ = cos iₒ
+    O: f64,
+    /// θ²
+    O2: f64,
+    /// 1 + θ, kept away from zero
+    one_plus_O: f64,
+    /// βₒ²
+    Bo2: f64,
+    /// η
+    n: f64,
+    /// (qₒ - s)⁴
+    qs4: f64,
+    /// ξ⁴
+    xi4: f64,
+    /// Use the truncated drag equations for low perigee
+    simple: bool,
+    /// Leave out the drag terms divided by eₒ for a near circular orbit
+    circular: bool,
+    /// C₁
+    C1: f64,
+    /// C₂
+    C2: f64,
+    /// C₃
+    C3: f64,
+    /// C₄
+    C4: f64,
+    /// C₅
+    C5: f64,
+    /// D₂
+    D2: f64,
+    /// D₃
+    D3: f64,
+    /// D₄
+    D4: f64,
+    /// Ṁ
+    M_dot: f64,
+    /// ω̇
+    omega_dot: f64,
+    /// First order part of Ω̇
+    Omega_dot1: f64,
+    /// Ω̇
+    Omega_dot: f64,
+    /// Earth constants
+    gravity: GravityModel,
+    /// Variant of the equations
+    ops_mode: OpsMode,
+    /// Lunar-solar and resonance terms, for a period of 225 minutes or more
+    deep_space: Option<DeepSpace>,
+    /// Longest time from the epoch to trust (minutes)
+    max_age: Option<f64>,
+    /// Refuse to propagate past `max_age`
+    strict: bool,
+    /// Epoch of the elements (Julian date, UTC)
+    epoch: f64,
+}
+
+impl Propagator {
+
+    /// Convert a TLE to SGP4 elements and compute the constants needed to
+    /// propagate it, using the default (WGS72) gravity model.
+    pub fn new(tle: &tle::TLE) -> Result<Propagator, PropagationError> {
+        Propagator::with_gravity_model(tle, GravityModel::default())
+    }
+
+    /// Convert a TLE to SGP4 elements using a particular set of Earth
+    /// constants.
+    pub fn with_gravity_model(tle: &tle::TLE, gravity: GravityModel) -> Result<Propagator, PropagationError> {
+        Propagator::with_options(tle, &PropagationOptions { gravity, ..PropagationOptions::default() })
+    }
+
+    /// Convert a TLE to SGP4 elements following a particular variant of the
+    /// equations, using the default (WGS72) gravity model.
+    pub fn with_ops_mode(tle: &tle::TLE, ops_mode: OpsMode) -> Result<Propagator, PropagationError> {
+        Propagator::with_options(tle, &PropagationOptions { ops_mode, ..PropagationOptions::default() })
+    }
+
+    /// Convert a TLE to SGP4 elements with the given `PropagationOptions`.
+    pub fn with_options(tle: &tle::TLE, options: &PropagationOptions) -> Result<Propagator, PropagationError> {
+        Propagator::init(tle, tle.epoch_julian_date(), options)
+    }
+
+    /// Compute the constants needed to propagate mean elements that are
+    /// already in radians and radians/minute, using the default (WGS72)
+    /// gravity model.
+    ///
+    /// Bare elements have no epoch, so the states it returns are dated from
+    /// Julian date zero unless one is given with
+    /// [`at_epoch`](#method.at_epoch).
+    pub fn from_elements(elements: &Elements) -> Result<Propagator, PropagationError> {
+        Propagator::init(elements, 0.0, &PropagationOptions::default())
+    }
+
+    /// Date the elements at `epoch` (Julian date, UTC), for a propagator
+    /// made with [`from_elements`](#method.from_elements)
+    pub fn at_epoch(self, epoch: f64) -> Propagator {
+        let mut propagator = Propagator { epoch, ..self };
+        if propagator.deep_space.is_some() {
+            propagator.deep_space = Some(DeepSpace::new(&propagator, propagator.ops_mode));
+        }
+        propagator
+    }
+
+    /// Epoch of the elements (Julian date, UTC)
+    pub fn epoch(&self) -> f64 {
+        self.epoch
+    }
+
+    /// Earth constants the elements were converted with, whose ellipsoid
+    /// geodetic positions should be measured from to match
+    pub fn gravity_model(&self) -> &GravityModel {
+        &self.gravity
+    }
+
+    /// Whether the SDP4 deep space terms are in use
+    pub fn is_deep_space(&self) -> bool {
+        self.deep_space.is_some()
+    }
+
+    fn init<E: Into<Elements>>(elements: E, epoch: f64, options: &PropagationOptions) -> Result<Propagator, PropagationError> {
+        let PropagationOptions { gravity, ops_mode, max_age, strict, model } = *options;
+        let k2 = gravity.k2();
+        let k4 = gravity.k4();
+        let A30 = gravity.A30();
+        let XKMPER = gravity.radius;
+
+        // s: parameter for the SGP4 density function, 78 km above the
+        // surface of the Earth (in Earth radii)
+        let S = 78.0 / XKMPER + RE;
+
+        // (qₒ - s)⁴: parameter for the SGP4 density function, where qₒ is
+        // 120 km above the surface of the Earth (in Earth radii)
+        let QS4 = ((120.0 - 78.0) / XKMPER).powi(4);
+
+        // NORAD elements, already in radians and radians per minute
+        let Elements {
+            mean_motion: n0,
+            eccentricity: e0,
+            inclination: Rad(i0),
+            raan: Rad(Omega0),
+            argp: Rad(wo),
+            mean_anomaly: Rad(M0),
+            bstar: Bstar,
+        } = elements.into();
+
+        if !(0.0..1.0).contains(&e0) || n0 <= 0.0 {
+            return Err(PropagationError::BadElements);
+        }
+
+        // Pre-compute expensive things
+        let cos_i0 = i0.cos();
+        let sin_io = i0.sin();
+        let e02 = e0.powi(2);
+
+
+        // ************************************************************************
+        // Section 1.
+        // Convert from NORAD (TLE) mean elements to SGP4 elements.
+
+        let (n0_dp, ao_dp) = kozai_to_brouwer(n0, e0, i0, &gravity, ops_mode);
+
+
+        // ************************************************************************
+        // Section 2.
+        // Determine perigee so we can deicide which SGP4 variant to use later.
+
+        // p = [aₒ"(1 - eₒ) - Rₑ] * XKMPER
+        let perigee = (ao_dp * (1.0 - e0) - RE) * XKMPER;
+
+        // For perigee below 220 km the equations are truncated to a linear
+        // variation in √a and a quadratic variation in mean anomaly. The C₃
+        // term, the δω term and the δM term are dropped.
+        let simple = perigee < 220.0;
+
+
+        // ************************************************************************
+        // Section 3.
+        // Calculate more constants
+
+        // Set parameter "s" depending on perigee of the satellite:
+        let (s, qs4) = if perigee >= 156.0 {
+            // Above 156 km we use the original value of s
+            (S, QS4)
+        }
+        else if perigee > 98.0 {
+            // Between 156 and 98 km use this modification:
+            // s = aₒ"(1 − eₒ) − s + aE
+            let s = ao_dp * (1.0 - e0) - S + RE;
+            (s, (QS4.powf(1.0/4.0) + S - s).powi(4))
+        }
+        else {
+            // Below 98 km s is fixed at 20 km
+            let s = (20.0 / XKMPER) + RE;
+            (s, (QS4.powf(1.0/4.0) + S - s).powi(4))
+        };
+
+        // θ = cos iₒ
+        let O = cos_i0;
+        let O2 = O * O;
+        let O4 = O2 * O2;
+        let one_plus_O = if (1.0 + O).abs() > RETROGRADE_EQUATORIAL { 1.0 + O } else { RETROGRADE_EQUATORIAL };
+
+        //        1
+        // ξ = -------
+        //     aₒ" - s
+        let xi = 1.0 / (ao_dp - s);
+        let xi2 = xi.powi(2);
+        let xi3 = xi.powi(3);
+        let xi4 = xi.powi(4);
+        let xi5 = xi.powi(5);
+
+        //               ½
+        // βₒ = (1 − eₒ²)
+        let Bo = (1.0 - e02).sqrt();
+        let Bo2 = Bo.powi(2);
+
+        // η = aₒ"eₒξ
+        let n = ao_dp * e0 * xi;
+        let n2 = n.powi(2);
+        let n3 = n.powi(3);
+        let n4 = n.powi(4);
+
+        //                           -⁷/₂⌈   ⌈    3                ⌉   3   k₂ξ    ⌈ 1   3  ⌉                ⌉
+        // C₂ = (qₒ − s)⁴ξ⁴nₒ"(1 - η²)   |aₒ"|1 + -η² + 4eₒη + eₒη³| + - -------- |-- + -θ²|(8 + 24η² + 3η⁴)|
+        //                               ⌊   ⌊    2                ⌋   2 (1 - η²) ⌊ 2   2  ⌋                ⌋
+        let C2 = qs4 * xi4 * n0_dp * (1.0 - n2).powf(-7.0/2.0) * (ao_dp * (1.0 + (1.5 * n2) + (4.0 * e0 * n) + (e0 * n3)) + 1.5 * (k2 * xi)/(1.0 - n2) * (-0.5 + (1.5 * O2)) * (8.0 + (24.0 * n2) + (3.0 * n4)));
+
+        // C₁ = B*C₂
+        let C1 = Bstar * C2;
+
+        //      (qₒ − s)⁴ξ⁵A₃₀ nₒ" aE sin iₒ
+        // C₃ = -----------------------------
+        //                 k₂eₒ
+        // (zero for a near circular orbit)
+        let circular = e0 <= CIRCULAR_ECCENTRICITY;
+        let C3 = if circular { 0.0 } else { (qs4 * xi5 * A30 * n0_dp * RE * sin_io) / (k2 * e0) };
+
+        //                                  -⁷/₂⌈⌈              1     1  ⌉      2k₂ξ       ⌈          ⌈    3            1    ⌉   3                                ⌉⌉
+        // C₄ = 2nₒ"(qₒ − s)⁴ξ⁴aₒ"βₒ²(1 - η²)   ||2η(1 + eₒη) + -eₒ + -η³| - ----------- × |3(1 - 3θ²)|1 + -η² - 2eₒη - -eₒη³| + -(1 - θ²)(2η² - eₒη - eₒη³)cos2ωₒ||
+        //                                      ⌊⌊              2     2  ⌋   aₒ"(1 - η²)   ⌊          ⌊    2            2    ⌋   4                                ⌋⌋
+        // This one is really long, so let's break it into some pieces:
+        //     |            C4_1               | |         C4_2          |  |    C4_3    | |             C4_4                |  |               C4_5              |
+        let C4_1 = 2.0 * n0_dp * qs4 * xi4 * ao_dp * Bo2 * (1.0 - n2).powf(-7.0/2.0);
+        let C4_2 = 2.0 * n * (1.0 + e0 * n) + (0.5 * e0) + (0.5 * n3);
+        let C4_3 = (2.0 * k2 * xi) / (ao_dp * (1.0 - n2));
+        let C4_4 = 3.0 * (1.0 - 3.0 * O2) * (1.0 + (1.5 * n2) - (2.0 * e0 * n) - (0.5 * e0 * n3));
+        let C4_5 = 0.75 * (1.0 - O2) * ((2.0 * n2) - (e0 * n) - (e0 * n3)) * (2.0 * wo).cos();
+        let C4 = C4_1 * (C4_2 - (C4_3 * (C4_4 + C4_5)));
+
+        //                               -⁷/₂⌈    11                ⌉
+        // C₅ = 2(qₒ − s)⁴ξ⁴aₒ"βₒ²(1 - η²)   |1 + --η(η + eₒ) + eₒη³|
+        //                                   ⌊     4                ⌋
+        let C5 = 2.0 * qs4 * xi4 * ao_dp * Bo2 * (1.0 - n2).powf(-7.0/2.0) * (1.0 + (2.75 * n * (n + e0)) + (e0 * n3));
+
+        // D₂ = 4aₒ"ξC₁²
+        let D2 = 4.0 * ao_dp * xi * C1.powi(2);
+
+        //      4
+        // D₃ = -aₒ"ξ²(17aₒ" + s)C₁³
+        //      3
+        let D3 = (4.0/3.0) * ao_dp * xi2 * (17.0 * ao_dp + s) * C1.powi(3);
+
+        //      2
+        // D₄ = -aₒ"²ξ³(221aₒ" + 31s)C₁⁴
+        //      3
+        let D4 = (2.0/3.0) * ao_dp * ao_dp * xi3 * (221.0 * ao_dp + (31.0 * s)) * C1.powi(4);
+
+
+        // ************************************************************************
+        // Section 4.
+        // Secular rates of change due to gravitation
+
+        // Common factors of the secular rates:
+        //
+        //      3k₂nₒ"             3k₂²nₒ"             5k₄nₒ"
+        // t₁ = --------  t₂ = ------------  t₃ = -----------
+        //      aₒ"²βₒ⁴         aₒ"⁴βₒ⁸             4aₒ"⁴βₒ⁸
+        let t1 = 3.0 * k2 * n0_dp / (ao_dp.powi(2) * Bo2.powi(2));
+        let t2 = t1 * k2 / (ao_dp.powi(2) * Bo2.powi(2));
+        let t3 = 1.25 * k4 * n0_dp / (ao_dp.powi(4) * Bo2.powi(4));
+
+        //            ⌈    3k₂(-1 + 3θ²)    3k₂²(13 - 78θ² + 137θ⁴)⌉
+        // Ṁ = nₒ" × |1 + ------------- + -----------------------|
+        //            ⌊      2aₒ"²βₒ³            16aₒ"⁴βₒ⁷        ⌋
+        let M_dot = n0_dp + 0.5 * t1 * Bo * (3.0 * O2 - 1.0) + 0.0625 * t2 * Bo * (13.0 - (78.0 * O2) + (137.0 * O4));
+
+        //           ⌈ 3k₂(1 - 5θ²)   3k₂²(7 - 114θ² + 395θ⁴)   5k₄(3 - 36θ² + 49θ⁴)⌉
+        // ω̇ = nₒ" × |-------------- + ----------------------- + --------------------|
+        //           ⌊   2aₒ"²βₒ⁴          16aₒ"⁴βₒ⁸               4aₒ"⁴βₒ⁸      ⌋
+        let omega_dot = -0.5 * t1 * (1.0 - 5.0 * O2) + 0.0625 * t2 * (7.0 - (114.0 * O2) + (395.0 * O4)) + t3 * (3.0 - (36.0 * O2) + (49.0 * O4));
+
+        //           ⌈  3k₂θ    3k₂²(4θ - 19θ³)   5k₄θ(3 - 7θ²)⌉
+        // Ω̇ = nₒ" × |- ----- + --------------- + -------------|
+        //           ⌊  aₒ"²βₒ⁴    2aₒ"⁴βₒ⁸          2aₒ"⁴βₒ⁸ ⌋
+        let Omega_dot1 = -t1 * O;
+        let Omega_dot = Omega_dot1 + (0.5 * t2 * (4.0 - 19.0 * O2) + 2.0 * t3 * (3.0 - 7.0 * O2)) * O;
+
+        let mut propagator = Propagator {
+            n0_dp,
+            ao_dp,
+            e0,
+            i0,
+            wo,
+            Omega0,
+            M0,
+            Bstar,
+            sin_io,
+            O,
+            O2,
+            one_plus_O,
+            Bo2,
+            n,
+            qs4,
+            xi4,
+            simple,
+            circular,
+            C1,
+            C2,
+            C3,
+            C4,
+            C5,
+            D2,
+            D3,
+            D4,
+            M_dot,
+            omega_dot,
+            Omega_dot1,
+            Omega_dot,
+            gravity,
+            ops_mode,
+            deep_space: None,
+            max_age: max_age.map(|days| days * MINUTES_PER_DAY),
+            strict,
+            epoch,
+        };
+
+        // Deep space orbits also get the effects of the Sun and Moon, and of
+        // resonance with the Earth's gravity field, which outweigh all but
+        // the simplest drag terms
+        let deep_space = match model {
+            Model::Auto => 2.0 * PI / n0_dp >= DEEP_SPACE_PERIOD,
+            Model::NearEarth => false,
+            Model::DeepSpace => true,
+        };
+        if deep_space {
+            propagator.simple = true;
+            propagator.deep_space = Some(DeepSpace::new(&propagator, ops_mode));
+        }
+
+        Ok(propagator)
+    }
+
+    /// ## Propagate
+    ///
+    /// Propagate the orbit to the desired time.
+    ///
+    /// `time` is the number of minutes since the TLE epoch. The returned
+    /// position is in kilometers and the velocity in kilometers/second.
+    pub fn propagate(&self, time: f64) -> Result<coordinates::StateVector, PropagationError> {
+        self.propagate_recording(time, &mut ResonanceState::default(), &mut verbose::Intermediates::default())
+    }
+
+    /// ## Propagate With
+    ///
+    /// Propagate the orbit to `time` minutes since the TLE epoch, as
+    /// [`propagate`](#method.propagate), carrying on the resonance
+    /// integration from where `resonance` left it. The results are the
+    /// same, but a deep space orbit in resonance integrates from the epoch
+    /// in 720 minute steps on every call to `propagate`, which adds up when
+    /// sampling far from the epoch. Going on from the last time only takes
+    /// the steps in between, as long as the times keep moving away from the
+    /// epoch.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// use sgp4::ResonanceState;
+    ///
+    /// // A geostationary satellite, in resonance with the Earth's gravity
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "28626",
+    ///     "1 28626U 05008A   06176.46683397 -.00000205  00000-0  10000-3 0  2190",
+    ///     "2 28626   0.0019 286.9433 0000335  13.7918  55.6504  1.00270176  4891",
+    /// ).unwrap();
+    /// let propagator = sgp4::Propagator::new(&tle).unwrap();
+    ///
+    /// // A month, hourly
+    /// let mut resonance = ResonanceState::default();
+    /// for hour in 0..30 * 24 {
+    ///     let time = 60.0 * hour as f64;
+    ///     let state = propagator.propagate_with(&mut resonance, time).unwrap();
+    ///     assert_eq!(state, propagator.propagate(time).unwrap());
+    /// }
+    /// ```
+    pub fn propagate_with(&self, resonance: &mut ResonanceState, time: f64) -> Result<coordinates::StateVector, PropagationError> {
+        self.propagate_recording(time, resonance, &mut verbose::Intermediates::default())
+    }
+
+    /// ## Propagate To Epoch
+    ///
+    /// Propagate the orbit to a hifitime `Epoch`, in any time scale,
+    /// counting the elapsed time from the propagator's epoch. That's kept
+    /// as a Julian date, which resolves some tens of microseconds; use
+    /// the free function [`propagate_to_epoch`](fn.propagate_to_epoch.html)
+    /// to start from the element set's epoch to the nanosecond. Requires
+    /// the `hifitime` feature.
+    #[cfg(feature = "hifitime")]
+    pub fn propagate_to_epoch(&self, time: Epoch) -> Result<coordinates::StateVector, PropagationError> {
+        self.propagate((time - time::epoch_from_julian_date(self.epoch)).to_unit(Unit::Minute))
+    }
+
+    /// ## Propagate Into
+    ///
+    /// Propagate the orbit to `time` minutes since the epoch, as
+    /// [`propagate`](#method.propagate), writing the state into `state`.
+    /// It doesn't allocate, and on an error `state` is left as it was.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// use sgp4::coordinates::{StateVector, TEME};
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    /// let propagator = sgp4::Propagator::new(&tle).unwrap();
+    ///
+    /// // Reuse one state every tick
+    /// let mut state = StateVector {
+    ///     epoch: 0.0,
+    ///     position: TEME::new(0.0, 0.0, 0.0),
+    ///     velocity: TEME::new(0.0, 0.0, 0.0),
+    /// };
+    /// for second in 0..60 {
+    ///     propagator.propagate_into(second as f64 / 60.0, &mut state).unwrap();
+    /// }
+    /// ```
+    pub fn propagate_into(&self, time: f64, state: &mut coordinates::StateVector) -> Result<(), PropagationError> {
+        *state = self.propagate(time)?;
+        Ok(())
+    }
+
+    /// ## Propagate Range Into
+    ///
+    /// Fill `out` with the states at `start`, `start + step`,
+    /// `start + 2 * step` and so on (minutes since the epoch), one per
+    /// element, without allocating. It stops at the first error and
+    /// returns it, with the states before it written and the rest left as
+    /// they were.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// use sgp4::coordinates::{StateVector, TEME};
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    /// let propagator = sgp4::Propagator::new(&tle).unwrap();
+    ///
+    /// // One orbit, once a minute, into a buffer on the stack
+    /// let empty = StateVector { epoch: 0.0, position: TEME::new(0.0, 0.0, 0.0), velocity: TEME::new(0.0, 0.0, 0.0) };
+    /// let mut states = [empty; 93];
+    /// propagator.propagate_range_into(0.0, 1.0, &mut states).unwrap();
+    /// assert_eq!(states[92], propagator.propagate(92.0).unwrap());
+    /// ```
+    pub fn propagate_range_into(&self, start: f64, step: f64, out: &mut [coordinates::StateVector]) -> Result<(), PropagationError> {
+        let mut resonance = ResonanceState::default();
+        for (index, state) in out.iter_mut().enumerate() {
+            *state = self.propagate_with(&mut resonance, start + step * index as f64)?;
+        }
+        Ok(())
+    }
+
+    /// Propagate, keeping the intermediate quantities in `record` as they're
+    /// computed. The arithmetic is the double precision kernel's.
+    fn propagate_recording(&self, time: f64, resonance: &mut ResonanceState, record: &mut verbose::Intermediates) -> Result<coordinates::StateVector, PropagationError> {
+        let state = precision::Kernel::<f64>::new(self).propagate_recording(time, resonance, record)?;
+        let (position, velocity) = (state.position, state.velocity);
+        Ok(coordinates::StateVector {
+            epoch: self.epoch + time / MINUTES_PER_DAY,
+            position: coordinates::TEME::new(position[0], position[1], position[2]),
+            velocity: coordinates::TEME::new(velocity[0], velocity[1], velocity[2]),
+        })
+    }
+
+    /// ## Propagate In
+    ///
+    /// Propagate the orbit to `time` minutes since the TLE epoch, with the
+    /// state in `units`. Earth radii are those of the propagator's gravity
+    /// model.
+    pub fn propagate_in(&self, time: f64, units: coordinates::Units) -> Result<coordinates::StateVector, PropagationError> {
+        Ok(self.propagate(time)?.in_units(units, &self.gravity))
+    }
+
+    /// ## Mean Elements
+    ///
+    /// The mean elements at `time` minutes since the TLE epoch: the epoch
+    /// elements with the secular effects of gravity and drag applied, but
+    /// none of the periodic terms. These are what to trend to see an orbit
+    /// decay or its plane precess.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    /// let propagator = sgp4::Propagator::new(&tle).unwrap();
+    ///
+    /// // The node regresses about 5° a day
+    /// let now = propagator.mean_elements(0.0).unwrap();
+    /// let tomorrow = propagator.mean_elements(1440.0).unwrap();
+    /// let regression = (now.raan - tomorrow.raan).to_degrees();
+    /// assert!((regression - sgp4::angle::Deg(5.0)).abs() < sgp4::angle::Deg(0.1));
+    /// ```
+    pub fn mean_elements(&self, time: f64) -> Result<elements::MeanElements, PropagationError> {
+        let secular = precision::Kernel::<f64>::new(self).secular(time, &mut ResonanceState::default(), &mut verbose::Intermediates::default())?;
+        let angle = |angle: f64| Rad(angle).normalized();
+        Ok(elements::MeanElements {
+            epoch: self.epoch + time / MINUTES_PER_DAY,
+            semi_major_axis: secular.a * self.gravity.radius,
+            eccentricity: secular.e,
+            inclination: Rad(secular.i),
+            raan: angle(secular.Omega),
+            argp: angle(secular.omega),
+            mean_anomaly: angle(secular.M),
+            mean_motion: secular.n,
+        })
+    }
+
+    /// Whether `time` (minutes since the TLE epoch) is further from the
+    /// epoch than the `max_age` the propagator was created with.
+    pub fn is_too_old(&self, time: f64) -> bool {
+        self.max_age.is_some_and(|max_age| time.abs() > max_age)
+    }
+
+    /// Propagate the orbit to each of `times` (minutes since the TLE
+    /// epoch), stopping at the first error.
+    pub fn propagate_many(&self, times: &[f64]) -> Result<Vec<coordinates::StateVector>, PropagationError> {
+        let mut resonance = ResonanceState::default();
+        times.iter().map(|&time| self.propagate_with(&mut resonance, time)).collect()
+    }
+
+    /// ## Sample
+    ///
+    /// Lazily propagate the orbit from `start` to `end` (inclusive) every
+    /// `step` minutes since the TLE epoch. Each item is the time and the
+    /// result of propagating to it.
+    ///
+    /// ### Panics
+    ///
+    /// If `step` isn't positive.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    /// let propagator = sgp4::Propagator::new(&tle).unwrap();
+    ///
+    /// // One orbit, once a minute
+    /// for (time, state) in propagator.sample(0.0, 92.0, 1.0) {
+    ///     let state = state.unwrap();
+    ///     println!("{} {} {} {}", time, state.position.X, state.position.Y, state.position.Z);
+    /// }
+    /// ```
+    pub fn sample(&self, start: f64, end: f64, step: f64) -> Samples<'_> {
+        assert!(step > 0.0, "sample step must be positive");
+
+        // Count the samples up front rather than accumulating steps, so
+        // rounding error doesn't drop or add the last one
+        let count = if end < start {
+            0
+        } else {
+            ((end - start) / step + 1.0e-9).floor() as usize + 1
+        };
+
+        Samples {
+            propagator: self,
+            resonance: ResonanceState::default(),
+            start,
+            step,
+            index: 0,
+            count,
+        }
+    }
+
+    /// ## Decay Time
+    ///
+    /// The time of the first sample from `start` to `end` (every `step`
+    /// minutes since the TLE epoch) at which the satellite has decayed, or
+    /// `None` if it's still in orbit at every sample.
+    ///
+    /// ### Panics
+    ///
+    /// If `step` isn't positive.
+    pub fn decay_time(&self, start: f64, end: f64, step: f64) -> Option<f64> {
+        self.sample(start, end, step)
+            .find(|(_, state)| *state == Err(PropagationError::Decayed))
+            .map(|(time, _)| time)
+    }
+}
+
+/// ## Samples
+///
+/// Iterator over evenly spaced propagation results, created by
+/// `Propagator::sample`.
+#[derive(Debug, Clone)]
+pub struct Samples<'a> {
+    propagator: &'a Propagator,
+    resonance: ResonanceState,
+    start: f64,
+    step: f64,
+    index: usize,
+    count: usize,
+}
+
+impl<'a> Iterator for Samples<'a> {
+    type Item = (f64, Result<coordinates::StateVector, PropagationError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+        let time = self.start + self.step * self.index as f64;
+        self.index += 1;
+        Some((time, self.propagator.propagate_with(&mut self.resonance, time)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<'a> ExactSizeIterator for Samples<'a> {}
+
+/// ## Propagate
+///
+/// Propagate the orbit to the desired time.
+///
+/// `time` is the number of minutes since the TLE epoch. The returned
+/// position is in kilometers and the velocity in kilometers/second.
+pub fn propagate(tle: &tle::TLE, time: f64) -> Result<coordinates::StateVector, PropagationError> {
+    Propagator::new(tle)?.propagate(time)
+}
+
+/// ## Propagate To
+///
+/// Propagate the orbit to an absolute UTC time. Requires the `chrono`
+/// feature.
+///
+/// ### Example
+///
+/// ```
+/// extern crate chrono;
+/// extern crate sgp4;
+///
+/// use chrono::{TimeZone, Utc};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let time = Utc.with_ymd_and_hms(2016, 7, 29, 0, 0, 0).unwrap();
+/// let state = sgp4::propagate_to(&tle, time).unwrap();
+/// ```
+#[cfg(feature = "chrono")]
+pub fn propagate_to(tle: &tle::TLE, time: DateTime<Utc>) -> Result<coordinates::StateVector, PropagationError> {
+    propagate(tle, minutes_since(tle.epoch(), time))
+}
+
+/// ## Propagate To Epoch
+///
+/// Propagate the orbit to a hifitime `Epoch`, in any time scale. The time
+/// since the element set epoch is the elapsed time, so a leap second
+/// between them is counted. Requires the `hifitime` feature.
+///
+/// ### Example
+///
+/// ```
+/// extern crate hifitime;
+/// extern crate sgp4;
+///
+/// use hifitime::Epoch;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let time = Epoch::from_gregorian_utc_at_midnight(2016, 7, 29);
+/// let state = sgp4::propagate_to_epoch(&tle, time).unwrap();
+/// ```
+#[cfg(feature = "hifitime")]
+pub fn propagate_to_epoch(tle: &tle::TLE, time: Epoch) -> Result<coordinates::StateVector, PropagationError> {
+    propagate(tle, (time - tle.hifitime_epoch()).to_unit(Unit::Minute))
+}
+
+/// Minutes from `epoch` to `time`
+#[cfg(feature = "chrono")]
+fn minutes_since(epoch: DateTime<Utc>, time: DateTime<Utc>) -> f64 {
+    let elapsed = time - epoch;
+    elapsed.num_seconds() as f64 / 60.0 + f64::from(elapsed.subsec_nanos()) / 60.0e9
+}
+
+/// ## Propagate Many
+///
+/// Propagate the orbit to each of `times` (minutes since the TLE epoch),
+/// converting the elements only once.
+pub fn propagate_many(tle: &tle::TLE, times: &[f64]) -> Result<Vec<coordinates::StateVector>, PropagationError> {
+    Propagator::new(tle)?.propagate_many(times)
+}
+
 #[cfg(test)]
 mod tests {
 
-    use tle::load_from_str;
-    use coordinates::TEME;
-    use super::propagate;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use super::{propagate, propagate_many, OpsMode, Propagator, PropagationError, MINUTES_PER_DAY};
+    use angle::Deg;
+    use test_support::{self, spacetrack_report_3_sdp4, spacetrack_report_3_sgp4};
 
     #[test]
     fn spacetrack_report_3_sgp4_test_case() {
-        // This testcase is from "SPACETRACK REPORT NO. 3, Models for
-        // Propagation of NORAD Element Sets, Hoots & Roehrich 1980
-        // pg. 81:
-        let tle = load_from_str(
-            "Test",
-            "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0     8",
-            "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518   105",
-        );
-
-        // Compute
-        let result0 = propagate(tle, 0.0);
-        assert_eq!(result0, TEME {
-            X: 0.0,
-            Y: 0.0,
-            Z: 0.0,
-        });
+        // Expected SGP4 positions and velocities from pg. 81 of the report:
+        let expected = [
+            (   0.0, 2328.97048951, -5995.22076416, 1719.97067261, 2.91207230, -0.98341546, -7.09081703),
+            ( 360.0, 2456.10705566, -6071.93853760, 1222.89727783, 2.67938992, -0.44829041, -7.22879231),
+            ( 720.0, 2567.56195068, -6112.50384522,  713.96397400, 2.44024599,  0.09810869, -7.31995916),
+            (1080.0, 2663.09078980, -6115.48229980,  196.39640427, 2.19611958,  0.65241995, -7.36282432),
+            (1440.0, 2742.55133057, -6079.67144775, -326.38095856, 1.94850229,  1.21106251, -7.35619372),
+        ];
+
+        let tle = spacetrack_report_3_sgp4();
+        for &(time, x, y, z, xdot, ydot, zdot) in expected.iter() {
+
+            // Compute
+            let result = propagate(&tle, time).unwrap();
+            let position = result.position;
+            let velocity = result.velocity;
+
+            // The report's FORTRAN used single precision in places, so we
+            // agree to within ten meters and two centimeters per second
+            assert!((position.X - x).abs() < 1e-2, "t={}: X={} expected {}", time, position.X, x);
+            assert!((position.Y - y).abs() < 1e-2, "t={}: Y={} expected {}", time, position.Y, y);
+            assert!((position.Z - z).abs() < 1e-2, "t={}: Z={} expected {}", time, position.Z, z);
+            assert!((velocity.X - xdot).abs() < 2e-5, "t={}: XDOT={} expected {}", time, velocity.X, xdot);
+            assert!((velocity.Y - ydot).abs() < 2e-5, "t={}: YDOT={} expected {}", time, velocity.Y, ydot);
+            assert!((velocity.Z - zdot).abs() < 2e-5, "t={}: ZDOT={} expected {}", time, velocity.Z, zdot);
+        }
+    }
+
+    #[test]
+    fn spacetrack_report_3_sdp4_test_case() {
+        // Expected SDP4 positions and velocities from pg. 82 of the report:
+        let expected = [
+            (   0.0,  7473.37066650,   428.95261765,   5828.74786377,  5.10715130,  6.44468284, -0.18613096),
+            ( 360.0, -3305.22537232, 32410.86328125, -24697.17675781, -1.30113538, -1.15131518, -0.28333528),
+            ( 720.0, 14271.28759766, 24110.46411133,  -4725.76837158, -0.32050445,  2.67984074, -2.08405289),
+            (1080.0, -9990.05883789, 22717.35522461, -23616.89062500, -1.01667246, -2.29026759,  0.72892364),
+            (1440.0,  9787.86975097, 33753.34667969, -15030.81176758, -1.09425966,  0.92358845, -1.52230928),
+        ];
+
+        let tle = spacetrack_report_3_sdp4();
+        for &(time, x, y, z, xdot, ydot, zdot) in expected.iter() {
+            let result = propagate(&tle, time).unwrap();
+            let position = result.position;
+            let velocity = result.velocity;
+
+            // Out at 40000 km the report's single precision costs more, so
+            // we agree to within thirty meters and a centimeter per second
+            assert!((position.X - x).abs() < 3e-2, "t={}: X={} expected {}", time, position.X, x);
+            assert!((position.Y - y).abs() < 3e-2, "t={}: Y={} expected {}", time, position.Y, y);
+            assert!((position.Z - z).abs() < 3e-2, "t={}: Z={} expected {}", time, position.Z, z);
+            assert!((velocity.X - xdot).abs() < 1e-5, "t={}: XDOT={} expected {}", time, velocity.X, xdot);
+            assert!((velocity.Y - ydot).abs() < 1e-5, "t={}: YDOT={} expected {}", time, velocity.Y, ydot);
+            assert!((velocity.Z - zdot).abs() < 1e-5, "t={}: ZDOT={} expected {}", time, velocity.Z, zdot);
+        }
+    }
+
+    #[test]
+    fn resonance() {
+        // A geostationary satellite stays near 42164 km, and comes back to
+        // nearly the same place each sidereal day
+        let geo = test_support::geostationary();
+        let propagator = Propagator::new(&geo).unwrap();
+
+        // The reference code's (tcppver.out) velocity at the epoch
+        let state = Propagator::with_ops_mode(&geo, OpsMode::Afspc).unwrap().propagate(0.0).unwrap();
+        let velocity = [state.velocity.X, state.velocity.Y, state.velocity.Z];
+        for (ours, theirs) in velocity.iter().zip(&[0.193105177, 3.068688251, 0.000438449]) {
+            assert!((ours - theirs).abs() < 1e-6, "{:?}", velocity);
+        }
+
+        for day in 0..30 {
+            let radius = propagator.propagate(day as f64 * MINUTES_PER_DAY).unwrap().position.norm();
+            assert!((radius - 42164.0).abs() < 50.0, "day {}: {} km", day, radius);
+        }
+
+        // A Molniya orbit stays between its perigee and apogee
+        let molniya = test_support::molniya();
+        // The reference code's (tcppver.out) state at the epoch, to a few
+        // meters
+        let state = Propagator::with_ops_mode(&molniya, OpsMode::Afspc).unwrap().propagate(0.0).unwrap();
+        let position = [state.position.X, state.position.Y, state.position.Z];
+        let velocity = [state.velocity.X, state.velocity.Y, state.velocity.Z];
+        for (ours, theirs) in position.iter().zip(&[2349.89483350, -14785.93811562, 0.02119378]) {
+            assert!((ours - theirs).abs() < 0.003, "{:?}", position);
+        }
+        for (ours, theirs) in velocity.iter().zip(&[2.721488096, -3.256811655, 4.498416672]) {
+            assert!((ours - theirs).abs() < 1e-6, "{:?}", velocity);
+        }
+
+        let propagator = Propagator::new(&molniya).unwrap();
+        let (perigee, apogee) = (molniya.perigee_km() + 6378.135, molniya.apogee_km() + 6378.135);
+        for hour in 0..(24 * 30) {
+            let radius = propagator.propagate(hour as f64 * 60.0).unwrap().position.norm();
+            assert!(radius > perigee - 100.0 && radius < apogee + 100.0, "hour {}: {} km", hour, radius);
+        }
+
+        // Each time integrates from the epoch, so the order doesn't matter
+        let times = [7200.0, -1440.0, 30.0, 7200.0];
+        let forward: Vec<_> = times.iter().map(|&time| propagator.propagate(time).unwrap()).collect();
+        let backward: Vec<_> = times.iter().rev().map(|&time| propagator.propagate(time).unwrap()).collect();
+        for (forward, backward) in forward.iter().zip(backward.iter().rev()) {
+            assert_eq!(forward, backward);
+        }
+    }
+
+    /// Counts each thread's heap allocations, so a test can check a call
+    /// makes none while others run alongside it
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    #[allow(unsafe_code)]
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn no_allocation() {
+        let tles = [
+            spacetrack_report_3_sgp4(),
+            test_support::molniya(),
+        ];
+        let mut decayed = tles[0].clone();
+        decayed.bstar = 0.5;
+
+        for tle in tles.iter().chain(Some(&decayed)) {
+            let mut state = propagate(tle, 0.0).unwrap();
+            let before = ALLOCATIONS.with(Cell::get);
+            let propagator = Propagator::new(tle).unwrap();
+            let mut errors = 0;
+            for day in 0..30 {
+                errors += propagator.propagate_into(day as f64 * MINUTES_PER_DAY, &mut state).is_err() as usize;
+            }
+            assert_eq!(ALLOCATIONS.with(Cell::get), before);
+            assert_eq!(errors > 0, tle.bstar == 0.5);
+        }
+
+        let propagator = Propagator::new(&tles[1]).unwrap();
+        let mut states = [propagate(&tles[1], 0.0).unwrap(); 64];
+        let before = ALLOCATIONS.with(Cell::get);
+        propagator.propagate_range_into(-1440.0, 90.0, &mut states).unwrap();
+        assert_eq!(ALLOCATIONS.with(Cell::get), before);
+    }
+
+    #[test]
+    fn propagate_range_into() {
+        let tle = spacetrack_report_3_sgp4();
+        let propagator = Propagator::new(&tle).unwrap();
+        let mut states = [propagator.propagate(0.0).unwrap(); 5];
+        propagator.propagate_range_into(-720.0, 360.0, &mut states).unwrap();
+        for (index, state) in states.iter().enumerate() {
+            assert_eq!(*state, propagator.propagate(-720.0 + 360.0 * index as f64).unwrap());
+        }
+        assert!(propagator.propagate_range_into(0.0, 1.0, &mut []).is_ok());
+
+        // Filled up to the first failure
+        let mut bad = tle.clone();
+        bad.bstar = 0.5;
+        let propagator = Propagator::new(&bad).unwrap();
+        let decay = propagator.decay_time(0.0, 30.0 * MINUTES_PER_DAY, MINUTES_PER_DAY).unwrap();
+        let unwritten = states[4];
+        let result = propagator.propagate_range_into(0.0, MINUTES_PER_DAY, &mut states);
+        assert_eq!(result, Err(PropagationError::Decayed));
+        let written = (decay / MINUTES_PER_DAY) as usize;
+        assert!(written < 4);
+        assert_eq!(states[written - 1], propagator.propagate(decay - MINUTES_PER_DAY).unwrap());
+        assert_eq!(states[4], unwritten);
+    }
+
+    #[test]
+    fn propagate_into() {
+        let tle = spacetrack_report_3_sgp4();
+        let propagator = Propagator::new(&tle).unwrap();
+        let mut state = propagator.propagate(0.0).unwrap();
+        propagator.propagate_into(360.0, &mut state).unwrap();
+        assert_eq!(state, propagator.propagate(360.0).unwrap());
+
+        // Left alone on an error
+        let mut bad = tle.clone();
+        bad.bstar = 0.5;
+        let propagator = Propagator::new(&bad).unwrap();
+        assert!(propagator.propagate_into(30.0 * MINUTES_PER_DAY, &mut state).is_err());
+        assert_eq!(state, Propagator::new(&tle).unwrap().propagate(360.0).unwrap());
+    }
+
+    #[test]
+    fn threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Propagator>();
+
+        let tles = [
+            spacetrack_report_3_sgp4(),
+            test_support::geostationary(),
+            test_support::molniya(),
+        ];
+
+        // Every thread propagates the same object to times in a different
+        // order, so resonance steps would be shared if any state were kept
+        for tle in tles.iter() {
+            let propagator = Propagator::new(tle).unwrap();
+            let times: Vec<f64> = (0..50).map(|index| index as f64 * 997.0 - 5000.0).collect();
+            let expected: Vec<_> = times.iter().map(|&time| propagator.propagate(time)).collect();
+            ::std::thread::scope(|scope| {
+                for thread in 0..8 {
+                    let (propagator, times, expected) = (&propagator, &times, &expected);
+                    scope.spawn(move || {
+                        for index in (0..times.len()).map(|index| (index * 7 + thread * 13) % times.len()) {
+                            assert_eq!(propagator.propagate(times[index]), expected[index]);
+                        }
+                    });
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn propagate_many_matches_propagate() {
+        let tle = spacetrack_report_3_sgp4();
+        let times = [0.0, 360.0, 720.0, 1080.0, 1440.0];
+
+        let states = propagate_many(&tle, &times).unwrap();
+        assert_eq!(states.len(), times.len());
+        for (state, &time) in states.iter().zip(times.iter()) {
+            assert_eq!(*state, propagate(&tle, time).unwrap());
+        }
+
+        // Stops at the first failure
+        let propagator = Propagator::new(&tle).unwrap();
+        assert_eq!(propagator.propagate_many(&[0.0, 525600.0]), Err(PropagationError::Diverged));
+    }
+
+    #[test]
+    fn sample() {
+        let tle = spacetrack_report_3_sgp4();
+        let propagator = Propagator::new(&tle).unwrap();
+
+        let samples: Vec<_> = propagator.sample(0.0, 1440.0, 360.0).collect();
+        assert_eq!(samples.len(), 5);
+        for (i, &(time, ref state)) in samples.iter().enumerate() {
+            assert_eq!(time, 360.0 * i as f64);
+            assert_eq!(*state, propagator.propagate(time));
+        }
+
+        // Rounding doesn't lose the last sample
+        assert_eq!(propagator.sample(0.0, 1.0, 0.1).count(), 11);
+        assert_eq!(propagator.sample(0.0, 1.05, 0.1).len(), 11);
+        assert_eq!(propagator.sample(10.0, 0.0, 1.0).count(), 0);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn propagate_to() {
+        use chrono::Duration;
+
+        let tle = test_support::iss();
+
+        let state = super::propagate_to(&tle, tle.epoch() + Duration::minutes(90)).unwrap();
+        let expected = propagate(&tle, 90.0).unwrap();
+        assert!((state.position.X - expected.position.X).abs() < 1e-9);
+        assert!((state.position.Y - expected.position.Y).abs() < 1e-9);
+        assert!((state.position.Z - expected.position.Z).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "hifitime")]
+    #[test]
+    fn propagate_to_epoch() {
+        use hifitime::{TimeScale, Unit};
+
+        let tle = test_support::iss();
+        let expected = propagate(&tle, 90.0).unwrap();
+
+        let utc = tle.hifitime_epoch() + 90 * Unit::Minute;
+        let state = super::propagate_to_epoch(&tle, utc).unwrap();
+        assert!((state.position - expected.position).norm() < 1e-9);
+
+        // The same instant in another time scale
+        let gps = utc.in_time_scale(TimeScale::GPST);
+        assert!((super::propagate_to_epoch(&tle, gps).unwrap().position - expected.position).norm() < 1e-9);
+
+        // From the Julian date, to within the tens of microseconds it
+        // resolves, a few millimeters at 7.7 km/s
+        let propagator = Propagator::new(&tle).unwrap();
+        let state = propagator.propagate_to_epoch(utc).unwrap();
+        assert!((state.position - expected.position).norm() < 1e-3);
+    }
+
+    #[test]
+    fn gravity_models() {
+        use gravity::GravityModel;
+
+        let tle = spacetrack_report_3_sgp4();
+        let wgs72 = Propagator::new(&tle).unwrap().propagate(1440.0).unwrap();
+        let wgs72_old = Propagator::with_gravity_model(&tle, GravityModel::WGS72_OLD).unwrap().propagate(1440.0).unwrap();
+        let wgs84 = Propagator::with_gravity_model(&tle, GravityModel::WGS84).unwrap().propagate(1440.0).unwrap();
+
+        // The two WGS72 sets differ only in the last digits of ke
+        assert!((wgs72.position.X - wgs72_old.position.X).abs() < 1e-3);
+        assert!((wgs72.position.Y - wgs72_old.position.Y).abs() < 1e-3);
+        assert!((wgs72.position.Z - wgs72_old.position.Z).abs() < 1e-3);
+
+        // WGS84 moves the satellite, but not far
+        let difference = ((wgs72.position.X - wgs84.position.X).powi(2)
+                        + (wgs72.position.Y - wgs84.position.Y).powi(2)
+                        + (wgs72.position.Z - wgs84.position.Z).powi(2)).sqrt();
+        assert!(difference > 1e-3);
+        assert!(difference < 10.0);
+    }
+
+    #[test]
+    fn output_units() {
+        use coordinates::Units;
+
+        let propagator = Propagator::new(&spacetrack_report_3_sgp4()).unwrap();
+        let km = propagator.propagate(360.0).unwrap();
+        let radii = propagator.propagate_in(360.0, Units::EarthRadii).unwrap();
+        let meters = propagator.propagate_in(360.0, Units::Meters).unwrap();
+        assert_eq!(propagator.propagate_in(360.0, Units::Kilometers).unwrap(), km);
+
+        // A low orbit, a little over an Earth radius out, at about an Earth
+        // radius every 14 minutes
+        assert!(radii.position.norm() > 1.0 && radii.position.norm() < 1.1);
+        assert!((radii.velocity.norm() - 1.0 / 14.0).abs() < 0.005);
+        assert!((radii.position.norm() * propagator.gravity.radius - km.position.norm()).abs() < 1e-9);
+        assert!((meters.velocity.norm() - 1000.0 * km.velocity.norm()).abs() < 1e-9);
+        assert_eq!(meters.epoch, km.epoch);
+    }
+
+    #[test]
+    fn kozai_to_brouwer() {
+        use gravity::GravityModel;
+        use std::f64::consts::PI;
+
+        let tle = spacetrack_report_3_sgp4();
+        let gravity = GravityModel::WGS72;
+        let n0 = tle.mean_motion * 2.0 * PI / MINUTES_PER_DAY;
+        let (e0, i0) = (tle.e, tle.i.to_radians().0);
+
+        let (n_improved, a_improved) = super::kozai_to_brouwer(n0, e0, i0, &gravity, OpsMode::Improved);
+        let (n_afspc, a_afspc) = super::kozai_to_brouwer(n0, e0, i0, &gravity, OpsMode::Afspc);
+        let (n_report, a_report) = super::kozai_to_brouwer(n0, e0, i0, &gravity, OpsMode::SpacetrackReport3);
+
+        // The reference code's two modes recover the same elements, and the
+        // report's semi-major axis differs only slightly
+        assert_eq!((n_improved, a_improved), (n_afspc, a_afspc));
+        assert_eq!(n_report, n_improved);
+        assert!((n_improved - n0).abs() < 1e-3 * n0);
+        assert_eq!(a_improved, (gravity.ke / n_improved).powf(2.0 / 3.0));
+        assert!(a_report != a_improved);
+        assert!((a_report - a_improved).abs() * gravity.radius < 0.01);
+
+        // So near Earth the two modes agree exactly, and the report's is
+        // within meters a day out
+        let improved = Propagator::with_ops_mode(&tle, OpsMode::Improved).unwrap().propagate(1440.0).unwrap();
+        let afspc = Propagator::with_ops_mode(&tle, OpsMode::Afspc).unwrap().propagate(1440.0).unwrap();
+        let report = Propagator::with_ops_mode(&tle, OpsMode::SpacetrackReport3).unwrap().propagate(1440.0).unwrap();
+        assert_eq!(improved, afspc);
+        let difference = ((improved.position.X - report.position.X).powi(2)
+                        + (improved.position.Y - report.position.Y).powi(2)
+                        + (improved.position.Z - report.position.Z).powi(2)).sqrt();
+        assert!(difference > 0.0);
+        assert!(difference < 0.1);
+    }
+
+    #[test]
+    fn orbit_shape() {
+        let tle = spacetrack_report_3_sgp4();
+        let propagator = Propagator::new(&tle).unwrap();
+        let radius = propagator.gravity.radius;
+
+        assert_eq!(tle.semi_major_axis_km(), propagator.ao_dp * radius);
+        assert_eq!(tle.perigee_km(), (propagator.ao_dp * (1.0 - tle.e) - 1.0) * radius);
+        assert!((tle.apogee_km() - tle.perigee_km() - 2.0 * tle.e * tle.semi_major_axis_km()).abs() < 1e-9);
+
+        // Above 54.7° inclination J₂ speeds the mean motion up a
+        // little, shortening the period by about 3 seconds
+        assert!((tle.period_minutes() - 89.624).abs() < 0.001);
+        assert!(tle.period_minutes() < MINUTES_PER_DAY / tle.mean_motion);
+        assert!(tle.perigee_km() < 220.0);
+        assert!(propagator.simple);
+    }
+
+    #[test]
+    fn max_age() {
+        use super::PropagationOptions;
+
+        let tle = spacetrack_report_3_sgp4();
+        let options = PropagationOptions { max_age: Some(3.0), ..PropagationOptions::default() };
+
+        // Lenient: still propagates, but flags the result
+        let lenient = Propagator::with_options(&tle, &options).unwrap();
+        assert!(!lenient.is_too_old(-4320.0));
+        assert!(lenient.is_too_old(4321.0));
+        assert!(lenient.is_too_old(-4321.0));
+        assert_eq!(lenient.propagate(5000.0), propagate(&tle, 5000.0));
+
+        let strict = Propagator::with_options(&tle, &PropagationOptions { strict: true, ..options }).unwrap();
+        assert!(strict.propagate(4320.0).is_ok());
+        assert_eq!(strict.propagate(4321.0), Err(PropagationError::TooOld));
+        assert_eq!(strict.propagate(-4321.0), Err(PropagationError::TooOld));
+
+        // No limit by default
+        assert!(!Propagator::new(&tle).unwrap().is_too_old(1.0e6));
+    }
+
+    #[test]
+    fn models() {
+        use super::{Model, PropagationOptions};
+        use elements::Elements;
+
+        let near_earth = PropagationOptions { model: Model::NearEarth, ..PropagationOptions::default() };
+        let deep_space = PropagationOptions { model: Model::DeepSpace, ..PropagationOptions::default() };
+
+        // Auto picks SDP4 for a geostationary orbit, and forcing SGP4
+        // leaves out the Sun and Moon
+        let geo = test_support::geostationary();
+        assert!(geo.is_deep_space());
+        let auto = Propagator::new(&geo).unwrap();
+        assert!(auto.is_deep_space());
+        let forced = Propagator::with_options(&geo, &deep_space).unwrap();
+        assert_eq!(auto.propagate(7200.0), forced.propagate(7200.0));
+        let sgp4 = Propagator::with_options(&geo, &near_earth).unwrap();
+        assert!(!sgp4.is_deep_space());
+        let difference = (sgp4.propagate(7200.0).unwrap().position - auto.propagate(7200.0).unwrap().position).norm();
+        assert!(difference > 1.0, "{} km", difference);
+
+        // And the other way round for a low orbit
+        let tle = spacetrack_report_3_sgp4();
+        assert!(!tle.is_deep_space());
+        assert_eq!(Propagator::with_options(&tle, &near_earth).unwrap().propagate(360.0), propagate(&tle, 360.0));
+        let sdp4 = Propagator::with_options(&tle, &deep_space).unwrap();
+        assert!(sdp4.is_deep_space());
+        assert!(sdp4.propagate(360.0).is_ok());
+
+        // The boundary is at a period of 225 minutes
+        let mut elements = Elements::from_tle(&tle);
+        for &(revolutions_per_day, expected) in &[(6.3, true), (6.45, false)] {
+            elements.mean_motion = revolutions_per_day * 2.0 * ::std::f64::consts::PI / MINUTES_PER_DAY;
+            assert_eq!(elements.is_deep_space(), expected);
+            assert_eq!(Propagator::from_elements(&elements).unwrap().is_deep_space(), expected);
+        }
+    }
+
+    #[test]
+    fn bad_elements() {
+        let mut tle = spacetrack_report_3_sgp4();
+        tle.e = 1.2;
+
+        assert_eq!(propagate(&tle, 0.0), Err(PropagationError::BadElements));
+    }
+
+    #[test]
+    fn circular() {
+        // Eccentricity 0.0000000 is in the catalog for real satellites
+        let tle = ::tle::load_from_str(
+            "ISS (ZARYA)",
+            "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+            "2 25544  51.6406 211.4156 0000000  85.8307 274.3426 15.54888439 11437",
+        ).unwrap();
+        assert_eq!(tle.e, 0.0);
+
+        let propagator = Propagator::new(&tle).unwrap();
+        assert!(propagator.circular);
+        assert_eq!(propagator.C3, 0.0);
+        let batch = ::batch::Batch::new(std::slice::from_ref(&propagator));
+        for &time in [0.0, 360.0, MINUTES_PER_DAY, 7.0 * MINUTES_PER_DAY].iter() {
+            let state = propagator.propagate(time).unwrap();
+            let radius = state.position.norm();
+            assert!((radius - 6780.0).abs() < 30.0, "t={}: r={}", time, radius);
+            assert!(state.velocity.norm().is_finite());
+            assert_eq!(batch.propagate_all(time)[0], Ok(state));
+        }
+
+        // Just above the threshold the terms are back, and small
+        let mut nearly = tle.clone();
+        nearly.e = 0.0002;
+        let nearly = Propagator::new(&nearly).unwrap();
+        assert!(!nearly.circular);
+        let difference = nearly.propagate(MINUTES_PER_DAY).unwrap().position
+            - propagator.propagate(MINUTES_PER_DAY).unwrap().position;
+        assert!(difference.norm() < 10.0);
+    }
+
+    #[test]
+    fn equatorial() {
+        let tle = spacetrack_report_3_sgp4();
+        let radius = |i: f64, e: f64, time: f64| {
+            let mut tle = tle.clone();
+            tle.i = Deg(i);
+            tle.e = e;
+            propagate(&tle, time).unwrap().position.norm()
+        };
+
+        for &e in [0.0, 0.0086731].iter() {
+            for &time in [0.0, 720.0, MINUTES_PER_DAY].iter() {
+                // An equatorial orbit behaves like its slightly inclined
+                // neighbours
+                let prograde = radius(0.0, e, time);
+                assert!((prograde - radius(0.001, e, time)).abs() < 0.1, "e={} t={}", e, time);
+
+                // The long period term is singular approaching 180°, like
+                // tan(i/2), but exactly retrograde it's held finite
+                let retrograde = radius(180.0, e, time);
+                assert!((retrograde - prograde).abs() < 100.0, "e={} t={}: r={} prograde {}", e, time, retrograde, prograde);
+            }
+        }
+    }
+
+    #[test]
+    fn diverged() {
+        let tle = spacetrack_report_3_sgp4();
+
+        // A year out the drag terms have pushed the eccentricity negative
+        assert_eq!(propagate(&tle, 525600.0), Err(PropagationError::Diverged));
+    }
+
+    #[test]
+    fn decayed() {
+        let mut tle = spacetrack_report_3_sgp4();
+
+        // With fifteen times the drag this orbit doesn't last two months
+        tle.bstar = 1.0e-3;
+        assert_eq!(propagate(&tle, 86400.0), Err(PropagationError::Decayed));
+
+        let propagator = Propagator::new(&tle).unwrap();
+        let time = propagator.decay_time(0.0, 86400.0, 60.0).unwrap();
+        assert!(time > 0.0 && time < 86400.0);
+        assert_eq!(propagator.propagate(time), Err(PropagationError::Decayed));
+        assert_eq!(Propagator::new(&spacetrack_report_3_sgp4()).unwrap().decay_time(0.0, 1440.0, 60.0), None);
+    }
+
+    #[test]
+    fn below_surface() {
+        let mut tle = spacetrack_report_3_sgp4();
 
+        // The mean orbit is a few kilometers above the surface, but the
+        // short period terms take it below once an orbit
+        tle.mean_motion = 17.02;
+        tle.e = 0.0005;
+        tle.bstar = 0.0;
+        let propagator = Propagator::new(&tle).unwrap();
+        assert!(propagator.propagate(0.0).is_ok());
+        let time = propagator.decay_time(0.0, 90.0, 1.0).unwrap();
+        assert!(propagator.propagate(time - 1.0).is_ok());
+        assert_eq!(propagator.propagate(time), Err(PropagationError::Decayed));
     }
 }
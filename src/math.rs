@@ -0,0 +1,163 @@
+/*!  # Math
+
+Floating point functions for `no_std` builds. Without the standard library
+`f64` has no `sin`, `sqrt` and so on; this trait supplies them from `libm`
+under the same names, so the rest of the crate reads the same either way.
+
+If a dependency links `std` anyway, its inherent methods take precedence and
+the trait goes unused, which is why it and its imports allow being unused.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use libm;
+
+/// The `f64` methods the crate uses that `core` doesn't provide
+#[allow(dead_code)]
+pub trait Float {
+
+    /// Largest integer less than or equal to `self`
+    fn floor(self) -> Self;
+
+    /// Nearest integer, rounding half-way cases away from zero
+    fn round(self) -> Self;
+
+    /// Square root
+    fn sqrt(self) -> Self;
+
+    /// `self` raised to an integer power
+    fn powi(self, n: i32) -> Self;
+
+    /// `self` raised to a floating point power
+    fn powf(self, n: Self) -> Self;
+
+    /// Sine (radians)
+    fn sin(self) -> Self;
+
+    /// Cosine (radians)
+    fn cos(self) -> Self;
+
+    /// Sine and cosine (radians)
+    fn sin_cos(self) -> (Self, Self) where Self: Sized;
+
+    /// Four quadrant arctangent of `self` (y) and `other` (x)
+    fn atan2(self, other: Self) -> Self;
+
+    /// Arcsine (radians)
+    fn asin(self) -> Self;
+
+    /// Arccosine (radians)
+    fn acos(self) -> Self;
+
+    /// Tangent (radians)
+    fn tan(self) -> Self;
+
+    /// Arctangent (radians)
+    fn atan(self) -> Self;
+
+    /// Natural logarithm
+    fn ln(self) -> Self;
+
+    /// Hyperbolic sine
+    fn sinh(self) -> Self;
+
+    /// Hyperbolic cosine
+    fn cosh(self) -> Self;
+
+    /// Hyperbolic tangent
+    fn tanh(self) -> Self;
+
+    /// Inverse hyperbolic tangent
+    fn atanh(self) -> Self;
+
+    /// Least non-negative remainder of `self` divided by `rhs`
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+impl Float for f64 {
+
+    fn floor(self) -> f64 {
+        libm::floor(self)
+    }
+
+    fn round(self) -> f64 {
+        libm::round(self)
+    }
+
+    fn sqrt(self) -> f64 {
+        libm::sqrt(self)
+    }
+
+    fn powi(self, n: i32) -> f64 {
+        libm::pow(self, f64::from(n))
+    }
+
+    fn powf(self, n: f64) -> f64 {
+        libm::pow(self, n)
+    }
+
+    fn sin(self) -> f64 {
+        libm::sin(self)
+    }
+
+    fn cos(self) -> f64 {
+        libm::cos(self)
+    }
+
+    fn sin_cos(self) -> (f64, f64) {
+        libm::sincos(self)
+    }
+
+    fn atan2(self, other: f64) -> f64 {
+        libm::atan2(self, other)
+    }
+
+    fn asin(self) -> f64 {
+        libm::asin(self)
+    }
+
+    fn acos(self) -> f64 {
+        libm::acos(self)
+    }
+
+    fn tan(self) -> f64 {
+        libm::tan(self)
+    }
+
+    fn atan(self) -> f64 {
+        libm::atan(self)
+    }
+
+    fn ln(self) -> f64 {
+        libm::log(self)
+    }
+
+    fn sinh(self) -> f64 {
+        libm::sinh(self)
+    }
+
+    fn cosh(self) -> f64 {
+        libm::cosh(self)
+    }
+
+    fn tanh(self) -> f64 {
+        libm::tanh(self)
+    }
+
+    fn atanh(self) -> f64 {
+        libm::atanh(self)
+    }
+
+    fn rem_euclid(self, rhs: f64) -> f64 {
+        let remainder = self % rhs;
+        if remainder < 0.0 {
+            remainder + rhs.abs()
+        } else {
+            remainder
+        }
+    }
+}
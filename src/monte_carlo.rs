@@ -0,0 +1,416 @@
+/*!  # Monte Carlo
+
+How far off a prediction might be, by sampling: perturb a TLE's elements
+at random within given standard deviations, propagate every sample, and
+measure how the positions spread about the nominal one. Requires the `std`
+feature, for threads.
+
+Where [`covariance`](../covariance/index.html) maps the uncertainty
+linearly, sampling has no such limit, so it holds for old TLEs whose
+along-track error has grown to a good part of the orbit, and for samples
+that reenter.
+
+Dispersion is measured in the nominal state's radial, in-track,
+cross-track frame, as in
+[`relative_ric`](../coordinates/fn.relative_ric.html). Each sample draws
+from its own generator seeded from `seed` and its index, so the results
+don't depend on how many threads are used.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+use std::thread;
+
+use angle::Rad;
+use coordinates::{relative_ric, StateVector};
+use elements::Elements;
+use tle::TLE;
+use {PropagationError, Propagator};
+
+
+/// ## Monte Carlo Options
+///
+/// How many samples to draw, and how.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonteCarloOptions {
+
+    /// Samples to propagate (default 1000)
+    pub samples: usize,
+
+    /// Seed for the random perturbations
+    pub seed: u64,
+
+    /// Threads to propagate with, or 0 (the default) for one per CPU
+    pub threads: usize,
+}
+
+impl Default for MonteCarloOptions {
+    fn default() -> MonteCarloOptions {
+        MonteCarloOptions {
+            samples: 1000,
+            seed: 0x5347_5034,
+            threads: 0,
+        }
+    }
+}
+
+/// ## Dispersion
+///
+/// How the samples spread about the nominal position at one time.
+/// Components are radial, in-track and cross-track, in kilometers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dispersion {
+
+    /// Minutes since the TLE epoch
+    pub time: f64,
+
+    /// Nominal TEME state
+    pub nominal: StateVector,
+
+    /// Samples propagated to this time
+    pub samples: usize,
+
+    /// Samples that couldn't be, most often because they had decayed
+    pub failed: usize,
+
+    /// Mean offset of the samples from the nominal position
+    pub mean: [f64; 3],
+
+    /// Covariance of the offsets about their mean (kilometers²)
+    pub covariance: [[f64; 3]; 3],
+
+    /// Root mean square distance from the nominal position
+    pub rms: f64,
+
+    /// Largest distance from the nominal position
+    pub max: f64,
+}
+
+impl Dispersion {
+
+    /// Standard deviation of each component (kilometers)
+    pub fn sigma(&self) -> [f64; 3] {
+        [
+            self.covariance[0][0].sqrt(),
+            self.covariance[1][1].sqrt(),
+            self.covariance[2][2].sqrt(),
+        ]
+    }
+}
+
+/// SplitMix64: small, fast and plenty random for sampling
+struct Random(u64);
+
+impl Random {
+
+    /// The generator for sample `index`
+    fn new(seed: u64, index: usize) -> Random {
+        Random(seed ^ (index as u64).wrapping_mul(0xD1B5_4A32_D192_ED03))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform on $[0, 1)$
+    fn uniform(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Standard normal, by the Box-Muller transform
+    fn normal(&mut self) -> f64 {
+        let u = 1.0 - self.uniform();
+        let v = self.uniform();
+        (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+    }
+}
+
+/// A random sample of the elements. Eccentricity and inclination are
+/// reflected back into range rather than redrawn, which keeps every draw
+/// usable.
+fn sample(elements: &Elements, sigma: &Elements, random: &mut Random) -> Elements {
+    let eccentricity = (elements.eccentricity + sigma.eccentricity * random.normal()).abs();
+    let inclination = (elements.inclination + sigma.inclination * random.normal()).abs();
+    Elements {
+        mean_motion: elements.mean_motion + sigma.mean_motion * random.normal(),
+        eccentricity: eccentricity.min(1.0 - f64::EPSILON),
+        inclination: if inclination > Rad(PI) { Rad(2.0 * PI) - inclination } else { inclination },
+        raan: elements.raan + sigma.raan * random.normal(),
+        argp: elements.argp + sigma.argp * random.normal(),
+        mean_anomaly: elements.mean_anomaly + sigma.mean_anomaly * random.normal(),
+        bstar: elements.bstar + sigma.bstar * random.normal(),
+    }
+}
+
+/// Running sums of the offsets at one time
+#[derive(Debug, Clone, Copy, Default)]
+struct Moments {
+    count: usize,
+    failed: usize,
+    sum: [f64; 3],
+    products: [[f64; 3]; 3],
+    squares: f64,
+    max: f64,
+}
+
+impl Moments {
+
+    fn add(&mut self, offset: [f64; 3]) {
+        self.count += 1;
+        for (i, row) in self.products.iter_mut().enumerate() {
+            self.sum[i] += offset[i];
+            for (j, product) in row.iter_mut().enumerate() {
+                *product += offset[i] * offset[j];
+            }
+        }
+        let square = offset.iter().map(|x| x * x).sum::<f64>();
+        self.squares += square;
+        self.max = self.max.max(square.sqrt());
+    }
+
+    fn merge(&mut self, other: &Moments) {
+        self.count += other.count;
+        self.failed += other.failed;
+        for i in 0..3 {
+            self.sum[i] += other.sum[i];
+            for j in 0..3 {
+                self.products[i][j] += other.products[i][j];
+            }
+        }
+        self.squares += other.squares;
+        self.max = self.max.max(other.max);
+    }
+
+    fn dispersion(&self, time: f64, nominal: StateVector) -> Dispersion {
+        let n = self.count.max(1) as f64;
+        let mean = [self.sum[0] / n, self.sum[1] / n, self.sum[2] / n];
+        let mut covariance = [[0.0; 3]; 3];
+        if self.count > 1 {
+            let m = (self.count - 1) as f64;
+            for (i, row) in covariance.iter_mut().enumerate() {
+                for (j, value) in row.iter_mut().enumerate() {
+                    *value = (self.products[i][j] - n * mean[i] * mean[j]) / m;
+                }
+            }
+        }
+        Dispersion {
+            time,
+            nominal,
+            samples: self.count,
+            failed: self.failed,
+            mean,
+            covariance,
+            rms: (self.squares / n).sqrt(),
+            max: self.max,
+        }
+    }
+}
+
+/// ## Dispersion
+///
+/// Perturb a TLE's elements `options.samples` times, each element by a
+/// normal deviate with the standard deviation given for it in `sigma` (in
+/// the units of [`Elements`](../elements/struct.Elements.html); zero holds
+/// it fixed), propagate them all to each of `times` (minutes since the
+/// epoch) and measure the spread.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::angle::Rad;
+/// use sgp4::elements::Elements;
+/// use sgp4::monte_carlo::{dispersion, MonteCarloOptions};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // Mostly uncertain in its drag
+/// let sigma = Elements {
+///     mean_motion: 1.0e-9,
+///     eccentricity: 1.0e-6,
+///     inclination: Rad(1.0e-5),
+///     raan: Rad(1.0e-5),
+///     argp: Rad(1.0e-4),
+///     mean_anomaly: Rad(1.0e-5),
+///     bstar: 0.5 * tle.bstar,
+/// };
+/// let options = MonteCarloOptions { samples: 200, ..MonteCarloOptions::default() };
+/// let week = dispersion(&tle, &sigma, &[0.0, 10080.0], &options).unwrap();
+///
+/// // Uncertainty grows along the orbit
+/// assert!(week[1].sigma()[1] > 10.0 * week[0].sigma()[1]);
+/// ```
+///
+/// ### Errors
+///
+/// Only when the nominal TLE can't be propagated. Samples that fail are
+/// counted in each `Dispersion`.
+pub fn dispersion(tle: &TLE, sigma: &Elements, times: &[f64], options: &MonteCarloOptions) -> Result<Vec<Dispersion>, PropagationError> {
+    let epoch = tle.epoch_julian_date();
+    let elements = Elements::from_tle(tle);
+    let nominal = Propagator::from_elements(&elements)?.at_epoch(epoch);
+    let nominal = times.iter()
+        .map(|&time| nominal.propagate(time))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let threads = match options.threads {
+        0 => thread::available_parallelism().map_or(1, |count| count.get()),
+        threads => threads,
+    }.min(options.samples.max(1));
+
+    // Each thread takes every nth sample
+    let nominal_states = &nominal;
+    let moments = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| scope.spawn(move || {
+                let mut moments = vec![Moments::default(); times.len()];
+                for index in (worker..options.samples).step_by(threads) {
+                    let mut random = Random::new(options.seed, index);
+                    let propagator = match Propagator::from_elements(&sample(&elements, sigma, &mut random)) {
+                        Ok(propagator) => propagator.at_epoch(epoch),
+                        Err(_) => {
+                            moments.iter_mut().for_each(|moments| moments.failed += 1);
+                            continue;
+                        }
+                    };
+                    for ((moments, &time), nominal) in moments.iter_mut().zip(times).zip(nominal_states) {
+                        match propagator.propagate(time) {
+                            Ok(state) => {
+                                let relative = relative_ric(nominal, &state);
+                                moments.add([relative.position.R, relative.position.I, relative.position.C]);
+                            }
+                            Err(_) => moments.failed += 1,
+                        }
+                    }
+                }
+                moments
+            }))
+            .collect();
+        workers.into_iter().map(|worker| worker.join().expect("Monte Carlo thread panicked")).collect::<Vec<_>>()
+    });
+
+    let mut total = vec![Moments::default(); times.len()];
+    for worker in &moments {
+        for (total, moments) in total.iter_mut().zip(worker) {
+            total.merge(moments);
+        }
+    }
+
+    Ok(total.iter()
+        .zip(times)
+        .zip(nominal)
+        .map(|((moments, &time), nominal)| moments.dispersion(time, nominal))
+        .collect())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use covariance::{diagonal, Covariance};
+    use MINUTES_PER_DAY;
+    use test_support::iss;
+
+    fn sigma(mean_motion: f64, bstar: f64) -> Elements {
+        Elements {
+            mean_motion,
+            eccentricity: 0.0,
+            inclination: Rad(0.0),
+            raan: Rad(0.0),
+            argp: Rad(0.0),
+            mean_anomaly: Rad(0.0),
+            bstar,
+        }
+    }
+
+    #[test]
+    fn random() {
+        let mut random = Random::new(1, 0);
+        let n = 100_000;
+        let draws: Vec<f64> = (0..n).map(|_| random.normal()).collect();
+        let mean = draws.iter().sum::<f64>() / n as f64;
+        let variance = draws.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / n as f64;
+        assert!(mean.abs() < 0.01, "{}", mean);
+        assert!((variance - 1.0).abs() < 0.02, "{}", variance);
+
+        assert_ne!(Random::new(1, 0).next(), Random::new(1, 1).next());
+        assert_ne!(Random::new(1, 0).next(), Random::new(2, 0).next());
+    }
+
+    #[test]
+    fn agrees_with_covariance() {
+        // Small enough to stay linear
+        let tle = iss();
+        let time = 2.0 * MINUTES_PER_DAY;
+        let options = MonteCarloOptions { samples: 2000, ..MonteCarloOptions::default() };
+        let sampled = dispersion(&tle, &sigma(1.0e-9, 0.0), &[time], &options).unwrap();
+
+        let linear = Covariance::new(&tle, diagonal(&[1.0e-9, 0.0, 0.0, 0.0, 0.0, 0.0])).unwrap()
+            .propagate(time).unwrap()
+            .position_ric();
+
+        let in_track = sampled[0].sigma()[1];
+        assert_eq!(sampled[0].samples, 2000);
+        assert_eq!(sampled[0].failed, 0);
+        assert!((in_track - linear[1][1].sqrt()).abs() < 0.05 * in_track, "{} {}", in_track, linear[1][1].sqrt());
+        assert!(sampled[0].mean[1].abs() < 0.1 * in_track);
+        assert!(sampled[0].rms >= in_track * 0.9 && sampled[0].max >= sampled[0].rms);
+    }
+
+    #[test]
+    fn threads_and_seeds() {
+        let tle = iss();
+        let sigma = sigma(1.0e-9, 0.5e-5);
+        let times = [0.0, 720.0, 1440.0];
+        let one = MonteCarloOptions { samples: 100, threads: 1, ..MonteCarloOptions::default() };
+        let four = MonteCarloOptions { threads: 4, ..one };
+
+        let a = dispersion(&tle, &sigma, &times, &one).unwrap();
+        let b = dispersion(&tle, &sigma, &times, &four).unwrap();
+        for (a, b) in a.iter().zip(&b) {
+            assert_eq!(a.samples, b.samples);
+            assert_eq!(a.max, b.max);
+            for i in 0..3 {
+                assert!((a.mean[i] - b.mean[i]).abs() <= 1e-9 * (1.0 + a.mean[i].abs()));
+            }
+            assert!((a.rms - b.rms).abs() <= 1e-9 * (1.0 + a.rms));
+        }
+
+        let other = dispersion(&tle, &sigma, &times, &MonteCarloOptions { seed: 7, ..one }).unwrap();
+        assert_ne!(a[2].max, other[2].max);
+    }
+
+    #[test]
+    fn fixed_elements() {
+        // With no uncertainty every sample is the nominal
+        let options = MonteCarloOptions { samples: 10, ..MonteCarloOptions::default() };
+        let result = dispersion(&iss(), &sigma(0.0, 0.0), &[60.0], &options).unwrap();
+        assert_eq!(result[0].samples, 10);
+        assert!(result[0].max < 1e-9);
+    }
+
+    #[test]
+    fn decayed_samples() {
+        // The nominal stays up, but with this much uncertainty in the drag
+        // some samples come down within the month
+        let options = MonteCarloOptions { samples: 50, ..MonteCarloOptions::default() };
+        let result = dispersion(&iss(), &sigma(0.0, 0.02), &[0.0, 30.0 * MINUTES_PER_DAY], &options).unwrap();
+        assert_eq!(result[0].failed, 0);
+        assert!(result[1].failed > 0);
+        assert_eq!(result[1].samples + result[1].failed, 50);
+    }
+}
@@ -0,0 +1,284 @@
+/*!  # Observer
+
+Where a satellite appears in the sky from a site on the ground.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+use coordinates::{ecef_from_geodetic, ecef_from_geodetic_with_gravity_model, ecef_from_teme, itrf_from_pef, sez_rotation, Geodetic, StateVector, ECEF, EARTH_ROTATION_RATE};
+use eop::Eop;
+use gravity::GravityModel;
+use time;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+
+/// Speed of light in a vacuum (kilometers/second)
+pub const SPEED_OF_LIGHT: f64 = 299_792.458;
+
+/// ## Doppler Shift
+///
+/// Change in frequency (Hz) of a signal at `frequency` (Hz) transmitted by
+/// a satellite moving at `range_rate` (kilometers/second, positive when
+/// moving away, as in [`LookAngles`](struct.LookAngles.html)). The
+/// received frequency is `frequency` plus the shift: higher while the
+/// satellite approaches, lower as it recedes.
+///
+/// For a signal sent up to the satellite, the frequency to transmit on so
+/// it arrives at `frequency` is `frequency` minus the shift.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// // 145.8 MHz from a satellite approaching at 7 km/s
+/// let shift = sgp4::observer::doppler_shift(-7.0, 145.8e6);
+/// assert!((shift - 3404.4).abs() < 0.1);
+/// ```
+pub fn doppler_shift(range_rate: f64, frequency: f64) -> f64 {
+    -range_rate / SPEED_OF_LIGHT * frequency
+}
+
+
+/// ## Look Angles
+///
+/// Topocentric direction and distance to a satellite.
+#[derive(Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LookAngles {
+
+    /// Azimuth (radians, clockwise from north, 0 to $2\pi$)
+    pub az: f64,
+
+    /// Elevation above the local horizon (radians)
+    pub el: f64,
+
+    /// Distance from the observer (kilometers)
+    pub range: f64,
+
+    /// Rate of change of the range (kilometers/second, positive when the
+    /// satellite is moving away)
+    pub range_rate: f64,
+}
+
+
+/// ## Observer
+///
+/// A fixed site on the ground, such as a ground station antenna.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::coordinates::Geodetic;
+/// use sgp4::observer::Observer;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let observer = Observer::new(Geodetic {
+///     lat: f64::to_radians(45.52),
+///     lon: f64::to_radians(-122.68),
+///     alt: 0.05,
+/// });
+///
+/// let time = 30.0;
+/// let state = sgp4::propagate(&tle, time).unwrap();
+/// let gmst = sgp4::time::gmst(tle.epoch_julian_date() + time / sgp4::MINUTES_PER_DAY);
+/// let look = observer.look_angles(&state, gmst);
+///
+/// println!("az {:.1}° el {:.1}°", look.az.to_degrees(), look.el.to_degrees());
+/// ```
+#[derive(Debug)]
+pub struct Observer {
+
+    /// Site location
+    location: Geodetic,
+
+    /// Site location in the Earth fixed frame (kilometers)
+    position: ECEF,
+}
+
+impl Observer {
+
+    /// A new observer at a location on the WGS84 ellipsoid
+    pub fn new(location: Geodetic) -> Observer {
+        let position = ecef_from_geodetic(&location);
+        Observer { location, position }
+    }
+
+    /// A new observer at a location on the ellipsoid of a gravity model,
+    /// such as the one a [`Propagator`](../struct.Propagator.html) uses
+    pub fn with_gravity_model(location: Geodetic, gravity: &GravityModel) -> Observer {
+        let position = ecef_from_geodetic_with_gravity_model(&location, gravity);
+        Observer { location, position }
+    }
+
+    /// Site location
+    pub fn location(&self) -> &Geodetic {
+        &self.location
+    }
+
+    /// ## Look Angles
+    ///
+    /// Azimuth, elevation, range and range rate to a satellite, given its
+    /// TEME state (as returned by [`propagate`](../fn.propagate.html)) and
+    /// the Greenwich mean sidereal time (radians) at the same instant.
+    pub fn look_angles(&self, state: &StateVector, gmst: f64) -> LookAngles {
+        let (position, velocity) = pef_state(state, gmst);
+        self.look(&position, &velocity)
+    }
+
+    /// ## Look Angles With EOP
+    ///
+    /// Azimuth, elevation, range and range rate to a satellite at the
+    /// state's epoch, with the Earth's orientation corrected by `eop` (see
+    /// the [`eop`](../eop/index.html) module) rather than taken from the
+    /// model alone.
+    pub fn look_angles_with_eop(&self, state: &StateVector, eop: &Eop) -> LookAngles {
+        let (position, velocity) = pef_state(state, time::gmst(eop.ut1(state.epoch)));
+        self.look(&itrf_from_pef(&position, eop), &itrf_from_pef(&velocity, eop))
+    }
+
+    /// Look angles to an Earth fixed position and velocity
+    fn look(&self, position: &ECEF, velocity: &ECEF) -> LookAngles {
+        // Line of sight from the site, in the local south, east, zenith
+        // frame
+        let rho = sez_rotation(&(*position - self.position), &self.location);
+        let range = rho.norm();
+        let range_rate = rho.dot(&sez_rotation(velocity, &self.location)) / range;
+
+        let mut az = rho.Y.atan2(-rho.X);
+        if az < 0.0 {
+            az += 2.0 * PI;
+        }
+        let el = (rho.Z / range).asin();
+
+        LookAngles { az, el, range, range_rate }
+    }
+}
+
+/// A TEME state in the pseudo Earth fixed frame, with the velocity relative
+/// to the rotating Earth
+fn pef_state(state: &StateVector, gmst: f64) -> (ECEF, ECEF) {
+    let position = ecef_from_teme(&state.position, gmst);
+
+    // The rotating frame adds -ω × r to the velocity
+    let rotated = ecef_from_teme(&state.velocity, gmst);
+    let velocity = ECEF::new(
+        rotated.X + EARTH_ROTATION_RATE * position.Y,
+        rotated.Y - EARTH_ROTATION_RATE * position.X,
+        rotated.Z,
+    );
+    (position, velocity)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coordinates::{TEME, WGS84_EQUATORIAL_RADIUS};
+    use time;
+    use test_support;
+
+    fn state(X: f64, Y: f64, Z: f64) -> StateVector {
+        StateVector {
+            epoch: 0.0,
+            position: TEME::new(X, Y, Z),
+            velocity: TEME::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    #[test]
+    fn overhead() {
+        let observer = Observer::new(Geodetic { lat: 0.0, lon: 0.0, alt: 0.0 });
+        let look = observer.look_angles(&state(WGS84_EQUATORIAL_RADIUS + 500.0, 0.0, 0.0), 0.0);
+
+        assert!((look.el - PI / 2.0).abs() < 1e-9);
+        assert!((look.range - 500.0).abs() < 1e-9);
+
+        // The WGS72 equator is two meters lower
+        let wgs72 = Observer::with_gravity_model(Geodetic { lat: 0.0, lon: 0.0, alt: 0.0 }, &GravityModel::WGS72);
+        let look = wgs72.look_angles(&state(WGS84_EQUATORIAL_RADIUS + 500.0, 0.0, 0.0), 0.0);
+        assert!((look.range - 500.002).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compass_points() {
+        let observer = Observer::new(Geodetic { lat: 0.0, lon: 0.0, alt: 0.0 });
+        let r = WGS84_EQUATORIAL_RADIUS;
+
+        // North, then east, of the site just above the horizon
+        let north = observer.look_angles(&state(r, 0.0, 1000.0), 0.0);
+        assert!(north.az.abs() < 1e-9);
+        assert!(north.el.abs() < 1e-9);
+
+        let east = observer.look_angles(&state(r, 1000.0, 0.0), 0.0);
+        assert!((east.az - PI / 2.0).abs() < 1e-9);
+
+        // Sidereal rotation moves the whole sky west
+        let west = observer.look_angles(&state(r, 1000.0, 0.0), PI);
+        assert!(west.el < 0.0);
+    }
+
+    #[test]
+    fn doppler() {
+        assert_eq!(doppler_shift(0.0, 437.0e6), 0.0);
+        assert!(doppler_shift(5.0, 437.0e6) < 0.0);
+        assert!((doppler_shift(-SPEED_OF_LIGHT / 1000.0, 1.0e6) - 1000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn range_rate() {
+        let tle = test_support::iss();
+        let observer = Observer::new(Geodetic {
+            lat: f64::to_radians(45.52),
+            lon: f64::to_radians(-122.68),
+            alt: 0.05,
+        });
+
+        let look = |t: f64| {
+            let state = ::propagate(&tle, t).unwrap();
+            observer.look_angles(&state, time::gmst(tle.epoch_julian_date() + t / ::MINUTES_PER_DAY))
+        };
+
+        // Compare to the range differenced over one second
+        for &t in [0.0, 17.0, 45.5, 90.0].iter() {
+            let dt = 1.0 / 60.0;
+            let estimate = (look(t + dt / 2.0).range - look(t - dt / 2.0).range) / 60.0 / dt;
+            assert!((look(t).range_rate - estimate).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn earth_orientation() {
+        let tle = test_support::iss();
+        let observer = Observer::new(Geodetic { lat: 0.8, lon: -2.1, alt: 0.05 });
+        let state = ::propagate(&tle, 30.0).unwrap();
+
+        // No correction is the model
+        let model = observer.look_angles(&state, time::gmst(state.epoch));
+        assert_eq!(observer.look_angles_with_eop(&state, &Eop::default()), model);
+
+        // A real correction moves the satellite by tens of meters
+        let corrected = observer.look_angles_with_eop(&state, &Eop::from_arcseconds(0.186180, 0.447357, -0.0461858));
+        let shift = (corrected.range - model.range).abs();
+        assert!(shift > 1e-3 && shift < 0.1);
+        assert!((corrected.el - model.el).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,255 @@
+/*!  # Orbit Ephemeris Messages
+
+Write propagated states as a CCSDS Orbit Ephemeris Message (OEM, CCSDS
+502.0-B-2) in its text (KVN) rendering, the ephemeris format STK, GMAT and
+most operators exchange.
+
+A message is a header, then a metadata block naming the object, the frame
+and the time system, then one line per state: the epoch, position in
+kilometers and velocity in kilometers/second. States come from the
+propagator in the TEME frame, which OEM calls `TEME`; states converted to
+another frame first are labelled with its CCSDS name. Epochs are UTC.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::fmt::{self, Write};
+
+use coordinates::frame::Frame;
+use coordinates::StateVector;
+use time;
+use tle::TLE;
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::string::String;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// OEM version written
+pub const VERSION: &str = "2.0";
+
+/// ## OEM Metadata
+///
+/// What the header and metadata block say about the states.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OemMetadata {
+
+    /// Who made the message (default `sgp4`)
+    pub originator: String,
+
+    /// When the message was made (Julian date, UTC)
+    pub creation_date: f64,
+
+    /// Name of the object, e.g. `ISS (ZARYA)`
+    pub object_name: String,
+
+    /// International designator, e.g. `1998-067A`
+    pub object_id: String,
+
+    /// Free text `COMMENT` lines for the metadata block
+    pub comments: Vec<String>,
+}
+
+impl OemMetadata {
+
+    /// Metadata for states propagated from a TLE, made at `creation_date`
+    /// (Julian date, UTC)
+    pub fn from_tle(tle: &TLE, creation_date: f64) -> OemMetadata {
+        let comment = format!("Propagated with SGP4 from the element set of epoch {}", time::iso_8601(tle.epoch_julian_date()));
+        OemMetadata {
+            originator: String::from("sgp4"),
+            creation_date,
+            object_name: String::from(tle.name.trim()),
+            object_id: object_id(&tle.int_designator),
+            comments: vec![comment],
+        }
+    }
+}
+
+/// The CCSDS form of a TLE's international designator: `98067A` becomes
+/// `1998-067A`. Launches from 1957 on have two digit years below 57 in
+/// this century.
+pub(crate) fn object_id(int_designator: &str) -> String {
+    let designator = int_designator.trim();
+    match designator.get(..2).and_then(|year| year.parse::<u32>().ok()) {
+        Some(year) if designator.len() >= 5 => {
+            let century = if year < 57 { 2000 } else { 1900 };
+            format!("{}-{}", century + year, &designator[2..])
+        }
+        _ => String::from(designator),
+    }
+}
+
+/// The CCSDS reference frame name of a frame
+fn ref_frame<F: Frame>() -> &'static str {
+    match F::NAME {
+        "J2000" => "EME2000",
+        "ECEF" => "ITRF",
+        name => name,
+    }
+}
+
+/// ## Write OEM
+///
+/// Write `states`, in time order, as an OEM to `out`.
+///
+/// ### Panics
+///
+/// If there are no states: a message needs a start and stop time.
+pub fn write_oem<F: Frame, W: Write>(out: &mut W, metadata: &OemMetadata, states: &[StateVector<F>]) -> fmt::Result {
+    let (first, last) = match (states.first(), states.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => panic!("an OEM needs at least one state"),
+    };
+
+    writeln!(out, "CCSDS_OEM_VERS = {}", VERSION)?;
+    writeln!(out, "CREATION_DATE = {}", time::iso_8601(metadata.creation_date))?;
+    writeln!(out, "ORIGINATOR = {}", metadata.originator)?;
+    writeln!(out)?;
+
+    writeln!(out, "META_START")?;
+    for comment in &metadata.comments {
+        writeln!(out, "COMMENT {}", comment)?;
+    }
+    writeln!(out, "OBJECT_NAME = {}", metadata.object_name)?;
+    writeln!(out, "OBJECT_ID = {}", metadata.object_id)?;
+    writeln!(out, "CENTER_NAME = EARTH")?;
+    writeln!(out, "REF_FRAME = {}", ref_frame::<F>())?;
+    writeln!(out, "TIME_SYSTEM = UTC")?;
+    writeln!(out, "START_TIME = {}", time::iso_8601(first.epoch))?;
+    writeln!(out, "STOP_TIME = {}", time::iso_8601(last.epoch))?;
+    writeln!(out, "META_STOP")?;
+    writeln!(out)?;
+
+    for state in states {
+        writeln!(out, "{} {:16.6} {:16.6} {:16.6} {:14.9} {:14.9} {:14.9}",
+                 time::iso_8601(state.epoch),
+                 state.position.X, state.position.Y, state.position.Z,
+                 state.velocity.X, state.velocity.Y, state.velocity.Z)?;
+    }
+    Ok(())
+}
+
+/// ## OEM
+///
+/// `states`, in time order, as the text of an OEM.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::oem::{to_oem, OemMetadata};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// // One orbit, once a minute
+/// let propagator = sgp4::Propagator::new(&tle).unwrap();
+/// let states: Vec<_> = propagator.sample(0.0, 92.0, 1.0)
+///     .map(|(_, state)| state.unwrap())
+///     .collect();
+///
+/// let oem = to_oem(&OemMetadata::from_tle(&tle, tle.epoch_julian_date()), &states);
+/// assert!(oem.contains("OBJECT_ID = 1998-067A"));
+/// assert!(oem.contains("REF_FRAME = TEME"));
+/// ```
+///
+/// ### Panics
+///
+/// If there are no states.
+pub fn to_oem<F: Frame>(metadata: &OemMetadata, states: &[StateVector<F>]) -> String {
+    let mut out = String::new();
+    write_oem(&mut out, metadata, states).expect("writing to a String can't fail");
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coordinates::{frame, Vec3};
+    use Propagator;
+    use test_support::iss;
+
+    #[test]
+    fn object_ids() {
+        assert_eq!(object_id("98067A  "), "1998-067A");
+        assert_eq!(object_id("21001BC "), "2021-001BC");
+        assert_eq!(object_id("57001B"), "1957-001B");
+        assert_eq!(object_id(""), "");
+        assert_eq!(object_id("UNKNOWN"), "UNKNOWN");
+    }
+
+    #[test]
+    fn message() {
+        let tle = iss();
+        let propagator = Propagator::new(&tle).unwrap();
+        let states: Vec<_> = propagator.sample(0.0, 2.0, 1.0).map(|(_, state)| state.unwrap()).collect();
+        let metadata = OemMetadata {
+            creation_date: time::julian_date(2016, 7, 29.0),
+            ..OemMetadata::from_tle(&tle, 0.0)
+        };
+        let oem = to_oem(&metadata, &states);
+        let lines: Vec<&str> = oem.lines().collect();
+
+        assert_eq!(lines[0], "CCSDS_OEM_VERS = 2.0");
+        assert_eq!(lines[1], "CREATION_DATE = 2016-07-29T00:00:00.000000");
+        assert_eq!(lines[2], "ORIGINATOR = sgp4");
+        assert_eq!(lines[4], "META_START");
+        assert!(lines[5].starts_with("COMMENT Propagated with SGP4 from the element set of epoch 2016-07-28T14:21:26.33"));
+        assert_eq!(lines[6], "OBJECT_NAME = ISS (ZARYA)");
+        assert_eq!(lines[7], "OBJECT_ID = 1998-067A");
+        assert_eq!(lines[8], "CENTER_NAME = EARTH");
+        assert_eq!(lines[9], "REF_FRAME = TEME");
+        assert_eq!(lines[10], "TIME_SYSTEM = UTC");
+        assert!(lines[11].starts_with("START_TIME = 2016-07-28T14:21:26.33"));
+        assert!(lines[12].starts_with("STOP_TIME = 2016-07-28T14:23:26.33"));
+        assert_eq!(lines[13], "META_STOP");
+        assert_eq!(lines.len(), 15 + states.len());
+
+        // Every state reads back
+        for (line, state) in lines[15..].iter().zip(&states) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            assert_eq!(fields.len(), 7);
+            let values: Vec<f64> = fields[1..].iter().map(|field| field.parse().unwrap()).collect();
+            assert!((values[0] - state.position.X).abs() < 1e-6);
+            assert!((values[2] - state.position.Z).abs() < 1e-6);
+            assert!((values[4] - state.velocity.Y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn frames() {
+        let state = StateVector::<frame::J2000> {
+            epoch: time::J2000,
+            position: Vec3::new(7000.0, 0.0, 0.0),
+            velocity: Vec3::new(0.0, 7.5, 0.0),
+        };
+        let metadata = OemMetadata {
+            originator: String::from("test"),
+            creation_date: time::J2000,
+            object_name: String::from("TEST"),
+            object_id: String::from("2000-001A"),
+            comments: Vec::new(),
+        };
+        let oem = to_oem(&metadata, &[state]);
+        assert!(oem.contains("\nREF_FRAME = EME2000\n"));
+        assert!(!oem.contains("COMMENT"));
+        assert!(oem.ends_with("2000-01-01T12:00:00.000000      7000.000000         0.000000         0.000000    0.000000000    7.500000000    0.000000000\n"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_states() {
+        to_oem::<frame::TEME>(&OemMetadata::from_tle(&iss(), 0.0), &[]);
+    }
+}
@@ -0,0 +1,1315 @@
+/*!  # Orbit Mean-Elements Messages
+
+Read element sets published as CCSDS Orbit Mean-Elements Messages (OMM),
+the format Celestrak and Space-Track serve as "GP" data. Each record becomes
+a [`TLE`](../tle/struct.TLE.html), so it can go straight to the propagator.
+
+Messages can be read in any of the three CCSDS renderings: KVN (`KEY =
+value` lines), XML, and JSON. JSON support needs the `serde_json` feature.
+Celestrak also serves GP data as CSV (`FORMAT=csv`), one record per line
+under a header of the same keywords, which needs the `csv` feature.
+
+Going the other way, [`Omm::from_tle`](struct.Omm.html#method.from_tle)
+gives the OMM fields of a TLE, and [`to_kvn`](fn.to_kvn.html) and
+[`to_xml`](fn.to_xml.html) write them out; JSON is written with serde. A
+TLE converted to an OMM and back is unchanged.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::error;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use angle::Deg;
+use oem::object_id;
+use time;
+use tle::TLE;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::string::{String, ToString};
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// OMM version written
+pub const VERSION: &str = "2.0";
+
+
+/// ## OMM
+///
+/// The fields of an OMM that SGP4 uses. Field names follow the OMM keywords;
+/// angles are in degrees and the mean motion in revolutions per day, as in
+/// the message itself.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "SCREAMING_SNAKE_CASE"))]
+pub struct Omm {
+
+    /// Name of the object
+    pub object_name: String,
+
+    /// International designator, e.g. `1998-067A`
+    pub object_id: String,
+
+    /// Body the orbit is about, `EARTH` for SGP4 elements
+    #[cfg_attr(feature = "serde", serde(default = "earth"))]
+    pub center_name: String,
+
+    /// Reference frame of the elements, `TEME` for SGP4 elements
+    #[cfg_attr(feature = "serde", serde(default = "teme"))]
+    pub ref_frame: String,
+
+    /// Time system of the epoch, `UTC` for SGP4 elements
+    #[cfg_attr(feature = "serde", serde(default = "utc"))]
+    pub time_system: String,
+
+    /// Theory the mean elements belong to, `SGP4` for elements the
+    /// propagator can use
+    #[cfg_attr(feature = "serde", serde(default = "sgp4"))]
+    pub mean_element_theory: String,
+
+    /// Epoch of the elements (UTC), e.g. `2016-07-28T14:21:26.330688`
+    pub epoch: String,
+
+    /// Mean motion (revolutions per day)
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "number"))]
+    pub mean_motion: f64,
+
+    /// Eccentricity
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "number"))]
+    pub eccentricity: f64,
+
+    /// Inclination (degrees)
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "number"))]
+    pub inclination: f64,
+
+    /// Right ascension of the ascending node (degrees)
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "number"))]
+    pub ra_of_asc_node: f64,
+
+    /// Argument of pericenter (degrees)
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "number"))]
+    pub arg_of_pericenter: f64,
+
+    /// Mean anomaly (degrees)
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "number"))]
+    pub mean_anomaly: f64,
+
+    /// Ephemeris type, always 0 for SGP4 elements
+    #[cfg_attr(feature = "serde", serde(default, deserialize_with = "number"))]
+    pub ephemeris_type: u8,
+
+    /// Classification (U=Unclassified)
+    #[cfg_attr(feature = "serde", serde(default = "unclassified"))]
+    pub classification_type: String,
+
+    /// Satellite catalog number
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "number"))]
+    pub norad_cat_id: u32,
+
+    /// Element set number
+    #[cfg_attr(feature = "serde", serde(default, deserialize_with = "number"))]
+    pub element_set_no: u16,
+
+    /// Revolution number at epoch
+    #[cfg_attr(feature = "serde", serde(default, deserialize_with = "number"))]
+    pub rev_at_epoch: u32,
+
+    /// B* drag term (Earth radii⁻¹)
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "number"))]
+    pub bstar: f64,
+
+    /// First time derivative of the mean motion divided by two
+    /// (revolutions/day²), as in the TLE
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "number"))]
+    pub mean_motion_dot: f64,
+
+    /// Second time derivative of the mean motion divided by six
+    /// (revolutions/day³), as in the TLE
+    #[cfg_attr(feature = "serde", serde(default, deserialize_with = "number"))]
+    pub mean_motion_ddot: f64,
+}
+
+/// Default classification when a record doesn't give one
+#[cfg(feature = "serde")]
+fn unclassified() -> String {
+    String::from("U")
+}
+
+/// Default center, frame, time system and theory, as Celestrak's JSON
+/// leaves them out
+#[cfg(feature = "serde")]
+fn earth() -> String {
+    String::from("EARTH")
+}
+
+#[cfg(feature = "serde")]
+fn teme() -> String {
+    String::from("TEME")
+}
+
+#[cfg(feature = "serde")]
+fn utc() -> String {
+    String::from("UTC")
+}
+
+#[cfg(feature = "serde")]
+fn sgp4() -> String {
+    String::from("SGP4")
+}
+
+/// Deserialize a number written either as a JSON number or as a string, as
+/// Space-Track does
+#[cfg(feature = "serde")]
+fn number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where D: Deserializer<'de>,
+          T: Deserialize<'de> + FromStr,
+{
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum NumberOrText<T> {
+        Number(T),
+        Text(String),
+    }
+
+    match NumberOrText::<T>::deserialize(deserializer)? {
+        NumberOrText::Number(value) => Ok(value),
+        NumberOrText::Text(text) => text.trim().parse().map_err(|_| D::Error::custom(format_args!("invalid number \"{}\"", text))),
+    }
+}
+
+
+/// ## OMM Errors
+///
+/// Why an OMM couldn't be read.
+#[derive(Debug)]
+pub enum OmmError {
+
+    /// The JSON was malformed or missing a required field
+    #[cfg(feature = "serde_json")]
+    Json(serde_json::Error),
+
+    /// The epoch isn't a CCSDS date and time
+    InvalidEpoch(String),
+
+    /// A required keyword is missing from a KVN or XML message
+    MissingField(&'static str),
+
+    /// A keyword in a KVN or XML message has a value that isn't a number
+    InvalidField {
+        /// The keyword
+        keyword: &'static str,
+        /// Its value
+        value: String,
+    },
+
+    /// The text isn't well-formed KVN or XML; holds the offending line or tag
+    Malformed(String),
+
+    /// The elements aren't SGP4 elements: the center isn't `EARTH`, the
+    /// frame `TEME`, the time system `UTC` or the theory `SGP4`
+    Unsupported {
+        /// The keyword
+        keyword: &'static str,
+        /// Its value
+        value: String,
+    },
+
+    /// The CSV was malformed, or a record has the wrong number of fields
+    #[cfg(feature = "csv")]
+    Csv(csv::Error),
+}
+
+impl fmt::Display for OmmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            #[cfg(feature = "serde_json")]
+            OmmError::Json(ref error) => write!(f, "{}", error),
+            OmmError::InvalidEpoch(ref epoch) => write!(f, "invalid epoch \"{}\"", epoch),
+            OmmError::MissingField(keyword) => write!(f, "missing {}", keyword),
+            OmmError::InvalidField { keyword, ref value } => write!(f, "invalid {} \"{}\"", keyword, value),
+            OmmError::Malformed(ref text) => write!(f, "malformed message at \"{}\"", text),
+            OmmError::Unsupported { keyword, ref value } => write!(f, "unsupported {} \"{}\"", keyword, value),
+            #[cfg(feature = "csv")]
+            OmmError::Csv(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl error::Error for OmmError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            #[cfg(feature = "serde_json")]
+            OmmError::Json(ref error) => Some(error),
+            OmmError::InvalidEpoch(_)
+            | OmmError::MissingField(_)
+            | OmmError::InvalidField { .. }
+            | OmmError::Malformed(_)
+            | OmmError::Unsupported { .. } => None,
+            #[cfg(feature = "csv")]
+            OmmError::Csv(ref error) => Some(error),
+        }
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl From<serde_json::Error> for OmmError {
+    fn from(error: serde_json::Error) -> OmmError {
+        OmmError::Json(error)
+    }
+}
+
+#[cfg(feature = "csv")]
+impl From<csv::Error> for OmmError {
+    fn from(error: csv::Error) -> OmmError {
+        OmmError::Csv(error)
+    }
+}
+
+
+/// Parse a CCSDS epoch, either `YYYY-MM-DDThh:mm:ss.s` or
+/// `YYYY-DDDThh:mm:ss.s` (with an optional trailing `Z`), into the year and
+/// fractional day of the year
+fn parse_epoch(epoch: &str) -> Option<(u16, f64)> {
+    let epoch = epoch.trim();
+    let epoch = epoch.strip_suffix('Z').unwrap_or(epoch);
+    let (date, clock) = match epoch.find('T') {
+        Some(index) => (&epoch[..index], &epoch[index + 1..]),
+        None => (epoch, "00:00:00"),
+    };
+
+    let mut date_parts = date.split('-');
+    let year = date_parts.next()?.parse::<u16>().ok()?;
+    let day_of_year = match (date_parts.next(), date_parts.next(), date_parts.next()) {
+        (Some(month), Some(day), None) => {
+            let month = month.parse::<u32>().ok().filter(|month| (1..=12).contains(month))?;
+            let day = day.parse::<u32>().ok().filter(|day| (1..=31).contains(day))?;
+            time::day_of_year(i32::from(year), month, f64::from(day))
+        }
+        (Some(day), None, None) => f64::from(day.parse::<u16>().ok().filter(|day| (1..=366).contains(day))?),
+        _ => return None,
+    };
+
+    let mut clock_parts = clock.split(':');
+    let hour = clock_parts.next()?.parse::<u8>().ok().filter(|hour| *hour < 24)?;
+    let minute = clock_parts.next()?.parse::<u8>().ok().filter(|minute| *minute < 60)?;
+    let second = clock_parts.next()?.parse::<f64>().ok().filter(|second| (0.0..61.0).contains(second))?;
+    if clock_parts.next().is_some() {
+        return None;
+    }
+
+    let seconds = f64::from(hour) * 3600.0 + f64::from(minute) * 60.0 + second;
+    Some((year, day_of_year + seconds / 86400.0))
+}
+
+/// The TLE form of an international designator: `1998-067A` becomes
+/// `98067A  `
+fn int_designator(object_id: &str) -> String {
+    let designator = match object_id.get(2..4) {
+        Some(_) if object_id.as_bytes().get(4) == Some(&b'-') => {
+            let mut designator = String::from(&object_id[2..4]);
+            designator.push_str(&object_id[5..]);
+            designator
+        }
+        _ => String::from(object_id),
+    };
+    format!("{:<8}", designator)
+}
+
+
+/// A TLE epoch as a CCSDS date and time, to the microsecond, which keeps
+/// every digit of the TLE's day fraction
+fn ccsds_epoch(year: u16, day_of_year: f64) -> String {
+    let mut day = day_of_year.floor();
+    let mut microseconds = ((day_of_year - day) * 86_400.0e6).round() as u64;
+    if microseconds >= 86_400_000_000 {
+        microseconds -= 86_400_000_000;
+        day += 1.0;
+    }
+
+    let (month, day) = time::calendar_date(i32::from(year), day);
+    let seconds = microseconds / 1_000_000;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}",
+            year, month, day as u32,
+            seconds / 3600, seconds / 60 % 60, seconds % 60, microseconds % 1_000_000)
+}
+
+
+/// Keyword and value pairs of one KVN or XML message, in order
+type Fields = Vec<(String, String)>;
+
+/// The value of `keyword`, if the message has it
+fn value<'a>(fields: &'a [(String, String)], keyword: &str) -> Option<&'a str> {
+    fields.iter()
+        .find(|&(key, _)| key == keyword)
+        .map(|(_, value)| value.as_str())
+}
+
+/// The value of a keyword the message must have
+fn required<'a>(fields: &'a [(String, String)], keyword: &'static str) -> Result<&'a str, OmmError> {
+    value(fields, keyword).ok_or(OmmError::MissingField(keyword))
+}
+
+/// The numeric value of a keyword the message must have
+fn required_number<T: FromStr>(fields: &[(String, String)], keyword: &'static str) -> Result<T, OmmError> {
+    parse_number(keyword, required(fields, keyword)?)
+}
+
+/// The numeric value of an optional keyword, or the default if it's absent
+fn optional_number<T: FromStr + Default>(fields: &[(String, String)], keyword: &'static str) -> Result<T, OmmError> {
+    match value(fields, keyword) {
+        Some(text) => parse_number(keyword, text),
+        None => Ok(T::default()),
+    }
+}
+
+/// Parse a keyword's value as a number
+fn parse_number<T: FromStr>(keyword: &'static str, text: &str) -> Result<T, OmmError> {
+    text.parse().map_err(|_| OmmError::InvalidField { keyword, value: String::from(text) })
+}
+
+
+impl Omm {
+
+    /// ## From TLE
+    ///
+    /// The OMM fields of an element set, as SGP4 elements about the Earth
+    /// in TEME with a UTC epoch. [`to_tle`](#method.to_tle) turns them back
+    /// into the same TLE.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// use sgp4::omm::Omm;
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    ///
+    /// let omm = Omm::from_tle(&tle);
+    /// assert_eq!(omm.object_id, "1998-067A");
+    /// assert_eq!(omm.epoch, "2016-07-28T14:21:26.330688");
+    /// assert_eq!(omm.mean_element_theory, "SGP4");
+    /// assert_eq!(omm.to_tle().unwrap(), tle);
+    /// ```
+    pub fn from_tle(tle: &TLE) -> Omm {
+        Omm {
+            object_name: String::from(tle.name.trim()),
+            object_id: object_id(&tle.int_designator),
+            center_name: String::from("EARTH"),
+            ref_frame: String::from("TEME"),
+            time_system: String::from("UTC"),
+            mean_element_theory: String::from("SGP4"),
+            epoch: ccsds_epoch(tle.epoch_year, tle.epoch_day),
+            mean_motion: tle.mean_motion,
+            eccentricity: tle.e,
+            inclination: tle.i.0,
+            ra_of_asc_node: tle.raan.0,
+            arg_of_pericenter: tle.omega.0,
+            mean_anomaly: tle.mean_anomaly.0,
+            ephemeris_type: 0,
+            classification_type: tle.classification.to_string(),
+            norad_cat_id: tle.sat_number,
+            element_set_no: tle.tle_version,
+            rev_at_epoch: tle.revolution_number,
+            bstar: tle.bstar,
+            mean_motion_dot: tle.first_mean_motion / 2.0,
+            mean_motion_ddot: tle.second_mean_motion / 6.0,
+        }
+    }
+
+    /// ## To TLE
+    ///
+    /// Convert to the element set the propagator takes.
+    ///
+    /// ### Errors
+    ///
+    /// `Unsupported` if the message isn't about the Earth, in TEME, with a
+    /// UTC epoch and SGP4 mean elements, and `InvalidEpoch` if the epoch
+    /// can't be read.
+    pub fn to_tle(&self) -> Result<TLE, OmmError> {
+        let supported = [
+            ("CENTER_NAME", &self.center_name, &["EARTH"][..]),
+            ("REF_FRAME", &self.ref_frame, &["TEME"][..]),
+            ("TIME_SYSTEM", &self.time_system, &["UTC"][..]),
+            ("MEAN_ELEMENT_THEORY", &self.mean_element_theory, &["SGP4", "SGP/SGP4"][..]),
+        ];
+        for &(keyword, value, allowed) in supported.iter() {
+            if !allowed.iter().any(|allowed| value.trim().eq_ignore_ascii_case(allowed)) {
+                return Err(OmmError::Unsupported { keyword, value: value.clone() });
+            }
+        }
+
+        let (epoch_year, epoch_day) = parse_epoch(&self.epoch)
+            .ok_or_else(|| OmmError::InvalidEpoch(self.epoch.clone()))?;
+
+        Ok(TLE {
+            name: self.object_name.clone(),
+            sat_number: self.norad_cat_id,
+            classification: self.classification_type.chars().next().unwrap_or('U'),
+            int_designator: int_designator(&self.object_id),
+            epoch_year,
+            epoch_day,
+            first_mean_motion: self.mean_motion_dot * 2.0,
+            second_mean_motion: self.mean_motion_ddot * 6.0,
+            bstar: self.bstar,
+            tle_version: self.element_set_no,
+            i: Deg(self.inclination),
+            raan: Deg(self.ra_of_asc_node),
+            e: self.eccentricity,
+            omega: Deg(self.arg_of_pericenter),
+            mean_anomaly: Deg(self.mean_anomaly),
+            mean_motion: self.mean_motion,
+            revolution_number: self.rev_at_epoch,
+        })
+    }
+
+    /// Gather the fields SGP4 needs from the keywords of a KVN or XML message
+    fn from_fields(fields: &[(String, String)]) -> Result<Omm, OmmError> {
+        Ok(Omm {
+            object_name: String::from(required(fields, "OBJECT_NAME")?),
+            object_id: String::from(required(fields, "OBJECT_ID")?),
+            center_name: String::from(value(fields, "CENTER_NAME").unwrap_or("EARTH")),
+            ref_frame: String::from(value(fields, "REF_FRAME").unwrap_or("TEME")),
+            time_system: String::from(value(fields, "TIME_SYSTEM").unwrap_or("UTC")),
+            mean_element_theory: String::from(value(fields, "MEAN_ELEMENT_THEORY").unwrap_or("SGP4")),
+            epoch: String::from(required(fields, "EPOCH")?),
+            mean_motion: required_number(fields, "MEAN_MOTION")?,
+            eccentricity: required_number(fields, "ECCENTRICITY")?,
+            inclination: required_number(fields, "INCLINATION")?,
+            ra_of_asc_node: required_number(fields, "RA_OF_ASC_NODE")?,
+            arg_of_pericenter: required_number(fields, "ARG_OF_PERICENTER")?,
+            mean_anomaly: required_number(fields, "MEAN_ANOMALY")?,
+            ephemeris_type: optional_number(fields, "EPHEMERIS_TYPE")?,
+            classification_type: String::from(value(fields, "CLASSIFICATION_TYPE").unwrap_or("U")),
+            norad_cat_id: required_number(fields, "NORAD_CAT_ID")?,
+            element_set_no: optional_number(fields, "ELEMENT_SET_NO")?,
+            rev_at_epoch: optional_number(fields, "REV_AT_EPOCH")?,
+            bstar: required_number(fields, "BSTAR")?,
+            mean_motion_dot: required_number(fields, "MEAN_MOTION_DOT")?,
+            mean_motion_ddot: optional_number(fields, "MEAN_MOTION_DDOT")?,
+        })
+    }
+
+    /// The keywords and values of the metadata, mean elements and TLE
+    /// parameters, as written in both KVN and XML
+    fn sections(&self) -> [Fields; 3] {
+        let field = |keyword: &str, value: String| (String::from(keyword), value);
+        [
+            vec![
+                field("OBJECT_NAME", self.object_name.clone()),
+                field("OBJECT_ID", self.object_id.clone()),
+                field("CENTER_NAME", self.center_name.clone()),
+                field("REF_FRAME", self.ref_frame.clone()),
+                field("TIME_SYSTEM", self.time_system.clone()),
+                field("MEAN_ELEMENT_THEORY", self.mean_element_theory.clone()),
+            ],
+            vec![
+                field("EPOCH", self.epoch.clone()),
+                field("MEAN_MOTION", self.mean_motion.to_string()),
+                field("ECCENTRICITY", self.eccentricity.to_string()),
+                field("INCLINATION", self.inclination.to_string()),
+                field("RA_OF_ASC_NODE", self.ra_of_asc_node.to_string()),
+                field("ARG_OF_PERICENTER", self.arg_of_pericenter.to_string()),
+                field("MEAN_ANOMALY", self.mean_anomaly.to_string()),
+            ],
+            vec![
+                field("EPHEMERIS_TYPE", self.ephemeris_type.to_string()),
+                field("CLASSIFICATION_TYPE", self.classification_type.clone()),
+                field("NORAD_CAT_ID", self.norad_cat_id.to_string()),
+                field("ELEMENT_SET_NO", self.element_set_no.to_string()),
+                field("REV_AT_EPOCH", self.rev_at_epoch.to_string()),
+                field("BSTAR", self.bstar.to_string()),
+                field("MEAN_MOTION_DOT", self.mean_motion_dot.to_string()),
+                field("MEAN_MOTION_DDOT", self.mean_motion_ddot.to_string()),
+            ],
+        ]
+    }
+}
+
+
+/// Replace the characters XML reserves in element text with entities
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Replace the predefined XML entities in element text
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return String::from(text);
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+
+/// ## Parse KVN
+///
+/// Read OMMs in the CCSDS keyword = value notation. A file may hold several
+/// messages, each starting with a `CCSDS_OMM_VERS` line. Blank lines,
+/// `COMMENT` lines and units in square brackets are ignored.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let kvn = "CCSDS_OMM_VERS = 2.0
+/// CREATION_DATE = 2016-07-28T18:00:00
+/// ORIGINATOR = CELESTRAK
+/// OBJECT_NAME = ISS (ZARYA)
+/// OBJECT_ID = 1998-067A
+/// CENTER_NAME = EARTH
+/// REF_FRAME = TEME
+/// TIME_SYSTEM = UTC
+/// MEAN_ELEMENT_THEORY = SGP4
+/// EPOCH = 2016-07-28T14:21:26.330688
+/// MEAN_MOTION = 15.54888439 [rev/day]
+/// ECCENTRICITY = .000178
+/// INCLINATION = 51.6406 [deg]
+/// RA_OF_ASC_NODE = 211.4156 [deg]
+/// ARG_OF_PERICENTER = 85.8307 [deg]
+/// MEAN_ANOMALY = 274.3426 [deg]
+/// EPHEMERIS_TYPE = 0
+/// CLASSIFICATION_TYPE = U
+/// NORAD_CAT_ID = 25544
+/// ELEMENT_SET_NO = 999
+/// REV_AT_EPOCH = 1143
+/// BSTAR = .11901E-4 [1/ER]
+/// MEAN_MOTION_DOT = .812E-5 [rev/day**2]
+/// MEAN_MOTION_DDOT = 0 [rev/day**3]
+/// ";
+///
+/// let tles = sgp4::omm::parse_kvn(kvn).unwrap();
+/// let state = sgp4::propagate(&tles[0], 0.0).unwrap();
+/// ```
+pub fn parse_kvn(text: &str) -> Result<Vec<TLE>, OmmError> {
+    let mut messages: Vec<Fields> = Vec::new();
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("COMMENT") {
+            continue;
+        }
+
+        let (keyword, value) = match line.find('=') {
+            Some(index) => (line[..index].trim(), line[index + 1..].trim()),
+            None => return Err(OmmError::Malformed(String::from(line))),
+        };
+
+        // Units, e.g. `15.54888439 [rev/day]`
+        let value = match value.find('[') {
+            Some(index) if value.ends_with(']') => value[..index].trim_end(),
+            _ => value,
+        };
+
+        if keyword == "CCSDS_OMM_VERS" || messages.is_empty() {
+            messages.push(Vec::new());
+        }
+        if let Some(fields) = messages.last_mut() {
+            fields.push((String::from(keyword), String::from(value)));
+        }
+    }
+
+    messages.iter().map(|fields| Omm::from_fields(fields)?.to_tle()).collect()
+}
+
+
+/// ## Parse XML
+///
+/// Read OMMs in the CCSDS XML schema: one `<omm>` document, or several
+/// wrapped in an `<ndm>` as Celestrak serves them (`FORMAT=xml`). Only
+/// elements inside an `<omm>` are read, and only by name, so the header,
+/// metadata and data sections may appear in any layout.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+/// <ndm>
+///   <omm id="CCSDS_OMM_VERS" version="2.0">
+///     <header><CREATION_DATE/><ORIGINATOR/></header>
+///     <body><segment>
+///       <metadata>
+///         <OBJECT_NAME>ISS (ZARYA)</OBJECT_NAME>
+///         <OBJECT_ID>1998-067A</OBJECT_ID>
+///         <CENTER_NAME>EARTH</CENTER_NAME>
+///         <REF_FRAME>TEME</REF_FRAME>
+///         <TIME_SYSTEM>UTC</TIME_SYSTEM>
+///         <MEAN_ELEMENT_THEORY>SGP4</MEAN_ELEMENT_THEORY>
+///       </metadata>
+///       <data>
+///         <meanElements>
+///           <EPOCH>2016-07-28T14:21:26.330688</EPOCH>
+///           <MEAN_MOTION>15.54888439</MEAN_MOTION>
+///           <ECCENTRICITY>.000178</ECCENTRICITY>
+///           <INCLINATION>51.6406</INCLINATION>
+///           <RA_OF_ASC_NODE>211.4156</RA_OF_ASC_NODE>
+///           <ARG_OF_PERICENTER>85.8307</ARG_OF_PERICENTER>
+///           <MEAN_ANOMALY>274.3426</MEAN_ANOMALY>
+///         </meanElements>
+///         <tleParameters>
+///           <EPHEMERIS_TYPE>0</EPHEMERIS_TYPE>
+///           <CLASSIFICATION_TYPE>U</CLASSIFICATION_TYPE>
+///           <NORAD_CAT_ID>25544</NORAD_CAT_ID>
+///           <ELEMENT_SET_NO>999</ELEMENT_SET_NO>
+///           <REV_AT_EPOCH>1143</REV_AT_EPOCH>
+///           <BSTAR>.11901E-4</BSTAR>
+///           <MEAN_MOTION_DOT>.812E-5</MEAN_MOTION_DOT>
+///           <MEAN_MOTION_DDOT>0</MEAN_MOTION_DDOT>
+///         </tleParameters>
+///       </data>
+///     </segment></body>
+///   </omm>
+/// </ndm>"#;
+///
+/// let tles = sgp4::omm::parse_xml(xml).unwrap();
+/// let state = sgp4::propagate(&tles[0], 0.0).unwrap();
+/// ```
+pub fn parse_xml(text: &str) -> Result<Vec<TLE>, OmmError> {
+    let mut messages: Vec<Fields> = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start..];
+
+        // Comments may contain '>', so skip to their own terminator
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").ok_or_else(|| OmmError::Malformed(String::from("<!--")))?;
+            rest = &rest[end + 3..];
+            continue;
+        }
+
+        let end = rest.find('>').ok_or_else(|| OmmError::Malformed(String::from(rest.lines().next().unwrap_or(rest))))?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        // Declarations, closing tags and empty elements carry no values
+        if tag.starts_with('?') || tag.starts_with('!') || tag.starts_with('/') || tag.ends_with('/') {
+            continue;
+        }
+
+        let name = tag.split_whitespace().next().unwrap_or("");
+        if name == "omm" {
+            messages.push(Vec::new());
+            continue;
+        }
+
+        // A leaf element is text followed directly by its own closing tag
+        let text_end = rest.find('<').unwrap_or(rest.len());
+        let closing = &rest[text_end..];
+        let is_leaf = closing.strip_prefix("</")
+            .and_then(|closing| closing.trim_start().strip_prefix(name))
+            .is_some_and(|closing| closing.trim_start().starts_with('>'));
+        if is_leaf {
+            if let Some(fields) = messages.last_mut() {
+                fields.push((String::from(name), unescape(rest[..text_end].trim())));
+            }
+            rest = closing;
+        }
+    }
+
+    messages.iter().map(|fields| Omm::from_fields(fields)?.to_tle()).collect()
+}
+
+
+/// ## Parse JSON
+///
+/// Read GP data in OMM JSON form: either one record or an array of them,
+/// as served by Celestrak (`FORMAT=json`) or Space-Track (which quotes the
+/// numbers). Requires the `serde_json` feature.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let json = r#"[{
+///     "OBJECT_NAME": "ISS (ZARYA)",
+///     "OBJECT_ID": "1998-067A",
+///     "EPOCH": "2016-07-28T14:21:26.330688",
+///     "MEAN_MOTION": 15.54888439,
+///     "ECCENTRICITY": 0.000178,
+///     "INCLINATION": 51.6406,
+///     "RA_OF_ASC_NODE": 211.4156,
+///     "ARG_OF_PERICENTER": 85.8307,
+///     "MEAN_ANOMALY": 274.3426,
+///     "EPHEMERIS_TYPE": 0,
+///     "CLASSIFICATION_TYPE": "U",
+///     "NORAD_CAT_ID": 25544,
+///     "ELEMENT_SET_NO": 999,
+///     "REV_AT_EPOCH": 1143,
+///     "BSTAR": 1.1901e-5,
+///     "MEAN_MOTION_DOT": 8.12e-6,
+///     "MEAN_MOTION_DDOT": 0
+/// }]"#;
+///
+/// let tles = sgp4::omm::parse_json(json).unwrap();
+/// let state = sgp4::propagate(&tles[0], 0.0).unwrap();
+/// ```
+#[cfg(feature = "serde_json")]
+pub fn parse_json(text: &str) -> Result<Vec<TLE>, OmmError> {
+    let records: Vec<Omm> = if text.trim_start().starts_with('[') {
+        serde_json::from_str(text)?
+    } else {
+        vec![serde_json::from_str(text)?]
+    };
+
+    records.iter().map(Omm::to_tle).collect()
+}
+
+
+/// ## Parse CSV
+///
+/// Read GP data in Celestrak's CSV form (`FORMAT=csv`): a header line of
+/// OMM keywords, in any order, then one record per line. Empty fields count
+/// as missing. Requires the `csv` feature.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let csv = "\
+/// OBJECT_NAME,OBJECT_ID,EPOCH,MEAN_MOTION,ECCENTRICITY,INCLINATION,RA_OF_ASC_NODE,ARG_OF_PERICENTER,MEAN_ANOMALY,EPHEMERIS_TYPE,CLASSIFICATION_TYPE,NORAD_CAT_ID,ELEMENT_SET_NO,REV_AT_EPOCH,BSTAR,MEAN_MOTION_DOT,MEAN_MOTION_DDOT
+/// ISS (ZARYA),1998-067A,2016-07-28T14:21:26.330688,15.54888439,.000178,51.6406,211.4156,85.8307,274.3426,0,U,25544,999,1143,.11901E-4,.812E-5,0
+/// ";
+///
+/// let tles = sgp4::omm::parse_csv(csv).unwrap();
+/// let state = sgp4::propagate(&tles[0], 0.0).unwrap();
+/// ```
+#[cfg(feature = "csv")]
+pub fn parse_csv(text: &str) -> Result<Vec<TLE>, OmmError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let keywords = reader.headers()?.clone();
+
+    reader.records().map(|record| {
+        let record = record?;
+        let fields: Fields = keywords.iter()
+            .zip(record.iter())
+            .filter(|&(_, value)| !value.is_empty())
+            .map(|(keyword, value)| (String::from(keyword), String::from(value)))
+            .collect();
+        Omm::from_fields(&fields)?.to_tle()
+    }).collect()
+}
+
+
+/// ## Write KVN
+///
+/// Write `messages` to `out` as OMMs in keyword = value notation, one after
+/// another, each with a header naming the `originator` and the
+/// `creation_date` (Julian date, UTC).
+pub fn write_kvn<W: Write>(out: &mut W, originator: &str, creation_date: f64, messages: &[Omm]) -> fmt::Result {
+    for (index, omm) in messages.iter().enumerate() {
+        if index > 0 {
+            writeln!(out)?;
+        }
+        writeln!(out, "CCSDS_OMM_VERS = {}", VERSION)?;
+        writeln!(out, "CREATION_DATE = {}", time::iso_8601(creation_date))?;
+        writeln!(out, "ORIGINATOR = {}", originator)?;
+        for section in omm.sections().iter() {
+            writeln!(out)?;
+            for (keyword, value) in section {
+                writeln!(out, "{} = {}", keyword, value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// ## To KVN
+///
+/// `messages` as the text of OMMs in keyword = value notation, which
+/// [`parse_kvn`](fn.parse_kvn.html) reads back.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::omm::{self, Omm};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let kvn = omm::to_kvn("sgp4", tle.epoch_julian_date(), &[Omm::from_tle(&tle)]);
+/// assert!(kvn.contains("MEAN_ELEMENT_THEORY = SGP4"));
+/// assert_eq!(omm::parse_kvn(&kvn).unwrap()[0], tle);
+/// ```
+pub fn to_kvn(originator: &str, creation_date: f64, messages: &[Omm]) -> String {
+    let mut out = String::new();
+    write_kvn(&mut out, originator, creation_date, messages).expect("writing to a String can't fail");
+    out
+}
+
+/// ## Write XML
+///
+/// Write `messages` to `out` as OMMs in the CCSDS XML schema, wrapped in
+/// one `<ndm>`, each with a header naming the `originator` and the
+/// `creation_date` (Julian date, UTC).
+pub fn write_xml<W: Write>(out: &mut W, originator: &str, creation_date: f64, messages: &[Omm]) -> fmt::Result {
+    let element = |out: &mut W, indent: &str, (keyword, value): &(String, String)| {
+        writeln!(out, "{}<{}>{}</{}>", indent, keyword, escape(value), keyword)
+    };
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, "<ndm>")?;
+    for omm in messages {
+        let [metadata, mean_elements, tle_parameters] = omm.sections();
+        writeln!(out, r#"  <omm id="CCSDS_OMM_VERS" version="{}">"#, VERSION)?;
+        writeln!(out, "    <header>")?;
+        element(out, "      ", &(String::from("CREATION_DATE"), time::iso_8601(creation_date)))?;
+        element(out, "      ", &(String::from("ORIGINATOR"), String::from(originator)))?;
+        writeln!(out, "    </header>")?;
+        writeln!(out, "    <body><segment>")?;
+        writeln!(out, "      <metadata>")?;
+        for field in &metadata {
+            element(out, "        ", field)?;
+        }
+        writeln!(out, "      </metadata>")?;
+        writeln!(out, "      <data>")?;
+        writeln!(out, "        <meanElements>")?;
+        for field in &mean_elements {
+            element(out, "          ", field)?;
+        }
+        writeln!(out, "        </meanElements>")?;
+        writeln!(out, "        <tleParameters>")?;
+        for field in &tle_parameters {
+            element(out, "          ", field)?;
+        }
+        writeln!(out, "        </tleParameters>")?;
+        writeln!(out, "      </data>")?;
+        writeln!(out, "    </segment></body>")?;
+        writeln!(out, "  </omm>")?;
+    }
+    writeln!(out, "</ndm>")
+}
+
+/// ## To XML
+///
+/// `messages` as the text of an XML document of OMMs, which
+/// [`parse_xml`](fn.parse_xml.html) reads back.
+pub fn to_xml(originator: &str, creation_date: f64, messages: &[Omm]) -> String {
+    let mut out = String::new();
+    write_xml(&mut out, originator, creation_date, messages).expect("writing to a String can't fail");
+    out
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::iss;
+
+    #[test]
+    fn epochs() {
+        let (year, day) = parse_epoch("2016-07-28T14:21:26.330688").unwrap();
+        assert_eq!(year, 2016);
+        assert!((day - 210.59822142).abs() < 1e-10);
+
+        assert_eq!(parse_epoch("2016-210T12:00:00Z"), Some((2016, 210.5)));
+        assert_eq!(parse_epoch("1980-01-01T00:00:00"), Some((1980, 1.0)));
+        assert_eq!(parse_epoch("2016-07-28"), Some((2016, 210.0)));
+        assert_eq!(parse_epoch("2016-13-01T00:00:00"), None);
+        assert_eq!(parse_epoch("2016-07-28T25:00:00"), None);
+        assert_eq!(parse_epoch("yesterday"), None);
+    }
+
+    #[test]
+    fn ccsds_epochs() {
+        assert_eq!(ccsds_epoch(2016, 210.59822142), "2016-07-28T14:21:26.330688");
+        assert_eq!(ccsds_epoch(2016, 1.0), "2016-01-01T00:00:00.000000");
+        assert_eq!(ccsds_epoch(2016, 366.5), "2016-12-31T12:00:00.000000");
+        assert_eq!(ccsds_epoch(2015, 59.9999999999999), "2015-03-01T00:00:00.000000");
+    }
+
+    #[test]
+    fn designators() {
+        assert_eq!(int_designator("1998-067A"), "98067A  ");
+        assert_eq!(int_designator("2019-074BZ"), "19074BZ ");
+        assert_eq!(int_designator(""), "        ");
+    }
+
+    /// Check the KVN and XML tests produce the ISS element set
+    fn assert_iss(tle: &TLE) {
+        let expected = iss();
+        assert_eq!(tle.name, expected.name);
+        assert_eq!(tle.sat_number, expected.sat_number);
+        assert_eq!(tle.classification, expected.classification);
+        assert_eq!(tle.int_designator, expected.int_designator);
+        assert_eq!(tle.epoch_year, expected.epoch_year);
+        assert!((tle.epoch_day - expected.epoch_day).abs() < 1e-10);
+        assert_eq!(tle.tle_version, expected.tle_version);
+        assert_eq!(tle.revolution_number, expected.revolution_number);
+        assert_eq!(tle.e, expected.e);
+        assert_eq!(tle.mean_motion, expected.mean_motion);
+        assert!((tle.first_mean_motion - expected.first_mean_motion).abs() < 1e-15);
+        assert!((tle.bstar - expected.bstar).abs() < 1e-15);
+    }
+
+    #[test]
+    fn kvn() {
+        let message = "CCSDS_OMM_VERS = 2.0
+COMMENT  GENERATED VIA SPACE-TRACK.ORG API
+CREATION_DATE = 2016-07-28T18:00:00
+ORIGINATOR = 18 SPCS
+
+OBJECT_NAME = ISS (ZARYA)
+OBJECT_ID = 1998-067A
+CENTER_NAME = EARTH
+REF_FRAME = TEME
+TIME_SYSTEM = UTC
+MEAN_ELEMENT_THEORY = SGP4
+EPOCH = 2016-07-28T14:21:26.330688
+MEAN_MOTION = 15.54888439 [rev/day]
+ECCENTRICITY = .000178
+INCLINATION = 51.6406 [deg]
+RA_OF_ASC_NODE = 211.4156 [deg]
+ARG_OF_PERICENTER = 85.8307 [deg]
+MEAN_ANOMALY = 274.3426 [deg]
+EPHEMERIS_TYPE = 0
+CLASSIFICATION_TYPE = U
+NORAD_CAT_ID = 25544
+ELEMENT_SET_NO = 999
+REV_AT_EPOCH = 1143
+BSTAR = .11901E-4 [1/ER]
+MEAN_MOTION_DOT = .812E-5 [rev/day**2]
+MEAN_MOTION_DDOT = 0 [rev/day**3]
+";
+        let tles = parse_kvn(message).unwrap();
+        assert_eq!(tles.len(), 1);
+        assert_iss(&tles[0]);
+
+        // Several messages back to back
+        let two = format!("{}\n{}", message, message.replace("25544", "25545"));
+        let tles = parse_kvn(&two).unwrap();
+        assert_eq!(tles.len(), 2);
+        assert_eq!(tles[1].sat_number, 25545);
+
+        assert_eq!(parse_kvn("").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn kvn_errors() {
+        match parse_kvn("CCSDS_OMM_VERS = 2.0\nOBJECT_NAME = ISS (ZARYA)\n") {
+            Err(OmmError::MissingField("OBJECT_ID")) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        match parse_kvn("CCSDS_OMM_VERS = 2.0\nMETA_START\n") {
+            Err(OmmError::Malformed(ref line)) if line == "META_START" => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let bad_number = "OBJECT_NAME = X\nOBJECT_ID = 1998-067A\nEPOCH = 2016-07-28\nMEAN_MOTION = fast\n";
+        match parse_kvn(bad_number) {
+            Err(OmmError::InvalidField { keyword: "MEAN_MOTION", ref value }) if value == "fast" => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn xml() {
+        let document = r#"<?xml version="1.0" encoding="UTF-8"?>
+<ndm xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+<!-- <omm> in a comment is not a message -->
+<omm id="CCSDS_OMM_VERS" version="2.0">
+<header><CREATION_DATE/><ORIGINATOR/></header>
+<body>
+<segment>
+<metadata>
+<OBJECT_NAME>ISS (ZARYA)</OBJECT_NAME>
+<OBJECT_ID>1998-067A</OBJECT_ID>
+<CENTER_NAME>EARTH</CENTER_NAME>
+<REF_FRAME>TEME</REF_FRAME>
+<TIME_SYSTEM>UTC</TIME_SYSTEM>
+<MEAN_ELEMENT_THEORY>SGP4</MEAN_ELEMENT_THEORY>
+</metadata>
+<data>
+<meanElements>
+<EPOCH>2016-07-28T14:21:26.330688</EPOCH>
+<MEAN_MOTION units="rev/day">15.54888439</MEAN_MOTION>
+<ECCENTRICITY>.000178</ECCENTRICITY>
+<INCLINATION units="deg">51.6406</INCLINATION>
+<RA_OF_ASC_NODE>211.4156</RA_OF_ASC_NODE>
+<ARG_OF_PERICENTER>85.8307</ARG_OF_PERICENTER>
+<MEAN_ANOMALY>274.3426</MEAN_ANOMALY>
+</meanElements>
+<tleParameters>
+<EPHEMERIS_TYPE>0</EPHEMERIS_TYPE>
+<CLASSIFICATION_TYPE>U</CLASSIFICATION_TYPE>
+<NORAD_CAT_ID>25544</NORAD_CAT_ID>
+<ELEMENT_SET_NO>999</ELEMENT_SET_NO>
+<REV_AT_EPOCH>1143</REV_AT_EPOCH>
+<BSTAR>.11901E-4</BSTAR>
+<MEAN_MOTION_DOT>.812E-5</MEAN_MOTION_DOT>
+<MEAN_MOTION_DDOT>0</MEAN_MOTION_DDOT>
+</tleParameters>
+</data>
+</segment>
+</body>
+</omm>
+<omm id="CCSDS_OMM_VERS" version="2.0">
+<body><segment><metadata>
+<OBJECT_NAME>R&amp;D SAT</OBJECT_NAME>
+<OBJECT_ID>1998-067A</OBJECT_ID>
+</metadata><data><meanElements>
+<EPOCH>2016-07-28T14:21:26.330688</EPOCH>
+<MEAN_MOTION>15.54888439</MEAN_MOTION>
+<ECCENTRICITY>.000178</ECCENTRICITY>
+<INCLINATION>51.6406</INCLINATION>
+<RA_OF_ASC_NODE>211.4156</RA_OF_ASC_NODE>
+<ARG_OF_PERICENTER>85.8307</ARG_OF_PERICENTER>
+<MEAN_ANOMALY>274.3426</MEAN_ANOMALY>
+</meanElements><tleParameters>
+<NORAD_CAT_ID>25545</NORAD_CAT_ID>
+<BSTAR>0</BSTAR>
+<MEAN_MOTION_DOT>0</MEAN_MOTION_DOT>
+</tleParameters></data></segment></body>
+</omm>
+</ndm>"#;
+        let tles = parse_xml(document).unwrap();
+        assert_eq!(tles.len(), 2);
+        assert_iss(&tles[0]);
+        assert_eq!(tles[1].name, "R&D SAT");
+        assert_eq!(tles[1].sat_number, 25545);
+        assert_eq!(tles[1].classification, 'U');
+        assert_eq!(tles[1].tle_version, 0);
+    }
+
+    #[test]
+    fn xml_errors() {
+        match parse_xml("<omm><body><OBJECT_NAME>ISS</OBJECT_NAME></body></omm>") {
+            Err(OmmError::MissingField("OBJECT_ID")) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        match parse_xml("<omm><OBJECT_NAME") {
+            Err(OmmError::Malformed(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json() {
+        let expected = iss();
+
+        // Celestrak writes numbers, Space-Track quotes them
+        let celestrak = r#"{"OBJECT_NAME":"ISS (ZARYA)","OBJECT_ID":"1998-067A","EPOCH":"2016-07-28T14:21:26.330688","MEAN_MOTION":15.54888439,"ECCENTRICITY":0.000178,"INCLINATION":51.6406,"RA_OF_ASC_NODE":211.4156,"ARG_OF_PERICENTER":85.8307,"MEAN_ANOMALY":274.3426,"EPHEMERIS_TYPE":0,"CLASSIFICATION_TYPE":"U","NORAD_CAT_ID":25544,"ELEMENT_SET_NO":999,"REV_AT_EPOCH":1143,"BSTAR":1.1901e-5,"MEAN_MOTION_DOT":8.12e-6,"MEAN_MOTION_DDOT":0}"#;
+        let space_track = r#"[{"CCSDS_OMM_VERS":"2.0","OBJECT_NAME":"ISS (ZARYA)","OBJECT_ID":"1998-067A","EPOCH":"2016-07-28T14:21:26.330688","MEAN_MOTION":"15.54888439","ECCENTRICITY":"0.00017800","INCLINATION":"51.6406","RA_OF_ASC_NODE":"211.4156","ARG_OF_PERICENTER":"85.8307","MEAN_ANOMALY":"274.3426","EPHEMERIS_TYPE":"0","CLASSIFICATION_TYPE":"U","NORAD_CAT_ID":"25544","ELEMENT_SET_NO":"999","REV_AT_EPOCH":"1143","BSTAR":"0.000011901000","MEAN_MOTION_DOT":"0.00000812","MEAN_MOTION_DDOT":"0.0000000000000"}]"#;
+
+        for json in [celestrak, space_track].iter() {
+            let tles = parse_json(json).unwrap();
+            assert_eq!(tles.len(), 1);
+            let tle = &tles[0];
+
+            assert_eq!(tle.name, expected.name);
+            assert_eq!(tle.sat_number, expected.sat_number);
+            assert_eq!(tle.int_designator, expected.int_designator);
+            assert_eq!(tle.epoch_year, expected.epoch_year);
+            assert!((tle.epoch_day - expected.epoch_day).abs() < 1e-10);
+            assert_eq!(tle.tle_version, expected.tle_version);
+            assert_eq!(tle.revolution_number, expected.revolution_number);
+            assert_eq!(tle.first_mean_motion, expected.first_mean_motion);
+            assert!((tle.bstar - expected.bstar).abs() < 1e-15);
+
+            let state = ::propagate(tle, 720.0).unwrap();
+            let expected_state = ::propagate(&expected, 720.0).unwrap();
+            assert!((state.position.X - expected_state.position.X).abs() < 1e-6);
+            assert!((state.position.Y - expected_state.position.Y).abs() < 1e-6);
+            assert!((state.position.Z - expected_state.position.Z).abs() < 1e-6);
+        }
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_errors() {
+        match parse_json(r#"{"OBJECT_NAME":"ISS (ZARYA)"}"#) {
+            Err(OmmError::Json(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let bad_epoch = r#"{"OBJECT_NAME":"X","OBJECT_ID":"","EPOCH":"soon","MEAN_MOTION":15,"ECCENTRICITY":0,"INCLINATION":0,"RA_OF_ASC_NODE":0,"ARG_OF_PERICENTER":0,"MEAN_ANOMALY":0,"NORAD_CAT_ID":1,"BSTAR":0,"MEAN_MOTION_DOT":0}"#;
+        match parse_json(bad_epoch) {
+            Err(OmmError::InvalidEpoch(ref epoch)) if epoch == "soon" => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn csv() {
+        // Celestrak's column order, then another with a quoted name and
+        // the optional columns empty
+        let celestrak = "\
+OBJECT_NAME,OBJECT_ID,EPOCH,MEAN_MOTION,ECCENTRICITY,INCLINATION,RA_OF_ASC_NODE,ARG_OF_PERICENTER,MEAN_ANOMALY,EPHEMERIS_TYPE,CLASSIFICATION_TYPE,NORAD_CAT_ID,ELEMENT_SET_NO,REV_AT_EPOCH,BSTAR,MEAN_MOTION_DOT,MEAN_MOTION_DDOT
+ISS (ZARYA),1998-067A,2016-07-28T14:21:26.330688,15.54888439,.000178,51.6406,211.4156,85.8307,274.3426,0,U,25544,999,1143,.11901E-4,.812E-5,0
+\"ISS (ZARYA), AGAIN\",1998-067A,2016-07-28T14:21:26.330688,15.54888439,.000178,51.6406,211.4156,85.8307,274.3426,,,25545,,,.11901E-4,.812E-5,
+";
+        let tles = parse_csv(celestrak).unwrap();
+        assert_eq!(tles.len(), 2);
+        assert_iss(&tles[0]);
+        assert_eq!(tles[1].name, "ISS (ZARYA), AGAIN");
+        assert_eq!(tles[1].classification, 'U');
+        assert_eq!(tles[1].tle_version, 0);
+
+        let reordered = "\
+NORAD_CAT_ID,OBJECT_NAME,OBJECT_ID,EPOCH,MEAN_MOTION,ECCENTRICITY,INCLINATION,RA_OF_ASC_NODE,ARG_OF_PERICENTER,MEAN_ANOMALY,BSTAR,MEAN_MOTION_DOT,ELEMENT_SET_NO,REV_AT_EPOCH
+25544,ISS (ZARYA),1998-067A,2016-07-28T14:21:26.330688,15.54888439,.000178,51.6406,211.4156,85.8307,274.3426,.11901E-4,.812E-5,999,1143
+";
+        assert_iss(&parse_csv(reordered).unwrap()[0]);
+
+        assert!(parse_csv("").unwrap().is_empty());
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn csv_errors() {
+        match parse_csv("OBJECT_NAME,OBJECT_ID\nISS (ZARYA),1998-067A\n") {
+            Err(OmmError::MissingField("EPOCH")) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        match parse_csv("OBJECT_NAME,OBJECT_ID\nISS (ZARYA)\n") {
+            Err(OmmError::Csv(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    /// Element sets across the fields' ranges: last century's designators,
+    /// negative derivatives and a second derivative, a classified one with
+    /// a name XML must escape
+    fn element_sets() -> Vec<TLE> {
+        let mut tles = vec![iss()];
+        tles.push(::tle::load_from_str(
+            "VANGUARD 1",
+            "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753",
+            "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667",
+        ).unwrap());
+        tles.push(::tle::TLE::builder()
+            .name("A & B <DEB>")
+            .sat_number(99999)
+            .classification('C')
+            .int_designator("24101ZZZ")
+            .epoch(2024, 366.99999999)
+            .first_mean_motion(-0.00012345)
+            .second_mean_motion(0.12345e-7)
+            .bstar(-0.33e-3)
+            .tle_version(12)
+            .inclination(Deg(98.7654))
+            .raan(Deg(0.0001))
+            .eccentricity(0.9876543)
+            .argp(Deg(359.9999))
+            .mean_anomaly(Deg(180.0))
+            .mean_motion(1.00270001)
+            .revolution_number(99999)
+            .build()
+            .unwrap());
+        tles
+    }
+
+    #[test]
+    fn tle_round_trip() {
+        for tle in element_sets() {
+            let omm = Omm::from_tle(&tle);
+            assert_eq!(omm.center_name, "EARTH");
+            assert_eq!(omm.ref_frame, "TEME");
+            assert_eq!(omm.time_system, "UTC");
+            assert_eq!(omm.mean_element_theory, "SGP4");
+
+            let back = omm.to_tle().unwrap();
+            assert_eq!(back.name, tle.name);
+            assert_eq!(back.int_designator, tle.int_designator);
+            assert_eq!(back.classification, tle.classification);
+            assert_eq!(back.to_lines(), tle.to_lines());
+        }
+
+        let vanguard = Omm::from_tle(&element_sets()[1]);
+        assert_eq!(vanguard.object_id, "1958-002B");
+        assert_eq!(vanguard.epoch, "2000-06-27T18:50:19.733568");
+    }
+
+    #[test]
+    fn kvn_and_xml_round_trip() {
+        let tles = element_sets();
+        let messages: Vec<Omm> = tles.iter().map(Omm::from_tle).collect();
+        let created = iss().epoch_julian_date();
+
+        let kvn = to_kvn("TEST", created, &messages);
+        assert!(kvn.starts_with("CCSDS_OMM_VERS = 2.0\nCREATION_DATE = 2016-07-28T14:21:26.33"));
+        assert!(kvn.contains("\nOBJECT_ID = 1998-067A\n"));
+        assert!(kvn.contains("\nMEAN_ELEMENT_THEORY = SGP4\n"));
+
+        let xml = to_xml("TEST", created, &messages);
+        assert!(xml.contains("<OBJECT_NAME>A &amp; B &lt;DEB&gt;</OBJECT_NAME>"));
+        assert!(xml.contains("<REF_FRAME>TEME</REF_FRAME>"));
+
+        for parsed in [parse_kvn(&kvn).unwrap(), parse_xml(&xml).unwrap()].iter() {
+            assert_eq!(parsed.len(), tles.len());
+            for (parsed, tle) in parsed.iter().zip(&tles) {
+                assert_eq!(parsed.name, tle.name);
+                assert_eq!(parsed.to_lines(), tle.to_lines());
+                assert!((parsed.second_mean_motion - tle.second_mean_motion).abs() <= 1e-15 * tle.second_mean_motion.abs());
+            }
+        }
+    }
+
+    #[cfg(feature = "serde_json")]
+    #[test]
+    fn json_round_trip() {
+        for tle in element_sets() {
+            let json = serde_json::to_string(&Omm::from_tle(&tle)).unwrap();
+            assert!(json.contains(r#""MEAN_ELEMENT_THEORY":"SGP4""#));
+            assert_eq!(parse_json(&json).unwrap()[0].to_lines(), tle.to_lines());
+        }
+    }
+
+    #[test]
+    fn unsupported() {
+        let mut omm = Omm::from_tle(&iss());
+        omm.mean_element_theory = String::from("DSST");
+        match omm.to_tle() {
+            Err(OmmError::Unsupported { keyword: "MEAN_ELEMENT_THEORY", ref value }) if value == "DSST" => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let kvn = to_kvn("TEST", 0.0, &[Omm::from_tle(&iss())]).replace("REF_FRAME = TEME", "REF_FRAME = EME2000");
+        match parse_kvn(&kvn) {
+            Err(OmmError::Unsupported { keyword: "REF_FRAME", ref value }) if value == "EME2000" => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        omm.mean_element_theory = String::from("sgp/sgp4");
+        assert!(omm.to_tle().is_ok());
+    }
+}
@@ -0,0 +1,615 @@
+/*!  # Passes
+
+When a satellite is above the horizon of a site on the ground: the rise
+(acquisition of signal), set (loss of signal) and highest point of each
+pass over a window of time.
+
+Elevation is sampled on a coarse grid to find where it crosses the
+horizon, each crossing is refined by bisection, and the peak between them
+by a golden section search. Passes shorter than the grid step can fall
+between samples and be missed; the default one minute step catches any
+low Earth orbit pass worth tracking.
+
+Setting `PassOptions::max_sun_elevation` keeps only visual passes: those
+where, at some point, the satellite is in sunlight while the observer's sky
+is dark enough to see it.
+
+To track a pass once it's found, [`pointing_schedule`](fn.pointing_schedule.html)
+gives the antenna azimuth and elevation through it, which
+[`rotctld_commands`](fn.rotctld_commands.html) writes out for a hamlib
+rotator, and [`received_frequencies`](fn.received_frequencies.html) the
+Doppler shifted downlink.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::fmt::Write;
+
+use coordinates::{StateVector, TEME};
+use observer::{doppler_shift, LookAngles, Observer};
+use sun::{self, Illumination};
+use time;
+use tle::TLE;
+use {PropagationError, Propagator, MINUTES_PER_DAY};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::string::String;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// ## Pass
+///
+/// One pass of a satellite over a site. Times are minutes since the TLE
+/// epoch and angles are in radians.
+///
+/// A pass already under way at the start of the search window has its
+/// `rise` at the start of the window, and one still under way at the end
+/// has its `set` at the end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pass {
+
+    /// Time the satellite rises above the minimum elevation
+    pub rise: f64,
+
+    /// Azimuth at rise (clockwise from north)
+    pub rise_azimuth: f64,
+
+    /// Time of the highest elevation
+    pub max_elevation_time: f64,
+
+    /// Highest elevation above the horizon
+    pub max_elevation: f64,
+
+    /// Time the satellite sets below the minimum elevation
+    pub set: f64,
+
+    /// Azimuth at set (clockwise from north)
+    pub set_azimuth: f64,
+}
+
+impl Pass {
+
+    /// Length of the pass (minutes)
+    pub fn duration(&self) -> f64 {
+        self.set - self.rise
+    }
+}
+
+/// ## Pass Options
+///
+/// How finely to search for passes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PassOptions {
+
+    /// Elevation a satellite has to be above to count as risen (radians,
+    /// default 0)
+    pub min_elevation: f64,
+
+    /// Spacing of the coarse elevation samples (minutes, default 1)
+    pub step: f64,
+
+    /// How closely to locate rise, set and peak times (minutes, default
+    /// 0.0001, or about 6 milliseconds)
+    pub tolerance: f64,
+
+    /// Only report passes that can be seen by eye: the satellite is sunlit
+    /// while the Sun is below this elevation (radians) at the observer.
+    /// Civil twilight ends at -6°. `None` (the default) reports every
+    /// pass.
+    pub max_sun_elevation: Option<f64>,
+}
+
+impl Default for PassOptions {
+    fn default() -> PassOptions {
+        PassOptions {
+            min_elevation: 0.0,
+            step: 1.0,
+            tolerance: 1.0e-4,
+            max_sun_elevation: None,
+        }
+    }
+}
+
+/// How often to check a pass for visibility (minutes)
+const VISIBILITY_STEP: f64 = 1.0 / 6.0;
+
+/// Look angles from an observer to a propagated satellite
+struct Sky<'a> {
+    propagator: &'a Propagator,
+    observer: &'a Observer,
+    options: &'a PassOptions,
+}
+
+impl<'a> Sky<'a> {
+
+    fn look(&self, time: f64) -> Result<LookAngles, PropagationError> {
+        let state = self.propagator.propagate(time)?;
+        // UTC stands in for UT1, well inside the tolerance on pass times
+        let gmst = time::gmst(self.propagator.epoch() + time / MINUTES_PER_DAY);
+        Ok(self.observer.look_angles(&state, gmst))
+    }
+
+    /// Elevation above the minimum
+    fn elevation(&self, time: f64) -> Result<f64, PropagationError> {
+        Ok(self.look(time)?.el - self.options.min_elevation)
+    }
+
+    /// Where the elevation crosses the minimum between `below` and `above`
+    fn crossing(&self, mut below: f64, mut above: f64) -> Result<f64, PropagationError> {
+        while (above - below).abs() > self.options.tolerance {
+            let middle = 0.5 * (below + above);
+            if self.elevation(middle)? < 0.0 {
+                below = middle;
+            } else {
+                above = middle;
+            }
+        }
+        Ok(0.5 * (below + above))
+    }
+
+    /// Time of the highest elevation between `start` and `end`
+    fn peak(&self, mut start: f64, mut end: f64) -> Result<f64, PropagationError> {
+        let ratio = 0.5 * (5.0_f64.sqrt() - 1.0);
+        let mut a = end - ratio * (end - start);
+        let mut b = start + ratio * (end - start);
+        let mut elevation_a = self.elevation(a)?;
+        let mut elevation_b = self.elevation(b)?;
+
+        while end - start > self.options.tolerance {
+            if elevation_a > elevation_b {
+                end = b;
+                b = a;
+                elevation_b = elevation_a;
+                a = end - ratio * (end - start);
+                elevation_a = self.elevation(a)?;
+            } else {
+                start = a;
+                a = b;
+                elevation_a = elevation_b;
+                b = start + ratio * (end - start);
+                elevation_b = self.elevation(b)?;
+            }
+        }
+        Ok(0.5 * (start + end))
+    }
+
+    /// Complete a pass from its rise and set and the highest coarse sample
+    fn pass(&self, rise: f64, set: f64, highest: f64) -> Result<Pass, PropagationError> {
+        let step = self.options.step;
+        let max_elevation_time = self.peak((highest - step).max(rise), (highest + step).min(set))?;
+        Ok(Pass {
+            rise,
+            rise_azimuth: self.look(rise)?.az,
+            max_elevation_time,
+            max_elevation: self.look(max_elevation_time)?.el,
+            set,
+            set_azimuth: self.look(set)?.az,
+        })
+    }
+
+    /// Whether the satellite is sunlit against a dark enough sky at some
+    /// point in a pass
+    fn is_visible(&self, pass: &Pass, max_sun_elevation: f64) -> Result<bool, PropagationError> {
+        for (time, state) in self.propagator.sample(pass.rise, pass.set, VISIBILITY_STEP) {
+            let julian_date = self.propagator.epoch() + time / MINUTES_PER_DAY;
+            let sun = StateVector {
+                epoch: julian_date,
+                position: sun::position(julian_date),
+                velocity: TEME::new(0.0, 0.0, 0.0),
+            };
+            let sun_elevation = self.observer.look_angles(&sun, time::gmst(julian_date)).el;
+            if sun_elevation < max_sun_elevation && sun::illumination(&state?, &sun.position) == Illumination::Sunlit {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Keep a pass, unless only visual passes are wanted and it isn't one
+    fn keep(&self, passes: &mut Vec<Pass>, pass: Pass) -> Result<(), PropagationError> {
+        let visible = match self.options.max_sun_elevation {
+            Some(max_sun_elevation) => self.is_visible(&pass, max_sun_elevation)?,
+            None => true,
+        };
+        if visible {
+            passes.push(pass);
+        }
+        Ok(())
+    }
+}
+
+/// ## Find Passes
+///
+/// Every pass of a satellite over `observer` between `start` and `end`
+/// (minutes since the TLE epoch). The propagator's epoch dates them, to know
+/// how far the Earth has turned.
+///
+/// ### Errors
+///
+/// Stops at the first time the orbit can't be propagated.
+///
+/// ### Panics
+///
+/// If `options.step` or `options.tolerance` isn't positive.
+pub fn find_passes(propagator: &Propagator, observer: &Observer, start: f64, end: f64, options: &PassOptions) -> Result<Vec<Pass>, PropagationError> {
+    assert!(options.step > 0.0, "pass search step must be positive");
+    assert!(options.tolerance > 0.0, "pass search tolerance must be positive");
+
+    let sky = Sky { propagator, observer, options };
+    let mut passes = Vec::new();
+    if end < start {
+        return Ok(passes);
+    }
+
+    // Sample on the same grid as `Propagator::sample`, plus the end itself
+    let count = ((end - start) / options.step + 1.0e-9).floor() as usize + 1;
+    let mut times: Vec<f64> = (0..count).map(|index| start + options.step * index as f64).collect();
+    if times.last().is_some_and(|&last| last < end) {
+        times.push(end);
+    }
+
+    // The rise of the pass under way and its highest sample so far
+    let mut current: Option<(f64, f64, f64)> = None;
+    let mut previous: Option<f64> = None;
+
+    for time in times {
+        let elevation = sky.elevation(time)?;
+
+        current = match (current, previous) {
+            (None, _) if elevation < 0.0 => None,
+            (None, None) => Some((start, time, elevation)),
+            (None, Some(before)) => Some((sky.crossing(before, time)?, time, elevation)),
+            (Some((rise, highest, _)), Some(before)) if elevation < 0.0 => {
+                sky.keep(&mut passes, sky.pass(rise, sky.crossing(time, before)?, highest)?)?;
+                None
+            }
+            (Some((rise, highest, peak)), _) if elevation <= peak => Some((rise, highest, peak)),
+            (Some((rise, _, _)), _) => Some((rise, time, elevation)),
+        };
+
+        previous = Some(time);
+    }
+
+    if let Some((rise, highest, _)) = current {
+        sky.keep(&mut passes, sky.pass(rise, end, highest)?)?;
+    }
+
+    Ok(passes)
+}
+
+/// ## Passes
+///
+/// Every pass of a TLE over `observer` between `start` and `end` (minutes
+/// since the TLE epoch), with the default `PassOptions`.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::coordinates::Geodetic;
+/// use sgp4::observer::Observer;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let portland = Observer::new(Geodetic {
+///     lat: f64::to_radians(45.52),
+///     lon: f64::to_radians(-122.68),
+///     alt: 0.05,
+/// });
+///
+/// // The next day
+/// for pass in sgp4::passes::passes(&tle, &portland, 0.0, 1440.0).unwrap() {
+///     println!("rise {:.1} set {:.1} max elevation {:.1}°",
+///              pass.rise, pass.set, pass.max_elevation.to_degrees());
+/// }
+/// ```
+pub fn passes(tle: &TLE, observer: &Observer, start: f64, end: f64) -> Result<Vec<Pass>, PropagationError> {
+    let propagator = Propagator::new(tle)?;
+    find_passes(&propagator, observer, start, end, &PassOptions::default())
+}
+
+/// ## Received Frequencies
+///
+/// The frequency (Hz) a signal sent at `frequency` (Hz) arrives at during a
+/// pass, every `step` minutes from rise to set, as `(time, frequency)`
+/// pairs. Times are minutes since the TLE epoch, and `epoch` is the Julian
+/// date of the TLE epoch, as for [`find_passes`](fn.find_passes.html).
+///
+/// ### Panics
+///
+/// If `step` isn't positive.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::coordinates::Geodetic;
+/// use sgp4::observer::Observer;
+/// use sgp4::passes::{find_passes, received_frequencies, PassOptions};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+/// let propagator = sgp4::Propagator::new(&tle).unwrap();
+/// let observer = Observer::new(Geodetic {
+///     lat: f64::to_radians(45.52),
+///     lon: f64::to_radians(-122.68),
+///     alt: 0.05,
+/// });
+///
+/// let passes = find_passes(&propagator, &observer, 0.0, 1440.0, &PassOptions::default()).unwrap();
+///
+/// // Tune the 145.8 MHz downlink every ten seconds through the first pass
+/// for (time, frequency) in received_frequencies(&propagator, &observer, &passes[0], 145.8e6, 1.0 / 6.0).unwrap() {
+///     println!("{:.3} {:.0}", time, frequency);
+/// }
+/// ```
+pub fn received_frequencies(propagator: &Propagator, observer: &Observer, pass: &Pass, frequency: f64, step: f64) -> Result<Vec<(f64, f64)>, PropagationError> {
+    let options = PassOptions::default();
+    let sky = Sky { propagator, observer, options: &options };
+
+    propagator.sample(pass.rise, pass.set, step)
+        .map(|(time, _)| {
+            let look = sky.look(time)?;
+            Ok((time, frequency + doppler_shift(look.range_rate, frequency)))
+        })
+        .collect()
+}
+
+/// ## Pointing
+///
+/// Where to point an antenna at one time. The time is minutes since the TLE
+/// epoch and angles are in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Pointing {
+
+    /// Time of the pointing
+    pub time: f64,
+
+    /// Azimuth (clockwise from north, 0 to $2\pi$)
+    pub az: f64,
+
+    /// Elevation above the horizon
+    pub el: f64,
+}
+
+/// ## Pointing Schedule
+///
+/// Azimuth and elevation every `step` minutes through a pass, from rise to
+/// set, for driving an antenna rotator. The set itself is always the last
+/// pointing, so the rotator follows the satellite all the way down. Times
+/// and `epoch` are as for [`find_passes`](fn.find_passes.html).
+///
+/// ### Panics
+///
+/// If `step` isn't positive.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::coordinates::Geodetic;
+/// use sgp4::observer::Observer;
+/// use sgp4::passes::{find_passes, pointing_schedule, rotctld_commands, PassOptions};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+/// let propagator = sgp4::Propagator::new(&tle).unwrap();
+/// let observer = Observer::new(Geodetic {
+///     lat: f64::to_radians(45.52),
+///     lon: f64::to_radians(-122.68),
+///     alt: 0.05,
+/// });
+///
+/// let passes = find_passes(&propagator, &observer, 0.0, 1440.0, &PassOptions::default()).unwrap();
+///
+/// // A new position every second through the first pass
+/// let schedule = pointing_schedule(&propagator, &observer, &passes[0], 1.0 / 60.0).unwrap();
+/// print!("{}", rotctld_commands(&schedule));
+/// ```
+pub fn pointing_schedule(propagator: &Propagator, observer: &Observer, pass: &Pass, step: f64) -> Result<Vec<Pointing>, PropagationError> {
+    let options = PassOptions::default();
+    let sky = Sky { propagator, observer, options: &options };
+    let pointing = |time: f64| -> Result<Pointing, PropagationError> {
+        let look = sky.look(time)?;
+        Ok(Pointing { time, az: look.az, el: look.el })
+    };
+
+    let mut schedule = propagator.sample(pass.rise, pass.set, step)
+        .map(|(time, _)| pointing(time))
+        .collect::<Result<Vec<_>, _>>()?;
+    if schedule.last().is_none_or(|last| last.time < pass.set) {
+        schedule.push(pointing(pass.set)?);
+    }
+    Ok(schedule)
+}
+
+/// ## rotctld Commands
+///
+/// A pointing schedule as hamlib `rotctld` set position commands, one
+/// `P <azimuth> <elevation>` line (degrees) per pointing, ready to send to
+/// the daemon in turn at each pointing's time. Elevations just below the
+/// horizon at rise and set are raised to zero, which rotators take.
+pub fn rotctld_commands(schedule: &[Pointing]) -> String {
+    let mut commands = String::new();
+    for pointing in schedule {
+        let _ = writeln!(commands, "P {:.2} {:.2}", pointing.az.to_degrees(), pointing.el.to_degrees().max(0.0));
+    }
+    commands
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coordinates::Geodetic;
+    use test_support::iss;
+
+    fn portland() -> Observer {
+        Observer::new(Geodetic {
+            lat: f64::to_radians(45.52),
+            lon: f64::to_radians(-122.68),
+            alt: 0.05,
+        })
+    }
+
+    #[test]
+    fn iss_over_portland() {
+        let tle = iss();
+        let observer = portland();
+        let propagator = Propagator::new(&tle).unwrap();
+        let options = PassOptions::default();
+        let sky = Sky { propagator: &propagator, observer: &observer, options: &options };
+
+        let passes = passes(&tle, &observer, 0.0, 1440.0).unwrap();
+        assert!(passes.len() >= 3);
+
+        for pass in &passes {
+            assert!(pass.rise < pass.max_elevation_time && pass.max_elevation_time < pass.set);
+            assert!(pass.duration() > 1.0 && pass.duration() < 15.0);
+            assert!(pass.max_elevation > 0.0);
+
+            // On the horizon at rise and set, and above it in between
+            assert!(sky.look(pass.rise).unwrap().el.abs() < 1e-4);
+            assert!(sky.look(pass.set).unwrap().el.abs() < 1e-4);
+
+            // Nothing higher on a fine scan
+            let mut time = pass.rise;
+            while time < pass.set {
+                assert!(sky.look(time).unwrap().el <= pass.max_elevation + 1e-9);
+                time += 0.01;
+            }
+        }
+
+        // Passes are in order and don't overlap
+        for pair in passes.windows(2) {
+            assert!(pair[0].set < pair[1].rise);
+        }
+    }
+
+    #[test]
+    fn doppler_over_a_pass() {
+        let tle = iss();
+        let observer = portland();
+        let propagator = Propagator::new(&tle).unwrap();
+
+        let pass = passes(&tle, &observer, 0.0, 1440.0).unwrap()[0];
+        let frequency = 145.8e6;
+        let schedule = received_frequencies(&propagator, &observer, &pass, frequency, 0.1).unwrap();
+        assert_eq!(schedule[0].0, pass.rise);
+
+        // High while approaching, low while receding, falling throughout
+        let (first, last) = (schedule[0].1, schedule[schedule.len() - 1].1);
+        assert!(first > frequency + 1000.0 && first < frequency + 3600.0);
+        assert!(last < frequency - 1000.0 && last > frequency - 3600.0);
+        assert!(schedule.windows(2).all(|pair| pair[1].1 < pair[0].1));
+    }
+
+    #[test]
+    fn rotator() {
+        let tle = iss();
+        let observer = portland();
+        let propagator = Propagator::new(&tle).unwrap();
+
+        let pass = passes(&tle, &observer, 0.0, 1440.0).unwrap()[0];
+        let schedule = pointing_schedule(&propagator, &observer, &pass, 0.25).unwrap();
+        assert_eq!(schedule[0].time, pass.rise);
+        assert_eq!(schedule[schedule.len() - 1].time, pass.set);
+        assert!(schedule.windows(2).all(|pair| pair[1].time > pair[0].time && pair[1].time - pair[0].time <= 0.25 + 1e-9));
+        assert!((schedule[0].az - pass.rise_azimuth).abs() < 1e-9);
+        assert!(schedule.iter().all(|pointing| pointing.el > -1e-4 && pointing.el <= pass.max_elevation + 1e-9));
+
+        let commands = rotctld_commands(&schedule);
+        let lines: Vec<&str> = commands.lines().collect();
+        assert_eq!(lines.len(), schedule.len());
+        assert!(lines.iter().all(|line| line.starts_with("P ") && line.split(' ').count() == 3));
+        assert_eq!(rotctld_commands(&[Pointing { time: 0.0, az: f64::to_radians(123.456), el: -1e-6 }]), "P 123.46 0.00\n");
+    }
+
+    #[test]
+    fn visual_passes() {
+        let tle = iss();
+        let observer = portland();
+        let propagator = Propagator::new(&tle).unwrap();
+        let epoch = tle.epoch_julian_date();
+
+        let all = find_passes(&propagator, &observer, 0.0, 3.0 * 1440.0, &PassOptions::default()).unwrap();
+        let options = PassOptions { max_sun_elevation: Some(f64::to_radians(-6.0)), ..PassOptions::default() };
+        let visual = find_passes(&propagator, &observer, 0.0, 3.0 * 1440.0, &options).unwrap();
+        assert!(!visual.is_empty() && visual.len() < all.len());
+
+        // Every visual pass is one of the passes, seen from a dark site
+        for pass in &visual {
+            assert!(all.contains(pass));
+            let julian_date = epoch + pass.max_elevation_time / MINUTES_PER_DAY;
+            let sun = StateVector {
+                epoch: julian_date,
+                position: sun::position(julian_date),
+                velocity: TEME::new(0.0, 0.0, 0.0),
+            };
+            let gmst = time::gmst(julian_date);
+            assert!(observer.look_angles(&sun, gmst).el < 0.0);
+        }
+
+        // The Sun is never that far below the horizon
+        let never = PassOptions { max_sun_elevation: Some(-f64::to_radians(90.0)), ..PassOptions::default() };
+        assert!(find_passes(&propagator, &observer, 0.0, 1440.0, &never).unwrap().is_empty());
+    }
+
+    #[test]
+    fn window_edges() {
+        let tle = iss();
+        let observer = portland();
+        let propagator = Propagator::new(&tle).unwrap();
+        let options = PassOptions::default();
+
+        let all = passes(&tle, &observer, 0.0, 1440.0).unwrap();
+        let pass = all[0];
+        let middle = pass.max_elevation_time;
+
+        // Cut through the middle of a pass: it's clipped at each side
+        let before = find_passes(&propagator, &observer, pass.rise - 10.0, middle, &options).unwrap();
+        assert_eq!(before.len(), 1);
+        assert!((before[0].rise - pass.rise).abs() < 1e-3);
+        assert_eq!(before[0].set, middle);
+
+        let after = find_passes(&propagator, &observer, middle, pass.set + 10.0, &options).unwrap();
+        assert_eq!(after.len(), 1);
+        assert_eq!(after[0].rise, middle);
+        assert!((after[0].set - pass.set).abs() < 1e-3);
+
+        // A higher mask makes for shorter passes
+        let masked = PassOptions { min_elevation: f64::to_radians(10.0), ..PassOptions::default() };
+        for high in find_passes(&propagator, &observer, 0.0, 1440.0, &masked).unwrap() {
+            let pass = all.iter().find(|pass| pass.rise < high.rise && high.set < pass.set).unwrap();
+            assert!((pass.max_elevation - high.max_elevation).abs() < 1e-6);
+        }
+
+        assert!(find_passes(&propagator, &observer, 10.0, 0.0, &options).unwrap().is_empty());
+    }
+}
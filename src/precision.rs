@@ -0,0 +1,907 @@
+/*!  # Precision
+
+The propagation kernel, generic over the floating point type.
+
+A [`Kernel<f32>`](struct.Kernel.html) keeps the propagator's constants and
+does all of its arithmetic in single precision, for embedded targets
+without a double precision FPU and for bulk work where memory bandwidth
+matters more than the last digits. Initialization still happens in double
+precision in [`Propagator::new`](../struct.Propagator.html#method.new); only
+the constants are rounded.
+
+The cost is accuracy. `f32` carries about seven significant digits, so a
+position of 7000 km is only good to about a meter before any arithmetic,
+and the mean anomaly grows by some 100 radians a day for a low orbit, so the
+along track error grows with time since epoch. For the ISS it's about a
+meter at epoch, ten meters after a day and half a kilometer after a week
+(see the tests). That is
+still well inside the accuracy of SGP4 itself, which drifts by kilometers a
+day, but `f32` results shouldn't be compared against `f64` ones or used for
+conjunction screening.
+
+This is the only copy of the algorithm: [`Propagator::propagate`](../struct.Propagator.html#method.propagate)
+runs a `Kernel<f64>`, and the [`batch`](../batch/index.html) propagator
+runs the same steps lane by lane. Times are always minutes in `f64`, and
+rounded to `F` inside. The deep space terms are worked out in double
+precision whatever `F` is, and only their results rounded.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Rem, Sub};
+
+use deep_space::{self, DeepSpace};
+use verbose::Intermediates;
+use {OpsMode, PropagationError, Propagator, ResonanceState, KEPLER_ITERATIONS, KEPLER_TOLERANCE, RE, RETROGRADE_EQUATORIAL};
+
+#[cfg(not(any(feature = "std", test)))]
+use libm;
+
+
+/// ## Real
+///
+/// The floating point operations the kernel needs, implemented for `f32`
+/// and `f64`.
+pub trait Real: Copy + PartialOrd
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+    + Neg<Output = Self> + Rem<Output = Self> + AddAssign
+{
+
+    /// Nearest value of this type
+    fn from_f64(value: f64) -> Self;
+
+    /// Widen to `f64`
+    fn to_f64(self) -> f64;
+
+    /// Absolute value
+    fn abs(self) -> Self;
+
+    /// Larger of two values
+    fn max(self, other: Self) -> Self;
+
+    /// Square root
+    fn sqrt(self) -> Self;
+
+    /// `self` raised to an integer power
+    fn powi(self, n: i32) -> Self;
+
+    /// `self` raised to a floating point power
+    fn powf(self, n: Self) -> Self;
+
+    /// Sine (radians)
+    fn sin(self) -> Self;
+
+    /// Cosine (radians)
+    fn cos(self) -> Self;
+
+    /// Four quadrant arctangent of `self` (y) and `other` (x)
+    fn atan2(self, other: Self) -> Self;
+}
+
+macro_rules! real {
+    ($float:ident, $sqrt:ident, $pow:ident, $sin:ident, $cos:ident, $atan2:ident) => {
+        impl Real for $float {
+
+            #[allow(trivial_numeric_casts)]
+            fn from_f64(value: f64) -> $float {
+                value as $float
+            }
+
+            fn to_f64(self) -> f64 {
+                f64::from(self)
+            }
+
+            fn abs(self) -> $float {
+                if self < 0.0 { -self } else { self }
+            }
+
+            fn max(self, other: $float) -> $float {
+                if self < other { other } else { self }
+            }
+
+            #[cfg(any(feature = "std", test))]
+            fn sqrt(self) -> $float {
+                $float::sqrt(self)
+            }
+
+            #[cfg(not(any(feature = "std", test)))]
+            fn sqrt(self) -> $float {
+                libm::$sqrt(self)
+            }
+
+            #[cfg(any(feature = "std", test))]
+            fn powi(self, n: i32) -> $float {
+                $float::powi(self, n)
+            }
+
+            #[cfg(not(any(feature = "std", test)))]
+            fn powi(self, n: i32) -> $float {
+                libm::$pow(self, n as $float)
+            }
+
+            #[cfg(any(feature = "std", test))]
+            fn powf(self, n: $float) -> $float {
+                $float::powf(self, n)
+            }
+
+            #[cfg(not(any(feature = "std", test)))]
+            fn powf(self, n: $float) -> $float {
+                libm::$pow(self, n)
+            }
+
+            #[cfg(any(feature = "std", test))]
+            fn sin(self) -> $float {
+                $float::sin(self)
+            }
+
+            #[cfg(not(any(feature = "std", test)))]
+            fn sin(self) -> $float {
+                libm::$sin(self)
+            }
+
+            #[cfg(any(feature = "std", test))]
+            fn cos(self) -> $float {
+                $float::cos(self)
+            }
+
+            #[cfg(not(any(feature = "std", test)))]
+            fn cos(self) -> $float {
+                libm::$cos(self)
+            }
+
+            #[cfg(any(feature = "std", test))]
+            fn atan2(self, other: $float) -> $float {
+                $float::atan2(self, other)
+            }
+
+            #[cfg(not(any(feature = "std", test)))]
+            fn atan2(self, other: $float) -> $float {
+                libm::$atan2(self, other)
+            }
+        }
+    };
+}
+
+real!(f32, sqrtf, powf, sinf, cosf, atan2f);
+real!(f64, sqrt, pow, sin, cos, atan2);
+
+/// ## State
+///
+/// TEME position (kilometers) and velocity (kilometers/second), in the
+/// kernel's precision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct State<F> {
+
+    /// X, Y and Z position (kilometers)
+    pub position: [F; 3],
+
+    /// X, Y and Z velocity (kilometers/second)
+    pub velocity: [F; 3],
+}
+
+/// ## Kernel
+///
+/// A propagator's constants in precision `F`.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::precision::Kernel;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+/// let propagator = sgp4::Propagator::new(&tle).unwrap();
+///
+/// let kernel: Kernel<f32> = Kernel::new(&propagator);
+/// let state = kernel.propagate(90.0).unwrap();
+///
+/// let reference = propagator.propagate(90.0).unwrap();
+/// assert!((f64::from(state.position[0]) - reference.position.X).abs() < 0.1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Kernel<F> {
+    n0_dp: F,
+    ao_dp: F,
+    e0: F,
+    i0: F,
+    wo: F,
+    Omega0: F,
+    M0: F,
+    Bstar: F,
+    sin_io: F,
+    O: F,
+    O2: F,
+    one_plus_O: F,
+    Bo2: F,
+    n: F,
+    qs4: F,
+    xi4: F,
+    simple: bool,
+    circular: bool,
+    C1: F,
+    C3: F,
+    C4: F,
+    C5: F,
+    D2: F,
+    D3: F,
+    D4: F,
+    M_dot: F,
+    omega_dot: F,
+    Omega_dot1: F,
+    Omega_dot: F,
+    ke: F,
+    k2: F,
+    A30: F,
+    XKMPER: F,
+    max_age: Option<f64>,
+    strict: bool,
+    ops_mode: OpsMode,
+    deep_space: Option<DeepSpace>,
+}
+
+/// The secularly updated mean elements at one time
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Secular<F> {
+    /// Ω (radians)
+    pub(crate) Omega: F,
+    /// ω (radians)
+    pub(crate) omega: F,
+    /// a (Earth radii)
+    pub(crate) a: F,
+    /// e
+    pub(crate) e: F,
+    /// i (radians)
+    pub(crate) i: F,
+    /// IL (radians)
+    pub(crate) IL: F,
+    /// β = √(1 - e²)
+    pub(crate) B: F,
+    /// n (radians/minute)
+    pub(crate) n: F,
+    /// M (radians)
+    pub(crate) M: F,
+}
+
+impl<F: Real> Secular<F> {
+
+    /// The same elements in precision `G`
+    fn convert<G: Real>(self) -> Secular<G> {
+        let k = |value: F| G::from_f64(value.to_f64());
+        Secular {
+            Omega: k(self.Omega),
+            omega: k(self.omega),
+            a: k(self.a),
+            e: k(self.e),
+            i: k(self.i),
+            IL: k(self.IL),
+            B: k(self.B),
+            n: k(self.n),
+            M: k(self.M),
+        }
+    }
+}
+
+/// The mean elements with the long period periodics, ready for Kepler's
+/// equation
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LongPeriod<F> {
+    /// Ω (radians)
+    Omega: F,
+    /// a (Earth radii)
+    a: F,
+    /// n (radians/minute)
+    n: F,
+    /// i (radians), its sine, and θ = cos i and θ²
+    i: F,
+    sin_i: F,
+    O: F,
+    O2: F,
+    /// a_xN and a_yN
+    axN: F,
+    ayN: F,
+    /// U = IL_T - Ω (radians)
+    U: F,
+}
+
+impl<F: Real> Kernel<F> {
+
+    /// Round an initialized propagator's constants to precision `F`
+    pub fn new(propagator: &Propagator) -> Kernel<F> {
+        let p = propagator;
+        Kernel {
+            n0_dp: F::from_f64(p.n0_dp),
+            ao_dp: F::from_f64(p.ao_dp),
+            e0: F::from_f64(p.e0),
+            i0: F::from_f64(p.i0),
+            wo: F::from_f64(p.wo),
+            Omega0: F::from_f64(p.Omega0),
+            M0: F::from_f64(p.M0),
+            Bstar: F::from_f64(p.Bstar),
+            sin_io: F::from_f64(p.sin_io),
+            O: F::from_f64(p.O),
+            O2: F::from_f64(p.O2),
+            one_plus_O: F::from_f64(p.one_plus_O),
+            Bo2: F::from_f64(p.Bo2),
+            n: F::from_f64(p.n),
+            qs4: F::from_f64(p.qs4),
+            xi4: F::from_f64(p.xi4),
+            simple: p.simple,
+            circular: p.circular,
+            C1: F::from_f64(p.C1),
+            C3: F::from_f64(p.C3),
+            C4: F::from_f64(p.C4),
+            C5: F::from_f64(p.C5),
+            D2: F::from_f64(p.D2),
+            D3: F::from_f64(p.D3),
+            D4: F::from_f64(p.D4),
+            M_dot: F::from_f64(p.M_dot),
+            omega_dot: F::from_f64(p.omega_dot),
+            Omega_dot1: F::from_f64(p.Omega_dot1),
+            Omega_dot: F::from_f64(p.Omega_dot),
+            ke: F::from_f64(p.gravity.ke),
+            k2: F::from_f64(p.gravity.k2()),
+            A30: F::from_f64(p.gravity.A30()),
+            XKMPER: F::from_f64(p.gravity.radius),
+            max_age: p.max_age,
+            strict: p.strict,
+            ops_mode: p.ops_mode,
+            deep_space: p.deep_space,
+        }
+    }
+
+    /// Propagate to `time` minutes since the TLE epoch. The same algorithm,
+    /// and the same errors, as `Propagator::propagate`.
+    pub fn propagate(&self, time: f64) -> Result<State<F>, PropagationError> {
+        self.propagate_recording(time, &mut ResonanceState::default(), &mut Intermediates::default())
+    }
+
+    /// Propagate, carrying on the resonance integration from `resonance`
+    /// and keeping the intermediate quantities in `record` as they're
+    /// computed
+    pub(crate) fn propagate_recording(&self, time: f64, resonance: &mut ResonanceState, record: &mut Intermediates) -> Result<State<F>, PropagationError> {
+        let secular = self.secular(time, resonance, record)?;
+        let long_period = self.long_period(time, secular, record)?;
+        let (Ew, converged) = kepler(&long_period, record);
+        if !converged {
+            #[cfg(feature = "log")]
+            warn!("Kepler's equation did not converge at t={} min", time);
+        }
+        self.short_period(&long_period, Ew, record)
+    }
+
+    /// The mean elements at `time` minutes since the epoch, with the secular
+    /// effects of drag and gravity, and in deep space of the Sun and Moon
+    pub(crate) fn secular(&self, time: f64, resonance: &mut ResonanceState, record: &mut Intermediates) -> Result<Secular<F>, PropagationError> {
+        let Kernel {
+            n0_dp,
+            ao_dp,
+            e0,
+            i0,
+            wo,
+            Omega0,
+            M0,
+            Bstar,
+            Bo2,
+            n,
+            qs4,
+            xi4,
+            simple,
+            circular,
+            C1,
+            C3,
+            C4,
+            C5,
+            D2,
+            D3,
+            D4,
+            M_dot,
+            omega_dot,
+            Omega_dot1,
+            Omega_dot,
+            ke,
+            max_age,
+            strict,
+            ..
+        } = *self;
+
+        if strict && max_age.is_some_and(|max_age| time.abs() > max_age) {
+            return Err(PropagationError::TooOld);
+        }
+
+        let k = F::from_f64;
+        let one = k(1.0);
+        let t = k(time);
+
+
+        // ************************************************************************
+        // Section 5.
+        // Secular effects of atmospheric drag and gravitation
+
+        // Secular effect of gravity:
+        // M_DF = Mₒ + Ṁ(t - tₒ)
+        // ω_DF = ωₒ + ω̇(t - tₒ)
+        // Ω_DF = Ωₒ + Ω̇(t - tₒ)
+        let M_DF = M0 + M_dot * t;
+        let omega_DF = wo + omega_dot * t;
+        let Omega_DF = Omega0 + Omega_dot * t;
+
+        // Secular effect of drag on the ascending node:
+        //              21 nₒ"k₂θ
+        // Ω = Ω_DF - -- --------- C₁(t - tₒ)²
+        //              2  aₒ"²βₒ²
+        let Omega = Omega_DF + k(3.5) * Bo2 * Omega_dot1 * C1 * t.powi(2);
+
+        // The drag terms are truncated for low perigee satellites
+        let (M_p, omega, tempa, tempe, templ) = if simple {
+            (
+                M_DF,
+                omega_DF,
+                one - C1 * t,
+                Bstar * C4 * t,
+                k(1.5) * C1 * t.powi(2),
+            )
+        }
+        else {
+            // δω = B*C₃(cos ωₒ)(t - tₒ)
+            let d_omega = Bstar * C3 * wo.cos() * t;
+
+            //        2             aE
+            // δM = - -(qₒ - s)⁴B*ξ⁴-- [(1 + η cos M_DF)³ - (1 + η cos Mₒ)³]
+            //        3             eₒη
+            // (zero for a near circular orbit)
+            let d_M = if circular {
+                k(0.0)
+            } else {
+                -k(2.0/3.0) * qs4 * Bstar * xi4 * (k(RE) / (e0 * n)) * ((one + n * M_DF.cos()).powi(3) - (one + n * M0.cos()).powi(3))
+            };
+
+            // Mp = M_DF + δω + δM
+            let M_p = M_DF + d_omega + d_M;
+
+            // ω = ω_DF - δω - δM
+            let omega = omega_DF - d_omega - d_M;
+
+            // 1 - C₁(t - tₒ) - D₂(t - tₒ)² - D₃(t - tₒ)³ - D₄(t - tₒ)⁴
+            let tempa = one - C1 * t - D2 * t.powi(2) - D3 * t.powi(3) - D4 * t.powi(4);
+
+            // B*C₄(t - tₒ) + B*C₅(sin Mp - sin Mₒ)
+            let tempe = Bstar * C4 * t + Bstar * C5 * (M_p.sin() - M0.sin());
+
+            // 3                                     1                                   1
+            // -C₁(t - tₒ)² + (D₂ + 2C₁²)(t - tₒ)³ + -(3D₃ + 12C₁D₂ + 10C₁³)(t - tₒ)⁴ + -(3D₄ + 12C₁D₃ + 6D₂² + 30C₁²D₂ + 15C₁⁴)(t - tₒ)⁵
+            // 2                                     4                                   5
+            let templ = k(1.5) * C1 * t.powi(2)
+                + (D2 + k(2.0) * C1.powi(2)) * t.powi(3)
+                + k(0.25) * (k(3.0) * D3 + k(12.0) * C1 * D2 + k(10.0) * C1.powi(3)) * t.powi(4)
+                + k(0.2) * (k(3.0) * D4 + k(12.0) * C1 * D3 + k(6.0) * D2.powi(2) + k(30.0) * C1.powi(2) * D2 + k(15.0) * C1.powi(4)) * t.powi(5);
+
+            (M_p, omega, tempa, tempe, templ)
+        };
+
+        // Secular effects of the Sun and Moon, and resonance, in deep space.
+        // Resonance changes the mean motion, and with it the semi-major axis
+        // drag acts on. These are always worked out in double precision.
+        let (e0, i, Omega, omega, M_p, ao_dp) = match self.deep_space {
+            Some(ref deep_space) => {
+                let mut mean = deep_space::Mean {
+                    e: e0.to_f64(),
+                    i: i0.to_f64(),
+                    omega: omega.to_f64(),
+                    Omega: Omega.to_f64(),
+                    M: M_p.to_f64(),
+                };
+                let n_ = deep_space.secular(time, &mut mean, resonance);
+                if n_ <= 0.0 {
+                    return Err(PropagationError::Diverged);
+                }
+                (k(mean.e), k(mean.i), k(mean.Omega), k(mean.omega), k(mean.M), ao_dp * (n0_dp / k(n_)).powf(k(2.0/3.0)))
+            }
+            None => (e0, i0, Omega, omega, M_p, ao_dp),
+        };
+
+        // a = aₒ"[1 - C₁(t - tₒ) - D₂(t - tₒ)² - D₃(t - tₒ)³ - D₄(t - tₒ)⁴]²
+        let a = ao_dp * tempa.powi(2);
+        record.M_DF = M_DF.to_f64();
+        record.omega_DF = omega_DF.to_f64();
+        record.Omega_DF = Omega_DF.to_f64();
+        record.Omega = Omega.to_f64();
+        record.M_p = M_p.to_f64();
+        record.omega = omega.to_f64();
+        record.a = a.to_f64();
+        if tempa <= k(0.0) || a < k(RE) {
+            return Err(PropagationError::Decayed);
+        }
+
+        // e = eₒ - B*C₄(t - tₒ) - B*C₅(sin Mp - sin Mₒ)
+        let e = e0 - tempe;
+        record.e = e.to_f64();
+        if !(k(-0.001)..one).contains(&e) {
+            return Err(PropagationError::Diverged);
+        }
+
+        // A slightly negative eccentricity is an artifact of the drag terms,
+        // keep it just above zero like the reference implementation does
+        let e = e.max(k(1.0e-6));
+
+        // IL = Mp + ω + Ω + nₒ"[...]
+        let IL = M_p + omega + Omega + n0_dp * templ;
+
+        //           ½
+        // β = (1 - e²)
+        let B = (one - e.powi(2)).sqrt();
+
+        //       kₑ
+        // n = ----
+        //     a³/₂
+        let n_ = ke / a.powf(k(3.0/2.0));
+        record.e = e.to_f64();
+        record.IL = IL.to_f64();
+        record.n = n_.to_f64();
+
+        Ok(Secular {
+            Omega,
+            omega,
+            a,
+            e,
+            i,
+            IL,
+            B,
+            n: n_,
+            M: M_p + n0_dp * templ,
+        })
+    }
+
+    /// Add the long period periodics to the mean elements at `time`
+    /// minutes since the epoch
+    pub(crate) fn long_period(&self, time: f64, secular: Secular<F>, record: &mut Intermediates) -> Result<LongPeriod<F>, PropagationError> {
+        let Kernel { i0, sin_io, O, O2, one_plus_O, k2, A30, .. } = *self;
+        let k = F::from_f64;
+        let one = k(1.0);
+
+        let Secular { Omega, omega, a, e, i, IL, B, n: n_, .. } = match self.deep_space {
+            Some(ref deep_space) => deep_space.periodics(time, self.ops_mode, secular.convert())?.convert(),
+            None => secular,
+        };
+
+        // In deep space the lunar-solar periodics change the inclination, so
+        // the terms that depend on it are worked out again
+        let (i, sin_i, O, O2, one_plus_O) = if self.deep_space.is_some() {
+            let O = i.cos();
+            let one_plus_O = if (one + O).abs() > k(RETROGRADE_EQUATORIAL) { one + O } else { k(RETROGRADE_EQUATORIAL) };
+            (i, i.sin(), O, O * O, one_plus_O)
+        } else {
+            (i0, sin_io, O, O2, one_plus_O)
+        };
+
+
+        // ************************************************************************
+        // Section 6.
+        // Long period periodic terms
+
+        // a_xN = e cos ω
+        let axN = e * omega.cos();
+
+        //         A₃₀ sin iₒ
+        // IL_L = ---------- a_xN (3 + 5θ)/(1 + θ)
+        //        8k₂aβ²
+        let IL_L = (A30 * sin_i) / (k(8.0) * k2 * a * B.powi(2)) * axN * (k(3.0) + k(5.0) * O) / one_plus_O;
+
+        //         A₃₀ sin iₒ
+        // a_yNL = ----------
+        //          4k₂aβ²
+        let ayNL = (A30 * sin_i) / (k(4.0) * k2 * a * B.powi(2));
+
+        // IL_T = IL + IL_L
+        let IL_T = IL + IL_L;
+
+        // a_yN = e sin ω + a_yNL
+        let ayN = e * omega.sin() + ayNL;
+        record.axN = axN.to_f64();
+        record.ayN = ayN.to_f64();
+        record.IL_T = IL_T.to_f64();
+
+        // U = IL_T - Ω
+        let U = (IL_T - Omega) % k(2.0 * PI);
+        record.U = U.to_f64();
+
+        Ok(LongPeriod { Omega, a, n: n_, i, sin_i, O, O2, axN, ayN, U })
+    }
+
+    /// Add the short period periodics to the solution `Ew` of Kepler's
+    /// equation, and turn the osculating elements into a state
+    pub(crate) fn short_period(&self, long_period: &LongPeriod<F>, Ew: F, record: &mut Intermediates) -> Result<State<F>, PropagationError> {
+        let Kernel { ke, k2, XKMPER, .. } = *self;
+        let LongPeriod { Omega, a, n: n_, i, sin_i, O, O2, axN, ayN, .. } = *long_period;
+        let k = F::from_f64;
+        let one = k(1.0);
+
+        let sin_Ew = Ew.sin();
+        let cos_Ew = Ew.cos();
+
+
+        // ************************************************************************
+        // Section 8.
+        // Short period periodic terms
+
+        // e cos E = a_xN cos(E + ω) + a_yN sin(E + ω)
+        let ecosE = axN * cos_Ew + ayN * sin_Ew;
+
+        // e sin E = a_xN sin(E + ω) - a_yN cos(E + ω)
+        let esinE = axN * sin_Ew - ayN * cos_Ew;
+
+        //                    ½
+        // e_L = (a_xN² + a_yN²)
+        let eL2 = axN.powi(2) + ayN.powi(2);
+
+        // p_L = a(1 - e_L²)
+        let pL = a * (one - eL2);
+        record.ecosE = ecosE.to_f64();
+        record.esinE = esinE.to_f64();
+        record.pL = pL.to_f64();
+        if pL < k(0.0) {
+            return Err(PropagationError::NegativeSemiLatusRectum);
+        }
+
+        // r = a(1 - e cos E)
+        let r = a * (one - ecosE);
+
+        //      kₑ√a
+        // ṙ = ---- e sin E
+        //       r
+        let r_dot = ke * a.sqrt() / r * esinE;
+
+        //       kₑ√p_L
+        // rḟ = -------
+        //         r
+        let rf_dot = ke * pL.sqrt() / r;
+
+        //           a  ⌈                         e sin E     ⌉
+        // cos u = - × |cos(E + ω) - a_xN + a_yN -------------|
+        //           r  ⌊                      1 + √(1 - e_L²)⌋
+        let BL = (one - eL2).sqrt();
+        let cos_u = (a / r) * (cos_Ew - axN + ayN * esinE / (one + BL));
+
+        //           a  ⌈                         e sin E     ⌉
+        // sin u = - × |sin(E + ω) - a_yN - a_xN -------------|
+        //           r  ⌊                      1 + √(1 - e_L²)⌋
+        let sin_u = (a / r) * (sin_Ew - ayN - axN * esinE / (one + BL));
+
+        // u = tan⁻¹(sin u / cos u)
+        let u = sin_u.atan2(cos_u);
+        let sin_2u = k(2.0) * sin_u * cos_u;
+        let cos_2u = k(2.0) * cos_u.powi(2) - one;
+
+        //       k₂
+        // Δr = ---(1 - θ²) cos 2u
+        //      2p_L
+        let dr = k2 / (k(2.0) * pL) * (one - O2) * cos_2u;
+
+        //         k₂
+        // Δu = - ----(7θ² - 1) sin 2u
+        //        4p_L²
+        let du = -k2 / (k(4.0) * pL.powi(2)) * (k(7.0) * O2 - one) * sin_2u;
+
+        //      3k₂θ
+        // ΔΩ = ----- sin 2u
+        //      2p_L²
+        let dOmega = (k(3.0) * k2 * O) / (k(2.0) * pL.powi(2)) * sin_2u;
+
+        //      3k₂θ
+        // Δi = ----- sin iₒ cos 2u
+        //      2p_L²
+        let di = (k(3.0) * k2 * O) / (k(2.0) * pL.powi(2)) * sin_i * cos_2u;
+
+        //        k₂n
+        // Δṙ = - ---(1 - θ²) sin 2u
+        //        p_L
+        let dr_dot = -k2 * n_ / pL * (one - O2) * sin_2u;
+
+        //       k₂n ⌈                   3          ⌉
+        // Δrḟ = ---|(1 - θ²) cos 2u + -(3θ² - 1)|
+        //       p_L ⌊                   2          ⌋
+        let drf_dot = k2 * n_ / pL * ((one - O2) * cos_2u + k(1.5) * (k(3.0) * O2 - one));
+
+        // The short period periodics are added to give the osculating
+        // quantities:
+        //          ⌈    3     √(1 - e_L²)         ⌉
+        // r_k = r |1 - -k₂ ----------- (3θ² - 1)| + Δr
+        //          ⌊    2        p_L²              ⌋
+        let rk = r * (one - k(1.5) * k2 * BL / pL.powi(2) * (k(3.0) * O2 - one)) + dr;
+        record.r = r.to_f64();
+        record.r_dot = r_dot.to_f64();
+        record.rf_dot = rf_dot.to_f64();
+        record.u = u.to_f64();
+        record.dr = dr.to_f64();
+        record.du = du.to_f64();
+        record.dOmega = dOmega.to_f64();
+        record.di = di.to_f64();
+        record.dr_dot = dr_dot.to_f64();
+        record.drf_dot = drf_dot.to_f64();
+        record.rk = rk.to_f64();
+        if rk < k(RE) {
+            return Err(PropagationError::Decayed);
+        }
+
+        // u_k = u + Δu
+        let uk = u + du;
+
+        // Ω_k = Ω + ΔΩ
+        let Omegak = Omega + dOmega;
+
+        // i_k = iₒ + Δi
+        let ik = i + di;
+
+        // ṙ_k = ṙ + Δṙ
+        let r_dotk = r_dot + dr_dot;
+
+        // rḟ_k = rḟ + Δrḟ
+        let rf_dotk = rf_dot + drf_dot;
+        record.uk = uk.to_f64();
+        record.Omegak = Omegak.to_f64();
+        record.ik = ik.to_f64();
+        record.r_dotk = r_dotk.to_f64();
+        record.rf_dotk = rf_dotk.to_f64();
+
+
+        // ************************************************************************
+        // Section 9.
+        // Unit orientation vectors
+
+        //     ⌈-sin Ω_k cos i_k⌉
+        // M = | cos Ω_k cos i_k|
+        //     ⌊      sin i_k   ⌋
+        let Mx = -Omegak.sin() * ik.cos();
+        let My = Omegak.cos() * ik.cos();
+        let Mz = ik.sin();
+
+        //     ⌈cos Ω_k⌉
+        // N = |sin Ω_k|
+        //     ⌊   0   ⌋
+        let Nx = Omegak.cos();
+        let Ny = Omegak.sin();
+
+        // U = M sin u_k + N cos u_k
+        let Ux = Mx * uk.sin() + Nx * uk.cos();
+        let Uy = My * uk.sin() + Ny * uk.cos();
+        let Uz = Mz * uk.sin();
+
+        // V = M cos u_k - N sin u_k
+        let Vx = Mx * uk.cos() - Nx * uk.sin();
+        let Vy = My * uk.cos() - Ny * uk.sin();
+        let Vz = Mz * uk.cos();
+
+        // Position is r = r_k U, scaled from Earth radii to kilometers, and
+        // velocity is ṙ = ṙ_k U + rḟ_k V, scaled from Earth radii/minute to
+        // kilometers/second
+        let v_scale = XKMPER / k(60.0);
+        Ok(State {
+            position: [rk * Ux * XKMPER, rk * Uy * XKMPER, rk * Uz * XKMPER],
+            velocity: [
+                (r_dotk * Ux + rf_dotk * Vx) * v_scale,
+                (r_dotk * Uy + rf_dotk * Vy) * v_scale,
+                (r_dotk * Uz + rf_dotk * Vz) * v_scale,
+            ],
+        })
+    }
+}
+
+/// Solve Kepler's equation for (E + ω), starting from U and keeping the
+/// reference implementation's arithmetic. Also returns whether it
+/// converged.
+pub(crate) fn kepler<F: Real>(long_period: &LongPeriod<F>, record: &mut Intermediates) -> (F, bool) {
+    let LongPeriod { axN, ayN, U, .. } = *long_period;
+    let one = F::from_f64(1.0);
+
+
+    // ************************************************************************
+    // Section 7.
+    // Solve Kepler's equation for (E + ω)
+
+    //                                   U - a_yN cos(E + ω)ᵢ + a_xN sin(E + ω)ᵢ - (E + ω)ᵢ
+    // (E + ω)ᵢ₊₁ = (E + ω)ᵢ + Δ(E + ω)ᵢ, Δ = ------------------------------------------------
+    //                                       1 - a_yN sin(E + ω)ᵢ - a_xN cos(E + ω)ᵢ
+    let mut Ew = U;
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < KEPLER_ITERATIONS && !converged {
+        let delta = (U - ayN * Ew.cos() + axN * Ew.sin() - Ew) / (one - ayN * Ew.sin() - axN * Ew.cos());
+        Ew += delta;
+        iterations += 1;
+        converged = delta.abs() <= F::from_f64(KEPLER_TOLERANCE);
+    }
+    record.kepler_iterations = iterations;
+    record.Ew = Ew.to_f64();
+    (Ew, converged)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coordinates::StateVector;
+    use test_support;
+
+    fn iss() -> Propagator {
+        let tle = test_support::iss();
+        Propagator::new(&tle).unwrap()
+    }
+
+    /// Position and velocity differences (kilometers, kilometers/second)
+    fn errors<F: Real>(state: &State<F>, reference: &StateVector) -> (f64, f64) {
+        let position = [reference.position.X, reference.position.Y, reference.position.Z];
+        let velocity = [reference.velocity.X, reference.velocity.Y, reference.velocity.Z];
+        let distance = |a: &[F; 3], b: &[f64; 3]| {
+            (0..3).map(|axis| (a[axis].to_f64() - b[axis]).powi(2)).sum::<f64>().sqrt()
+        };
+        (distance(&state.position, &position), distance(&state.velocity, &velocity))
+    }
+
+    #[test]
+    fn double_is_the_propagator() {
+        let molniya = test_support::molniya();
+        for propagator in [iss(), Propagator::new(&molniya).unwrap()].iter() {
+            let kernel: Kernel<f64> = Kernel::new(propagator);
+            for &time in [0.0, 90.0, -720.0, 1440.0, 7.0 * 1440.0].iter() {
+                let (position, velocity) = errors(&kernel.propagate(time).unwrap(), &propagator.propagate(time).unwrap());
+                assert_eq!((position, velocity), (0.0, 0.0));
+            }
+        }
+    }
+
+    #[test]
+    fn single_precision_penalty() {
+        let propagator = iss();
+        let kernel: Kernel<f32> = Kernel::new(&propagator);
+
+        // A meter near epoch, growing with the mean anomaly to hundreds of
+        // meters after a week
+        for &(time, max_position, max_velocity) in [
+            (0.0, 0.002, 2e-6),
+            (90.0, 0.002, 2e-6),
+            (1440.0, 0.02, 2e-5),
+            (7.0 * 1440.0, 0.6, 1e-3),
+        ].iter() {
+            let state = kernel.propagate(time).unwrap();
+            let (position, velocity) = errors(&state, &propagator.propagate(time).unwrap());
+            assert!(position < max_position, "{} km at {} minutes", position, time);
+            assert!(velocity < max_velocity, "{} km/s at {} minutes", velocity, time);
+        }
+    }
+
+    #[test]
+    fn deep_space_single() {
+        let molniya = test_support::molniya();
+        let propagator = Propagator::new(&molniya).unwrap();
+        let kernel: Kernel<f32> = Kernel::new(&propagator);
+
+        // A float resolves meters out at apogee, but the resonance is
+        // integrated in double precision, so the error doesn't grow much
+        for &time in [0.0, 360.0, 1440.0, 7.0 * 1440.0].iter() {
+            let (position, velocity) = errors(&kernel.propagate(time).unwrap(), &propagator.propagate(time).unwrap());
+            assert!(position < 0.05, "{} km at {} minutes", position, time);
+            assert!(velocity < 2e-5, "{} km/s at {} minutes", velocity, time);
+        }
+    }
+
+    #[test]
+    fn same_errors() {
+        let mut tle = test_support::iss();
+        tle.mean_motion = 16.4;
+        tle.bstar = 0.05;
+        let propagator = Propagator::new(&tle).unwrap();
+        let kernel: Kernel<f32> = Kernel::new(&propagator);
+
+        let time = 10.0 * 1440.0;
+        assert_eq!(kernel.propagate(time).unwrap_err(), propagator.propagate(time).unwrap_err());
+    }
+}
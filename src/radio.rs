@@ -0,0 +1,267 @@
+/*!  # Radio
+
+Pass reports for working a satellite by radio: for each pass over a
+station, a table of where to point and what to tune to through it.
+
+Each row gives the time, azimuth, elevation, range and range rate, and
+the Doppler corrected frequencies for the satellite's nominal downlink and
+uplink: the frequency the downlink is received on, and the frequency to
+transmit on so the uplink arrives at the satellite on its nominal one. A
+report prints as a plain text table.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::fmt;
+
+use observer::{doppler_shift, Observer};
+use passes::{find_passes, Pass, PassOptions};
+use time;
+use tle::TLE;
+use {PropagationError, Propagator, MINUTES_PER_DAY};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// ## Radio Options
+///
+/// The satellite's nominal frequencies, and how to find and tabulate its
+/// passes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadioOptions {
+
+    /// Nominal downlink frequency (Hz), if any (default none)
+    pub downlink: Option<f64>,
+
+    /// Nominal uplink frequency (Hz), if any (default none)
+    pub uplink: Option<f64>,
+
+    /// Time between rows (minutes, default 0.5)
+    pub step: f64,
+
+    /// How to search for passes
+    pub passes: PassOptions,
+}
+
+impl Default for RadioOptions {
+    fn default() -> RadioOptions {
+        RadioOptions {
+            downlink: None,
+            uplink: None,
+            step: 0.5,
+            passes: PassOptions::default(),
+        }
+    }
+}
+
+/// ## Radio Row
+///
+/// One line of a pass table. The time is minutes since the TLE epoch and
+/// angles are in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RadioRow {
+
+    /// Time of the row
+    pub time: f64,
+
+    /// Azimuth (clockwise from north)
+    pub az: f64,
+
+    /// Elevation above the horizon
+    pub el: f64,
+
+    /// Distance to the satellite (kilometers)
+    pub range: f64,
+
+    /// Rate of change of the range (kilometers/second, positive when
+    /// moving away)
+    pub range_rate: f64,
+
+    /// Frequency to receive the downlink on (Hz)
+    pub downlink: Option<f64>,
+
+    /// Frequency to transmit the uplink on (Hz)
+    pub uplink: Option<f64>,
+}
+
+/// ## Radio Pass
+///
+/// A pass and its table, from rise to set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadioPass {
+
+    /// Julian date of the TLE epoch, the times are counted from
+    pub epoch: f64,
+
+    /// The pass
+    pub pass: Pass,
+
+    /// A row every `RadioOptions::step` minutes from rise, and one at set
+    pub rows: Vec<RadioRow>,
+}
+
+/// An optional frequency in a table column
+struct Frequency(Option<f64>);
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(frequency) => write!(f, "{:>14.0}", frequency),
+            None => write!(f, "{:>14}", "-"),
+        }
+    }
+}
+
+impl fmt::Display for RadioPass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let utc = |time: f64| time::iso_8601(self.epoch + time / MINUTES_PER_DAY);
+        writeln!(f, "Rise {} az {:.1}°, maximum elevation {:.1}° at {}, set {} az {:.1}°",
+                 utc(self.pass.rise), self.pass.rise_azimuth.to_degrees(),
+                 self.pass.max_elevation.to_degrees(), utc(self.pass.max_elevation_time),
+                 utc(self.pass.set), self.pass.set_azimuth.to_degrees())?;
+        writeln!(f, "{:<26} {:>7} {:>6} {:>10} {:>11} {:>14} {:>14}",
+                 "Time (UTC)", "Az (°)", "El (°)", "Range (km)", "Rate (km/s)", "RX (Hz)", "TX (Hz)")?;
+        for row in &self.rows {
+            writeln!(f, "{:<26} {:>7.1} {:>6.1} {:>10.1} {:>11.3} {} {}",
+                     utc(row.time), row.az.to_degrees(), row.el.to_degrees(),
+                     row.range, row.range_rate, Frequency(row.downlink), Frequency(row.uplink))?;
+        }
+        Ok(())
+    }
+}
+
+/// ## Radio Report
+///
+/// Every pass of a TLE over `observer` between `start` and `end` (minutes
+/// since the TLE epoch), each with its table.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::coordinates::Geodetic;
+/// use sgp4::observer::Observer;
+/// use sgp4::radio::{radio_report, RadioOptions};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+/// let observer = Observer::new(Geodetic {
+///     lat: f64::to_radians(45.52),
+///     lon: f64::to_radians(-122.68),
+///     alt: 0.05,
+/// });
+///
+/// // The ISS's 2 m voice repeater
+/// let options = RadioOptions {
+///     downlink: Some(145.80e6),
+///     uplink: Some(144.49e6),
+///     ..RadioOptions::default()
+/// };
+/// for pass in radio_report(&tle, &observer, 0.0, 1440.0, &options).unwrap() {
+///     println!("{}", pass);
+/// }
+/// ```
+///
+/// ### Panics
+///
+/// If `options.step`, or a step or tolerance in `options.passes`, isn't
+/// positive.
+pub fn radio_report(tle: &TLE, observer: &Observer, start: f64, end: f64, options: &RadioOptions) -> Result<Vec<RadioPass>, PropagationError> {
+    assert!(options.step > 0.0, "report step must be positive");
+
+    let propagator = Propagator::new(tle)?;
+    let epoch = tle.epoch_julian_date();
+    let row = |time: f64| -> Result<RadioRow, PropagationError> {
+        let state = propagator.propagate(time)?;
+        let look = observer.look_angles(&state, time::gmst(epoch + time / MINUTES_PER_DAY));
+        Ok(RadioRow {
+            time,
+            az: look.az,
+            el: look.el,
+            range: look.range,
+            range_rate: look.range_rate,
+            downlink: options.downlink.map(|frequency| frequency + doppler_shift(look.range_rate, frequency)),
+            uplink: options.uplink.map(|frequency| frequency - doppler_shift(look.range_rate, frequency)),
+        })
+    };
+
+    find_passes(&propagator, observer, start, end, &options.passes)?
+        .into_iter()
+        .map(|pass| {
+            let mut rows = propagator.sample(pass.rise, pass.set, options.step)
+                .map(|(time, _)| row(time))
+                .collect::<Result<Vec<_>, _>>()?;
+            if rows.last().is_none_or(|last| last.time < pass.set) {
+                rows.push(row(pass.set)?);
+            }
+            Ok(RadioPass { epoch, pass, rows })
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coordinates::Geodetic;
+    use test_support::iss;
+
+    fn portland() -> Observer {
+        Observer::new(Geodetic {
+            lat: f64::to_radians(45.52),
+            lon: f64::to_radians(-122.68),
+            alt: 0.05,
+        })
+    }
+
+    #[test]
+    fn report() {
+        let options = RadioOptions { downlink: Some(437.8e6), uplink: Some(145.99e6), ..RadioOptions::default() };
+        let report = radio_report(&iss(), &portland(), 0.0, 1440.0, &options).unwrap();
+        let passes = ::passes::passes(&iss(), &portland(), 0.0, 1440.0).unwrap();
+        assert_eq!(report.len(), passes.len());
+
+        for (radio, pass) in report.iter().zip(&passes) {
+            assert_eq!(radio.pass, *pass);
+            assert_eq!(radio.rows[0].time, pass.rise);
+            assert_eq!(radio.rows[radio.rows.len() - 1].time, pass.set);
+
+            for row in &radio.rows {
+                // Approaching: hear it high, transmit low; and the other way
+                // round once it's going away
+                let (downlink, uplink) = (row.downlink.unwrap(), row.uplink.unwrap());
+                assert_eq!(downlink > 437.8e6, row.range_rate < 0.0);
+                assert_eq!(uplink < 145.99e6, row.range_rate < 0.0);
+                let shift = doppler_shift(row.range_rate, 437.8e6);
+                assert!((downlink - 437.8e6 - shift).abs() < 1e-6);
+                assert!(row.range > 400.0 && row.range < 3000.0);
+            }
+        }
+
+        // A header, column titles and a line a row
+        let table = report[0].to_string();
+        assert_eq!(table.lines().count(), 2 + report[0].rows.len());
+        assert!(table.lines().nth(1).unwrap().starts_with("Time (UTC)"));
+        assert!(table.lines().nth(2).unwrap().starts_with("2016-07-2"));
+    }
+
+    #[test]
+    fn no_frequencies() {
+        let report = radio_report(&iss(), &portland(), 0.0, 1440.0, &RadioOptions::default()).unwrap();
+        let row = report[0].rows[0];
+        assert_eq!((row.downlink, row.uplink), (None, None));
+        assert!(report[0].to_string().lines().nth(2).unwrap().ends_with("             -              -"));
+    }
+}
@@ -0,0 +1,566 @@
+/*!  # SATCAT
+
+What's known about each object besides its orbit, from Celestrak's
+satellite catalog (SATCAT): when it was launched and when it decayed, who
+owns it, whether it's a payload, rocket body or debris, and how big it
+looks to radar.
+
+Celestrak publishes the catalog as CSV with a header line, the format
+[`parse_csv`](fn.parse_csv.html) reads, and used to publish it as
+fixed-width text, which [`parse_legacy`](fn.parse_legacy.html) reads.
+[`join`](fn.join.html) pairs each TLE in a
+[`Catalog`](../catalog/struct.Catalog.html) with its entry.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::error;
+use std::fmt;
+use std::iter::FromIterator;
+
+#[cfg(any(feature = "std", test))]
+use std::collections::{btree_map, BTreeMap};
+#[cfg(not(any(feature = "std", test)))]
+use alloc::collections::{btree_map, BTreeMap};
+
+use catalog::Catalog;
+use time;
+use tle::TLE;
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::string::String;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// ## Object Type
+///
+/// What kind of object a catalog entry is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectType {
+
+    /// A satellite or other spacecraft
+    Payload,
+
+    /// A spent upper stage
+    RocketBody,
+
+    /// Fragments and anything else shed along the way
+    Debris,
+
+    /// Not yet identified
+    Unknown,
+}
+
+impl ObjectType {
+
+    /// The type from a SATCAT code (`PAY`, `R/B`, `DEB`, `UNK`) or the
+    /// spelled out names Space-Track uses
+    fn from_code(code: &str) -> Option<ObjectType> {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "PAY" | "PAYLOAD" => Some(ObjectType::Payload),
+            "R/B" | "ROCKET BODY" => Some(ObjectType::RocketBody),
+            "DEB" | "DEBRIS" => Some(ObjectType::Debris),
+            "UNK" | "UNKNOWN" | "TBA" | "" => Some(ObjectType::Unknown),
+            _ => None,
+        }
+    }
+}
+
+/// ## RCS Size
+///
+/// Radar cross section size class, in the bands Space-Track publishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RcsSize {
+
+    /// Under 0.1 m²
+    Small,
+
+    /// 0.1 m² to 1 m²
+    Medium,
+
+    /// Over 1 m²
+    Large,
+}
+
+impl RcsSize {
+
+    /// The class of a radar cross section (square meters)
+    pub fn from_area(rcs: f64) -> RcsSize {
+        if rcs < 0.1 {
+            RcsSize::Small
+        } else if rcs <= 1.0 {
+            RcsSize::Medium
+        } else {
+            RcsSize::Large
+        }
+    }
+
+    /// The class from its name, `SMALL`, `MEDIUM` or `LARGE`
+    fn from_name(name: &str) -> Option<RcsSize> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "SMALL" => Some(RcsSize::Small),
+            "MEDIUM" => Some(RcsSize::Medium),
+            "LARGE" => Some(RcsSize::Large),
+            _ => None,
+        }
+    }
+}
+
+
+/// ## SATCAT Entry
+///
+/// One object in the satellite catalog. Dates are Julian dates of midnight
+/// UTC at the start of the day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SatcatEntry {
+
+    /// Name of the object
+    pub name: String,
+
+    /// International designator in full COSPAR form (`1998-067A`)
+    pub int_designator: String,
+
+    /// NORAD catalog number
+    pub sat_number: u32,
+
+    /// Payload, rocket body or debris
+    pub object_type: ObjectType,
+
+    /// Owner or source, as a SATCAT code (`US`, `CIS`, `ISS`, …)
+    pub owner: String,
+
+    /// When it was launched
+    pub launch_date: Option<f64>,
+
+    /// When it reentered, if it has
+    pub decay_date: Option<f64>,
+
+    /// Radar cross section (square meters), where the catalog gives it
+    pub rcs: Option<f64>,
+
+    /// Radar cross section size class, from `rcs` or as the catalog gives
+    /// it
+    pub rcs_size: Option<RcsSize>,
+}
+
+impl SatcatEntry {
+
+    /// Whether the object has reentered
+    pub fn is_decayed(&self) -> bool {
+        self.decay_date.is_some()
+    }
+}
+
+
+/// ## SATCAT Errors
+///
+/// Why a catalog couldn't be read. Lines are numbered from 1, counting the
+/// header.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SatcatError {
+
+    /// The CSV header doesn't have a column every entry needs
+    MissingColumn(&'static str),
+
+    /// A field couldn't be read
+    InvalidField {
+        /// Line number
+        line: usize,
+        /// Name of the field
+        field: &'static str,
+        /// Its text
+        value: String,
+    },
+
+    /// A fixed-width line ends before the satellite name
+    LineTooShort {
+        /// Line number
+        line: usize,
+        /// Length of the line
+        length: usize,
+    },
+}
+
+impl fmt::Display for SatcatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SatcatError::MissingColumn(column) => write!(f, "no {} column", column),
+            SatcatError::InvalidField { line, field, ref value } => write!(f, "line {}: invalid {} \"{}\"", line, field, value),
+            SatcatError::LineTooShort { line, length } => write!(f, "line {}: only {} characters", line, length),
+        }
+    }
+}
+
+impl error::Error for SatcatError {}
+
+
+/// ## Satcat
+///
+/// Catalog entries, keyed by NORAD catalog number.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::catalog::Catalog;
+/// use sgp4::satcat::{self, ObjectType, Satcat};
+///
+/// let satcat: Satcat = satcat::parse("\
+/// OBJECT_NAME,OBJECT_ID,NORAD_CAT_ID,OBJECT_TYPE,OPS_STATUS_CODE,OWNER,LAUNCH_DATE,LAUNCH_SITE,DECAY_DATE,PERIOD,INCLINATION,APOGEE,PERIGEE,RCS,DATA_STATUS_CODE,ORBIT_CENTER,ORBIT_TYPE
+/// ISS (ZARYA),1998-067A,25544,PAY,+,ISS,1998-11-20,TYMSC,,92.9,51.64,422,418,399.0524,,EA,ORB
+/// ").unwrap().into_iter().collect();
+///
+/// let catalog = Catalog::parse("\
+/// ISS (ZARYA)
+/// 1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+/// 2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+/// ").unwrap();
+///
+/// for (tle, entry) in satcat::join(&catalog, &satcat) {
+///     let entry = entry.unwrap();
+///     assert_eq!(entry.object_type, ObjectType::Payload);
+///     assert_eq!(entry.launch_date, Some(sgp4::time::julian_date(1998, 11, 20.0)));
+///     println!("{} is owned by {}", tle.name, entry.owner);
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Satcat {
+    entries: BTreeMap<u32, SatcatEntry>,
+}
+
+impl Satcat {
+
+    /// An empty catalog
+    pub fn new() -> Satcat {
+        Satcat::default()
+    }
+
+    /// Add an entry, returning the one it replaces
+    pub fn insert(&mut self, entry: SatcatEntry) -> Option<SatcatEntry> {
+        self.entries.insert(entry.sat_number, entry)
+    }
+
+    /// The entry for a NORAD catalog number
+    pub fn get(&self, sat_number: u32) -> Option<&SatcatEntry> {
+        self.entries.get(&sat_number)
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Every entry, in order of catalog number
+    pub fn iter(&self) -> btree_map::Values<'_, u32, SatcatEntry> {
+        self.entries.values()
+    }
+}
+
+impl FromIterator<SatcatEntry> for Satcat {
+    fn from_iter<I: IntoIterator<Item = SatcatEntry>>(entries: I) -> Satcat {
+        let mut satcat = Satcat::new();
+        satcat.extend(entries);
+        satcat
+    }
+}
+
+impl Extend<SatcatEntry> for Satcat {
+    fn extend<I: IntoIterator<Item = SatcatEntry>>(&mut self, entries: I) {
+        for entry in entries {
+            self.insert(entry);
+        }
+    }
+}
+
+/// ## Join
+///
+/// Every TLE in `catalog`, in order of catalog number, with its SATCAT
+/// entry if `satcat` has one.
+pub fn join<'a>(catalog: &'a Catalog, satcat: &'a Satcat) -> impl Iterator<Item = (&'a TLE, Option<&'a SatcatEntry>)> + 'a {
+    catalog.iter().map(move |tle| (tle, satcat.get(tle.sat_number)))
+}
+
+
+/// ## Parse
+///
+/// Read a catalog in either format: CSV if the first line is a header
+/// starting with `OBJECT_NAME`, otherwise fixed-width.
+pub fn parse(text: &str) -> Result<Vec<SatcatEntry>, SatcatError> {
+    let first = text.lines().find(|line| !line.trim().is_empty()).unwrap_or("");
+    if first.trim_start().starts_with("OBJECT_NAME") {
+        parse_csv(text)
+    } else {
+        parse_legacy(text)
+    }
+}
+
+/// ## Parse CSV
+///
+/// Read Celestrak's CSV catalog. The header names the columns, which may
+/// come in any order; `OBJECT_NAME`, `OBJECT_ID` and `NORAD_CAT_ID` are
+/// required, and `OBJECT_TYPE`, `OWNER`, `LAUNCH_DATE`, `DECAY_DATE`,
+/// `RCS` and `RCS_SIZE` are read if they're there. `RCS` may be an area in
+/// square meters or a size class.
+pub fn parse_csv(text: &str) -> Result<Vec<SatcatEntry>, SatcatError> {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let header = match lines.next() {
+        Some((_, header)) => split_csv(header),
+        None => return Ok(Vec::new()),
+    };
+    let column = |name: &str| header.iter().position(|column| column.trim() == name);
+    let required = |name: &'static str| column(name).ok_or(SatcatError::MissingColumn(name));
+    let (name, object_id, norad_cat_id) = (required("OBJECT_NAME")?, required("OBJECT_ID")?, required("NORAD_CAT_ID")?);
+    let (object_type, owner) = (column("OBJECT_TYPE"), column("OWNER"));
+    let (launch_date, decay_date) = (column("LAUNCH_DATE"), column("DECAY_DATE"));
+    let (rcs, rcs_size) = (column("RCS"), column("RCS_SIZE"));
+
+    lines.map(|(index, line)| {
+        let line_number = index + 1;
+        let fields = split_csv(line);
+        let field = |column: Option<usize>| column.and_then(|column| fields.get(column)).map_or("", |field| field.trim());
+        let invalid = |field: &'static str, value: &str| SatcatError::InvalidField { line: line_number, field, value: String::from(value) };
+
+        let sat_number = field(Some(norad_cat_id));
+        let sat_number = sat_number.parse::<u32>().map_err(|_| invalid("NORAD_CAT_ID", sat_number))?;
+        let kind = field(object_type);
+        let kind = ObjectType::from_code(kind).ok_or_else(|| invalid("OBJECT_TYPE", kind))?;
+        let launch = field(launch_date);
+        let launch = parse_date(launch).map_err(|_| invalid("LAUNCH_DATE", launch))?;
+        let decay = field(decay_date);
+        let decay = parse_date(decay).map_err(|_| invalid("DECAY_DATE", decay))?;
+
+        // RCS is an area in Celestrak's catalog, and might be a class
+        let (area, mut size) = match field(rcs) {
+            "" | "N/A" => (None, None),
+            text => match (text.parse::<f64>(), RcsSize::from_name(text)) {
+                (Ok(area), _) => (Some(area), Some(RcsSize::from_area(area))),
+                (Err(_), Some(size)) => (None, Some(size)),
+                (Err(_), None) => return Err(invalid("RCS", text)),
+            },
+        };
+        match field(rcs_size) {
+            "" => {}
+            text => size = Some(RcsSize::from_name(text).ok_or_else(|| invalid("RCS_SIZE", text))?),
+        }
+
+        Ok(SatcatEntry {
+            name: String::from(field(Some(name))),
+            int_designator: String::from(field(Some(object_id))),
+            sat_number,
+            object_type: kind,
+            owner: String::from(field(owner)),
+            launch_date: launch,
+            decay_date: decay,
+            rcs: area,
+            rcs_size: size,
+        })
+    }).collect()
+}
+
+/// ## Parse Legacy
+///
+/// Read the fixed-width catalog Celestrak published as `satcat.txt`: the
+/// designator in columns 1–11, catalog number in 14–18, a `*` for payloads
+/// in column 21, the name in 24–47, owner in 50–54, launch date in 57–66,
+/// decay date in 76–85 and radar cross section (square meters, or `N/A`)
+/// in 120–127. There's no object type column; objects that aren't payloads
+/// are rocket bodies or debris if their names say so.
+pub fn parse_legacy(text: &str) -> Result<Vec<SatcatEntry>, SatcatError> {
+    text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()).map(|(index, line)| {
+        let line_number = index + 1;
+        if line.len() < 24 {
+            return Err(SatcatError::LineTooShort { line: line_number, length: line.len() });
+        }
+        let column = |start: usize, end: usize| line.get(start..end.min(line.len())).unwrap_or("").trim();
+        let invalid = |field: &'static str, value: &str| SatcatError::InvalidField { line: line_number, field, value: String::from(value) };
+
+        let sat_number = column(13, 18);
+        let sat_number = sat_number.parse::<u32>().map_err(|_| invalid("catalog number", sat_number))?;
+        let name = column(23, 47);
+        let object_type = if column(20, 21) == "*" {
+            ObjectType::Payload
+        } else if name.contains("R/B") {
+            ObjectType::RocketBody
+        } else if name.split_whitespace().any(|word| word == "DEB") {
+            ObjectType::Debris
+        } else {
+            ObjectType::Unknown
+        };
+        let launch = column(56, 66);
+        let launch = parse_date(launch).map_err(|_| invalid("launch date", launch))?;
+        let decay = column(75, 85);
+        let decay = parse_date(decay).map_err(|_| invalid("decay date", decay))?;
+        let rcs = match column(119, 127) {
+            "" | "N/A" => None,
+            text => Some(text.parse::<f64>().map_err(|_| invalid("radar cross section", text))?),
+        };
+
+        Ok(SatcatEntry {
+            name: String::from(name),
+            int_designator: String::from(column(0, 11)),
+            sat_number,
+            object_type,
+            owner: String::from(column(49, 54)),
+            launch_date: launch,
+            decay_date: decay,
+            rcs,
+            rcs_size: rcs.map(RcsSize::from_area),
+        })
+    }).collect()
+}
+
+/// Julian date of midnight at the start of a `YYYY-MM-DD` date, or `None`
+/// for a blank one
+fn parse_date(date: &str) -> Result<Option<f64>, ()> {
+    if date.is_empty() {
+        return Ok(None);
+    }
+    let mut parts = date.splitn(3, '-');
+    let mut part = || parts.next().and_then(|part| part.parse::<u32>().ok()).ok_or(());
+    let (year, month, day) = (part()?, part()?, part()?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return Err(());
+    }
+    Ok(Some(time::julian_date(year as i32, month, f64::from(day))))
+}
+
+/// The fields of a CSV line, with quoted fields unquoted and doubled
+/// quotes inside them made single
+fn split_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "\
+OBJECT_NAME,OBJECT_ID,NORAD_CAT_ID,OBJECT_TYPE,OPS_STATUS_CODE,OWNER,LAUNCH_DATE,LAUNCH_SITE,DECAY_DATE,PERIOD,INCLINATION,APOGEE,PERIGEE,RCS,DATA_STATUS_CODE,ORBIT_CENTER,ORBIT_TYPE
+SL-1 R/B,1957-001A,1,R/B,D,CIS,1957-10-04,TYMSC,1957-12-01,96.19,65.10,938,214,20.42,,EA,IMP
+SPUTNIK 1,1957-001B,2,PAY,D,CIS,1957-10-04,TYMSC,1958-01-03,96.10,65.00,1080,64,,,EA,IMP
+ISS (ZARYA),1998-067A,25544,PAY,+,ISS,1998-11-20,TYMSC,,92.9,51.64,422,418,399.0524,,EA,ORB
+\"FENGYUN 1C DEB, PIECE\",1999-025AAA,29733,DEB,,PRC,1999-05-10,TSC,,100.24,98.87,873,797,0.0491,,EA,ORB
+";
+
+    #[test]
+    fn csv() {
+        let entries = parse(CSV).unwrap();
+        assert_eq!(entries.len(), 4);
+
+        let rocket = &entries[0];
+        assert_eq!(rocket.name, "SL-1 R/B");
+        assert_eq!(rocket.int_designator, "1957-001A");
+        assert_eq!(rocket.object_type, ObjectType::RocketBody);
+        assert_eq!(rocket.owner, "CIS");
+        assert_eq!(rocket.launch_date, Some(2436115.5));
+        assert_eq!(rocket.decay_date, Some(time::julian_date(1957, 12, 1.0)));
+        assert_eq!(rocket.rcs_size, Some(RcsSize::Large));
+        assert!(rocket.is_decayed());
+
+        let sputnik = &entries[1];
+        assert_eq!(sputnik.rcs, None);
+        assert_eq!(sputnik.rcs_size, None);
+
+        let iss = &entries[2];
+        assert_eq!(iss.object_type, ObjectType::Payload);
+        assert_eq!(iss.decay_date, None);
+        assert_eq!(iss.rcs, Some(399.0524));
+
+        // A quoted name with a comma in it
+        let debris = &entries[3];
+        assert_eq!(debris.name, "FENGYUN 1C DEB, PIECE");
+        assert_eq!(debris.sat_number, 29733);
+        assert_eq!(debris.object_type, ObjectType::Debris);
+        assert_eq!(debris.rcs_size, Some(RcsSize::Small));
+
+        // Columns in another order, with size classes
+        let entries = parse_csv("\
+NORAD_CAT_ID,OBJECT_ID,OBJECT_NAME,RCS_SIZE
+25544,1998-067A,ISS (ZARYA),LARGE
+").unwrap();
+        assert_eq!(entries[0].rcs_size, Some(RcsSize::Large));
+        assert_eq!(entries[0].object_type, ObjectType::Unknown);
+        assert_eq!(entries[0].launch_date, None);
+    }
+
+    #[test]
+    fn legacy() {
+        let text = "\
+1957-001A    00001   D SL-1 R/B                  CIS    1957-10-04  TYMSC  1957-12-01     96.2   65.1     938     214   20.4200  EA
+1957-001B    00002  *D SPUTNIK 1                 CIS    1957-10-04  TYMSC  1958-01-03     96.1   65.0    1080      64       N/A  EA
+1999-025AAA  29733     FENGYUN 1C DEB            PRC    1999-05-10  TSC                  100.2   98.9     873     797    0.0491  EA
+";
+        let entries = parse(text).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].sat_number, 1);
+        assert_eq!(entries[0].name, "SL-1 R/B");
+        assert_eq!(entries[0].int_designator, "1957-001A");
+        assert_eq!(entries[0].object_type, ObjectType::RocketBody);
+        assert_eq!(entries[0].owner, "CIS");
+        assert_eq!(entries[0].launch_date, Some(2436115.5));
+        assert_eq!(entries[0].decay_date, Some(time::julian_date(1957, 12, 1.0)));
+        assert_eq!(entries[0].rcs, Some(20.42));
+        assert_eq!(entries[1].object_type, ObjectType::Payload);
+        assert_eq!(entries[1].rcs, None);
+        assert_eq!(entries[2].object_type, ObjectType::Debris);
+        assert_eq!(entries[2].decay_date, None);
+        assert_eq!(entries[2].rcs_size, Some(RcsSize::Small));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse_csv("OBJECT_NAME,OBJECT_ID\nISS,1998-067A\n").unwrap_err(), SatcatError::MissingColumn("NORAD_CAT_ID"));
+        assert_eq!(
+            parse_csv("OBJECT_NAME,OBJECT_ID,NORAD_CAT_ID,LAUNCH_DATE\nISS,1998-067A,25544,1998-13-20\n").unwrap_err(),
+            SatcatError::InvalidField { line: 2, field: "LAUNCH_DATE", value: String::from("1998-13-20") },
+        );
+        assert_eq!(parse_legacy("1957-001A    00001").unwrap_err(), SatcatError::LineTooShort { line: 1, length: 18 });
+        assert!(parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn join() {
+        let satcat: Satcat = parse(CSV).unwrap().into_iter().collect();
+        let catalog = Catalog::parse("\
+ISS (ZARYA)
+1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+").unwrap();
+        let mut unknown = catalog.get(25544).unwrap().clone();
+        unknown.sat_number = 90000;
+        let catalog: Catalog = catalog.into_iter().chain(Some(unknown)).collect();
+
+        let joined: Vec<(u32, Option<&str>)> = super::join(&catalog, &satcat)
+            .map(|(tle, entry)| (tle.sat_number, entry.map(|entry| entry.owner.as_str())))
+            .collect();
+        assert_eq!(joined, vec![(25544, Some("ISS")), (90000, None)]);
+    }
+}
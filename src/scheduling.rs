@@ -0,0 +1,352 @@
+/*!  # Scheduling
+
+Contact scheduling for a network of ground stations: every window in
+which a station can see a satellite, and a schedule of them with no
+conflicts.
+
+A station can only track one satellite at a time, and a satellite is
+only worked by one station at a time, so contact windows that overlap on
+either conflict. A schedule keeps as many windows as fit, picked greedily
+by one of two rules: earliest ending first, which fits the most contacts
+into the window, or by satellite priority, which serves the most
+important satellites first and fills in around them.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::cmp::Ordering;
+use std::error;
+use std::fmt;
+
+use observer::Observer;
+use passes::{find_passes, Pass, PassOptions};
+use tle::TLE;
+use {PropagationError, Propagator, MINUTES_PER_DAY};
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// ## Contact
+///
+/// A window in which a station can see a satellite.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+
+    /// Index of the satellite
+    pub satellite: usize,
+
+    /// Index of the station
+    pub station: usize,
+
+    /// Start of the window, when the satellite rises (Julian date, UTC)
+    pub start: f64,
+
+    /// End of the window, when the satellite sets (Julian date, UTC)
+    pub end: f64,
+
+    /// The pass, with times in minutes since the satellite's TLE epoch
+    pub pass: Pass,
+}
+
+impl Contact {
+
+    /// Length of the window (minutes)
+    pub fn duration(&self) -> f64 {
+        self.pass.duration()
+    }
+
+    /// Whether two windows can't both be kept: they share a station or a
+    /// satellite, and overlap once the first is extended by `turnaround`
+    /// minutes
+    pub fn conflicts_with(&self, other: &Contact, turnaround: f64) -> bool {
+        let pad = turnaround / MINUTES_PER_DAY;
+        let shared = self.station == other.station || self.satellite == other.satellite;
+        shared && self.start < other.end + pad && other.start < self.end + pad
+    }
+}
+
+/// ## Contact Errors
+///
+/// Why contact windows couldn't be found.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactError {
+
+    /// Index of the satellite that couldn't be propagated
+    pub satellite: usize,
+
+    /// What went wrong
+    pub error: PropagationError,
+}
+
+impl fmt::Display for ContactError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "satellite {}: {}", self.satellite, self.error)
+    }
+}
+
+impl error::Error for ContactError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// ## Contact Windows
+///
+/// Every window in which one of `stations` can see one of `satellites`
+/// between `start` and `end` (Julian dates, UTC), in order of start.
+/// Contacts refer to satellites and stations by their index in the
+/// slices.
+///
+/// ### Errors
+///
+/// The first satellite that couldn't be propagated through the window.
+///
+/// ### Panics
+///
+/// If a step or tolerance in `options` isn't positive.
+pub fn contact_windows(satellites: &[TLE], stations: &[Observer], start: f64, end: f64, options: &PassOptions) -> Result<Vec<Contact>, ContactError> {
+    let mut contacts = Vec::new();
+    for (satellite, tle) in satellites.iter().enumerate() {
+        let error = |error| ContactError { satellite, error };
+        let propagator = Propagator::new(tle).map_err(error)?;
+        let epoch = tle.epoch_julian_date();
+        let (from, to) = ((start - epoch) * MINUTES_PER_DAY, (end - epoch) * MINUTES_PER_DAY);
+        for (station, observer) in stations.iter().enumerate() {
+            for pass in find_passes(&propagator, observer, from, to, options).map_err(error)? {
+                contacts.push(Contact {
+                    satellite,
+                    station,
+                    start: epoch + pass.rise / MINUTES_PER_DAY,
+                    end: epoch + pass.set / MINUTES_PER_DAY,
+                    pass,
+                });
+            }
+        }
+    }
+    contacts.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap_or(Ordering::Equal));
+    Ok(contacts)
+}
+
+/// ## Strategy
+///
+/// Which windows a schedule keeps first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+
+    /// Earliest ending first: the most contacts that fit
+    Greedy,
+
+    /// Highest satellite priority first, then earliest ending
+    Priority,
+}
+
+/// ## Schedule Options
+///
+/// How to pick contacts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleOptions {
+
+    /// Which windows to keep first (default `Greedy`)
+    pub strategy: Strategy,
+
+    /// Priority of each satellite by index, higher first, for the
+    /// `Priority` strategy. Satellites past the end have priority 0
+    /// (default none).
+    pub priorities: Vec<u32>,
+
+    /// Shortest window worth scheduling (minutes, default 0)
+    pub min_duration: f64,
+
+    /// Time a station needs after one contact before the next, to slew
+    /// and reconfigure, and a satellite between stations (minutes,
+    /// default 0)
+    pub turnaround: f64,
+}
+
+impl Default for ScheduleOptions {
+    fn default() -> ScheduleOptions {
+        ScheduleOptions {
+            strategy: Strategy::Greedy,
+            priorities: Vec::new(),
+            min_duration: 0.0,
+            turnaround: 0.0,
+        }
+    }
+}
+
+/// ## Schedule
+///
+/// A conflict free schedule from `contacts`, in order of start. A window
+/// is kept if it doesn't conflict with any kept before it, trying windows
+/// in the order `options.strategy` gives.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::angle::Deg;
+/// use sgp4::coordinates::Geodetic;
+/// use sgp4::observer::Observer;
+/// use sgp4::passes::PassOptions;
+/// use sgp4::scheduling::{contact_windows, schedule, ScheduleOptions, Strategy};
+///
+/// let iss = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+/// let mut trailing = iss.clone();
+/// trailing.mean_anomaly += Deg(10.0);
+///
+/// let station = |lat: f64, lon: f64| Observer::new(Geodetic {
+///     lat: lat.to_radians(),
+///     lon: lon.to_radians(),
+///     alt: 0.0,
+/// });
+/// let stations = [station(45.52, -122.68), station(47.61, -122.33)];
+///
+/// let start = iss.epoch_julian_date();
+/// let windows = contact_windows(&[iss, trailing], &stations, start, start + 1.0, &PassOptions::default()).unwrap();
+///
+/// // The ISS comes first
+/// let options = ScheduleOptions {
+///     strategy: Strategy::Priority,
+///     priorities: vec![1, 0],
+///     turnaround: 2.0,
+///     ..ScheduleOptions::default()
+/// };
+/// for contact in schedule(&windows, &options) {
+///     println!("satellite {} from station {} for {:.1} minutes",
+///              contact.satellite, contact.station, contact.duration());
+/// }
+/// ```
+pub fn schedule(contacts: &[Contact], options: &ScheduleOptions) -> Vec<Contact> {
+    let priority = |contact: &Contact| options.priorities.get(contact.satellite).cloned().unwrap_or(0);
+    let mut candidates: Vec<&Contact> = contacts.iter()
+        .filter(|contact| contact.duration() >= options.min_duration)
+        .collect();
+    candidates.sort_by(|a, b| {
+        let by_priority = match options.strategy {
+            Strategy::Greedy => Ordering::Equal,
+            Strategy::Priority => priority(b).cmp(&priority(a)),
+        };
+        by_priority.then(a.end.partial_cmp(&b.end).unwrap_or(Ordering::Equal))
+    });
+
+    let mut kept: Vec<Contact> = Vec::new();
+    for candidate in candidates {
+        if !kept.iter().any(|contact| contact.conflicts_with(candidate, options.turnaround)) {
+            kept.push(*candidate);
+        }
+    }
+    kept.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap_or(Ordering::Equal));
+    kept
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use angle::Deg;
+    use coordinates::Geodetic;
+    use test_support::iss;
+
+    fn station(lat: f64, lon: f64) -> Observer {
+        Observer::new(Geodetic { lat: lat.to_radians(), lon: lon.to_radians(), alt: 0.0 })
+    }
+
+    /// A window with made up times (minutes)
+    fn window(satellite: usize, station: usize, start: f64, end: f64) -> Contact {
+        Contact {
+            satellite,
+            station,
+            start: start / MINUTES_PER_DAY,
+            end: end / MINUTES_PER_DAY,
+            pass: Pass {
+                rise: start,
+                rise_azimuth: 0.0,
+                max_elevation_time: (start + end) / 2.0,
+                max_elevation: 0.5,
+                set: end,
+                set_azimuth: 0.0,
+            },
+        }
+    }
+
+    fn no_conflicts(schedule: &[Contact], turnaround: f64) -> bool {
+        schedule.iter().enumerate().all(|(index, a)| {
+            schedule[index + 1..].iter().all(|b| !a.conflicts_with(b, turnaround))
+        })
+    }
+
+    #[test]
+    fn windows() {
+        let mut trailing = iss();
+        trailing.mean_anomaly += Deg(10.0);
+        let satellites = [iss(), trailing];
+        let stations = [station(45.52, -122.68), station(47.61, -122.33), station(-33.87, 151.21)];
+        let start = iss().epoch_julian_date();
+        let contacts = contact_windows(&satellites, &stations, start, start + 1.0, &PassOptions::default()).unwrap();
+
+        // Every pass of every pair, in order
+        let passes = ::passes::passes(&iss(), &stations[2], 0.0, MINUTES_PER_DAY).unwrap();
+        let sydney: Vec<_> = contacts.iter().filter(|c| c.satellite == 0 && c.station == 2).collect();
+        assert_eq!(sydney.len(), passes.len());
+        assert_eq!(sydney[0].pass, passes[0]);
+        assert!(contacts.windows(2).all(|pair| pair[0].start <= pair[1].start));
+        assert!(contacts.iter().all(|c| c.start >= start && c.end <= start + 1.0));
+
+        // Portland and Seattle see the same passes at the same times, so
+        // only one of them works each
+        let kept = schedule(&contacts, &ScheduleOptions::default());
+        assert!(kept.len() < contacts.len());
+        assert!(no_conflicts(&kept, 0.0));
+
+        let options = ScheduleOptions { strategy: Strategy::Priority, priorities: vec![0, 5], turnaround: 5.0, ..ScheduleOptions::default() };
+        let kept = schedule(&contacts, &options);
+        assert!(no_conflicts(&kept, 5.0));
+        let trailing = contacts.iter().filter(|c| c.satellite == 1 && c.station != 2).count();
+        assert_eq!(kept.iter().filter(|c| c.satellite == 1 && c.station != 2).count(), trailing / 2);
+    }
+
+    #[test]
+    fn strategies() {
+        // One long pass of satellite 0 overlapping two short ones of
+        // satellite 1, all at the same station
+        let contacts = [window(0, 0, 0.0, 10.0), window(1, 0, 1.0, 4.0), window(1, 0, 6.0, 9.0)];
+
+        let kept = schedule(&contacts, &ScheduleOptions::default());
+        assert_eq!(kept, [contacts[1], contacts[2]]);
+
+        let options = ScheduleOptions { strategy: Strategy::Priority, priorities: vec![1], ..ScheduleOptions::default() };
+        assert_eq!(schedule(&contacts, &options), [contacts[0]]);
+
+        // Too short, and too close together to turn around between
+        let options = ScheduleOptions { min_duration: 5.0, ..ScheduleOptions::default() };
+        assert_eq!(schedule(&contacts, &options), [contacts[0]]);
+        let options = ScheduleOptions { turnaround: 3.0, ..ScheduleOptions::default() };
+        assert_eq!(schedule(&contacts, &options), [contacts[1]]);
+
+        // Different stations and satellites don't conflict
+        let contacts = [window(0, 0, 0.0, 10.0), window(1, 1, 1.0, 4.0)];
+        assert_eq!(schedule(&contacts, &ScheduleOptions::default()).len(), 2);
+        let contacts = [window(0, 0, 0.0, 10.0), window(0, 1, 1.0, 4.0)];
+        assert_eq!(schedule(&contacts, &ScheduleOptions::default()).len(), 1);
+    }
+
+    #[test]
+    fn decayed() {
+        let mut decaying = iss();
+        decaying.bstar = 0.5;
+        let start = iss().epoch_julian_date();
+        let error = contact_windows(&[iss(), decaying], &[station(45.52, -122.68)], start, start + 30.0, &PassOptions::default()).unwrap_err();
+        assert_eq!(error.satellite, 1);
+        assert_eq!(error.error, PropagationError::Decayed);
+    }
+}
@@ -0,0 +1,431 @@
+/*!  # Screening
+
+Search a whole catalog for conjunctions: every pair of objects that comes
+within a threshold distance over a window of time. Requires the `std`
+feature, for threads.
+
+Checking every pair of a catalog of thousands of objects by propagating
+both is far too slow, so cheap geometric filters go first:
+
+1. **Apogee/perigee sieve.** Two orbits whose altitude ranges don't
+   overlap (within the threshold) can never meet.
+2. **Orbit path filter.** Two orbits can only meet near the line where
+   their planes cross, on the arc where the planes are closer than the
+   threshold. If their radii differ by more than the threshold all along
+   that arc, they can't meet. The planes turn under $J_2$, so this is
+   checked once a day through the window.
+
+Both filters use the mean elements, so they allow a `pad` for the short
+period and drag effects SGP4 adds. The pairs that are left are searched in
+time with [`conjunction::approaches`](../conjunction/fn.approaches.html),
+spread across threads.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+use std::thread;
+
+use conjunction::{self, Approach, Object};
+use coordinates::{cross, dot};
+use elements::Elements;
+use gravity::GravityModel;
+use tle::TLE;
+use {kozai_to_brouwer, OpsMode, PropagationError, MINUTES_PER_DAY};
+
+
+/// Planes closer than this (radians) are treated as coplanar: the path
+/// filter can't rule them out
+const COPLANAR: f64 = 0.5 * PI / 180.0;
+
+/// Points on each side of a crossing to compare the radii at
+const ARC_SAMPLES: usize = 16;
+
+/// ## Screening Options
+///
+/// Thresholds and effort for `screen`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreeningOptions {
+
+    /// Report approaches closer than this (kilometers, default 10)
+    pub threshold: f64,
+
+    /// Extra margin the filters allow for SGP4's departures from the mean
+    /// orbit (kilometers, default 25)
+    pub pad: f64,
+
+    /// Spacing of the coarse time search (minutes, default 1)
+    pub step: f64,
+
+    /// Threads to search with, or 0 (the default) for one per CPU
+    pub threads: usize,
+}
+
+impl Default for ScreeningOptions {
+    fn default() -> ScreeningOptions {
+        ScreeningOptions {
+            threshold: 10.0,
+            pad: 25.0,
+            step: conjunction::DEFAULT_STEP,
+            threads: 0,
+        }
+    }
+}
+
+/// ## Conjunction
+///
+/// Two catalog objects coming within the threshold distance.
+#[derive(Debug, PartialEq)]
+pub struct Conjunction {
+
+    /// Index of the first object in the catalog
+    pub first: usize,
+
+    /// Index of the second object in the catalog
+    pub second: usize,
+
+    /// Time and geometry of the closest approach
+    pub approach: Approach,
+}
+
+/// ## Screening
+///
+/// What `screen` found, and what it couldn't check.
+#[derive(Debug, Default)]
+pub struct Screening {
+
+    /// Every approach closer than the threshold, in time order
+    pub conjunctions: Vec<Conjunction>,
+
+    /// Objects that couldn't be initialized, by catalog index. They're left
+    /// out of every pair.
+    pub unusable: Vec<(usize, PropagationError)>,
+
+    /// Pairs (by catalog index) whose search stopped early because one of
+    /// the objects couldn't be propagated, such as on reentry
+    pub incomplete: Vec<(usize, usize, PropagationError)>,
+
+    /// Pairs ruled out by the apogee/perigee sieve
+    pub sieved: usize,
+
+    /// Pairs ruled out by the orbit path filter
+    pub filtered: usize,
+}
+
+/// The mean orbit of a catalog object, for the filters
+#[derive(Debug)]
+struct Orbit {
+    object: Object,
+    /// Perigee and apogee radii (kilometers)
+    perigee: f64,
+    apogee: f64,
+    /// Semi-latus rectum (kilometers)
+    p: f64,
+    e: f64,
+    i: f64,
+    raan: f64,
+    argp: f64,
+    /// Secular J₂ rates of the node and perigee (radians/day)
+    raan_dot: f64,
+    argp_dot: f64,
+}
+
+impl Orbit {
+
+    fn new(tle: &TLE) -> Result<Orbit, PropagationError> {
+        let object = Object::new(tle)?;
+        let gravity = GravityModel::default();
+        let elements = Elements::from_tle(tle);
+        let (e, i) = (elements.eccentricity, elements.inclination.0);
+        let (n, a) = kozai_to_brouwer(elements.mean_motion, e, i, &gravity, OpsMode::Improved);
+
+        // In Earth radii for the rates, then kilometers
+        let p = a * (1.0 - e * e);
+        let rate = 1.5 * gravity.J2 * n / (p * p) * MINUTES_PER_DAY;
+        let cos_i = i.cos();
+
+        Ok(Orbit {
+            object,
+            perigee: a * (1.0 - e) * gravity.radius,
+            apogee: a * (1.0 + e) * gravity.radius,
+            p: p * gravity.radius,
+            e,
+            i,
+            raan: elements.raan.0,
+            argp: elements.argp.0,
+            raan_dot: -rate * cos_i,
+            argp_dot: 0.5 * rate * (5.0 * cos_i * cos_i - 1.0),
+        })
+    }
+
+    /// Node and argument of perigee at a Julian date
+    fn orientation(&self, julian_date: f64) -> (f64, f64) {
+        let days = julian_date - self.object.epoch();
+        (self.raan + self.raan_dot * days, self.argp + self.argp_dot * days)
+    }
+
+    /// Unit normal to the orbit plane, and the directions of the node and
+    /// 90° past it
+    fn axes(&self, julian_date: f64) -> ([f64; 3], [f64; 3], [f64; 3]) {
+        let (raan, _) = self.orientation(julian_date);
+        let (sin_raan, cos_raan) = raan.sin_cos();
+        let (sin_i, cos_i) = self.i.sin_cos();
+        let normal = [sin_i * sin_raan, -sin_i * cos_raan, cos_i];
+        let node = [cos_raan, sin_raan, 0.0];
+        (normal, node, cross(&normal, &node))
+    }
+
+    /// Radius (kilometers) where the orbit passes through a direction in
+    /// its plane
+    fn radius_towards(&self, direction: &[f64; 3], julian_date: f64) -> f64 {
+        let (_, node, ninety) = self.axes(julian_date);
+        let (_, argp) = self.orientation(julian_date);
+        let u = dot(direction, &ninety).atan2(dot(direction, &node));
+        self.p / (1.0 + self.e * (u - argp).cos())
+    }
+}
+
+/// Whether the altitude ranges of two orbits come within `margin`
+fn overlap(a: &Orbit, b: &Orbit, margin: f64) -> bool {
+    a.perigee.max(b.perigee) - a.apogee.min(b.apogee) <= margin
+}
+
+/// Smallest difference in radius (kilometers) of two orbits near the
+/// points where their planes cross, over the arcs where the planes are
+/// within `margin` of each other, or `None` if they're too close to
+/// coplanar to tell
+fn path_separation(a: &Orbit, b: &Orbit, julian_date: f64, margin: f64) -> Option<f64> {
+    let (normal_a, _, _) = a.axes(julian_date);
+    let (normal_b, _, _) = b.axes(julian_date);
+    let line = cross(&normal_a, &normal_b);
+    let sin_mutual = dot(&line, &line).sqrt();
+    if sin_mutual < COPLANAR.sin() {
+        return None;
+    }
+
+    // At an angle θ from the line the planes are r sin θ sin Δ apart
+    let arc = (margin / (a.perigee.min(b.perigee) * sin_mutual)).min(1.0).asin();
+
+    let mut closest = f64::INFINITY;
+    for &sign in [1.0, -1.0].iter() {
+        let towards = [sign * line[0] / sin_mutual, sign * line[1] / sin_mutual, sign * line[2] / sin_mutual];
+        let across_a = cross(&normal_a, &towards);
+        let across_b = cross(&normal_b, &towards);
+        for sample in 0..=2 * ARC_SAMPLES {
+            let (sin, cos) = (arc * (sample as f64 / ARC_SAMPLES as f64 - 1.0)).sin_cos();
+            let in_a = [cos * towards[0] + sin * across_a[0], cos * towards[1] + sin * across_a[1], cos * towards[2] + sin * across_a[2]];
+            let in_b = [cos * towards[0] + sin * across_b[0], cos * towards[1] + sin * across_b[1], cos * towards[2] + sin * across_b[2]];
+            closest = closest.min((a.radius_towards(&in_a, julian_date) - b.radius_towards(&in_b, julian_date)).abs());
+        }
+    }
+    Some(closest)
+}
+
+/// Whether the orbit paths come within `margin` at some point from `start`
+/// to `end`, checking once a day
+fn paths_meet(a: &Orbit, b: &Orbit, start: f64, end: f64, margin: f64) -> bool {
+    let days = (end - start).ceil().max(1.0) as usize;
+    (0..=days)
+        .map(|day| (start + day as f64).min(end))
+        .any(|julian_date| path_separation(a, b, julian_date, margin).is_none_or(|separation| separation <= margin))
+}
+
+/// ## Screen
+///
+/// Every approach between two objects of `catalog` closer than
+/// `options.threshold` from `start` to `end` (Julian dates, UTC).
+///
+/// ### Panics
+///
+/// If `options.step` isn't positive.
+///
+/// ### Example
+///
+/// ```no_run
+/// extern crate sgp4;
+///
+/// use sgp4::screening::{screen, ScreeningOptions};
+///
+/// let catalog = sgp4::tle::load_from_file("active.txt").unwrap();
+/// let start = sgp4::time::julian_date(2016, 7, 29.0);
+///
+/// let screening = screen(&catalog, start, start + 7.0, &ScreeningOptions::default());
+/// for conjunction in &screening.conjunctions {
+///     println!("{} {} {:.3} km at {:.5}",
+///              catalog[conjunction.first].name,
+///              catalog[conjunction.second].name,
+///              conjunction.approach.miss_distance,
+///              conjunction.approach.time);
+/// }
+/// ```
+pub fn screen(catalog: &[TLE], start: f64, end: f64, options: &ScreeningOptions) -> Screening {
+    assert!(options.step > 0.0, "screening step must be positive");
+
+    let mut screening = Screening::default();
+    let mut orbits = Vec::with_capacity(catalog.len());
+    for (index, tle) in catalog.iter().enumerate() {
+        match Orbit::new(tle) {
+            Ok(orbit) => orbits.push((index, orbit)),
+            Err(error) => screening.unusable.push((index, error)),
+        }
+    }
+
+    // Filter every pair
+    let margin = options.threshold + options.pad;
+    let mut candidates = Vec::new();
+    for (position, &(first, ref a)) in orbits.iter().enumerate() {
+        for &(second, ref b) in &orbits[position + 1..] {
+            if !overlap(a, b, margin) {
+                screening.sieved += 1;
+            } else if !paths_meet(a, b, start, end, margin) {
+                screening.filtered += 1;
+            } else {
+                candidates.push((first, a, second, b));
+            }
+        }
+    }
+
+    // Search what's left in time, each thread taking every nth pair
+    let threads = match options.threads {
+        0 => thread::available_parallelism().map_or(1, |count| count.get()),
+        threads => threads,
+    };
+    let candidates = &candidates;
+    let results = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| scope.spawn(move || {
+                let mut found = Vec::new();
+                for &(first, a, second, b) in candidates.iter().skip(worker).step_by(threads) {
+                    match conjunction::approaches(&a.object, &b.object, start, end, options.step) {
+                        Ok(approaches) => found.extend(approaches.into_iter()
+                            .filter(|approach| approach.miss_distance < options.threshold)
+                            .map(|approach| Ok(Conjunction { first, second, approach }))),
+                        Err(error) => found.push(Err((first, second, error))),
+                    }
+                }
+                found
+            }))
+            .collect();
+        workers.into_iter().map(|worker| worker.join().expect("screening thread panicked")).collect::<Vec<_>>()
+    });
+
+    for result in results.into_iter().flatten() {
+        match result {
+            Ok(conjunction) => screening.conjunctions.push(conjunction),
+            Err(incomplete) => screening.incomplete.push(incomplete),
+        }
+    }
+    screening.conjunctions.sort_by(|a, b| a.approach.time.total_cmp(&b.approach.time));
+    screening.incomplete.sort_by_key(|&(first, second, _)| (first, second));
+
+    screening
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use angle::Deg;
+    use test_support::iss;
+
+    fn catalog() -> Vec<TLE> {
+        let iss = iss();
+
+        // Meets the ISS where the planes cross, twice an orbit
+        let mut neighbour = iss.clone();
+        neighbour.sat_number = 90001;
+        neighbour.raan += Deg(5.0);
+        neighbour.mean_anomaly -= Deg(3.1);
+
+        // A few hundred kilometers higher
+        let mut higher = iss.clone();
+        higher.sat_number = 90002;
+        higher.mean_motion = 14.2;
+
+        // Same altitude, but half an orbit away
+        let mut opposite = iss.clone();
+        opposite.sat_number = 90003;
+        opposite.raan += Deg(5.0);
+        opposite.mean_anomaly += Deg(180.0);
+
+        // Can't be propagated at all
+        let mut broken = iss.clone();
+        broken.sat_number = 90004;
+        broken.e = 1.5;
+
+        vec![iss, neighbour, higher, opposite, broken]
+    }
+
+    #[test]
+    fn screen_catalog() {
+        let catalog = catalog();
+        let start = catalog[0].epoch_julian_date();
+        let options = ScreeningOptions { threads: 3, ..ScreeningOptions::default() };
+        let screening = screen(&catalog, start, start + 0.5, &options);
+
+        assert_eq!(screening.unusable, vec![(4, PropagationError::BadElements)]);
+        assert!(screening.incomplete.is_empty());
+
+        // The higher orbit is sieved out against the other three
+        assert_eq!(screening.sieved, 3);
+
+        // Only the neighbour meets the ISS, about 15 times in half a day
+        assert!(screening.conjunctions.len() >= 14 && screening.conjunctions.len() <= 16);
+        for conjunction in &screening.conjunctions {
+            assert_eq!((conjunction.first, conjunction.second), (0, 1));
+            assert!(conjunction.approach.miss_distance < options.threshold);
+        }
+        assert!(screening.conjunctions.windows(2).all(|pair| pair[0].approach.time <= pair[1].approach.time));
+
+        // The same on one thread
+        let single = screen(&catalog, start, start + 0.5, &ScreeningOptions { threads: 1, ..options });
+        assert_eq!(single.conjunctions, screening.conjunctions);
+    }
+
+    #[test]
+    fn path_filter() {
+        let iss = iss();
+        let start = iss.epoch_julian_date();
+
+        // An equatorial orbit and an eccentric polar one, which crosses the
+        // equator at perigee and apogee
+        let mut equatorial = iss.clone();
+        equatorial.i = Deg(0.0);
+        equatorial.raan = Deg(0.0);
+        equatorial.e = 0.0;
+        let mut polar = equatorial.clone();
+        polar.i = Deg(90.0);
+        polar.e = 0.1;
+        polar.omega = Deg(0.0);
+
+        let (a, b) = (Orbit::new(&equatorial).unwrap(), Orbit::new(&polar).unwrap());
+        assert!(overlap(&a, &b, 0.0));
+        let separation = path_separation(&a, &b, start, 0.0).unwrap();
+        let expected = (a.perigee - b.perigee).abs().min((a.perigee - b.apogee).abs());
+        assert!((separation - expected).abs() < 1e-6);
+        assert!(!paths_meet(&a, &b, start, start + 1.0, 100.0));
+
+        // Tilted by only a degree, the eccentric orbit is well inside or
+        // outside the equatorial one where the planes cross, but reaches its
+        // radius along the arc where the planes are still close
+        let mut tilted = polar.clone();
+        tilted.i = Deg(1.0);
+        tilted.omega = Deg(80.0);
+        let b = Orbit::new(&tilted).unwrap();
+        assert!(path_separation(&a, &b, start, 0.0).unwrap() > 40.0);
+        assert!(path_separation(&a, &b, start, 35.0).unwrap() < 20.0);
+
+        // Circular orbits only differ by their sizes
+        polar.e = 0.0;
+        let b = Orbit::new(&polar).unwrap();
+        assert!((path_separation(&a, &b, start, 0.0).unwrap() - (a.perigee - b.perigee).abs()).abs() < 1e-6);
+
+        // Nothing can be said about coplanar orbits
+        assert_eq!(path_separation(&a, &a, start, 0.0), None);
+        assert!(paths_meet(&a, &a, start, start + 1.0, 0.0));
+    }
+}
@@ -0,0 +1,235 @@
+/*!  # Space-Track
+
+A blocking client for the [Space-Track.org](https://www.space-track.org)
+API, with the `spacetrack` feature.
+
+Space-Track is the 18th Space Defense Squadron's own distribution of
+element sets, and needs an account. [`SpaceTrack::login`](struct.SpaceTrack.html#method.login)
+signs in and keeps the session cookie; queries then go to the GP classes
+(`gp` for the latest elements of each object, `gp_history` for everything
+published) in JSON, and each record is read as an OMM into a
+[`TLE`](../tle/struct.TLE.html).
+
+Space-Track limits accounts to 30 requests a minute and 300 an hour, and
+asks that the same data isn't downloaded more than once an hour: ask for
+many satellites in one query rather than one query each.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use ureq;
+
+use fetch::{self, FetchError};
+use omm;
+use tle::TLE;
+
+
+/// The Space-Track server
+pub const SPACE_TRACK_URL: &str = "https://www.space-track.org";
+
+/// ## Space-Track
+///
+/// A signed in Space-Track session.
+///
+/// ### Example
+///
+/// ```no_run
+/// extern crate sgp4;
+///
+/// use sgp4::spacetrack::SpaceTrack;
+///
+/// let session = SpaceTrack::login("user@example.com", "password").unwrap();
+///
+/// let latest = session.latest(&[25544, 48274]).unwrap();
+/// let july = session.history(25544, "2016-07-01", "2016-08-01").unwrap();
+/// println!("{} current, {} from July 2016", latest.len(), july.len());
+///
+/// session.logout().unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpaceTrack {
+    agent: ureq::Agent,
+    base_url: String,
+    cookie: String,
+}
+
+impl SpaceTrack {
+
+    /// Sign in to Space-Track.org
+    pub fn login(identity: &str, password: &str) -> Result<SpaceTrack, FetchError> {
+        SpaceTrack::login_to(SPACE_TRACK_URL, identity, password)
+    }
+
+    /// Sign in to a server with the Space-Track API at `base_url`
+    pub fn login_to(base_url: &str, identity: &str, password: &str) -> Result<SpaceTrack, FetchError> {
+        let agent = fetch::agent();
+        let base_url = String::from(base_url.trim_end_matches('/'));
+        let response = agent.post(&format!("{}/ajaxauth/login", base_url))
+            .send_form(&[("identity", identity), ("password", password)])?;
+
+        // The session is a cookie; a refused login is still a 200, with a
+        // message in the body instead
+        let cookie = response.all("Set-Cookie")
+            .iter()
+            .filter_map(|header| header.split(';').next())
+            .map(str::trim)
+            .filter(|pair| pair.contains('='))
+            .collect::<Vec<_>>()
+            .join("; ");
+        let body = response.into_string()?;
+        if cookie.is_empty() || body.contains("Failed") {
+            return Err(FetchError::Login);
+        }
+
+        Ok(SpaceTrack { agent, base_url, cookie })
+    }
+
+    /// ## Latest
+    ///
+    /// The newest element set for each satellite.
+    pub fn latest(&self, sat_numbers: &[u32]) -> Result<Vec<TLE>, FetchError> {
+        self.query(&format!("class/gp/NORAD_CAT_ID/{}/orderby/NORAD_CAT_ID", list(sat_numbers)))
+    }
+
+    /// ## History
+    ///
+    /// Every element set for a satellite with an epoch from `start` up to
+    /// `end`, oldest first. Dates are UTC, as `2016-07-01` or
+    /// `2016-07-01T12:00:00`.
+    pub fn history(&self, sat_number: u32, start: &str, end: &str) -> Result<Vec<TLE>, FetchError> {
+        self.query(&format!("class/gp_history/NORAD_CAT_ID/{}/EPOCH/{}--{}/orderby/EPOCH", sat_number, start, end))
+    }
+
+    /// ## Query
+    ///
+    /// Any query of the GP classes, as the path that follows
+    /// `/basicspacedata/query/` in the API, without the format.
+    pub fn query(&self, query: &str) -> Result<Vec<TLE>, FetchError> {
+        let url = format!("{}/basicspacedata/query/{}/format/json", self.base_url, query.trim_matches('/'));
+        let text = self.agent.get(&url)
+            .set("Cookie", &self.cookie)
+            .call()?
+            .into_string()?;
+        Ok(omm::parse_json(&text)?)
+    }
+
+    /// Sign out, ending the session
+    pub fn logout(self) -> Result<(), FetchError> {
+        self.agent.get(&format!("{}/ajaxauth/logout", self.base_url))
+            .set("Cookie", &self.cookie)
+            .call()?;
+        Ok(())
+    }
+}
+
+/// Satellite numbers as a comma separated list
+fn list(sat_numbers: &[u32]) -> String {
+    sat_numbers.iter()
+        .map(|number| number.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    const GP: &str = r#"[{
+        "OBJECT_NAME": "ISS (ZARYA)",
+        "OBJECT_ID": "1998-067A",
+        "EPOCH": "2016-07-28T14:21:26.330688",
+        "MEAN_MOTION": "15.54888439",
+        "ECCENTRICITY": "0.00017800",
+        "INCLINATION": "51.6406",
+        "RA_OF_ASC_NODE": "211.4156",
+        "ARG_OF_PERICENTER": "85.8307",
+        "MEAN_ANOMALY": "274.3426",
+        "EPHEMERIS_TYPE": "0",
+        "CLASSIFICATION_TYPE": "U",
+        "NORAD_CAT_ID": "25544",
+        "ELEMENT_SET_NO": "999",
+        "REV_AT_EPOCH": "1143",
+        "BSTAR": "0.000011901",
+        "MEAN_MOTION_DOT": "0.00000812",
+        "MEAN_MOTION_DDOT": "0"
+    }]"#;
+
+    /// Answer each request with the next canned response, returning each
+    /// request's headers and body
+    fn serve(responses: Vec<String>) -> (String, thread::JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            responses.into_iter().map(|response| {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                    request.push_str(&line);
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                request.push_str(&String::from_utf8(body).unwrap());
+                stream.write_all(response.as_bytes()).unwrap();
+                request
+            }).collect()
+        });
+        (url, server)
+    }
+
+    fn response(headers: &str, body: &str) -> String {
+        format!("HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: {}\r\n{}\r\n{}", body.len(), headers, body)
+    }
+
+    #[test]
+    fn session() {
+        let (url, server) = serve(vec![
+            response("Set-Cookie: chocolatechip=abc123; expires=Fri, 17-Oct-2026 00:00:00 GMT; path=/\r\n", "\"\""),
+            response("Content-Type: application/json\r\n", GP),
+            response("", "\"Successfully logged out\""),
+        ]);
+
+        let session = SpaceTrack::login_to(&url, "user@example.com", "p&ss").unwrap();
+        let tles = session.latest(&[25544, 48274]).unwrap();
+        assert_eq!(tles.len(), 1);
+        assert_eq!(tles[0].sat_number, 25544);
+        assert_eq!(tles[0].mean_motion, 15.54888439);
+        session.logout().unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /ajaxauth/login "));
+        assert!(requests[0].ends_with("identity=user%40example.com&password=p%26ss"));
+        assert!(requests[1].starts_with("GET /basicspacedata/query/class/gp/NORAD_CAT_ID/25544,48274/orderby/NORAD_CAT_ID/format/json "));
+        assert!(requests[1].contains("chocolatechip=abc123\r\n"));
+        assert!(requests[2].starts_with("GET /ajaxauth/logout "));
+    }
+
+    #[test]
+    fn refused() {
+        let (url, server) = serve(vec![response("", r#"{"Login":"Failed"}"#)]);
+        match SpaceTrack::login_to(&url, "user@example.com", "wrong") {
+            Err(FetchError::Login) => {}
+            other => panic!("{:?}", other),
+        }
+        server.join().unwrap();
+    }
+}
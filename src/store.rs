@@ -0,0 +1,356 @@
+/*!  # Store
+
+A persistent archive of element sets in SQLite, with the `sqlite`
+feature.
+
+Each element set is kept by its satellite number and epoch, as the two
+lines it was published as, so reading one back gives exactly the TLE that
+went in. Loading the same file or download twice doesn't grow the archive:
+an element set already stored is skipped, and one with the same satellite
+and epoch but different lines (a correction) replaces it. Epochs are Julian
+dates (UTC), and the archive answers the question most historical analysis
+starts with: which elements were current for a satellite at some time in
+the past.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::error;
+use std::fmt;
+use std::path::Path;
+
+use rusqlite::{self, params, Connection, OptionalExtension, Row};
+
+use catalog::Catalog;
+use tle::{self, TleFormatError, TleParseError, TLE};
+
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS tle (
+        sat_number INTEGER NOT NULL,
+        epoch REAL NOT NULL,
+        name TEXT NOT NULL,
+        line1 TEXT NOT NULL,
+        line2 TEXT NOT NULL,
+        PRIMARY KEY (sat_number, epoch)
+    );
+";
+
+const INSERT: &str = "
+    INSERT INTO tle (sat_number, epoch, name, line1, line2) VALUES (?1, ?2, ?3, ?4, ?5)
+    ON CONFLICT (sat_number, epoch) DO UPDATE
+        SET name = excluded.name, line1 = excluded.line1, line2 = excluded.line2
+        WHERE line1 != excluded.line1 OR line2 != excluded.line2
+";
+
+/// ## Store Errors
+///
+/// Why the archive couldn't be read or written.
+#[derive(Debug)]
+pub enum StoreError {
+
+    /// The database failed
+    Sqlite(rusqlite::Error),
+
+    /// An element set's lines don't parse
+    Parse(TleParseError),
+
+    /// An element set has a value that doesn't fit its columns
+    Format(TleFormatError),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StoreError::Sqlite(ref error) => write!(f, "{}", error),
+            StoreError::Parse(ref error) => write!(f, "{}", error),
+            StoreError::Format(ref error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            StoreError::Sqlite(ref error) => Some(error),
+            StoreError::Parse(ref error) => Some(error),
+            StoreError::Format(ref error) => Some(error),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(error: rusqlite::Error) -> StoreError {
+        StoreError::Sqlite(error)
+    }
+}
+
+impl From<TleParseError> for StoreError {
+    fn from(error: TleParseError) -> StoreError {
+        StoreError::Parse(error)
+    }
+}
+
+impl From<TleFormatError> for StoreError {
+    fn from(error: TleFormatError) -> StoreError {
+        StoreError::Format(error)
+    }
+}
+
+/// The name and lines of a row
+type Lines = (String, String, String);
+
+fn lines(row: &Row) -> rusqlite::Result<Lines> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+}
+
+fn parse((name, line1, line2): Lines) -> Result<TLE, StoreError> {
+    Ok(tle::load_from_str(&name, &line1, &line2)?)
+}
+
+/// ## Store
+///
+/// An archive of element sets in an SQLite database.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::store::Store;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let mut store = Store::open_in_memory().unwrap();
+/// assert!(store.insert(&tle).unwrap());
+///
+/// // The same lines again are skipped
+/// assert!(!store.insert(&tle).unwrap());
+///
+/// // The elements that were current a day later
+/// let current = store.latest_before(25544, tle.epoch_julian_date() + 1.0).unwrap().unwrap();
+/// assert_eq!(current.to_lines().unwrap(), tle.to_lines().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+
+    /// Open the archive in the database file at `path`, creating it if need
+    /// be
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Store, StoreError> {
+        Store::with_connection(Connection::open(path)?)
+    }
+
+    /// A new archive in memory, gone when it's dropped
+    pub fn open_in_memory() -> Result<Store, StoreError> {
+        Store::with_connection(Connection::open_in_memory()?)
+    }
+
+    /// Keep the archive in an open database, alongside any other tables in
+    /// it
+    pub fn with_connection(connection: Connection) -> Result<Store, StoreError> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Store { connection })
+    }
+
+    /// ## Insert
+    ///
+    /// Store an element set, replacing one of the same satellite and epoch
+    /// with different lines. Whether anything changed: `false` if it was
+    /// already stored.
+    pub fn insert(&mut self, tle: &TLE) -> Result<bool, StoreError> {
+        Ok(self.insert_all(Some(tle))? == 1)
+    }
+
+    /// ## Insert All
+    ///
+    /// Store many element sets, such as a whole download, in one
+    /// transaction. How many were new or replaced others.
+    pub fn insert_all<'a, I: IntoIterator<Item = &'a TLE>>(&mut self, tles: I) -> Result<usize, StoreError> {
+        let transaction = self.connection.transaction()?;
+        let mut changed = 0;
+        {
+            let mut insert = transaction.prepare_cached(INSERT)?;
+            for tle in tles {
+                // Key on the epoch as written, which is rounded, so the
+                // same lines always land on the same row
+                let (line1, line2) = tle.to_lines()?;
+                let epoch = tle::load_from_str(&tle.name, &line1, &line2)?.epoch_julian_date();
+                changed += insert.execute(params![tle.sat_number, epoch, tle.name, line1, line2])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(changed)
+    }
+
+    /// ## Latest Before
+    ///
+    /// The element set for a satellite with the latest epoch at or before
+    /// `julian_date` (UTC), the one that was current then, or `None` if
+    /// there isn't one that old.
+    pub fn latest_before(&self, sat_number: u32, julian_date: f64) -> Result<Option<TLE>, StoreError> {
+        self.connection
+            .query_row(
+                "SELECT name, line1, line2 FROM tle WHERE sat_number = ?1 AND epoch <= ?2 ORDER BY epoch DESC LIMIT 1",
+                params![sat_number, julian_date],
+                lines,
+            )
+            .optional()?
+            .map(parse)
+            .transpose()
+    }
+
+    /// The newest element set for a satellite
+    pub fn latest(&self, sat_number: u32) -> Result<Option<TLE>, StoreError> {
+        self.latest_before(sat_number, f64::INFINITY)
+    }
+
+    /// ## History
+    ///
+    /// Every element set for a satellite with an epoch from `start` up to
+    /// `end` (Julian dates, UTC), oldest first.
+    pub fn history(&self, sat_number: u32, start: f64, end: f64) -> Result<Vec<TLE>, StoreError> {
+        let mut query = self.connection.prepare_cached(
+            "SELECT name, line1, line2 FROM tle WHERE sat_number = ?1 AND epoch >= ?2 AND epoch < ?3 ORDER BY epoch",
+        )?;
+        let rows = query.query_map(params![sat_number, start, end], lines)?;
+        rows.map(|row| parse(row?)).collect()
+    }
+
+    /// ## Catalog At
+    ///
+    /// The element sets that were current at `julian_date` (UTC): for each
+    /// satellite, the latest at or before then.
+    pub fn catalog_at(&self, julian_date: f64) -> Result<Catalog, StoreError> {
+        let mut query = self.connection.prepare_cached(
+            "SELECT name, line1, line2 FROM tle AS current WHERE epoch = (
+                SELECT MAX(epoch) FROM tle WHERE sat_number = current.sat_number AND epoch <= ?1
+            )",
+        )?;
+        let rows = query.query_map(params![julian_date], lines)?;
+        rows.map(|row| parse(row?)).collect()
+    }
+
+    /// Satellite numbers with anything stored, in order
+    pub fn satellites(&self) -> Result<Vec<u32>, StoreError> {
+        let mut query = self.connection.prepare_cached("SELECT DISTINCT sat_number FROM tle ORDER BY sat_number")?;
+        let rows = query.query_map([], |row| row.get(0))?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// How many element sets are stored
+    pub fn len(&self) -> Result<usize, StoreError> {
+        let count: i64 = self.connection.query_row("SELECT COUNT(*) FROM tle", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    /// Whether nothing is stored
+    pub fn is_empty(&self) -> Result<bool, StoreError> {
+        Ok(self.len()? == 0)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use angle::Deg;
+    use std::env;
+    use std::fs;
+
+    fn iss(epoch_day: f64, mean_motion: f64) -> TLE {
+        TLE::builder()
+            .name("ISS (ZARYA)")
+            .sat_number(25544)
+            .int_designator("98067A")
+            .epoch(2016, epoch_day)
+            .bstar(1.1901e-5)
+            .inclination(Deg(51.6406))
+            .raan(Deg(211.4156))
+            .eccentricity(0.000178)
+            .argp(Deg(85.8307))
+            .mean_anomaly(Deg(274.3426))
+            .mean_motion(mean_motion)
+            .build()
+            .unwrap()
+    }
+
+    /// The lines of each element set, to compare them
+    fn lines<'a, I: IntoIterator<Item = &'a TLE>>(tles: I) -> Vec<(String, String)> {
+        tles.into_iter().map(|tle| tle.to_lines().unwrap()).collect()
+    }
+
+    #[test]
+    fn deduplicate() {
+        let mut store = Store::open_in_memory().unwrap();
+        let tles = [iss(210.5, 15.5488), iss(211.5, 15.5489), iss(212.5, 15.5490)];
+        assert_eq!(store.insert_all(&tles).unwrap(), 3);
+        assert_eq!(store.insert_all(&tles).unwrap(), 0);
+        assert_eq!(store.len().unwrap(), 3);
+
+        // An epoch finer than the lines hold is the same element set once
+        // it's written out
+        let fine = iss(213.123456789123, 15.5491);
+        let (line1, line2) = fine.to_lines().unwrap();
+        assert!(store.insert(&fine).unwrap());
+        assert!(!store.insert(&tle::load_from_str(&fine.name, &line1, &line2).unwrap()).unwrap());
+        assert_eq!(store.len().unwrap(), 4);
+
+        // A correction of the same epoch replaces it
+        let corrected = iss(211.5, 15.5499);
+        assert!(store.insert(&corrected).unwrap());
+        assert_eq!(store.len().unwrap(), 4);
+        assert_eq!(lines(&store.latest_before(25544, tles[1].epoch_julian_date()).unwrap()), lines(Some(&corrected)));
+    }
+
+    #[test]
+    fn queries() {
+        let mut store = Store::open_in_memory().unwrap();
+        assert!(store.is_empty().unwrap());
+        let tles = [iss(210.5, 15.5488), iss(211.5, 15.5489), iss(212.5, 15.5490)];
+        let mut other = iss(211.0, 14.2);
+        other.sat_number = 43013;
+        other.name = String::from("NOAA 20");
+        store.insert_all(tles.iter().chain(Some(&other))).unwrap();
+
+        let epoch = |day: f64| tles[0].epoch_julian_date() + day - 210.5;
+        assert!(store.latest_before(25544, epoch(210.0)).unwrap().is_none());
+        assert_eq!(lines(&store.latest_before(25544, epoch(210.5)).unwrap()), lines(&tles[..1]));
+        assert_eq!(lines(&store.latest_before(25544, epoch(212.4)).unwrap()), lines(&tles[1..2]));
+        assert_eq!(lines(&store.latest(25544).unwrap()), lines(&tles[2..]));
+        assert!(store.latest(1).unwrap().is_none());
+
+        assert_eq!(lines(&store.history(25544, epoch(211.0), epoch(213.0)).unwrap()), lines(&tles[1..]));
+        assert_eq!(store.satellites().unwrap(), vec![25544, 43013]);
+
+        let catalog = store.catalog_at(epoch(211.2)).unwrap();
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(lines(catalog.get(25544)), lines(&tles[..1]));
+        assert_eq!(catalog.get(43013).unwrap().name, "NOAA 20");
+        assert_eq!(store.catalog_at(epoch(210.7)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn file() {
+        let path = env::temp_dir().join(format!("sgp4-store-{}.sqlite", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let tle = iss(210.5, 15.5488);
+        Store::open(&path).unwrap().insert(&tle).unwrap();
+
+        let store = Store::open(&path).unwrap();
+        assert_eq!(lines(&store.latest(25544).unwrap()), lines(Some(&tle)));
+        drop(store);
+        fs::remove_file(&path).unwrap();
+    }
+}
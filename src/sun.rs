@@ -0,0 +1,463 @@
+/*!  # Sun
+
+Where the Sun is, to about 0.01°, whether a satellite is in the Earth's
+shadow, and where an orbit's plane lies relative to the Sun: its beta
+angle, and the local time of its nodes.
+
+The solar ephemeris is the low precision series from the Astronomical
+Almanac (Vallado, algorithm 29), in the mean equator and equinox of date.
+That differs from TEME by the nutation in right ascension, a few
+arcseconds, which is far below the accuracy of either the series or SGP4.
+
+[`eclipse`](../eclipse/index.html) finds the times a satellite enters and
+leaves the shadow.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+
+use coordinates::{StateVector, TEME, WGS84_EQUATORIAL_RADIUS};
+use time::J2000;
+use tle::TLE;
+use {PropagationError, Propagator, MINUTES_PER_DAY};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
+
+/// Astronomical unit (kilometers)
+pub const AU: f64 = 149_597_870.7;
+
+/// Radius of the Sun's photosphere (kilometers)
+pub const SUN_RADIUS: f64 = 696_000.0;
+
+/// ## Position
+///
+/// Geocentric position of the Sun (kilometers) at a Julian date. The
+/// series is in terms of UT1 but UTC, or even TT, makes no difference at
+/// its accuracy.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// // Vallado, example 5-1: 2006 April 2, 00:00 UTC
+/// let sun = sgp4::sun::position(2453827.5);
+/// assert!((sun.X / sgp4::sun::AU - 0.9771945).abs() < 1e-5);
+/// ```
+pub fn position(julian_date: f64) -> TEME {
+    let T = (julian_date - J2000) / 36525.0;
+
+    // Mean longitude and mean anomaly (degrees)
+    let mean_longitude = mean_longitude(julian_date);
+    let M = (357.5291092 + 35999.05034 * T).to_radians();
+
+    let longitude = (mean_longitude + 1.914666471 * M.sin() + 0.019994643 * (2.0 * M).sin()).to_radians();
+    let distance = (1.000140612 - 0.016708617 * M.cos() - 0.000139589 * (2.0 * M).cos()) * AU;
+    let obliquity = (23.439291 - 0.0130042 * T).to_radians();
+
+    let (sin_longitude, cos_longitude) = longitude.sin_cos();
+    let (sin_obliquity, cos_obliquity) = obliquity.sin_cos();
+    TEME::new(
+        distance * cos_longitude,
+        distance * cos_obliquity * sin_longitude,
+        distance * sin_obliquity * sin_longitude,
+    )
+}
+
+/// Mean longitude of the Sun (degrees), which is also the right ascension
+/// of the mean Sun that mean solar time is kept by
+fn mean_longitude(julian_date: f64) -> f64 {
+    280.460 + 36000.771 * (julian_date - J2000) / 36525.0
+}
+
+/// Rate the mean Sun moves (degrees/day)
+const MEAN_LONGITUDE_RATE: f64 = 36000.771 / 36525.0;
+
+/// ## Illumination
+///
+/// How much of the Sun a satellite can see past the Earth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Illumination {
+
+    /// The whole Sun is visible
+    Sunlit,
+
+    /// The Earth covers part of the Sun
+    Penumbra,
+
+    /// The Earth covers all of the Sun
+    Umbra,
+}
+
+/// ## Illumination
+///
+/// Whether a satellite is in sunlight or the Earth's shadow, given its
+/// TEME state and the Sun's position (as from [`position`](fn.position.html))
+/// at the same instant.
+///
+/// The shadow is conical: the Earth (a sphere of the WGS84 equatorial
+/// radius) and the Sun are compared as disks seen from the satellite, so
+/// the penumbra has its true width. The atmosphere is ignored.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::sun::{self, Illumination};
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let state = sgp4::propagate(&tle, 0.0).unwrap();
+/// let sun = sun::position(tle.epoch_julian_date());
+/// if sun::illumination(&state, &sun) == Illumination::Sunlit {
+///     println!("in sunlight");
+/// }
+/// ```
+pub fn illumination(state: &StateVector, sun: &TEME) -> Illumination {
+    let (penumbra, umbra) = shadow_margins(&state.position, sun);
+    if penumbra >= 0.0 {
+        Illumination::Sunlit
+    } else if umbra <= 0.0 {
+        Illumination::Umbra
+    } else {
+        Illumination::Penumbra
+    }
+}
+
+/// How far (radians) the Sun's disk, seen from a satellite at `satellite`,
+/// is clear of the Earth's: the first margin is negative once the Earth
+/// covers any of the Sun, the second once it covers all of it. They change
+/// smoothly, so eclipse times can be found as their roots.
+pub(crate) fn shadow_margins(satellite: &TEME, sun: &TEME) -> (f64, f64) {
+    // From the satellite to the Sun and to the center of the Earth
+    let to_sun = [sun.X - satellite.X, sun.Y - satellite.Y, sun.Z - satellite.Z];
+    let to_earth = [-satellite.X, -satellite.Y, -satellite.Z];
+    let sun_distance = norm(&to_sun);
+    let earth_distance = norm(&to_earth);
+
+    // Apparent radii of the two disks and the angle between their centers
+    let earth_radius = (WGS84_EQUATORIAL_RADIUS / earth_distance).min(1.0).asin();
+    let sun_radius = (SUN_RADIUS / sun_distance).asin();
+    let cos_separation = (to_sun[0] * to_earth[0] + to_sun[1] * to_earth[1] + to_sun[2] * to_earth[2])
+                       / (sun_distance * earth_distance);
+    let separation = cos_separation.clamp(-1.0, 1.0).acos();
+
+    (separation - (earth_radius + sun_radius), separation - (earth_radius - sun_radius))
+}
+
+/// ## Beta Angle
+///
+/// The angle (radians) between the plane of a satellite's orbit and the
+/// direction of the Sun at a Julian date, positive when the Sun is on the
+/// side the orbit's angular momentum points to (north of the plane, for a
+/// prograde orbit). The plane is that of the mean elements, so the angle
+/// changes smoothly, with the node's precession and the seasons, rather
+/// than wobbling through each orbit.
+///
+/// The higher its magnitude, the more of each orbit the satellite spends in
+/// sunlight: a low Earth orbit isn't eclipsed at all above about 70°.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let beta = sgp4::sun::beta_angle(&tle, tle.epoch_julian_date()).unwrap();
+/// assert!((beta.to_degrees() - 69.0).abs() < 1.0);
+/// ```
+pub fn beta_angle(tle: &TLE, julian_date: f64) -> Result<f64, PropagationError> {
+    let propagator = Propagator::new(tle)?;
+    beta(&propagator, tle.epoch_julian_date(), julian_date)
+}
+
+/// The beta angle from a propagator for a TLE of epoch `epoch`
+fn beta(propagator: &Propagator, epoch: f64, julian_date: f64) -> Result<f64, PropagationError> {
+    let mean = propagator.mean_elements((julian_date - epoch) * MINUTES_PER_DAY)?;
+    let (sin_i, cos_i) = mean.inclination.sin_cos();
+    let (sin_raan, cos_raan) = mean.raan.sin_cos();
+    let normal = [sin_i * sin_raan, -sin_i * cos_raan, cos_i];
+
+    let sun = position(julian_date);
+    let sun = [sun.X, sun.Y, sun.Z];
+    let sin_beta = (normal[0] * sun[0] + normal[1] * sun[1] + normal[2] * sun[2]) / norm(&sun);
+    Ok(sin_beta.clamp(-1.0, 1.0).asin())
+}
+
+/// ## Daily Beta Angles
+///
+/// The beta angle of a TLE's orbit once a day from `start` to `end`
+/// (Julian dates), as `(julian_date, beta)` pairs, for plotting its
+/// history through a mission. See [`beta_angle`](fn.beta_angle.html).
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let start = tle.epoch_julian_date();
+/// for (julian_date, beta) in sgp4::sun::daily_beta_angles(&tle, start, start + 60.0).unwrap() {
+///     println!("{:.1} {:6.1}°", julian_date, beta.unwrap().to_degrees());
+/// }
+/// ```
+pub fn daily_beta_angles(tle: &TLE, start: f64, end: f64) -> Result<BetaAngles, PropagationError> {
+    let count = if end < start { 0 } else { (end - start + 1.0e-9).floor() as usize + 1 };
+    Ok(BetaAngles {
+        propagator: Propagator::new(tle)?,
+        epoch: tle.epoch_julian_date(),
+        start,
+        index: 0,
+        count,
+    })
+}
+
+/// ## Beta Angles
+///
+/// Iterator over beta angles a day apart, from
+/// [`daily_beta_angles`](fn.daily_beta_angles.html).
+#[derive(Debug, Clone)]
+pub struct BetaAngles {
+    propagator: Propagator,
+    epoch: f64,
+    start: f64,
+    index: usize,
+    count: usize,
+}
+
+impl Iterator for BetaAngles {
+    type Item = (f64, Result<f64, PropagationError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+        let julian_date = self.start + self.index as f64;
+        self.index += 1;
+        Some((julian_date, beta(&self.propagator, self.epoch, julian_date)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.count - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for BetaAngles {}
+
+/// ## Local Time of the Ascending Node
+///
+/// The mean local solar time (hours, 0 to 24) at which a satellite crosses
+/// the equator northbound, at a Julian date: how far round the ascending
+/// node of its mean elements is from the mean Sun, as an hour angle, plus
+/// noon. A sun synchronous orbit keeps it nearly fixed, a "10:30 orbit"
+/// crossing at 10.5; other orbits sweep through all of them.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let epoch = tle.epoch_julian_date();
+/// let ltan = sgp4::sun::ltan(&tle, epoch).unwrap();
+/// let ltdn = sgp4::sun::ltdn(&tle, epoch).unwrap();
+/// assert!(((ltdn - ltan).rem_euclid(24.0) - 12.0).abs() < 1e-9);
+///
+/// // The ISS's node is 24 hours round in a couple of months
+/// let drift = sgp4::sun::ltan_drift(&tle, epoch).unwrap();
+/// assert!((drift + 0.4).abs() < 0.01);
+/// ```
+pub fn ltan(tle: &TLE, julian_date: f64) -> Result<f64, PropagationError> {
+    let propagator = Propagator::new(tle)?;
+    let mean = propagator.mean_elements((julian_date - tle.epoch_julian_date()) * MINUTES_PER_DAY)?;
+    let hour_angle = mean.raan.to_degrees().0 - mean_longitude(julian_date);
+    Ok((hour_angle / 15.0 + 12.0).rem_euclid(24.0))
+}
+
+/// ## Local Time of the Descending Node
+///
+/// The mean local solar time (hours, 0 to 24) at which a satellite crosses
+/// the equator southbound, twelve hours from
+/// [the ascending node's](fn.ltan.html).
+pub fn ltdn(tle: &TLE, julian_date: f64) -> Result<f64, PropagationError> {
+    Ok((ltan(tle, julian_date)? + 12.0).rem_euclid(24.0))
+}
+
+/// ## Local Time of the Ascending Node Drift
+///
+/// How fast the [local time of the ascending node](fn.ltan.html) changes
+/// (hours/day) at a Julian date: the node's precession from $J_2$ less the
+/// mean Sun's motion. Nearly zero for a sun synchronous orbit, where what's
+/// left is what station keeping has to correct.
+pub fn ltan_drift(tle: &TLE, julian_date: f64) -> Result<f64, PropagationError> {
+    let propagator = Propagator::new(tle)?;
+    let mean = propagator.mean_elements((julian_date - tle.epoch_julian_date()) * MINUTES_PER_DAY)?;
+    let drift = mean.raan_rate().0 * MINUTES_PER_DAY - MEAN_LONGITUDE_RATE.to_radians();
+    Ok(drift * 12.0 / PI)
+}
+
+fn norm(vector: &[f64; 3]) -> f64 {
+    (vector[0] * vector[0] + vector[1] * vector[1] + vector[2] * vector[2]).sqrt()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use angle::{Deg, Rad};
+    use time;
+    use test_support;
+
+    fn at(X: f64, Y: f64, Z: f64) -> StateVector {
+        StateVector {
+            epoch: 0.0,
+            position: TEME::new(X, Y, Z),
+            velocity: TEME::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    #[test]
+    fn vallado_5_1() {
+        let sun = position(2453827.5);
+        assert!((sun.X / AU - 0.9771945).abs() < 1e-5);
+        assert!((sun.Y / AU - 0.1924424).abs() < 1e-5);
+        assert!((sun.Z / AU - 0.0834308).abs() < 1e-5);
+    }
+
+    #[test]
+    fn seasons() {
+        // Near the equinoxes and solstices of 2016
+        let equinox = position(time::julian_date(2016, 3, 20.19));
+        assert!((equinox.Z / equinox.X).abs() < 1e-3);
+        assert!(equinox.X > 0.0);
+
+        let solstice = position(time::julian_date(2016, 6, 20.95));
+        let declination = (solstice.Z / (solstice.X.powi(2) + solstice.Y.powi(2) + solstice.Z.powi(2)).sqrt()).asin();
+        assert!((declination.to_degrees() - 23.43).abs() < 0.01);
+    }
+
+    #[test]
+    fn shadow() {
+        let sun = TEME::new(AU, 0.0, 0.0);
+        let r = WGS84_EQUATORIAL_RADIUS + 500.0;
+
+        assert_eq!(illumination(&at(r, 0.0, 0.0), &sun), Illumination::Sunlit);
+        assert_eq!(illumination(&at(0.0, r, 0.0), &sun), Illumination::Sunlit);
+        assert_eq!(illumination(&at(-r, 0.0, 0.0), &sun), Illumination::Umbra);
+
+        // Just behind the Earth's limb the Sun is partly covered
+        let angle = (WGS84_EQUATORIAL_RADIUS / r).acos();
+        assert_eq!(illumination(&at(-r * angle.sin(), r * angle.cos(), 0.0), &sun), Illumination::Penumbra);
+    }
+
+    #[test]
+    fn iss_orbit() {
+        let tle = test_support::iss();
+        let propagator = ::Propagator::new(&tle).unwrap();
+        let sun = position(tle.epoch_julian_date());
+
+        // Over one orbit the shadow is entered and left through the penumbra
+        let states: Vec<Illumination> = propagator.sample(0.0, 92.6, 0.01)
+            .map(|(_, state)| illumination(&state.unwrap(), &sun))
+            .collect();
+        assert!(states.contains(&Illumination::Umbra));
+        assert!(states.contains(&Illumination::Penumbra));
+        assert!(!states.windows(2).any(|pair| matches!(
+            (pair[0], pair[1]),
+            (Illumination::Sunlit, Illumination::Umbra) | (Illumination::Umbra, Illumination::Sunlit)
+        )));
+    }
+
+    #[test]
+    fn beta_angles() {
+        let tle = test_support::iss();
+        let start = tle.epoch_julian_date();
+
+        // Close to the angle from the osculating plane
+        let state = ::propagate(&tle, 0.0).unwrap();
+        let normal = state.position.cross(&state.velocity);
+        let sun = position(start);
+        let osculating = (normal.dot(&sun) / (normal.norm() * sun.norm())).asin();
+        let beta = beta_angle(&tle, start).unwrap();
+        assert!((beta - osculating).abs() < 0.2_f64.to_radians(), "{} {}", beta, osculating);
+
+        // Never more than the inclination plus the Sun's declination, and
+        // swinging through zero as the node precesses
+        let angles: Vec<f64> = daily_beta_angles(&tle, start, start + 60.0).unwrap()
+            .map(|(_, beta)| beta.unwrap())
+            .collect();
+        assert_eq!(angles.len(), 61);
+        assert_eq!(angles[0], beta);
+        assert!(angles.iter().all(|beta| beta.abs() < (51.65_f64 + 23.44).to_radians()));
+        assert!(angles.iter().any(|&beta| beta < 0.0));
+        assert!(angles.windows(2).all(|pair| (pair[1] - pair[0]).abs() < 6.0_f64.to_radians()));
+
+        let mut angles = daily_beta_angles(&tle, start, start + 2.5).unwrap();
+        assert_eq!(angles.len(), 3);
+        assert_eq!(angles.nth(2).unwrap().0, start + 2.0);
+        assert_eq!(daily_beta_angles(&tle, start, start - 1.0).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn node_local_time() {
+        // A dusk-dawn orbit: at the March equinox the Sun is at right
+        // ascension 0, so a node at 90° crosses at 18:00 by the true Sun,
+        // and 8 minutes later by the mean Sun, 2° behind it then
+        let equinox = time::julian_date(2024, 3, 20.0);
+        let (year, day) = time::day_of_year_from_julian_date(equinox);
+        let a = 7078.0;
+        let i = ::elements::sun_synchronous_inclination(a, 0.0).unwrap();
+        let elements = ::elements::Elements::from_keplerian(a, 0.0, i, Rad(PI / 2.0), Rad(0.0), Rad(0.0), 0.0).unwrap();
+        let tle = TLE::builder()
+            .epoch(year as u16, day)
+            .mean_motion(elements.revolutions_per_day())
+            .inclination(i)
+            .raan(Deg(90.0))
+            .build()
+            .unwrap();
+
+        let ltan = ltan(&tle, equinox).unwrap();
+        assert!((ltan - 18.13).abs() < 0.01, "{}", ltan);
+        assert!((ltdn(&tle, equinox).unwrap() - 6.13).abs() < 0.01);
+
+        // It stays put through the year, as the ISS's doesn't
+        assert!(ltan_drift(&tle, equinox).unwrap().abs() < 1e-3);
+        let later = self::ltan(&tle, equinox + 100.0).unwrap();
+        assert!((later - ltan).abs() < 0.05, "{} {}", ltan, later);
+        let drift = ltan_drift(&tle, equinox + 100.0).unwrap();
+        assert!(drift.abs() < 1e-3);
+    }
+}
@@ -0,0 +1,74 @@
+/*!  # Test Support
+
+Element sets shared by the tests of several modules.
+*/
+
+use tle::{self, ParseOptions, TLE};
+
+
+/// The ISS, the element set most tests start from
+pub const ISS: [&str; 3] = [
+    "ISS (ZARYA)",
+    "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+];
+
+/// Molniya 1-36 (08195 in `SGP4-VER.TLE`), in 12 hour resonance
+pub const MOLNIYA: [&str; 3] = [
+    "MOLNIYA 1-36",
+    "1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813",
+    "2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656",
+];
+
+/// 28626 in `SGP4-VER.TLE`, geostationary and in 24 hour resonance
+pub const GEOSTATIONARY: [&str; 3] = [
+    "28626",
+    "1 28626U 05008A   06176.46683397 -.00000205  00000-0  10000-3 0  2190",
+    "2 28626   0.0019 286.9433 0000335  13.7918  55.6504  1.00270176  4891",
+];
+
+/// The near Earth test case of Spacetrack Report #3, pg. 81. The checksums
+/// in the report are wrong.
+pub const SPACETRACK_REPORT_3_SGP4: [&str; 3] = [
+    "Test",
+    "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0     8",
+    "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518   105",
+];
+
+/// The deep space test case of Spacetrack Report #3, pg. 82
+pub const SPACETRACK_REPORT_3_SDP4: [&str; 3] = [
+    "Test",
+    "1 11801U          80230.29629788  .01431103  00000-0  14311-1 0    13",
+    "2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848    13",
+];
+
+/// Parse one of the element sets above, without checking the checksums
+pub fn load(lines: &[&str; 3]) -> TLE {
+    let options = ParseOptions { verify_checksums: false, ..ParseOptions::default() };
+    tle::load_from_str_with_options(lines[0], lines[1], lines[2], &options).unwrap()
+}
+
+/// [`ISS`](constant.ISS.html)
+pub fn iss() -> TLE {
+    load(&ISS)
+}
+
+/// [`MOLNIYA`](constant.MOLNIYA.html)
+pub fn molniya() -> TLE {
+    load(&MOLNIYA)
+}
+
+/// [`GEOSTATIONARY`](constant.GEOSTATIONARY.html)
+pub fn geostationary() -> TLE {
+    load(&GEOSTATIONARY)
+}
+
+/// [`SPACETRACK_REPORT_3_SGP4`](constant.SPACETRACK_REPORT_3_SGP4.html)
+pub fn spacetrack_report_3_sgp4() -> TLE {
+    load(&SPACETRACK_REPORT_3_SGP4)
+}
+
+/// [`SPACETRACK_REPORT_3_SDP4`](constant.SPACETRACK_REPORT_3_SDP4.html)
+pub fn spacetrack_report_3_sdp4() -> TLE {
+    load(&SPACETRACK_REPORT_3_SDP4)
+}
@@ -0,0 +1,414 @@
+/*!  # Time
+
+Calendar and Julian date conversions. TLE epochs are given as a year and a
+fractional day of that year, while most astronomical formulas want a Julian
+date; these helpers convert between them.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::f64::consts::PI;
+use tle;
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Duration, TimeZone, Utc};
+#[cfg(feature = "hifitime")]
+use hifitime::{Epoch, Unit};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::string::String;
+
+/// Julian date of the J2000.0 epoch (2000 January 1, 12:00 TT)
+pub const J2000: f64 = 2451545.0;
+
+/// Difference between a Julian date and a Modified Julian date
+pub const MJD_OFFSET: f64 = 2400000.5;
+
+/// Julian date of the Unix epoch (1970 January 1, 00:00 UTC)
+pub const UNIX_EPOCH: f64 = 2440587.5;
+
+/// Days in each month of a common (non-leap) year
+const DAYS_IN_MONTH: [u32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+
+/// Whether `year` is a leap year in the Gregorian calendar
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Number of days in `month` (1–12) of `year`
+fn days_in_month(year: i32, month: u32) -> u32 {
+    if month == 2 && is_leap_year(year) {
+        29
+    } else {
+        DAYS_IN_MONTH[month as usize - 1]
+    }
+}
+
+/// ## Julian Date
+///
+/// Julian date of a Gregorian calendar date. The `day` of the month may
+/// carry a fractional part for the time of day, so 1.0 is midnight at the
+/// start of the first day of the month. (Meeus, *Astronomical Algorithms*,
+/// ch. 7)
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// // 2000 January 1, 12:00
+/// assert_eq!(sgp4::time::julian_date(2000, 1, 1.5), 2451545.0);
+/// ```
+pub fn julian_date(year: i32, month: u32, day: f64) -> f64 {
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 12)
+    } else {
+        (year, month)
+    };
+
+    // Gregorian calendar correction
+    let a = (f64::from(y) / 100.0).floor();
+    let b = 2.0 - a + (a / 4.0).floor();
+
+    (365.25 * (f64::from(y) + 4716.0)).floor() + (30.6001 * f64::from(m + 1)).floor() + day + b - 1524.5
+}
+
+/// Julian date of a fractional day of the year, where day 1.0 is midnight at
+/// the start of January 1st (the convention used for TLE epochs)
+pub fn julian_date_from_day_of_year(year: i32, day_of_year: f64) -> f64 {
+    julian_date(year, 1, 1.0) + day_of_year - 1.0
+}
+
+/// Year and fractional day of the year of a Julian date, the inverse of
+/// [`julian_date_from_day_of_year`](fn.julian_date_from_day_of_year.html)
+pub fn day_of_year_from_julian_date(julian_date: f64) -> (i32, f64) {
+    // A first guess at the year can be out by one either side of
+    // January 1st
+    let mut year = 2000 + ((julian_date - 2451544.5) / 365.25).floor() as i32;
+    if self::julian_date(year, 1, 1.0) > julian_date {
+        year -= 1;
+    } else if self::julian_date(year + 1, 1, 1.0) <= julian_date {
+        year += 1;
+    }
+    (year, julian_date - self::julian_date(year, 1, 1.0) + 1.0)
+}
+
+/// Modified Julian date of a Julian date
+pub fn modified_julian_date(julian_date: f64) -> f64 {
+    julian_date - MJD_OFFSET
+}
+
+/// Julian date of a Modified Julian date
+pub fn julian_date_from_modified(modified_julian_date: f64) -> f64 {
+    modified_julian_date + MJD_OFFSET
+}
+
+/// Julian date (UTC) of a Unix timestamp in seconds. Leap seconds aren't
+/// counted in Unix time, so neither are they here.
+pub fn julian_date_from_unix(seconds: f64) -> f64 {
+    UNIX_EPOCH + seconds / 86400.0
+}
+
+/// Julian date (UTC) of a hifitime `Epoch`, in whatever time scale it was
+/// made. Requires the `hifitime` feature.
+#[cfg(feature = "hifitime")]
+pub fn julian_date_from_epoch(epoch: Epoch) -> f64 {
+    epoch.to_jde_utc_days()
+}
+
+/// hifitime `Epoch` of a Julian date (UTC), with the leap seconds up to
+/// it. Requires the `hifitime` feature.
+#[cfg(feature = "hifitime")]
+pub fn epoch_from_julian_date(julian_date: f64) -> Epoch {
+    Epoch::from_jde_utc(julian_date)
+}
+
+/// ## Day of Year
+///
+/// Fractional day of the year of a calendar date, where January 1.0 is
+/// day 1.0.
+pub fn day_of_year(year: i32, month: u32, day: f64) -> f64 {
+    let preceding: u32 = (1..month).map(|m| days_in_month(year, m)).sum();
+    f64::from(preceding) + day
+}
+
+/// ## Calendar Date
+///
+/// Month (1–12) and fractional day of the month of a fractional day of the
+/// year. This is the inverse of [`day_of_year`](fn.day_of_year.html).
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// // Day 60 is February 29th in a leap year
+/// assert_eq!(sgp4::time::calendar_date(2016, 60.25), (2, 29.25));
+/// ```
+pub fn calendar_date(year: i32, day_of_year: f64) -> (u32, f64) {
+    let mut day = day_of_year;
+    for month in 1..12 {
+        let length = f64::from(days_in_month(year, month));
+        if day < length + 1.0 {
+            return (month, day);
+        }
+        day -= length;
+    }
+    (12, day)
+}
+
+/// ## ISO 8601
+///
+/// A Julian date (UTC) written as `YYYY-MM-DDThh:mm:ss.ssssss`, to the
+/// nearest microsecond, as CCSDS messages and most interchange formats
+/// want it. A Julian date only resolves some tens of microseconds, so the
+/// last digit or two are noise.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let jd = sgp4::time::julian_date(2016, 7, 28.75);
+/// assert_eq!(sgp4::time::iso_8601(jd), "2016-07-28T18:00:00.000000");
+/// ```
+pub fn iso_8601(julian_date: f64) -> String {
+    // Whole microseconds since midnight, carried into the next day if they
+    // round up to it
+    let mut midnight = (julian_date - 0.5).floor() + 0.5;
+    let mut microseconds = ((julian_date - midnight) * 86_400.0e6).round() as u64;
+    if microseconds >= 86_400_000_000 {
+        microseconds -= 86_400_000_000;
+        midnight += 1.0;
+    }
+
+    let (year, day_of_year) = day_of_year_from_julian_date(midnight);
+    let (month, day) = calendar_date(year, day_of_year.round());
+    let seconds = microseconds / 1_000_000;
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}",
+            year, month, day as u32,
+            seconds / 3600, seconds / 60 % 60, seconds % 60, microseconds % 1_000_000)
+}
+
+/// ## Greenwich Mean Sidereal Time
+///
+/// The angle (radians, 0 to $2\pi$) between the mean equinox and the
+/// Greenwich meridian at a Julian date in the UT1 time scale, using the
+/// IAU-82 model. This is the rotation between the TEME frame SGP4 works in
+/// and an Earth-fixed frame.
+///
+/// $$\theta_{GMST} = 67310.54841\ s + (876600\ h + 8640184.812866\ s) T + 0.093104 T\^2 - 6.2\times 10\^{-6} T\^3$$
+///
+/// where $T$ is the number of Julian centuries of UT1 since J2000.0.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// // Vallado, example 3-5: 1992 August 20, 12:14 UT1
+/// let jd = sgp4::time::julian_date(1992, 8, 20.0 + (12.0 * 60.0 + 14.0) / 1440.0);
+/// let gmst = sgp4::time::gmst(jd);
+/// assert!((gmst.to_degrees() - 152.578787886).abs() < 1e-6);
+/// ```
+pub fn gmst(julian_date_ut1: f64) -> f64 {
+    let T = (julian_date_ut1 - J2000) / 36525.0;
+
+    // Seconds of time
+    let seconds = -6.2e-6 * T * T * T
+                + 0.093104 * T * T
+                + (876600.0 * 3600.0 + 8640184.812866) * T
+                + 67310.54841;
+
+    // 86400 seconds of time is 360 degrees, or 240 seconds per degree
+    let theta = (seconds / 240.0).to_radians() % (2.0 * PI);
+    if theta < 0.0 {
+        theta + 2.0 * PI
+    } else {
+        theta
+    }
+}
+
+impl tle::TLE {
+
+    /// Julian date (UTC) of the element set epoch
+    pub fn epoch_julian_date(&self) -> f64 {
+        julian_date_from_day_of_year(i32::from(self.epoch_year), self.epoch_day)
+    }
+
+    /// ## Epoch
+    ///
+    /// The element set epoch as a UTC timestamp, to the nearest nanosecond.
+    /// Requires the `chrono` feature.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate chrono;
+    /// extern crate sgp4;
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    ///
+    /// assert_eq!(tle.epoch().to_rfc3339(), "2016-07-28T14:21:26.330688+00:00");
+    /// ```
+    #[cfg(feature = "chrono")]
+    pub fn epoch(&self) -> DateTime<Utc> {
+        // Day 1.0 is midnight at the start of January 1st
+        let start_of_year = Utc.with_ymd_and_hms(i32::from(self.epoch_year), 1, 1, 0, 0, 0).unwrap();
+        start_of_year + Duration::nanoseconds(((self.epoch_day - 1.0) * 86_400.0e9).round() as i64)
+    }
+
+    /// ## Hifitime Epoch
+    ///
+    /// The element set epoch as a hifitime `Epoch` in UTC, to the nearest
+    /// nanosecond. The time of day is counted from midnight of the epoch's
+    /// own date, so leap seconds earlier in the year don't shift it.
+    /// Requires the `hifitime` feature.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate hifitime;
+    /// extern crate sgp4;
+    ///
+    /// use hifitime::{Epoch, Unit};
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    ///
+    /// let expected = Epoch::from_gregorian_utc(2016, 7, 28, 14, 21, 26, 330_688_000);
+    /// assert!((tle.hifitime_epoch() - expected).abs() <= 1 * Unit::Microsecond);
+    /// ```
+    #[cfg(feature = "hifitime")]
+    pub fn hifitime_epoch(&self) -> Epoch {
+        let day = self.epoch_day.floor();
+        let (month, day_of_month) = calendar_date(i32::from(self.epoch_year), day);
+        let midnight = Epoch::from_gregorian_utc_at_midnight(i32::from(self.epoch_year), month as u8, day_of_month as u8);
+        midnight + Unit::Nanosecond * ((self.epoch_day - day) * 86_400.0e9).round()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support;
+
+    #[test]
+    fn julian_dates() {
+        // Examples from Meeus, chapter 7
+        assert_eq!(julian_date(1957, 10, 4.81), 2436116.31);
+        assert_eq!(julian_date(1987, 6, 19.5), 2446966.0);
+        assert_eq!(julian_date(1600, 12, 31.0), 2305812.5);
+        assert_eq!(julian_date(2000, 1, 1.0), 2451544.5);
+        assert_eq!(julian_date(1858, 11, 17.0), MJD_OFFSET);
+
+        assert_eq!(julian_date_from_unix(0.0), julian_date(1970, 1, 1.0));
+        assert_eq!(julian_date_from_unix(946728000.0), J2000);
+
+        assert_eq!(modified_julian_date(J2000), 51544.5);
+        assert_eq!(julian_date_from_modified(51544.5), J2000);
+    }
+
+    #[test]
+    fn day_of_year_round_trip() {
+        assert_eq!(day_of_year(2016, 7, 28.5), 210.5);
+        assert_eq!(day_of_year(2015, 12, 31.0), 365.0);
+        assert_eq!(calendar_date(2016, 210.5), (7, 28.5));
+        assert_eq!(calendar_date(2015, 365.0), (12, 31.0));
+        assert_eq!(calendar_date(2015, 1.0), (1, 1.0));
+        assert_eq!(calendar_date(2015, 59.75), (2, 28.75));
+        assert_eq!(calendar_date(2015, 60.0), (3, 1.0));
+        assert_eq!(day_of_year_from_julian_date(julian_date(2016, 7, 28.5)), (2016, 210.5));
+        assert_eq!(day_of_year_from_julian_date(julian_date(2016, 1, 1.0)), (2016, 1.0));
+        assert_eq!(day_of_year_from_julian_date(julian_date(2015, 12, 31.75)), (2015, 365.75));
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(1900));
+    }
+
+    #[test]
+    fn iso_8601_dates() {
+        assert_eq!(iso_8601(J2000), "2000-01-01T12:00:00.000000");
+        assert_eq!(iso_8601(julian_date(2016, 2, 29.75)), "2016-02-29T18:00:00.000000");
+
+        // Rounding up to midnight carries into the next year
+        assert_eq!(iso_8601(julian_date(2016, 1, 1.0) - 1.0e-12), "2016-01-01T00:00:00.000000");
+        assert!(iso_8601(julian_date(2015, 12, 31.0) + 0.5 - 1.0e-5).starts_with("2015-12-31T11:59:59.13"));
+    }
+
+    #[test]
+    fn greenwich_mean_sidereal_time() {
+        // Vallado, example 3-5
+        let jd = julian_date(1992, 8, 20.0 + (12.0 * 60.0 + 14.0) / 1440.0);
+        assert!((gmst(jd).to_degrees() - 152.578787886).abs() < 1e-6);
+
+        // J2000.0
+        assert!((gmst(J2000).to_degrees() - 280.46061837).abs() < 1e-6);
+
+        // Always in [0, 2pi), even before J2000
+        let theta = gmst(2415020.0);
+        assert!((0.0..2.0 * PI).contains(&theta));
+    }
+
+    #[test]
+    fn epoch_julian_date() {
+        let tle = test_support::iss();
+
+        // 2016 July 28, 14:21:26.33 UTC
+        assert!((tle.epoch_julian_date() - (julian_date(2016, 7, 28.0) + 0.59822142)).abs() < 1e-9);
+        assert!((tle.epoch_julian_date() - 2457598.09822142).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn epoch() {
+        use chrono::{Duration, TimeZone, Utc};
+
+        let iss = test_support::iss();
+
+        // Day 210 of 2016 is July 28th
+        let expected = Utc.with_ymd_and_hms(2016, 7, 28, 14, 21, 26).unwrap() + Duration::microseconds(330_688);
+        assert!((iss.epoch() - expected).num_microseconds().unwrap().abs() <= 1);
+
+        // Two digit years before 57 are in the 1900s; day 275 of 1980 (a
+        // leap year) is October 1st
+        let str3 = test_support::spacetrack_report_3_sgp4();
+        let expected = Utc.with_ymd_and_hms(1980, 10, 1, 23, 41, 24).unwrap() + Duration::microseconds(113_760);
+        assert!((str3.epoch() - expected).num_microseconds().unwrap().abs() <= 1);
+    }
+
+    #[cfg(feature = "hifitime")]
+    #[test]
+    fn hifitime_epoch() {
+        use hifitime::{Epoch, Unit};
+
+        let iss = test_support::iss();
+        let expected = Epoch::from_gregorian_utc(2016, 7, 28, 14, 21, 26, 330_688_000);
+        assert!((iss.hifitime_epoch() - expected).abs() <= Unit::Microsecond * 1);
+        assert!((julian_date_from_epoch(iss.hifitime_epoch()) - iss.epoch_julian_date()).abs() < 1e-9);
+        assert!((epoch_from_julian_date(iss.epoch_julian_date()) - expected).abs() <= Unit::Microsecond * 50);
+
+        // Noon on the last day of 2016, and on the first of 2017, a day and
+        // the leap second apart
+        let before = tle::TLE::builder().epoch(2016, 366.5).mean_motion(15.5).build().unwrap();
+        let after = tle::TLE::builder().epoch(2017, 1.5).mean_motion(15.5).build().unwrap();
+        assert_eq!(before.hifitime_epoch(), Epoch::from_gregorian_utc(2016, 12, 31, 12, 0, 0, 0));
+        assert_eq!(after.hifitime_epoch(), Epoch::from_gregorian_utc(2017, 1, 1, 12, 0, 0, 0));
+        assert_eq!(after.hifitime_epoch() - before.hifitime_epoch(), Unit::Second * 86_401);
+    }
+}
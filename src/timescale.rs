@@ -0,0 +1,278 @@
+/*!  # Time Scales
+
+Conversions between UTC, TAI, TT and UT1.
+
+TLE epochs and the propagator work in UTC. Sidereal time wants UT1, which
+follows the Earth's rotation and is within 0.9 seconds of UTC (exactly, from
+[Earth orientation parameters](../eop/index.html)). Precession and nutation,
+as in [`j2000_from_teme`](../coordinates/fn.j2000_from_teme.html), want TT,
+which runs a fixed 32.184 seconds ahead of TAI, which is ahead of UTC by a
+whole number of leap seconds.
+
+The built in leap second table runs to the leap second at the end of 2016.
+When the IERS announces another, add it to a
+[`LeapSeconds`](struct.LeapSeconds.html) table.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use eop::Eop;
+use time;
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// TT − TAI (seconds)
+pub const TT_MINUS_TAI: f64 = 32.184;
+
+/// Seconds in a day
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// The modified Julian date (UTC) from which each TAI − UTC (seconds)
+/// applies, from IERS Bulletin C
+const LEAP_SECONDS: [(f64, f64); 28] = [
+    (41317.0, 10.0), // 1972-01-01
+    (41499.0, 11.0), // 1972-07-01
+    (41683.0, 12.0), // 1973-01-01
+    (42048.0, 13.0), // 1974-01-01
+    (42413.0, 14.0), // 1975-01-01
+    (42778.0, 15.0), // 1976-01-01
+    (43144.0, 16.0), // 1977-01-01
+    (43509.0, 17.0), // 1978-01-01
+    (43874.0, 18.0), // 1979-01-01
+    (44239.0, 19.0), // 1980-01-01
+    (44786.0, 20.0), // 1981-07-01
+    (45151.0, 21.0), // 1982-07-01
+    (45516.0, 22.0), // 1983-07-01
+    (46247.0, 23.0), // 1985-07-01
+    (47161.0, 24.0), // 1988-01-01
+    (47892.0, 25.0), // 1990-01-01
+    (48257.0, 26.0), // 1991-01-01
+    (48804.0, 27.0), // 1992-07-01
+    (49169.0, 28.0), // 1993-07-01
+    (49534.0, 29.0), // 1994-07-01
+    (50083.0, 30.0), // 1996-01-01
+    (50630.0, 31.0), // 1997-07-01
+    (51179.0, 32.0), // 1999-01-01
+    (53736.0, 33.0), // 2006-01-01
+    (54832.0, 34.0), // 2009-01-01
+    (56109.0, 35.0), // 2012-07-01
+    (57204.0, 36.0), // 2015-07-01
+    (57754.0, 37.0), // 2017-01-01
+];
+
+
+/// ## Leap Seconds
+///
+/// A table of TAI − UTC, starting from the built in one.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::timescale::LeapSeconds;
+///
+/// // A leap second that hasn't happened (yet)
+/// let mut leap_seconds = LeapSeconds::new();
+/// leap_seconds.insert(sgp4::time::modified_julian_date(sgp4::time::julian_date(2035, 1, 1.0)), 38.0);
+///
+/// let jd = sgp4::time::julian_date(2036, 1, 1.0);
+/// assert_eq!(leap_seconds.tai_minus_utc(jd), 38.0);
+/// assert_eq!(sgp4::timescale::tai_minus_utc(jd), 37.0);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeapSeconds {
+
+    /// Modified Julian date (UTC) and TAI − UTC (seconds) from then on, in
+    /// date order
+    table: Vec<(f64, f64)>,
+}
+
+impl Default for LeapSeconds {
+    fn default() -> LeapSeconds {
+        LeapSeconds { table: LEAP_SECONDS.to_vec() }
+    }
+}
+
+impl LeapSeconds {
+
+    /// The built in table
+    pub fn new() -> LeapSeconds {
+        LeapSeconds::default()
+    }
+
+    /// Set TAI − UTC (seconds) from a modified Julian date (UTC) on,
+    /// replacing any entry for the same date
+    pub fn insert(&mut self, modified_julian_date: f64, tai_minus_utc: f64) {
+        match self.table.binary_search_by(|&(mjd, _)| mjd.total_cmp(&modified_julian_date)) {
+            Ok(index) => self.table[index].1 = tai_minus_utc,
+            Err(index) => self.table.insert(index, (modified_julian_date, tai_minus_utc)),
+        }
+    }
+
+    /// TAI − UTC (seconds) at a Julian date (UTC)
+    pub fn tai_minus_utc(&self, julian_date_utc: f64) -> f64 {
+        offset(&self.table, julian_date_utc)
+    }
+
+    /// A Julian date in UTC as a Julian date in TAI
+    pub fn tai_from_utc(&self, julian_date_utc: f64) -> f64 {
+        julian_date_utc + self.tai_minus_utc(julian_date_utc) / SECONDS_PER_DAY
+    }
+
+    /// A Julian date in TAI as a Julian date in UTC
+    pub fn utc_from_tai(&self, julian_date_tai: f64) -> f64 {
+        utc_from_tai_with(&self.table, julian_date_tai)
+    }
+
+    /// A Julian date in UTC as a Julian date in TT
+    pub fn tt_from_utc(&self, julian_date_utc: f64) -> f64 {
+        tt_from_tai(self.tai_from_utc(julian_date_utc))
+    }
+
+    /// A Julian date in TT as a Julian date in UTC
+    pub fn utc_from_tt(&self, julian_date_tt: f64) -> f64 {
+        self.utc_from_tai(tai_from_tt(julian_date_tt))
+    }
+}
+
+/// TAI − UTC from a table. Dates before the first entry, when UTC wasn't
+/// kept to whole seconds of TAI, get the first offset.
+fn offset(table: &[(f64, f64)], julian_date_utc: f64) -> f64 {
+    let mjd = time::modified_julian_date(julian_date_utc);
+    let index = table.partition_point(|&(start, _)| start <= mjd);
+    table[index.saturating_sub(1)].1
+}
+
+/// TAI to UTC. The offset is looked up at the UTC date, which is found by
+/// removing the offset in force a leap second earlier.
+fn utc_from_tai_with(table: &[(f64, f64)], julian_date_tai: f64) -> f64 {
+    let guess = julian_date_tai - offset(table, julian_date_tai) / SECONDS_PER_DAY;
+    julian_date_tai - offset(table, guess) / SECONDS_PER_DAY
+}
+
+/// TAI − UTC (seconds) at a Julian date (UTC), from the built in table
+pub fn tai_minus_utc(julian_date_utc: f64) -> f64 {
+    offset(&LEAP_SECONDS, julian_date_utc)
+}
+
+/// ## TAI From UTC
+///
+/// A Julian date in UTC as a Julian date in TAI, from the built in leap
+/// second table.
+pub fn tai_from_utc(julian_date_utc: f64) -> f64 {
+    julian_date_utc + tai_minus_utc(julian_date_utc) / SECONDS_PER_DAY
+}
+
+/// A Julian date in TAI as a Julian date in UTC, from the built in leap
+/// second table
+pub fn utc_from_tai(julian_date_tai: f64) -> f64 {
+    utc_from_tai_with(&LEAP_SECONDS, julian_date_tai)
+}
+
+/// A Julian date in TAI as a Julian date in TT
+pub fn tt_from_tai(julian_date_tai: f64) -> f64 {
+    julian_date_tai + TT_MINUS_TAI / SECONDS_PER_DAY
+}
+
+/// A Julian date in TT as a Julian date in TAI
+pub fn tai_from_tt(julian_date_tt: f64) -> f64 {
+    julian_date_tt - TT_MINUS_TAI / SECONDS_PER_DAY
+}
+
+/// ## TT From UTC
+///
+/// A Julian date in UTC as a Julian date in TT, from the built in leap
+/// second table.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::load_from_str(
+///     "ISS (ZARYA)",
+///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+/// ).unwrap();
+///
+/// let state = sgp4::propagate(&tle, 0.0).unwrap();
+/// let tt = sgp4::timescale::tt_from_utc(state.epoch);
+/// let position = sgp4::coordinates::j2000_from_teme(&state.position, tt);
+/// ```
+pub fn tt_from_utc(julian_date_utc: f64) -> f64 {
+    tt_from_tai(tai_from_utc(julian_date_utc))
+}
+
+/// A Julian date in TT as a Julian date in UTC, from the built in leap
+/// second table
+pub fn utc_from_tt(julian_date_tt: f64) -> f64 {
+    utc_from_tai(tai_from_tt(julian_date_tt))
+}
+
+/// ## UT1 From UTC
+///
+/// A Julian date in UTC as a Julian date in UT1, given Earth orientation
+/// parameters, or approximated by UTC itself (to within 0.9 seconds)
+/// without them.
+pub fn ut1_from_utc(julian_date_utc: f64, eop: Option<&Eop>) -> f64 {
+    eop.map_or(julian_date_utc, |eop| eop.ut1(julian_date_utc))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seconds between two Julian dates
+    fn seconds(a: f64, b: f64) -> f64 {
+        (a - b) * SECONDS_PER_DAY
+    }
+
+    #[test]
+    fn offsets() {
+        assert_eq!(tai_minus_utc(time::julian_date(1960, 1, 1.0)), 10.0);
+        assert_eq!(tai_minus_utc(time::julian_date(1972, 1, 1.0)), 10.0);
+        assert_eq!(tai_minus_utc(time::julian_date(2016, 7, 28.5)), 36.0);
+        assert_eq!(tai_minus_utc(time::julian_date(2016, 12, 31.999)), 36.0);
+        assert_eq!(tai_minus_utc(time::julian_date(2017, 1, 1.0)), 37.0);
+        assert_eq!(tai_minus_utc(time::julian_date(2030, 1, 1.0)), 37.0);
+    }
+
+    #[test]
+    fn conversions() {
+        // Vallado, example 3-7: 2004 May 14, 16:43 UTC
+        let utc = time::julian_date(2004, 5, 14.0 + (16.0 * 60.0 + 43.0) / 1440.0);
+        assert!((seconds(tai_from_utc(utc), utc) - 32.0).abs() < 1e-4);
+        assert!((seconds(tt_from_utc(utc), utc) - 64.184).abs() < 1e-4);
+
+        // And back, including either side of a leap second
+        for &utc in [utc, time::julian_date(2016, 12, 31.9999), time::julian_date(2017, 1, 1.0001)].iter() {
+            assert!(seconds(utc_from_tai(tai_from_utc(utc)), utc).abs() < 1e-4);
+            assert!(seconds(utc_from_tt(tt_from_utc(utc)), utc).abs() < 1e-4);
+        }
+
+        let eop = Eop { dut1: -0.4, ..Eop::default() };
+        assert!((seconds(ut1_from_utc(utc, Some(&eop)), utc) + 0.4).abs() < 1e-4);
+        assert_eq!(ut1_from_utc(utc, None), utc);
+    }
+
+    #[test]
+    fn extended() {
+        let mut leap_seconds = LeapSeconds::new();
+        assert_eq!(leap_seconds, LeapSeconds::default());
+
+        let next = time::julian_date(2035, 1, 1.0);
+        leap_seconds.insert(time::modified_julian_date(next), 38.0);
+        assert_eq!(leap_seconds.tai_minus_utc(next - 0.5), 37.0);
+        assert_eq!(leap_seconds.tai_minus_utc(next), 38.0);
+        assert!((seconds(leap_seconds.tt_from_utc(next), next) - 70.184).abs() < 1e-4);
+        assert!(seconds(leap_seconds.utc_from_tt(leap_seconds.tt_from_utc(next)), next).abs() < 1e-4);
+    }
+}
@@ -7,10 +7,40 @@
         unused_import_braces,
         unused_qualifications)]
 
+use std::cmp::Ordering;
+use std::error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+use std::str::FromStr;
+
+use angle::Deg;
+use time;
+
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::string::String;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+
 /// ## Satellite elements from a TLE file
 ///
 /// Read "Two Line Element" files that are a standard way of distributing
 /// defined orbits.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TLE {
 
     /// Name of the object
@@ -44,19 +74,19 @@ pub struct TLE {
     pub tle_version: u16,
 
     /// Inclination
-    pub i: f64,
+    pub i: Deg,
 
     /// Right ascension of the ascending node
-    pub raan: f64,
+    pub raan: Deg,
 
     /// Eccentricity
     pub e: f64,
 
     /// Argument of perigee
-    pub omega: f64,
+    pub omega: Deg,
 
     /// Mean Anomaly
-    pub mean_anomaly: f64,
+    pub mean_anomaly: Deg,
 
     /// Mean Motion (revolutions per day)
     pub mean_motion: f64,
@@ -66,6 +96,354 @@ pub struct TLE {
 }
 
 
+/// ## TLE Parse Errors
+///
+/// Why a TLE couldn't be read. Lines are numbered as in the TLE format
+/// itself, so `line: 1` is the first line of elements (not the name).
+#[derive(Debug, Clone, PartialEq)]
+pub enum TleParseError {
+
+    /// The line is too short to hold every field
+    LineTooShort {
+        /// TLE line number (1 or 2)
+        line: u8,
+        /// Length of the line that was given
+        length: usize,
+    },
+
+    /// The text in a field couldn't be parsed
+    InvalidField {
+        /// TLE line number (1 or 2)
+        line: u8,
+        /// Name of the field
+        field: &'static str,
+        /// Columns (zero-indexed) the field occupies in the line
+        columns: Range<usize>,
+    },
+
+    /// The checksum at the end of the line doesn't match its contents
+    ChecksumMismatch {
+        /// TLE line number (1 or 2)
+        line: u8,
+        /// Checksum computed from the contents of the line
+        expected: u8,
+        /// Checksum written at the end of the line
+        found: u8,
+    },
+
+    /// The text isn't a 2-line or 3-line element set
+    LineCount {
+        /// Number of non-blank lines in the text
+        found: usize,
+    },
+
+    /// A character that makes the columns ambiguous: a tab that stood for
+    /// more than one space, or anything outside ASCII
+    UnexpectedCharacter {
+        /// TLE line number (1 or 2)
+        line: u8,
+        /// Column (zero-indexed, in bytes) of the character
+        column: usize,
+        /// The character
+        found: char,
+    },
+}
+
+impl fmt::Display for TleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TleParseError::LineTooShort { line, length } => {
+                write!(f, "line {} is too short ({} characters)", line, length)
+            }
+            TleParseError::InvalidField { line, field, ref columns } => {
+                write!(f, "line {}, columns {}-{}: invalid {}", line, columns.start, columns.end - 1, field)
+            }
+            TleParseError::ChecksumMismatch { line, expected, found } => {
+                write!(f, "line {} checksum is {} but the line sums to {}", line, found, expected)
+            }
+            TleParseError::LineCount { found } => {
+                write!(f, "expected 2 or 3 lines but found {}", found)
+            }
+            TleParseError::UnexpectedCharacter { line, column, found } => {
+                write!(f, "line {}, column {}: unexpected character {:?}", line, column, found)
+            }
+        }
+    }
+}
+
+impl error::Error for TleParseError {}
+
+/// ## TLE Format Errors
+///
+/// Why an element set couldn't be written as TLE lines. Lines are numbered
+/// as for [`TleParseError`](enum.TleParseError.html).
+#[derive(Debug, Clone, PartialEq)]
+pub enum TleFormatError {
+
+    /// A value doesn't fit the columns of its field
+    OutOfRange {
+        /// TLE line number (1 or 2)
+        line: u8,
+        /// Name of the field
+        field: &'static str,
+        /// Columns (zero-indexed) the field occupies in the line
+        columns: Range<usize>,
+    },
+}
+
+impl fmt::Display for TleFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TleFormatError::OutOfRange { line, field, ref columns } => {
+                write!(f, "line {}, columns {}-{}: {} doesn't fit", line, columns.start, columns.end - 1, field)
+            }
+        }
+    }
+}
+
+impl error::Error for TleFormatError {}
+
+/// ## Parse Options
+///
+/// Settings for how strictly TLEs are read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ParseOptions {
+
+    /// Verify the modulo 10 checksum at the end of each line (default
+    /// `true`). Some sources, including the test cases in Spacetrack Report
+    /// #3, publish lines with bad checksums.
+    pub verify_checksums: bool,
+
+    /// Two digit epoch years from `year_pivot` up are in the 1900s, and
+    /// those below it in the 2000s (default `YEAR_PIVOT`). Use 0 for an
+    /// archive that is all 20th century, or 100 once TLEs reach 2057.
+    pub year_pivot: u16,
+}
+
+/// The standard two digit year pivot: TLE years 57 to 99 are 1957 to 1999,
+/// since nothing was in orbit before Sputnik
+pub const YEAR_PIVOT: u16 = 57;
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            verify_checksums: true,
+            year_pivot: YEAR_PIVOT,
+        }
+    }
+}
+
+/// Number of characters in each line of elements, up to (but not including)
+/// the checksum
+const LINE_LENGTH: usize = 68;
+
+/// ## Checksum
+///
+/// Modulo 10 checksum of a line of elements: the sum of all the digits,
+/// counting each minus sign as 1, over the first 68 columns. Anything past
+/// column 68, such as the checksum already on the line, is ignored.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let line = "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990";
+/// assert_eq!(sgp4::tle::checksum(line), 0);
+/// ```
+pub fn checksum(line: &str) -> u8 {
+    let sum = line.chars()
+        .take(LINE_LENGTH)
+        .map(|c| match c {
+            '-' => 1,
+            _ => c.to_digit(10).unwrap_or(0),
+        })
+        .sum::<u32>();
+    (sum % 10) as u8
+}
+
+/// ## Tidy
+///
+/// Undo the damage real feeds do to a line of elements before reading its
+/// columns:
+///
+/// - Line endings and trailing whitespace are dropped.
+/// - Tabs standing in for single spaces become spaces. A tab that must have
+///   stood for more than one space, leaving the line the wrong length, is
+///   an error rather than a guess, as is anything outside ASCII.
+/// - Without checksum verification, a line missing its trailing optional
+///   fields (`required` is the length through the last field that isn't)
+///   is padded out with blanks.
+fn tidy(text: &str, line: u8, required: usize, options: &ParseOptions) -> Result<String, TleParseError> {
+    let mut text = String::from(text.trim_end());
+
+    if let Some((column, found)) = text.char_indices().find(|&(_, c)| !c.is_ascii()) {
+        return Err(TleParseError::UnexpectedCharacter { line, column, found });
+    }
+
+    if let Some(column) = text.find('\t') {
+        text = text.replace('\t', " ");
+        let lengths = if options.verify_checksums { LINE_LENGTH + 1..LINE_LENGTH + 2 } else { LINE_LENGTH..LINE_LENGTH + 2 };
+        if !lengths.contains(&text.len()) {
+            return Err(TleParseError::UnexpectedCharacter { line, column, found: '\t' });
+        }
+    }
+
+    if !options.verify_checksums && text.len() >= required && text.len() < LINE_LENGTH {
+        let padding = LINE_LENGTH - text.len();
+        text.extend(std::iter::repeat_n(' ', padding));
+    }
+
+    Ok(text)
+}
+
+/// Check the checksum in the last column of a line
+fn verify_checksum(text: &str, line: u8) -> Result<(), TleParseError> {
+    if text.len() <= LINE_LENGTH {
+        return Err(TleParseError::LineTooShort { line, length: text.len() });
+    }
+    let found = parse_field::<u8>(text, line, "checksum", LINE_LENGTH..LINE_LENGTH + 1)?;
+    let expected = checksum(text);
+    if found != expected {
+        return Err(TleParseError::ChecksumMismatch { line, expected, found });
+    }
+    Ok(())
+}
+
+/// Get the text in `columns` of a line
+fn field<'a>(text: &'a str, line: u8, field: &'static str, columns: Range<usize>) -> Result<&'a str, TleParseError> {
+    text.get(columns.clone()).ok_or(TleParseError::InvalidField { line, field, columns })
+}
+
+/// Parse the (trimmed) text in `columns` of a line
+fn parse_field<T: FromStr>(text: &str, line: u8, name: &'static str, columns: Range<usize>) -> Result<T, TleParseError> {
+    field(text, line, name, columns.clone())?
+        .trim()
+        .parse::<T>()
+        .map_err(|_| TleParseError::InvalidField { line, field: name, columns })
+}
+
+/// Parse a field written in the TLE's "decimal point assumed" exponential
+/// notation, e.g. ` 12345-3` is $0.12345 \times 10\^{-3}$
+fn parse_exponential(text: &str, line: u8, name: &'static str, columns: Range<usize>) -> Result<f64, TleParseError> {
+    let invalid = TleParseError::InvalidField { line, field: name, columns: columns.clone() };
+    let value = field(text, line, name, columns.clone())?;
+
+    // Sign, five digits of mantissa, then a signed single digit exponent
+    let sign = match value.get(0..1) {
+        Some("-") => -1.0,
+        Some(" ") | Some("+") => 1.0,
+        _ => return Err(invalid),
+    };
+    let mantissa = value.get(1..6).and_then(|m| m.parse::<u32>().ok()).ok_or(invalid.clone())?;
+    let exponent = value.get(6..8).and_then(|e| e.trim().parse::<i32>().ok()).ok_or(invalid)?;
+
+    Ok(sign * (f64::from(mantissa) / 100000.0) * 10_f64.powi(exponent))
+}
+
+/// The five digit mantissa (signed) and the exponent a value is written
+/// with in the TLE's "decimal point assumed" exponential notation: $-0.12345
+/// \times 10\^{-3}$ is `(-12345, -3)`, and zero is `(0, 0)`. Values too
+/// small for a single digit exponent are zero, and those too large (or not
+/// finite) are `None`.
+fn exponential_digits(value: f64) -> Option<(i32, i32)> {
+    if !value.is_finite() {
+        return None;
+    }
+    if value == 0.0 {
+        return Some((0, 0));
+    }
+
+    // Normalize to 0.1 <= mantissa < 1
+    let mut mantissa = value.abs();
+    let mut exponent = 0_i32;
+    while mantissa >= 1.0 {
+        mantissa /= 10.0;
+        exponent += 1;
+    }
+    while mantissa < 0.1 {
+        mantissa *= 10.0;
+        exponent -= 1;
+    }
+
+    // Rounding to five digits can carry into a sixth
+    let mut digits = (mantissa * 100000.0).round() as i32;
+    if digits == 100000 {
+        digits = 10000;
+        exponent += 1;
+    }
+
+    match exponent {
+        10.. => None,
+        ..=-10 => Some((0, 0)),
+        _ => Some((if value < 0.0 { -digits } else { digits }, exponent)),
+    }
+}
+
+/// Write a value in the TLE's "decimal point assumed" exponential notation,
+/// the inverse of `parse_exponential`: $-0.12345 \times 10\^{-3}$ is
+/// `-12345-3`. A value whose exponent needs more than one digit is written
+/// as the largest that fits, and NaN as zero.
+fn format_exponential(value: f64) -> String {
+    let sign = if value < 0.0 { '-' } else { ' ' };
+    match exponential_digits(value) {
+        Some((0, _)) => format!("{}00000-0", sign),
+        Some((digits, exponent)) => format!("{}{:05}{}{}", sign, digits.abs(), if exponent < 0 { '-' } else { '+' }, exponent.abs()),
+        None if value.is_nan() => String::from(" 00000-0"),
+        None => format!("{}99999+9", sign),
+    }
+}
+
+/// Write the first derivative of mean motion field, e.g. ` .00000812`,
+/// saturating at ±.99999999
+fn format_decimal(value: f64) -> String {
+    let value = saturate(value, FIRST_MEAN_MOTION);
+    let text = format!("{:.8}", value.abs());
+    let text = text.strip_prefix('0').unwrap_or(&text);
+    format!("{}{}", if value < 0.0 { '-' } else { ' ' }, text)
+}
+
+/// A fixed point column: the scale to units of its last digit, and the
+/// range the value in those units has to round into
+type Column = (f64, Range<i64>);
+
+/// The epoch day of the year, `DDD.DDDDDDDD`
+const EPOCH_DAY: Column = (1.0e8, 0..100_000_000_000);
+
+/// The first derivative of mean motion, halved, `±.DDDDDDDD`
+const FIRST_MEAN_MOTION: Column = (1.0e8, -99_999_999..100_000_000);
+
+/// The angles, `DDD.DDDD` degrees
+const ANGLE: Column = (1.0e4, -999_999..10_000_000);
+
+/// The eccentricity, with the decimal point assumed, `DDDDDDD`
+const ECCENTRICITY: Column = (1.0e7, 0..10_000_000);
+
+/// The mean motion, `DD.DDDDDDDD` revolutions/day
+const MEAN_MOTION: Column = (1.0e8, -999_999_999..10_000_000_000);
+
+/// Whether a value is finite and rounds into its column
+fn fits(value: f64, column: Column) -> bool {
+    let (scale, range) = column;
+    value.is_finite() && range.contains(&fixed(value, scale))
+}
+
+/// The nearest value to `value` that fits its column: the value itself if
+/// it fits, the end of the column's range if it's too large or too small,
+/// and zero if it isn't a number
+fn saturate(value: f64, column: Column) -> f64 {
+    let (scale, range) = column.clone();
+    if fits(value, column) {
+        value
+    } else if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(range.start as f64 / scale, (range.end - 1) as f64 / scale)
+    }
+}
+
 /// Read a TLE from Strings
 ///
 /// ### Example
@@ -77,130 +455,927 @@ pub struct TLE {
 /// let line2 = "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990";
 /// let line3 = "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
 ///
-/// let tle = sgp4::tle::load_from_str(line1, line2, line3);
+/// let tle = sgp4::tle::load_from_str(line1, line2, line3).unwrap();
+/// ```
+///
+/// ### Errors
+///
+/// Returns a `TleParseError` describing the first line and field that
+/// couldn't be read, or the first line with a bad checksum.
+pub fn load_from_str(line1: &str, line2: &str, line3: &str) -> Result<TLE, TleParseError> {
+    load_from_str_with_options(line1, line2, line3, &ParseOptions::default())
+}
+
+/// Read a TLE from Strings with the given `ParseOptions`
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::tle::ParseOptions;
+///
+/// // The test case from Spacetrack Report #3 has bad checksums
+/// let line1 = "Test";
+/// let line2 = "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0     8";
+/// let line3 = "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518   105";
+///
+/// let options = ParseOptions { verify_checksums: false, ..ParseOptions::default() };
+/// let tle = sgp4::tle::load_from_str_with_options(line1, line2, line3, &options).unwrap();
 /// ```
-pub fn load_from_str(line1: &str, line2: &str, line3: &str) -> TLE {
+pub fn load_from_str_with_options(line1: &str, line2: &str, line3: &str, options: &ParseOptions) -> Result<TLE, TleParseError> {
 
     // The first line of a TLE (optional) is the Human-readable name of the object
-    let name = String::from(line1);
+    let name = String::from(line1.trim_end());
+
+    // Line 1 can stop after the BSTAR drag term, and line 2 after the mean
+    // motion, if the checksums aren't needed
+    let line2 = &tidy(line2, 1, 61, options)?;
+    let line3 = &tidy(line3, 2, 63, options)?;
+    for (number, line) in [(1, line2), (2, line3)].iter() {
+        if line.len() < LINE_LENGTH {
+            return Err(TleParseError::LineTooShort { line: *number, length: line.len() });
+        }
+    }
 
     // Parse Line 1:
     // Field 1, Columns: 00-01, Content: Line Number (always 1)
-    // Ignore
+    if field(line2, 1, "line number", 0..1)? != "1" {
+        return Err(TleParseError::InvalidField { line: 1, field: "line number", columns: 0..1 });
+    }
 
     // Field 2, Columns: 02–06, Content: Satellite number
-    let sat_number = line2[2..7].parse::<u32>().unwrap();
+    let sat_number = parse_field::<u32>(line2, 1, "satellite number", 2..7)?;
 
     // Field 3, Columns: 07–07, Content: Classification (U=Unclassified)
-    let classification = line2[7..8].chars().next().unwrap();
+    let classification = parse_field::<char>(line2, 1, "classification", 7..8)?;
 
     // Field 4, Columns: 09–10 ...
     // Field 5, Columns: 11-13 ...
     // Field 6, Columns: 14-16, Content: International Designator
-    let int_designator = String::from(line2[9..17].chars().as_str());
+    let int_designator = String::from(field(line2, 1, "international designator", 9..17)?);
 
     // Field 7, Columns: 18–19, Content: Epoch Year (last two digits of year)
-    let mut epoch_year = line2[18..20].parse::<u16>().unwrap();
-    if epoch_year > 56 {
+    let mut epoch_year = parse_field::<u16>(line2, 1, "epoch year", 18..20)?;
+    if epoch_year >= options.year_pivot {
         epoch_year += 1900;
     } else {
         epoch_year += 2000;
     }
 
     // Field 8, Columns: 20–31, Content: Epoch (day of the year and fractional portion of the day)
-    let epoch_day = line2[20..32].parse::<f64>().unwrap();
+    let epoch_day = parse_field::<f64>(line2, 1, "epoch day", 20..32)?;
 
     // Field 9, Columns: 33–42, Content: First Time Derivative of the Mean Motion divided by two
-    let first_mean_motion = line2[33..43].replace(" .", "0.").parse::<f64>().unwrap() * 2.0;
-
-    // Field 10, Columns: 45–52, Content: Second Time Derivative of Mean Motion divided by six (decimal point assumed)
-    let mut second_mean_motion_sign = 1.0;
-    if line2[44..45].chars().next().unwrap() == '-' {
-        second_mean_motion_sign = -1.0;
-    }
-    let second_mean_motion_exp = line2[51..52].parse::<i32>().unwrap();
-    let mut second_mean_motion = line2[45..50].parse::<f64>().unwrap();
-    second_mean_motion /= 100000.0;
-    second_mean_motion *= second_mean_motion_sign;
-    second_mean_motion *= 10_f64.powi(-second_mean_motion_exp);
-    second_mean_motion *= 6.0;
-
-    // Field 11, Columns: 53–60, Content: BSTAR drag term
-    let bstar_sign: f64;
-    if line2[53..54].chars().next().unwrap() == '-' {
-        bstar_sign = -1.0;
-    } else {
-        bstar_sign = 1.0;
-    }
-    let bstar_exp = line2[59..61].parse::<i32>().unwrap();
-    let mut bstar = line2[54..59].parse::<f64>().unwrap();
-    bstar /= 100000.0;
-    bstar *= bstar_sign;
-    bstar *= 10_f64.powi(bstar_exp);
+    let first_mean_motion = parse_field::<f64>(line2, 1, "first time derivative of the mean motion", 33..43)? * 2.0;
+
+    // Field 10, Columns: 44–51, Content: Second Time Derivative of Mean Motion divided by six (decimal point assumed)
+    let second_mean_motion = parse_exponential(line2, 1, "second time derivative of the mean motion", 44..52)? * 6.0;
+
+    // Field 11, Columns: 53–60, Content: BSTAR drag term (decimal point assumed)
+    let bstar = parse_exponential(line2, 1, "BSTAR drag term", 53..61)?;
 
     // Field 12, Columns: 62–63, Content: The number 0 (originally this should have been "Ephemeris type")
     // Ignored
 
     // Field 13, Columns: 64–67, Content: Element set number. Incremented when a new TLE is generated for this object
-    let tle_version: u16;
-    match line2[64..68].parse::<u16>() {
-        Ok(n) => tle_version = n,
-        Err(_) => tle_version = 0,
-    }
+    // Some sources leave this blank
+    let tle_version = if field(line2, 1, "element set number", 64..68)?.trim().is_empty() {
+        0
+    } else {
+        parse_field::<u16>(line2, 1, "element set number", 64..68)?
+    };
 
     // Field 14, Columns: 68–68, Content: Checksum (modulo 10)
-    // TODO: Checksum
+    if options.verify_checksums {
+        verify_checksum(line2, 1)?;
+    }
 
 
     // Parse Line 2:
     // Field 1, Columns: 00-01, Content: Line Number (always 2)
-    // Ignore
+    if field(line3, 2, "line number", 0..1)? != "2" {
+        return Err(TleParseError::InvalidField { line: 2, field: "line number", columns: 0..1 });
+    }
 
     // Field 2, Columns: 02–06, Content: Satellite number
     // Ignore (redundant)
 
     // Field 3, Columns: 08–15, Content: Inclination (degrees)
-    let i = line3[8..16].trim().parse::<f64>().unwrap();
+    let i = parse_field::<f64>(line3, 2, "inclination", 8..16).map(Deg)?;
 
     // Field 4, Columns: 17–24, Content: Right ascension of the ascending node (degrees)
-    let raan = line3[17..25].trim().parse::<f64>().unwrap();
+    let raan = parse_field::<f64>(line3, 2, "right ascension of the ascending node", 17..25).map(Deg)?;
 
-    // Field 5, Columns: 26–32, Content: Eccentricity
-    let e = ("0.".to_string() + &line3[26..33]).parse::<f64>().unwrap();
+    // Field 5, Columns: 26–32, Content: Eccentricity (decimal point assumed)
+    let e = parse_field::<u32>(line3, 2, "eccentricity", 26..33)
+        .map(|e| f64::from(e) / 10_000_000.0)?;
 
     // Field 6, Columns: 34–41, Content: Argument of perigee (degrees)
-    let omega = line3[34..42].trim().parse::<f64>().unwrap();
+    let omega = parse_field::<f64>(line3, 2, "argument of perigee", 34..42).map(Deg)?;
 
     // Field 7, Columns: 43–50, Content: Mean Anomaly (degrees)
-    let mean_anomaly = line3[43..51].trim().parse::<f64>().unwrap();
+    let mean_anomaly = parse_field::<f64>(line3, 2, "mean anomaly", 43..51).map(Deg)?;
 
     // Field 8, Columns: 52–62, Content: Mean Motion (revolutions per day)
-    let mean_motion = line3[52..63].trim().parse::<f64>().unwrap();
+    let mean_motion = parse_field::<f64>(line3, 2, "mean motion", 52..63)?;
 
     // Field 9, Columns: 63–67, Content: Revolution number at epoch (revolutions)
-    let revolution_number = line3[63..68].trim().parse::<u32>().unwrap();
+    // Some sources leave this blank too
+    let revolution_number = if field(line3, 2, "revolution number", 63..68)?.trim().is_empty() {
+        0
+    } else {
+        parse_field::<u32>(line3, 2, "revolution number", 63..68)?
+    };
+
+    // Field 10, Columns: 68–68, Content: Checksum (modulo 10)
+    if options.verify_checksums {
+        verify_checksum(line3, 2)?;
+    }
+
+    Ok(TLE {
+        name,
+        sat_number,
+        classification,
+        int_designator,
+        epoch_year,
+        epoch_day,
+        first_mean_motion,
+        second_mean_motion,
+        bstar,
+        tle_version,
+        i,
+        raan,
+        e,
+        omega,
+        mean_anomaly,
+        mean_motion,
+        revolution_number,
+    })
+}
+
+
+impl TLE {
+
+    /// ## To Lines
+    ///
+    /// Write the elements back out as the two lines of a TLE, with every
+    /// field in its column and the checksums computed. The name is not
+    /// included. Revolution and element set numbers too large for their
+    /// columns wrap around, as they do in published element sets, and drag
+    /// terms too small for theirs are written as zero.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// let line1 = "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990";
+    /// let line2 = "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
+    ///
+    /// let mut tle = sgp4::tle::load_from_str("ISS (ZARYA)", line1, line2).unwrap();
+    /// assert_eq!(tle.to_lines().unwrap(), (String::from(line1), String::from(line2)));
+    ///
+    /// tle.tle_version += 1;
+    /// assert_eq!(tle.to_lines().unwrap().0, "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0 10004");
+    ///
+    /// tle.e = 0.99999999;
+    /// assert!(tle.to_lines().is_err());
+    /// ```
+    ///
+    /// ### Errors
+    ///
+    /// `OutOfRange` for the first value that doesn't fit its columns, such
+    /// as a satellite number over 99999, an eccentricity that rounds to 1,
+    /// or a first derivative of the mean motion of 2 revolutions/day² or
+    /// more.
+    pub fn to_lines(&self) -> Result<(String, String), TleFormatError> {
+        let out_of_range = |line: u8, field: &'static str, columns: Range<usize>| TleFormatError::OutOfRange { line, field, columns };
+        let check = |fits: bool, line: u8, field: &'static str, columns: Range<usize>| {
+            if fits { Ok(()) } else { Err(out_of_range(line, field, columns)) }
+        };
+        check(self.sat_number <= 99999, 1, "satellite number", 2..7)?;
+        check(self.classification.is_ascii(), 1, "classification", 7..8)?;
+        check(self.int_designator.is_ascii() && self.int_designator.len() <= 8, 1, "international designator", 9..17)?;
+        check(fits(self.epoch_day, EPOCH_DAY), 1, "epoch day", 20..32)?;
+        check(fits(self.first_mean_motion / 2.0, FIRST_MEAN_MOTION), 1, "first time derivative of the mean motion", 33..43)?;
+        check(exponential_digits(self.second_mean_motion / 6.0).is_some(), 1, "second time derivative of the mean motion", 44..52)?;
+        check(exponential_digits(self.bstar).is_some(), 1, "BSTAR drag term", 53..61)?;
+        let angles = [
+            (self.i, "inclination", 8..16),
+            (self.raan, "right ascension of the ascending node", 17..25),
+            (self.omega, "argument of perigee", 34..42),
+            (self.mean_anomaly, "mean anomaly", 43..51),
+        ];
+        for (angle, field, columns) in angles.iter().cloned() {
+            check(fits(angle.0, ANGLE), 2, field, columns)?;
+        }
+        check(fits(self.e, ECCENTRICITY), 2, "eccentricity", 26..33)?;
+        check(fits(self.mean_motion, MEAN_MOTION), 2, "mean motion", 52..63)?;
+
+        Ok(self.saturated_lines())
+    }
+
+    /// The two lines as `to_lines` writes them, but with each value that
+    /// doesn't fit its columns written as the nearest that does. Characters
+    /// outside ASCII are written as `?`, and an international designator
+    /// longer than eight characters is cut short.
+    fn saturated_lines(&self) -> (String, String) {
+        let ascii = |c: char| if c.is_ascii() { c } else { '?' };
+        let int_designator: String = self.int_designator.chars().map(ascii).take(8).collect();
+        let sat_number = self.sat_number.min(99999);
+        let angle = |angle: Deg| saturate(angle.0, ANGLE);
+
+        let mut line1 = format!(
+            "1 {:05}{} {:<8} {:02}{:012.8} {} {} {} 0 {:>4}",
+            sat_number,
+            ascii(self.classification),
+            int_designator,
+            self.epoch_year % 100,
+            saturate(self.epoch_day, EPOCH_DAY),
+            format_decimal(self.first_mean_motion / 2.0),
+            format_exponential(self.second_mean_motion / 6.0),
+            format_exponential(self.bstar),
+            self.tle_version % 10000,
+        );
+
+        let mut line2 = format!(
+            "2 {:05} {:>8.4} {:>8.4} {:07} {:>8.4} {:>8.4} {:>11.8}{:>5}",
+            sat_number,
+            angle(self.i),
+            angle(self.raan),
+            fixed(saturate(self.e, ECCENTRICITY), 1.0e7),
+            angle(self.omega),
+            angle(self.mean_anomaly),
+            saturate(self.mean_motion, MEAN_MOTION),
+            self.revolution_number % 100000,
+        );
+
+        for line in [&mut line1, &mut line2].iter_mut() {
+            let sum = checksum(line);
+            line.push(char::from(b'0' + sum));
+        }
+
+        (line1, line2)
+    }
+
+    /// ## Builder
+    ///
+    /// Start an element set from individual values rather than TLE text.
+    /// See [`TleBuilder`](struct.TleBuilder.html).
+    pub fn builder() -> TleBuilder {
+        TleBuilder::default()
+    }
+}
+
+/// The name line, if the TLE has a name, then the two lines of elements as
+/// [`to_lines`](struct.TLE.html#method.to_lines) writes them, separated by
+/// newlines. A value that doesn't fit its columns is written as the nearest
+/// one that does, so the lines always parse, if not always to the same
+/// element set; call `to_lines` to catch those.
+impl fmt::Display for TLE {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (line1, line2) = self.saturated_lines();
+        if !self.name.is_empty() {
+            writeln!(f, "{}", self.name)?;
+        }
+        write!(f, "{}\n{}", line1, line2)
+    }
+}
+
+/// Read a 2-line or 3-line TLE, as [`parse`](fn.parse.html) does
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::tle::TLE;
+///
+/// let text = "\
+/// ISS (ZARYA)
+/// 1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+/// 2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
+///
+/// let tle: TLE = text.parse().unwrap();
+/// assert_eq!(tle.name, "ISS (ZARYA)");
+/// assert_eq!(tle.to_string(), text);
+/// ```
+impl FromStr for TLE {
+    type Err = TleParseError;
+
+    fn from_str(text: &str) -> Result<TLE, TleParseError> {
+        parse(text)
+    }
+}
+
+impl TLE {
+
+    /// Everything the lines hold, at the precision they hold it
+    fn key(&self) -> Key<'_> {
+        Key {
+            sat_number: self.sat_number,
+            epoch_year: self.epoch_year,
+            epoch_day: fixed(self.epoch_day, 1.0e8),
+            classification: self.classification,
+            int_designator: self.int_designator.trim_end(),
+            first_mean_motion: fixed(self.first_mean_motion / 2.0, 1.0e8),
+            second_mean_motion: exponential_digits(self.second_mean_motion / 6.0),
+            bstar: exponential_digits(self.bstar),
+            tle_version: self.tle_version % 10000,
+            i: fixed(self.i.0, 1.0e4),
+            raan: fixed(self.raan.0, 1.0e4),
+            e: fixed(self.e, 1.0e7),
+            omega: fixed(self.omega.0, 1.0e4),
+            mean_anomaly: fixed(self.mean_anomaly.0, 1.0e4),
+            mean_motion: fixed(self.mean_motion, 1.0e8),
+            revolution_number: self.revolution_number % 100000,
+        }
+    }
+}
+
+/// A value in units of the last digit a TLE column holds, `scale` to the
+/// unit
+fn fixed(value: f64, scale: f64) -> i64 {
+    (value * scale).round() as i64
+}
+
+/// The values of an element set as integers in units of the last digit of
+/// their columns, to compare and hash TLEs by without writing out their
+/// lines. The satellite and epoch come first, to sort by.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Key<'a> {
+    sat_number: u32,
+    epoch_year: u16,
+    epoch_day: i64,
+    classification: char,
+    int_designator: &'a str,
+    first_mean_motion: i64,
+    second_mean_motion: Option<(i32, i32)>,
+    bstar: Option<(i32, i32)>,
+    tle_version: u16,
+    i: i64,
+    raan: i64,
+    e: i64,
+    omega: i64,
+    mean_anomaly: i64,
+    mean_motion: i64,
+    revolution_number: u32,
+}
+
+/// Two TLEs are equal when they're the same element set: the same
+/// satellite and epoch, written as the same two lines. Values are compared
+/// at the precision the lines hold them, so an element set read from an
+/// OMM equals the one read from its TLE, and the name isn't compared.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use std::collections::HashSet;
+/// use sgp4::tle::TLE;
+///
+/// let iss: TLE = "\
+/// ISS (ZARYA)
+/// 1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+/// 2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433".parse().unwrap();
+///
+/// let mut renamed = iss.clone();
+/// renamed.name = String::from("ISS");
+/// renamed.epoch_day += 1e-12;
+/// assert_eq!(renamed, iss);
+///
+/// let unique: HashSet<TLE> = vec![iss, renamed].into_iter().collect();
+/// assert_eq!(unique.len(), 1);
+/// ```
+impl PartialEq for TLE {
+    fn eq(&self, other: &TLE) -> bool {
+        self.key() == other.key()
+    }
+}
 
-    // Field 10, Columns: 69–69, Content: Checksum (modulo 10)
-    // TODO: Checksum
+impl Eq for TLE {}
 
-    TLE {
-        name: name,
-        sat_number: sat_number,
-        classification: classification,
-        int_designator: int_designator,
-        epoch_year: epoch_year,
-        epoch_day: epoch_day,
-        first_mean_motion: first_mean_motion,
-        second_mean_motion: second_mean_motion,
-        bstar: bstar,
-        tle_version: tle_version,
-        i: i,
-        raan: raan,
-        e: e,
-        omega: omega,
-        mean_anomaly: mean_anomaly,
-        mean_motion: mean_motion,
-        revolution_number: revolution_number,
+/// TLEs sort by satellite number, then epoch. Different element sets for
+/// the same satellite and epoch sort by the rest of their values, in the
+/// order the lines hold them.
+impl Ord for TLE {
+    fn cmp(&self, other: &TLE) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl PartialOrd for TLE {
+    fn partial_cmp(&self, other: &TLE) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for TLE {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+
+/// ## TLE Build Errors
+///
+/// Why a `TleBuilder` couldn't make an element set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TleBuildError {
+
+    /// A value with no default wasn't given: the epoch or the mean motion
+    Missing(&'static str),
+
+    /// A value is outside what SGP4 can propagate or a TLE can hold
+    OutOfRange(&'static str),
+}
+
+impl fmt::Display for TleBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TleBuildError::Missing(field) => write!(f, "no {} given", field),
+            TleBuildError::OutOfRange(field) => write!(f, "{} is out of range", field),
+        }
+    }
+}
+
+impl error::Error for TleBuildError {}
+
+/// ## TLE Builder
+///
+/// An element set put together from an epoch, mean elements and B*, for
+/// orbits that were designed rather than tracked. Angles are `Deg` or
+/// `Rad`, and the mean motion in revolutions per day, as in a TLE. The epoch and
+/// mean motion must be given; everything else defaults to zero, an
+/// unclassified satellite number 0 and no name.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// use sgp4::angle::{Deg, Rad};
+///
+/// let tle = sgp4::tle::TLE::builder()
+///     .name("CUBESAT")
+///     .epoch(2024, 100.5)
+///     .inclination(Deg(97.4))
+///     .raan(Deg(45.0))
+///     .eccentricity(0.001)
+///     .argp(Rad(std::f64::consts::FRAC_PI_2))
+///     .mean_anomaly(Deg(270.0))
+///     .mean_motion(15.2)
+///     .bstar(2.0e-4)
+///     .build()
+///     .unwrap();
+///
+/// let state = sgp4::propagate(&tle, 90.0).unwrap();
+/// let (line1, line2) = tle.to_lines().unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TleBuilder {
+    name: String,
+    sat_number: u32,
+    classification: Option<char>,
+    int_designator: String,
+    epoch: Option<(u16, f64)>,
+    first_mean_motion: f64,
+    second_mean_motion: f64,
+    bstar: f64,
+    tle_version: u16,
+    inclination: Deg,
+    raan: Deg,
+    eccentricity: f64,
+    argp: Deg,
+    mean_anomaly: Deg,
+    mean_motion: Option<f64>,
+    revolution_number: u32,
+}
+
+impl TleBuilder {
+
+    /// Name of the object
+    pub fn name(mut self, name: &str) -> TleBuilder {
+        self.name = String::from(name);
+        self
+    }
+
+    /// Satellite catalog number, up to 99999
+    pub fn sat_number(mut self, sat_number: u32) -> TleBuilder {
+        self.sat_number = sat_number;
+        self
+    }
+
+    /// Classification (default `U`, unclassified)
+    pub fn classification(mut self, classification: char) -> TleBuilder {
+        self.classification = Some(classification);
+        self
+    }
+
+    /// International designator, up to eight characters (`98067A`)
+    pub fn int_designator(mut self, int_designator: &str) -> TleBuilder {
+        self.int_designator = String::from(int_designator);
+        self
+    }
+
+    /// Epoch as a year and fractional day of the year, where day 1.0 is
+    /// midnight at the start of January 1st. The year must be 1957 to
+    /// 2056, the century a two digit TLE year is read in with the standard
+    /// pivot.
+    pub fn epoch(mut self, year: u16, day_of_year: f64) -> TleBuilder {
+        self.epoch = Some((year, day_of_year));
+        self
+    }
+
+    /// Epoch as a Julian date (UTC)
+    pub fn epoch_julian_date(self, julian_date: f64) -> TleBuilder {
+        let (year, day_of_year) = time::day_of_year_from_julian_date(julian_date);
+        self.epoch(year.max(0) as u16, day_of_year)
+    }
+
+    /// First time derivative of the mean motion (revolutions/day²), as on
+    /// line 1 doubled
+    pub fn first_mean_motion(mut self, first_mean_motion: f64) -> TleBuilder {
+        self.first_mean_motion = first_mean_motion;
+        self
+    }
+
+    /// Second time derivative of the mean motion (revolutions/day³), as on
+    /// line 1 times six
+    pub fn second_mean_motion(mut self, second_mean_motion: f64) -> TleBuilder {
+        self.second_mean_motion = second_mean_motion;
+        self
+    }
+
+    /// B* drag term (per Earth radius)
+    pub fn bstar(mut self, bstar: f64) -> TleBuilder {
+        self.bstar = bstar;
+        self
+    }
+
+    /// Element set number
+    pub fn tle_version(mut self, tle_version: u16) -> TleBuilder {
+        self.tle_version = tle_version;
+        self
+    }
+
+    /// Inclination (0° to 180°), in `Deg` or `Rad`
+    pub fn inclination<A: Into<Deg>>(mut self, inclination: A) -> TleBuilder {
+        self.inclination = inclination.into();
+        self
+    }
+
+    /// Right ascension of the ascending node, in `Deg` or `Rad`
+    pub fn raan<A: Into<Deg>>(mut self, raan: A) -> TleBuilder {
+        self.raan = raan.into();
+        self
+    }
+
+    /// Eccentricity (0 to less than 1)
+    pub fn eccentricity(mut self, eccentricity: f64) -> TleBuilder {
+        self.eccentricity = eccentricity;
+        self
+    }
+
+    /// Argument of perigee, in `Deg` or `Rad`
+    pub fn argp<A: Into<Deg>>(mut self, argp: A) -> TleBuilder {
+        self.argp = argp.into();
+        self
+    }
+
+    /// Mean anomaly, in `Deg` or `Rad`
+    pub fn mean_anomaly<A: Into<Deg>>(mut self, mean_anomaly: A) -> TleBuilder {
+        self.mean_anomaly = mean_anomaly.into();
+        self
+    }
+
+    /// Kozai mean motion (revolutions per day)
+    pub fn mean_motion(mut self, mean_motion: f64) -> TleBuilder {
+        self.mean_motion = Some(mean_motion);
+        self
+    }
+
+    /// Revolution number at the epoch
+    pub fn revolution_number(mut self, revolution_number: u32) -> TleBuilder {
+        self.revolution_number = revolution_number;
+        self
+    }
+
+    /// ## Build
+    ///
+    /// Check the values, including that each fits its columns of the
+    /// lines, and make the element set. Angles outside 0° to 360° are
+    /// wrapped into it.
+    pub fn build(&self) -> Result<TLE, TleBuildError> {
+        let (epoch_year, epoch_day) = self.epoch.ok_or(TleBuildError::Missing("epoch"))?;
+        let mean_motion = self.mean_motion.ok_or(TleBuildError::Missing("mean motion"))?;
+
+        let check = |ok: bool, field: &'static str| if ok { Ok(()) } else { Err(TleBuildError::OutOfRange(field)) };
+        check(self.sat_number <= 99999, "satellite number")?;
+        check(self.int_designator.len() <= 8, "international designator")?;
+        check((1900 + YEAR_PIVOT..2000 + YEAR_PIVOT).contains(&epoch_year), "epoch year")?;
+        check((1.0..367.0).contains(&epoch_day), "epoch day")?;
+        check(mean_motion > 0.0 && mean_motion.is_finite(), "mean motion")?;
+        check((0.0..1.0).contains(&self.eccentricity), "eccentricity")?;
+        check((0.0..=180.0).contains(&self.inclination.0), "inclination")?;
+        for &(angle, field) in [(self.raan, "right ascension"), (self.argp, "argument of perigee"), (self.mean_anomaly, "mean anomaly")].iter() {
+            check(angle.0.is_finite(), field)?;
+        }
+        check(self.bstar.is_finite() && self.first_mean_motion.is_finite() && self.second_mean_motion.is_finite(), "drag terms")?;
+
+        let tle = TLE {
+            name: self.name.clone(),
+            sat_number: self.sat_number,
+            classification: self.classification.unwrap_or('U'),
+            int_designator: self.int_designator.clone(),
+            epoch_year,
+            epoch_day,
+            first_mean_motion: self.first_mean_motion,
+            second_mean_motion: self.second_mean_motion,
+            bstar: self.bstar,
+            tle_version: self.tle_version,
+            i: self.inclination,
+            raan: self.raan.normalized(),
+            e: self.eccentricity,
+            omega: self.argp.normalized(),
+            mean_anomaly: self.mean_anomaly.normalized(),
+            mean_motion,
+            revolution_number: self.revolution_number,
+        };
+        match tle.to_lines() {
+            Ok(_) => Ok(tle),
+            Err(TleFormatError::OutOfRange { field, .. }) => Err(TleBuildError::OutOfRange(field)),
+        }
+    }
+}
+
+/// Read a TLE from text holding either two lines of elements, or a name
+/// line followed by two lines of elements
+///
+/// The format is detected from whether the first line starts with `1 `.
+/// Blank lines and surrounding whitespace are ignored, and a 2-line element
+/// set gets an empty `name`.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let tle = sgp4::tle::parse("\
+/// 1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+/// 2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+/// ").unwrap();
+///
+/// assert_eq!(tle.name, "");
+/// assert_eq!(tle.sat_number, 25544);
+/// ```
+pub fn parse(text: &str) -> Result<TLE, TleParseError> {
+    parse_with_options(text, &ParseOptions::default())
+}
+
+/// Read a 2-line or 3-line TLE with the given `ParseOptions`
+pub fn parse_with_options(text: &str, options: &ParseOptions) -> Result<TLE, TleParseError> {
+    let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+
+    match lines[..] {
+        [line1, line2] if is_line1(line1) => load_from_str_with_options("", line1, line2, options),
+        [name, line1, line2] => load_from_str_with_options(name_from(name), line1, line2, options),
+        _ => Err(TleParseError::LineCount { found: lines.len() }),
+    }
+}
+
+/// ## Read Errors
+///
+/// Why a file of many TLEs couldn't be read.
+#[derive(Debug)]
+pub enum ReadError {
+
+    /// The file couldn't be read
+    #[cfg(feature = "std")]
+    Io(io::Error),
+
+    /// An entry couldn't be parsed
+    Parse {
+        /// Line number (counting from 1) in the input where the entry starts
+        line: usize,
+        /// What was wrong with the entry
+        error: TleParseError,
+    },
+
+    /// The input ended part way through an entry
+    Truncated {
+        /// Line number (counting from 1) in the input where the entry starts
+        line: usize,
+    },
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            #[cfg(feature = "std")]
+            ReadError::Io(ref error) => write!(f, "{}", error),
+            ReadError::Parse { line, ref error } => write!(f, "entry starting at line {}: {}", line, error),
+            ReadError::Truncated { line } => write!(f, "entry starting at line {} is incomplete", line),
+        }
+    }
+}
+
+impl error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            #[cfg(feature = "std")]
+            ReadError::Io(ref error) => Some(error),
+            ReadError::Parse { ref error, .. } => Some(error),
+            ReadError::Truncated { .. } => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> ReadError {
+        ReadError::Io(error)
+    }
+}
+
+/// Whether a line looks like the first line of elements rather than a name
+fn is_line1(line: &str) -> bool {
+    line.starts_with("1 ")
+}
+
+/// Whether a line of a catalog has nothing to read: blank, or a `#` comment
+fn is_skipped(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.starts_with('#')
+}
+
+/// The object name from a name line. Space-Track's 3LE format prefixes
+/// names with "0 ".
+fn name_from(line: &str) -> &str {
+    let line = line.trim_end();
+    line.strip_prefix("0 ").unwrap_or(line)
+}
+
+/// Assemble the next 2-line or 3-line entry from a source of numbered,
+/// non-blank lines. Returns `None` once the source is exhausted.
+fn next_entry<S, F>(mut next_line: F, options: &ParseOptions) -> Option<Result<TLE, ReadError>>
+    where S: AsRef<str>,
+          F: FnMut() -> Option<Result<(usize, S), ReadError>>
+{
+    let (number, first) = match next_line()? {
+        Ok(line) => line,
+        Err(error) => return Some(Err(error)),
+    };
+    let mut next = || next_line().unwrap_or(Err(ReadError::Truncated { line: number }));
+
+    let second = match next() {
+        Ok((_, line)) => line,
+        Err(error) => return Some(Err(error)),
+    };
+
+    // A line 1 followed by a line 2 is an entry without a name
+    let result = if is_line1(first.as_ref()) && second.as_ref().starts_with("2 ") {
+        load_from_str_with_options("", first.as_ref(), second.as_ref(), options)
+    } else {
+        let third = match next() {
+            Ok((_, line)) => line,
+            Err(error) => return Some(Err(error)),
+        };
+        load_from_str_with_options(name_from(first.as_ref()), second.as_ref(), third.as_ref(), options)
+    };
+
+    Some(result.map_err(|error| ReadError::Parse { line: number, error }))
+}
+
+/// Read every TLE in a catalog
+///
+/// The text may hold any mix of 2-line entries and 3-line entries (with a
+/// name line first, as Celestrak publishes them). Blank lines and lines
+/// starting with `#` are skipped. Entries without a name get an empty
+/// `name`.
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// let catalog = "\
+/// ISS (ZARYA)
+/// 1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+/// 2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433
+/// 1 11801U          80230.29629788  .01431103  00000-0  14311-1 0    13
+/// 2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848    13
+/// ";
+///
+/// let tles = sgp4::tle::parse_all(catalog).unwrap();
+/// assert_eq!(tles.len(), 2);
+/// ```
+///
+/// ### Errors
+///
+/// Stops at the first entry that can't be parsed and returns a `ReadError`
+/// with the line number it starts at.
+pub fn parse_all(text: &str) -> Result<Vec<TLE>, ReadError> {
+    parse_all_with_options(text, &ParseOptions::default())
+}
+
+/// Read every TLE in a catalog with the given `ParseOptions`
+pub fn parse_all_with_options(text: &str, options: &ParseOptions) -> Result<Vec<TLE>, ReadError> {
+    let mut lines = text.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|&(_, line)| !is_skipped(line));
+
+    let mut tles = Vec::new();
+    while let Some(tle) = next_entry(|| lines.next().map(Ok), options) {
+        tles.push(tle?);
+    }
+
+    Ok(tles)
+}
+
+/// Read every TLE in a catalog file. See [`parse_all`](fn.parse_all.html)
+/// for the formats accepted.
+#[cfg(feature = "std")]
+pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Vec<TLE>, ReadError> {
+    parse_all(&fs::read_to_string(path)?)
+}
+
+
+/// ## TLE Reader
+///
+/// Read TLEs one at a time from any buffered reader, such as a large
+/// catalog file or a network stream, without holding the whole catalog in
+/// memory. Accepts the same formats as [`parse_all`](fn.parse_all.html).
+///
+/// Each item is either the next TLE or the reason the next entry couldn't
+/// be read; reading carries on after an error.
+///
+/// ### Example
+///
+/// ```no_run
+/// extern crate sgp4;
+///
+/// use std::fs::File;
+/// use std::io::BufReader;
+/// use sgp4::tle::TleReader;
+///
+/// let file = File::open("active.txt").unwrap();
+/// for tle in TleReader::new(BufReader::new(file)) {
+///     let tle = tle.unwrap();
+///     println!("{}", tle.name);
+/// }
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct TleReader<R> {
+    reader: R,
+    options: ParseOptions,
+    line_number: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: io::BufRead> TleReader<R> {
+
+    /// Read TLEs with the default `ParseOptions`
+    pub fn new(reader: R) -> TleReader<R> {
+        TleReader::with_options(reader, ParseOptions::default())
+    }
+
+    /// Read TLEs with the given `ParseOptions`
+    pub fn with_options(reader: R, options: ParseOptions) -> TleReader<R> {
+        TleReader { reader, options, line_number: 0 }
+    }
+
+    /// The next line worth reading, with its line number
+    fn next_line(&mut self) -> Option<Result<(usize, String), ReadError>> {
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(_) => {
+                    self.line_number += 1;
+                    if !is_skipped(&line) {
+                        let length = line.trim_end_matches(&['\r', '\n'][..]).len();
+                        line.truncate(length);
+                        return Some(Ok((self.line_number, line)));
+                    }
+                }
+                Err(error) => return Some(Err(ReadError::Io(error))),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: io::BufRead> Iterator for TleReader<R> {
+    type Item = Result<TLE, ReadError>;
+
+    fn next(&mut self) -> Option<Result<TLE, ReadError>> {
+        let options = self.options;
+        next_entry(|| self.next_line(), &options)
     }
 }
 
@@ -208,7 +1383,11 @@ pub fn load_from_str(line1: &str, line2: &str, line3: &str) -> TLE {
 #[cfg(test)]
 mod tests {
 
-    use super::load_from_str;
+    use super::{checksum, format_decimal, format_exponential, load_from_str, load_from_str_with_options, parse_all, parse_exponential, ParseOptions, ReadError, TleFormatError, TleParseError, YEAR_PIVOT};
+    use angle::Deg;
+    use test_support;
+
+    const UNCHECKED: ParseOptions = ParseOptions { verify_checksums: false, year_pivot: YEAR_PIVOT };
 
     #[test]
     fn spacetrack_report_3_sgp4_test_case() {
@@ -219,8 +1398,8 @@ mod tests {
         let line2 = "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0     8";
         let line3 = "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518   105";
 
-        // Load our TLE:
-        let tle = load_from_str(line1, line2, line3);
+        // Load our TLE (the checksums in the report are wrong):
+        let tle = load_from_str_with_options(line1, line2, line3, &UNCHECKED).unwrap();
 
         // We should get these values:
         assert_eq!(tle.name, "");
@@ -233,12 +1412,537 @@ mod tests {
         assert_eq!(tle.second_mean_motion, 0.13844e-3*6.0);
         assert_eq!(tle.bstar, 0.66816e-4);
         assert_eq!(tle.tle_version, 0);
-        assert_eq!(tle.i, 72.8435);
-        assert_eq!(tle.raan, 115.9689);
+        assert_eq!(tle.i, Deg(72.8435));
+        assert_eq!(tle.raan, Deg(115.9689));
         assert_eq!(tle.e, 0.0086731);
-        assert_eq!(tle.omega, 52.6988);
-        assert_eq!(tle.mean_anomaly, 110.5714);
+        assert_eq!(tle.omega, Deg(52.6988));
+        assert_eq!(tle.mean_anomaly, Deg(110.5714));
         assert_eq!(tle.mean_motion, 16.05824518);
         assert_eq!(tle.revolution_number, 10);
     }
+
+    #[test]
+    fn year_pivot() {
+        let line2 = "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518   105";
+        let year = |year: &str, options: &ParseOptions| {
+            let line1 = format!("1 88888U          {}275.98708465  .00073094  13844-3  66816-4 0     8", year);
+            load_from_str_with_options("", &line1, line2, options).unwrap().epoch_year
+        };
+
+        assert_eq!(year("57", &UNCHECKED), 1957);
+        assert_eq!(year("99", &UNCHECKED), 1999);
+        assert_eq!(year("00", &UNCHECKED), 2000);
+        assert_eq!(year("56", &UNCHECKED), 2056);
+
+        let later = ParseOptions { year_pivot: 70, ..UNCHECKED };
+        assert_eq!(year("57", &later), 2057);
+        assert_eq!(year("70", &later), 1970);
+
+        let historical = ParseOptions { year_pivot: 0, ..UNCHECKED };
+        assert_eq!(year("00", &historical), 1900);
+        let modern = ParseOptions { year_pivot: 100, ..UNCHECKED };
+        assert_eq!(year("99", &modern), 2099);
+    }
+
+    #[test]
+    fn short_line() {
+        let line2 = "1 88888U          80275.98708465  .00073094  13844-3";
+        let line3 = "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518   105";
+
+        assert_eq!(load_from_str("", line2, line3).err(), Some(TleParseError::LineTooShort { line: 1, length: 52 }));
+    }
+
+    #[test]
+    fn invalid_field() {
+        let line2 = "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0     8";
+        let line3 = "2 88888  72.84x5 115.9689 0086731  52.6988 110.5714 16.05824518   105";
+
+        let error = load_from_str_with_options("", line2, line3, &UNCHECKED).err().unwrap();
+        assert_eq!(error, TleParseError::InvalidField { line: 2, field: "inclination", columns: 8..16 });
+        assert_eq!(error.to_string(), "line 2, columns 8-15: invalid inclination");
+    }
+
+    #[test]
+    fn swapped_lines() {
+        let line2 = "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0     8";
+        let line3 = "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518   105";
+
+        assert_eq!(load_from_str_with_options("", line3, line2, &UNCHECKED).err(), Some(TleParseError::InvalidField { line: 1, field: "line number", columns: 0..1 }));
+    }
+
+    #[test]
+    fn checksums() {
+        let line1 = "ISS (ZARYA)";
+        let line2 = "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990";
+        let line3 = "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
+        assert!(load_from_str(line1, line2, line3).is_ok());
+
+        // Missing checksum
+        assert_eq!(load_from_str(line1, &line2[..68], line3).err(), Some(TleParseError::LineTooShort { line: 1, length: 68 }));
+        assert!(load_from_str_with_options(line1, &line2[..68], line3, &UNCHECKED).is_ok());
+    }
+
+    #[test]
+    fn checksum_digits() {
+        // The last column of each line is its checksum
+        for line in [
+            "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+            "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+            "1 11801U          80230.29629788  .01431103  00000-0  14311-1 0    13",
+            "2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848    13",
+            "1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753",
+            "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667",
+            "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0     9",
+        ].iter() {
+            let expected = line[68..].parse::<u8>().unwrap();
+            assert_eq!(checksum(line), expected, "{}", line);
+            assert_eq!(checksum(&line[..68]), expected);
+        }
+
+        // Minus signs count as one, everything else but digits as zero
+        assert_eq!(checksum("-"), 1);
+        assert_eq!(checksum("1 -+.A 9"), 1);
+        assert_eq!(checksum(""), 0);
+    }
+
+    /// Damaged copies of the ISS lines, as they turn up in real feeds
+    #[test]
+    fn sloppy_lines() {
+        let line1 = "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990";
+        let line2 = "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
+        let clean = load_from_str("ISS (ZARYA)", line1, line2).unwrap();
+        let with_checksum = |line: &str| format!("{}{}", line, checksum(line));
+
+        let readable = [
+            // Windows line endings and trailing blanks
+            ("ISS (ZARYA)\r", format!("{}\r", line1), format!("{}\r\n", line2)),
+            ("ISS (ZARYA)   ", format!("{}   ", line1), format!("{} \t", line2)),
+            // A tab for a single space
+            ("ISS (ZARYA)", line1.replacen(' ', "\t", 1), line2.replacen("  ", " \t", 1)),
+            // Explicit plus signs
+            ("ISS (ZARYA)", line1.replace(" 11901-4", "+11901-4"), String::from(line2)),
+            ("ISS (ZARYA)", with_checksum(&line1[..68].replace(" 00000-0", "+00000+0")), String::from(line2)),
+            ("ISS (ZARYA)", line1.replace(" .00000812", "+.00000812"), String::from(line2)),
+        ];
+        for (name, line1, line2) in readable.iter() {
+            let tle = load_from_str(name, line1, line2).unwrap();
+            assert_eq!(tle.name, clean.name);
+            assert_eq!(tle.to_lines(), clean.to_lines());
+        }
+
+        // Blank element set and revolution numbers read as zero
+        let tle = load_from_str("", &with_checksum(&format!("{}    ", &line1[..64])), &with_checksum(&format!("{}     ", &line2[..63]))).unwrap();
+        assert_eq!((tle.tle_version, tle.revolution_number), (0, 0));
+
+        // Without checksums the optional trailing fields can be missing
+        let tle = load_from_str_with_options("", &line1[..61], &line2[..63], &UNCHECKED).unwrap();
+        assert_eq!((tle.bstar, tle.tle_version, tle.mean_motion, tle.revolution_number), (clean.bstar, 0, clean.mean_motion, 0));
+
+        // A tab that stood for several spaces can't be undone
+        let error = load_from_str("", &line1.replacen("   ", "\t", 1), line2).unwrap_err();
+        assert_eq!(error, TleParseError::UnexpectedCharacter { line: 1, column: 15, found: '\t' });
+        assert_eq!(error.to_string(), "line 1, column 15: unexpected character '\\t'");
+
+        // Not ASCII, which would shift the columns after it
+        let error = load_from_str("", line1, &line2.replace("51.6406", "51٫6406")).unwrap_err();
+        assert_eq!(error, TleParseError::UnexpectedCharacter { line: 2, column: 11, found: '٫' });
+    }
+
+    #[test]
+    fn truncated_lines() {
+        let line1 = "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990";
+        let line2 = "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
+
+        // Every truncation is an error, never a panic, and without
+        // checksums anything through the last required field reads
+        for end in 0..line1.len() {
+            let length = line1[..end].trim_end().len();
+            assert_eq!(load_from_str("", &line1[..end], line2).unwrap_err(), TleParseError::LineTooShort { line: 1, length });
+            let unchecked = load_from_str_with_options("", &line1[..end], line2, &UNCHECKED);
+            assert_eq!(unchecked.is_ok(), length >= 61, "{}", &line1[..end]);
+        }
+        for end in 0..line2.len() {
+            let length = line2[..end].trim_end().len();
+            assert_eq!(load_from_str("", line1, &line2[..end]).unwrap_err(), TleParseError::LineTooShort { line: 2, length });
+            let unchecked = load_from_str_with_options("", line1, &line2[..end], &UNCHECKED);
+            assert_eq!(unchecked.is_ok(), length >= 63, "{}", &line2[..end]);
+        }
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let line2 = "1 88888U          80275.98708465  .00073094  13844-3  66816-4 0     8";
+        let line3 = "2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518   105";
+
+        let error = load_from_str("", line2, line3).err().unwrap();
+        assert_eq!(error, TleParseError::ChecksumMismatch { line: 1, expected: 9, found: 8 });
+        assert_eq!(error.to_string(), "line 1 checksum is 8 but the line sums to 9");
+    }
+
+    #[test]
+    fn exponential_fields() {
+        let some = String::from;
+        assert_eq!(format_exponential(0.0), some(" 00000-0"));
+        assert_eq!(format_exponential(1.1901e-5), some(" 11901-4"));
+        assert_eq!(format_exponential(-0.66816e-4), some("-66816-4"));
+        assert_eq!(format_exponential(0.5), some(" 50000+0"));
+        assert_eq!(format_exponential(0.0999999999), some(" 10000+0"));
+        assert_eq!(format_decimal(0.00000812), some(" .00000812"));
+        assert_eq!(format_decimal(-0.00073094), some("-.00073094"));
+
+        for text in [" 11901-4", "-66816-4", " 13844-3", " 10000+0", "-99999-9", " 99999+9"].iter() {
+            let value = parse_exponential(text, 1, "test", 0..8).unwrap();
+            assert_eq!(format_exponential(value), some(text));
+        }
+
+        // Too small for the exponent is zero, too large is the largest that
+        // fits
+        assert_eq!(format_exponential(1.0e-12), some(" 00000-0"));
+        assert_eq!(format_exponential(0.999995e9), some(" 99999+9"));
+        assert_eq!(format_exponential(f64::NEG_INFINITY), some("-99999+9"));
+        assert_eq!(format_exponential(f64::NAN), some(" 00000-0"));
+        assert_eq!(format_decimal(0.999999995), some(" .99999999"));
+        assert_eq!(format_decimal(-1.5), some("-.99999999"));
+        assert_eq!(format_decimal(f64::NAN), some(" .00000000"));
+    }
+
+    #[test]
+    fn to_lines() {
+        let elements = [
+            ("1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+             "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433"),
+            ("1 11801U          80230.29629788  .01431103  00000-0  14311-1 0    13",
+             "2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848    13"),
+            ("1 00005U 58002B   00179.78495062 -.00000023  00000-0 -28098-4 0  4753",
+             "2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667"),
+        ];
+
+        for &(line1, line2) in elements.iter() {
+            let tle = load_from_str_with_options("", line1, line2, &UNCHECKED).unwrap();
+            let (written1, written2) = tle.to_lines().unwrap();
+
+            // Element set numbers and checksums aren't always written
+            // consistently, so compare the fields and check the sums
+            assert_eq!(&written1[..64], &line1[..64]);
+            assert_eq!(&written2[..68], &line2[..68]);
+            assert_eq!(load_from_str("", &written1, &written2).unwrap().sat_number, tle.sat_number);
+        }
+
+        // Values that don't fit their columns are errors, not garbled lines
+        let iss = load_from_str("", elements[0].0, elements[0].1).unwrap();
+        let out_of_range = |line, field, columns| Err(TleFormatError::OutOfRange { line, field, columns });
+        let mut tle = iss.clone();
+        tle.first_mean_motion = 2.0;
+        assert_eq!(tle.to_lines(), out_of_range(1, "first time derivative of the mean motion", 33..43));
+        let mut tle = iss.clone();
+        tle.bstar = 1.0e10;
+        assert_eq!(tle.to_lines(), out_of_range(1, "BSTAR drag term", 53..61));
+        let mut tle = iss.clone();
+        tle.int_designator = String::from("1998-067A");
+        assert_eq!(tle.to_lines(), out_of_range(1, "international designator", 9..17));
+        let mut tle = iss.clone();
+        tle.sat_number = 100000;
+        assert_eq!(tle.to_lines(), out_of_range(1, "satellite number", 2..7));
+        let mut tle = iss.clone();
+        tle.e = 0.99999996;
+        assert_eq!(tle.to_lines(), out_of_range(2, "eccentricity", 26..33));
+        let mut tle = iss.clone();
+        tle.raan = Deg(1000.0);
+        assert_eq!(tle.to_lines(), out_of_range(2, "right ascension of the ascending node", 17..25));
+        let mut tle = iss.clone();
+        tle.mean_motion = 100.0;
+        assert_eq!(tle.to_lines(), out_of_range(2, "mean motion", 52..63));
+        assert_eq!(
+            tle.to_lines().unwrap_err().to_string(),
+            "line 2, columns 52-62: mean motion doesn't fit",
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use serde_json;
+        use super::TLE;
+
+        let tle = test_support::iss();
+
+        let json = serde_json::to_string(&tle).unwrap();
+        let copy: TLE = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(copy.name, tle.name);
+        assert_eq!(copy.int_designator, tle.int_designator);
+        assert_eq!(copy.epoch_day, tle.epoch_day);
+        assert_eq!(copy.bstar, tle.bstar);
+        assert_eq!(copy.mean_motion, tle.mean_motion);
+        assert_eq!(copy.revolution_number, tle.revolution_number);
+    }
+
+    #[test]
+    fn catalog() {
+        let catalog = "ISS (ZARYA)        \r\n\
+            1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990\r\n\
+            2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433\r\n\
+            \r\n\
+            1 11801U          80230.29629788  .01431103  00000-0  14311-1 0    13\r\n\
+            2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848    13\r\n\
+            0 ISS (ZARYA)\r\n\
+            1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990\r\n\
+            2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433\r\n";
+
+        let tles = parse_all(catalog).unwrap();
+        assert_eq!(tles.len(), 3);
+        assert_eq!(tles[0].name, "ISS (ZARYA)");
+        assert_eq!(tles[0].sat_number, 25544);
+        assert_eq!(tles[1].name, "");
+        assert_eq!(tles[1].sat_number, 11801);
+        assert_eq!(tles[2].name, "ISS (ZARYA)");
+    }
+
+    #[test]
+    fn catalog_errors() {
+        let bad = "ISS (ZARYA)\n\
+            1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990\n\
+            2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433\n\
+            TEST\n\
+            1 88888U          80275.98708465  .00073094  13844-3  66816-4 0     8\n\
+            2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518   105\n";
+        match parse_all(bad) {
+            Err(ReadError::Parse { line: 4, error: TleParseError::ChecksumMismatch { line: 1, .. } }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let truncated = "ISS (ZARYA)\n\
+            1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990\n";
+        match parse_all(truncated) {
+            Err(ReadError::Truncated { line: 1 }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn catalog_file() {
+        use std::fs;
+        use super::load_from_file;
+
+        let path = ::std::env::temp_dir().join("sgp4-catalog-file-test.txt");
+        fs::write(&path, "ISS (ZARYA)\n\
+            1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990\n\
+            2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433\n").unwrap();
+        let tles = load_from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(tles.len(), 1);
+
+        match load_from_file(&path) {
+            Err(ReadError::Io(_)) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn reader() {
+        use super::TleReader;
+
+        let catalog = "# Two satellites\r\n\
+            ISS (ZARYA)\r\n\
+            1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990\r\n\
+            2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433\r\n\
+            \r\n\
+            TEST\n\
+            1 88888U          80275.98708465  .00073094  13844-3  66816-4 0     8\n\
+            2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518   105\n\
+            1 11801U          80230.29629788  .01431103  00000-0  14311-1 0    13\n\
+            2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848    13";
+
+        let results: Vec<_> = TleReader::new(catalog.as_bytes()).collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().name, "ISS (ZARYA)");
+        match results[1] {
+            Err(ReadError::Parse { line: 6, .. }) => {}
+            ref other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(results[2].as_ref().unwrap().sat_number, 11801);
+
+        let results: Vec<_> = TleReader::with_options(catalog.as_bytes(), UNCHECKED).collect();
+        assert!(results.iter().all(|result| result.is_ok()));
+    }
+
+    #[test]
+    fn detect_format() {
+        use super::parse;
+
+        let line1 = "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990";
+        let line2 = "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
+
+        let tle = parse(&format!("{}\n{}", line1, line2)).unwrap();
+        assert_eq!(tle.name, "");
+
+        let tle = parse(&format!("ISS (ZARYA)\r\n{}\r\n{}\r\n", line1, line2)).unwrap();
+        assert_eq!(tle.name, "ISS (ZARYA)");
+
+        let tle = parse(&format!("0 ISS (ZARYA)\n{}\n{}", line1, line2)).unwrap();
+        assert_eq!(tle.name, "ISS (ZARYA)");
+
+        assert_eq!(parse(line1).unwrap_err(), TleParseError::LineCount { found: 1 });
+        assert_eq!(parse(&format!("ISS\n{}\n{}\n{}", line1, line2, line2)).unwrap_err(), TleParseError::LineCount { found: 4 });
+    }
+
+    #[test]
+    fn from_str_and_display() {
+        use super::TLE;
+
+        let line1 = "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990";
+        let line2 = "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
+
+        let tle: TLE = format!("ISS (ZARYA)\n{}\n{}\n", line1, line2).parse().unwrap();
+        assert_eq!(tle.name, "ISS (ZARYA)");
+        assert_eq!(tle.to_string(), format!("ISS (ZARYA)\n{}\n{}", line1, line2));
+
+        // Without a name, and composed with other text
+        let unnamed: TLE = format!("{}\n{}", line1, line2).parse().unwrap();
+        assert_eq!(unnamed.to_string(), format!("{}\n{}", line1, line2));
+        assert_eq!(format!("{}\n", unnamed).parse::<TLE>().unwrap().to_lines(), unnamed.to_lines());
+
+        // Canonical lines, whatever the input looked like
+        let sloppy = format!("ISS (ZARYA)  \r\n\r\n{}\r\n{}  ", line1.replace("U 98067A ", "U 98067A\t"), line2);
+        assert_eq!(sloppy.parse::<TLE>().unwrap().to_string(), tle.to_string());
+
+        assert_eq!(line1.parse::<TLE>().unwrap_err(), TleParseError::LineCount { found: 1 });
+    }
+
+    #[test]
+    fn display_out_of_range() {
+        use super::TLE;
+
+        // Every field out of range is written saturated, not a panic
+        let mut tle = test_support::iss();
+        tle.sat_number = 123_456;
+        tle.classification = 'é';
+        tle.int_designator = String::from("1998-067A");
+        tle.epoch_day = -1.0;
+        tle.first_mean_motion = f64::NAN;
+        tle.second_mean_motion = f64::INFINITY;
+        tle.bstar = -1.0e10;
+        tle.i = Deg(f64::NAN);
+        tle.raan = Deg(1000.0);
+        tle.omega = Deg(-1000.0);
+        tle.e = 2.0;
+        tle.mean_motion = 100.0;
+        assert!(tle.to_lines().is_err());
+
+        let text = tle.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(&lines[1][..68], "1 99999? 1998-067 16000.00000000  .00000000  99999+9 -99999+9 0  999");
+        assert_eq!(&lines[2][..68], "2 99999   0.0000 999.9999 9999999 -99.9999 274.3426 99.99999999 1143");
+
+        // The saturated lines are still a valid element set
+        let written: TLE = text.parse().unwrap();
+        assert_eq!(written.sat_number, 99999);
+        assert_eq!(written.to_string(), text);
+    }
+
+    #[test]
+    fn equality_and_ordering() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::{BTreeSet, HashSet};
+        use std::hash::{Hash, Hasher};
+        use super::TLE;
+
+        let hash = |tle: &TLE| {
+            let mut hasher = DefaultHasher::new();
+            tle.hash(&mut hasher);
+            hasher.finish()
+        };
+        let iss = test_support::iss();
+
+        // Differences finer than the lines hold, and the name, don't count
+        let mut same = iss.clone();
+        same.name = String::new();
+        same.epoch_day += 1e-11;
+        same.bstar = 1.1901e-5;
+        same.raan = Deg(211.41561);
+        same.int_designator = String::from("98067A  ");
+        assert_eq!(same, iss);
+        assert_eq!(hash(&same), hash(&iss));
+        assert_eq!(same.to_lines().unwrap(), iss.to_lines().unwrap());
+
+        // A change in the last digit of any column does
+        let mut digit = iss.clone();
+        digit.raan = Deg(211.4157);
+        let mut drag = iss.clone();
+        drag.bstar = 1.1902e-5;
+
+        let mut version = iss.clone();
+        version.tle_version += 1;
+        let mut century = iss.clone();
+        century.epoch_year = 1916;
+        let mut later = iss.clone();
+        later.epoch_day += 0.5;
+        let mut other = iss.clone();
+        other.sat_number = 25545;
+        for different in [&digit, &drag, &version, &century, &later, &other].iter() {
+            assert_ne!(*different, &iss);
+        }
+
+        // By satellite, then epoch
+        let mut sorted = [other.clone(), later.clone(), iss.clone(), century.clone(), version.clone()];
+        sorted.sort();
+        assert_eq!(sorted[0], century);
+        assert!(sorted[1] == iss || sorted[1] == version);
+        assert_eq!(sorted[3], later);
+        assert_eq!(sorted[4], other);
+        assert!(iss < later && later < other);
+
+        let unique: HashSet<TLE> = vec![iss.clone(), same.clone(), later.clone()].into_iter().collect();
+        assert_eq!(unique.len(), 2);
+        let unique: BTreeSet<TLE> = vec![later.clone(), same, iss.clone()].into_iter().collect();
+        assert_eq!(unique.into_iter().collect::<Vec<_>>(), vec![iss, later]);
+    }
+
+    #[test]
+    fn builder() {
+        use super::{TleBuildError, TLE};
+
+        let line1 = "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990";
+        let line2 = "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
+        let builder = TLE::builder()
+            .name("ISS (ZARYA)")
+            .sat_number(25544)
+            .int_designator("98067A")
+            .epoch(2016, 210.59822142)
+            .first_mean_motion(0.00001624)
+            .bstar(0.11901e-4)
+            .tle_version(999)
+            .inclination(Deg(51.6406))
+            .raan(Deg(211.4156 - 360.0))
+            .eccentricity(0.000178)
+            .argp(Deg(85.8307))
+            .mean_anomaly(Deg(274.3426))
+            .mean_motion(15.54888439)
+            .revolution_number(1143);
+        let tle = builder.build().unwrap();
+        assert_eq!(tle.classification, 'U');
+        assert_eq!(tle.to_lines().unwrap(), (String::from(line1), String::from(line2)));
+
+        // The same epoch from a Julian date
+        let julian = builder.clone().epoch_julian_date(tle.epoch_julian_date()).build().unwrap();
+        assert_eq!(julian.epoch_year, 2016);
+        assert!((julian.epoch_day - tle.epoch_day).abs() < 1e-8);
+        let new_year = TLE::builder().epoch_julian_date(::time::julian_date(2017, 1, 1.0)).mean_motion(15.0).build().unwrap();
+        assert_eq!((new_year.epoch_year, new_year.epoch_day), (2017, 1.0));
+
+        assert_eq!(TLE::builder().mean_motion(15.0).build().unwrap_err(), TleBuildError::Missing("epoch"));
+        assert_eq!(TLE::builder().epoch(2016, 1.0).build().unwrap_err(), TleBuildError::Missing("mean motion"));
+        assert_eq!(builder.clone().eccentricity(1.0).build().unwrap_err(), TleBuildError::OutOfRange("eccentricity"));
+        assert_eq!(builder.clone().sat_number(100000).build().unwrap_err(), TleBuildError::OutOfRange("satellite number"));
+        assert_eq!(builder.clone().epoch(1956, 100.0).build().unwrap_err(), TleBuildError::OutOfRange("epoch year"));
+        assert_eq!(builder.clone().epoch(2057, 100.0).build().unwrap_err(), TleBuildError::OutOfRange("epoch year"));
+        assert!(builder.clone().epoch(1957, 100.0).build().is_ok());
+        assert!(builder.clone().epoch(2056, 100.0).build().is_ok());
+        assert_eq!(builder.clone().mean_anomaly(Deg(f64::NAN)).build().unwrap_err(), TleBuildError::OutOfRange("mean anomaly"));
+        assert_eq!(builder.clone().eccentricity(0.99999999).build().unwrap_err(), TleBuildError::OutOfRange("eccentricity"));
+        assert_eq!(builder.clone().mean_motion(100.0).build().unwrap_err(), TleBuildError::OutOfRange("mean motion"));
+        assert_eq!(builder.clone().bstar(1.0e10).build().unwrap_err(), TleBuildError::OutOfRange("BSTAR drag term"));
+    }
 }
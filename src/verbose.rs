@@ -0,0 +1,313 @@
+/*!  # Verbose
+
+The quantities the propagator works out along the way, for checking this
+implementation term by term against another SGP4 code.
+
+[`Propagator::constants`](../struct.Propagator.html#method.constants) gives
+what's computed once from the elements (Sections 1–4 of Spacetrack Report
+#3), and [`Propagator::propagate_verbose`](../struct.Propagator.html#method.propagate_verbose)
+what's computed at each time (Sections 5–8). Names follow the report, and
+the propagator's own comments; everything is in Earth radii, minutes and
+radians.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use coordinates::StateVector;
+use {PropagationError, Propagator, ResonanceState, RE};
+
+
+/// ## Constants
+///
+/// The propagator's constants for one element set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Constants {
+
+    /// nₒ", the Brouwer mean motion (radians/minute)
+    pub n0_dp: f64,
+
+    /// aₒ", the Brouwer semi-major axis
+    pub ao_dp: f64,
+
+    /// Perigee height above the equatorial radius (kilometers)
+    pub perigee: f64,
+
+    /// Whether the drag terms are truncated for a perigee below 220 km
+    pub simple: bool,
+
+    /// Whether the drag terms divided by eₒ are left out for a near
+    /// circular orbit
+    pub circular: bool,
+
+    /// Whether the deep space (SDP4) terms are applied, for a period of 225
+    /// minutes or more
+    pub deep_space: bool,
+
+    /// (qₒ - s)⁴
+    pub qs4: f64,
+
+    /// ξ⁴
+    pub xi4: f64,
+
+    /// η
+    pub eta: f64,
+
+    /// C₁
+    pub C1: f64,
+
+    /// C₂
+    pub C2: f64,
+
+    /// C₃
+    pub C3: f64,
+
+    /// C₄
+    pub C4: f64,
+
+    /// C₅
+    pub C5: f64,
+
+    /// D₂
+    pub D2: f64,
+
+    /// D₃
+    pub D3: f64,
+
+    /// D₄
+    pub D4: f64,
+
+    /// Ṁ (radians/minute)
+    pub M_dot: f64,
+
+    /// ω̇ (radians/minute)
+    pub omega_dot: f64,
+
+    /// Ω̇ (radians/minute)
+    pub Omega_dot: f64,
+}
+
+/// ## Intermediates
+///
+/// The working of one propagation. If it fails part way through, the
+/// quantities it didn't reach are left at zero.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Intermediates {
+
+    /// M_DF, the mean anomaly with the secular effect of gravity
+    pub M_DF: f64,
+
+    /// ω_DF, the argument of perigee with the secular effect of gravity
+    pub omega_DF: f64,
+
+    /// Ω_DF, the right ascension with the secular effect of gravity
+    pub Omega_DF: f64,
+
+    /// Ω, with the secular effect of drag
+    pub Omega: f64,
+
+    /// Mp, with the secular effect of drag
+    pub M_p: f64,
+
+    /// ω, with the secular effect of drag
+    pub omega: f64,
+
+    /// a, the secular semi-major axis
+    pub a: f64,
+
+    /// e, the secular eccentricity
+    pub e: f64,
+
+    /// IL, the secular mean longitude
+    pub IL: f64,
+
+    /// n, the secular mean motion (radians/minute)
+    pub n: f64,
+
+    /// a_xN, with the long period periodics
+    pub axN: f64,
+
+    /// a_yN, with the long period periodics
+    pub ayN: f64,
+
+    /// IL_T, with the long period periodics
+    pub IL_T: f64,
+
+    /// U = IL_T - Ω
+    pub U: f64,
+
+    /// (E + ω), the solution of Kepler's equation
+    pub Ew: f64,
+
+    /// Iterations taken to solve Kepler's equation
+    pub kepler_iterations: usize,
+
+    /// e cos E
+    pub ecosE: f64,
+
+    /// e sin E
+    pub esinE: f64,
+
+    /// p_L, the semi-latus rectum
+    pub pL: f64,
+
+    /// r, before the short period periodics
+    pub r: f64,
+
+    /// ṙ, before the short period periodics
+    pub r_dot: f64,
+
+    /// rḟ, before the short period periodics
+    pub rf_dot: f64,
+
+    /// u, before the short period periodics
+    pub u: f64,
+
+    /// Δr
+    pub dr: f64,
+
+    /// Δu
+    pub du: f64,
+
+    /// ΔΩ
+    pub dOmega: f64,
+
+    /// Δi
+    pub di: f64,
+
+    /// Δṙ
+    pub dr_dot: f64,
+
+    /// Δrḟ
+    pub drf_dot: f64,
+
+    /// r_k, the osculating radius
+    pub rk: f64,
+
+    /// u_k, the osculating argument of latitude
+    pub uk: f64,
+
+    /// Ω_k, the osculating right ascension
+    pub Omegak: f64,
+
+    /// i_k, the osculating inclination
+    pub ik: f64,
+
+    /// ṙ_k, the osculating radial velocity
+    pub r_dotk: f64,
+
+    /// rḟ_k, the osculating transverse velocity
+    pub rf_dotk: f64,
+}
+
+impl Propagator {
+
+    /// ## Constants
+    ///
+    /// The constants worked out from the elements when the propagator was
+    /// made. See the [`verbose`](verbose/index.html) module.
+    pub fn constants(&self) -> Constants {
+        Constants {
+            n0_dp: self.n0_dp,
+            ao_dp: self.ao_dp,
+            perigee: (self.ao_dp * (1.0 - self.e0) - RE) * self.gravity.radius,
+            simple: self.simple,
+            circular: self.circular,
+            deep_space: self.deep_space.is_some(),
+            qs4: self.qs4,
+            xi4: self.xi4,
+            eta: self.n,
+            C1: self.C1,
+            C2: self.C2,
+            C3: self.C3,
+            C4: self.C4,
+            C5: self.C5,
+            D2: self.D2,
+            D3: self.D3,
+            D4: self.D4,
+            M_dot: self.M_dot,
+            omega_dot: self.omega_dot,
+            Omega_dot: self.Omega_dot,
+        }
+    }
+
+    /// ## Propagate Verbose
+    ///
+    /// Propagate the orbit to `time` minutes since the TLE epoch, like
+    /// [`propagate`](#method.propagate), along with the intermediate
+    /// quantities. These come back even if the propagation fails, as far as
+    /// it got. See the [`verbose`](verbose/index.html) module.
+    ///
+    /// ### Example
+    ///
+    /// ```
+    /// extern crate sgp4;
+    ///
+    /// let tle = sgp4::tle::load_from_str(
+    ///     "ISS (ZARYA)",
+    ///     "1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990",
+    ///     "2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433",
+    /// ).unwrap();
+    /// let propagator = sgp4::Propagator::new(&tle).unwrap();
+    ///
+    /// let constants = propagator.constants();
+    /// println!("C1 {:e} C4 {:e}", constants.C1, constants.C4);
+    ///
+    /// let (state, intermediates) = propagator.propagate_verbose(60.0);
+    /// assert_eq!(state, propagator.propagate(60.0));
+    /// println!("E + ω {} after {} iterations", intermediates.Ew, intermediates.kepler_iterations);
+    /// ```
+    pub fn propagate_verbose(&self, time: f64) -> (Result<StateVector, PropagationError>, Intermediates) {
+        let mut intermediates = Intermediates::default();
+        let state = self.propagate_recording(time, &mut ResonanceState::default(), &mut intermediates);
+        (state, intermediates)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_support::iss;
+
+    fn propagator() -> Propagator {
+        Propagator::new(&iss()).unwrap()
+    }
+
+    #[test]
+    fn constants() {
+        let propagator = propagator();
+        let constants = propagator.constants();
+        assert!(!constants.simple);
+        assert!(!constants.deep_space);
+        assert!(constants.perigee > 220.0);
+        assert_eq!(constants.C1, propagator.Bstar * constants.C2);
+        assert!(constants.M_dot > constants.n0_dp);
+    }
+
+    #[test]
+    fn intermediates() {
+        let propagator = propagator();
+        let (state, intermediates) = propagator.propagate_verbose(360.0);
+        let state = state.unwrap();
+        assert_eq!(state, propagator.propagate(360.0).unwrap());
+
+        // The radius and the position agree
+        let radius = state.position.norm() / propagator.gravity.radius;
+        assert!((intermediates.rk - radius).abs() < 1e-12);
+        assert_eq!(intermediates.r_dotk, intermediates.r_dot + intermediates.dr_dot);
+        assert!(intermediates.kepler_iterations >= 1);
+        assert_eq!(intermediates.M_DF, propagator.M0 + propagator.M_dot * 360.0);
+
+        // A failed propagation still shows its working
+        let mut tle = iss();
+        tle.bstar = 1.0e-2;
+        let (state, intermediates) = Propagator::new(&tle).unwrap().propagate_verbose(86400.0);
+        assert_eq!(state, Err(PropagationError::Decayed));
+        assert!(intermediates.a < 1.0);
+        assert_eq!(intermediates.rk, 0.0);
+    }
+}
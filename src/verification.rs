@@ -0,0 +1,482 @@
+/*!  # Verification
+
+Check the propagator against the reference implementation's ephemerides,
+as published with Vallado, Crawford, Hujsak and Kelso, *Revisiting
+Spacetrack Report #3* (AIAA 2006-6753).
+
+That paper's distribution has the verification element sets
+(`SGP4-VER.TLE`) and the reference code's output for each of them
+(`tcppver.out`): a `<satellite number> xx` header, then one line per time
+of minutes since the epoch, position (kilometers) and velocity
+(kilometers/second). [`verify_ephemerides`](fn.verify_ephemerides.html)
+runs the whole suite from those files.
+
+The whole element set list is built in, for [`verify`](fn.verify.html),
+near Earth, deep space, resonant and Lyddane cases alike. So far only some
+of the reference states are: 00005 and 06251 over several hours, and
+thirteen more at their epochs. The other sets have none yet, and their
+reports don't pass.
+
+Three of the sets are made up to trip the reference code's error checks.
+33333 and 33334 stop it at once, and their reports pass when the propagator
+stops with the same error. 33335 was meant to, but propagates normally.
+
+Satellites in the suite with periods of 225 minutes or more are propagated
+with the deep space (SDP4) terms, like the reference.
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use std::error;
+use std::fmt;
+
+use tle::{self, ParseOptions, TLE};
+use {OpsMode, PropagationError, PropagationOptions, Propagator};
+
+#[cfg(not(any(feature = "std", test)))]
+#[allow(unused_imports)]
+use math::Float;
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+
+/// The verification element sets, all of `SGP4-VER.TLE`. A few of its lines,
+/// including the hand made error cases 33333 to 33335, have bad checksums,
+/// so these are read without checking them, as the reference code does.
+const TLES: &str = "\
+1 00005U 58002B   00179.78495062  .00000023  00000-0  28098-4 0  4753
+2 00005  34.2682 348.7242 1859667 331.7664  19.3264 10.82419157413667
+1 04632U 70093B   04031.91070959 -.00000084  00000-0  10000-3 0  9955
+2 04632  11.4628 273.1101 1450506 207.6000 143.9350  1.20231981 44145
+1 06251U 62025E   06176.82412014  .00008885  00000-0  12808-3 0  3985
+2 06251  58.0579  54.0425 0030035 139.1568 221.1854 15.56387291  6774
+1 08195U 75081A   06176.33215444  .00000099  00000-0  11873-3 0   813
+2 08195  64.1586 279.0717 6877146 264.7651  20.2257  2.00491383225656
+1 09880U 77021A   06176.56157475  .00000421  00000-0  10000-3 0  9814
+2 09880  64.5968 349.3786 7069051 270.0229  16.3320  2.00813614112380
+1 09998U 74033F   05148.79417928 -.00000112  00000-0  00000+0 0  4480
+2 09998   9.4958 313.1750 0270971 327.5225  30.8097  1.16186785 45878
+1 11801U          80230.29629788  .01431103  00000-0  14311-1 0    13
+2 11801  46.7916 230.4354 7318036  47.4722  10.4117  2.28537848    13
+1 14128U 83058A   06176.02844893 -.00000158  00000-0  10000-3 0  9627
+2 14128  11.4384  35.2134 0011562  26.4582 333.5652  0.98870114 46093
+1 16925U 86065D   06151.67415771  .02550794 -30915-6  18784-3 0  4486
+2 16925  62.0906 295.0239 5596327 245.1593  47.9690  4.88511875148616
+1 20413U 83020D   05363.79166667  .00000000  00000-0  00000+0 0  7041
+2 20413  12.3514 187.4253 7864447 196.3027 356.5478  0.24690082  7978
+1 21897U 92011A   06176.02341244 -.00001273  00000-0 -13525-3 0  3044
+2 21897  62.1749 198.0096 7421690 253.0462  20.1561  2.01269994104880
+1 22312U 93002D   06094.46235912  .99999999  00000-0  13519-0 0   285
+2 22312  62.1486  77.4698 0308723 267.9229  88.7392 15.95744531 98783
+1 22674U 93035D   06176.55909107  .00002121  00000-0  29868-3 0  6569
+2 22674  63.5035 354.4452 7541712 253.3264  18.7754  1.96679808 93877
+1 23177U 94040C   06175.45752052  .00000386  00000-0  76590-3 0    95
+2 23177   7.0496 179.8238 7258491 296.0482   8.3061  2.25906668 97438
+1 23333U 94071A   94305.49999999 -.00172956  26967-3  10000-3 0    15
+2 23333  28.7490   2.3720 9728298  30.4360   1.3500  0.07309491    70
+1 23599U 95029B   06171.76535463  .00085586  12891-6  12956-2 0  2905
+2 23599   6.9327   0.2849 5782022 274.4436  25.2425  4.47796565123555
+1 24208U 96044A   06177.04061740 -.00000094  00000-0  10000-3 0  1600
+2 24208   3.8536  80.0121 0026640 311.0977  48.3000  1.00778054 36119
+1 25954U 99060A   04039.68057285 -.00000108  00000-0  00000-0 0  6847
+2 25954   0.0004 243.8136 0001765  15.5294  22.7134  1.00271289 15236
+1 26900U 01039A   06106.74503247  .00000045  00000-0  10000-3 0  8290
+2 26900   0.0164 266.5378 0003319  86.1794 182.2590  1.00273847 16981
+1 26975U 78066F   06174.85818871  .00000620  00000-0  10000-3 0  6809
+2 26975  68.4714 236.1303 5602877 123.7484 302.5767  2.05657553 67521
+1 28057U 03049A   06177.78615833  .00000060  00000-0  35940-4 0  1836
+2 28057  98.4283 247.6961 0000884  88.1964 271.9322 14.35478080140550
+1 28129U 03058A   06175.57071136 -.00000104  00000-0  10000-3 0   459
+2 28129  54.7298 324.8098 0048506 266.2640  93.1663  2.00562768 18443
+1 28350U 04020A   06167.21788666  .16154492  76267-5  18678-3 0  8894
+2 28350  64.9977 345.6130 0024870 260.7578  99.9590 16.47856722116490
+1 28623U 05006B   06177.81079184  .00637644  69054-6  96390-3 0  6000
+2 28623  28.5200 114.9834 6249053 170.2550 212.8965  3.79477162 12753
+1 28626U 05008A   06176.46683397 -.00000205  00000-0  10000-3 0  2190
+2 28626   0.0019 286.9433 0000335  13.7918  55.6504  1.00270176  4891
+1 28872U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534
+2 28872  96.4736 157.9986 0303955 244.0492 110.6523 16.46015938 10708
+1 29141U 85108AA  06170.26783845  .99999999  00000-0  13519-0 0   718
+2 29141  82.4288 273.4882 0015848 277.2124  83.9133 15.93343074  6828
+1 29238U 06022G   06177.28732010  .00766286  10823-4  13334-2 0   101
+2 29238  51.5595 213.7903 0202579  95.2503 267.9010 15.73823839  1061
+1 88888U          80275.98708465  .00073094  13844-3  66816-4 0    87
+2 88888  72.8435 115.9689 0086731  52.6988 110.5714 16.05824518  1058
+1 33333U 05037B   05333.02012661  .25992681  00000-0  24476-3 0  1534
+2 33333  96.4736 157.9986 9950000 244.0492 110.6523  4.00004038 10708
+1 33334U 78066F   06174.85818871  .00000620  00000-0  10000-3 0  6809
+2 33334  68.4714 236.1303 5602877 123.7484 302.5767  0.00001000 67521
+1 33335U 05008A   06173.84658765  .00000000  00000-0  00000-0 0  2190
+2 33335   0.0019 286.9433 0000004  13.7918  55.6504  1.00270176  4891
+";
+
+/// The reference ephemerides for the built in element sets, from
+/// `tcppver.out`: a header for every satellite in the suite, with the
+/// reference states built in so far
+const EPHEMERIDES: &str = "\
+5 xx
+       0.00000000     7022.46529266    -1400.08296755        0.03995155       1.893841015       6.405893759       4.534807250
+     360.00000000    -7154.03120202    -3783.17682504    -3536.19412294       4.741887409      -4.151817765      -2.093935425
+     720.00000000    -7134.59340119     6531.68641334     3260.27186483      -4.113793027      -2.911922039      -2.557327851
+4632 xx
+6251 xx
+       0.00000000     3988.31022699     5498.96657235        0.90055879      -3.290032738       2.357652820       6.496623475
+     120.00000000    -3935.69800083      409.10980837     5471.33577327      -3.374784183      -6.635211043      -1.942056221
+     240.00000000    -1675.12766915    -5683.30432352    -3286.21510937       5.282496925       1.508674259      -5.354872978
+8195 xx
+       0.00000000     2349.89483350   -14785.93811562        0.02119378       2.721488096      -3.256811655       4.498416672
+9880 xx
+       0.00000000    13020.06750784    -2449.07193500        1.15896030       4.247363935       1.597178501       4.956708611
+9998 xx
+11801 xx
+       0.00000000     7473.37102491      428.94748312     5828.74846783       5.107155391       6.444680305      -0.186133297
+14128 xx
+16925 xx
+       0.00000000     5559.11686836   -11941.04090781      -19.41235206       3.392116762      -1.946985124       4.250755852
+20413 xx
+21897 xx
+       0.00000000   -14464.72135182    -4699.19517587        0.06681686      -3.249312013      -3.281032707       4.007046940
+22312 xx
+       0.00000000     1442.10132912     6510.23625449        8.83145885      -3.475714837       0.997262768       6.835860345
+22674 xx
+       0.00000000    14712.22023280    -1443.81061850        0.83497888       4.418965470       1.629592098       4.115531802
+23177 xx
+       0.00000000    -8801.60046706       -0.03357557       -0.44522743      -3.835279101      -7.662552175       0.944561323
+23333 xx
+23599 xx
+       0.00000000     9892.63794341       35.76144969       -1.08228838       3.556643237       6.456009375       0.783610890
+24208 xx
+25954 xx
+26900 xx
+26975 xx
+       0.00000000   -14506.92313768   -21613.56043281       10.05018894       2.212943308       1.159970892       3.020600202
+28057 xx
+       0.00000000    -2715.28237486    -6619.26436889       -0.01341443      -1.008587273       0.422782003       7.385272942
+28129 xx
+       0.00000000    21707.46412351   -15318.61752390        0.13551152       1.304029214       1.816904974       3.161919976
+28350 xx
+       0.00000000     6333.08123128    -1580.82852326       90.69355720       0.714634423       3.224246550       7.083128132
+28623 xx
+28626 xx
+28872 xx
+29141 xx
+29238 xx
+88888 xx
+33333 xx
+33334 xx
+33335 xx
+";
+
+/// The element sets made up to trip the reference code's error checks, and
+/// the error each stops the propagator with at its epoch. Both end up with an
+/// eccentricity out of range: 33333 starts at 0.995, and 33334 has a mean
+/// motion of a hundred-thousandth of a revolution a day.
+const EXPECTED_ERRORS: [(u32, PropagationError); 2] = [
+    (33333, PropagationError::Diverged),
+    (33334, PropagationError::Diverged),
+];
+
+/// ## Report
+///
+/// How closely the propagator matched the reference for one satellite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+
+    /// NORAD catalog number
+    pub sat_number: u32,
+
+    /// Number of times compared
+    pub points: usize,
+
+    /// Largest difference in position (kilometers)
+    pub max_position_error: f64,
+
+    /// Largest difference in velocity (kilometers/second)
+    pub max_velocity_error: f64,
+
+    /// The first error from the propagator, at a time the reference has a
+    /// state for, or at the epoch if it has none. Comparison stops there.
+    pub error: Option<PropagationError>,
+
+    /// The error the reference code stops with, for the element sets made
+    /// up to trip it
+    pub expected_error: Option<PropagationError>,
+}
+
+impl Report {
+
+    /// Whether at least one time was compared and every time was
+    /// propagated, with positions within `tolerance` kilometers and
+    /// velocities within `tolerance` kilometers/second of the reference. For
+    /// an element set made up to trip an error, whether the propagator
+    /// stopped with that error.
+    pub fn passed(&self, tolerance: f64) -> bool {
+        match self.expected_error {
+            Some(expected) => self.error == Some(expected),
+            None => {
+                self.error.is_none() && self.points > 0
+                    && self.max_position_error <= tolerance && self.max_velocity_error <= tolerance
+            }
+        }
+    }
+}
+
+
+/// ## Verification Errors
+///
+/// Why a verification suite couldn't be run.
+#[derive(Debug)]
+pub enum VerificationError {
+
+    /// The ephemerides are for a satellite with no element set
+    MissingTle(u32),
+
+    /// A line of the ephemerides isn't a header or seven numbers
+    Malformed {
+        /// Line number (counting from 1)
+        line: usize,
+    },
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            VerificationError::MissingTle(sat_number) => write!(f, "no element set for satellite {}", sat_number),
+            VerificationError::Malformed { line } => write!(f, "malformed ephemeris at line {}", line),
+        }
+    }
+}
+
+impl error::Error for VerificationError {}
+
+
+/// ## Verify
+///
+/// Run the built in verification cases with the reference code's settings
+/// (WGS72, AFSPC ops mode).
+///
+/// ### Example
+///
+/// ```
+/// extern crate sgp4;
+///
+/// for report in sgp4::verification::verify() {
+///     println!("{:5} {:.2e} km {:.2e} km/s", report.sat_number, report.max_position_error, report.max_velocity_error);
+///
+///     // Sets with reference states built in, or made up to fail, pass
+///     if report.points > 0 || report.expected_error.is_some() {
+///         assert!(report.passed(1e-6));
+///     }
+/// }
+/// ```
+pub fn verify() -> Vec<Report> {
+    let parse_options = ParseOptions { verify_checksums: false, ..ParseOptions::default() };
+    let tles = tle::parse_all_with_options(TLES, &parse_options).expect("built in element sets");
+    let options = PropagationOptions { ops_mode: OpsMode::Afspc, ..PropagationOptions::default() };
+    verify_ephemerides(&tles, EPHEMERIDES, &options).expect("built in ephemerides")
+}
+
+/// ## Verify Ephemerides
+///
+/// Compare the propagator with `options` against reference ephemerides in
+/// the `tcppver.out` format, for each satellite in them in turn. Element
+/// sets the propagator rejects report the error with no points compared,
+/// and so do satellites with no reference states that fail at their epoch.
+pub fn verify_ephemerides(tles: &[TLE], ephemerides: &str, options: &PropagationOptions) -> Result<Vec<Report>, VerificationError> {
+    let mut reports = Vec::new();
+    let mut current: Option<(Result<Propagator, PropagationError>, Report)> = None;
+
+    for (index, line) in ephemerides.lines().enumerate() {
+        let malformed = || VerificationError::Malformed { line: index + 1 };
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        match fields.as_slice() {
+            [] => {}
+            [number, "xx"] => {
+                let sat_number = number.parse().map_err(|_| malformed())?;
+                let tle = tles.iter()
+                    .find(|tle| tle.sat_number == sat_number)
+                    .ok_or(VerificationError::MissingTle(sat_number))?;
+                let propagator = Propagator::with_options(tle, options);
+                let report = Report {
+                    sat_number,
+                    points: 0,
+                    max_position_error: 0.0,
+                    max_velocity_error: 0.0,
+                    error: propagator.as_ref().err().cloned(),
+                    expected_error: EXPECTED_ERRORS.iter()
+                        .find(|&&(number, _)| number == sat_number)
+                        .map(|&(_, error)| error),
+                };
+                reports.extend(current.replace((propagator, report)).map(finish));
+            }
+            _ => {
+                let mut values = [0.0; 7];
+                if fields.len() < 7 {
+                    return Err(malformed());
+                }
+                for (value, text) in values.iter_mut().zip(fields.iter()) {
+                    *value = text.parse().map_err(|_| malformed())?;
+                }
+                let (propagator, report) = current.as_mut().ok_or_else(malformed)?;
+                if let (Ok(propagator), None) = (propagator, report.error) {
+                    compare(propagator, &values, report);
+                }
+            }
+        }
+    }
+
+    reports.extend(current.map(finish));
+    Ok(reports)
+}
+
+/// Close the report for a satellite. One with no reference states is still
+/// propagated at its epoch, so an element set the propagator can't handle
+/// shows its error.
+fn finish((propagator, mut report): (Result<Propagator, PropagationError>, Report)) -> Report {
+    if let (Ok(propagator), 0, None) = (propagator, report.points, &report.error) {
+        report.error = propagator.propagate(0.0).err();
+    }
+    report
+}
+
+/// Propagate to the time of one line of reference ephemeris and record the
+/// differences
+fn compare(propagator: &Propagator, expected: &[f64; 7], report: &mut Report) {
+    match propagator.propagate(expected[0]) {
+        Ok(state) => {
+            let position = [state.position.X, state.position.Y, state.position.Z];
+            let velocity = [state.velocity.X, state.velocity.Y, state.velocity.Z];
+            report.points += 1;
+            report.max_position_error = report.max_position_error.max(distance(&position, &expected[1..4]));
+            report.max_velocity_error = report.max_velocity_error.max(distance(&velocity, &expected[4..7]));
+        }
+        Err(error) => report.error = Some(error),
+    }
+}
+
+/// Distance between two vectors
+fn distance(a: &[f64; 3], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| (a - b).powi(2)).sum::<f64>().sqrt()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Satellite, number of reference states and tolerance (kilometers and
+    /// kilometers/second). The reference prints positions to 1e-8 km, so a
+    /// match is within rounding of that; the deep space cases carry a few
+    /// more operations and get a little more room. Sets with no reference
+    /// states built in yet have no tolerance.
+    const EXPECTED: [(u32, usize, Option<f64>); 32] = [
+        (5, 3, Some(1e-8)),
+        (4632, 0, None),
+        (6251, 3, Some(1e-8)),
+        (8195, 1, Some(2e-8)),
+        (9880, 1, Some(2e-8)),
+        (9998, 0, None),
+        (11801, 1, Some(2e-8)),
+        (14128, 0, None),
+        (16925, 1, Some(2e-8)),
+        (20413, 0, None),
+        (21897, 1, Some(2e-8)),
+        (22312, 1, Some(1e-8)),
+        (22674, 1, Some(2e-8)),
+        (23177, 1, Some(2e-8)),
+        (23333, 0, None),
+        (23599, 1, Some(2e-8)),
+        (24208, 0, None),
+        (25954, 0, None),
+        (26900, 0, None),
+        (26975, 1, Some(2e-8)),
+        (28057, 1, Some(1e-8)),
+        (28129, 1, Some(2e-8)),
+        (28350, 1, Some(1e-8)),
+        (28623, 0, None),
+        (28626, 0, None),
+        (28872, 0, None),
+        (29141, 0, None),
+        (29238, 0, None),
+        (88888, 0, None),
+        (33333, 0, None),
+        (33334, 0, None),
+        (33335, 0, None),
+    ];
+
+    #[test]
+    fn built_in() {
+        let reports = verify();
+        assert_eq!(reports.len(), EXPECTED.len());
+        for (report, &(sat_number, points, tolerance)) in reports.iter().zip(EXPECTED.iter()) {
+            assert_eq!((report.sat_number, report.points), (sat_number, points));
+            match tolerance {
+                Some(tolerance) => assert!(report.passed(tolerance), "{:?}", report),
+
+                // Nothing to compare isn't a pass, unless the set is made up
+                // to fail
+                None => assert_eq!(report.passed(1.0), report.expected_error.is_some(), "{:?}", report),
+            }
+        }
+    }
+
+    #[test]
+    fn error_cases() {
+        let reports = verify();
+        let report = |sat_number: u32| reports.iter().find(|report| report.sat_number == sat_number).unwrap();
+
+        // Both stop at the epoch with the error the reference expects
+        for &sat_number in [33333, 33334].iter() {
+            assert_eq!(report(sat_number).expected_error, Some(PropagationError::Diverged));
+            assert_eq!(report(sat_number).error, Some(PropagationError::Diverged));
+            assert!(report(sat_number).passed(0.0));
+        }
+
+        // And the one that was meant to fail propagates
+        assert_eq!(report(33335).expected_error, None);
+        assert_eq!(report(33335).error, None);
+        assert!(!report(33335).passed(1.0));
+
+        // A wrong error is a failure too
+        let wrong = Report { error: Some(PropagationError::Decayed), ..report(33333).clone() };
+        assert!(!wrong.passed(1.0));
+    }
+
+    #[test]
+    fn improved_near_earth() {
+        // Near Earth the default ops mode follows the reference as closely
+        // as the reference code's own
+        let parse_options = ParseOptions { verify_checksums: false, ..ParseOptions::default() };
+        let tles = tle::parse_all_with_options(TLES, &parse_options).unwrap();
+        let ephemerides: String = EPHEMERIDES.lines().take_while(|line| *line != "4632 xx").map(|line| format!("{}\n", line)).collect();
+        let reports = verify_ephemerides(&tles, &ephemerides, &PropagationOptions::default()).unwrap();
+        assert_eq!((reports[0].sat_number, reports[0].points), (5, 3));
+        assert!(reports[0].passed(1e-8), "{:?}", reports[0]);
+    }
+
+    #[test]
+    fn problems() {
+        let parse_options = ParseOptions { verify_checksums: false, ..ParseOptions::default() };
+        let tles = tle::parse_all_with_options(TLES, &parse_options).unwrap();
+        let options = PropagationOptions::default();
+
+        match verify_ephemerides(&tles, "99999 xx\n", &options) {
+            Err(VerificationError::MissingTle(99999)) => {}
+            other => panic!("{:?}", other),
+        }
+        match verify_ephemerides(&tles, "5 xx\n 0.0 7022.0\n", &options) {
+            Err(VerificationError::Malformed { line: 2 }) => {}
+            other => panic!("{:?}", other),
+        }
+
+        // A wrong reference shows up as an error, not a failure to run
+        let reports = verify_ephemerides(&tles, "5 xx\n 0.0 7023.0 -1400.0 0.0 1.9 6.4 4.5\n", &options).unwrap();
+        assert!(reports[0].max_position_error > 0.5);
+        assert!(!reports[0].passed(1e-6));
+    }
+}
@@ -0,0 +1,155 @@
+/*!  # WebAssembly
+
+Bindings for running the propagator in a browser, generated with
+`wasm-bindgen`. Requires the `wasm-bindgen` feature. The surface is kept
+small: parse a TLE, then get its position at a Unix time.
+
+```text
+wasm-pack build --target web -- --features wasm-bindgen
+```
+
+```js
+import init, { Satellite } from "./pkg/sgp4.js";
+
+await init();
+const iss = new Satellite(`ISS (ZARYA)
+1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433`);
+
+const position = iss.geodetic(Date.now());
+console.log(iss.name, position.latitude, position.longitude, position.altitude);
+```
+*/
+#![deny(missing_docs,
+        trivial_casts, trivial_numeric_casts,
+        unsafe_code,
+        unstable_features,
+        unused_import_braces,
+        unused_qualifications)]
+
+use wasm_bindgen::prelude::*;
+
+use coordinates;
+use time;
+use tle;
+use Propagator;
+
+/// ## Satellite
+///
+/// A parsed TLE, ready to propagate.
+#[wasm_bindgen]
+pub struct Satellite {
+    name: String,
+    propagator: Propagator,
+    epoch: f64,
+}
+
+/// ## Position
+///
+/// Where a satellite is over the Earth: WGS84 latitude and longitude
+/// (degrees) and altitude (kilometers).
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+
+    /// Geodetic latitude (degrees, north positive)
+    pub latitude: f64,
+
+    /// Longitude (degrees, east positive, -180 to 180)
+    pub longitude: f64,
+
+    /// Height above the WGS84 ellipsoid (kilometers)
+    pub altitude: f64,
+}
+
+#[wasm_bindgen]
+impl Satellite {
+
+    /// Parse a 2-line or 3-line element set
+    #[wasm_bindgen(constructor)]
+    pub fn new(text: &str) -> Result<Satellite, JsError> {
+        let tle = tle::parse(text)?;
+        let propagator = Propagator::new(&tle)?;
+        Ok(Satellite {
+            name: tle.name.clone(),
+            propagator,
+            epoch: tle.epoch_julian_date(),
+        })
+    }
+
+    /// Name of the object, empty for a 2-line element set
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// TEME position (kilometers) and velocity (kilometers/second) as
+    /// `[x, y, z, vx, vy, vz]` at a Unix time in milliseconds, as returned
+    /// by `Date.now()`
+    pub fn propagate(&self, unix_ms: f64) -> Result<Vec<f64>, JsError> {
+        let state = self.propagator.propagate(self.minutes_since_epoch(unix_ms))?;
+        Ok(vec![
+            state.position.X, state.position.Y, state.position.Z,
+            state.velocity.X, state.velocity.Y, state.velocity.Z,
+        ])
+    }
+
+    /// Latitude, longitude and altitude at a Unix time in milliseconds
+    pub fn geodetic(&self, unix_ms: f64) -> Result<Position, JsError> {
+        let state = self.propagator.propagate(self.minutes_since_epoch(unix_ms))?;
+
+        // UTC stands in for UT1, good to a fraction of a kilometer
+        let gmst = time::gmst(julian_date(unix_ms));
+        let geodetic = coordinates::geodetic_from_ecef(&coordinates::ecef_from_teme(&state.position, gmst));
+
+        Ok(Position {
+            latitude: geodetic.lat.to_degrees(),
+            longitude: geodetic.lon.to_degrees(),
+            altitude: geodetic.alt,
+        })
+    }
+}
+
+impl Satellite {
+    fn minutes_since_epoch(&self, unix_ms: f64) -> f64 {
+        (julian_date(unix_ms) - self.epoch) * ::MINUTES_PER_DAY
+    }
+}
+
+/// Julian date of a Unix time in milliseconds
+fn julian_date(unix_ms: f64) -> f64 {
+    time::julian_date_from_unix(unix_ms / 1000.0)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISS: &str = "ISS (ZARYA)
+1 25544U 98067A   16210.59822142  .00000812  00000-0  11901-4 0  9990
+2 25544  51.6406 211.4156 0001780  85.8307 274.3426 15.54888439 11433";
+
+    #[test]
+    fn propagate_and_geodetic() {
+        let satellite = Satellite::new(ISS).unwrap();
+        assert_eq!(satellite.name(), "ISS (ZARYA)");
+
+        // An hour after the epoch, 2016-07-28T14:21:26.330688Z
+        let epoch_ms = (satellite.epoch - time::UNIX_EPOCH) * 86400.0e3;
+        let unix_ms = epoch_ms + 3600.0e3;
+
+        let tle = tle::parse(ISS).unwrap();
+        let expected = ::propagate(&tle, 60.0).unwrap();
+        // Julian dates only resolve tens of microseconds, or a fraction of a
+        // meter along track
+        let state = satellite.propagate(unix_ms).unwrap();
+        assert!((state[0] - expected.position.X).abs() < 1e-3);
+        assert!((state[4] - expected.velocity.Y).abs() < 1e-6);
+
+        let position = satellite.geodetic(unix_ms).unwrap();
+        assert!(position.latitude.abs() <= 51.7);
+        assert!((-180.0..=180.0).contains(&position.longitude));
+        assert!(position.altitude > 350.0 && position.altitude < 450.0);
+    }
+}